@@ -1,6 +1,7 @@
 use std::string::ToString;
 
 pub mod types;
+pub mod weighted;
 
 pub const NULL_TERRAIN: &'static str = "t_null";
 pub const NULL_FURNITURE: &'static str = "f_null";
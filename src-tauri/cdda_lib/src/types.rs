@@ -1,9 +1,10 @@
 use derive_more::Display;
 use num_traits::int::PrimInt;
 use rand::distr::uniform::SampleUniform;
-use rand::{rng, Rng};
+use rand::Rng;
 use serde::de;
 use serde::de::{Deserialize, Deserializer, Error, Visitor};
+use serde::ser::{Serialize, SerializeStruct, SerializeTuple, Serializer};
 use serde_derive::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::HashMap;
@@ -124,15 +125,14 @@ pub enum MeabyVec<T> {
     Vec(Vec<T>),
 }
 
-impl<T: Clone> MeabyVec<T> {
+impl<T> MeabyVec<T> {
+    /// Applies `fun` to every element in place without changing whether this
+    /// is a single value or a list
     pub fn apply<F>(&mut self, fun: F)
     where
-        F: Fn(&mut T),
+        F: FnMut(&mut T),
     {
-        match self {
-            MeabyVec::Single(s) => fun(s),
-            MeabyVec::Vec(v) => v.iter_mut().for_each(|v| fun(v)),
-        };
+        self.iter_mut().for_each(fun);
     }
 
     pub fn map<F, R>(self, fun: F) -> Vec<R>
@@ -145,16 +145,47 @@ impl<T: Clone> MeabyVec<T> {
         }
     }
 
-    pub fn for_each<F>(&self, mut fun: F)
+    pub fn for_each<F>(&self, fun: F)
     where
         F: FnMut(&T),
     {
+        self.iter().for_each(fun)
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            MeabyVec::Single(s) => std::slice::from_ref(s),
+            MeabyVec::Vec(v) => v.as_slice(),
+        }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
         match self {
-            MeabyVec::Single(s) => fun(s),
-            MeabyVec::Vec(v) => v.iter().for_each(fun),
+            MeabyVec::Single(s) => std::slice::from_mut(s),
+            MeabyVec::Vec(v) => v.as_mut_slice(),
         }
     }
 
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.as_mut_slice().iter_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.as_slice().first()
+    }
+
     pub fn into_vec(self) -> Vec<T> {
         match self {
             MeabyVec::Single(s) => vec![s],
@@ -165,15 +196,54 @@ impl<T: Clone> MeabyVec<T> {
     pub fn into_single(self) -> Option<T> {
         match self {
             MeabyVec::Single(s) => Some(s),
-            MeabyVec::Vec(v) => v.first().map(|v| v.clone()),
+            MeabyVec::Vec(v) => v.into_iter().next(),
         }
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Serialize)]
+impl<T> IntoIterator for MeabyVec<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a MeabyVec<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut MeabyVec<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// The JSON shape a [`Weighted`] was deserialized from. It is used to write
+/// the value back in the same shape
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum WeightedFormat {
+    /// `[data, weight]`
+    #[default]
+    Array,
+    /// `{ "sprite": data, "weight": weight }`
+    Object,
+}
+
+#[derive(Debug, Clone)]
 pub struct Weighted<T> {
     pub data: T,
     pub weight: i32,
+    pub format: WeightedFormat,
 }
 
 impl<T> Weighted<T> {
@@ -181,6 +251,49 @@ impl<T> Weighted<T> {
         Self {
             data: data.into(),
             weight,
+            format: WeightedFormat::default(),
+        }
+    }
+
+    pub fn map<F, R>(self, fun: F) -> Weighted<R>
+    where
+        F: FnOnce(T) -> R,
+    {
+        Weighted {
+            data: fun(self.data),
+            weight: self.weight,
+            format: self.format,
+        }
+    }
+}
+
+// The format is only used for serialization and does not change the value
+impl<T: PartialEq> PartialEq for Weighted<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data && self.weight == other.weight
+    }
+}
+
+impl<T: Eq> Eq for Weighted<T> {}
+
+impl<T: Serialize> Serialize for Weighted<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.format {
+            WeightedFormat::Array => {
+                let mut seq = serializer.serialize_tuple(2)?;
+                seq.serialize_element(&self.data)?;
+                seq.serialize_element(&self.weight)?;
+                seq.end()
+            },
+            WeightedFormat::Object => {
+                let mut map = serializer.serialize_struct("Weighted", 2)?;
+                map.serialize_field("sprite", &self.data)?;
+                map.serialize_field("weight", &self.weight)?;
+                map.end()
+            },
         }
     }
 }
@@ -222,7 +335,11 @@ where
                     .next_element()?
                     .ok_or_else(|| de::Error::invalid_length(1, &self))?;
 
-                Ok(Weighted { data, weight })
+                Ok(Weighted {
+                    data,
+                    weight,
+                    format: WeightedFormat::Array,
+                })
             }
 
             // Handle the map format: { "weight": ..., "sprite": ... }
@@ -255,7 +372,11 @@ where
                 let weight = weight
                     .ok_or_else(|| serde::de::Error::missing_field("weight"))?;
 
-                Ok(Weighted { data, weight })
+                Ok(Weighted {
+                    data,
+                    weight,
+                    format: WeightedFormat::Object,
+                })
             }
         }
 
@@ -288,11 +409,36 @@ impl<T> MeabyWeighted<T> {
 
     pub fn to_weighted(self) -> Weighted<T> {
         match self {
-            MeabyWeighted::NotWeighted(d) => Weighted { data: d, weight: 1 },
+            MeabyWeighted::NotWeighted(d) => Weighted::new(d, 1),
             MeabyWeighted::Weighted(w) => w,
         }
     }
 
+    pub fn as_data(&self) -> &T {
+        match self {
+            MeabyWeighted::NotWeighted(nw) => nw,
+            MeabyWeighted::Weighted(w) => &w.data,
+        }
+    }
+
+    pub fn as_data_mut(&mut self) -> &mut T {
+        match self {
+            MeabyWeighted::NotWeighted(nw) => nw,
+            MeabyWeighted::Weighted(w) => &mut w.data,
+        }
+    }
+
+    /// Maps the data while keeping the weight and the JSON shape
+    pub fn map<F, R>(self, fun: F) -> MeabyWeighted<R>
+    where
+        F: FnOnce(T) -> R,
+    {
+        match self {
+            MeabyWeighted::NotWeighted(nw) => MeabyWeighted::NotWeighted(fun(nw)),
+            MeabyWeighted::Weighted(w) => MeabyWeighted::Weighted(w.map(fun)),
+        }
+    }
+
     pub fn weight_or_one(&self) -> i32 {
         match self {
             MeabyWeighted::Weighted(w) => w.weight,
@@ -394,43 +540,83 @@ impl<'de, T: PrimInt + Clone + SampleUniform + Deserialize<'de>>
     }
 }
 
+/// Returned when a [`NumberOrRange`] has a lower bound that is greater than its
+/// upper bound
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvertedRangeError<T> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: Debug> fmt::Display for InvertedRangeError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid range [{:?}, {:?}], the lower bound is greater than the upper bound",
+            self.from, self.to
+        )
+    }
+}
+
+impl<T: Debug> std::error::Error for InvertedRangeError<T> {}
+
 impl<T: PrimInt + Clone + SampleUniform> NumberOrRange<T> {
-    pub fn rand_number(&self) -> T {
-        match self.clone() {
-            NumberOrRange::Number(n) => n,
-            NumberOrRange::Range((from, to)) => {
-                let mut rng = rng();
-                //let mut rng = RANDOM.write().unwrap();
-                let num = rng.random_range(from..to);
-                num
-            },
+    /// Lower bound of the range. Inverted ranges are treated as if their
+    /// bounds were swapped
+    pub fn min(&self) -> T {
+        match self {
+            NumberOrRange::Number(n) => *n,
+            NumberOrRange::Range((from, to)) => *from.min(to),
         }
     }
 
-    pub fn is_random_hit(&self, default_upper_bound: T) -> bool {
-        match self.clone() {
-            NumberOrRange::Number(n) => {
-                // This will always be true
-                if n >= default_upper_bound {
-                    return true;
-                }
-
-                let mut rng = rng();
-                //let mut rng = RANDOM.write().unwrap();
-                let num = rng.random_range(n..default_upper_bound);
+    /// Upper bound of the range. Inverted ranges are treated as if their
+    /// bounds were swapped
+    pub fn max(&self) -> T {
+        match self {
+            NumberOrRange::Number(n) => *n,
+            NumberOrRange::Range((from, to)) => *from.max(to),
+        }
+    }
 
-                num == n
+    pub fn validate(&self) -> Result<(), InvertedRangeError<T>> {
+        match self {
+            NumberOrRange::Range((from, to)) if from > to => {
+                Err(InvertedRangeError {
+                    from: *from,
+                    to: *to,
+                })
             },
-            NumberOrRange::Range((from, to)) => {
-                let mut rng = rng();
-                //let mut rng = RANDOM.write().unwrap();
-                let num = rng.random_range(from..to);
+            _ => Ok(()),
+        }
+    }
 
-                num == from
-            },
+    /// Picks a number between [`Self::min`] and [`Self::max`] (inclusive)
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> T {
+        match self {
+            NumberOrRange::Number(n) => *n,
+            NumberOrRange::Range(_) => rng.random_range(self.min()..=self.max()),
         }
     }
 
+    pub fn is_random_hit<R: Rng + ?Sized>(
+        &self,
+        default_upper_bound: T,
+        rng: &mut R,
+    ) -> bool {
+        let (from, to) = match self {
+            NumberOrRange::Number(n) => (*n, default_upper_bound),
+            NumberOrRange::Range(_) => (self.min(), self.max()),
+        };
+
+        // This will always be true
+        if from >= to {
+            return true;
+        }
+
+        rng.random_range(from..to) == from
+    }
+
     pub fn get_from_to(&self) -> (T, T) {
         match self.clone() {
             NumberOrRange::Number(n) => (n, n),
@@ -490,6 +676,7 @@ pub trait ImportCDDAObject: Clone + Debug {
     fn merge(base: &Self, override_: &Self) -> Self;
 
     fn copy_from(&self) -> Option<&CDDAIdentifier>;
+    fn set_copy_from(&mut self, copy_from: Option<CDDAIdentifier>);
 
     fn extend(&self) -> Option<&CDDAExtendOp>;
     fn delete(&self) -> Option<&CDDADeleteOp>;
@@ -562,3 +749,116 @@ pub trait ImportCDDAObject: Clone + Debug {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use serde_json::json;
+
+    const SAMPLES: usize = 1_000;
+
+    #[test]
+    fn test_number_or_range_samples_inclusive_bounds() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let range = NumberOrRange::Range((2, 4));
+
+        let samples: Vec<i32> =
+            (0..SAMPLES).map(|_| range.sample(&mut rng)).collect();
+
+        assert!(samples.iter().all(|s| (2..=4).contains(s)));
+        assert!(samples.contains(&2));
+        assert!(samples.contains(&4));
+
+        assert_eq!(NumberOrRange::Number(7).sample(&mut rng), 7);
+        assert_eq!(NumberOrRange::Range((5, 5)).sample(&mut rng), 5);
+    }
+
+    #[test]
+    fn test_number_or_range_inverted_range() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let range = NumberOrRange::Range((4, 2));
+
+        assert_eq!(
+            range.validate(),
+            Err(InvertedRangeError { from: 4, to: 2 })
+        );
+        assert_eq!(range.min(), 2);
+        assert_eq!(range.max(), 4);
+
+        // Inverted ranges are sampled as if their bounds were swapped
+        for _ in 0..SAMPLES {
+            assert!((2..=4).contains(&range.sample(&mut rng)));
+        }
+
+        assert_eq!(NumberOrRange::Range((2, 4)).validate(), Ok(()));
+        assert_eq!(NumberOrRange::Number(3).validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_weighted_keeps_array_format() {
+        let value = json!(["t_floor", 3]);
+        let weighted: Weighted<String> =
+            serde_json::from_value(value.clone()).unwrap();
+
+        assert_eq!(weighted.data, "t_floor");
+        assert_eq!(weighted.weight, 3);
+        assert!(matches!(weighted.format, WeightedFormat::Array));
+        assert_eq!(serde_json::to_value(&weighted).unwrap(), value);
+    }
+
+    #[test]
+    fn test_weighted_keeps_object_format() {
+        let value = json!({ "sprite": 5, "weight": 2 });
+        let weighted: Weighted<i32> =
+            serde_json::from_value(value.clone()).unwrap();
+
+        assert_eq!(weighted.data, 5);
+        assert_eq!(weighted.weight, 2);
+        assert!(matches!(weighted.format, WeightedFormat::Object));
+        assert_eq!(serde_json::to_value(&weighted).unwrap(), value);
+    }
+
+    #[test]
+    fn test_weighted_rejects_invalid_json() {
+        let invalid = [
+            json!(["t_floor"]),
+            json!({ "sprite": "t_floor" }),
+            json!({ "weight": 2 }),
+            json!({ "sprite": "t_floor", "weight": 2, "chance": 1 }),
+            json!("t_floor"),
+        ];
+
+        for value in invalid {
+            assert!(
+                serde_json::from_value::<Weighted<String>>(value.clone())
+                    .is_err(),
+                "{} should not deserialize",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_meaby_weighted_round_trip() {
+        let value = json!([
+            "t_floor",
+            ["t_dirt", 2],
+            { "sprite": "t_grass", "weight": 3 }
+        ]);
+        let values: MeabyVec<MeabyWeighted<String>> =
+            serde_json::from_value(value.clone()).unwrap();
+
+        let weights: Vec<i32> =
+            values.iter().map(MeabyWeighted::weight_or_one).collect();
+        assert_eq!(weights, vec![1, 2, 3]);
+        assert_eq!(serde_json::to_value(&values).unwrap(), value);
+
+        let single = json!("t_floor");
+        let values: MeabyVec<MeabyWeighted<String>> =
+            serde_json::from_value(single.clone()).unwrap();
+        assert!(matches!(values, MeabyVec::Single(_)));
+        assert_eq!(serde_json::to_value(&values).unwrap(), single);
+    }
+}
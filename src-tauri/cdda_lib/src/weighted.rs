@@ -0,0 +1,146 @@
+use crate::types::Weighted;
+use rand::Rng;
+
+/// Picks the index of one of the `weights` with a probability proportional to
+/// its weight.
+///
+/// Zero and negative weights are never chosen. The weights are summed up as
+/// `u64` so large weights cannot overflow. Returns `None` if there is no
+/// positive weight.
+pub fn choose_weighted_index<I, R>(weights: I, rng: &mut R) -> Option<usize>
+where
+    I: IntoIterator<Item = i32>,
+    R: Rng + ?Sized,
+{
+    let mut total: u64 = 0;
+    let cumulative = weights
+        .into_iter()
+        .map(|w| {
+            total += w.max(0) as u64;
+            total
+        })
+        .collect::<Vec<u64>>();
+
+    if total == 0 {
+        return None;
+    }
+
+    let chosen = rng.random_range(0..total);
+
+    // The first entry whose cumulative weight exceeds the chosen number. Entries
+    // with a weight of zero share the cumulative weight of their predecessor
+    // and can therefore never be picked
+    Some(cumulative.partition_point(|c| *c <= chosen))
+}
+
+/// Picks one of the `items` with a probability proportional to its weight.
+/// See [`choose_weighted_index`] for how invalid weights are handled.
+pub fn choose_weighted<T, I, R>(items: I, rng: &mut R) -> Option<T>
+where
+    I: IntoIterator<Item = (T, i32)>,
+    R: Rng + ?Sized,
+{
+    let (mut items, weights): (Vec<T>, Vec<i32>) = items.into_iter().unzip();
+    let index = choose_weighted_index(weights, rng)?;
+
+    Some(items.swap_remove(index))
+}
+
+/// Collections with weighted entries that one entry can be chosen from
+pub trait WeightedChoice<T> {
+    fn choose_weighted<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&T>;
+}
+
+impl<T> WeightedChoice<T> for [Weighted<T>] {
+    fn choose_weighted<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&T> {
+        choose_weighted(self.iter().map(|w| (&w.data, w.weight)), rng)
+    }
+}
+
+impl<T> WeightedChoice<T> for Vec<Weighted<T>> {
+    fn choose_weighted<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&T> {
+        self.as_slice().choose_weighted(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const SAMPLES: usize = 100_000;
+
+    fn sample_counts(weights: &[i32]) -> Vec<usize> {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut counts = vec![0; weights.len()];
+
+        for _ in 0..SAMPLES {
+            let index =
+                choose_weighted_index(weights.iter().copied(), &mut rng)
+                    .unwrap();
+            counts[index] += 1;
+        }
+
+        counts
+    }
+
+    #[test]
+    fn test_distribution_matches_weights() {
+        let weights = [1, 2, 7];
+        let counts = sample_counts(&weights);
+
+        for (count, weight) in counts.iter().zip(weights) {
+            let expected = SAMPLES as f64 * weight as f64 / 10.0;
+            let deviation = (*count as f64 - expected).abs() / expected;
+
+            assert!(
+                deviation < 0.05,
+                "Expected about {} samples but got {}",
+                expected,
+                count
+            );
+        }
+    }
+
+    #[test]
+    fn test_zero_and_negative_weights_are_never_chosen() {
+        let counts = sample_counts(&[0, 3, -5, 1, 0]);
+
+        assert_eq!(counts[0], 0);
+        assert_eq!(counts[2], 0);
+        assert_eq!(counts[4], 0);
+        assert_eq!(counts[1] + counts[3], SAMPLES);
+    }
+
+    #[test]
+    fn test_large_weights_do_not_overflow() {
+        let counts = sample_counts(&[i32::MAX, i32::MAX, i32::MAX]);
+
+        for count in counts {
+            assert!(count > SAMPLES / 4);
+        }
+    }
+
+    #[test]
+    fn test_no_positive_weights() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        assert_eq!(choose_weighted_index([], &mut rng), None);
+        assert_eq!(choose_weighted_index([0, -1], &mut rng), None);
+    }
+
+    #[test]
+    fn test_seeded_choice_is_deterministic() {
+        let items: Vec<Weighted<&str>> = vec![
+            Weighted::new("a", 1),
+            Weighted::new("b", 1),
+            Weighted::new("c", 1),
+        ];
+
+        let first = items.choose_weighted(&mut StdRng::seed_from_u64(7));
+        let second = items.choose_weighted(&mut StdRng::seed_from_u64(7));
+
+        assert_eq!(first, second);
+    }
+}
@@ -136,6 +136,13 @@ fn cdda_entry_impl(tokens: TokenStream) -> TokenStream {
                 self.copy_from.as_ref()
             }
 
+            fn set_copy_from(
+                &mut self,
+                copy_from: Option<cdda_lib::types::CDDAIdentifier>,
+            ) {
+                self.copy_from = copy_from;
+            }
+
             fn extend(&self) -> Option<&cdda_lib::types::CDDAExtendOp> {
                 self.extend.as_ref()
             }
@@ -0,0 +1,102 @@
+//! The import, rendering and export pipeline of the editor without any
+//! dependency on a running Tauri application, so it can be reused by other
+//! frontends, a command line interface or integration tests.
+//!
+//! None of these functions lock or emit anything, the caller owns the CDDA
+//! data, the projects and the tilesets. Rendering a project returns a
+//! [`GetMappedCDDAIdsError`] instead of panicking when its maps can not be
+//! resolved.
+
+use crate::data::io::load_cdda_json_data;
+use crate::features::map::CalculateParametersError;
+use crate::features::mods::{
+    find_mods, resolve_load_order, ModLoadOrderError, DEFAULT_CDDA_MODS_PATH,
+};
+use crate::features::program_data::{load_project_maps, LoadProjectMapsError};
+use crate::features::tileset::legacy_tileset;
+use crate::features::tileset::legacy_tileset::fallback::get_fallback_tilesheet;
+use crate::util::get_size;
+use cdda_lib::types::CDDAIdentifier;
+use std::path::Path;
+use thiserror::Error;
+
+pub use crate::data::io::DeserializedCDDAJsonData;
+pub use crate::features::map::export::ExportTarget;
+pub use crate::features::map::handlers::{export_mapgen, SaveMapgenError};
+pub use crate::features::map::GetMappedCDDAIdsError;
+pub use crate::features::program_data::{
+    LiveViewerData, Project, ProjectName, ProjectSaveState, ProjectType, ZLevel,
+};
+pub use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+pub use crate::features::viewer::handlers::{
+    reload_project_maps, ReloadProjectError,
+};
+pub use crate::features::viewer::render::{render_project, ProjectRender};
+
+#[derive(Debug, Error)]
+pub enum LoadCDDADataError {
+    #[error(transparent)]
+    ModLoadOrderError(#[from] ModLoadOrderError),
+
+    #[error(transparent)]
+    LoadFailed(#[from] anyhow::Error),
+}
+
+/// Loads the CDDA json data in the `json_data_path` of the CDDA installation
+/// at `cdda_path` together with the bundled `mods` and the mods they depend on
+pub async fn load_cdda_data(
+    cdda_path: &Path,
+    json_data_path: &Path,
+    mods: &[CDDAIdentifier],
+) -> Result<DeserializedCDDAJsonData, LoadCDDADataError> {
+    let available = find_mods(&[cdda_path.join(DEFAULT_CDDA_MODS_PATH)]);
+    let load_order = resolve_load_order(&available, mods)?;
+
+    Ok(
+        load_cdda_json_data(cdda_path, json_data_path, load_order, None, None)
+            .await?,
+    )
+}
+
+/// Loads the `tileset` from the gfx directory of the CDDA installation at
+/// `cdda_path`
+pub async fn load_tileset(
+    cdda_path: &Path,
+    tileset: &str,
+) -> Result<LegacyTilesheet, anyhow::Error> {
+    legacy_tileset::load_tileset(cdda_path, tileset).await
+}
+
+/// The tileset which is embedded into the editor and draws every tile as the
+/// character it is mapped to
+pub fn fallback_tileset() -> LegacyTilesheet {
+    get_fallback_tilesheet()
+}
+
+#[derive(Debug, Error)]
+pub enum LoadProjectError {
+    #[error(transparent)]
+    LoadProjectMapsError(#[from] LoadProjectMapsError),
+
+    #[error(transparent)]
+    CalculateParametersError(#[from] CalculateParametersError),
+}
+
+/// Creates the project `name` and imports its maps from the files the `ty`
+/// points to
+pub async fn load_project(
+    name: ProjectName,
+    ty: ProjectType,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<Project, LoadProjectError> {
+    let mut maps = load_project_maps(&ty).await?;
+
+    for (_, collection) in maps.iter_mut() {
+        collection.calculate_parameters(&json_data.palettes)?;
+    }
+
+    let mut project = Project::new(name, get_size(&maps), ty);
+    project.maps = maps;
+
+    Ok(project)
+}
@@ -0,0 +1,19 @@
+//! Regenerates the TypeScript event catalog of the frontend from the events
+//! in `src/events.rs`
+
+use std::fs;
+
+const CATALOG_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../src/src/tauri/events/catalog.ts"
+);
+
+fn main() {
+    fs::write(
+        CATALOG_PATH,
+        cdda_map_editor_2_lib::typescript_definitions(),
+    )
+    .expect("Failed to write the event catalog");
+
+    println!("Wrote the event catalog to {}", CATALOG_PATH);
+}
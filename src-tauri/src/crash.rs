@@ -0,0 +1,89 @@
+use crate::events::{CrashReportedEvent, EmitEvent};
+use crate::features::toast::ToastMessage;
+use log::error;
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    match info.payload().downcast_ref::<&str>() {
+        Some(s) => s.to_string(),
+        None => match info.payload().downcast_ref::<String>() {
+            Some(s) => s.clone(),
+            None => "Unknown panic".to_string(),
+        },
+    }
+}
+
+fn write_crash_file(
+    crash_dir: &PathBuf,
+    report: &str,
+) -> std::io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    fs::create_dir_all(crash_dir)?;
+
+    let path = crash_dir.join(format!("crash-{}.txt", timestamp));
+    fs::write(&path, report)?;
+
+    Ok(path)
+}
+
+/// Installs a panic hook which writes a crash report with a backtrace into
+/// `crash_dir` and notifies the frontend, so panics in background tasks do not
+/// go unnoticed. The previously installed hook is still called afterward.
+pub fn install_panic_hook(app: AppHandle, crash_dir: PathBuf) {
+    let previous_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let message = panic_message(info);
+        let location = info
+            .location()
+            .map(|l| l.to_string())
+            .unwrap_or_else(|| "unknown location".to_string());
+        let thread = std::thread::current()
+            .name()
+            .unwrap_or("unnamed")
+            .to_string();
+
+        let report = format!(
+            "Version: {}\nThread: {}\nLocation: {}\nMessage: {}\n\nBacktrace:\n{}\n",
+            env!("CARGO_PKG_VERSION"),
+            thread,
+            location,
+            message,
+            Backtrace::force_capture()
+        );
+
+        let crash_file = match write_crash_file(&crash_dir, &report) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                error!("Failed to write crash file: {}", e);
+                None
+            },
+        };
+
+        error!("Panic in thread {} at {}: {}", thread, location, message);
+
+        // Emitting can fail if the panic happened while the app is shutting
+        // down, in which case there is nobody left to notify
+        let _ = app.emit_event(ToastMessage::error(format!(
+            "An internal error occurred: {}. Save your work and restart the \
+             editor to recover.",
+            message
+        )));
+        let _ = app.emit_event(CrashReportedEvent {
+            message,
+            location,
+            crash_file,
+        });
+
+        previous_hook(info);
+    }));
+}
@@ -0,0 +1,174 @@
+use crate::data::field_type::CDDAFieldType;
+use crate::data::furniture::CDDAFurniture;
+use crate::data::map_object::CDDAHarvest;
+use crate::data::monster::CDDAMonster;
+use crate::data::overmap::{
+    CDDAOvermapLocation, CDDAOvermapSpecial, CDDAOvermapTerrain,
+};
+use crate::data::region_settings::CDDARegionSettings;
+use crate::data::section::RawEntry;
+use crate::data::terrain::CDDATerrain;
+use crate::data::trap::CDDATrap;
+use crate::data::vehicle_parts::CDDAVehiclePart;
+use crate::features::diagnostics::ImportDiagnostic;
+use crate::features::program_data::EditorConfig;
+use cdda_lib::types::CDDAIdentifier;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Has to be increased whenever the layout of the cache or the way entries
+/// are selected for it changes, so caches of older versions are discarded
+const CDDA_DATA_CACHE_VERSION: u32 = 4;
+
+const CDDA_DATA_CACHE_FILE: &str = "cdda_data.msgpack";
+
+/// The file of the CDDA installation which contains its version
+const CDDA_VERSION_FILE: &str = "VERSION.txt";
+
+pub fn get_cdda_data_cache_path(config: &EditorConfig) -> PathBuf {
+    config.config_path.join("cache").join(CDDA_DATA_CACHE_FILE)
+}
+
+/// Reads the version of the CDDA installation at `cdda_path`. Installations
+/// which were built from source do not have a version file.
+pub fn read_cdda_version(cdda_path: &Path) -> Option<String> {
+    fs::read_to_string(cdda_path.join(CDDA_VERSION_FILE)).ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedFileInfo {
+    pub path: PathBuf,
+    /// Modification time in milliseconds since the unix epoch
+    pub modified: u128,
+    pub size: u64,
+}
+
+/// Everything the loaded CDDA data depends on. The cache is only used if the
+/// key it was written with equals the key of the current load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CDDADataCacheKey {
+    pub editor_version: String,
+    pub cdda_version: Option<String>,
+    pub mods: Vec<CDDAIdentifier>,
+    pub files: Vec<CachedFileInfo>,
+}
+
+impl CDDADataCacheKey {
+    pub fn new(
+        cdda_version: Option<String>,
+        mods: Vec<CDDAIdentifier>,
+        files: &[PathBuf],
+    ) -> Result<Self, std::io::Error> {
+        let mut file_infos = Vec::with_capacity(files.len());
+
+        for path in files {
+            let metadata = fs::metadata(path)?;
+            let modified = metadata
+                .modified()?
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or_default();
+
+            file_infos.push(CachedFileInfo {
+                path: path.clone(),
+                modified,
+                size: metadata.len(),
+            });
+        }
+
+        Ok(Self {
+            editor_version: env!("CARGO_PKG_VERSION").to_string(),
+            cdda_version,
+            mods,
+            files: file_infos,
+        })
+    }
+}
+
+/// The loaded CDDA data as it is stored in the cache. Palettes and mapgens
+/// hold their mappings as trait objects, so they are stored as their json
+/// entries and deserialized again when the cache is read. The entries of the
+/// lazy sections are stored as they are and are still only deserialized when
+/// their section is accessed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedCDDAJsonData {
+    pub region_settings: HashMap<CDDAIdentifier, CDDARegionSettings>,
+    pub terrain: HashMap<CDDAIdentifier, CDDATerrain>,
+    pub furniture: HashMap<CDDAIdentifier, CDDAFurniture>,
+    pub overmap_locations: HashMap<CDDAIdentifier, CDDAOvermapLocation>,
+    pub overmap_terrains: HashMap<CDDAIdentifier, CDDAOvermapTerrain>,
+    pub overmap_specials: HashMap<CDDAIdentifier, CDDAOvermapSpecial>,
+    pub vehicle_parts: HashMap<CDDAIdentifier, CDDAVehiclePart>,
+    pub monsters: HashMap<CDDAIdentifier, CDDAMonster>,
+    pub harvests: HashMap<CDDAIdentifier, CDDAHarvest>,
+    pub field_types: HashMap<CDDAIdentifier, CDDAFieldType>,
+    pub traps: HashMap<CDDAIdentifier, CDDATrap>,
+    pub update_mapgen_ids: HashSet<CDDAIdentifier>,
+    pub sources: HashMap<String, HashMap<CDDAIdentifier, PathBuf>>,
+    pub copied_from: HashMap<String, HashMap<CDDAIdentifier, CDDAIdentifier>>,
+    pub mods: Vec<CDDAIdentifier>,
+    /// The json entries of the palettes and mapgens
+    pub entries: Vec<RawEntry>,
+    /// The entries of the lazy sections, grouped by their `type`
+    pub lazy_entries: HashMap<String, Vec<RawEntry>>,
+    pub diagnostics: Vec<ImportDiagnostic>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CDDADataCache {
+    version: u32,
+    key: CDDADataCacheKey,
+    data: CachedCDDAJsonData,
+}
+
+/// Reads the cached data at `path` if the cache was written for the `key`
+pub fn read_cdda_data_cache(
+    path: &Path,
+    key: &CDDADataCacheKey,
+) -> Option<CachedCDDAJsonData> {
+    let reader = BufReader::new(File::open(path).ok()?);
+
+    let cache: CDDADataCache = match rmp_serde::from_read(reader) {
+        Ok(cache) => cache,
+        Err(e) => {
+            warn!("Failed to read the CDDA data cache: {}", e);
+            return None;
+        },
+    };
+
+    if cache.version != CDDA_DATA_CACHE_VERSION || &cache.key != key {
+        info!("The CDDA data cache is outdated");
+        return None;
+    }
+
+    Some(cache.data)
+}
+
+pub fn write_cdda_data_cache(
+    path: &Path,
+    key: CDDADataCacheKey,
+    data: CachedCDDAJsonData,
+) -> Result<(), anyhow::Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let cache = CDDADataCache {
+        version: CDDA_DATA_CACHE_VERSION,
+        key,
+        data,
+    };
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    rmp_serde::encode::write_named(&mut writer, &cache)?;
+
+    info!("Wrote the CDDA data cache to {}", path.display());
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use cdda_lib::types::{CDDAIdentifier, CDDAString};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A duration like the `half_life` of a field. CDDA accepts either a number
+/// of turns or a string like "30 minutes" or "1 h 30 m"
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum CDDADuration {
+    Turns(i64),
+    Text(String),
+    Other(Value),
+}
+
+impl CDDADuration {
+    /// Returns the duration in seconds. One turn is one second long
+    pub fn to_seconds(&self) -> Option<i64> {
+        match self {
+            CDDADuration::Turns(turns) => Some(*turns),
+            CDDADuration::Text(text) => parse_duration_text(text),
+            CDDADuration::Other(_) => None,
+        }
+    }
+}
+
+fn unit_to_seconds(unit: &str) -> Option<i64> {
+    match unit {
+        "t" | "turn" | "turns" | "s" | "second" | "seconds" => Some(1),
+        "m" | "min" | "minute" | "minutes" => Some(60),
+        "h" | "hour" | "hours" => Some(60 * 60),
+        "d" | "day" | "days" => Some(60 * 60 * 24),
+        _ => None,
+    }
+}
+
+fn parse_duration_text(text: &str) -> Option<i64> {
+    let mut seconds = 0;
+    let mut parts = text.split_whitespace();
+
+    while let Some(part) = parts.next() {
+        // The unit can either be separated from the number like "30 minutes"
+        // or directly attached to it like "30m"
+        let split_index = part
+            .find(|c: char| !c.is_ascii_digit() && c != '-')
+            .unwrap_or(part.len());
+        let (number, unit) = part.split_at(split_index);
+        let number: i64 = number.parse().ok()?;
+
+        let unit = match unit.is_empty() {
+            true => parts.next().unwrap_or("turns"),
+            false => unit,
+        };
+
+        seconds += number * unit_to_seconds(unit)?;
+    }
+
+    Some(seconds)
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FieldIntensityLevel {
+    pub name: Option<CDDAString>,
+    pub sym: Option<String>,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDAFieldType {
+    #[serde(alias = "abstract")]
+    pub id: CDDAIdentifier,
+    #[serde(default)]
+    pub intensity_levels: Vec<FieldIntensityLevel>,
+    pub half_life: Option<CDDADuration>,
+}
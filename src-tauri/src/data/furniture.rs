@@ -1,3 +1,6 @@
+use crate::data::map_object::{
+    CDDABash, CDDADeconstruct, CDDAHarvestBySeason, CDDAPlantData,
+};
 use cdda_lib::types::{CDDAIdentifier, CDDAString, MeabyVec};
 use cdda_macros::cdda_entry;
 use serde::{Deserialize, Serialize};
@@ -13,5 +16,11 @@ pub struct CDDAFurniture {
     pub color: Option<MeabyVec<String>>,
     pub connect_groups: Option<MeabyVec<CDDAIdentifier>>,
     pub connects_to: Option<MeabyVec<CDDAIdentifier>>,
+    pub bash: Option<CDDABash>,
+    pub deconstruct: Option<CDDADeconstruct>,
+    pub harvest_by_season: Option<Vec<CDDAHarvestBySeason>>,
+    pub plant_data: Option<CDDAPlantData>,
+    pub move_cost_mod: Option<i32>,
+    pub light_emitted: Option<i32>,
     pub flags: Vec<String>,
 }
@@ -1,22 +1,47 @@
-use crate::data::furniture::CDDAFurniture;
+use crate::data::cache::{
+    read_cdda_data_cache, read_cdda_version, write_cdda_data_cache,
+    CDDADataCacheKey, CachedCDDAJsonData,
+};
+use crate::data::field_type::CDDAFieldType;
+use crate::data::furniture::{CDDAFurniture, CDDAFurnitureIntermediate};
 use crate::data::item::CDDAItemGroup;
-use crate::data::map_data::OmTerrain;
+use crate::data::map_data::{
+    unknown_mapping_fields, OmTerrain, MAPGEN_OBJECT_FIELDS,
+};
+use crate::data::map_object::CDDAHarvest;
 use crate::data::monster::CDDAMonster;
-use crate::data::monster_group::CDDAMonsterGroup;
+use crate::data::monster_group::{
+    CDDAMonsterGroup, CDDAMonsterGroupIntermediate,
+};
+use crate::data::npc::{CDDANpc, CDDANpcClass};
 use crate::data::overmap::{
-    CDDAOvermapLocation, CDDAOvermapSpecial, CDDAOvermapTerrain,
+    CDDAOvermapLocation, CDDAOvermapLocationIntermediate, CDDAOvermapSpecial,
+    CDDAOvermapSpecialIntermediate, CDDAOvermapTerrain,
+    CDDAOvermapTerrainIntermediate,
 };
 use crate::data::palettes::CDDAPalette;
 use crate::data::region_settings::CDDARegionSettings;
-use crate::data::terrain::CDDATerrain;
-use crate::data::vehicle_parts::CDDAVehiclePart;
-use crate::data::vehicles::CDDAVehicle;
-use crate::data::{CDDAJsonEntry, TileLayer};
-use crate::features::map::MapData;
+use crate::data::section::{LazySection, RawEntry};
+use crate::data::terrain::{CDDATerrain, CDDATerrainIntermediate};
+use crate::data::trap::CDDATrap;
+use crate::data::vehicle_parts::{
+    CDDAVehiclePart, CDDAVehiclePartIntermediate, APPLIANCE_PART_PREFIX,
+};
+use crate::data::vehicles::{
+    CDDAVehicle, CDDAVehicleIntermediate, VehiclePart,
+};
+use crate::data::{replace_region_setting, CDDAJsonEntry, TileLayer};
+use crate::events::{EditorEvent, EventPayload};
+use crate::features::connect_groups::CustomConnectGroups;
+use crate::features::diagnostics::{Diagnostics, ImportDiagnostic};
+use crate::features::jobs::{JobHandle, JobId};
+use crate::features::map::{CalculateParametersError, MapData};
+use crate::features::mods::CDDAMod;
+use crate::features::palette::check_palette_references;
 use crate::features::program_data::io::ProgramDataLoader;
 use crate::features::program_data::{EditorData, MapDataCollection};
 use crate::util::Load;
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 use async_walkdir::WalkDir;
 use cdda_lib::types::{
     CDDAIdentifier, DistributionInner, ImportCDDAObject, MeabyVec,
@@ -27,15 +52,18 @@ use futures_lite::stream::StreamExt;
 use glam::UVec2;
 use log::kv::Source;
 use log::{debug, error, info, warn};
-use serde::Serialize;
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::fs::File;
 use std::io::BufReader;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::string::ToString;
 use thiserror::Error;
 
+/// The CDDA json data which is used by the editor. Sections which are not
+/// needed by every map, like item groups, monster groups, vehicles and npcs,
+/// are only deserialized when they are accessed for the first time.
 #[derive(Default, Serialize, Clone)]
 pub struct DeserializedCDDAJsonData {
     pub palettes: HashMap<CDDAIdentifier, CDDAPalette>,
@@ -43,14 +71,33 @@ pub struct DeserializedCDDAJsonData {
     pub region_settings: HashMap<CDDAIdentifier, CDDARegionSettings>,
     pub terrain: HashMap<CDDAIdentifier, CDDATerrain>,
     pub furniture: HashMap<CDDAIdentifier, CDDAFurniture>,
-    pub item_groups: HashMap<CDDAIdentifier, CDDAItemGroup>,
+    pub item_groups: LazySection<CDDAItemGroup>,
     pub overmap_locations: HashMap<CDDAIdentifier, CDDAOvermapLocation>,
     pub overmap_terrains: HashMap<CDDAIdentifier, CDDAOvermapTerrain>,
     pub overmap_specials: HashMap<CDDAIdentifier, CDDAOvermapSpecial>,
-    pub vehicles: HashMap<CDDAIdentifier, CDDAVehicle>,
+    pub vehicles: LazySection<CDDAVehicle>,
     pub vehicle_parts: HashMap<CDDAIdentifier, CDDAVehiclePart>,
-    pub monster_groups: HashMap<CDDAIdentifier, CDDAMonsterGroup>,
+    pub monster_groups: LazySection<CDDAMonsterGroup>,
     pub monsters: HashMap<CDDAIdentifier, CDDAMonster>,
+    pub harvests: HashMap<CDDAIdentifier, CDDAHarvest>,
+    pub field_types: HashMap<CDDAIdentifier, CDDAFieldType>,
+    pub npcs: LazySection<CDDANpc>,
+    pub npc_classes: LazySection<CDDANpcClass>,
+    pub traps: HashMap<CDDAIdentifier, CDDATrap>,
+    /// Ids of all mapgen entries in `map_data` which are update mapgens
+    pub update_mapgen_ids: HashSet<CDDAIdentifier>,
+    /// The file every entry was loaded from, grouped by the `type` of the
+    /// entry in the json files
+    pub sources: HashMap<String, HashMap<CDDAIdentifier, PathBuf>>,
+    /// The id every entry copies from, grouped by the `type` of the entry in
+    /// the json files
+    pub copied_from: HashMap<String, HashMap<CDDAIdentifier, CDDAIdentifier>>,
+    /// The ids of the mods which were loaded on top of the core data, in load
+    /// order
+    pub mods: Vec<CDDAIdentifier>,
+    /// The problems which were found while the data was imported
+    #[serde(skip)]
+    pub diagnostics: Diagnostics,
 }
 
 #[derive(Debug, Error)]
@@ -90,10 +137,118 @@ pub enum GetConnectsToError {
 }
 
 impl DeserializedCDDAJsonData {
+    /// The region settings of the `region` a project selected, falling back
+    /// to the `default` region settings if the project did not select any or
+    /// the region does not exist in the loaded data
+    pub fn get_region_settings(
+        &self,
+        region: Option<&CDDAIdentifier>,
+    ) -> Option<&CDDARegionSettings> {
+        region
+            .and_then(|id| self.region_settings.get(id))
+            .or_else(|| {
+                self.region_settings.get(&CDDAIdentifier::from("default"))
+            })
+    }
+
+    /// Replace regional ids like `t_region_groundcover`, regional item groups
+    /// and regional vehicles with an actual id from the region settings of the
+    /// `region`
+    pub fn resolve_region_setting(
+        &self,
+        id: &CDDAIdentifier,
+        region: Option<&CDDAIdentifier>,
+    ) -> CDDAIdentifier {
+        match self.get_region_settings(region) {
+            None => id.clone(),
+            Some(region_settings) => {
+                replace_region_setting(id, region_settings)
+            },
+        }
+    }
+
+    /// Returns the vehicle part which represents the appliance with the `id`.
+    /// The id can either be the vehicle part itself, an appliance item which
+    /// has a corresponding `ap_` part or a vehicle made out of a single part
+    pub fn get_appliance_part(
+        &self,
+        id: &CDDAIdentifier,
+    ) -> Option<&CDDAVehiclePart> {
+        if let Some(part) = self.vehicle_parts.get(id) {
+            return Some(part);
+        }
+
+        let prefixed_id =
+            CDDAIdentifier(format!("{}{}", APPLIANCE_PART_PREFIX, id));
+
+        if let Some(part) = self.vehicle_parts.get(&prefixed_id) {
+            return Some(part);
+        }
+
+        let vehicle = self.vehicles.get(id)?;
+        let part = match vehicle.parts.as_slice() {
+            [placement] => match placement.parts.as_slice() {
+                [part] => part,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        let part_id = match part {
+            VehiclePart::Inline(id) => id,
+            VehiclePart::Object { part, .. } => part,
+        };
+
+        // Strip the variant of the part
+        let part_id = match part_id.0.split_once('#') {
+            None => part_id.clone(),
+            Some((id, _)) => id.into(),
+        };
+
+        self.vehicle_parts.get(&part_id)
+    }
+
+    /// Calculates the parameters of the update mapgens with the `ids` so they
+    /// can be applied on top of another map
+    pub fn calculate_update_mapgen_parameters(
+        &mut self,
+        ids: &[CDDAIdentifier],
+    ) -> Result<(), CalculateParametersError> {
+        for id in ids {
+            match self.map_data.get_mut(id) {
+                None => {},
+                Some(map_data) => map_data.calculate_parameters(&self.palettes)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The connect groups of the `id` in the game data together with the
+    /// `custom_connect_groups` of the project. Ids which only have custom
+    /// groups do not have to exist in the game data.
     pub fn get_connect_groups(
         &self,
         id: CDDAIdentifier,
         layer: &TileLayer,
+        custom_connect_groups: &CustomConnectGroups,
+    ) -> Result<HashSet<CDDAIdentifier>, GetConnectGroupsError> {
+        let groups = self.get_json_connect_groups(id.clone(), layer);
+
+        match custom_connect_groups.connect_groups.get(&id) {
+            None => groups,
+            Some(custom) => {
+                let mut groups = groups.unwrap_or_default();
+                groups.extend(custom.iter().cloned());
+                Ok(groups)
+            },
+        }
+    }
+
+    fn get_json_connect_groups(
+        &self,
+        id: CDDAIdentifier,
+        layer: &TileLayer,
     ) -> Result<HashSet<CDDAIdentifier>, GetConnectGroupsError> {
         match layer {
             TileLayer::Terrain => {
@@ -167,10 +322,30 @@ impl DeserializedCDDAJsonData {
         }
     }
 
+    /// The groups the `id` connects to in the game data together with the
+    /// groups it connects to in the `custom_connect_groups` of the project
     pub fn get_connects_to(
         &self,
         id: CDDAIdentifier,
         layer: &TileLayer,
+        custom_connect_groups: &CustomConnectGroups,
+    ) -> Result<HashSet<CDDAIdentifier>, GetConnectsToError> {
+        let connects_to = self.get_json_connects_to(id.clone(), layer);
+
+        match custom_connect_groups.connects_to.get(&id) {
+            None => connects_to,
+            Some(custom) => {
+                let mut connects_to = connects_to.unwrap_or_default();
+                connects_to.extend(custom.iter().cloned());
+                Ok(connects_to)
+            },
+        }
+    }
+
+    fn get_json_connects_to(
+        &self,
+        id: CDDAIdentifier,
+        layer: &TileLayer,
     ) -> Result<HashSet<CDDAIdentifier>, GetConnectsToError> {
         match layer {
             TileLayer::Terrain => {
@@ -273,390 +448,1436 @@ impl DeserializedCDDAJsonData {
     }
 }
 
-pub struct CDDADataLoader {
-    pub json_path: PathBuf,
+/// An entry of the CDDA data which was added, changed or removed by a reload
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct ChangedEntry {
+    /// The `type` of the entry in the json files
+    pub json_type: String,
+    pub id: CDDAIdentifier,
 }
 
-impl Load<DeserializedCDDAJsonData> for CDDADataLoader {
-    async fn load(&mut self) -> Result<DeserializedCDDAJsonData, Error> {
-        let mut walkdir = WalkDir::new(&self.json_path);
+impl DeserializedCDDAJsonData {
+    /// The data as it is stored in the cache. The `entries` are the json
+    /// entries of the palettes and mapgens in the data.
+    fn to_cache(&self, entries: Vec<RawEntry>) -> CachedCDDAJsonData {
+        let lazy_entries = HashMap::from([
+            ("item_group".to_string(), self.item_groups.raw_entries()),
+            (
+                "monstergroup".to_string(),
+                self.monster_groups.raw_entries(),
+            ),
+            ("vehicle".to_string(), self.vehicles.raw_entries()),
+            ("npc".to_string(), self.npcs.raw_entries()),
+            ("npc_class".to_string(), self.npc_classes.raw_entries()),
+        ]);
+
+        CachedCDDAJsonData {
+            region_settings: self.region_settings.clone(),
+            terrain: self.terrain.clone(),
+            furniture: self.furniture.clone(),
+            overmap_locations: self.overmap_locations.clone(),
+            overmap_terrains: self.overmap_terrains.clone(),
+            overmap_specials: self.overmap_specials.clone(),
+            vehicle_parts: self.vehicle_parts.clone(),
+            monsters: self.monsters.clone(),
+            harvests: self.harvests.clone(),
+            field_types: self.field_types.clone(),
+            traps: self.traps.clone(),
+            update_mapgen_ids: self.update_mapgen_ids.clone(),
+            sources: self.sources.clone(),
+            copied_from: self.copied_from.clone(),
+            mods: self.mods.clone(),
+            entries,
+            lazy_entries,
+            diagnostics: self.diagnostics.all(),
+        }
+    }
 
-        let mut cdda_data = DeserializedCDDAJsonData::default();
-        cdda_data.add_hardcoded_map_data();
+    /// Moves the entry `id` of the `json_type` from `other` into the data, or
+    /// removes it from the data if `other` does not contain it
+    fn take_entry(
+        &mut self,
+        other: &mut DeserializedCDDAJsonData,
+        json_type: &str,
+        id: &CDDAIdentifier,
+    ) {
+        macro_rules! take {
+            ($field:ident) => {
+                match other.$field.remove(id) {
+                    None => {
+                        self.$field.remove(id);
+                    },
+                    Some(entry) => {
+                        self.$field.insert(id.clone(), entry);
+                    },
+                }
+            };
+        }
 
-        let mut intermediate_vehicles = HashMap::new();
-        let mut intermediate_vehicle_parts = HashMap::new();
-        let mut intermediate_terrains = HashMap::new();
-        let mut intermediate_furnitures = HashMap::new();
-        let mut intermediate_overmap_locations = HashMap::new();
-        let mut intermediate_overmap_terrains = HashMap::new();
-        let mut intermediate_overmap_specials = HashMap::new();
-        let mut intermediate_monster_groups = HashMap::new();
+        match json_type {
+            "mapgen" => {
+                take!(map_data);
 
-        while let Some(entry) = walkdir.next().await {
-            let entry = entry?;
+                match other.update_mapgen_ids.contains(id) {
+                    true => self.update_mapgen_ids.insert(id.clone()),
+                    false => self.update_mapgen_ids.remove(id),
+                };
+            },
+            "region_settings" => take!(region_settings),
+            "palette" => take!(palettes),
+            "terrain" => take!(terrain),
+            "furniture" => take!(furniture),
+            "item_group" => take!(item_groups),
+            "monstergroup" => take!(monster_groups),
+            "overmap_location" => take!(overmap_locations),
+            "overmap_terrain" => take!(overmap_terrains),
+            "overmap_special" => take!(overmap_specials),
+            "vehicle" => take!(vehicles),
+            "vehicle_part" => take!(vehicle_parts),
+            "harvest" => take!(harvests),
+            "field_type" => take!(field_types),
+            "npc" => take!(npcs),
+            "npc_class" => take!(npc_classes),
+            "trap" => take!(traps),
+            _ => {},
+        }
+    }
 
-            let path = entry.path();
-            let extension = match path.extension() {
-                None => {
-                    info!(
-                        "Skipping entry {:?} because it does not have an extension",
-                        entry.path()
-                    );
+    /// Remembers that the entry `id` of the `json_type` was loaded from the
+    /// file at `path` and which entry it copies from
+    fn add_source(
+        &mut self,
+        json_type: &str,
+        id: &CDDAIdentifier,
+        path: &Path,
+        copy_from: Option<&CDDAIdentifier>,
+    ) {
+        self.sources
+            .entry(json_type.to_string())
+            .or_default()
+            .insert(id.clone(), path.to_path_buf());
+
+        self.set_copied_from(json_type, id, copy_from);
+    }
+
+    /// Sets the entry the entry `id` of the `json_type` copies from. Entries
+    /// which copy from an entry with the same id replace it, so they do not
+    /// depend on it.
+    fn set_copied_from(
+        &mut self,
+        json_type: &str,
+        id: &CDDAIdentifier,
+        copy_from: Option<&CDDAIdentifier>,
+    ) {
+        let copied_from =
+            self.copied_from.entry(json_type.to_string()).or_default();
+
+        match copy_from.filter(|c| *c != id) {
+            None => copied_from.remove(id),
+            Some(copy_from) => {
+                copied_from.insert(id.clone(), copy_from.clone())
+            },
+        };
+    }
+
+    /// The entries of every `type` which were loaded from the file at `path`
+    fn entries_from(&self, path: &Path) -> BTreeSet<ChangedEntry> {
+        self.sources
+            .iter()
+            .flat_map(|(json_type, sources)| {
+                sources
+                    .iter()
+                    .filter(|(_, source)| source.as_path() == path)
+                    .map(|(id, _)| ChangedEntry {
+                        json_type: json_type.clone(),
+                        id: id.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    /// The entries which copy from one of the `entries`, directly or through
+    /// other entries, and were not loaded from the file at `path`
+    fn entries_copying_from(
+        &self,
+        entries: &BTreeSet<ChangedEntry>,
+        path: &Path,
+    ) -> BTreeSet<ChangedEntry> {
+        let mut to_visit: Vec<ChangedEntry> = entries.iter().cloned().collect();
+        let mut dependents = BTreeSet::new();
+
+        while let Some(entry) = to_visit.pop() {
+            let Some(copied_from) = self.copied_from.get(&entry.json_type)
+            else {
+                continue;
+            };
+
+            for (id, copy_from) in copied_from {
+                if *copy_from != entry.id {
                     continue;
-                },
-                Some(e) => e,
+                }
+
+                let from_path = self
+                    .sources
+                    .get(&entry.json_type)
+                    .and_then(|s| s.get(id))
+                    .is_some_and(|s| s.as_path() == path);
+
+                let dependent = ChangedEntry {
+                    json_type: entry.json_type.clone(),
+                    id: id.clone(),
+                };
+
+                if from_path || entries.contains(&dependent) {
+                    continue;
+                }
+
+                if dependents.insert(dependent.clone()) {
+                    to_visit.push(dependent);
+                }
+            }
+        }
+
+        dependents
+    }
+
+    /// Reads the json file at `path` again and replaces all entries which were
+    /// loaded from it, without loading the rest of the CDDA data again. The
+    /// files of the entries the file copies from are read as well, so
+    /// `copy-from` is resolved like in a full load. Entries of other files
+    /// which copy from the entries of the file are resolved again as well. A
+    /// file which does not exist anymore removes all of its entries.
+    ///
+    /// Returns the entries which were added, changed or removed.
+    pub fn reload_json_file(
+        &mut self,
+        path: &Path,
+    ) -> Result<BTreeSet<ChangedEntry>, Error> {
+        self.diagnostics.clear_file(path);
+
+        let values = match path.exists() {
+            true => read_json_values(path).inspect_err(|e| {
+                self.diagnostics
+                    .report(ImportDiagnostic::from_file_error(path, e))
+            })?,
+            false => vec![],
+        };
+
+        // The entries which were loaded from the file before and the entries
+        // which are loaded from it now
+        let mut entries = self.entries_from(path);
+        for value in values.iter() {
+            let Some(json_type) = value.get("type").and_then(|t| t.as_str())
+            else {
+                continue;
             };
 
-            if extension != "json" {
-                info!(
-                    "Skipping {:?} because it is not a json file",
-                    entry.path()
-                );
+            entries.extend(raw_entry_ids(value).into_iter().map(|id| {
+                ChangedEntry {
+                    json_type: json_type.to_string(),
+                    id,
+                }
+            }));
+        }
+
+        let dependents = self.entries_copying_from(&entries, path);
+        let mut dependent_files: Vec<PathBuf> = vec![];
+
+        for entry in dependents.iter() {
+            let Some(source) = self
+                .sources
+                .get(&entry.json_type)
+                .and_then(|s| s.get(&entry.id))
+            else {
+                continue;
+            };
+
+            if !dependent_files.contains(source) {
+                dependent_files.push(source.clone());
+            }
+        }
+
+        let mut to_visit = copy_from_of(&values)?;
+        let mut visited = HashSet::new();
+        let mut dependencies: Vec<(PathBuf, Vec<serde_json::Value>)> = vec![];
+
+        while let Some((json_type, id)) = to_visit.pop() {
+            if !visited.insert((json_type, id.clone())) {
                 continue;
             }
 
-            info!("Reading and parsing json file at {:?}", entry.path());
-            let reader = BufReader::new(File::open(entry.path())?);
+            let Some(source) =
+                self.sources.get(json_type).and_then(|s| s.get(&id))
+            else {
+                continue;
+            };
 
-            let des = match serde_json::from_reader::<
-                BufReader<File>,
-                Vec<CDDAJsonEntry>,
-            >(reader)
+            if source == path
+                || dependent_files.contains(source)
+                || dependencies.iter().any(|(p, _)| p == source)
             {
-                Ok(des) => des,
-                Err(e) => {
-                    error!(
-                        "Failed to deserialize {:?}, error: {}",
-                        entry.path(),
-                        e
-                    );
-                    continue;
+                continue;
+            }
+
+            let source_values = read_json_values(source)?;
+            to_visit.extend(copy_from_of(&source_values)?);
+            dependencies.push((source.clone(), source_values));
+        }
+
+        // The changed file is added last, so its entries override the entries
+        // of the files it depends on
+        let mut collector = CDDAJsonCollector::default();
+        for (source, source_values) in dependencies.into_iter().rev() {
+            collector.add_values(&source, source_values)?;
+        }
+
+        collector.add_values(path, values)?;
+
+        // The files of the entries which copy from the file are added after
+        // it, like they would be in a full load
+        for source in dependent_files.iter() {
+            collector.add_values(source, read_json_values(source)?)?;
+        }
+
+        let mut reloaded = collector.finish();
+
+        let mut changed = self.entries_from(path);
+        changed.extend(reloaded.entries_from(path));
+
+        for entry in changed.iter() {
+            self.take_entry(&mut reloaded, &entry.json_type, &entry.id);
+
+            let copy_from = reloaded
+                .copied_from
+                .get(&entry.json_type)
+                .and_then(|c| c.get(&entry.id))
+                .cloned();
+            self.set_copied_from(
+                &entry.json_type,
+                &entry.id,
+                copy_from.as_ref(),
+            );
+
+            let source = reloaded
+                .sources
+                .get(&entry.json_type)
+                .and_then(|s| s.get(&entry.id))
+                .filter(|s| s.as_path() == path);
+            let sources =
+                self.sources.entry(entry.json_type.clone()).or_default();
+
+            match source {
+                None => sources.remove(&entry.id),
+                Some(source) => {
+                    sources.insert(entry.id.clone(), source.clone())
                 },
             };
+        }
 
-            for des_entry in des {
-                match des_entry {
-                    CDDAJsonEntry::Mapgen(mapgen) => {
-                        if let Some(om_terrain) = mapgen.om_terrain.clone() {
-                            match om_terrain {
-                                OmTerrain::Single(id) => {
-                                    debug!(
-                                        "Found Single Mapgen '{}' in {:?}",
-                                        id,
-                                        entry.path()
-                                    );
+        for entry in dependents.iter() {
+            self.take_entry(&mut reloaded, &entry.json_type, &entry.id);
+        }
+
+        changed.extend(dependents);
+
+        // The problems of the files it depends on were already reported when
+        // they were loaded, only the problems of the file are reported again
+        for diagnostic in reloaded.diagnostics.all() {
+            if diagnostic.path == path {
+                self.diagnostics.report(diagnostic);
+            }
+        }
+
+        Ok(changed)
+    }
+}
+
+fn read_json_values(path: &Path) -> Result<Vec<serde_json::Value>, Error> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// The `type`s and ids of the entries the json `values` copy from
+fn copy_from_of(
+    values: &[serde_json::Value],
+) -> Result<Vec<(&'static str, CDDAIdentifier)>, Error> {
+    let mut copy_from = vec![];
+
+    for value in values {
+        copy_from.extend(CDDAJsonEntry::deserialize(value)?.copy_from());
+    }
+
+    Ok(copy_from)
+}
+
+/// The ids of a json entry which was not deserialized yet
+fn raw_entry_ids(value: &serde_json::Value) -> Vec<CDDAIdentifier> {
+    value
+        .get("id")
+        .or_else(|| value.get("abstract"))
+        .and_then(|id| MeabyVec::<CDDAIdentifier>::deserialize(id).ok())
+        .map(|ids| ids.into_vec())
+        .unwrap_or_default()
+}
+
+/// Describes a json entry which was not deserialized yet for error messages,
+/// like `terrain t_floor`
+fn describe_raw_entry(value: &serde_json::Value) -> String {
+    let json_type = value.get("type").and_then(|t| t.as_str());
+    let ids: Vec<String> = raw_entry_ids(value)
+        .iter()
+        .map(|id| id.to_string())
+        .collect();
+
+    format!("{} {}", json_type.unwrap_or("entry"), ids.join(", "))
+}
+
+/// The `type`s of the json entries which are only deserialized when their
+/// section of the data is accessed for the first time
+const LAZY_JSON_TYPES: &[&str] =
+    &["item_group", "monstergroup", "vehicle", "npc", "npc_class"];
+
+/// Deserializes the `raw` entries of a lazy section of the data. Problems
+/// with the entries are reported to the `diagnostics`.
+fn load_lazy_section(
+    raw: Vec<RawEntry>,
+    diagnostics: &Diagnostics,
+) -> DeserializedCDDAJsonData {
+    let mut collector = CDDAJsonCollector::default();
+    collector.cdda_data.diagnostics = diagnostics.clone();
+
+    for entry in raw {
+        let result = CDDAJsonEntry::deserialize(&entry.value)
+            .map_err(Error::from)
+            .and_then(|des| collector.add_entries(&entry.path, vec![des]));
+
+        if let Err(e) = result {
+            let message = format!(
+                "Failed to load the {}: {}",
+                describe_raw_entry(&entry.value),
+                e
+            );
+
+            error!("{} in {:?}", message, entry.path);
+            diagnostics.report(ImportDiagnostic {
+                id: raw_entry_ids(&entry.value).into_iter().next(),
+                ..ImportDiagnostic::error(&entry.path, message)
+            });
+        }
+    }
+
+    collector.finish()
+}
+
+/// Inserts the `object` with the `id` into the `intermediate_objects`.
+///
+/// Mods change objects of the core data or of mods they depend on by copying
+/// from an object with the same id. Since the new object replaces the
+/// previous one, it is merged with the previous object right away instead of
+/// when the collector is finished.
+fn insert_intermediate<T: ImportCDDAObject>(
+    intermediate_objects: &mut HashMap<CDDAIdentifier, T>,
+    id: CDDAIdentifier,
+    mut object: T,
+    diagnostics: &Diagnostics,
+    path: &Path,
+) {
+    if object.copy_from() == Some(&id) {
+        match intermediate_objects.contains_key(&id) {
+            true => object = object.calculate_copy(intermediate_objects),
+            false => {
+                warn!("Could not copy {} due to it not existing", id);
+                diagnostics.report(ImportDiagnostic::warning(
+                    path,
+                    Some(id.clone()),
+                    format!("Copies from {} which does not exist yet", id),
+                ));
+            },
+        }
+
+        object.set_copy_from(None);
+    }
 
-                                    let mut map_data_collection: MapDataCollection = mapgen.try_into()?;
+    intermediate_objects.insert(id, object);
+}
+
+/// Collects the entries of CDDA json files into the deserialized data.
+/// Entries which can copy from other entries are only resolved in
+/// [`CDDAJsonCollector::finish`], after all entries they can copy from were
+/// added.
+struct CDDAJsonCollector {
+    cdda_data: DeserializedCDDAJsonData,
+    intermediate_vehicles: HashMap<CDDAIdentifier, CDDAVehicleIntermediate>,
+    intermediate_vehicle_parts:
+        HashMap<CDDAIdentifier, CDDAVehiclePartIntermediate>,
+    intermediate_terrains: HashMap<CDDAIdentifier, CDDATerrainIntermediate>,
+    intermediate_furnitures: HashMap<CDDAIdentifier, CDDAFurnitureIntermediate>,
+    intermediate_overmap_locations:
+        HashMap<CDDAIdentifier, CDDAOvermapLocationIntermediate>,
+    intermediate_overmap_terrains:
+        HashMap<CDDAIdentifier, CDDAOvermapTerrainIntermediate>,
+    intermediate_overmap_specials:
+        HashMap<CDDAIdentifier, CDDAOvermapSpecialIntermediate>,
+    intermediate_monster_groups:
+        HashMap<CDDAIdentifier, CDDAMonsterGroupIntermediate>,
+    /// The entries of the lazy sections, grouped by their `type`
+    lazy_entries: HashMap<&'static str, Vec<RawEntry>>,
+    /// The json entries of the palettes and mapgens which are stored in the
+    /// cache. They are only collected if this is set.
+    cache_entries: Option<Vec<RawEntry>>,
+}
+
+impl Default for CDDAJsonCollector {
+    fn default() -> Self {
+        let mut cdda_data = DeserializedCDDAJsonData::default();
+        cdda_data.add_hardcoded_map_data();
+
+        Self {
+            cdda_data,
+            intermediate_vehicles: HashMap::new(),
+            intermediate_vehicle_parts: HashMap::new(),
+            intermediate_terrains: HashMap::new(),
+            intermediate_furnitures: HashMap::new(),
+            intermediate_overmap_locations: HashMap::new(),
+            intermediate_overmap_terrains: HashMap::new(),
+            intermediate_overmap_specials: HashMap::new(),
+            intermediate_monster_groups: HashMap::new(),
+            lazy_entries: HashMap::new(),
+            cache_entries: None,
+        }
+    }
+}
+
+impl CDDAJsonCollector {
+    /// Adds the json `values` which were read from the file at `path`. The
+    /// entries of the lazy sections are only stored until their section is
+    /// accessed. Nothing is added if one of the other entries can not be
+    /// deserialized.
+    fn add_values(
+        &mut self,
+        path: &Path,
+        values: Vec<serde_json::Value>,
+    ) -> Result<(), Error> {
+        let mut des = vec![];
+        let mut raw = vec![];
+        let mut cached = vec![];
+        let mut unknown_fields = vec![];
+
+        for value in values {
+            let json_type = value.get("type").and_then(|t| t.as_str());
+
+            match LAZY_JSON_TYPES.iter().find(|t| Some(**t) == json_type) {
+                Some(json_type) => raw.push((*json_type, value)),
+                None => {
+                    let entry =
+                        CDDAJsonEntry::deserialize(&value).map_err(|e| {
+                            anyhow!(
+                                "Failed to deserialize the {}: {}",
+                                describe_raw_entry(&value),
+                                e
+                            )
+                        })?;
+
+                    let fields = match &entry {
+                        CDDAJsonEntry::Mapgen(_) => unknown_mapping_fields(
+                            &value["object"],
+                            &MAPGEN_OBJECT_FIELDS,
+                        ),
+                        CDDAJsonEntry::Palette(_) => {
+                            unknown_mapping_fields(&value, &["type", "id"])
+                        },
+                        _ => vec![],
+                    };
+
+                    if !fields.is_empty() {
+                        let id = entry
+                            .source_ids()
+                            .and_then(|(_, ids)| ids.into_iter().next());
+                        unknown_fields.push((id, fields));
+                    }
+
+                    if matches!(
+                        entry,
+                        CDDAJsonEntry::Palette(_) | CDDAJsonEntry::Mapgen(_)
+                    ) {
+                        cached.push(RawEntry {
+                            path: path.to_path_buf(),
+                            value,
+                        });
+                    }
+
+                    des.push(entry);
+                },
+            }
+        }
+
+        for (json_type, value) in raw {
+            let ids = raw_entry_ids(&value);
+
+            if ids.is_empty() {
+                self.cdda_data.diagnostics.report(ImportDiagnostic::warning(
+                    path,
+                    None,
+                    format!("The {} does not have an id", json_type),
+                ));
+            }
+
+            let copy_from = value
+                .get("copy-from")
+                .and_then(|c| CDDAIdentifier::deserialize(c).ok());
+
+            for id in ids {
+                self.cdda_data.add_source(
+                    json_type,
+                    &id,
+                    path,
+                    copy_from.as_ref(),
+                );
+            }
+
+            self.lazy_entries
+                .entry(json_type)
+                .or_default()
+                .push(RawEntry {
+                    path: path.to_path_buf(),
+                    value,
+                });
+        }
+
+        self.add_entries(path, des)?;
+
+        for (id, fields) in unknown_fields {
+            for field in fields {
+                self.cdda_data.diagnostics.report(ImportDiagnostic::warning(
+                    path,
+                    id.clone(),
+                    format!(
+                        "The field {} is not supported by the editor and \
+                         ignored",
+                        field
+                    ),
+                ));
+            }
+        }
+
+        if let Some(cache_entries) = self.cache_entries.as_mut() {
+            cache_entries.extend(cached);
+        }
 
-                                    cdda_data.map_data.insert(
+        Ok(())
+    }
+
+    /// Adds the entries `des` which were read from the file at `path`
+    fn add_entries(
+        &mut self,
+        path: &Path,
+        des: Vec<CDDAJsonEntry>,
+    ) -> Result<(), Error> {
+        for des_entry in des.iter() {
+            if let Some((json_type, ids)) = des_entry.source_ids() {
+                let copy_from = des_entry.copy_from().map(|(_, id)| id);
+
+                for id in ids {
+                    self.cdda_data.add_source(
+                        json_type,
+                        &id,
+                        path,
+                        copy_from.as_ref(),
+                    );
+                }
+            }
+        }
+
+        for des_entry in des {
+            match des_entry {
+                CDDAJsonEntry::Mapgen(mapgen) => {
+                    if let Some(om_terrain) = mapgen.om_terrain.clone() {
+                        match om_terrain {
+                            OmTerrain::Single(id) => {
+                                debug!(
+                                    "Found Single Mapgen '{}' in {:?}",
+                                    id, path
+                                );
+
+                                let mut map_data_collection: MapDataCollection =
+                                    mapgen.try_into()?;
+
+                                self.cdda_data.map_data.insert(
+                                    CDDAIdentifier(id.clone()),
+                                    map_data_collection
+                                        .maps
+                                        .remove(&UVec2::ZERO)
+                                        .unwrap(),
+                                );
+                            },
+                            OmTerrain::Duplicate(duplicate) => {
+                                debug!(
+                                    "Found Duplicate Mapgen '{:?}' in {:?}",
+                                    duplicate, path
+                                );
+
+                                let map_data_collection: MapDataCollection =
+                                    mapgen.try_into()?;
+
+                                for id in duplicate.iter() {
+                                    self.cdda_data.map_data.insert(
                                         CDDAIdentifier(id.clone()),
                                         map_data_collection
                                             .maps
-                                            .remove(&UVec2::ZERO)
-                                            .unwrap(),
+                                            .get(&UVec2::ZERO)
+                                            .unwrap()
+                                            .clone(),
                                     );
-                                },
-                                OmTerrain::Duplicate(duplicate) => {
-                                    debug!(
-                                        "Found Duplicate Mapgen '{:?}' in {:?}",
-                                        duplicate,
-                                        entry.path()
+                                }
+                            },
+                            OmTerrain::Nested(nested) => {
+                                debug!(
+                                    "Found Nested Mapgen '{:?}' in {:?}",
+                                    nested, path
+                                );
+
+                                let map_data_collection: MapDataCollection =
+                                    mapgen.try_into()?;
+
+                                for (coords, map_data) in
+                                    map_data_collection.maps
+                                {
+                                    let om_terrain = nested
+                                        .get(coords.y as usize)
+                                        .unwrap()
+                                        .get(coords.x as usize)
+                                        .unwrap()
+                                        .clone();
+
+                                    self.cdda_data.map_data.insert(
+                                        CDDAIdentifier(om_terrain),
+                                        map_data,
                                     );
+                                }
+                            },
+                        }
+                    } else if let Some(nested_mapgen) =
+                        mapgen.nested_mapgen_id.clone()
+                    {
+                        debug!(
+                            "Found Nested Mapgen Object '{}' in {:?}",
+                            nested_mapgen, path
+                        );
 
-                                    let map_data_collection: MapDataCollection =
-                                        mapgen.try_into()?;
-
-                                    for id in duplicate.iter() {
-                                        cdda_data.map_data.insert(
-                                            CDDAIdentifier(id.clone()),
-                                            map_data_collection
-                                                .maps
-                                                .get(&UVec2::ZERO)
-                                                .unwrap()
-                                                .clone(),
-                                        );
-                                    }
-                                },
-                                OmTerrain::Nested(nested) => {
-                                    debug!(
-                                        "Found Nested Mapgen '{:?}' in {:?}",
-                                        nested,
-                                        entry.path()
-                                    );
+                        let mut map_data_collection: MapDataCollection =
+                            mapgen.try_into()?;
 
-                                    let map_data_collection: MapDataCollection =
-                                        mapgen.try_into()?;
+                        self.cdda_data.map_data.insert(
+                            nested_mapgen.clone(),
+                            map_data_collection
+                                .maps
+                                .remove(&UVec2::ZERO)
+                                .unwrap(),
+                        );
+                    } else if let Some(update_mapgen) =
+                        mapgen.update_mapgen_id.clone()
+                    {
+                        debug!(
+                            "Found Update Mapgen Object '{:?}' in {:?}",
+                            update_mapgen, path
+                        );
 
-                                    for (coords, map_data) in
-                                        map_data_collection.maps
-                                    {
-                                        let om_terrain = nested
-                                            .get(coords.y as usize)
-                                            .unwrap()
-                                            .get(coords.x as usize)
-                                            .unwrap()
-                                            .clone();
-
-                                        cdda_data.map_data.insert(
-                                            CDDAIdentifier(om_terrain),
-                                            map_data,
-                                        );
-                                    }
-                                },
-                            }
-                        } else if let Some(nested_mapgen) =
-                            mapgen.nested_mapgen_id.clone()
-                        {
-                            debug!(
-                                "Found Nested Mapgen Object '{}' in {:?}",
-                                nested_mapgen,
-                                entry.path()
-                            );
+                        let mut map_data_collection: MapDataCollection =
+                            mapgen.try_into()?;
+
+                        self.cdda_data
+                            .update_mapgen_ids
+                            .insert(update_mapgen.clone());
+                        self.cdda_data.map_data.insert(
+                            update_mapgen.clone(),
+                            map_data_collection
+                                .maps
+                                .remove(&UVec2::ZERO)
+                                .unwrap(),
+                        );
+                    } else {
+                        self.cdda_data.diagnostics.report(
+                            ImportDiagnostic::warning(
+                                path,
+                                None,
+                                "The mapgen does not have an om_terrain, \
+                                 nested_mapgen_id or update_mapgen_id and is \
+                                 ignored",
+                            ),
+                        );
+                    }
+                },
+                CDDAJsonEntry::Harvest(harvest) => {
+                    debug!("Found Harvest {} in {:?}", harvest.id, path);
+                    self.cdda_data.harvests.insert(harvest.id.clone(), harvest);
+                },
+                CDDAJsonEntry::FieldType(field_type) => {
+                    debug!("Found FieldType {} in {:?}", field_type.id, path);
+                    self.cdda_data
+                        .field_types
+                        .insert(field_type.id.clone(), field_type);
+                },
+                CDDAJsonEntry::Npc(npc) => {
+                    debug!("Found Npc {} in {:?}", npc.id, path);
+                    self.cdda_data.npcs.insert(npc.id.clone(), npc);
+                },
+                CDDAJsonEntry::NpcClass(class) => {
+                    debug!("Found NpcClass {} in {:?}", class.id, path);
+                    self.cdda_data.npc_classes.insert(class.id.clone(), class);
+                },
+                CDDAJsonEntry::Trap(trap) => {
+                    debug!("Found Trap {} in {:?}", trap.id, path);
+                    self.cdda_data.traps.insert(trap.id.clone(), trap);
+                },
+                CDDAJsonEntry::RegionSettings(rs) => {
+                    debug!("Found Region setting {} in {:?}", rs.id, path);
+                    self.cdda_data.region_settings.insert(rs.id.clone(), rs);
+                },
+                CDDAJsonEntry::Palette(p) => {
+                    debug!("Found Palette {} in {:?}", p.id, path);
+                    self.cdda_data.palettes.insert(p.id.clone(), p.into());
+                },
+                CDDAJsonEntry::Terrain(terrain) => {
+                    for ident in terrain.id.iter() {
+                        debug!("Found Terrain entry {} in {:?}", &ident, path);
+
+                        let mut clone = terrain.clone();
+                        clone.id = MeabyVec::Single(ident.clone());
+
+                        insert_intermediate(
+                            &mut self.intermediate_terrains,
+                            ident.clone(),
+                            clone,
+                            &self.cdda_data.diagnostics,
+                            path,
+                        );
+                    }
+                },
+                CDDAJsonEntry::Furniture(furniture) => {
+                    for ident in furniture.id.iter() {
+                        debug!(
+                            "Found Furniture entry {} in {:?}",
+                            &ident, path
+                        );
 
-                            let mut map_data_collection: MapDataCollection =
-                                mapgen.try_into()?;
+                        let mut clone = furniture.clone();
+                        clone.id = MeabyVec::Single(ident.clone());
 
-                            cdda_data.map_data.insert(
-                                nested_mapgen.clone(),
-                                map_data_collection
-                                    .maps
-                                    .remove(&UVec2::ZERO)
-                                    .unwrap(),
-                            );
-                        } else if let Some(update_mapgen) =
-                            mapgen.update_mapgen_id.clone()
-                        {
-                            debug!(
-                                "Found Update Mapgen Object '{:?}' in {:?}",
-                                update_mapgen,
-                                entry.path()
-                            );
+                        insert_intermediate(
+                            &mut self.intermediate_furnitures,
+                            ident.clone(),
+                            clone,
+                            &self.cdda_data.diagnostics,
+                            path,
+                        );
+                    }
+                },
+                CDDAJsonEntry::ItemGroup(group) => {
+                    let new_group: CDDAItemGroup = group.into();
+                    debug!(
+                        "Found ItemGroup entry {} in {:?}",
+                        new_group.id, path
+                    );
+                    self.cdda_data
+                        .item_groups
+                        .insert(new_group.id.clone(), new_group);
+                },
+                CDDAJsonEntry::MonsterGroup(group) => {
+                    for ident in group.id.iter() {
+                        debug!(
+                            "Found MonsterGroup entry {} in {:?}",
+                            ident, path
+                        );
 
-                            let mut map_data_collection: MapDataCollection =
-                                mapgen.try_into()?;
+                        let mut clone = group.clone();
+                        clone.id = MeabyVec::Single(ident.clone());
 
-                            cdda_data.map_data.insert(
-                                update_mapgen.clone(),
-                                map_data_collection
-                                    .maps
-                                    .remove(&UVec2::ZERO)
-                                    .unwrap(),
-                            );
-                        }
-                    },
-                    CDDAJsonEntry::RegionSettings(rs) => {
+                        insert_intermediate(
+                            &mut self.intermediate_monster_groups,
+                            ident.clone(),
+                            clone,
+                            &self.cdda_data.diagnostics,
+                            path,
+                        );
+                    }
+                },
+                CDDAJsonEntry::OvermapLocation(location) => {
+                    for ident in location.id.iter() {
                         debug!(
-                            "Found Region setting {} in {:?}",
-                            rs.id,
-                            entry.path()
+                            "Found OvermapLocation entry {} in {:?}",
+                            &ident, path
                         );
-                        cdda_data.region_settings.insert(rs.id.clone(), rs);
-                    },
-                    CDDAJsonEntry::Palette(p) => {
-                        debug!("Found Palette {} in {:?}", p.id, entry.path());
-                        cdda_data.palettes.insert(p.id.clone(), p.into());
-                    },
-                    CDDAJsonEntry::Terrain(terrain) => {
-                        for ident in terrain.id.clone().into_vec() {
-                            debug!(
-                                "Found Terrain entry {} in {:?}",
-                                &ident,
-                                entry.path()
-                            );
 
-                            let mut clone = terrain.clone();
-                            clone.id = MeabyVec::Single(ident.clone());
+                        let mut clone = location.clone();
+                        clone.id = MeabyVec::Single(ident.clone());
 
-                            intermediate_terrains.insert(ident, clone);
-                        }
-                    },
-                    CDDAJsonEntry::Furniture(furniture) => {
-                        for ident in furniture.id.clone().into_vec() {
-                            debug!(
-                                "Found Furniture entry {} in {:?}",
-                                &ident,
-                                entry.path()
-                            );
+                        insert_intermediate(
+                            &mut self.intermediate_overmap_locations,
+                            ident.clone(),
+                            clone,
+                            &self.cdda_data.diagnostics,
+                            path,
+                        );
+                    }
+                },
+                CDDAJsonEntry::OvermapTerrain(terrain) => {
+                    for ident in terrain.id.iter() {
+                        debug!(
+                            "Found OvermapTerrain entry {} in {:?}",
+                            &ident, path
+                        );
 
-                            let mut clone = furniture.clone();
-                            clone.id = MeabyVec::Single(ident.clone());
+                        let mut clone = terrain.clone();
+                        clone.id = MeabyVec::Single(ident.clone());
 
-                            intermediate_furnitures.insert(ident, clone);
-                        }
-                    },
-                    CDDAJsonEntry::ItemGroup(group) => {
-                        let new_group: CDDAItemGroup = group.into();
+                        insert_intermediate(
+                            &mut self.intermediate_overmap_terrains,
+                            ident.clone(),
+                            clone,
+                            &self.cdda_data.diagnostics,
+                            path,
+                        );
+                    }
+                },
+                CDDAJsonEntry::OvermapSpecial(s) => {
+                    for ident in s.id.iter() {
                         debug!(
-                            "Found ItemGroup entry {} in {:?}",
-                            new_group.id,
-                            entry.path()
+                            "Found OvermapSpecial entry {} in {:?}",
+                            &ident, path
                         );
-                        cdda_data
-                            .item_groups
-                            .insert(new_group.id.clone(), new_group);
-                    },
-                    CDDAJsonEntry::MonsterGroup(group) => {
-                        for ident in group.id.clone().into_vec() {
-                            debug!(
-                                "Found MonsterGroup entry {} in {:?}",
-                                ident,
-                                entry.path()
-                            );
 
-                            let mut clone = group.clone();
-                            clone.id = MeabyVec::Single(ident.clone());
+                        let mut clone = s.clone();
+                        clone.id = MeabyVec::Single(ident.clone());
 
-                            intermediate_monster_groups.insert(ident, clone);
-                        }
-                    },
-                    CDDAJsonEntry::OvermapLocation(location) => {
-                        for ident in location.id.clone().into_vec() {
-                            debug!(
-                                "Found OvermapLocation entry {} in {:?}",
-                                &ident,
-                                entry.path()
-                            );
+                        insert_intermediate(
+                            &mut self.intermediate_overmap_specials,
+                            ident.clone(),
+                            clone,
+                            &self.cdda_data.diagnostics,
+                            path,
+                        );
+                    }
+                },
+                CDDAJsonEntry::Vehicle(v) => {
+                    for ident in v.id.iter() {
+                        debug!("Found Vehicle entry {} in {:?}", &ident, path);
+
+                        let mut clone = v.clone();
+                        clone.id = MeabyVec::Single(ident.clone());
+
+                        insert_intermediate(
+                            &mut self.intermediate_vehicles,
+                            ident.clone(),
+                            clone,
+                            &self.cdda_data.diagnostics,
+                            path,
+                        );
+                    }
+                },
+                CDDAJsonEntry::VehiclePart(vp) => {
+                    for ident in vp.id.iter() {
+                        debug!(
+                            "Found VehiclePart entry {} in {:?}",
+                            &ident, path
+                        );
 
-                            let mut clone = location.clone();
-                            clone.id = MeabyVec::Single(ident.clone());
+                        let mut clone = vp.clone();
+                        clone.id = MeabyVec::Single(ident.clone());
 
-                            intermediate_overmap_locations.insert(ident, clone);
-                        }
-                    },
-                    CDDAJsonEntry::OvermapTerrain(terrain) => {
-                        for ident in terrain.id.clone().into_vec() {
-                            debug!(
-                                "Found OvermapTerrain entry {} in {:?}",
-                                &ident,
-                                entry.path()
-                            );
+                        insert_intermediate(
+                            &mut self.intermediate_vehicle_parts,
+                            ident.clone(),
+                            clone,
+                            &self.cdda_data.diagnostics,
+                            path,
+                        );
+                    }
+                },
+                _ => {
+                    info!("Unused JSON entry in {:?}", path);
+                },
+            }
+        }
+        Ok(())
+    }
 
-                            let mut clone = terrain.clone();
-                            clone.id = MeabyVec::Single(ident.clone());
+    /// Amount of objects which are resolved when the collector is finished
+    fn intermediate_count(&self) -> usize {
+        self.intermediate_vehicles.len()
+            + self.intermediate_vehicle_parts.len()
+            + self.intermediate_terrains.len()
+            + self.intermediate_furnitures.len()
+            + self.intermediate_overmap_locations.len()
+            + self.intermediate_overmap_terrains.len()
+            + self.intermediate_monster_groups.len()
+    }
 
-                            intermediate_overmap_terrains.insert(ident, clone);
-                        }
-                    },
-                    CDDAJsonEntry::OvermapSpecial(s) => {
-                        for ident in s.id.clone().into_vec() {
-                            debug!(
-                                "Found OvermapSpecial entry {} in {:?}",
-                                &ident,
-                                entry.path()
-                            );
+    fn report_missing_copy_from(
+        &self,
+        json_type: &str,
+        id: &CDDAIdentifier,
+        copy_from: &CDDAIdentifier,
+    ) {
+        let Some(path) = self
+            .cdda_data
+            .sources
+            .get(json_type)
+            .and_then(|s| s.get(id))
+        else {
+            return;
+        };
+
+        self.cdda_data.diagnostics.report(ImportDiagnostic::warning(
+            path,
+            Some(id.clone()),
+            format!("Copies from {} which does not exist", copy_from),
+        ));
+    }
 
-                            let mut clone = s.clone();
-                            clone.id = MeabyVec::Single(ident.clone());
+    /// Reports the palettes which are used by the loaded mapgens but do not
+    /// exist or include each other
+    fn report_palette_references(&self) {
+        let Some(sources) = self.cdda_data.sources.get("mapgen") else {
+            return;
+        };
 
-                            intermediate_overmap_specials.insert(ident, clone);
-                        }
-                    },
-                    CDDAJsonEntry::Vehicle(v) => {
-                        for ident in v.id.clone().into_vec() {
-                            debug!(
-                                "Found Vehicle entry {} in {:?}",
-                                &ident,
-                                entry.path()
-                            );
+        for (id, map_data) in self.cdda_data.map_data.iter() {
+            let Some(path) = sources.get(id) else {
+                continue;
+            };
 
-                            let mut clone = v.clone();
-                            clone.id = MeabyVec::Single(ident.clone());
+            let parameters = map_data
+                .parameters
+                .iter()
+                .map(|(id, p)| (id.clone(), p.clone()))
+                .collect();
 
-                            intermediate_vehicles.insert(ident, clone);
-                        }
-                    },
-                    CDDAJsonEntry::VehiclePart(vp) => {
-                        for ident in vp.id.clone().into_vec() {
-                            debug!(
-                                "Found VehiclePart entry {} in {:?}",
-                                &ident,
-                                entry.path()
-                            );
+            let problems = check_palette_references(
+                &map_data.palettes,
+                &parameters,
+                &self.cdda_data.palettes,
+            );
 
-                            let mut clone = vp.clone();
-                            clone.id = MeabyVec::Single(ident.clone());
+            for problem in problems {
+                self.cdda_data.diagnostics.report(ImportDiagnostic {
+                    id: Some(id.clone()),
+                    ..ImportDiagnostic::error(path, problem.to_string())
+                });
+            }
+        }
+    }
 
-                            intermediate_vehicle_parts.insert(ident, clone);
+    fn finish(self) -> DeserializedCDDAJsonData {
+        self.finish_with_progress(|_| {})
+    }
+
+    /// Resolves the objects which copy from other objects. The `on_progress`
+    /// is called with the amount of resolved objects after every object.
+    fn finish_with_progress(
+        mut self,
+        mut on_progress: impl FnMut(usize),
+    ) -> DeserializedCDDAJsonData {
+        let mut flattened = 0;
+
+        macro_rules! flatten {
+            ($json_type:literal, $intermediate:ident, $field:ident) => {
+                for (id, intermediate) in self.$intermediate.iter() {
+                    if let Some(copy_from) = intermediate.copy_from() {
+                        if !self.$intermediate.contains_key(copy_from) {
+                            self.report_missing_copy_from(
+                                $json_type, id, copy_from,
+                            );
                         }
-                    },
-                    _ => {
-                        info!("Unused JSON entry in {:?}", entry.path());
-                    },
+                    }
+
+                    self.cdda_data.$field.insert(
+                        id.clone(),
+                        intermediate.calculate_copy(&self.$intermediate).into(),
+                    );
+
+                    flattened += 1;
+                    on_progress(flattened);
                 }
-            }
+            };
         }
 
-        for (id, intermediate_vehicle) in intermediate_vehicles.iter() {
-            cdda_data.vehicles.insert(
-                id.clone(),
-                intermediate_vehicle
-                    .calculate_copy(&intermediate_vehicles)
-                    .into(),
-            );
+        flatten!("vehicle", intermediate_vehicles, vehicles);
+        flatten!("vehicle_part", intermediate_vehicle_parts, vehicle_parts);
+        flatten!("terrain", intermediate_terrains, terrain);
+        flatten!("furniture", intermediate_furnitures, furniture);
+        flatten!(
+            "overmap_location",
+            intermediate_overmap_locations,
+            overmap_locations
+        );
+        flatten!(
+            "overmap_terrain",
+            intermediate_overmap_terrains,
+            overmap_terrains
+        );
+        flatten!("monstergroup", intermediate_monster_groups, monster_groups);
+
+        self.report_palette_references();
+        self.install_lazy_sections();
+
+        self.cdda_data
+    }
+
+    /// Moves the collected entries of the lazy sections into their sections
+    fn install_lazy_sections(&mut self) {
+        macro_rules! lazy {
+            ($json_type:literal, $field:ident) => {
+                if let Some(raw) = self.lazy_entries.remove($json_type) {
+                    self.cdda_data.$field = LazySection::new(
+                        $json_type,
+                        raw,
+                        self.cdda_data.diagnostics.clone(),
+                        |raw, diagnostics| {
+                            load_lazy_section(raw, diagnostics)
+                                .$field
+                                .into_map()
+                        },
+                    );
+                }
+            };
         }
 
-        for (id, intermediate_vehicle_part) in intermediate_vehicle_parts.iter()
-        {
-            cdda_data.vehicle_parts.insert(
-                id.clone(),
-                intermediate_vehicle_part
-                    .calculate_copy(&intermediate_vehicle_parts)
-                    .into(),
-            );
+        lazy!("item_group", item_groups);
+        lazy!("monstergroup", monster_groups);
+        lazy!("vehicle", vehicles);
+        lazy!("npc", npcs);
+        lazy!("npc_class", npc_classes);
+    }
+
+    /// Restores the data which was stored in the cache. The objects in the
+    /// cache were already resolved, so nothing has to be flattened again.
+    fn from_cache(
+        cached: CachedCDDAJsonData,
+    ) -> Result<DeserializedCDDAJsonData, Error> {
+        let mut collector = Self::default();
+
+        for entry in cached.entries {
+            let des = CDDAJsonEntry::deserialize(&entry.value)?;
+            collector.add_entries(&entry.path, vec![des])?;
         }
 
-        for (id, intermediate_terrain) in intermediate_terrains.iter() {
-            cdda_data.terrain.insert(
-                id.clone(),
-                intermediate_terrain
-                    .calculate_copy(&intermediate_terrains)
-                    .into(),
-            );
+        for (json_type, raw) in cached.lazy_entries {
+            let Some(json_type) =
+                LAZY_JSON_TYPES.iter().find(|t| **t == json_type)
+            else {
+                continue;
+            };
+
+            collector.lazy_entries.insert(json_type, raw);
         }
 
-        for (id, intermediate_furniture) in intermediate_furnitures.iter() {
-            cdda_data.furniture.insert(
-                id.clone(),
-                intermediate_furniture
-                    .calculate_copy(&intermediate_furnitures)
-                    .into(),
-            );
+        // Problems found while the entries are added again were already
+        // reported when the cache was written
+        let diagnostics = Diagnostics::default();
+        for diagnostic in cached.diagnostics {
+            diagnostics.report(diagnostic);
         }
+        collector.cdda_data.diagnostics = diagnostics;
+
+        collector.install_lazy_sections();
+
+        let mut cdda_data = collector.cdda_data;
+        cdda_data.region_settings = cached.region_settings;
+        cdda_data.terrain = cached.terrain;
+        cdda_data.furniture = cached.furniture;
+        cdda_data.overmap_locations = cached.overmap_locations;
+        cdda_data.overmap_terrains = cached.overmap_terrains;
+        cdda_data.overmap_specials = cached.overmap_specials;
+        cdda_data.vehicle_parts = cached.vehicle_parts;
+        cdda_data.monsters = cached.monsters;
+        cdda_data.harvests = cached.harvests;
+        cdda_data.field_types = cached.field_types;
+        cdda_data.traps = cached.traps;
+        cdda_data.update_mapgen_ids = cached.update_mapgen_ids;
+        cdda_data.sources = cached.sources;
+        cdda_data.copied_from = cached.copied_from;
+        cdda_data.mods = cached.mods;
 
-        for (id, intermediate_overmap_location) in
-            intermediate_overmap_locations.iter()
-        {
-            cdda_data.overmap_locations.insert(
-                id.clone(),
-                intermediate_overmap_location
-                    .calculate_copy(&intermediate_overmap_locations)
-                    .into(),
-            );
+        Ok(cdda_data)
+    }
+}
+/// Amount of files after which the loading progress is reported again
+const PROGRESS_FILE_INTERVAL: usize = 25;
+
+/// Amount of flattened objects after which the loading progress is reported
+/// again
+const PROGRESS_OBJECT_INTERVAL: usize = 500;
+
+/// Share of the total progress which is spent parsing the json files, the rest
+/// is spent flattening the objects which copy from other objects
+const PARSING_PROGRESS_SHARE: f32 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CDDADataLoadPhase {
+    /// Searching the json files of the core data and the mods
+    Discovering,
+    /// Reading and parsing the discovered json files
+    Parsing,
+    /// Resolving the objects which copy from other objects
+    Flattening,
+    Finished,
+}
+
+/// Sent while the CDDA json data of a job is loaded
+#[derive(Debug, Clone, Serialize)]
+pub struct CDDADataLoadProgressEvent {
+    pub job: JobId,
+    pub phase: CDDADataLoadPhase,
+    pub files_discovered: usize,
+    pub files_parsed: usize,
+    pub objects_flattened: usize,
+    pub objects_total: usize,
+}
+
+impl EventPayload for CDDADataLoadProgressEvent {
+    const EVENT: EditorEvent = EditorEvent::CDDADataLoadProgress;
+}
+
+pub struct CDDADataLoader {
+    pub json_path: PathBuf,
+    /// Mods which are loaded on top of the core data, in load order
+    pub mods: Vec<CDDAMod>,
+    /// The file the used json entries are cached in. The cache is only read if
+    /// none of the json files changed since it was written.
+    pub cache_path: Option<PathBuf>,
+    pub cdda_version: Option<String>,
+    pub job: Option<JobHandle>,
+}
+
+impl CDDADataLoader {
+    fn check_canceled(&self) -> Result<(), Error> {
+        match self.job.as_ref().is_some_and(|j| j.is_canceled()) {
+            true => Err(anyhow!("Loading the CDDA data was canceled")),
+            false => Ok(()),
         }
+    }
 
-        for (id, intermediate_overmap_terrain) in
-            intermediate_overmap_terrains.iter()
-        {
-            cdda_data.overmap_terrains.insert(
-                id.clone(),
-                intermediate_overmap_terrain
-                    .calculate_copy(&intermediate_overmap_terrains)
-                    .into(),
-            );
+    /// Sends the `progress` to the frontend and updates the job with it
+    fn report(&self, progress: &CDDADataLoadProgressEvent) {
+        let Some(job) = &self.job else {
+            return;
+        };
+
+        let (fraction, message) = match progress.phase {
+            CDDADataLoadPhase::Discovering => {
+                (None, format!("Found {} files", progress.files_discovered))
+            },
+            CDDADataLoadPhase::Parsing => (
+                Some(
+                    progress.files_parsed as f32
+                        / progress.files_discovered.max(1) as f32
+                        * PARSING_PROGRESS_SHARE,
+                ),
+                format!(
+                    "Read {} of {} files",
+                    progress.files_parsed, progress.files_discovered
+                ),
+            ),
+            CDDADataLoadPhase::Flattening => (
+                Some(
+                    PARSING_PROGRESS_SHARE
+                        + progress.objects_flattened as f32
+                            / progress.objects_total.max(1) as f32
+                            * (1. - PARSING_PROGRESS_SHARE),
+                ),
+                format!(
+                    "Resolved {} of {} objects",
+                    progress.objects_flattened, progress.objects_total
+                ),
+            ),
+            CDDADataLoadPhase::Finished => (Some(1.), "Finished".to_string()),
+        };
+
+        if let Some(fraction) = fraction {
+            job.set_progress(fraction);
         }
+        job.set_message(message);
+        job.emit_event(progress.clone());
+    }
+
+    /// Adds the paths of every json file in the directory at `path` to the
+    /// `files`
+    async fn discover_files(
+        &self,
+        path: &Path,
+        files: &mut Vec<PathBuf>,
+        progress: &mut CDDADataLoadProgressEvent,
+    ) -> Result<(), Error> {
+        let mut walkdir = WalkDir::new(path);
 
-        for (id, intermediate_monster_group) in
-            intermediate_monster_groups.iter()
+        while let Some(entry) = walkdir.next().await {
+            let entry = entry?;
+            self.check_canceled()?;
+
+            let path = entry.path();
+            let extension = match path.extension() {
+                None => {
+                    info!(
+                        "Skipping entry {:?} because it does not have an extension",
+                        entry.path()
+                    );
+                    continue;
+                },
+                Some(e) => e,
+            };
+
+            if extension != "json" {
+                info!(
+                    "Skipping {:?} because it is not a json file",
+                    entry.path()
+                );
+                continue;
+            }
+
+            files.push(path);
+
+            progress.files_discovered += 1;
+            if progress.files_discovered % PROGRESS_FILE_INTERVAL == 0 {
+                self.report(progress);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The key the cache has to be written with to be used for the `files`
+    fn cache_key(&self, files: &[PathBuf]) -> Option<CDDADataCacheKey> {
+        self.cache_path.as_ref()?;
+
+        let mods = self.mods.iter().map(|m| m.info.id.clone()).collect();
+
+        match CDDADataCacheKey::new(self.cdda_version.clone(), mods, files) {
+            Ok(key) => Some(key),
+            Err(e) => {
+                warn!("Not using the CDDA data cache, error: {}", e);
+                None
+            },
+        }
+    }
+
+    /// Reads and parses the json `files` and adds their entries to the
+    /// `collector`
+    fn add_json_files(
+        &self,
+        collector: &mut CDDAJsonCollector,
+        files: Vec<PathBuf>,
+        progress: &mut CDDADataLoadProgressEvent,
+    ) -> Result<(), Error> {
+        for path in files {
+            self.check_canceled()?;
+
+            info!("Reading and parsing json file at {:?}", path);
+
+            if let Err(e) = read_json_values(&path)
+                .and_then(|values| collector.add_values(&path, values))
+            {
+                error!("Failed to deserialize {:?}, error: {}", path, e);
+                collector
+                    .cdda_data
+                    .diagnostics
+                    .report(ImportDiagnostic::from_file_error(&path, &e));
+            }
+
+            progress.files_parsed += 1;
+            if progress.files_parsed % PROGRESS_FILE_INTERVAL == 0 {
+                self.report(progress);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Load<DeserializedCDDAJsonData> for CDDADataLoader {
+    async fn load(&mut self) -> Result<DeserializedCDDAJsonData, Error> {
+        let mut progress = CDDADataLoadProgressEvent {
+            job: self.job.as_ref().map(|j| j.id()).unwrap_or_default(),
+            phase: CDDADataLoadPhase::Discovering,
+            files_discovered: 0,
+            files_parsed: 0,
+            objects_flattened: 0,
+            objects_total: 0,
+        };
+        self.report(&progress);
+
+        let mut files = vec![];
+        self.discover_files(&self.json_path, &mut files, &mut progress)
+            .await?;
+
+        // Entries of later mods replace the entries of the core data and the
+        // mods which were loaded before them, so their files are read after
+        // the files of the core data
+        for cdda_mod in self.mods.iter() {
+            let id = &cdda_mod.info.id;
+            info!("Loading mod {} from {:?}", id, cdda_mod.json_path);
+
+            self.discover_files(&cdda_mod.json_path, &mut files, &mut progress)
+                .await?;
+        }
+
+        progress.phase = CDDADataLoadPhase::Parsing;
+        self.report(&progress);
+
+        let cache_key = self.cache_key(&files);
+        let cached = match (&self.cache_path, &cache_key) {
+            (Some(cache_path), Some(key)) => {
+                read_cdda_data_cache(cache_path, key)
+            },
+            _ => None,
+        };
+
+        if let Some(cached) = cached {
+            info!("Loading the CDDA data from the cache");
+            let cdda_data = CDDAJsonCollector::from_cache(cached)?;
+
+            progress.phase = CDDADataLoadPhase::Finished;
+            progress.files_parsed = progress.files_discovered;
+            self.report(&progress);
+
+            return Ok(cdda_data);
+        }
+
+        let mut collector = CDDAJsonCollector::default();
+        if cache_key.is_some() {
+            collector.cache_entries = Some(vec![]);
+        }
+
+        self.add_json_files(&mut collector, files, &mut progress)?;
+        let cache_entries = collector.cache_entries.take();
+
+        self.check_canceled()?;
+
+        progress.phase = CDDADataLoadPhase::Flattening;
+        progress.objects_total = collector.intermediate_count();
+        self.report(&progress);
+
+        let mut cdda_data = collector.finish_with_progress(|flattened| {
+            progress.objects_flattened = flattened;
+            if flattened % PROGRESS_OBJECT_INTERVAL == 0 {
+                self.report(&progress);
+            }
+        });
+        cdda_data.mods = self.mods.iter().map(|m| m.info.id.clone()).collect();
+
+        self.check_canceled()?;
+
+        if let (Some(cache_path), Some(key), Some(entries)) =
+            (&self.cache_path, cache_key, cache_entries)
         {
-            cdda_data.monster_groups.insert(
-                id.clone(),
-                intermediate_monster_group
-                    .calculate_copy(&intermediate_monster_groups)
-                    .into(),
-            );
+            let cached = cdda_data.to_cache(entries);
+            if let Err(e) = write_cdda_data_cache(cache_path, key, cached) {
+                warn!("Failed to write the CDDA data cache, error: {}", e);
+            }
         }
 
+        progress.phase = CDDADataLoadPhase::Finished;
+        self.report(&progress);
+
         Ok(cdda_data)
     }
 }
@@ -671,22 +1892,205 @@ mod tests {
         tokio_test::block_on(async {
             let mut data_loader = CDDADataLoader {
                 json_path: PathBuf::from(CDDA_TEST_JSON_PATH),
+                mods: vec![],
+                cache_path: None,
+                cdda_version: None,
+                job: None,
             };
 
             data_loader.load().await.expect("Loading to not fail");
         })
     }
+
+    #[test]
+    fn test_report_unknown_mapgen_fields() {
+        let path = PathBuf::from("test.json");
+        let values = serde_json::json!([{
+            "type": "mapgen",
+            "method": "json",
+            "om_terrain": "test_mapgen",
+            "object": {
+                "//": "A comment",
+                "fill_ter": "t_grass",
+                "place_monster": [],
+                "place_unknown": []
+            }
+        }]);
+
+        let mut collector = CDDAJsonCollector::default();
+        collector
+            .add_values(&path, serde_json::from_value(values).unwrap())
+            .unwrap();
+
+        let diagnostics = collector.cdda_data.diagnostics.all();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].id, Some("test_mapgen".into()));
+        assert!(diagnostics[0].message.contains("place_unknown"));
+    }
+
+    #[test]
+    fn test_reload_resolves_copying_entries() {
+        let directory = std::env::temp_dir().join("test_reload_copy_from");
+        fs::create_dir_all(&directory).unwrap();
+
+        let base_path = directory.join("base.json");
+        let copy_path = directory.join("copy.json");
+
+        let write_base = |symbol: &str| {
+            let values = serde_json::json!([
+                {
+                    "type": "terrain",
+                    "id": "t_base",
+                    "symbol": symbol,
+                    "flags": []
+                }
+            ]);
+            fs::write(&base_path, values.to_string()).unwrap();
+        };
+
+        write_base(".");
+        let copy = serde_json::json!([
+            {
+                "type": "terrain",
+                "id": "t_copy",
+                "copy-from": "t_base",
+                "flags": []
+            }
+        ]);
+        fs::write(&copy_path, copy.to_string()).unwrap();
+
+        let mut collector = CDDAJsonCollector::default();
+        for path in [&base_path, &copy_path] {
+            collector
+                .add_values(path, read_json_values(path).unwrap())
+                .unwrap();
+        }
+        let mut cdda_data = collector.finish();
+
+        let copy = cdda_data.terrain.get(&"t_copy".into()).unwrap();
+        assert_eq!(copy.symbol, Some('.'));
+
+        write_base("#");
+        let changed = cdda_data.reload_json_file(&base_path).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let copy = cdda_data.terrain.get(&"t_copy".into()).unwrap();
+        assert_eq!(copy.symbol, Some('#'));
+        assert!(changed.contains(&ChangedEntry {
+            json_type: "terrain".to_string(),
+            id: "t_copy".into(),
+        }));
+
+        let sources = cdda_data.sources.get("terrain").unwrap();
+        assert_eq!(sources.get(&"t_copy".into()), Some(&copy_path));
+    }
+
+    #[test]
+    fn test_cdda_data_cache_round_trip() {
+        let path = PathBuf::from("test.json");
+        let values = serde_json::json!([
+            { "type": "terrain", "id": "t_test", "symbol": ".", "flags": [] },
+            {
+                "type": "terrain",
+                "id": "t_test_copy",
+                "copy-from": "t_test",
+                "flags": ["TRANSPARENT"]
+            },
+            { "type": "furniture", "id": "f_test", "flags": [] },
+            {
+                "type": "palette",
+                "id": "test_palette",
+                "terrain": { ".": "t_test" }
+            },
+            {
+                "type": "mapgen",
+                "method": "json",
+                "om_terrain": "test_mapgen",
+                "object": {
+                    "fill_ter": "t_test",
+                    "rows": ["."],
+                    "palettes": ["test_palette"]
+                }
+            },
+            { "type": "item_group", "id": "test_items", "items": ["rock"] }
+        ]);
+
+        let mut collector = CDDAJsonCollector::default();
+        collector.cache_entries = Some(vec![]);
+        collector
+            .add_values(&path, serde_json::from_value(values).unwrap())
+            .unwrap();
+
+        let entries = collector.cache_entries.take().unwrap();
+        let cdda_data = collector.finish();
+
+        let cache_path = std::env::temp_dir().join("test_cdda_data.msgpack");
+        let key = CDDADataCacheKey::new(None, vec![], &[]).unwrap();
+        let cached = cdda_data.to_cache(entries);
+        write_cdda_data_cache(&cache_path, key.clone(), cached).unwrap();
+
+        let cached = read_cdda_data_cache(&cache_path, &key).unwrap();
+        fs::remove_file(&cache_path).unwrap();
+
+        let restored = CDDAJsonCollector::from_cache(cached).unwrap();
+
+        let copy = restored.terrain.get(&"t_test_copy".into()).unwrap();
+        assert_eq!(copy.symbol, Some('.'));
+        assert_eq!(copy.flags, vec!["TRANSPARENT".to_string()]);
+        assert!(restored.furniture.contains_key(&"f_test".into()));
+        assert!(restored.palettes.contains_key(&"test_palette".into()));
+        assert!(restored.map_data.contains_key(&"test_mapgen".into()));
+        assert!(restored.item_groups.contains_key(&"test_items".into()));
+
+        let sources = restored.sources.get("terrain").unwrap();
+        assert_eq!(sources.get(&"t_test".into()), Some(&path));
+    }
 }
 
+/// Loads the CDDA json data together with the `mods`, which have to be in load
+/// order. If a `job` is passed, the loading reports its progress to it and can
+/// be canceled through it. If a `cache_path` is passed, the data is loaded from
+/// the cache at the path when the json files did not change since the last
+/// load.
 pub async fn load_cdda_json_data(
     cdda_path: impl Into<PathBuf>,
     json_data_path: impl Into<PathBuf>,
+    mods: Vec<CDDAMod>,
+    cache_path: Option<PathBuf>,
+    job: Option<JobHandle>,
 ) -> Result<DeserializedCDDAJsonData, anyhow::Error> {
+    let cdda_path = cdda_path.into();
+
     let mut data_loader = CDDADataLoader {
-        json_path: cdda_path.into().join(json_data_path.into()),
+        json_path: cdda_path.join(json_data_path.into()),
+        mods,
+        cache_path,
+        cdda_version: read_cdda_version(&cdda_path),
+        job: job.clone(),
     };
 
-    data_loader.load().await
+    let result = data_loader.load().await;
+
+    if let Some(job) = job {
+        job.finish(&result);
+    }
+
+    result
+}
+
+/// Loads the CDDA json data from the `json` of a single file which is not
+/// read from the disk, like the data that is embedded into the editor. The
+/// `path` is only used as the source of the entries.
+pub fn load_embedded_cdda_json_data(
+    path: &Path,
+    json: &[u8],
+) -> Result<DeserializedCDDAJsonData, Error> {
+    let values: Vec<serde_json::Value> = serde_json::from_slice(json)?;
+
+    let mut collector = CDDAJsonCollector::default();
+    collector.add_values(path, values)?;
+
+    Ok(collector.finish())
 }
 
 pub fn get_saved_editor_data() -> Result<EditorData, Error> {
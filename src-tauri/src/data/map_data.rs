@@ -4,15 +4,26 @@ use crate::data::map_data::IntoMapDataCollectionError::MissingNestedOmTerrain;
 use crate::data::palettes::Parameter;
 use crate::features::map::map_properties::ComputersProperty;
 use crate::features::map::map_properties::CorpsesProperty;
+use crate::features::map::map_properties::NpcsProperty;
+use crate::features::map::map_properties::{
+    GraffitiProperty, LiquidsProperty, LootProperty, RubbleProperty,
+    SealedItemProperty,
+};
 use crate::features::map::map_properties::ToiletsProperty;
 use crate::features::map::map_properties::TrapsProperty;
-use crate::features::map::map_properties::VehiclesProperty;
+use crate::features::map::map_properties::{
+    AppliancesProperty, VehiclesProperty,
+};
 use crate::features::map::map_properties::{
     FieldsProperty, FurnitureProperty, MonstersProperty, NestedProperty,
     SignsProperty, TerrainProperty,
 };
 use crate::features::map::map_properties::{GaspumpsProperty, ItemsProperty};
 use crate::features::map::place::{PlaceFurniture, PlaceNested, PlaceTerrain};
+use crate::features::map::set::{
+    PlaceableSetType, RemovableSetType, Set, SetLine, SetOperation, SetPoint,
+    SetSquare,
+};
 use crate::features::map::SetTile;
 use crate::features::map::DEFAULT_MAP_DATA_SIZE;
 use crate::features::map::{
@@ -20,16 +31,20 @@ use crate::features::map::{
 };
 use crate::features::program_data::{MapCoordinates, MapDataCollection};
 use cdda_lib::types::{
-    CDDAIdentifier, CDDAString, DistributionInner, MapGenValue, MeabyVec,
-    MeabyWeighted, NumberOrRange, ParameterIdentifier, Weighted,
+    CDDAIdentifier, CDDAString, DistributionInner, InvertedRangeError,
+    MapGenValue, MeabyVec, MeabyWeighted, NumberOrRange, ParameterIdentifier,
+    Weighted,
 };
 use cdda_lib::{DEFAULT_MAP_HEIGHT, DEFAULT_MAP_WIDTH};
 use glam::{IVec2, UVec2};
 use indexmap::IndexMap;
+use log::warn;
 use paste::paste;
+use rand::{Rng, RngCore};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 use std::sync::Arc;
 use thiserror::Error;
 
@@ -43,18 +58,94 @@ pub enum OmTerrain {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SetIntermediate {
-    line: Option<CDDAIdentifier>,
-    point: Option<CDDAIdentifier>,
-    square: Option<CDDAIdentifier>,
+    line: Option<String>,
+    point: Option<String>,
+    square: Option<String>,
     id: Option<CDDAIdentifier>,
-    x: Option<NumberOrRange<u32>>,
-    y: Option<NumberOrRange<u32>>,
+    x: Option<NumberOrRange<i32>>,
+    y: Option<NumberOrRange<i32>>,
     z: Option<i32>,
-    x2: Option<NumberOrRange<u32>>,
-    y2: Option<NumberOrRange<u32>>,
+    x2: Option<NumberOrRange<i32>>,
+    y2: Option<NumberOrRange<i32>>,
     amount: Option<NumberOrRange<u32>>,
     chance: Option<u32>,
-    repeat: Option<(u32, u32)>,
+    repeat: Option<NumberOrRange<u32>>,
+}
+
+impl SetIntermediate {
+    fn operation(&self, ty: &str) -> Option<SetOperation> {
+        if let Ok(ty) = PlaceableSetType::from_str(ty) {
+            return Some(SetOperation::Place {
+                id: self.id.clone()?,
+                ty,
+            });
+        }
+
+        if let Ok(ty) = RemovableSetType::from_str(ty) {
+            return Some(SetOperation::Remove { ty });
+        }
+
+        match ty {
+            "radiation" => Some(SetOperation::Radiation {
+                amount: self.amount.clone()?,
+            }),
+            "variable" => Some(SetOperation::Variable {
+                id: self.id.clone()?,
+            }),
+            "bash" => Some(SetOperation::Bash {}),
+            "burn" => Some(SetOperation::Burn {}),
+            _ => None,
+        }
+    }
+
+    /// Converts the set to the local coordinates of the map at the `offset`.
+    /// Returns [`None`] if the set is missing a field its operation needs.
+    pub fn to_set(&self, offset: IVec2) -> Option<Arc<dyn Set>> {
+        let (x, y) = (self.x.clone()? - offset.x, self.y.clone()? - offset.y);
+        let z = self.z.unwrap_or(0);
+        let chance = self.chance.unwrap_or(1);
+        let repeat = self.repeat.clone().unwrap_or(NumberOrRange::Number(1));
+
+        if let Some(ty) = &self.point {
+            return Some(Arc::new(SetPoint {
+                x,
+                y,
+                z,
+                chance,
+                repeat,
+                operation: self.operation(ty)?,
+            }));
+        }
+
+        let (x2, y2) =
+            (self.x2.clone()? - offset.x, self.y2.clone()? - offset.y);
+
+        if let Some(ty) = &self.line {
+            return Some(Arc::new(SetLine {
+                from_x: x,
+                from_y: y,
+                to_x: x2,
+                to_y: y2,
+                z,
+                chance,
+                repeat,
+                operation: self.operation(ty)?,
+            }));
+        }
+
+        let ty = self.square.as_ref()?;
+
+        Some(Arc::new(SetSquare {
+            top_left_x: x,
+            top_left_y: y,
+            bottom_right_x: x2,
+            bottom_right_y: y2,
+            z,
+            chance,
+            repeat,
+            operation: self.operation(ty)?,
+        }))
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -94,6 +185,8 @@ pub struct MapGenMonsters {
     pub id: MapGenMonsterType,
     pub chance: Option<NumberOrRange<u32>>,
     pub pack_size: Option<NumberOrRange<u32>>,
+    /// Scales how many monsters of a group are spawned
+    pub density: Option<f32>,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Deserialize, Serialize)]
@@ -193,40 +286,86 @@ impl<'de> Deserialize<'de> for OmTerrainMatch {
     }
 }
 
+/// The suffixes of rotated overmap terrains
+const ROTATION_SUFFIXES: [&str; 4] = ["_north", "_east", "_south", "_west"];
+
+/// The suffixes of linear overmap terrains like roads together with the
+/// linear subtype they belong to
+const LINEAR_SUFFIXES: [(&str, &str); 16] = [
+    ("_isolated", "isolated"),
+    ("_end_north", "end"),
+    ("_end_east", "end"),
+    ("_end_south", "end"),
+    ("_end_west", "end"),
+    ("_ns", "straight"),
+    ("_ew", "straight"),
+    ("_ne", "curved"),
+    ("_es", "curved"),
+    ("_sw", "curved"),
+    ("_wn", "curved"),
+    ("_nes", "tee"),
+    ("_new", "tee"),
+    ("_nsw", "tee"),
+    ("_esw", "tee"),
+    ("_nesw", "four_way"),
+];
+
+/// Splits the id of a linear overmap terrain into its type and linear
+/// subtype, `road_ne` is split into `road` and `curved`
+fn split_linear(id: &str) -> Option<(&str, &str)> {
+    LINEAR_SUFFIXES.iter().find_map(|(suffix, subtype)| {
+        id.strip_suffix(suffix).map(|base| (base, *subtype))
+    })
+}
+
+/// The id without the rotation suffix, `house_01_north` becomes `house_01`
+fn strip_rotation(id: &str) -> &str {
+    ROTATION_SUFFIXES
+        .iter()
+        .find_map(|suffix| id.strip_suffix(suffix))
+        .unwrap_or(id)
+}
+
 impl OmTerrainMatch {
+    /// Whether the overmap terrain `ident` matches, the match types follow
+    /// https://github.com/CleverRaven/Cataclysm-DDA/blob/master/doc/JSON/OVERMAP.md#overmap-terrain-matching
+    ///
+    /// Linear terrains are recognized by their suffixes since the overmap
+    /// terrain definitions are not available here
     pub fn matches_identifier(&self, ident: &CDDAIdentifier) -> bool {
-        // https://github.com/CleverRaven/Cataclysm-DDA/blob/master/doc/JSON/JSON_INFO.md#Starting-locations
+        let om_terrain = self.om_terrain.0.as_str();
+        let ident = ident.0.as_str();
+
         match self.om_terrain_match_type {
-            OmTerrainMatchType::Exact => &self.om_terrain == ident,
+            OmTerrainMatchType::Exact => om_terrain == ident,
             OmTerrainMatchType::Type => {
-                // Strip any suffixes like rotation or linear directions
-                let base_type = ident.0.split('_').next().unwrap_or("");
-                let match_type =
-                    self.om_terrain.0.split('_').next().unwrap_or("");
-                base_type == match_type
+                // Strip the suffixes of rotations and linear terrains
+                let base_type = split_linear(ident)
+                    .map(|(base, _)| base)
+                    .unwrap_or_else(|| strip_rotation(ident));
+
+                base_type == om_terrain
             },
             OmTerrainMatchType::Subtype => {
-                // Match base type and linear type suffix
-                let parts: Vec<&str> = ident.0.split('_').collect();
-                let match_parts: Vec<&str> =
-                    self.om_terrain.0.split('_').collect();
-
-                if parts.len() >= 2 && match_parts.len() >= 2 {
-                    parts[0] == match_parts[0] && parts[1] == match_parts[1]
-                } else {
-                    false
+                // Linear terrains match by their type and linear subtype, so
+                // `road_curved` matches `road_ne` and `road_sw`
+                match split_linear(ident) {
+                    Some((base, subtype)) => om_terrain
+                        .strip_prefix(base)
+                        .and_then(|s| s.strip_prefix('_'))
+                        .is_some_and(|s| s == subtype),
+                    None => strip_rotation(ident) == om_terrain,
                 }
             },
             OmTerrainMatchType::Prefix => {
                 // Must be complete prefix with underscore delimiter
-                ident.0.starts_with(&self.om_terrain.0)
-                    && (ident.0.len() == self.om_terrain.0.len()
-                        || ident.0.chars().nth(self.om_terrain.0.len())
-                            == Some('_'))
+                ident.strip_prefix(om_terrain).is_some_and(|rest| {
+                    rest.is_empty() || rest.starts_with('_')
+                })
             },
             OmTerrainMatchType::Contains => {
                 // Simple substring match
-                ident.0.contains(&self.om_terrain.0)
+                ident.contains(om_terrain)
             },
         }
     }
@@ -237,38 +376,38 @@ impl OmTerrainMatch {
 pub enum MapGenNestedIntermediate {
     Chunks {
         chunks: MeabyVec<MeabyWeighted<MapGenValue>>,
+        else_chunks: Option<MeabyVec<MeabyWeighted<MapGenValue>>>,
         neighbors: Option<HashMap<NeighborDirection, MeabyVec<OmTerrainMatch>>>,
+        joins: Option<HashMap<NeighborDirection, MeabyVec<CDDAIdentifier>>>,
     },
     ElseChunks {
         else_chunks: MeabyVec<MeabyWeighted<MapGenValue>>,
         neighbors: Option<HashMap<NeighborDirection, MeabyVec<OmTerrainMatch>>>,
+        joins: Option<HashMap<NeighborDirection, MeabyVec<CDDAIdentifier>>>,
     },
 }
 
 impl Into<MapGenNested> for MapGenNestedIntermediate {
     fn into(self) -> MapGenNested {
-        let (transformed_chunks, neighbors, is_else) = match self {
-            MapGenNestedIntermediate::Chunks { chunks, neighbors } => (
-                chunks
-                    .into_vec()
-                    .into_iter()
-                    .map(MeabyWeighted::to_weighted)
-                    .collect(),
+        let (chunks, else_chunks, neighbors, joins) = match self {
+            MapGenNestedIntermediate::Chunks {
+                chunks,
+                else_chunks,
                 neighbors,
-                false,
-            ),
+                joins,
+            } => (chunks.into_vec(), else_chunks, neighbors, joins),
             MapGenNestedIntermediate::ElseChunks {
                 else_chunks,
                 neighbors,
-            } => (
-                else_chunks
-                    .into_vec()
-                    .into_iter()
-                    .map(MeabyWeighted::to_weighted)
-                    .collect(),
-                neighbors,
-                true,
-            ),
+                joins,
+            } => (vec![], Some(else_chunks), neighbors, joins),
+        };
+
+        let to_weighted = |chunks: Vec<MeabyWeighted<MapGenValue>>| {
+            chunks
+                .into_iter()
+                .map(MeabyWeighted::to_weighted)
+                .collect::<Vec<_>>()
         };
 
         let neighbors = neighbors.map(|neighbors| {
@@ -277,11 +416,19 @@ impl Into<MapGenNested> for MapGenNestedIntermediate {
             )
         });
 
+        let joins = joins.map(|joins| {
+            HashMap::from_iter(
+                joins.into_iter().map(|(p, j)| (p, j.into_vec())),
+            )
+        });
+
         MapGenNested {
             neighbors,
-            joins: None,
-            chunks: transformed_chunks,
-            invert_condition: is_else,
+            joins,
+            chunks: to_weighted(chunks),
+            else_chunks: else_chunks
+                .map(|c| to_weighted(c.into_vec()))
+                .unwrap_or_default(),
         }
     }
 }
@@ -352,12 +499,82 @@ pub enum MapGenTrap {
     MapGenValue(MapGenValue),
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MapGenGraffiti {
+    pub text: Option<String>,
+    pub snippet: Option<CDDAIdentifier>,
+}
+
+fn default_rubble_type() -> CDDAIdentifier {
+    CDDAIdentifier::from("f_rubble")
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MapGenRubble {
+    #[serde(default = "default_rubble_type")]
+    pub rubble_type: CDDAIdentifier,
+    /// The terrain the rubble is placed on
+    pub floor_type: Option<CDDAIdentifier>,
+    /// Whether bashing items are spawned with the rubble
+    #[serde(default)]
+    pub items: bool,
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MapGenLiquid {
+    pub liquid: CDDAIdentifier,
+    pub amount: Option<NumberOrRange<u32>>,
+}
+
+/// A single item, or a single item picked from the item `group`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MapGenLoot {
+    pub group: Option<CDDAIdentifier>,
+    pub item: Option<CDDAIdentifier>,
+    /// The percent chance that the item is placed
+    pub chance: Option<NumberOrRange<u32>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MapGenSealedItemSpawn {
+    /// The item for `item` and the item group for `items`
+    pub item: CDDAIdentifier,
+    pub chance: Option<NumberOrRange<u32>>,
+}
+
+/// Furniture which contains an item or an item group, like a planted seed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MapGenSealedItem {
+    pub furniture: CDDAIdentifier,
+    pub item: Option<MapGenSealedItemSpawn>,
+    pub items: Option<MapGenSealedItemSpawn>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MapGenAppliance {
+    /// The appliance item or the id of a single part appliance vehicle
+    #[serde(alias = "appliance")]
+    pub item: CDDAIdentifier,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MapGenCorpse {
     pub group: CDDAIdentifier,
     pub age: Option<i32>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MapGenNpc {
+    /// The id of the npc template, not the id of its npc class
+    pub class: CDDAIdentifier,
+    #[serde(default)]
+    pub target: bool,
+    pub add_trait: Option<MeabyVec<CDDAIdentifier>>,
+    pub unique_id: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MapGenMonster {
     #[serde(rename = "mon")]
@@ -374,7 +591,8 @@ macro_rules! create_place_inner {
         paste! {
             #[derive(Debug, Clone)]
             pub struct [<Place $name>] {
-                pub property: [<$name Property>]
+                pub property: [<$name Property>],
+                pub inner: [<PlaceInner $name>],
             }
 
             impl Place for [<Place $name>] {
@@ -383,8 +601,14 @@ macro_rules! create_place_inner {
                     position: &IVec2,
                     map_data: &MapData,
                     json_data: &DeserializedCDDAJsonData,
+                    rng: &mut dyn RngCore,
                 ) -> Option<Vec<SetTile>> {
-                    self.property.get_commands(position, map_data, json_data)
+                    self.property
+                        .get_commands(position, map_data, json_data, rng)
+                }
+
+                fn to_json(&self) -> Result<Value, serde_json::Error> {
+                    serde_json::to_value(&self.inner)
                 }
             }
 
@@ -397,7 +621,8 @@ macro_rules! create_place_inner {
             impl Into<Arc<dyn Place>> for [<PlaceInner $name>] {
                 fn into(self) -> Arc<dyn Place> {
                     Arc::new([<Place $name>] {
-                        property: [<$name Property>]::from(self)
+                        property: [<$name Property>]::from(self.clone()),
+                        inner: self,
                     })
                 }
             }
@@ -417,6 +642,7 @@ impl Into<Arc<dyn Place>> for PlaceInnerFurniture {
             visible: FurnitureProperty {
                 mapgen_value: self.furniture_id.clone().into(),
             },
+            inner: self,
         })
     }
 }
@@ -433,6 +659,7 @@ impl Into<Arc<dyn Place>> for PlaceInnerTerrain {
             visible: TerrainProperty {
                 mapgen_value: self.terrain_id.clone().into(),
             },
+            inner: self,
         })
     }
 }
@@ -447,8 +674,9 @@ impl Into<Arc<dyn Place>> for PlaceInnerNested {
     fn into(self) -> Arc<dyn Place> {
         Arc::new(PlaceNested {
             nested_property: NestedProperty {
-                nested: vec![Weighted::new(self.chunks, 1)],
+                nested: vec![Weighted::new(self.chunks.clone(), 1)],
             },
+            inner: self,
         })
     }
 }
@@ -461,8 +689,14 @@ pub struct PlaceInnerMonster {
 
 impl Into<Arc<dyn Place>> for PlaceInnerMonster {
     fn into(self) -> Arc<dyn Place> {
+        // Both kinds of monster places share the same json
+        let inner = PlaceInnerMonsters {
+            value: self.value.clone(),
+        };
+
         Arc::new(PlaceMonsters {
             property: MonstersProperty::from(self),
+            inner,
         })
     }
 }
@@ -484,6 +718,13 @@ create_place_inner!(Toilets, ());
 create_place_inner!(Traps, MapGenTrap);
 create_place_inner!(Vehicles, MapGenVehicle);
 create_place_inner!(Corpses, MapGenCorpse);
+create_place_inner!(Appliances, MapGenAppliance);
+create_place_inner!(Npcs, MapGenNpc);
+create_place_inner!(Graffiti, MapGenGraffiti);
+create_place_inner!(Rubble, MapGenRubble);
+create_place_inner!(Liquids, MapGenLiquid);
+create_place_inner!(SealedItem, MapGenSealedItem);
+create_place_inner!(Loot, MapGenLoot);
 
 const fn default_chance() -> i32 {
     100
@@ -555,6 +796,13 @@ impl_from!(PlaceInnerGaspumps);
 impl_from!(PlaceInnerTraps);
 impl_from!(PlaceInnerVehicles);
 impl_from!(PlaceInnerCorpses);
+impl_from!(PlaceInnerAppliances);
+impl_from!(PlaceInnerNpcs);
+impl_from!(PlaceInnerGraffiti);
+impl_from!(PlaceInnerRubble);
+impl_from!(PlaceInnerLiquids);
+impl_from!(PlaceInnerSealedItem);
+impl_from!(PlaceInnerLoot);
 
 impl IntoArcDyn<PlaceOuter<PlaceInnerMonster>> for PlaceOuter<Arc<dyn Place>> {
     fn into_arc_dyn_place(
@@ -599,8 +847,14 @@ impl IntoArcDyn<PlaceOuter<PlaceInnerMonsters>> for PlaceOuter<Arc<dyn Place>> {
 }
 
 impl<T> PlaceOuter<T> {
-    pub fn coordinates(&self) -> IVec2 {
-        IVec2::new(self.x.rand_number(), self.y.rand_number())
+    pub fn coordinates<R: Rng + ?Sized>(&self, rng: &mut R) -> IVec2 {
+        IVec2::new(self.x.sample(rng), self.y.sample(rng))
+    }
+
+    pub fn validate(&self) -> Result<(), InvertedRangeError<i32>> {
+        self.x.validate()?;
+        self.y.validate()?;
+        self.repeat.validate()
     }
 }
 
@@ -628,6 +882,19 @@ macro_rules! map_data_object {
                     pub [<place_ $place_field>]: Vec<PlaceOuter<[<PlaceInner$place_field: camel>]>>,
                 )*
             }
+
+            impl $name {
+                /// The json keys of every field
+                pub fn fields() -> Vec<&'static str> {
+                    vec![
+                        $(stringify!($r_field),)*
+                        $(
+                            stringify!($place_field),
+                            concat!("place_", stringify!($place_field)),
+                        )*
+                    ]
+                }
+            }
         }
     };
 }
@@ -659,9 +926,44 @@ map_data_object!(
     gaspumps:  MeabyVec<MeabyWeighted<MapGenGaspump>>,
     traps:  MeabyVec<MeabyWeighted<MapGenTrap>>,
     vehicles: MeabyVec<MeabyWeighted<MapGenVehicle>>,
-    corpses: MeabyVec<MeabyWeighted<MapGenCorpse>>
+    corpses: MeabyVec<MeabyWeighted<MapGenCorpse>>,
+    appliances: MeabyVec<MeabyWeighted<MapGenAppliance>>,
+    npcs: MeabyVec<MeabyWeighted<MapGenNpc>>,
+    graffiti: MeabyVec<MeabyWeighted<MapGenGraffiti>>,
+    rubble: MeabyVec<MeabyWeighted<MapGenRubble>>,
+    liquids: MeabyVec<MeabyWeighted<MapGenLiquid>>,
+    sealed_item: MeabyVec<MeabyWeighted<MapGenSealedItem>>,
+    loot: MeabyVec<MeabyWeighted<MapGenLoot>>
 );
 
+/// The keys of a mapgen object which are not part of the
+/// [`CDDAMapDataObjectCommonIntermediate`]
+pub const MAPGEN_OBJECT_FIELDS: [&str; 3] = ["fill_ter", "rows", "mapgensize"];
+
+/// The keys of the json `object` of a mapgen or palette which are not read by
+/// the editor. Besides the mappings, only the `other_fields` are known.
+/// Comments are never reported.
+pub fn unknown_mapping_fields(
+    object: &Value,
+    other_fields: &[&str],
+) -> Vec<String> {
+    let Some(object) = object.as_object() else {
+        return vec![];
+    };
+
+    let known = CDDAMapDataObjectCommonIntermediate::fields();
+
+    object
+        .keys()
+        .filter(|key| !key.starts_with("//"))
+        .filter(|key| {
+            !known.contains(&key.as_str())
+                && !other_fields.contains(&key.as_str())
+        })
+        .cloned()
+        .collect()
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CDDAMapDataObjectIntermediate {
     pub fill_ter: Option<DistributionInner>,
@@ -727,18 +1029,19 @@ impl CDDAMapDataIntermediate {
         }
 
         let mut toilet_map = HashMap::new();
-        for (char, _) in self.object.common.toilets.clone() {
-            let toilet_prop = Arc::new(FurnitureProperty {
-                mapgen_value: MapGenValue::String("f_toilet".into()),
-            });
+        for (char, toilet) in self.object.common.toilets.clone() {
+            let toilet_prop = Arc::new(ToiletsProperty { toilet });
 
             toilet_map.insert(char, toilet_prop as Arc<dyn Property>);
         }
 
         let mut computer_map = HashMap::new();
-        for (char, _) in self.object.common.computers.clone() {
-            let ter_prop = Arc::new(FurnitureProperty {
-                mapgen_value: MapGenValue::String("f_console".into()),
+        for (char, computer) in self.object.common.computers.clone() {
+            let ter_prop = Arc::new(ComputersProperty {
+                computer: computer
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
             });
 
             computer_map.insert(char, ter_prop as Arc<dyn Property>);
@@ -748,7 +1051,6 @@ impl CDDAMapDataIntermediate {
         for (char, monster) in self.object.common.monsters.clone() {
             let monster_prop = Arc::new(MonstersProperty {
                 monster: monster
-                    .into_vec()
                     .into_iter()
                     .map(MeabyWeighted::to_weighted)
                     .collect(),
@@ -761,7 +1063,6 @@ impl CDDAMapDataIntermediate {
         for (char, monster) in self.object.common.monster.clone() {
             let monster_prop = Arc::new(MonstersProperty {
                 monster: monster
-                    .into_vec()
                     .into_iter()
                     .map(MeabyWeighted::to_weighted)
                     .collect(),
@@ -774,8 +1075,6 @@ impl CDDAMapDataIntermediate {
         for (char, nested) in self.object.common.nested.clone() {
             let nested_terrain_prop = Arc::new(NestedProperty {
                 nested: nested
-                    .clone()
-                    .into_vec()
                     .into_iter()
                     .map(|mw| mw.to_weighted())
                     .map(|w| Weighted::<MapGenNested>::new(w.data, w.weight))
@@ -788,7 +1087,6 @@ impl CDDAMapDataIntermediate {
         for (char, field) in self.object.common.fields.clone() {
             let field_prop = Arc::new(FieldsProperty {
                 field: field
-                    .into_vec()
                     .into_iter()
                     .map(MeabyWeighted::to_weighted)
                     .collect(),
@@ -800,7 +1098,6 @@ impl CDDAMapDataIntermediate {
         for (char, items) in self.object.common.items.clone() {
             let item_prop = Arc::new(ItemsProperty {
                 items: items
-                    .into_vec()
                     .into_iter()
                     .map(MeabyWeighted::to_weighted)
                     .collect(),
@@ -812,7 +1109,6 @@ impl CDDAMapDataIntermediate {
         for (char, sign) in self.object.common.signs.clone() {
             let sign_prop = Arc::new(SignsProperty {
                 signs: sign
-                    .into_vec()
                     .into_iter()
                     .map(MeabyWeighted::to_weighted)
                     .collect(),
@@ -824,7 +1120,6 @@ impl CDDAMapDataIntermediate {
         for (char, gaspump) in self.object.common.gaspumps.clone() {
             let gaspump_prop = Arc::new(GaspumpsProperty {
                 gaspumps: gaspump
-                    .into_vec()
                     .into_iter()
                     .map(MeabyWeighted::to_weighted)
                     .collect(),
@@ -836,7 +1131,6 @@ impl CDDAMapDataIntermediate {
         for (char, trap) in self.object.common.traps.clone() {
             let trap_prop = Arc::new(TrapsProperty {
                 trap: trap
-                    .into_vec()
                     .into_iter()
                     .map(MeabyWeighted::to_weighted)
                     .map(|v| {
@@ -856,7 +1150,6 @@ impl CDDAMapDataIntermediate {
         for (char, vehicles) in self.object.common.vehicles.clone() {
             let vehicles_prop = Arc::new(VehiclesProperty {
                 vehicles: vehicles
-                    .into_vec()
                     .into_iter()
                     .map(MeabyWeighted::to_weighted)
                     .collect(),
@@ -868,7 +1161,6 @@ impl CDDAMapDataIntermediate {
         for (char, corpses) in self.object.common.corpses.clone() {
             let corpses_prop = Arc::new(CorpsesProperty {
                 corpses: corpses
-                    .into_vec()
                     .into_iter()
                     .map(MeabyWeighted::to_weighted)
                     .collect(),
@@ -876,6 +1168,85 @@ impl CDDAMapDataIntermediate {
             corpses_map.insert(char, corpses_prop as Arc<dyn Property>);
         }
 
+        let mut appliances_map = HashMap::new();
+        for (char, appliances) in self.object.common.appliances.clone() {
+            let appliances_prop = Arc::new(AppliancesProperty {
+                appliances: appliances
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            appliances_map
+                .insert(char, appliances_prop as Arc<dyn Property>);
+        }
+
+        let mut npcs_map = HashMap::new();
+        for (char, npcs) in self.object.common.npcs.clone() {
+            let npcs_prop = Arc::new(NpcsProperty {
+                npcs: npcs
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            npcs_map.insert(char, npcs_prop as Arc<dyn Property>);
+        }
+
+        let mut graffiti_map = HashMap::new();
+        for (char, graffiti) in self.object.common.graffiti.clone() {
+            let graffiti_prop = Arc::new(GraffitiProperty {
+                graffiti: graffiti
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            graffiti_map.insert(char, graffiti_prop as Arc<dyn Property>);
+        }
+
+        let mut rubble_map = HashMap::new();
+        for (char, rubble) in self.object.common.rubble.clone() {
+            let rubble_prop = Arc::new(RubbleProperty {
+                rubble: rubble
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            rubble_map.insert(char, rubble_prop as Arc<dyn Property>);
+        }
+
+        let mut liquids_map = HashMap::new();
+        for (char, liquids) in self.object.common.liquids.clone() {
+            let liquids_prop = Arc::new(LiquidsProperty {
+                liquids: liquids
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            liquids_map.insert(char, liquids_prop as Arc<dyn Property>);
+        }
+
+        let mut sealed_item_map = HashMap::new();
+        for (char, sealed_item) in self.object.common.sealed_item.clone() {
+            let sealed_item_prop = Arc::new(SealedItemProperty {
+                sealed_item: sealed_item
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            sealed_item_map
+                .insert(char, sealed_item_prop as Arc<dyn Property>);
+        }
+
+        let mut loot_map = HashMap::new();
+        for (char, loot) in self.object.common.loot.clone() {
+            let loot_prop = Arc::new(LootProperty {
+                loot: loot
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            loot_map.insert(char, loot_prop as Arc<dyn Property>);
+        }
+
         properties.insert(MappingKind::Terrain, terrain_map);
         properties.insert(MappingKind::Furniture, furniture_map);
         properties.insert(MappingKind::Monsters, monsters_map);
@@ -889,11 +1260,38 @@ impl CDDAMapDataIntermediate {
         properties.insert(MappingKind::Trap, trap_map);
         properties.insert(MappingKind::Vehicle, vehicles_map);
         properties.insert(MappingKind::Corpse, corpses_map);
+        properties.insert(MappingKind::Appliance, appliances_map);
         properties.insert(MappingKind::Monster, monster_map);
+        properties.insert(MappingKind::Npc, npcs_map);
+        properties.insert(MappingKind::Graffiti, graffiti_map);
+        properties.insert(MappingKind::Rubble, rubble_map);
+        properties.insert(MappingKind::Liquid, liquids_map);
+        properties.insert(MappingKind::SealedItem, sealed_item_map);
+        properties.insert(MappingKind::Loot, loot_map);
 
         properties
     }
 
+    fn get_set(&self, map_coordinates: MapCoordinates) -> Vec<Arc<dyn Set>> {
+        let map_size = self.object.mapgen_size.unwrap_or(DEFAULT_MAP_DATA_SIZE);
+        let offset = (map_coordinates * map_size).as_ivec2();
+
+        self.object
+            .common
+            .set
+            .iter()
+            .filter_map(|set| {
+                let converted = set.to_set(offset);
+
+                if converted.is_none() {
+                    warn!("Skipping invalid set entry {:?}", set);
+                }
+
+                converted
+            })
+            .collect()
+    }
+
     fn get_place(
         &self,
         map_coordinates: MapCoordinates,
@@ -911,6 +1309,10 @@ impl CDDAMapDataIntermediate {
                     let mut map_vec = vec![];
 
                     for mapping in self.object.common.[<place_ $multi:lower>].iter() {
+                        if let Err(e) = mapping.validate() {
+                            warn!("{} in place_{}", e, stringify!([<$multi:lower>]));
+                        }
+
                         let remapped_x = mapping.x.clone() - (map_coordinates.x * map_size.x as u32) as i32;
                         let remapped_y = mapping.y.clone() - (map_coordinates.y * map_size.y as u32) as i32;
 
@@ -937,6 +1339,10 @@ impl CDDAMapDataIntermediate {
                     let mut map_vec = vec![];
 
                     for mapping in self.object.common.[<place_ $name:lower>].iter() {
+                        if let Err(e) = mapping.validate() {
+                            warn!("{} in place_{}", e, stringify!([<$name:lower>]));
+                        }
+
                         let remapped_x = mapping.x.clone() - (map_coordinates.x * DEFAULT_MAP_WIDTH as u32) as i32;
                         let remapped_y = mapping.y.clone() - (map_coordinates.y * DEFAULT_MAP_HEIGHT as u32) as i32;
 
@@ -972,6 +1378,13 @@ impl CDDAMapDataIntermediate {
         insert_place!(ItemGroups, items);
         insert_place!(Vehicle, vehicles);
         insert_place!(Corpse, corpses);
+        insert_place!(Appliance, appliances);
+        insert_place!(Npc, npcs);
+        insert_place!(Graffiti);
+        insert_place!(Rubble);
+        insert_place!(Liquid, liquids);
+        insert_place!(SealedItem, sealed_item);
+        insert_place!(Loot);
 
         place
     }
@@ -983,6 +1396,42 @@ pub enum IntoMapDataCollectionError {
     MissingNestedOmTerrain,
 }
 
+/// The cells of the overmap terrain at `map_coordinates` in a mapgen whose
+/// `rows` span multiple overmap terrains. Rows and columns which are missing
+/// in the mapgen are left blank instead of shifting the following cells
+fn nested_om_terrain_cells(
+    rows: Option<&Vec<String>>,
+    map_coordinates: UVec2,
+) -> IndexMap<UVec2, Cell> {
+    let mut cells = IndexMap::new();
+
+    for row in 0..DEFAULT_MAP_HEIGHT {
+        let mut characters = rows
+            .and_then(|rows| {
+                rows.get(map_coordinates.y as usize * DEFAULT_MAP_HEIGHT + row)
+            })
+            .map(|r| {
+                r.chars()
+                    .skip(map_coordinates.x as usize * DEFAULT_MAP_WIDTH)
+                    .take(DEFAULT_MAP_WIDTH)
+                    .collect::<Vec<char>>()
+            })
+            .unwrap_or_default()
+            .into_iter();
+
+        for column in 0..DEFAULT_MAP_WIDTH {
+            cells.insert(
+                UVec2::new(column as u32, row as u32),
+                Cell {
+                    character: characters.next().unwrap_or(' '),
+                },
+            );
+        }
+    }
+
+    cells
+}
+
 impl TryInto<MapDataCollection> for CDDAMapDataIntermediate {
     type Error = IntoMapDataCollectionError;
 
@@ -993,96 +1442,47 @@ impl TryInto<MapDataCollection> for CDDAMapDataIntermediate {
             None => {},
             Some(om) => {
                 if let OmTerrain::Nested(n) = om {
-                    let num_rows = n.len();
-                    let num_cols =
-                        n.get(0).ok_or(MissingNestedOmTerrain)?.len();
-
-                    for map_row_index in 0..num_rows {
-                        for map_column_index in 0..num_cols {
-                            let mut nested_cells = IndexMap::new();
-
-                            match self.object.rows.clone() {
-                                None => {
-                                    for row in 0..DEFAULT_MAP_HEIGHT {
-                                        for column in 0..DEFAULT_MAP_WIDTH {
-                                            nested_cells.insert(
-                                                UVec2::new(
-                                                    column as u32,
-                                                    row as u32,
-                                                ),
-                                                Cell { character: ' ' },
-                                            );
-                                        }
-                                    }
-                                },
-                                Some(map_row_slice) => {
-                                    let new_slice: Vec<String> = map_row_slice
-                                        [map_row_index * DEFAULT_MAP_HEIGHT
-                                            ..map_row_index
-                                                * DEFAULT_MAP_HEIGHT
-                                                + DEFAULT_MAP_HEIGHT]
-                                        .into_iter()
-                                        .map(|str| {
-                                            str.chars()
-                                                .skip(
-                                                    map_column_index
-                                                        * DEFAULT_MAP_WIDTH,
-                                                )
-                                                .take(DEFAULT_MAP_WIDTH)
-                                                .collect::<String>()
-                                        })
-                                        .collect();
-
-                                    for (row_index, slice) in
-                                        new_slice.into_iter().enumerate()
-                                    {
-                                        for (column_index, character) in
-                                            slice.chars().enumerate()
-                                        {
-                                            nested_cells.insert(
-                                                UVec2::new(
-                                                    column_index as u32,
-                                                    row_index as u32,
-                                                ),
-                                                Cell { character },
-                                            );
-                                        }
-                                    }
-                                },
-                            }
+                    if n.first().is_none_or(|ids| ids.is_empty()) {
+                        return Err(MissingNestedOmTerrain);
+                    }
 
+                    // Rows of overmap terrains can differ in length, every
+                    // overmap terrain in them gets its own map
+                    for (map_row_index, ids) in n.iter().enumerate() {
+                        for map_column_index in 0..ids.len() {
                             let map_coordinates = UVec2::new(
                                 map_column_index as u32,
                                 map_row_index as u32,
                             );
+
+                            let nested_cells = nested_om_terrain_cells(
+                                self.object.rows.as_ref(),
+                                map_coordinates,
+                            );
+
                             let mut map_data = MapData::default();
 
                             let properties = self.get_properties();
                             let place = self.get_place(map_coordinates);
+                            let set = self.get_set(map_coordinates);
 
                             map_data.cells = nested_cells;
                             map_data.properties = properties;
                             map_data.place = place;
+                            map_data.set = set;
                             map_data.parameters =
                                 self.object.common.parameters.clone();
                             map_data.palettes =
                                 self.object.common.palettes.clone();
                             map_data.fill = self.object.fill_ter.clone();
-                            map_data.map_size = self
-                                .object
-                                .mapgen_size
-                                .unwrap_or(DEFAULT_MAP_DATA_SIZE);
+                            map_data.map_size = DEFAULT_MAP_DATA_SIZE;
                             map_data.flags = self.object.common.flags.clone();
                             map_data.predecessor =
                                 self.object.common.predecessor_mapgen.clone();
 
-                            map_data_collection.maps.insert(
-                                UVec2::new(
-                                    map_column_index as u32,
-                                    map_row_index as u32,
-                                ),
-                                map_data,
-                            );
+                            map_data_collection
+                                .maps
+                                .insert(map_coordinates, map_data);
                         }
                     }
 
@@ -1096,6 +1496,15 @@ impl TryInto<MapDataCollection> for CDDAMapDataIntermediate {
 
         let properties = self.get_properties();
         let place = self.get_place(UVec2::ZERO);
+        let set = self.get_set(UVec2::ZERO);
+
+        // Nested and update mapgens only change the cells which are mapped,
+        // so blank cells do not need to exist at all
+        let sparse =
+            self.nested_mapgen_id.is_some() || self.update_mapgen_id.is_some();
+        let is_mapped = |character: &char| {
+            properties.values().any(|m| m.contains_key(character))
+        };
 
         let mut cells = IndexMap::new();
 
@@ -1114,13 +1523,19 @@ impl TryInto<MapDataCollection> for CDDAMapDataIntermediate {
                     },
                 };
 
+                if sparse && char == ' ' && !is_mapped(&char) {
+                    continue;
+                }
+
                 cells.insert(UVec2::new(column, row), Cell { character: char });
             }
         }
 
         map_data.cells = cells;
+        map_data.sparse = sparse;
         map_data.properties = properties;
         map_data.place = place;
+        map_data.set = set;
         map_data.parameters = self.object.common.parameters.clone();
         map_data.palettes = self.object.common.palettes.clone();
         map_data.fill = self.object.fill_ter.clone();
@@ -1187,3 +1602,100 @@ pub struct MapGenVehicle {
     #[serde(default = "default_rotation")]
     pub rotation: MeabyVec<i32>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::map::MapDataConfig;
+    use serde_json::json;
+
+    fn om_terrain_match(
+        om_terrain: &str,
+        om_terrain_match_type: OmTerrainMatchType,
+    ) -> OmTerrainMatch {
+        OmTerrainMatch {
+            om_terrain: om_terrain.into(),
+            om_terrain_match_type,
+        }
+    }
+
+    #[test]
+    fn test_om_terrain_match_types() {
+        use OmTerrainMatchType::{Contains, Exact, Prefix, Subtype, Type};
+
+        let matches = |om_terrain, ty, ident: &str| {
+            om_terrain_match(om_terrain, ty).matches_identifier(&ident.into())
+        };
+
+        assert!(matches("road_ns", Exact, "road_ns"));
+        assert!(!matches("road", Exact, "road_ns"));
+
+        assert!(matches("road", Type, "road_ns"));
+        assert!(matches("road", Type, "road_end_north"));
+        assert!(matches("road", Type, "road_nesw"));
+        assert!(matches("house_01", Type, "house_01_west"));
+        assert!(!matches("house", Type, "house_01_west"));
+
+        assert!(matches("road_curved", Subtype, "road_ne"));
+        assert!(matches("road_curved", Subtype, "road_wn"));
+        assert!(matches("road_end", Subtype, "road_end_east"));
+        assert!(!matches("road_curved", Subtype, "road_ns"));
+        assert!(matches("s_gas", Subtype, "s_gas_north"));
+
+        assert!(matches("forest", Prefix, "forest"));
+        assert!(matches("forest", Prefix, "forest_thick"));
+        assert!(!matches("forest", Prefix, "forestcabin"));
+
+        assert!(matches("forest", Contains, "forestcabin"));
+        assert!(matches("water", Contains, "lake_water"));
+    }
+    #[test]
+    fn test_nested_neighbors_and_joins() {
+        let nested: MapGenNestedIntermediate = serde_json::from_value(json!({
+            "chunks": [ "mall_entrance" ],
+            "neighbors": { "north": [ "road", "field" ] },
+            "joins": { "east": "mall_a_1_to_mall_a_2" }
+        }))
+        .unwrap();
+        let nested: MapGenNested = nested.into();
+
+        let mut config = MapDataConfig::default();
+        config
+            .simulated_neighbors
+            .insert(NeighborDirection::North, vec!["road_end_south".into()]);
+        assert!(!nested.conditions_match(&config));
+
+        config.simulated_joins.insert(
+            NeighborDirection::East,
+            vec!["mall_a_1_to_mall_a_2".into()],
+        );
+        assert!(nested.conditions_match(&config));
+    }
+    #[test]
+    fn test_nested_else_chunks() {
+        let nested: MapGenNestedIntermediate = serde_json::from_value(json!({
+            "chunks": [ [ "fence_gate", 2 ], "fence" ],
+            "else_chunks": "null",
+            "neighbors": {
+                "south": {
+                    "om_terrain": "road",
+                    "om_terrain_match_type": "TYPE"
+                }
+            }
+        }))
+        .unwrap();
+        let nested: MapGenNested = nested.into();
+
+        let mut config = MapDataConfig::default();
+        let chunks = nested.chunks_for(&config);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].data, MapGenValue::String("null".into()));
+
+        config
+            .simulated_neighbors
+            .insert(NeighborDirection::South, vec!["road_ew".into()]);
+        let chunks = nested.chunks_for(&config);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].weight, 2);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::data::item::ItemEntry;
+use cdda_lib::types::{CDDAIdentifier, CDDAString};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Items which are dropped when a terrain or furniture is bashed or
+/// deconstructed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum CDDADrops {
+    Group(CDDAIdentifier),
+    Entries(Vec<ItemEntry>),
+    // Anything we don't understand yet, this is here so a single unknown drop
+    // entry does not prevent the whole file from being loaded
+    Other(Value),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDABash {
+    pub str_min: Option<i32>,
+    pub str_max: Option<i32>,
+    pub sound: Option<CDDAString>,
+    pub sound_fail: Option<CDDAString>,
+    pub ter_set: Option<CDDAIdentifier>,
+    pub furn_set: Option<CDDAIdentifier>,
+    pub items: Option<CDDADrops>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDADeconstruct {
+    pub ter_set: Option<CDDAIdentifier>,
+    pub furn_set: Option<CDDAIdentifier>,
+    pub items: Option<CDDADrops>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDAHarvestBySeason {
+    pub seasons: Vec<Season>,
+    pub id: CDDAIdentifier,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDAPlantData {
+    pub transform: Option<CDDAIdentifier>,
+    pub base: Option<CDDAIdentifier>,
+    pub growth_multiplier: Option<f32>,
+    pub harvest_multiplier: Option<f32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDAHarvestEntry {
+    pub drop: CDDAIdentifier,
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+    pub base_num: Option<Vec<f32>>,
+    pub scale_num: Option<Vec<f32>>,
+    pub max: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDAHarvest {
+    pub id: CDDAIdentifier,
+    #[serde(default)]
+    pub entries: Vec<CDDAHarvestEntry>,
+}
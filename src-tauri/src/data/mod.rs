@@ -1,39 +1,50 @@
-use rand::distr::Distribution;
+pub mod cache;
+pub mod field_type;
 pub mod furniture;
 pub mod io;
 pub mod item;
 pub mod map_data;
+pub mod map_object;
 mod monster;
 mod monster_group;
+pub mod npc;
 pub mod overmap;
 pub mod palettes;
 pub mod region_settings;
+pub mod section;
 pub mod terrain;
+pub mod trap;
 pub mod vehicle_parts;
 pub mod vehicles;
 
 use crate::data::furniture::{CDDAFurniture, CDDAFurnitureIntermediate};
 use crate::data::item::CDDAItemGroupIntermediate;
-use crate::data::map_data::CDDAMapDataIntermediate;
+use crate::data::map_data::{CDDAMapDataIntermediate, OmTerrain};
+use crate::data::field_type::CDDAFieldType;
+use crate::data::map_object::CDDAHarvest;
 use crate::data::monster_group::CDDAMonsterGroupIntermediate;
+use crate::data::npc::{CDDANpc, CDDANpcClass};
 use crate::data::overmap::{
     CDDAOvermapLocationIntermediate, CDDAOvermapSpecialIntermediate,
     CDDAOvermapTerrainIntermediate,
 };
 use crate::data::palettes::CDDAPaletteIntermediate;
-use crate::data::region_settings::{CDDARegionSettings, RegionIdentifier};
+use crate::data::region_settings::CDDARegionSettings;
 use crate::data::terrain::{CDDATerrain, CDDATerrainIntermediate};
+use crate::data::trap::CDDATrap;
 use crate::data::vehicle_parts::CDDAVehiclePartIntermediate;
 use crate::data::vehicles::CDDAVehicleIntermediate;
 use crate::util::GetRandom;
 use cdda_lib::types::{
     CDDADistributionInner, CDDAIdentifier, DistributionInner, IdOrAbstract,
-    MapGenValue, MeabyVec, MeabyWeighted, ParameterIdentifier,
+    ImportCDDAObject, MapGenValue, MeabyVec, MeabyWeighted,
+    ParameterIdentifier,
 };
+use cdda_lib::weighted::choose_weighted_index;
 use derive_more::Display;
 use indexmap::IndexMap;
-use rand::distr::weighted::WeightedIndex;
-use rand::{rng, Rng};
+use log::warn;
+use rand::{rng, Rng, RngCore};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use std::collections::{BTreeMap, HashMap};
@@ -89,50 +100,52 @@ where
     Ok(comments)
 }
 
+/// Maximum number of regional ids which are resolved one after another, so a
+/// region which maps an id back onto itself does not recurse forever
+const MAX_REGION_SETTING_DEPTH: usize = 8;
+
+/// Replaces a regional id like `t_region_groundcover`, a regional item group or
+/// a regional vehicle with a weighted random member of the `region_setting`.
+/// Ids which are not regional are returned unchanged
 pub fn replace_region_setting(
     id: &CDDAIdentifier,
     region_setting: &CDDARegionSettings,
-    terrain: &HashMap<CDDAIdentifier, CDDATerrain>,
-    furniture: &HashMap<CDDAIdentifier, CDDAFurniture>,
 ) -> CDDAIdentifier {
-    // If it starts with t_region, we know it is a regional setting
-    if id.starts_with("t_region") {
-        if id.starts_with("f_") {
-            return replace_region_setting(
-                region_setting
-                    .region_terrain_and_furniture
-                    .furniture
-                    .get(&RegionIdentifier(id.0.clone()))
-                    .expect("Furniture Region identifier to exist")
-                    .get_random(),
-                region_setting,
-                terrain,
-                furniture,
-            );
-        } else if id.0.starts_with("t_") {
-            return replace_region_setting(
-                region_setting
-                    .region_terrain_and_furniture
-                    .terrain
-                    .get(&RegionIdentifier(id.0.clone()))
-                    .expect("Terrain Region identifier to exist")
-                    .get_random(),
-                region_setting,
-                terrain,
-                furniture,
-            );
+    replace_region_setting_with(id, region_setting, &mut rng())
+}
+
+pub fn replace_region_setting_with<R: Rng + ?Sized>(
+    id: &CDDAIdentifier,
+    region_setting: &CDDARegionSettings,
+    rng: &mut R,
+) -> CDDAIdentifier {
+    let mut id = id.clone();
+
+    // A region can replace a regional id with another regional id
+    for _ in 0..MAX_REGION_SETTING_DEPTH {
+        match region_setting.regional_replacements(&id) {
+            None => return id,
+            Some(replacements) => {
+                id = replacements.get_random_with(rng).clone();
+            },
         }
     }
 
-    id.clone()
+    warn!(
+        "Stopped resolving regional id {} of region {} after {} replacements",
+        id, region_setting.id, MAX_REGION_SETTING_DEPTH
+    );
+
+    id
 }
 
 impl GetIdentifier for DistributionInner {
     type Error = Infallible;
 
-    fn get_identifier(
+    fn get_identifier_with(
         &self,
         calculated_parameters: &IndexMap<ParameterIdentifier, CDDAIdentifier>,
+        _rng: &mut dyn RngCore,
     ) -> Result<CDDAIdentifier, Infallible> {
         match self {
             DistributionInner::Param { param, fallback } => {
@@ -150,9 +163,10 @@ impl GetIdentifier for DistributionInner {
 impl GetIdentifier for CDDAIdentifier {
     type Error = Infallible;
 
-    fn get_identifier(
+    fn get_identifier_with(
         &self,
         _calculated_parameters: &IndexMap<ParameterIdentifier, CDDAIdentifier>,
+        _rng: &mut dyn RngCore,
     ) -> Result<CDDAIdentifier, Infallible> {
         Ok(self.clone())
     }
@@ -161,15 +175,16 @@ impl GetIdentifier for CDDAIdentifier {
 impl GetIdentifier for CDDADistributionInner {
     type Error = GetIdentifierError;
 
-    fn get_identifier(
+    fn get_identifier_with(
         &self,
         calculated_parameters: &IndexMap<ParameterIdentifier, CDDAIdentifier>,
+        rng: &mut dyn RngCore,
     ) -> Result<CDDAIdentifier, GetIdentifierError> {
         match self {
             CDDADistributionInner::String(s) => Ok(s.clone()),
-            CDDADistributionInner::Distribution(d) => {
-                Ok(d.distribution.get_identifier(calculated_parameters)?)
-            },
+            CDDADistributionInner::Distribution(d) => Ok(d
+                .distribution
+                .get_identifier_with(calculated_parameters, rng)?),
             CDDADistributionInner::Param { param, fallback } => {
                 let calculated = calculated_parameters
                     .get(param)
@@ -205,14 +220,15 @@ impl GetIdentifier for CDDADistributionInner {
 impl GetIdentifier for MapGenValue {
     type Error = GetIdentifierError;
 
-    fn get_identifier(
+    fn get_identifier_with(
         &self,
         calculated_parameters: &IndexMap<ParameterIdentifier, CDDAIdentifier>,
+        rng: &mut dyn RngCore,
     ) -> Result<CDDAIdentifier, GetIdentifierError> {
         match self {
             MapGenValue::String(s) => Ok(s.clone()),
             MapGenValue::Distribution(d) => {
-                Ok(d.get_identifier(calculated_parameters)?)
+                Ok(d.get_identifier_with(calculated_parameters, rng)?)
             },
             MapGenValue::Param { param, fallback } => calculated_parameters
                 .get(param)
@@ -243,26 +259,20 @@ impl GetIdentifier for MapGenValue {
 impl<T: Clone + GetIdentifier> GetIdentifier for MeabyVec<MeabyWeighted<T>> {
     type Error = GetRandomError;
 
-    fn get_identifier(
+    fn get_identifier_with(
         &self,
         calculated_parameters: &IndexMap<ParameterIdentifier, CDDAIdentifier>,
+        rng: &mut dyn RngCore,
     ) -> Result<CDDAIdentifier, Self::Error> {
-        let mut weights = vec![];
-        let mut self_vec = self.clone().into_vec();
-
-        self.for_each(|v| weights.push(v.weight_or_one()));
-
-        let weighted_index = WeightedIndex::new(weights.clone())
-            .map_err(|_| WeightedIndexError::InvalidWeights(weights.clone()))?;
+        let weights: Vec<i32> =
+            self.iter().map(|v| v.weight_or_one()).collect();
 
-        // let mut rng = RANDOM.write().unwrap();
-        let mut rng = rng();
+        let chosen_index = choose_weighted_index(weights.iter().copied(), rng)
+            .ok_or_else(|| WeightedIndexError::InvalidWeights(weights))?;
 
-        let chosen_index = weighted_index.sample(&mut rng);
-        let item = self_vec.remove(chosen_index);
-
-        item.data()
-            .get_identifier(calculated_parameters)
+        self.as_slice()[chosen_index]
+            .as_data()
+            .get_identifier_with(calculated_parameters, rng)
             .map_err(|_| GetRandomError::GetIdentifierError(chosen_index))
     }
 }
@@ -282,8 +292,10 @@ impl<T: Clone + GetIdentifier> GetIdentifier for MeabyVec<MeabyWeighted<T>> {
 pub enum TileLayer {
     Terrain = 0,
     Furniture = 1,
-    Monster = 2,
-    Field = 3,
+    Trap = 2,
+    Vehicle = 3,
+    Monster = 4,
+    Field = 5,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -318,10 +330,14 @@ pub enum CDDAJsonEntry {
     OvermapSpecial(CDDAOvermapSpecialIntermediate),
     Vehicle(CDDAVehicleIntermediate),
     VehiclePart(CDDAVehiclePartIntermediate),
+    Harvest(CDDAHarvest),
+    FieldType(CDDAFieldType),
+    Npc(CDDANpc),
+    NpcClass(CDDANpcClass),
+    Trap(CDDATrap),
 
     // -- UNUSED
     WeatherType,
-    FieldType,
     #[serde(rename = "LOOT_ZONE")]
     LootZone,
     WeaponCategory,
@@ -338,7 +354,6 @@ pub enum CDDAJsonEntry {
     Scenario,
     RotatableSymbol,
     Requirement,
-    Trap,
     SpeedDescription,
     ScentType,
     VehiclePlacement,
@@ -369,7 +384,6 @@ pub enum CDDAJsonEntry {
     HitRange,
     Profession,
     HarvestDropType,
-    Harvest,
     Gate,
     Recipe,
     EventStatistic,
@@ -422,7 +436,6 @@ pub enum CDDAJsonEntry {
     OvermapSpecialMigration,
     OterIdMigration,
     BodyPart,
-    NpcClass,
     TerFurnMigration,
     VehiclePartMigration,
     CampMigration,
@@ -432,7 +445,6 @@ pub enum CDDAJsonEntry {
     ChargeRemovalBlacklist,
     TalkTopic,
     Mutation,
-    Npc,
     TraitGroup,
     ShopkeeperConsumption,
     ShopkeeperBlacklist,
@@ -486,10 +498,100 @@ pub enum CDDAJsonEntry {
     #[serde(rename = "ITEM")]
     Item,
     FaultGroup,
+    #[serde(rename = "MOD_INFO")]
+    ModInfo,
     #[default]
     Unknown,
 }
 
+impl CDDAJsonEntry {
+    /// The `type` of the entry in the json files together with all ids it
+    /// defines. Returns None for entries which are not used by the editor.
+    pub fn source_ids(&self) -> Option<(&'static str, Vec<CDDAIdentifier>)> {
+        let ids_of =
+            |ids: &MeabyVec<CDDAIdentifier>| ids.iter().cloned().collect();
+
+        let source_ids = match self {
+            CDDAJsonEntry::Mapgen(mapgen) => {
+                let mut ids: Vec<CDDAIdentifier> = match &mapgen.om_terrain {
+                    None => vec![],
+                    Some(OmTerrain::Single(s)) => {
+                        vec![CDDAIdentifier(s.clone())]
+                    },
+                    Some(OmTerrain::Duplicate(d)) => {
+                        d.iter().cloned().map(CDDAIdentifier).collect()
+                    },
+                    Some(OmTerrain::Nested(n)) => n
+                        .iter()
+                        .flatten()
+                        .cloned()
+                        .map(CDDAIdentifier)
+                        .collect(),
+                };
+
+                ids.extend(mapgen.nested_mapgen_id.clone());
+                ids.extend(mapgen.update_mapgen_id.clone());
+
+                ("mapgen", ids)
+            },
+            CDDAJsonEntry::RegionSettings(rs) => {
+                ("region_settings", vec![rs.id.clone()])
+            },
+            CDDAJsonEntry::Palette(p) => ("palette", vec![p.id.clone()]),
+            CDDAJsonEntry::Terrain(t) => ("terrain", ids_of(&t.id)),
+            CDDAJsonEntry::Furniture(f) => ("furniture", ids_of(&f.id)),
+            CDDAJsonEntry::ItemGroup(g) => ("item_group", vec![g.id.clone()]),
+            CDDAJsonEntry::MonsterGroup(g) => ("monstergroup", ids_of(&g.id)),
+            CDDAJsonEntry::OvermapLocation(l) => {
+                ("overmap_location", ids_of(&l.id))
+            },
+            CDDAJsonEntry::OvermapTerrain(t) => {
+                ("overmap_terrain", ids_of(&t.id))
+            },
+            CDDAJsonEntry::OvermapSpecial(s) => {
+                ("overmap_special", ids_of(&s.id))
+            },
+            CDDAJsonEntry::Vehicle(v) => ("vehicle", ids_of(&v.id)),
+            CDDAJsonEntry::VehiclePart(vp) => ("vehicle_part", ids_of(&vp.id)),
+            CDDAJsonEntry::Harvest(h) => ("harvest", vec![h.id.clone()]),
+            CDDAJsonEntry::FieldType(f) => ("field_type", vec![f.id.clone()]),
+            CDDAJsonEntry::Npc(n) => ("npc", vec![n.id.clone()]),
+            CDDAJsonEntry::NpcClass(c) => ("npc_class", vec![c.id.clone()]),
+            CDDAJsonEntry::Trap(t) => ("trap", vec![t.id.clone()]),
+            _ => return None,
+        };
+
+        Some(source_ids)
+    }
+
+    /// The `type` of the entry in the json files together with the id it
+    /// copies from, if it copies from another entry
+    pub fn copy_from(&self) -> Option<(&'static str, CDDAIdentifier)> {
+        let copy_from = match self {
+            CDDAJsonEntry::Terrain(t) => ("terrain", t.copy_from()),
+            CDDAJsonEntry::Furniture(f) => ("furniture", f.copy_from()),
+            CDDAJsonEntry::MonsterGroup(g) => ("monstergroup", g.copy_from()),
+            CDDAJsonEntry::OvermapLocation(l) => {
+                ("overmap_location", l.copy_from())
+            },
+            CDDAJsonEntry::OvermapTerrain(t) => {
+                ("overmap_terrain", t.copy_from())
+            },
+            CDDAJsonEntry::OvermapSpecial(s) => {
+                ("overmap_special", s.copy_from())
+            },
+            CDDAJsonEntry::Vehicle(v) => ("vehicle", v.copy_from()),
+            CDDAJsonEntry::VehiclePart(vp) => ("vehicle_part", vp.copy_from()),
+            _ => return None,
+        };
+
+        match copy_from {
+            (json_type, Some(id)) => Some((json_type, id.clone())),
+            (_, None) => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Display, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum KnownCataVariant {
@@ -507,8 +609,149 @@ pub enum KnownCataVariant {
 pub trait GetIdentifier {
     type Error;
 
-    fn get_identifier(
+    /// Resolves the identifier, random choices are made with the `rng`
+    fn get_identifier_with(
         &self,
         calculated_parameters: &IndexMap<ParameterIdentifier, CDDAIdentifier>,
+        rng: &mut dyn RngCore,
     ) -> Result<CDDAIdentifier, Self::Error>;
+
+    fn get_identifier(
+        &self,
+        calculated_parameters: &IndexMap<ParameterIdentifier, CDDAIdentifier>,
+    ) -> Result<CDDAIdentifier, Self::Error> {
+        self.get_identifier_with(calculated_parameters, &mut rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::region_settings::{
+        RegionIdentifier, RegionTerrainAndFurniture,
+    };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn replacements(ids: &[(&str, i32)]) -> IndexMap<CDDAIdentifier, i32> {
+        ids.iter()
+            .map(|(id, w)| (CDDAIdentifier::from(*id), *w))
+            .collect()
+    }
+
+    fn region() -> CDDARegionSettings {
+        let mut terrain = IndexMap::new();
+        terrain.insert(
+            RegionIdentifier("t_region_groundcover".into()),
+            replacements(&[("t_grass", 3), ("t_dirt", 1), ("t_moss", 0)]),
+        );
+        terrain.insert(
+            RegionIdentifier("t_region_groundcover_urban".into()),
+            replacements(&[("t_region_groundcover", 1)]),
+        );
+        terrain.insert(
+            RegionIdentifier("t_region_loop".into()),
+            replacements(&[("t_region_loop", 1)]),
+        );
+        terrain
+            .insert(RegionIdentifier("t_region_empty".into()), IndexMap::new());
+
+        let mut furniture = IndexMap::new();
+        furniture.insert(
+            RegionIdentifier("f_region_flower".into()),
+            replacements(&[("f_dandelion", 1)]),
+        );
+
+        let mut region_item_groups = IndexMap::new();
+        region_item_groups.insert(
+            RegionIdentifier("region_trash".into()),
+            replacements(&[("trash_forest", 1)]),
+        );
+
+        let mut region_vehicles = IndexMap::new();
+        region_vehicles.insert(
+            RegionIdentifier("region_parked_car".into()),
+            replacements(&[("car", 1)]),
+        );
+
+        CDDARegionSettings {
+            id: "test_region".into(),
+            default_oter: vec![],
+            default_groundcover: vec![],
+            region_terrain_and_furniture: RegionTerrainAndFurniture {
+                terrain,
+                furniture,
+            },
+            region_item_groups,
+            region_vehicles,
+            river_scale: None,
+        }
+    }
+
+    #[test]
+    fn test_replace_region_terrain() {
+        let region = region();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let id = replace_region_setting_with(
+                &"t_region_groundcover".into(),
+                &region,
+                &mut rng,
+            );
+
+            assert!(
+                id == CDDAIdentifier::from("t_grass")
+                    || id == CDDAIdentifier::from("t_dirt")
+            );
+        }
+    }
+
+    #[test]
+    fn test_replace_all_region_categories() {
+        let region = region();
+
+        assert_eq!(
+            replace_region_setting(&"f_region_flower".into(), &region),
+            CDDAIdentifier::from("f_dandelion")
+        );
+        assert_eq!(
+            replace_region_setting(&"region_trash".into(), &region),
+            CDDAIdentifier::from("trash_forest")
+        );
+        assert_eq!(
+            replace_region_setting(&"region_parked_car".into(), &region),
+            CDDAIdentifier::from("car")
+        );
+    }
+
+    #[test]
+    fn test_replace_nested_and_unknown_region_ids() {
+        let region = region();
+
+        // A regional id which is replaced with another regional id is resolved
+        // until a non regional id is found
+        let id = replace_region_setting(
+            &"t_region_groundcover_urban".into(),
+            &region,
+        );
+        assert!(
+            id == CDDAIdentifier::from("t_grass")
+                || id == CDDAIdentifier::from("t_dirt")
+        );
+
+        // Ids without a replacement in the region are kept
+        for id in ["t_region_unknown", "t_region_empty", "t_floor"] {
+            assert_eq!(
+                replace_region_setting(&id.into(), &region),
+                CDDAIdentifier::from(id)
+            );
+        }
+
+        // A region which maps an id onto itself does not recurse forever
+        assert_eq!(
+            replace_region_setting(&"t_region_loop".into(), &region),
+            CDDAIdentifier::from("t_region_loop")
+        );
+    }
 }
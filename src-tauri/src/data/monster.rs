@@ -15,4 +15,7 @@ pub struct CDDAMonster {
     pub flags: Vec<String>,
     pub name: MonsterName,
     pub description: CDDAString,
+    pub looks_like: Option<CDDAIdentifier>,
+    pub symbol: Option<String>,
+    pub color: Option<String>,
 }
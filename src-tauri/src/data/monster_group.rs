@@ -1,9 +1,9 @@
 use crate::data::{GetIdentifier, GetIdentifierError, WeightedIndexError};
 use cdda_lib::types::{CDDAIdentifier, NumberOrRange, ParameterIdentifier};
+use cdda_lib::weighted::choose_weighted_index;
 use cdda_macros::cdda_entry;
 use indexmap::IndexMap;
-use rand::distr::weighted::WeightedIndex;
-use rand::distr::Distribution;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
@@ -67,30 +67,34 @@ impl CDDAMonsterGroup {
         &self,
         monstergroups: &HashMap<CDDAIdentifier, CDDAMonsterGroup>,
         calculated_parameters: &IndexMap<ParameterIdentifier, CDDAIdentifier>,
+        rng: &mut dyn RngCore,
     ) -> Result<CDDAIdentifier, GetRandomMonsterError> {
         let mut weights = vec![];
         self.monsters.iter().for_each(|m| weights.push(m.weight));
 
-        let weighted_index = WeightedIndex::new(weights.clone())
-            .map_err(|_| WeightedIndexError::InvalidWeights(weights))?;
-
-        // TODO: Replace with RANDOM; Random not here due to deadlock
-        let chosen_index = weighted_index.sample(&mut rand::rng());
+        let chosen_index = choose_weighted_index(weights.iter().copied(), rng)
+            .ok_or_else(|| WeightedIndexError::InvalidWeights(weights))?;
 
         let chosen_monster = &self.monsters[chosen_index];
 
         let id = match &chosen_monster.id {
-            MonsterGroupMonsterKind::Monster { monster } => {
-                monster.get_identifier(calculated_parameters).unwrap()
-            },
+            MonsterGroupMonsterKind::Monster { monster } => monster
+                .get_identifier_with(calculated_parameters, rng)
+                .unwrap(),
             MonsterGroupMonsterKind::Group { group } => {
-                let id = group.get_identifier(calculated_parameters).unwrap();
+                let id = group
+                    .get_identifier_with(calculated_parameters, rng)
+                    .unwrap();
                 let group = monstergroups.get(&id).ok_or(
                     GetRandomMonsterError::MissingMonstergroup(id.to_string()),
                 )?;
 
                 group
-                    .get_random_monster(monstergroups, calculated_parameters)?
+                    .get_random_monster(
+                        monstergroups,
+                        calculated_parameters,
+                        rng,
+                    )?
                     .clone()
             },
         };
@@ -0,0 +1,32 @@
+use cdda_lib::types::{CDDAIdentifier, CDDAString};
+use serde::{Deserialize, Serialize};
+
+/// An npc template which can be placed by mapgen through its id
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDANpc {
+    pub id: CDDAIdentifier,
+    pub class: Option<CDDAIdentifier>,
+    pub name_unique: Option<CDDAString>,
+    pub name_suffix: Option<CDDAString>,
+    pub faction: Option<CDDAIdentifier>,
+}
+
+/// A trait an npc class gives its npcs, either a single trait or a group of
+/// traits
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDANpcClassTrait {
+    #[serde(rename = "trait")]
+    pub id: Option<CDDAIdentifier>,
+    pub group: Option<CDDAIdentifier>,
+    /// Chance in percent that an npc of the class gets the trait
+    pub prob: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDANpcClass {
+    pub id: CDDAIdentifier,
+    pub name: Option<CDDAString>,
+    pub job_description: Option<CDDAString>,
+    #[serde(default)]
+    pub traits: Vec<CDDANpcClassTrait>,
+}
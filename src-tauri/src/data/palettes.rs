@@ -1,10 +1,15 @@
 use crate::data::io::DeserializedCDDAJsonData;
-use crate::data::map_data::{MapGenItem, MapGenMonsters};
+use crate::data::map_data::{
+    MapGenCorpse, MapGenGraffiti, MapGenItem, MapGenLiquid, MapGenLoot,
+    MapGenMonsters, MapGenNpc, MapGenRubble, MapGenSealedItem,
+};
 use crate::data::GetIdentifier;
 use crate::data::KnownCataVariant;
 use crate::features::map::map_properties::ItemsProperty;
 use crate::features::map::map_properties::{
-    FurnitureProperty, MonstersProperty, TerrainProperty,
+    CorpsesProperty, FurnitureProperty, GraffitiProperty, LiquidsProperty,
+    LootProperty, MonstersProperty, NpcsProperty, RubbleProperty,
+    SealedItemProperty, TerrainProperty,
 };
 use crate::features::map::{
     CalculateParametersError, MapData, MappingKind, Property, SetTile,
@@ -16,14 +21,30 @@ use cdda_lib::types::{
 use futures_lite::StreamExt;
 use glam::IVec2;
 use indexmap::IndexMap;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 pub type Palettes = HashMap<CDDAIdentifier, CDDAPalette>;
 
+/// The palettes a map can use, which are the palettes of the loaded CDDA data
+/// and the palettes of the project the map belongs to. The palettes of the
+/// project are not visible to other projects.
+#[derive(Debug, Clone, Copy)]
+pub struct PaletteScope<'a> {
+    pub cdda: &'a Palettes,
+    pub project: &'a Palettes,
+}
+
+impl<'a> PaletteScope<'a> {
+    pub fn get(&self, id: &CDDAIdentifier) -> Option<&'a CDDAPalette> {
+        self.project.get(id).or_else(|| self.cdda.get(id))
+    }
+}
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub enum ParameterScope {
     // https://github.com/CleverRaven/Cataclysm-DDA/blob/master/doc/JSON/MAPGEN.md#mapgen-parameters
@@ -78,16 +99,17 @@ pub struct CDDAPaletteIntermediate {
     pub monsters: HashMap<char, Value>,
 
     #[serde(default)]
-    pub npcs: HashMap<char, Value>,
+    pub npcs: HashMap<char, MeabyVec<MeabyWeighted<MapGenNpc>>>,
 
     #[serde(default)]
     pub items: HashMap<char, MeabyVec<MeabyWeighted<MapGenItem>>>,
 
     #[serde(default)]
-    pub loot: HashMap<char, Value>,
+    pub loot: HashMap<char, MeabyVec<MeabyWeighted<MapGenLoot>>>,
 
     #[serde(default)]
-    pub sealed_item: HashMap<char, Value>,
+    pub sealed_item:
+        HashMap<char, MeabyVec<MeabyWeighted<MapGenSealedItem>>>,
 
     #[serde(default)]
     pub fields: HashMap<char, Value>,
@@ -96,13 +118,13 @@ pub struct CDDAPaletteIntermediate {
     pub signs: HashMap<char, Value>,
 
     #[serde(default)]
-    pub rubble: HashMap<char, Value>,
+    pub rubble: HashMap<char, MeabyVec<MeabyWeighted<MapGenRubble>>>,
 
     #[serde(default)]
-    pub liquids: HashMap<char, Value>,
+    pub liquids: HashMap<char, MeabyVec<MeabyWeighted<MapGenLiquid>>>,
 
     #[serde(default)]
-    pub corpses: HashMap<char, Value>,
+    pub corpses: HashMap<char, MeabyVec<MeabyWeighted<MapGenCorpse>>>,
 
     #[serde(default)]
     pub computers: HashMap<char, Value>,
@@ -119,15 +141,41 @@ pub struct CDDAPaletteIntermediate {
     #[serde(default)]
     pub vehicles: HashMap<char, Value>,
 
+    #[serde(default)]
+    pub appliances: HashMap<char, Value>,
+
     #[serde(default)]
     pub traps: HashMap<char, Value>,
 
     #[serde(default)]
-    pub graffiti: HashMap<char, Value>,
+    pub graffiti: HashMap<char, MeabyVec<MeabyWeighted<MapGenGraffiti>>>,
 }
 
 impl Into<CDDAPalette> for CDDAPaletteIntermediate {
     fn into(self) -> CDDAPalette {
+        let mut symbols = HashSet::new();
+        symbols.extend(self.terrain.keys());
+        symbols.extend(self.furniture.keys());
+        symbols.extend(self.monster.keys());
+        symbols.extend(self.monsters.keys());
+        symbols.extend(self.npcs.keys());
+        symbols.extend(self.items.keys());
+        symbols.extend(self.loot.keys());
+        symbols.extend(self.sealed_item.keys());
+        symbols.extend(self.fields.keys());
+        symbols.extend(self.signs.keys());
+        symbols.extend(self.rubble.keys());
+        symbols.extend(self.liquids.keys());
+        symbols.extend(self.corpses.keys());
+        symbols.extend(self.computers.keys());
+        symbols.extend(self.nested.keys());
+        symbols.extend(self.toilets.keys());
+        symbols.extend(self.gaspumps.keys());
+        symbols.extend(self.vehicles.keys());
+        symbols.extend(self.appliances.keys());
+        symbols.extend(self.traps.keys());
+        symbols.extend(self.graffiti.keys());
+
         let mut properties = HashMap::new();
 
         let mut terrain_map = HashMap::new();
@@ -173,14 +221,107 @@ impl Into<CDDAPalette> for CDDAPaletteIntermediate {
             item_map.insert(char, item_prop as Arc<dyn Property>);
         }
 
+        let mut npcs_map = HashMap::new();
+        for (char, npcs) in self.npcs {
+            let npcs_prop = Arc::new(NpcsProperty {
+                npcs: npcs
+                    .into_vec()
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            npcs_map.insert(char, npcs_prop as Arc<dyn Property>);
+        }
+
+        let mut graffiti_map = HashMap::new();
+        for (char, graffiti) in self.graffiti {
+            let graffiti_prop = Arc::new(GraffitiProperty {
+                graffiti: graffiti
+                    .into_vec()
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            graffiti_map.insert(char, graffiti_prop as Arc<dyn Property>);
+        }
+
+        let mut rubble_map = HashMap::new();
+        for (char, rubble) in self.rubble {
+            let rubble_prop = Arc::new(RubbleProperty {
+                rubble: rubble
+                    .into_vec()
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            rubble_map.insert(char, rubble_prop as Arc<dyn Property>);
+        }
+
+        let mut liquids_map = HashMap::new();
+        for (char, liquids) in self.liquids {
+            let liquids_prop = Arc::new(LiquidsProperty {
+                liquids: liquids
+                    .into_vec()
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            liquids_map.insert(char, liquids_prop as Arc<dyn Property>);
+        }
+
+        let mut corpses_map = HashMap::new();
+        for (char, corpses) in self.corpses {
+            let corpses_prop = Arc::new(CorpsesProperty {
+                corpses: corpses
+                    .into_vec()
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            corpses_map.insert(char, corpses_prop as Arc<dyn Property>);
+        }
+
+        let mut sealed_item_map = HashMap::new();
+        for (char, sealed_item) in self.sealed_item {
+            let sealed_item_prop = Arc::new(SealedItemProperty {
+                sealed_item: sealed_item
+                    .into_vec()
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            sealed_item_map
+                .insert(char, sealed_item_prop as Arc<dyn Property>);
+        }
+
+        let mut loot_map = HashMap::new();
+        for (char, loot) in self.loot {
+            let loot_prop = Arc::new(LootProperty {
+                loot: loot
+                    .into_vec()
+                    .into_iter()
+                    .map(MeabyWeighted::to_weighted)
+                    .collect(),
+            });
+            loot_map.insert(char, loot_prop as Arc<dyn Property>);
+        }
+
         properties.insert(MappingKind::Terrain, terrain_map);
         properties.insert(MappingKind::Furniture, furniture_map);
         properties.insert(MappingKind::Monsters, monster_map);
         properties.insert(MappingKind::ItemGroups, item_map);
+        properties.insert(MappingKind::Npc, npcs_map);
+        properties.insert(MappingKind::Graffiti, graffiti_map);
+        properties.insert(MappingKind::Rubble, rubble_map);
+        properties.insert(MappingKind::Liquid, liquids_map);
+        properties.insert(MappingKind::Corpse, corpses_map);
+        properties.insert(MappingKind::SealedItem, sealed_item_map);
+        properties.insert(MappingKind::Loot, loot_map);
 
         CDDAPalette {
             id: self.id,
             properties,
+            symbols,
             comment: self.comment,
             parameters: self.parameters,
             palettes: self.palettes,
@@ -195,6 +336,11 @@ pub struct CDDAPalette {
     #[serde(skip)]
     pub properties: HashMap<MappingKind, HashMap<char, Arc<dyn Property>>>,
 
+    /// Every symbol the palette defines, including the symbols of mappings
+    /// which are not rendered by the editor
+    #[serde(skip)]
+    pub symbols: HashSet<char>,
+
     #[serde(rename = "//")]
     pub comment: Comment,
 
@@ -208,11 +354,40 @@ pub struct CDDAPalette {
 impl CDDAPalette {
     pub fn calculate_parameters(
         &self,
-        all_palettes: &Palettes,
+        all_palettes: PaletteScope,
+        rng: &mut dyn RngCore,
+    ) -> Result<
+        IndexMap<ParameterIdentifier, CDDAIdentifier>,
+        CalculateParametersError,
+    > {
+        self.calculate_parameters_inner(all_palettes, &mut vec![], rng)
+    }
+
+    /// The `chain` contains the palettes which are currently calculated and
+    /// include this palette
+    fn calculate_parameters_inner(
+        &self,
+        all_palettes: PaletteScope,
+        chain: &mut Vec<CDDAIdentifier>,
+        rng: &mut dyn RngCore,
     ) -> Result<
         IndexMap<ParameterIdentifier, CDDAIdentifier>,
         CalculateParametersError,
     > {
+        if let Some(start) = chain.iter().position(|id| id == &self.id) {
+            let cycle: Vec<String> = chain[start..]
+                .iter()
+                .chain(std::iter::once(&self.id))
+                .map(|id| id.0.clone())
+                .collect();
+
+            return Err(CalculateParametersError::CyclicPalette(
+                cycle.join(" -> "),
+            ));
+        }
+
+        chain.push(self.id.clone());
+
         let mut calculated_parameters: IndexMap<
             ParameterIdentifier,
             CDDAIdentifier,
@@ -224,26 +399,61 @@ impl CDDAPalette {
                 parameter
                     .default
                     .distribution
-                    .get_identifier(&calculated_parameters)?,
+                    .get_identifier_with(&calculated_parameters, rng)?,
             );
         }
 
         for mapgen_value in self.palettes.iter() {
-            let id = mapgen_value.get_identifier(&calculated_parameters)?;
+            let id = mapgen_value
+                .get_identifier_with(&calculated_parameters, rng)?;
 
             all_palettes
                 .get(&id)
                 .ok_or(CalculateParametersError::MissingPalette(id.0))?
-                .calculate_parameters(all_palettes)?
+                .calculate_parameters_inner(all_palettes, chain, rng)?
                 .into_iter()
                 .for_each(|(child_id, child_param)| {
                     calculated_parameters.insert(child_id, child_param);
                 })
         }
 
+        chain.pop();
+
         Ok(calculated_parameters)
     }
 
+    /// Returns the id of the palette which maps the `character` for the
+    /// `mapping_kind`. This is either this palette or one of the palettes it
+    /// includes.
+    pub fn find_mapping_palette(
+        &self,
+        mapping_kind: &MappingKind,
+        character: &char,
+        calculated_parameters: &IndexMap<ParameterIdentifier, CDDAIdentifier>,
+        all_palettes: PaletteScope,
+    ) -> Option<CDDAIdentifier> {
+        let is_mapped = self
+            .properties
+            .get(mapping_kind)
+            .is_some_and(|m| m.contains_key(character));
+
+        if is_mapped {
+            return Some(self.id.clone());
+        }
+
+        self.palettes.iter().find_map(|mapgen_value| {
+            let palette_id =
+                mapgen_value.get_identifier(calculated_parameters).ok()?;
+
+            all_palettes.get(&palette_id)?.find_mapping_palette(
+                mapping_kind,
+                character,
+                calculated_parameters,
+                all_palettes,
+            )
+        })
+    }
+
     pub fn get_visible_mapping(
         &self,
         mapping_kind: impl Borrow<MappingKind>,
@@ -251,18 +461,21 @@ impl CDDAPalette {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
         let mapping = self.properties.get(mapping_kind.borrow())?;
 
         if let Some(id) = mapping.get(character.borrow()) {
-            return id.get_commands(position, map_data, json_data);
+            return id.get_commands(position, map_data, json_data, rng);
         }
 
         for mapgen_value in self.palettes.iter() {
             let palette_id = mapgen_value
-                .get_identifier(&map_data.calculated_parameters)
+                .get_identifier_with(&map_data.calculated_parameters, rng)
                 .ok()?;
-            let palette = json_data.palettes.get(&palette_id)?;
+            let palette = map_data
+                .palette_scope(&json_data.palettes)
+                .get(&palette_id)?;
 
             if let Some(id) = palette.get_visible_mapping(
                 mapping_kind.borrow(),
@@ -270,6 +483,7 @@ impl CDDAPalette {
                 position,
                 map_data,
                 json_data,
+                rng,
             ) {
                 return Some(id);
             }
@@ -6,10 +6,16 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, Deserialize, Eq, PartialEq, Hash, Serialize)]
 pub struct RegionIdentifier(pub String);
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// The weighted ids a regional id like `t_region_groundcover` is replaced with
+pub type RegionalReplacements =
+    IndexMap<RegionIdentifier, IndexMap<CDDAIdentifier, i32>>;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct RegionTerrainAndFurniture {
-    pub terrain: IndexMap<RegionIdentifier, IndexMap<CDDAIdentifier, i32>>,
-    pub furniture: IndexMap<RegionIdentifier, IndexMap<CDDAIdentifier, i32>>,
+    #[serde(default)]
+    pub terrain: RegionalReplacements,
+    #[serde(default)]
+    pub furniture: RegionalReplacements,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -117,5 +123,33 @@ pub struct CDDARegionSettings {
     pub default_oter: Vec<String>,
     pub default_groundcover: Vec<Weighted<CDDAIdentifier>>,
     pub region_terrain_and_furniture: RegionTerrainAndFurniture,
+    /// Regional item groups which are replaced with an item group of the region
+    #[serde(default)]
+    pub region_item_groups: RegionalReplacements,
+    /// Regional vehicles which are replaced with a vehicle of the region
+    #[serde(default)]
+    pub region_vehicles: RegionalReplacements,
     pub river_scale: Option<f32>,
 }
+
+impl CDDARegionSettings {
+    /// The weighted replacements of the regional `id`, looked up in every
+    /// category of the region. Returns `None` if the id is not regional or the
+    /// region does not define any replacement for it
+    pub fn regional_replacements(
+        &self,
+        id: &CDDAIdentifier,
+    ) -> Option<&IndexMap<CDDAIdentifier, i32>> {
+        let region_id = RegionIdentifier(id.0.clone());
+
+        [
+            &self.region_terrain_and_furniture.terrain,
+            &self.region_terrain_and_furniture.furniture,
+            &self.region_item_groups,
+            &self.region_vehicles,
+        ]
+        .into_iter()
+        .filter_map(|category| category.get(&region_id))
+        .find(|replacements| !replacements.is_empty())
+    }
+}
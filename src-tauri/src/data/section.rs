@@ -0,0 +1,122 @@
+use crate::features::diagnostics::Diagnostics;
+use cdda_lib::types::CDDAIdentifier;
+use log::info;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+/// A json entry which was read from the file at `path` but not deserialized
+/// yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawEntry {
+    pub path: PathBuf,
+    pub value: serde_json::Value,
+}
+
+/// A section of the CDDA data which is only deserialized when it is accessed
+/// for the first time. The section dereferences to the map of its objects, so
+/// it can be used like the map itself.
+pub struct LazySection<T> {
+    /// The `type` of the entries of the section in the json files
+    json_type: &'static str,
+    raw: Mutex<Vec<RawEntry>>,
+    loaded: OnceLock<HashMap<CDDAIdentifier, T>>,
+    /// The diagnostics problems with the entries are reported to when the
+    /// section is loaded
+    diagnostics: Diagnostics,
+    load: fn(Vec<RawEntry>, &Diagnostics) -> HashMap<CDDAIdentifier, T>,
+}
+
+impl<T> LazySection<T> {
+    /// Creates a section of the `raw` entries which are deserialized with
+    /// `load` when the section is accessed
+    pub fn new(
+        json_type: &'static str,
+        raw: Vec<RawEntry>,
+        diagnostics: Diagnostics,
+        load: fn(Vec<RawEntry>, &Diagnostics) -> HashMap<CDDAIdentifier, T>,
+    ) -> Self {
+        Self {
+            json_type,
+            raw: Mutex::new(raw),
+            loaded: OnceLock::new(),
+            diagnostics,
+            load,
+        }
+    }
+
+    fn get(&self) -> &HashMap<CDDAIdentifier, T> {
+        self.loaded.get_or_init(|| {
+            let raw = std::mem::take(&mut *self.raw.lock().unwrap());
+
+            info!(
+                "Loading {} {} entries of the CDDA data",
+                raw.len(),
+                self.json_type
+            );
+
+            (self.load)(raw, &self.diagnostics)
+        })
+    }
+
+    /// The entries which were not deserialized yet. They are empty once the
+    /// section was accessed.
+    pub fn raw_entries(&self) -> Vec<RawEntry> {
+        self.raw.lock().unwrap().clone()
+    }
+
+    pub fn into_map(mut self) -> HashMap<CDDAIdentifier, T> {
+        self.get();
+        self.loaded.take().unwrap_or_default()
+    }
+}
+
+impl<T> Default for LazySection<T> {
+    fn default() -> Self {
+        Self {
+            json_type: "",
+            raw: Mutex::new(vec![]),
+            loaded: OnceLock::from(HashMap::new()),
+            diagnostics: Diagnostics::default(),
+            load: |_, _| HashMap::new(),
+        }
+    }
+}
+
+impl<T: Clone> Clone for LazySection<T> {
+    fn clone(&self) -> Self {
+        Self {
+            json_type: self.json_type,
+            raw: Mutex::new(self.raw.lock().unwrap().clone()),
+            loaded: self.loaded.clone(),
+            diagnostics: self.diagnostics.clone(),
+            load: self.load,
+        }
+    }
+}
+
+impl<T> Deref for LazySection<T> {
+    type Target = HashMap<CDDAIdentifier, T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl<T> DerefMut for LazySection<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.get();
+        self.loaded.get_mut().expect("Section to be loaded")
+    }
+}
+
+impl<T: Serialize> Serialize for LazySection<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.get().serialize(serializer)
+    }
+}
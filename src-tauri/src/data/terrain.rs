@@ -1,3 +1,4 @@
+use crate::data::map_object::{CDDABash, CDDADeconstruct, CDDAHarvestBySeason};
 use cdda_lib::types::{CDDAIdentifier, CDDAString, MeabyVec};
 use cdda_macros::cdda_entry;
 use serde::{Deserialize, Serialize};
@@ -13,5 +14,12 @@ pub struct CDDATerrain {
     pub color: Option<MeabyVec<String>>,
     pub connect_groups: Option<MeabyVec<CDDAIdentifier>>,
     pub connects_to: Option<MeabyVec<CDDAIdentifier>>,
+    pub bash: Option<CDDABash>,
+    pub deconstruct: Option<CDDADeconstruct>,
+    pub harvest_by_season: Option<Vec<CDDAHarvestBySeason>>,
+    pub move_cost: Option<i32>,
+    pub light_emitted: Option<i32>,
+    /// The terrain which is placed above this terrain as its roof
+    pub roof: Option<CDDAIdentifier>,
     pub flags: Vec<String>,
 }
@@ -0,0 +1,16 @@
+use cdda_lib::types::{CDDAIdentifier, CDDAString};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CDDATrap {
+    pub id: CDDAIdentifier,
+    pub name: Option<CDDAString>,
+    pub symbol: Option<char>,
+    pub color: Option<String>,
+    pub looks_like: Option<CDDAIdentifier>,
+    /// How hard the trap is to spot, 0 means it is always visible
+    #[serde(default)]
+    pub visibility: i32,
+    #[serde(default)]
+    pub flags: Vec<String>,
+}
@@ -4,6 +4,10 @@ use cdda_macros::cdda_entry;
 use serde::{Deserialize, Serialize};
 use strum_macros::EnumString;
 
+/// Vehicle parts of appliances are prefixed with this, for example the
+/// appliance item `fridge` is represented by the part `ap_fridge`
+pub const APPLIANCE_PART_PREFIX: &str = "ap_";
+
 #[derive(Debug, Default, EnumString, Clone, Serialize, Deserialize)]
 pub enum Location {
     OnWindshield,
@@ -54,4 +58,6 @@ pub struct CDDAVehiclePart {
     pub flags: Vec<String>,
     #[serde(default)]
     pub location: Option<String>,
+    pub symbol: Option<String>,
+    pub color: Option<String>,
 }
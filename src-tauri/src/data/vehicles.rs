@@ -1,7 +1,13 @@
+use crate::data::vehicle_parts::{CDDAVehiclePart, Location};
+use crate::util::Rotation;
 use cdda_lib::types::CDDAIdentifier;
 use cdda_lib::types::CDDAString;
 use cdda_macros::cdda_entry;
+use glam::IVec2;
+use log::warn;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
 
 // What is the part of the vehicle at x, y made of?
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -29,3 +35,106 @@ pub struct CDDAVehicle {
     pub parts: Vec<VehiclePartPlacement>,
     pub flags: Vec<String>,
 }
+
+/// The part which is drawn at a single position of a vehicle
+#[derive(Debug, Clone)]
+pub struct VisibleVehiclePart<'a> {
+    pub part: &'a CDDAVehiclePart,
+    /// The variant of the part which is specified after a `#` in the part id
+    pub variant: Option<String>,
+    pub location: Location,
+}
+
+impl CDDAVehicle {
+    /// Returns the part with the highest location priority for every position
+    /// of this vehicle after rotating it by `rotation` degrees
+    pub fn get_visible_parts<'a>(
+        &self,
+        vehicle_parts: &'a HashMap<CDDAIdentifier, CDDAVehiclePart>,
+        rotation: i32,
+    ) -> HashMap<IVec2, VisibleVehiclePart<'a>> {
+        let mut visible_parts: HashMap<IVec2, VisibleVehiclePart> =
+            HashMap::new();
+
+        let rotation_radians = (rotation as f32).to_radians();
+
+        for part in self.parts.iter() {
+            // Positive y -> right
+            // Negative y -> left
+            // Positive x -> up
+            // Negative x -> down
+            let base_position = IVec2::new(part.x, part.y);
+
+            // TODO: This rotation looks pretty munted for any rotation other than 0, 90, 180 and 270 degrees
+            let rotated_x = (base_position.x as f32 * rotation_radians.cos()
+                - base_position.y as f32 * rotation_radians.sin())
+            .round() as i32;
+            let rotated_y = (base_position.x as f32 * rotation_radians.sin()
+                + base_position.y as f32 * rotation_radians.cos())
+            .round() as i32;
+
+            let part_position = IVec2::new(rotated_x, rotated_y);
+
+            for vp in part.parts.iter() {
+                let ident = match vp {
+                    VehiclePart::Inline(id) => id.clone(),
+                    VehiclePart::Object { part, .. } => part.clone(),
+                };
+
+                let (raw_ident, variant) = match ident.0.split_once('#') {
+                    None => (ident.clone(), None),
+                    Some((ident, ty)) => (ident.into(), Some(ty.to_string())),
+                };
+
+                let vp_entry = match vehicle_parts.get(&raw_ident) {
+                    None => {
+                        warn!(
+                            "Vehicle Part {} does not exist in cdda data",
+                            raw_ident
+                        );
+                        continue;
+                    },
+                    Some(vp) => vp,
+                };
+
+                let location: Location = Location::from_str(
+                    &vp_entry
+                        .location
+                        .clone()
+                        .unwrap_or("structure".to_string()),
+                )
+                .unwrap_or(Location::Structure);
+
+                let is_higher_priority = match visible_parts.get(&part_position)
+                {
+                    None => true,
+                    Some(p) => location.priority() > p.location.priority(),
+                };
+
+                if is_higher_priority {
+                    visible_parts.insert(
+                        part_position,
+                        VisibleVehiclePart {
+                            part: vp_entry,
+                            variant,
+                            location,
+                        },
+                    );
+                }
+            }
+        }
+
+        visible_parts
+    }
+}
+
+/// Converts the rotation of a placed vehicle into the rotation of its part
+/// sprites
+pub fn get_part_sprite_rotation(rotation: i32) -> Rotation {
+    match rotation % 360 {
+        0..90 => Rotation::Deg270,
+        180..270 => Rotation::Deg90,
+        // TODO: dirty hack to make the rotation work "counter clockwise"
+        n => Rotation::from(n + 90),
+    }
+}
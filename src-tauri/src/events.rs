@@ -1,7 +1,275 @@
-pub const EDITOR_DATA_CHANGED: &str = "editor_data_changed";
-pub const TILESET_CHANGED: &str = "tileset_changed";
-pub const PLACE_SPRITES: &str = "place_sprites";
-pub const TAB_CREATED: &str = "tab_created";
-pub const TAB_REMOVED: &str = "tab_removed";
-pub const UPDATE_LIVE_VIEWER: &str = "update_live_viewer";
-pub const TOAST_MESSAGE: &str = "emit_toast_message";
+use crate::features::program_data::{
+    EditorConfig, EditorData, ProjectName, RecentProject, Tab,
+};
+use crate::features::tileset::Projection;
+use crate::features::toast::ToastMessage;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use strum_macros::EnumIter;
+use tauri::{Emitter, Runtime};
+
+/// All events that are sent from the backend to the frontend.
+///
+/// The TypeScript definitions in `src/src/tauri/events/catalog.ts` are
+/// generated from this enum. A test makes sure that the checked in file is up
+/// to date.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, EnumIter)]
+pub enum EditorEvent {
+    EditorDataChanged,
+    ConfigChanged,
+    ProjectListChanged,
+    OpenedProjectChanged,
+    TilesetChanged,
+    PlaceSprites,
+    TabCreated,
+    TabRemoved,
+    UpdateLiveViewer,
+    ToastMessage,
+    CrashReported,
+    JobUpdated,
+    ProjectChanged,
+    WatcherStatusChanged,
+    CDDADataChanged,
+    ModsLoaded,
+    CDDADataLoadProgress,
+}
+
+impl EditorEvent {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            EditorEvent::EditorDataChanged => "editor_data_changed",
+            EditorEvent::ConfigChanged => "config_changed",
+            EditorEvent::ProjectListChanged => "project_list_changed",
+            EditorEvent::OpenedProjectChanged => "opened_project_changed",
+            EditorEvent::TilesetChanged => "tileset_changed",
+            EditorEvent::PlaceSprites => "place_sprites",
+            EditorEvent::TabCreated => "tab_created",
+            EditorEvent::TabRemoved => "tab_removed",
+            EditorEvent::UpdateLiveViewer => "update_live_viewer",
+            EditorEvent::ToastMessage => "emit_toast_message",
+            EditorEvent::CrashReported => "crash_reported",
+            EditorEvent::JobUpdated => "job_updated",
+            EditorEvent::ProjectChanged => "project_changed",
+            EditorEvent::WatcherStatusChanged => "watcher_status_changed",
+            EditorEvent::CDDADataChanged => "cdda_data_changed",
+            EditorEvent::ModsLoaded => "mods_loaded",
+            EditorEvent::CDDADataLoadProgress => "cdda_data_load_progress",
+        }
+    }
+
+    /// The TypeScript type of the payload which is sent with this event
+    const fn payload_ts_type(&self) -> &'static str {
+        match self {
+            EditorEvent::EditorDataChanged => "EditorData",
+            EditorEvent::ConfigChanged => "ConfigChangedEvent",
+            EditorEvent::ProjectListChanged => "ProjectListChangedEvent",
+            EditorEvent::OpenedProjectChanged => "OpenedProjectChangedEvent",
+            EditorEvent::TilesetChanged => "TilesetChangedEvent",
+            EditorEvent::PlaceSprites => "PlaceSpritesEvent",
+            EditorEvent::TabCreated => {
+                "{ name: string, tab_type: TabTypeKind }"
+            },
+            EditorEvent::TabRemoved => "{ name: string }",
+            EditorEvent::UpdateLiveViewer => "null",
+            EditorEvent::ToastMessage => "{ type: ToastType, message: string }",
+            EditorEvent::CrashReported => "CrashReportedEvent",
+            EditorEvent::JobUpdated => "JobInfo",
+            EditorEvent::ProjectChanged => "ProjectChangedEvent",
+            EditorEvent::WatcherStatusChanged => "WatcherStatusChangedEvent",
+            EditorEvent::CDDADataChanged => "CDDADataChangedEvent",
+            EditorEvent::ModsLoaded => "ModsLoadedEvent",
+            EditorEvent::CDDADataLoadProgress => "CDDADataLoadProgressEvent",
+        }
+    }
+}
+
+/// The TypeScript types that are referenced by the payload types and the
+/// module they are imported from
+const TS_IMPORTS: &[(&str, &str)] = &[
+    ("EditorData", "../types/editor.js"),
+    ("ConfigChangedEvent", "../types/editor.js"),
+    ("ProjectListChangedEvent", "../types/editor.js"),
+    ("OpenedProjectChangedEvent", "../types/editor.js"),
+    ("TilesetChangedEvent", "../types/spritesheet.js"),
+    ("PlaceSpritesEvent", "../types/map_data.js"),
+    ("TabTypeKind", "../../shared/hooks/useTabs.js"),
+    ("ToastType", "./types.js"),
+    ("CrashReportedEvent", "../types/editor.js"),
+    ("JobInfo", "../types/jobs.js"),
+    ("ProjectChangedEvent", "../types/editor.js"),
+    ("WatcherStatusChangedEvent", "../types/editor.js"),
+    ("CDDADataChangedEvent", "../types/editor.js"),
+    ("ModsLoadedEvent", "../types/editor.js"),
+    ("CDDADataLoadProgressEvent", "../types/editor.js"),
+];
+
+/// A payload which can only be sent with the event [`EventPayload::EVENT`]
+pub trait EventPayload: Serialize + Clone {
+    const EVENT: EditorEvent;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChangedEvent {
+    pub config: EditorConfig,
+    pub available_tilesets: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectListChangedEvent {
+    pub openable_projects: HashSet<ProjectName>,
+    pub recent_projects: HashSet<RecentProject>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenedProjectChangedEvent {
+    pub opened_project: Option<ProjectName>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReportedEvent {
+    pub message: String,
+    pub location: String,
+    /// Path to the written crash report, if it could be written
+    pub crash_file: Option<PathBuf>,
+}
+
+/// Sent when the maps of a project were edited or saved
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectChangedEvent {
+    pub name: ProjectName,
+    /// Whether the project has unsaved changes
+    pub dirty: bool,
+}
+
+/// Sent when the tileset or the season of its tiles changed
+#[derive(Debug, Clone, Serialize)]
+pub struct TilesetChangedEvent {
+    /// The selected tileset, [`None`] if the fallback tileset is used
+    pub tileset: Option<String>,
+    /// The geometry of the tiles of the new tileset
+    pub projection: Projection,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateLiveViewerEvent;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TabRemovedEvent {
+    pub name: String,
+}
+
+impl EventPayload for EditorData {
+    const EVENT: EditorEvent = EditorEvent::EditorDataChanged;
+}
+
+impl EventPayload for ConfigChangedEvent {
+    const EVENT: EditorEvent = EditorEvent::ConfigChanged;
+}
+
+impl EventPayload for ProjectListChangedEvent {
+    const EVENT: EditorEvent = EditorEvent::ProjectListChanged;
+}
+
+impl EventPayload for OpenedProjectChangedEvent {
+    const EVENT: EditorEvent = EditorEvent::OpenedProjectChanged;
+}
+
+impl EventPayload for TilesetChangedEvent {
+    const EVENT: EditorEvent = EditorEvent::TilesetChanged;
+}
+
+impl EventPayload for Tab {
+    const EVENT: EditorEvent = EditorEvent::TabCreated;
+}
+
+impl EventPayload for TabRemovedEvent {
+    const EVENT: EditorEvent = EditorEvent::TabRemoved;
+}
+
+impl EventPayload for UpdateLiveViewerEvent {
+    const EVENT: EditorEvent = EditorEvent::UpdateLiveViewer;
+}
+
+impl EventPayload for ToastMessage {
+    const EVENT: EditorEvent = EditorEvent::ToastMessage;
+}
+
+impl EventPayload for CrashReportedEvent {
+    const EVENT: EditorEvent = EditorEvent::CrashReported;
+}
+
+impl EventPayload for ProjectChangedEvent {
+    const EVENT: EditorEvent = EditorEvent::ProjectChanged;
+}
+
+pub trait EmitEvent<R: Runtime> {
+    /// Emits the event that belongs to the `payload` to all listeners
+    fn emit_event<P: EventPayload>(&self, payload: P) -> tauri::Result<()>;
+}
+
+impl<R: Runtime, E: Emitter<R>> EmitEvent<R> for E {
+    fn emit_event<P: EventPayload>(&self, payload: P) -> tauri::Result<()> {
+        self.emit(P::EVENT.name(), payload)
+    }
+}
+
+/// Generates the content of the TypeScript event catalog, which is written by
+/// the `event_catalog` binary
+pub fn typescript_definitions() -> String {
+    use strum::IntoEnumIterator;
+
+    let mut out = String::new();
+
+    out.push_str(
+        "// This file is generated from src-tauri/src/events.rs, do not edit it manually.\n",
+    );
+    out.push_str("// Run `cargo run --bin event_catalog` to regenerate it.\n");
+
+    for (ty, module) in TS_IMPORTS {
+        out.push_str(&format!("import type {{{}}} from \"{}\";\n", ty, module));
+    }
+
+    out.push_str("\nexport enum TauriEvent {\n");
+    for event in EditorEvent::iter() {
+        out.push_str(&format!(
+            "    {} = \"{}\",\n",
+            event.name().to_uppercase(),
+            event.name()
+        ));
+    }
+    out.push_str("}\n");
+
+    out.push_str("\nexport interface TauriEventMap {\n");
+    for event in EditorEvent::iter() {
+        out.push_str(&format!(
+            "    [TauriEvent.{}]: {};\n",
+            event.name().to_uppercase(),
+            event.payload_ts_type()
+        ));
+    }
+    out.push_str("}\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOG_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../src/src/tauri/events/catalog.ts"
+    );
+
+    #[test]
+    fn test_typescript_catalog_is_up_to_date() {
+        let generated = typescript_definitions();
+        let existing = std::fs::read_to_string(CATALOG_PATH).unwrap();
+        assert_eq!(
+            existing, generated,
+            "The TypeScript event catalog is out of date, run `cargo run --bin \
+             event_catalog` to regenerate it"
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::analysis::{analyze_map as analyze, MapAnalysis};
+use crate::features::program_data::{EditorData, MappedCDDAIdContainer, ZLevel};
+use crate::impl_serialize_for_error;
+use crate::util::{
+    get_current_project, get_json_data, CDDADataError, GetCurrentProjectError,
+};
+use serde::{Serialize, Serializer};
+use std::collections::HashMap;
+use std::ops::Deref;
+use tauri::async_runtime::Mutex;
+use tauri::State;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AnalyzeMapError {
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error("No map is opened")]
+    NoMapOpened,
+}
+
+impl_serialize_for_error!(AnalyzeMapError);
+
+/// Scores the opened map and lists issues like unreachable rooms or item
+/// groups which can never spawn. The map has to be rendered first.
+#[tauri::command]
+pub async fn analyze_map(
+    editor_data: State<'_, Mutex<EditorData>>,
+    mapped_cdda_ids: State<
+        '_,
+        Mutex<Option<HashMap<ZLevel, MappedCDDAIdContainer>>>,
+    >,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<MapAnalysis, AnalyzeMapError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let editor_data_lock = editor_data.lock().await;
+    let project = get_current_project(&editor_data_lock)?;
+
+    let mapped_cdda_ids_lock = mapped_cdda_ids.lock().await;
+    let mapped_cdda_ids = match mapped_cdda_ids_lock.deref() {
+        None => return Err(AnalyzeMapError::NoMapOpened),
+        Some(m) => m,
+    };
+
+    Ok(analyze(project, mapped_cdda_ids, json_data))
+}
@@ -0,0 +1,866 @@
+pub(crate) mod handlers;
+
+use crate::data::furniture::CDDAFurniture;
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::map_data::{MapGenItem, PlaceItems, ReferenceOrInPlace};
+use crate::data::terrain::CDDATerrain;
+use crate::features::map::map_properties::ItemsProperty;
+use crate::features::map::{MappedCDDAId, MappedCDDAIdsForTile, MappingKind};
+use crate::features::program_data::{
+    MapDataCollection, MappedCDDAIdContainer, Project, ZLevel,
+};
+use crate::util::IVec3JsonKey;
+use cdda_lib::types::CDDAIdentifier;
+use glam::{IVec2, IVec3, UVec2};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub const DOOR_FLAG: &str = "DOOR";
+pub const WALL_FLAG: &str = "WALL";
+pub const INDOORS_FLAG: &str = "INDOORS";
+pub const GOES_UP_FLAG: &str = "GOES_UP";
+pub const GOES_DOWN_FLAG: &str = "GOES_DOWN";
+
+/// The score of a map without any findings
+pub const MAX_SCORE: u32 = 100;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FindingKind {
+    UnreachableArea,
+    BlockedDoor,
+    RooflessIndoors,
+    MissingOutdoorLighting,
+    EmptyItemGroup,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FindingSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl FindingSeverity {
+    /// How many points a finding of this severity costs
+    pub fn penalty(&self) -> u32 {
+        match self {
+            FindingSeverity::Info => 1,
+            FindingSeverity::Warning => 5,
+            FindingSeverity::Error => 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MapFinding {
+    pub kind: FindingKind,
+    pub severity: FindingSeverity,
+    /// Describes the issue and how it can be fixed
+    pub message: String,
+    /// The global cells the finding applies to. Empty if it applies to the
+    /// whole z-level.
+    pub positions: Vec<IVec3JsonKey>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MapAnalysis {
+    /// Starts at 100 and goes down for every finding depending on its
+    /// severity
+    pub score: u32,
+    pub findings: Vec<MapFinding>,
+}
+
+impl MapAnalysis {
+    pub fn new(findings: Vec<MapFinding>) -> Self {
+        let penalty: u32 = findings.iter().map(|f| f.severity.penalty()).sum();
+
+        Self {
+            score: MAX_SCORE.saturating_sub(penalty),
+            findings,
+        }
+    }
+}
+
+fn has_flag(flags: &[String], flag: &str) -> bool {
+    flags.iter().any(|f| f == flag)
+}
+
+fn get_terrain<'a>(
+    mapped_id: &Option<MappedCDDAId>,
+    json_data: &'a DeserializedCDDAJsonData,
+) -> Option<&'a CDDATerrain> {
+    let id = mapped_id.as_ref()?;
+
+    json_data.terrain.get(&id.tilesheet_id.id)
+}
+
+fn get_furniture<'a>(
+    mapped_id: &Option<MappedCDDAId>,
+    json_data: &'a DeserializedCDDAJsonData,
+) -> Option<&'a CDDAFurniture> {
+    let id = mapped_id.as_ref()?;
+
+    json_data.furniture.get(&id.tilesheet_id.id)
+}
+
+/// Returns true if the terrain or furniture of the tile has the `flag`
+fn tile_has_flag(
+    ids: &MappedCDDAIdsForTile,
+    flag: &str,
+    json_data: &DeserializedCDDAJsonData,
+) -> bool {
+    let terrain = get_terrain(&ids.terrain, json_data)
+        .is_some_and(|t| has_flag(&t.flags, flag));
+    let furniture = get_furniture(&ids.furniture, json_data)
+        .is_some_and(|f| has_flag(&f.flags, flag));
+
+    terrain || furniture
+}
+
+/// A tile can be walked on if it is a door, or if neither its terrain nor its
+/// furniture block movement. Ids which could not be found are treated as
+/// walkable
+fn is_walkable(
+    ids: &MappedCDDAIdsForTile,
+    json_data: &DeserializedCDDAJsonData,
+) -> bool {
+    if tile_has_flag(ids, DOOR_FLAG, json_data) {
+        return true;
+    }
+
+    let terrain_walkable = match get_terrain(&ids.terrain, json_data) {
+        None => true,
+        Some(t) => t.move_cost != Some(0) && !has_flag(&t.flags, WALL_FLAG),
+    };
+
+    let furniture_walkable = match get_furniture(&ids.furniture, json_data) {
+        None => true,
+        Some(f) => f.move_cost_mod != Some(-1),
+    };
+
+    terrain_walkable && furniture_walkable
+}
+
+fn to_keys(positions: impl IntoIterator<Item = IVec3>) -> Vec<IVec3JsonKey> {
+    let mut positions: Vec<IVec3> = positions.into_iter().collect();
+    positions.sort_by_key(|p| (p.z, p.y, p.x));
+
+    positions.into_iter().map(IVec3JsonKey).collect()
+}
+
+const NEIGHBORS: [IVec2; 8] = [
+    IVec2::new(-1, -1),
+    IVec2::new(0, -1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 0),
+    IVec2::new(1, 0),
+    IVec2::new(-1, 1),
+    IVec2::new(0, 1),
+    IVec2::new(1, 1),
+];
+
+/// Finds the groups of walkable cells which can not be reached from the edge
+/// of the z-level or from a staircase
+fn find_unreachable_areas(
+    container: &MappedCDDAIdContainer,
+    json_data: &DeserializedCDDAJsonData,
+) -> Vec<MapFinding> {
+    let walkable: HashSet<IVec3> = container
+        .ids
+        .iter()
+        .filter(|(_, ids)| is_walkable(ids, json_data))
+        .map(|(position, _)| position.clone())
+        .collect();
+
+    let Some(first) = container.ids.keys().next() else {
+        return vec![];
+    };
+
+    let (min, max) = container
+        .ids
+        .keys()
+        .fold((first.clone(), first.clone()), |(min, max), p| {
+            (min.min(*p), max.max(*p))
+        });
+
+    let is_entry = |position: &IVec3| {
+        let ids = &container.ids[position];
+
+        position.x == min.x
+            || position.y == min.y
+            || position.x == max.x
+            || position.y == max.y
+            || tile_has_flag(ids, GOES_UP_FLAG, json_data)
+            || tile_has_flag(ids, GOES_DOWN_FLAG, json_data)
+    };
+
+    let flood_fill = |start: Vec<IVec3>, visited: &mut HashSet<IVec3>| {
+        let mut queue = VecDeque::from(start);
+        let mut area = vec![];
+
+        while let Some(position) = queue.pop_front() {
+            area.push(position);
+
+            for offset in NEIGHBORS.iter() {
+                let neighbor = position + offset.extend(0);
+
+                if walkable.contains(&neighbor) && visited.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        area
+    };
+
+    let mut visited: HashSet<IVec3> =
+        walkable.iter().filter(|p| is_entry(p)).cloned().collect();
+    flood_fill(visited.iter().cloned().collect(), &mut visited);
+
+    let mut findings = vec![];
+
+    for position in to_keys(walkable.iter().cloned()) {
+        if !visited.insert(position.0) {
+            continue;
+        }
+
+        let area = flood_fill(vec![position.0], &mut visited);
+
+        findings.push(MapFinding {
+            kind: FindingKind::UnreachableArea,
+            severity: FindingSeverity::Warning,
+            message: format!(
+                "{} walkable cells can not be reached from the edge of the \
+                 map or a staircase. Add a door or remove a wall to connect \
+                 them.",
+                area.len()
+            ),
+            positions: to_keys(area),
+        });
+    }
+
+    findings
+}
+
+/// Finds doors which do not have walkable cells on two opposite sides, so
+/// they open into a wall
+fn find_blocked_doors(
+    container: &MappedCDDAIdContainer,
+    json_data: &DeserializedCDDAJsonData,
+) -> Option<MapFinding> {
+    // Cells outside of the map are treated as walkable, since the map next
+    // to it is not known
+    let is_walkable_at = |position: IVec3| match container.ids.get(&position) {
+        None => true,
+        Some(ids) => is_walkable(ids, json_data),
+    };
+
+    let blocked: Vec<IVec3> = container
+        .ids
+        .iter()
+        .filter(|(_, ids)| tile_has_flag(ids, DOOR_FLAG, json_data))
+        .filter(|(position, _)| {
+            let vertical = is_walkable_at(**position + IVec3::Y)
+                && is_walkable_at(**position - IVec3::Y);
+            let horizontal = is_walkable_at(**position + IVec3::X)
+                && is_walkable_at(**position - IVec3::X);
+
+            !vertical && !horizontal
+        })
+        .map(|(position, _)| position.clone())
+        .collect();
+
+    if blocked.is_empty() {
+        return None;
+    }
+
+    Some(MapFinding {
+        kind: FindingKind::BlockedDoor,
+        severity: FindingSeverity::Warning,
+        message: format!(
+            "{} doors do not have walkable cells on two opposite sides. Doors \
+             should sit inside of a wall with floor on both sides.",
+            blocked.len()
+        ),
+        positions: to_keys(blocked),
+    })
+}
+
+/// Finds indoor cells whose terrain does not define a roof and which do not
+/// have anything above them
+fn find_roofless_indoors(
+    z: ZLevel,
+    container: &MappedCDDAIdContainer,
+    above: Option<&MappedCDDAIdContainer>,
+    json_data: &DeserializedCDDAJsonData,
+) -> Option<MapFinding> {
+    let roofless: Vec<IVec3> = container
+        .ids
+        .iter()
+        .filter(|(position, ids)| {
+            let Some(terrain) = get_terrain(&ids.terrain, json_data) else {
+                return false;
+            };
+
+            if !has_flag(&terrain.flags, INDOORS_FLAG) || terrain.roof.is_some()
+            {
+                return false;
+            }
+
+            !above.is_some_and(|above| {
+                above
+                    .ids
+                    .get(&(**position + IVec3::Z))
+                    .is_some_and(|ids| ids.terrain.is_some())
+            })
+        })
+        .map(|(position, _)| position.clone())
+        .collect();
+
+    if roofless.is_empty() {
+        return None;
+    }
+
+    Some(MapFinding {
+        kind: FindingKind::RooflessIndoors,
+        severity: FindingSeverity::Warning,
+        message: format!(
+            "{} indoor cells on z-level {} have no roof. Use terrain with a \
+             roof or add a roof on the z-level above.",
+            roofless.len(),
+            z
+        ),
+        positions: to_keys(roofless),
+    })
+}
+
+/// Finds z-levels with buildings where nothing outside emits light, so the
+/// surroundings are pitch black at night
+fn find_missing_outdoor_lighting(
+    z: ZLevel,
+    container: &MappedCDDAIdContainer,
+    json_data: &DeserializedCDDAJsonData,
+) -> Option<MapFinding> {
+    let mut has_indoors = false;
+    let mut has_outdoors = false;
+
+    for ids in container.ids.values() {
+        let Some(terrain) = get_terrain(&ids.terrain, json_data) else {
+            continue;
+        };
+
+        if has_flag(&terrain.flags, INDOORS_FLAG) {
+            has_indoors = true;
+            continue;
+        }
+
+        has_outdoors = true;
+
+        let terrain_light = terrain.light_emitted.unwrap_or_default();
+        let furniture_light = get_furniture(&ids.furniture, json_data)
+            .and_then(|f| f.light_emitted)
+            .unwrap_or_default();
+
+        if terrain_light > 0 || furniture_light > 0 {
+            return None;
+        }
+    }
+
+    if !has_indoors || !has_outdoors {
+        return None;
+    }
+
+    Some(MapFinding {
+        kind: FindingKind::MissingOutdoorLighting,
+        severity: FindingSeverity::Info,
+        message: format!(
+            "Nothing outside of the buildings on z-level {} emits light. \
+             Consider adding street lights or lit terrain.",
+            z
+        ),
+        positions: vec![],
+    })
+}
+
+/// Describes why the item group of the `item` can never spawn anything in
+/// the `region`
+fn empty_item_group_reason(
+    item: &MapGenItem,
+    json_data: &DeserializedCDDAJsonData,
+    region: Option<&CDDAIdentifier>,
+) -> Option<(FindingSeverity, String)> {
+    if item.chance.as_ref().is_some_and(|c| c.max() == 0) {
+        return Some((
+            FindingSeverity::Warning,
+            "has a chance of 0".to_string(),
+        ));
+    }
+
+    if item.repeat.as_ref().is_some_and(|r| r.max() == 0) {
+        return Some((
+            FindingSeverity::Warning,
+            "is repeated 0 times".to_string(),
+        ));
+    }
+
+    match &item.item {
+        ReferenceOrInPlace::Reference(id) => match json_data
+            .item_groups
+            .get(&json_data.resolve_region_setting(id, region))
+        {
+            None => Some((
+                FindingSeverity::Error,
+                format!("references the unknown item group {}", id),
+            )),
+            Some(group) if group.common.entries.is_empty() => Some((
+                FindingSeverity::Warning,
+                format!(
+                    "references the item group {} which has no entries",
+                    id
+                ),
+            )),
+            Some(_) => None,
+        },
+        ReferenceOrInPlace::InPlace(group) => {
+            let is_empty = group.common.entries.is_empty()
+                && group.items.is_empty()
+                && group.groups.is_empty();
+
+            is_empty.then(|| {
+                (
+                    FindingSeverity::Warning,
+                    "defines an item group without entries".to_string(),
+                )
+            })
+        },
+    }
+}
+
+/// Finds item mappings and place entries of the `project` which can never
+/// spawn an item
+fn find_empty_item_groups(
+    project: &Project,
+    json_data: &DeserializedCDDAJsonData,
+) -> Vec<MapFinding> {
+    let mut findings = vec![];
+
+    for (z, collection) in project.maps.iter() {
+        for (map_coordinates, map_data) in collection.maps.iter() {
+            let to_global = |x: u32, y: u32| {
+                MapDataCollection::map_to_global_cell_coords(
+                    map_coordinates,
+                    &UVec2::new(x, y),
+                    *z,
+                )
+            };
+
+            let mut cells_by_character: HashMap<char, Vec<IVec3>> =
+                HashMap::new();
+
+            for (coordinates, cell) in map_data.cells.iter() {
+                cells_by_character
+                    .entry(cell.character)
+                    .or_default()
+                    .push(to_global(coordinates.x, coordinates.y));
+            }
+
+            for (character, positions) in cells_by_character {
+                let Some(property) = map_data.get_property(
+                    &MappingKind::ItemGroups,
+                    &character,
+                    json_data,
+                ) else {
+                    continue;
+                };
+                let Some(property) = property.downcast_ref::<ItemsProperty>()
+                else {
+                    continue;
+                };
+
+                for item in property.items.iter() {
+                    let Some((severity, reason)) = empty_item_group_reason(
+                        &item.data,
+                        json_data,
+                        project.region.as_ref(),
+                    ) else {
+                        continue;
+                    };
+
+                    findings.push(MapFinding {
+                        kind: FindingKind::EmptyItemGroup,
+                        severity,
+                        message: format!(
+                            "The items mapped to '{}' will never spawn, the \
+                             mapping {}.",
+                            character, reason
+                        ),
+                        positions: to_keys(positions.iter().cloned()),
+                    });
+                }
+            }
+
+            let place = map_data
+                .place
+                .get(&MappingKind::ItemGroups)
+                .into_iter()
+                .flatten();
+
+            for entry in place {
+                let Some(place_items) = entry.inner.downcast_ref::<PlaceItems>()
+                else {
+                    continue;
+                };
+
+                let reason = match entry.chance <= 0 || entry.repeat.max() <= 0
+                {
+                    true => Some((
+                        FindingSeverity::Warning,
+                        "has a chance or repeat of 0".to_string(),
+                    )),
+                    false => place_items.property.items.iter().find_map(|i| {
+                        empty_item_group_reason(
+                            &i.data,
+                            json_data,
+                            project.region.as_ref(),
+                        )
+                    }),
+                };
+
+                let Some((severity, reason)) = reason else {
+                    continue;
+                };
+
+                let position = to_global(
+                    entry.x.min().max(0) as u32,
+                    entry.y.min().max(0) as u32,
+                );
+
+                findings.push(MapFinding {
+                    kind: FindingKind::EmptyItemGroup,
+                    severity,
+                    message: format!(
+                        "The items placed at {},{} will never spawn, the \
+                         place entry {}.",
+                        position.x, position.y, reason
+                    ),
+                    positions: vec![IVec3JsonKey(position)],
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+/// Scores the rendered `mapped_cdda_ids` and the maps of the `project` with
+/// a set of heuristics for issues new mappers commonly run into
+pub fn analyze_map(
+    project: &Project,
+    mapped_cdda_ids: &HashMap<ZLevel, MappedCDDAIdContainer>,
+    json_data: &DeserializedCDDAJsonData,
+) -> MapAnalysis {
+    // Regional ids are resolved once with the region of the project, the same
+    // way the render resolves them
+    let mapped_cdda_ids: HashMap<ZLevel, MappedCDDAIdContainer> =
+        match json_data.get_region_settings(project.region.as_ref()) {
+            None => mapped_cdda_ids.clone(),
+            Some(region_settings) => mapped_cdda_ids
+                .iter()
+                .map(|(z, container)| {
+                    (
+                        *z,
+                        container
+                            .with_region_settings_replaced(region_settings),
+                    )
+                })
+                .collect(),
+        };
+
+    let mut z_levels: Vec<&ZLevel> = mapped_cdda_ids.keys().collect();
+    z_levels.sort();
+
+    let mut findings = vec![];
+
+    for z in z_levels {
+        let container = &mapped_cdda_ids[z];
+
+        findings.extend(find_unreachable_areas(container, json_data));
+        findings.extend(find_blocked_doors(container, json_data));
+        findings.extend(find_roofless_indoors(
+            *z,
+            container,
+            mapped_cdda_ids.get(&(z + 1)),
+            json_data,
+        ));
+        findings.extend(find_missing_outdoor_lighting(
+            *z, container, json_data,
+        ));
+    }
+
+    findings.extend(find_empty_item_groups(project, json_data));
+
+    MapAnalysis::new(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::item::CDDAItemGroup;
+    use crate::features::map::{Cell, MapData, Property};
+    use crate::features::tileset::legacy_tileset::TilesheetCDDAId;
+    use cdda_lib::types::Weighted;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    fn test_json_data() -> DeserializedCDDAJsonData {
+        let mut json_data = DeserializedCDDAJsonData::default();
+
+        let terrain = [
+            json!({ "id": "t_grass", "flags": [] }),
+            json!({ "id": "t_floor", "flags": ["INDOORS"], "roof": "t_roof" }),
+            json!({ "id": "t_floor_roofless", "flags": ["INDOORS"] }),
+            json!({ "id": "t_wall", "flags": ["WALL"], "move_cost": 0 }),
+            json!({ "id": "t_door_c", "flags": ["DOOR"], "move_cost": 0 }),
+            json!({ "id": "t_stairs_down", "flags": ["GOES_DOWN"] }),
+        ];
+
+        for terrain in terrain {
+            let terrain: CDDATerrain = serde_json::from_value(terrain).unwrap();
+            json_data.terrain.insert(terrain.id.clone(), terrain);
+        }
+
+        let lamp: CDDAFurniture = serde_json::from_value(
+            json!({ "id": "f_lamp", "flags": [], "light_emitted": 10 }),
+        )
+        .unwrap();
+        json_data.furniture.insert(lamp.id.clone(), lamp);
+
+        let groups = [
+            json!({ "id": "empty_group", "entries": [] }),
+            json!({ "id": "trash", "entries": [{ "item": "can_food" }] }),
+        ];
+
+        for group in groups {
+            let group: CDDAItemGroup = serde_json::from_value(group).unwrap();
+            json_data.item_groups.insert(group.id.clone(), group);
+        }
+
+        json_data
+    }
+
+    fn mapped_id(id: &str) -> Option<MappedCDDAId> {
+        Some(MappedCDDAId::simple(TilesheetCDDAId::simple(id)))
+    }
+
+    /// Builds the z-level `z` from `rows`, where ' ' is grass, '.' is floor
+    /// with a roof, ',' is floor without a roof, '#' is a wall, '+' is a
+    /// door, '>' is a staircase and 'L' is a lamp on grass
+    fn container(z: ZLevel, rows: &[&str]) -> MappedCDDAIdContainer {
+        let mut ids = HashMap::new();
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, character) in row.chars().enumerate() {
+                let (terrain, furniture) = match character {
+                    ' ' => ("t_grass", None),
+                    '.' => ("t_floor", None),
+                    ',' => ("t_floor_roofless", None),
+                    '#' => ("t_wall", None),
+                    '+' => ("t_door_c", None),
+                    '>' => ("t_stairs_down", None),
+                    'L' => ("t_grass", mapped_id("f_lamp")),
+                    _ => panic!("Unknown character {}", character),
+                };
+
+                ids.insert(
+                    IVec3::new(x as i32, y as i32, z),
+                    MappedCDDAIdsForTile {
+                        terrain: mapped_id(terrain),
+                        furniture,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        MappedCDDAIdContainer { ids }
+    }
+
+    fn positions(finding: &MapFinding) -> Vec<IVec3> {
+        finding.positions.iter().map(|p| p.0).collect()
+    }
+
+    fn map_item(item: Value) -> Weighted<MapGenItem> {
+        Weighted::new(serde_json::from_value::<MapGenItem>(item).unwrap(), 1)
+    }
+
+    /// A project with a single map whose cell at 1,1 is 'i', which is
+    /// mapped to the `items`
+    fn item_project(items: Vec<Weighted<MapGenItem>>) -> Project {
+        let mut map_data = MapData::default();
+        map_data
+            .cells
+            .insert(UVec2::new(1, 1), Cell { character: 'i' });
+        map_data.properties.insert(
+            MappingKind::ItemGroups,
+            HashMap::from([(
+                'i',
+                Arc::new(ItemsProperty { items }) as Arc<dyn Property>,
+            )]),
+        );
+
+        let mut project = Project::default();
+        project.maps.insert(
+            0,
+            MapDataCollection {
+                maps: HashMap::from([(UVec2::ZERO, map_data)]),
+            },
+        );
+
+        project
+    }
+
+    #[test]
+    fn test_score_goes_down_by_severity() {
+        let finding = |severity| MapFinding {
+            kind: FindingKind::UnreachableArea,
+            severity,
+            message: String::new(),
+            positions: vec![],
+        };
+
+        let analysis = MapAnalysis::new(vec![
+            finding(FindingSeverity::Info),
+            finding(FindingSeverity::Warning),
+            finding(FindingSeverity::Error),
+        ]);
+        assert_eq!(analysis.score, 79);
+
+        let analysis = MapAnalysis::new(
+            (0..10).map(|_| finding(FindingSeverity::Error)).collect(),
+        );
+        assert_eq!(analysis.score, 0);
+    }
+
+    #[test]
+    fn test_unreachable_areas() {
+        let json_data = test_json_data();
+
+        let closed =
+            container(0, &["      ", " #### ", " #..# ", " #### ", "      "]);
+        let findings = find_unreachable_areas(&closed, &json_data);
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(
+            positions(&findings[0]),
+            vec![IVec3::new(2, 2, 0), IVec3::new(3, 2, 0)]
+        );
+
+        let with_door =
+            container(0, &["      ", " #+## ", " #..# ", " #### ", "      "]);
+        assert!(find_unreachable_areas(&with_door, &json_data).is_empty());
+
+        let with_stairs =
+            container(0, &["      ", " #### ", " #>.# ", " #### ", "      "]);
+        assert!(find_unreachable_areas(&with_stairs, &json_data).is_empty());
+    }
+
+    #[test]
+    fn test_blocked_doors() {
+        let json_data = test_json_data();
+
+        let walled_in =
+            container(0, &["     ", " ### ", " #+# ", " ### ", "     "]);
+        let finding = find_blocked_doors(&walled_in, &json_data).unwrap();
+
+        assert_eq!(positions(&finding), vec![IVec3::new(2, 2, 0)]);
+
+        let in_wall = container(0, &["     ", " #+# ", "     "]);
+        assert!(find_blocked_doors(&in_wall, &json_data).is_none());
+
+        // The cells next to the map are not known, so they count as
+        // walkable
+        let at_edge = container(0, &["#+#", "   "]);
+        assert!(find_blocked_doors(&at_edge, &json_data).is_none());
+    }
+
+    #[test]
+    fn test_roofless_indoors() {
+        let json_data = test_json_data();
+        let ground = container(0, &["..,,"]);
+
+        let finding =
+            find_roofless_indoors(0, &ground, None, &json_data).unwrap();
+        assert_eq!(
+            positions(&finding),
+            vec![IVec3::new(2, 0, 0), IVec3::new(3, 0, 0)]
+        );
+
+        // Anything above the roofless floor counts as its roof
+        let above = container(1, &["  ##"]);
+        assert!(
+            find_roofless_indoors(0, &ground, Some(&above), &json_data)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_missing_outdoor_lighting() {
+        let json_data = test_json_data();
+
+        let dark = container(0, &["  .."]);
+        let finding =
+            find_missing_outdoor_lighting(0, &dark, &json_data).unwrap();
+        assert!(finding.positions.is_empty());
+
+        let lit = container(0, &[" L.."]);
+        assert!(find_missing_outdoor_lighting(0, &lit, &json_data).is_none());
+
+        // Without a building the darkness is expected
+        let field = container(0, &["    "]);
+        assert!(find_missing_outdoor_lighting(0, &field, &json_data).is_none());
+    }
+
+    #[test]
+    fn test_empty_item_groups() {
+        let json_data = test_json_data();
+
+        let severities = |items: Vec<Weighted<MapGenItem>>| {
+            find_empty_item_groups(&item_project(items), &json_data)
+                .into_iter()
+                .map(|f| (f.severity.clone(), positions(&f)))
+                .collect::<Vec<_>>()
+        };
+
+        let findings = severities(vec![
+            map_item(json!({ "item": "missing_group" })),
+            map_item(json!({ "item": "empty_group" })),
+            map_item(json!({ "item": "trash", "chance": 0 })),
+            map_item(json!({ "item": "trash", "repeat": 0 })),
+            map_item(json!({ "item": { "entries": [] } })),
+        ]);
+
+        assert!(matches!(
+            findings.as_slice(),
+            [
+                (FindingSeverity::Error, _),
+                (FindingSeverity::Warning, _),
+                (FindingSeverity::Warning, _),
+                (FindingSeverity::Warning, _),
+                (FindingSeverity::Warning, _),
+            ]
+        ));
+        assert!(
+            findings
+                .iter()
+                .all(|(_, p)| p.as_slice() == [IVec3::new(1, 1, 0)])
+        );
+
+        let findings = severities(vec![
+            map_item(json!({ "item": "trash" })),
+            map_item(json!({ "item": "trash", "chance": 50, "repeat": 2 })),
+        ]);
+        assert!(findings.is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+use crate::events::{EmitEvent, TabRemovedEvent};
+use crate::features::archive::{
+    ArchiveError, ProjectArchiveLoader, ProjectArchiveSaver,
+};
+use crate::features::program_data::changes::{
+    EditorDataChanges, EditorDataScope,
+};
+use crate::features::program_data::io::ProgramDataSaver;
+use crate::features::program_data::{EditorData, ProjectName, RecentProject};
+use crate::features::viewer::cache::RenderCache;
+use crate::impl_serialize_for_error;
+use crate::util::{is_valid_file_name, Load, Save, SaveError};
+use serde::{Serialize, Serializer};
+use std::fs;
+use std::ops::Deref;
+use std::path::PathBuf;
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, State};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ArchiveProjectError {
+    #[error("No project with the name {0} is loaded")]
+    UnknownProject(ProjectName),
+
+    #[error("Failed to save, {0}")]
+    SaveError(#[from] SaveError),
+}
+
+impl_serialize_for_error!(ArchiveProjectError);
+
+/// Writes the loaded project with the `name` and all files it references into
+/// a single compressed archive at `path`. If `unload` is set, the project is
+/// closed and removed from the loaded projects afterward.
+#[tauri::command]
+pub async fn archive_project(
+    name: ProjectName,
+    path: PathBuf,
+    unload: bool,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<(), ArchiveProjectError> {
+    let mut editor_data_lock = editor_data.lock().await;
+
+    let project = editor_data_lock
+        .loaded_projects
+        .get(&name)
+        .ok_or(ArchiveProjectError::UnknownProject(name.clone()))?;
+
+    let archive_saver = ProjectArchiveSaver { path };
+    archive_saver.save(project).await?;
+
+    if !unload {
+        return Ok(());
+    }
+
+    if editor_data_lock.opened_project.as_ref() == Some(&name) {
+        editor_data_lock.opened_project = None;
+        app.emit_event(TabRemovedEvent { name: name.clone() })
+            .unwrap();
+    }
+
+    editor_data_lock.loaded_projects.remove(&name);
+    editor_data_lock.openable_projects.remove(&name);
+    render_cache.invalidate_project(&name);
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    saver.save(editor_data_lock.deref()).await?;
+
+    editor_data_changes.notify(
+        &app,
+        &[EditorDataScope::OpenedProject, EditorDataScope::ProjectList],
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum RestoreProjectError {
+    #[error(transparent)]
+    ArchiveError(#[from] ArchiveError),
+
+    #[error("A project with the name {0} already exists")]
+    ProjectAlreadyExists(ProjectName),
+
+    #[error("The archived project name {0} can not be used as a file name")]
+    InvalidProjectName(ProjectName),
+
+    #[error("Failed to save, {0}")]
+    SaveError(#[from] SaveError),
+}
+
+impl_serialize_for_error!(RestoreProjectError);
+
+/// Restores the project in the archive at `path` and adds it to the recent
+/// projects. The files of the project are written into `restore_dir`.
+#[tauri::command]
+pub async fn restore_project(
+    path: PathBuf,
+    restore_dir: PathBuf,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+) -> Result<ProjectName, RestoreProjectError> {
+    let mut editor_data_lock = editor_data.lock().await;
+
+    let mut loader = ProjectArchiveLoader { path };
+    let archive = loader.load().await?;
+    let name = &archive.project.name;
+
+    // Nothing is written before it is clear that the project can be restored.
+    // The name comes from the archive and is used as the name of the project
+    // file, so it must not point outside of the config directory
+    if !is_valid_file_name(name) {
+        return Err(RestoreProjectError::InvalidProjectName(name.clone()));
+    }
+
+    let name_taken = editor_data_lock.loaded_projects.contains_key(name)
+        || editor_data_lock
+            .recent_projects
+            .iter()
+            .any(|p| &p.name == name);
+
+    if name_taken {
+        return Err(RestoreProjectError::ProjectAlreadyExists(name.clone()));
+    }
+
+    let project = archive.restore(&restore_dir)?;
+
+    let config_path = editor_data_lock.config.config_path.clone();
+
+    fs::write(
+        config_path.join(format!("{}.json", project.name)),
+        serde_json::to_string_pretty(&project).map_err(SaveError::from)?,
+    )
+    .map_err(SaveError::from)?;
+
+    editor_data_lock.recent_projects.insert(RecentProject {
+        path: config_path.clone(),
+        name: project.name.clone(),
+        thumbnail: None,
+    });
+
+    let saver = ProgramDataSaver { path: config_path };
+    saver.save(editor_data_lock.deref()).await?;
+
+    editor_data_changes.notify(&app, &[EditorDataScope::ProjectList]);
+
+    Ok(project.name)
+}
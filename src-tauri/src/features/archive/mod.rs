@@ -0,0 +1,178 @@
+pub(crate) mod handlers;
+
+use crate::features::program_data::{
+    LiveViewerData, Project, ProjectSaveState, ProjectType,
+};
+use crate::util::{Load, Save, SaveError};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Version of the archive format, increased when the format changes in a way
+/// that older versions cannot read
+pub const PROJECT_ARCHIVE_VERSION: u32 = 1;
+
+/// Compression level which is passed to zstd
+const ARCHIVE_COMPRESSION_LEVEL: i32 = 10;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedFile {
+    /// The path of the file when it was archived
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// A project together with all files it references, so it can be backed up or
+/// shared as a single file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectArchive {
+    pub version: u32,
+    pub project: Project,
+    pub files: Vec<ArchivedFile>,
+}
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("Failed to read or write the archive, {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The archive is not a valid project archive, {0}")]
+    InvalidContent(#[from] serde_json::Error),
+
+    #[error("The archive was created with the unsupported version {0}")]
+    UnsupportedVersion(u32),
+}
+
+/// All files outside the editor directory which are needed to open the
+/// `project`
+pub fn referenced_files(project: &mut Project) -> Vec<&mut PathBuf> {
+    match &mut project.ty {
+        ProjectType::MapEditor(ProjectSaveState::Unsaved) => vec![],
+        ProjectType::MapEditor(ProjectSaveState::Saved { path }) => vec![path],
+        ProjectType::LiveViewer(LiveViewerData::Terrain {
+            mapgen_file_paths,
+            ..
+        }) => mapgen_file_paths.iter_mut().collect(),
+        ProjectType::LiveViewer(LiveViewerData::Special {
+            mapgen_file_paths,
+            om_file_paths,
+            ..
+        }) => mapgen_file_paths
+            .iter_mut()
+            .chain(om_file_paths.iter_mut())
+            .collect(),
+        ProjectType::LiveViewer(LiveViewerData::Linear {
+            mapgen_file_paths,
+            ..
+        })
+        | ProjectType::LiveViewer(LiveViewerData::Nested {
+            mapgen_file_paths,
+            ..
+        })
+        | ProjectType::LiveViewer(LiveViewerData::Update {
+            mapgen_file_paths,
+            ..
+        }) => mapgen_file_paths.iter_mut().collect(),
+    }
+}
+
+/// Writes a project and its referenced files into a zstd compressed archive
+pub struct ProjectArchiveSaver {
+    pub path: PathBuf,
+}
+
+impl Save<Project> for ProjectArchiveSaver {
+    async fn save(&self, data: &Project) -> Result<(), SaveError> {
+        let mut project = data.clone();
+        let mut files = vec![];
+
+        for path in referenced_files(&mut project) {
+            files.push(ArchivedFile {
+                path: path.clone(),
+                content: fs::read_to_string(&path)?,
+            });
+        }
+
+        let archive = ProjectArchive {
+            version: PROJECT_ARCHIVE_VERSION,
+            project,
+            files,
+        };
+
+        let serialized = serde_json::to_vec(&archive)?;
+        let compressed =
+            zstd::encode_all(serialized.as_slice(), ARCHIVE_COMPRESSION_LEVEL)?;
+
+        fs::write(&self.path, compressed)?;
+        info!("Archived project {} to {}", data.name, self.path.display());
+
+        Ok(())
+    }
+}
+
+/// Reads an archive without writing any of its files
+pub struct ProjectArchiveLoader {
+    pub path: PathBuf,
+}
+
+impl Load<ProjectArchive, ArchiveError> for ProjectArchiveLoader {
+    async fn load(&mut self) -> Result<ProjectArchive, ArchiveError> {
+        let compressed = fs::read(&self.path)?;
+        let decompressed = zstd::decode_all(compressed.as_slice())?;
+        let archive: ProjectArchive = serde_json::from_slice(&decompressed)?;
+
+        if archive.version > PROJECT_ARCHIVE_VERSION {
+            return Err(ArchiveError::UnsupportedVersion(archive.version));
+        }
+
+        Ok(archive)
+    }
+}
+
+impl ProjectArchive {
+    /// Restores the project of the archive. The archived files are written
+    /// into `restore_dir` and the project is changed to reference them there.
+    pub fn restore(
+        mut self,
+        restore_dir: &Path,
+    ) -> Result<Project, ArchiveError> {
+        fs::create_dir_all(restore_dir)?;
+
+        let mut used_names = HashSet::new();
+
+        for (file, path) in self
+            .files
+            .iter()
+            .zip(referenced_files(&mut self.project))
+        {
+            let file_name = file
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "file.json".to_string());
+
+            // Files from different directories can have the same name
+            let mut restored_name = file_name.clone();
+            let mut n = 1;
+            while !used_names.insert(restored_name.clone()) {
+                restored_name = format!("{}_{}", n, file_name);
+                n += 1;
+            }
+
+            let restored_path = restore_dir.join(restored_name);
+            fs::write(&restored_path, &file.content)?;
+            *path = restored_path;
+        }
+
+        info!(
+            "Restored project {} into {}",
+            self.project.name,
+            restore_dir.display()
+        );
+
+        Ok(self.project)
+    }
+}
@@ -0,0 +1,142 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::catalog::{
+    overmap_special_mapgen_sources, vanilla_map_catalog, VanillaMapEntry,
+};
+use crate::features::program_data::changes::EditorDataChanges;
+use crate::features::program_data::EditorData;
+use crate::features::viewer::handlers::{
+    create_viewer, OpenViewerData, OpenViewerError,
+};
+use crate::impl_serialize_for_error;
+use crate::util::{get_json_data, CDDADataError};
+use cdda_lib::types::CDDAIdentifier;
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, State};
+use thiserror::Error;
+
+/// Lists every map of the loaded CDDA data, so it can be browsed without
+/// pointing the editor at any files
+#[tauri::command]
+pub async fn get_vanilla_map_catalog(
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<Vec<VanillaMapEntry>, CDDADataError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    Ok(vanilla_map_catalog(json_data))
+}
+
+#[derive(Debug, Error)]
+pub enum OpenVanillaMapError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error("Could not find the file the map {0} is defined in")]
+    NoSource(CDDAIdentifier),
+
+    #[error("Could not find the overmap special {0}")]
+    NoOvermapSpecial(CDDAIdentifier),
+
+    #[error("Could not find the mapgen of any overmap terrain of {0}")]
+    NoSpecialMapgens(CDDAIdentifier),
+
+    #[error(transparent)]
+    OpenViewerError(#[from] OpenViewerError),
+}
+
+impl_serialize_for_error!(OpenVanillaMapError);
+
+/// Opens a map of the loaded CDDA data in a live viewer, which never changes
+/// the file the map is defined in
+#[tauri::command]
+pub async fn open_vanilla_map(
+    om_id: CDDAIdentifier,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<(), OpenVanillaMapError> {
+    let source = {
+        let json_data_lock = json_data.lock().await;
+        let json_data = get_json_data(&json_data_lock)?;
+
+        json_data
+            .sources
+            .get("mapgen")
+            .and_then(|s| s.get(&om_id))
+            .cloned()
+            .ok_or(OpenVanillaMapError::NoSource(om_id.clone()))?
+    };
+
+    create_viewer(
+        app,
+        OpenViewerData::Terrain {
+            mapgen_file_paths: vec![source],
+            project_name: om_id.0.clone(),
+            om_id,
+            stacked_om_ids: BTreeMap::new(),
+            update_ids: vec![],
+        },
+        editor_data,
+        editor_data_changes,
+        json_data,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Opens an overmap special of the loaded CDDA data in a live viewer which
+/// places every overmap terrain of the special at its point on every z-level
+#[tauri::command]
+pub async fn open_vanilla_special(
+    special_id: CDDAIdentifier,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<(), OpenVanillaMapError> {
+    let (om_file_path, mapgen_file_paths) = {
+        let json_data_lock = json_data.lock().await;
+        let json_data = get_json_data(&json_data_lock)?;
+
+        let special = json_data
+            .overmap_specials
+            .get(&special_id)
+            .ok_or(OpenVanillaMapError::NoOvermapSpecial(special_id.clone()))?;
+
+        let om_file_path = json_data
+            .sources
+            .get("overmap_special")
+            .and_then(|s| s.get(&special_id))
+            .cloned()
+            .ok_or(OpenVanillaMapError::NoSource(special_id.clone()))?;
+
+        let mapgen_file_paths =
+            overmap_special_mapgen_sources(json_data, special);
+
+        if mapgen_file_paths.is_empty() {
+            return Err(OpenVanillaMapError::NoSpecialMapgens(special_id));
+        }
+
+        (om_file_path, mapgen_file_paths)
+    };
+
+    create_viewer(
+        app,
+        OpenViewerData::Special {
+            mapgen_file_paths,
+            om_file_paths: vec![om_file_path],
+            project_name: special_id.0.clone(),
+            om_id: special_id,
+        },
+        editor_data,
+        editor_data_changes,
+        json_data,
+    )
+    .await?;
+
+    Ok(())
+}
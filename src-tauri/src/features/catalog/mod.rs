@@ -0,0 +1,102 @@
+pub(crate) mod handlers;
+
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::overmap::{CDDAOvermapSpecial, OvermapSpecialSubType};
+use crate::features::map::importing::resolve_om_terrain_id;
+use cdda_lib::types::{CDDAIdentifier, CDDAString};
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VanillaMapKind {
+    Terrain,
+    Update,
+    /// An overmap special, which is opened with all of its overmap terrains
+    Special,
+}
+
+/// A map of the loaded CDDA data which can be opened in a read-only viewer
+#[derive(Debug, Clone, Serialize)]
+pub struct VanillaMapEntry {
+    pub id: CDDAIdentifier,
+    /// Name of the overmap terrain with the same id, if there is one
+    pub name: Option<CDDAString>,
+    pub kind: VanillaMapKind,
+    pub source: Option<PathBuf>,
+}
+
+/// Every map and overmap special in the loaded CDDA data, sorted by its id
+pub fn vanilla_map_catalog(
+    json_data: &DeserializedCDDAJsonData,
+) -> Vec<VanillaMapEntry> {
+    let mapgen_sources = json_data.sources.get("mapgen");
+
+    let mut entries: Vec<VanillaMapEntry> = json_data
+        .map_data
+        .keys()
+        .map(|id| VanillaMapEntry {
+            id: id.clone(),
+            name: json_data
+                .overmap_terrains
+                .get(id)
+                .and_then(|t| t.name.clone()),
+            kind: match json_data.update_mapgen_ids.contains(id) {
+                true => VanillaMapKind::Update,
+                false => VanillaMapKind::Terrain,
+            },
+            source: mapgen_sources.and_then(|s| s.get(id)).cloned(),
+        })
+        .collect();
+
+    let special_sources = json_data.sources.get("overmap_special");
+
+    entries.extend(json_data.overmap_specials.keys().map(|id| {
+        VanillaMapEntry {
+            id: id.clone(),
+            name: None,
+            kind: VanillaMapKind::Special,
+            source: special_sources.and_then(|s| s.get(id)).cloned(),
+        }
+    }));
+
+    entries.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+    entries
+}
+
+/// The files the mapgens of every overmap terrain of the overmap special
+/// `special` are defined in, without duplicates
+pub fn overmap_special_mapgen_sources(
+    json_data: &DeserializedCDDAJsonData,
+    special: &CDDAOvermapSpecial,
+) -> Vec<PathBuf> {
+    let OvermapSpecialSubType::Fixed { overmaps } = &special.ty else {
+        return vec![];
+    };
+
+    let Some(mapgen_sources) = json_data.sources.get("mapgen") else {
+        return vec![];
+    };
+
+    let mut sources: Vec<PathBuf> = vec![];
+
+    for overmap in overmaps {
+        let Some(om_id) = overmap.overmap.clone() else {
+            continue;
+        };
+
+        let (base_id, _) = resolve_om_terrain_id(om_id.clone());
+
+        let source = mapgen_sources
+            .get(&om_id)
+            .or_else(|| mapgen_sources.get(&base_id));
+
+        if let Some(source) = source {
+            if !sources.contains(source) {
+                sources.push(source.clone());
+            }
+        }
+    }
+
+    sources
+}
@@ -0,0 +1,50 @@
+use crate::features::connect_groups::CustomConnectGroups;
+use crate::features::program_data::io::ProgramDataSaver;
+use crate::features::program_data::EditorData;
+use crate::util::{
+    get_current_project, get_current_project_mut, GetCurrentProjectError,
+    Save,
+};
+use cdda_lib::types::CDDAIdentifier;
+use log::warn;
+use std::collections::HashSet;
+use std::ops::Deref;
+use tauri::async_runtime::Mutex;
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_custom_connect_groups(
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<CustomConnectGroups, GetCurrentProjectError> {
+    let editor_data_lock = editor_data.lock().await;
+    let project = get_current_project(&editor_data_lock)?;
+
+    Ok(project.custom_connect_groups.clone())
+}
+
+/// Assigns custom connect groups to the terrain or furniture `id` of the
+/// current project. The groups do not have to exist in the game data.
+#[tauri::command]
+pub async fn set_custom_connect_groups(
+    id: CDDAIdentifier,
+    connect_groups: HashSet<CDDAIdentifier>,
+    connects_to: HashSet<CDDAIdentifier>,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<(), GetCurrentProjectError> {
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    project
+        .custom_connect_groups
+        .assign(id, connect_groups, connects_to);
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    if let Err(e) = saver.save(editor_data_lock.deref()).await {
+        warn!("Failed to save the custom connect groups, {}", e);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+pub(crate) mod handlers;
+
+use cdda_lib::types::CDDAIdentifier;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Connect groups which only exist in a project. They are added to the groups
+/// of the game data, so modders can preview how a new terrain family connects
+/// before writing its definitions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CustomConnectGroups {
+    /// The groups each terrain or furniture id is part of
+    #[serde(default)]
+    pub connect_groups: HashMap<CDDAIdentifier, HashSet<CDDAIdentifier>>,
+
+    /// The groups each terrain or furniture id connects to
+    #[serde(default)]
+    pub connects_to: HashMap<CDDAIdentifier, HashSet<CDDAIdentifier>>,
+}
+
+impl CustomConnectGroups {
+    /// Replaces the custom groups of the `id`. Passing no groups removes the
+    /// id from the custom groups.
+    pub fn assign(
+        &mut self,
+        id: CDDAIdentifier,
+        connect_groups: HashSet<CDDAIdentifier>,
+        connects_to: HashSet<CDDAIdentifier>,
+    ) {
+        for (map, groups) in [
+            (&mut self.connect_groups, connect_groups),
+            (&mut self.connects_to, connects_to),
+        ] {
+            match groups.is_empty() {
+                true => {
+                    map.remove(&id);
+                },
+                false => {
+                    map.insert(id.clone(), groups);
+                },
+            }
+        }
+    }
+}
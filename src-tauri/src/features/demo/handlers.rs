@@ -0,0 +1,75 @@
+use crate::events::EmitEvent;
+use crate::features::demo::{
+    demo_project, load_demo_json_data, DemoJsonData, DemoProjectError,
+    DEMO_PROJECT_NAME,
+};
+use crate::features::program_data::changes::{
+    EditorDataChanges, EditorDataScope,
+};
+use crate::features::program_data::{EditorData, Tab, TabType};
+use crate::impl_serialize_for_error;
+use serde::{Serialize, Serializer};
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, State};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum OpenDemoProjectError {
+    #[error("Failed to load the demo data, {0}")]
+    LoadError(#[from] anyhow::Error),
+
+    #[error(transparent)]
+    DemoProjectError(#[from] DemoProjectError),
+
+    #[error(transparent)]
+    TauriError(#[from] tauri::Error),
+}
+
+impl_serialize_for_error!(OpenDemoProjectError);
+
+/// Opens a map editor project with the embedded demo map. The demo data is
+/// kept apart from the loaded CDDA data and is used to draw the demo while no
+/// CDDA data is loaded, so the demo works without any configuration.
+#[tauri::command]
+pub async fn open_demo_project(
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    demo_json_data: State<'_, DemoJsonData>,
+) -> Result<(), OpenDemoProjectError> {
+    let mut demo_json_data_lock = demo_json_data.0.lock().await;
+    let mut editor_data_lock = editor_data.lock().await;
+
+    let name = DEMO_PROJECT_NAME.to_string();
+
+    if !editor_data_lock.loaded_projects.contains_key(&name) {
+        let demo_data = load_demo_json_data()?;
+        // The demo map only uses vanilla ids and brings its own palette, so
+        // it can also be drawn with the loaded CDDA data
+        let project = demo_project(&demo_data)?;
+
+        if demo_json_data_lock.is_none() {
+            demo_json_data_lock.replace(demo_data);
+        }
+
+        // The demo is not added to the openable projects, since it can not be
+        // loaded again after a restart until it is saved
+        editor_data_lock
+            .loaded_projects
+            .insert(name.clone(), project);
+    }
+
+    editor_data_lock.opened_project = Some(name.clone());
+
+    app.emit_event(Tab {
+        name,
+        tab_type: TabType::MapEditor,
+    })?;
+
+    editor_data_changes.notify(
+        &app,
+        &[EditorDataScope::ProjectList, EditorDataScope::OpenedProject],
+    );
+
+    Ok(())
+}
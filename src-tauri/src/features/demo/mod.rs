@@ -0,0 +1,127 @@
+pub(crate) mod handlers;
+
+use crate::data::io::{load_embedded_cdda_json_data, DeserializedCDDAJsonData};
+use crate::features::map::{CalculateParametersError, DEFAULT_MAP_DATA_SIZE};
+use crate::features::palette::read_palettes;
+use crate::features::program_data::{
+    MapDataCollection, Project, ProjectSaveState, ProjectType,
+};
+use cdda_lib::types::CDDAIdentifier;
+use glam::UVec2;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::async_runtime::Mutex;
+use thiserror::Error;
+use tokio::sync::MutexGuard;
+
+/// A tiny data set with a few terrains, furniture, a single palette and a
+/// single map, so new users can try the editor before they picked their CDDA
+/// installation. Without a tileset, it is drawn with the embedded fallback
+/// tileset.
+const DEMO_DATA: &[u8] = include_bytes!("demo_data.json");
+
+/// The path which is reported as the source of the entries of the demo data
+const DEMO_DATA_PATH: &str = "<embedded demo data>";
+
+const DEMO_OM_TERRAIN: &str = "demo_house";
+
+pub const DEMO_PROJECT_NAME: &str = "Demo";
+
+pub fn load_demo_json_data() -> Result<DeserializedCDDAJsonData, anyhow::Error>
+{
+    load_embedded_cdda_json_data(Path::new(DEMO_DATA_PATH), DEMO_DATA)
+}
+
+/// The demo data, which is kept apart from the loaded CDDA data. It is only
+/// used while no CDDA data is loaded, so the demo project can be drawn and
+/// edited without any configuration.
+#[derive(Default)]
+pub struct DemoJsonData(pub Mutex<Option<DeserializedCDDAJsonData>>);
+
+/// Locks the loaded CDDA data or, while none is loaded, the demo data. The
+/// demo data takes the place of the CDDA data in the lock order.
+pub async fn lock_json_data<'a>(
+    json_data: &'a Mutex<Option<DeserializedCDDAJsonData>>,
+    demo_json_data: &'a DemoJsonData,
+) -> MutexGuard<'a, Option<DeserializedCDDAJsonData>> {
+    let json_data_lock = json_data.lock().await;
+
+    if json_data_lock.is_some() {
+        return json_data_lock;
+    }
+
+    drop(json_data_lock);
+    demo_json_data.0.lock().await
+}
+
+#[derive(Debug, Error)]
+pub enum DemoProjectError {
+    #[error("The demo data does not contain the map {0}")]
+    MissingDemoMap(CDDAIdentifier),
+
+    #[error("Failed to read the palettes of the demo data, {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    CalculateParametersError(#[from] CalculateParametersError),
+}
+
+/// Creates an unsaved map editor project with the map of the `demo_data`. The
+/// palettes of the demo data belong to the project, so they are also
+/// available when the demo is opened with the loaded CDDA data.
+pub fn demo_project(
+    demo_data: &DeserializedCDDAJsonData,
+) -> Result<Project, DemoProjectError> {
+    let om_id = CDDAIdentifier::from(DEMO_OM_TERRAIN);
+
+    let map_data = demo_data
+        .map_data
+        .get(&om_id)
+        .cloned()
+        .ok_or(DemoProjectError::MissingDemoMap(om_id))?;
+
+    let mut project = Project::new(
+        DEMO_PROJECT_NAME.to_string(),
+        DEFAULT_MAP_DATA_SIZE,
+        ProjectType::MapEditor(ProjectSaveState::Unsaved),
+    );
+    project.maps = HashMap::from([(
+        0,
+        MapDataCollection {
+            maps: HashMap::from([(UVec2::ZERO, map_data)]),
+        },
+    )]);
+    project.palettes = read_palettes(serde_json::from_slice(DEMO_DATA)?)?
+        .into_values()
+        .collect();
+    project.share_palettes();
+
+    for (_, collection) in project.maps.iter_mut() {
+        collection.calculate_parameters(&demo_data.palettes)?;
+    }
+
+    Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_data() {
+        let demo_data = load_demo_json_data().unwrap();
+
+        assert!(demo_data.terrain.contains_key(&"t_wall".into()));
+        assert!(demo_data.furniture.contains_key(&"f_table".into()));
+        assert!(demo_data.map_data.contains_key(&DEMO_OM_TERRAIN.into()));
+
+        let project = demo_project(&demo_data).unwrap();
+        let map_data = &project.maps[&0].maps[&UVec2::ZERO];
+
+        assert_eq!(project.palettes.len(), demo_data.palettes.len());
+        assert!(map_data
+            .palette_scope(&HashMap::new())
+            .get(&"demo_house_palette".into())
+            .is_some());
+    }
+}
@@ -0,0 +1,17 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::diagnostics::FileDiagnostics;
+use crate::util::{get_json_data, CDDADataError};
+use tauri::async_runtime::Mutex;
+use tauri::State;
+
+/// The problems which were found while the CDDA json data and the mods of the
+/// opened project were imported, grouped by the file they were found in
+#[tauri::command]
+pub async fn get_import_diagnostics(
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<Vec<FileDiagnostics>, CDDADataError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    Ok(json_data.diagnostics.by_file())
+}
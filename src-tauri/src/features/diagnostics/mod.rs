@@ -0,0 +1,120 @@
+pub(crate) mod handlers;
+
+use cdda_lib::types::CDDAIdentifier;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiagnosticSeverity {
+    /// The entry was loaded, but probably not in the way the author intended
+    Warning,
+    /// The entry or the whole file could not be loaded
+    Error,
+}
+
+/// A problem which was found while the CDDA json data was imported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportDiagnostic {
+    pub severity: DiagnosticSeverity,
+    pub path: PathBuf,
+    /// The id of the entry with the problem, if the problem is not about the
+    /// whole file
+    pub id: Option<CDDAIdentifier>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ImportDiagnostic {
+    pub fn error(path: &Path, message: impl Into<String>) -> Self {
+        Self {
+            severity: DiagnosticSeverity::Error,
+            path: path.to_path_buf(),
+            id: None,
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(
+        path: &Path,
+        id: Option<CDDAIdentifier>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity: DiagnosticSeverity::Warning,
+            path: path.to_path_buf(),
+            id,
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    /// An error that made the file at `path` fail to load. The position is
+    /// taken from the error if it happened while the json was parsed.
+    pub fn from_file_error(path: &Path, error: &anyhow::Error) -> Self {
+        let mut diagnostic = Self::error(path, error.to_string());
+
+        // Errors of values which were already parsed do not have a position
+        if let Some(e) = error.downcast_ref::<serde_json::Error>() {
+            if e.line() > 0 {
+                diagnostic.line = Some(e.line());
+                diagnostic.column = Some(e.column());
+            }
+        }
+
+        diagnostic
+    }
+}
+
+/// The problems of every file which were found while the CDDA json data was
+/// imported. Clones share the same problems, so problems which are only found
+/// when a lazy section is loaded end up in the diagnostics of the data.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    entries: Arc<Mutex<Vec<ImportDiagnostic>>>,
+}
+
+/// All problems of a single file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDiagnostics {
+    pub path: PathBuf,
+    pub diagnostics: Vec<ImportDiagnostic>,
+}
+
+impl Diagnostics {
+    pub fn report(&self, diagnostic: ImportDiagnostic) {
+        self.entries.lock().unwrap().push(diagnostic);
+    }
+
+    /// Removes the problems of the file at `path`, for example because it is
+    /// loaded again
+    pub fn clear_file(&self, path: &Path) {
+        self.entries.lock().unwrap().retain(|d| d.path != path);
+    }
+
+    pub fn all(&self) -> Vec<ImportDiagnostic> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn by_file(&self) -> Vec<FileDiagnostics> {
+        let mut files: BTreeMap<PathBuf, Vec<ImportDiagnostic>> =
+            BTreeMap::new();
+
+        for diagnostic in self.entries.lock().unwrap().iter() {
+            files
+                .entry(diagnostic.path.clone())
+                .or_default()
+                .push(diagnostic.clone());
+        }
+
+        files
+            .into_iter()
+            .map(|(path, diagnostics)| FileDiagnostics { path, diagnostics })
+            .collect()
+    }
+}
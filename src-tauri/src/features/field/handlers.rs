@@ -0,0 +1,60 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::field::{simulate_field_decay, FieldDecayPreview};
+use crate::features::program_data::EditorData;
+use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+use crate::util::{get_json_data, CDDADataError};
+use cdda_lib::types::CDDAIdentifier;
+use serde::Serialize;
+use std::ops::Deref;
+use std::sync::Arc;
+use tauri::async_runtime::Mutex;
+use tauri::State;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+pub enum GetFieldDecayPreviewError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error("Field {0} does not exist")]
+    UnknownField(CDDAIdentifier),
+}
+
+/// Previews every intensity a field goes through while it decays, starting
+/// at the `intensity` and `age` it was placed with
+#[tauri::command]
+pub async fn get_field_decay_preview(
+    field: CDDAIdentifier,
+    intensity: Option<i32>,
+    age: Option<i32>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+    fallback_tilesheet: State<'_, Arc<LegacyTilesheet>>,
+) -> Result<FieldDecayPreview, GetFieldDecayPreviewError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let field_type = match json_data.field_types.get(&field) {
+        None => return Err(GetFieldDecayPreviewError::UnknownField(field)),
+        Some(f) => f,
+    };
+
+    let editor_data_lock = editor_data.lock().await;
+    let tilesheet_lock = tilesheet.lock().await;
+    let tilesheet = match editor_data_lock
+        .config
+        .rendered_tilesheet(tilesheet_lock.as_ref())
+    {
+        None => fallback_tilesheet.deref().deref(),
+        Some(t) => t,
+    };
+
+    Ok(simulate_field_decay(
+        field_type,
+        intensity.unwrap_or(1),
+        age.unwrap_or(0),
+        tilesheet,
+        json_data,
+    ))
+}
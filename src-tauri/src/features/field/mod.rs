@@ -0,0 +1,163 @@
+pub mod handlers;
+
+use crate::data::field_type::{CDDAFieldType, FieldIntensityLevel};
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::TileLayer;
+use crate::features::connect_groups::CustomConnectGroups;
+use crate::features::map::MappedCDDAId;
+use crate::features::program_data::AdjacentSprites;
+use crate::features::tileset::legacy_tileset::{
+    LegacyTilesheet, SpriteIndex, TilesheetCDDAId,
+};
+use crate::features::tileset::{Tilesheet, VariantPicker};
+use cdda_lib::types::CDDAIdentifier;
+use serde::Serialize;
+
+/// A single intensity of a field while it decays
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDecayStage {
+    pub intensity: i32,
+    /// Seconds after the map was generated at which this stage starts
+    pub starts_at: i64,
+    pub level: Option<FieldIntensityLevel>,
+    pub fg: Option<Vec<SpriteIndex>>,
+    pub bg: Option<Vec<SpriteIndex>>,
+    /// Only set if the tileset does not contain a sprite for this field
+    pub fallback: Option<SpriteIndex>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDecayPreview {
+    pub field: CDDAIdentifier,
+    pub half_life: Option<i64>,
+    pub stages: Vec<FieldDecayStage>,
+    /// Seconds after which the field is completely gone, [`None`] if the
+    /// field does not decay
+    pub removed_at: Option<i64>,
+}
+
+/// Simulates how a field placed with the `intensity` and `age` decays over
+/// time.
+///
+/// CDDA lowers the intensity of a field by one roughly every `half_life`
+/// seconds and resets its age afterward. The actual decay is random, so this
+/// uses the expected time of every step.
+pub fn simulate_field_decay(
+    field_type: &CDDAFieldType,
+    intensity: i32,
+    age: i32,
+    tilesheet: &LegacyTilesheet,
+    json_data: &DeserializedCDDAJsonData,
+) -> FieldDecayPreview {
+    let max_intensity = match field_type.intensity_levels.len() {
+        0 => intensity.max(1),
+        len => len as i32,
+    };
+    let intensity = intensity.clamp(1, max_intensity);
+    let half_life = field_type
+        .half_life
+        .as_ref()
+        .and_then(|h| h.to_seconds())
+        .filter(|h| *h > 0);
+
+    let mut stages = Vec::new();
+    let mut starts_at = 0;
+
+    for current_intensity in (1..=intensity).rev() {
+        let (fg, bg, fallback) = get_stage_sprites(
+            &field_type.id,
+            current_intensity,
+            tilesheet,
+            json_data,
+        );
+
+        stages.push(FieldDecayStage {
+            intensity: current_intensity,
+            starts_at,
+            level: field_type
+                .intensity_levels
+                .get(current_intensity as usize - 1)
+                .cloned(),
+            fg,
+            bg,
+            fallback,
+        });
+
+        let half_life = match half_life {
+            // Fields without a half life never decay
+            None => break,
+            Some(h) => h,
+        };
+
+        // Only the first stage is affected by the initial age of the field
+        starts_at += match current_intensity == intensity {
+            true => (half_life - age as i64).max(0),
+            false => half_life,
+        };
+    }
+
+    FieldDecayPreview {
+        field: field_type.id.clone(),
+        half_life,
+        stages,
+        removed_at: half_life.map(|_| starts_at),
+    }
+}
+
+/// Tilesets can define one weighted foreground sprite for every intensity of
+/// a field, so the sprite variant is selected by the intensity
+fn get_stage_sprites(
+    id: &CDDAIdentifier,
+    intensity: i32,
+    tilesheet: &LegacyTilesheet,
+    json_data: &DeserializedCDDAJsonData,
+) -> (
+    Option<Vec<SpriteIndex>>,
+    Option<Vec<SpriteIndex>>,
+    Option<SpriteIndex>,
+) {
+    let mapped_id = MappedCDDAId::simple(TilesheetCDDAId::simple(id.clone()));
+
+    let sprite = match tilesheet.get_sprite(&mapped_id, json_data) {
+        None => {
+            return (
+                None,
+                None,
+                Some(tilesheet.get_fallback(&mapped_id, json_data)),
+            );
+        },
+        Some(s) => s,
+    };
+
+    let picker =
+        VariantPicker::new(0).with_pinned(Some(intensity as usize - 1));
+    let adjacent_sprites = AdjacentSprites {
+        top: None,
+        right: None,
+        bottom: None,
+        left: None,
+    };
+
+    let fg = sprite.get_fg_id(
+        &picker,
+        &mapped_id,
+        &TileLayer::Field,
+        &adjacent_sprites,
+        json_data,
+        &CustomConnectGroups::default(),
+    );
+    let bg = sprite.get_bg_id(
+        &picker,
+        &mapped_id,
+        &TileLayer::Field,
+        &adjacent_sprites,
+        json_data,
+        &CustomConnectGroups::default(),
+    );
+
+    (
+        fg.map(|r| r.data.into_vec()),
+        bg.map(|r| r.data.into_vec()),
+        None,
+    )
+}
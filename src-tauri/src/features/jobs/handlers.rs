@@ -0,0 +1,28 @@
+use crate::features::jobs::{JobId, JobInfo, Jobs};
+use serde::Serialize;
+use tauri::State;
+use thiserror::Error;
+
+#[tauri::command]
+pub async fn list_jobs(jobs: State<'_, Jobs>) -> Result<Vec<JobInfo>, ()> {
+    Ok(jobs.list())
+}
+
+#[derive(Debug, Error, Serialize)]
+pub enum CancelJobError {
+    #[error("No job with the id {0} exists")]
+    UnknownJob(JobId),
+}
+
+/// Requests the job with the `id` to stop. The job stops at the next point
+/// where it checks for cancellation and is then marked as canceled.
+#[tauri::command]
+pub async fn cancel_job(
+    id: JobId,
+    jobs: State<'_, Jobs>,
+) -> Result<(), CancelJobError> {
+    match jobs.cancel(id) {
+        true => Ok(()),
+        false => Err(CancelJobError::UnknownJob(id)),
+    }
+}
@@ -0,0 +1,219 @@
+pub(crate) mod handlers;
+
+use crate::events::{EditorEvent, EmitEvent, EventPayload};
+use indexmap::IndexMap;
+use log::warn;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+
+/// Amount of finished jobs which are kept so the frontend can still show
+/// their result
+const MAX_FINISHED_JOBS: usize = 20;
+
+pub type JobId = u64;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed { error: String },
+    Canceled,
+}
+
+impl JobState {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, JobState::Running)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: JobId,
+    pub name: String,
+    /// Progress between 0 and 1 if the total amount of work is known
+    pub progress: Option<f32>,
+    /// Short description of what the job is currently doing
+    pub message: Option<String>,
+    #[serde(flatten)]
+    pub state: JobState,
+}
+
+impl EventPayload for JobInfo {
+    const EVENT: EditorEvent = EditorEvent::JobUpdated;
+}
+
+#[derive(Debug)]
+struct JobEntry {
+    info: JobInfo,
+    canceled: Arc<AtomicBool>,
+}
+
+/// Registry of long-running operations which can report their progress and
+/// be canceled by the user
+#[derive(Debug, Clone, Default)]
+pub struct Jobs {
+    next_id: Arc<AtomicU64>,
+    entries: Arc<Mutex<IndexMap<JobId, JobEntry>>>,
+}
+
+impl Jobs {
+    pub fn start(
+        &self,
+        app: &AppHandle,
+        name: impl Into<String>,
+    ) -> JobHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let canceled = Arc::new(AtomicBool::new(false));
+
+        let info = JobInfo {
+            id,
+            name: name.into(),
+            progress: None,
+            message: None,
+            state: JobState::Running,
+        };
+
+        emit_job_update(app, &info);
+
+        self.entries.lock().unwrap().insert(
+            id,
+            JobEntry {
+                info,
+                canceled: canceled.clone(),
+            },
+        );
+
+        JobHandle {
+            id,
+            canceled,
+            jobs: self.clone(),
+            app: app.clone(),
+        }
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.entries
+            .lock()
+            .unwrap()
+            .values()
+            .map(|e| e.info.clone())
+            .collect()
+    }
+
+    /// Requests the job with the `id` to stop. Returns false if no such job
+    /// exists.
+    pub fn cancel(&self, id: JobId) -> bool {
+        match self.entries.lock().unwrap().get(&id) {
+            None => false,
+            Some(entry) => {
+                entry.canceled.store(true, Ordering::Relaxed);
+                true
+            },
+        }
+    }
+
+    fn update(
+        &self,
+        id: JobId,
+        f: impl FnOnce(&mut JobInfo),
+    ) -> Option<JobInfo> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get_mut(&id)?;
+        f(&mut entry.info);
+        let info = entry.info.clone();
+
+        // Remove the oldest finished jobs once there are too many
+        let finished: Vec<JobId> = entries
+            .values()
+            .filter(|e| e.info.state.is_finished())
+            .map(|e| e.info.id)
+            .collect();
+        let to_remove = finished.len().saturating_sub(MAX_FINISHED_JOBS);
+
+        for id in finished.iter().take(to_remove) {
+            entries.shift_remove(id);
+        }
+
+        Some(info)
+    }
+}
+
+fn emit_job_update(app: &AppHandle, info: &JobInfo) {
+    if let Err(e) = app.emit_event(info.clone()) {
+        warn!("Failed to send update of job {}: {}", info.id, e);
+    }
+}
+
+/// Handle which is passed to a long-running operation. The operation should
+/// regularly check [`JobHandle::is_canceled`] and stop if it returns true.
+#[derive(Debug, Clone)]
+pub struct JobHandle {
+    id: JobId,
+    canceled: Arc<AtomicBool>,
+    jobs: Jobs,
+    app: AppHandle,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.canceled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_progress(&self, progress: f32) {
+        let info = self.jobs.update(self.id, |info| {
+            info.progress = Some(progress.clamp(0., 1.))
+        });
+
+        if let Some(info) = info {
+            emit_job_update(&self.app, &info);
+        }
+    }
+
+    pub fn set_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        let info =
+            self.jobs.update(self.id, |info| info.message = Some(message));
+
+        if let Some(info) = info {
+            emit_job_update(&self.app, &info);
+        }
+    }
+
+    /// Sends an event which belongs to the job, like a detailed progress
+    /// report
+    pub fn emit_event<P: EventPayload>(&self, payload: P) {
+        if let Err(e) = self.app.emit_event(payload) {
+            warn!("Failed to send event of job {}: {}", self.id, e);
+        }
+    }
+
+    /// Marks the job as finished. The job counts as canceled if cancellation
+    /// was requested, regardless of the `result`.
+    pub fn finish<T, E: ToString>(&self, result: &Result<T, E>) {
+        let state = match (self.is_canceled(), result) {
+            (true, _) => JobState::Canceled,
+            (false, Ok(_)) => JobState::Completed,
+            (false, Err(e)) => JobState::Failed {
+                error: e.to_string(),
+            },
+        };
+
+        let info = self.jobs.update(self.id, |info| {
+            if matches!(state, JobState::Completed) {
+                info.progress = Some(1.);
+            }
+            info.state = state;
+        });
+
+        if let Some(info) = info {
+            emit_job_update(&self.app, &info);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::legend::{
+    build_legend, legend_to_markdown, LegendFormat,
+};
+use crate::features::program_data::{EditorData, ZLevel};
+use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+use crate::impl_serialize_for_error;
+use crate::util::{
+    get_current_project, get_json_data, CDDADataError, GetCurrentProjectError,
+    UVec2JsonKey,
+};
+use serde::{Serialize, Serializer};
+use std::ops::Deref;
+use std::sync::Arc;
+use tauri::async_runtime::Mutex;
+use tauri::State;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExportLegendError {
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error("There is no map at {0} on the z-level {1}")]
+    NoMap(String, ZLevel),
+
+    #[error("Failed to serialize the legend, {0}")]
+    SerializeError(#[from] serde_json::Error),
+}
+
+impl_serialize_for_error!(ExportLegendError);
+
+/// Generates a legend of the characters of the map at the `coordinates` of the
+/// current project, either as json or as a markdown table
+#[tauri::command]
+pub async fn export_map_legend(
+    z: ZLevel,
+    coordinates: UVec2JsonKey,
+    format: LegendFormat,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+    fallback_tilesheet: State<'_, Arc<LegacyTilesheet>>,
+) -> Result<String, ExportLegendError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let editor_data_lock = editor_data.lock().await;
+    let project = get_current_project(&editor_data_lock)?;
+
+    let map_data = project
+        .maps
+        .get(&z)
+        .and_then(|c| c.maps.get(&coordinates.0))
+        .ok_or(ExportLegendError::NoMap(
+            format!("{},{}", coordinates.0.x, coordinates.0.y),
+            z,
+        ))?;
+
+    let tilesheet_lock = tilesheet.lock().await;
+    let tilesheet = match tilesheet_lock.deref() {
+        None => fallback_tilesheet.deref().deref(),
+        Some(t) => t,
+    };
+
+    let legend = build_legend(map_data, tilesheet, json_data);
+
+    match format {
+        LegendFormat::Json => Ok(serde_json::to_string_pretty(&legend)?),
+        LegendFormat::Markdown => Ok(legend_to_markdown(&legend)),
+    }
+}
@@ -0,0 +1,294 @@
+pub(crate) mod handlers;
+
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::map_data::ReferenceOrInPlace;
+use crate::data::{GetIdentifier, TileLayer};
+use crate::features::connect_groups::CustomConnectGroups;
+use crate::features::map::map_properties::ItemsProperty;
+use crate::features::map::{MapData, MappedCDDAId, MappingKind, MappingSource};
+use crate::features::program_data::AdjacentSprites;
+use crate::features::tileset::legacy_tileset::{
+    LegacyTilesheet, SpriteIndex, TilesheetCDDAId,
+};
+use crate::features::tileset::{Tilesheet, VariantPicker};
+use cdda_lib::types::{CDDAIdentifier, CDDAString, MapGenValue};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LegendFormat {
+    Json,
+    Markdown,
+}
+
+/// Something a character can be mapped to
+#[derive(Debug, Clone, Serialize)]
+pub struct LegendValue {
+    pub id: CDDAIdentifier,
+    pub name: Option<String>,
+    /// The foreground sprite of the current tileset, or its background sprite
+    /// if there is no foreground
+    pub sprite: Option<SpriteIndex>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LegendMapping {
+    pub kind: MappingKind,
+    pub source: MappingSource,
+    /// Every terrain, furniture or item group the character can be mapped
+    /// to. Empty for the kinds which are not described by an id.
+    pub values: Vec<LegendValue>,
+}
+
+/// Describes what a single character of the rows is mapped to
+#[derive(Debug, Clone, Serialize)]
+pub struct LegendEntry {
+    pub character: char,
+    /// How often the character is used in the rows
+    pub count: usize,
+    pub mappings: Vec<LegendMapping>,
+}
+
+fn cdda_string_text(value: &CDDAString) -> String {
+    match value {
+        CDDAString::String(s) => s.clone(),
+        CDDAString::StringMap { str } => str.clone(),
+    }
+}
+
+/// Every id a mapgen value can resolve to. Distributions resolve to all of
+/// their ids instead of a random one.
+fn possible_ids(
+    mapgen_value: &MapGenValue,
+    map_data: &MapData,
+) -> Vec<CDDAIdentifier> {
+    let parameters = &map_data.calculated_parameters;
+
+    match mapgen_value {
+        MapGenValue::Distribution(distribution) => distribution
+            .as_slice()
+            .iter()
+            .filter_map(|v| v.clone().data().get_identifier(parameters).ok())
+            .collect(),
+        _ => mapgen_value.get_identifier(parameters).ok().into_iter().collect(),
+    }
+}
+
+fn get_sprite(
+    id: &CDDAIdentifier,
+    layer: &TileLayer,
+    tilesheet: &LegacyTilesheet,
+    json_data: &DeserializedCDDAJsonData,
+) -> Option<SpriteIndex> {
+    let mapped_id = MappedCDDAId::simple(TilesheetCDDAId::simple(id.clone()));
+    let sprite = tilesheet.get_sprite(&mapped_id, json_data)?;
+
+    let picker = VariantPicker::new(0);
+    let adjacent_sprites = AdjacentSprites {
+        top: None,
+        right: None,
+        bottom: None,
+        left: None,
+    };
+    let custom_connect_groups = CustomConnectGroups::default();
+
+    sprite
+        .get_fg_id(
+            &picker,
+            &mapped_id,
+            layer,
+            &adjacent_sprites,
+            json_data,
+            &custom_connect_groups,
+        )
+        .or_else(|| {
+            sprite.get_bg_id(
+                &picker,
+                &mapped_id,
+                layer,
+                &adjacent_sprites,
+                json_data,
+                &custom_connect_groups,
+            )
+        })
+        .and_then(|r| r.data.as_slice().first().cloned())
+}
+
+fn legend_values(
+    map_data: &MapData,
+    kind: &MappingKind,
+    character: &char,
+    tilesheet: &LegacyTilesheet,
+    json_data: &DeserializedCDDAJsonData,
+) -> Vec<LegendValue> {
+    match kind {
+        MappingKind::Terrain | MappingKind::Furniture => {
+            let Some(mapgen_value) =
+                map_data.get_mapgen_value(kind, character, json_data)
+            else {
+                return vec![];
+            };
+
+            let layer = match kind {
+                MappingKind::Terrain => TileLayer::Terrain,
+                _ => TileLayer::Furniture,
+            };
+
+            possible_ids(&mapgen_value, map_data)
+                .into_iter()
+                .map(|id| {
+                    let name = match kind {
+                        MappingKind::Terrain => json_data
+                            .terrain
+                            .get(&id)
+                            .and_then(|t| t.name.as_ref()),
+                        _ => json_data
+                            .furniture
+                            .get(&id)
+                            .and_then(|f| f.name.as_ref()),
+                    };
+
+                    LegendValue {
+                        name: name.map(cdda_string_text),
+                        sprite: get_sprite(&id, &layer, tilesheet, json_data),
+                        id,
+                    }
+                })
+                .collect()
+        },
+        MappingKind::ItemGroups => {
+            let Some(property) =
+                map_data.get_property(kind, character, json_data)
+            else {
+                return vec![];
+            };
+            let Some(property) = property.downcast_ref::<ItemsProperty>() else {
+                return vec![];
+            };
+
+            // Item groups which are defined in place do not have an id
+            property
+                .items
+                .iter()
+                .filter_map(|item| match &item.data.item {
+                    ReferenceOrInPlace::Reference(id) => Some(LegendValue {
+                        id: id.clone(),
+                        name: None,
+                        sprite: None,
+                    }),
+                    ReferenceOrInPlace::InPlace(_) => None,
+                })
+                .collect()
+        },
+        _ => vec![],
+    }
+}
+
+/// Builds the legend of every character which is used in the rows of the
+/// `map_data`, sorted by the character
+pub fn build_legend(
+    map_data: &MapData,
+    tilesheet: &LegacyTilesheet,
+    json_data: &DeserializedCDDAJsonData,
+) -> Vec<LegendEntry> {
+    let mut counts: IndexMap<char, usize> = IndexMap::new();
+
+    for cell in map_data.cells.values() {
+        *counts.entry(cell.character).or_default() += 1;
+    }
+
+    counts.sort_keys();
+
+    counts
+        .into_iter()
+        .map(|(character, count)| {
+            let mappings = MappingKind::iter()
+                .filter_map(|kind| {
+                    let source = map_data.get_mapping_source(
+                        &kind,
+                        &character,
+                        json_data,
+                    )?;
+
+                    Some(LegendMapping {
+                        values: legend_values(
+                            map_data, &kind, &character, tilesheet, json_data,
+                        ),
+                        kind,
+                        source,
+                    })
+                })
+                .collect();
+
+            LegendEntry {
+                character,
+                count,
+                mappings,
+            }
+        })
+        .collect()
+}
+
+/// Escapes the characters which would break a markdown table cell
+fn escape_markdown(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('`', "\\`")
+}
+
+/// Renders the `legend` as a markdown table with one row per mapping
+pub fn legend_to_markdown(legend: &[LegendEntry]) -> String {
+    let mut markdown = String::from(
+        "| Symbol | Count | Kind | Source | Mapped to |\n\
+         |--------|-------|------|--------|-----------|\n",
+    );
+
+    for entry in legend {
+        let symbol = match entry.character {
+            ' ' => "` `".to_string(),
+            c => format!("`{}`", escape_markdown(&c.to_string())),
+        };
+
+        if entry.mappings.is_empty() {
+            markdown.push_str(&format!(
+                "| {} | {} | | | *unmapped* |\n",
+                symbol, entry.count
+            ));
+            continue;
+        }
+
+        for mapping in entry.mappings.iter() {
+            let kind = serde_json::to_value(&mapping.kind)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default();
+
+            let source = match &mapping.source {
+                MappingSource::Map => "map".to_string(),
+                MappingSource::Palette { id } => format!("palette `{}`", id),
+            };
+
+            let values = mapping
+                .values
+                .iter()
+                .map(|v| match &v.name {
+                    None => format!("`{}`", v.id),
+                    Some(name) => {
+                        format!("{} (`{}`)", escape_markdown(name), v.id)
+                    },
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            markdown.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                symbol, entry.count, kind, source, values
+            ));
+        }
+    }
+
+    markdown
+}
@@ -0,0 +1,464 @@
+use crate::data::map_data::{
+    MapGenAppliance, MapGenComputer, MapGenCorpse, MapGenField, MapGenGaspump,
+    MapGenGraffiti, MapGenItem, MapGenLiquid, MapGenLoot, MapGenMonsters,
+    MapGenNestedIntermediate, MapGenNpc, MapGenRubble, MapGenSealedItem,
+    MapGenSign, MapGenTrap, MapGenVehicle, PlaceInnerAppliances,
+    PlaceInnerComputers, PlaceInnerCorpses, PlaceInnerFields,
+    PlaceInnerFurniture, PlaceInnerGaspumps, PlaceInnerGraffiti,
+    PlaceInnerItems, PlaceInnerLiquids, PlaceInnerLoot, PlaceInnerMonster,
+    PlaceInnerMonsters, PlaceInnerNested, PlaceInnerNpcs, PlaceInnerRubble,
+    PlaceInnerSealedItem, PlaceInnerSigns, PlaceInnerTerrain,
+    PlaceInnerToilets, PlaceInnerTraps, PlaceInnerVehicles, PlaceOuter,
+    SetIntermediate,
+};
+use crate::features::map::{
+    MapData, MapDataFlag, MappingKind, Place, DEFAULT_MAP_DATA_SIZE,
+};
+use crate::features::raw_json::value_contains_id;
+use cdda_lib::types::{CDDAIdentifier, MapGenValue, MeabyVec, MeabyWeighted};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExportMapgenError {
+    #[error("Failed to access the file {0}, {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("The file {0} is not a valid CDDA json file, {1}")]
+    InvalidJson(PathBuf, serde_json::Error),
+
+    #[error("Failed to serialize the map data, {0}")]
+    SerializeError(#[from] serde_json::Error),
+
+    #[error("The mapgen of {0} is not an object")]
+    InvalidMapgen(CDDAIdentifier),
+
+    #[error(
+        "The mapgen of {0} defines multiple overmap terrains in a grid, which \
+         cannot be exported from a single map"
+    )]
+    NestedMapgen(CDDAIdentifier),
+
+    #[error(
+        "The mapgen can not be exported for the {target:?} version of the \
+         game, {}",
+        issues.join(", ")
+    )]
+    Incompatible {
+        target: ExportTarget,
+        issues: Vec<String>,
+    },
+}
+
+/// The version of the game exported mapgen has to work with
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Ord,
+    PartialOrd,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportTarget {
+    /// The latest stable release, 0.G
+    Stable,
+    #[default]
+    Experimental,
+}
+
+/// Keys of mapgen objects which were added after a stable release and can
+/// not be expressed with older syntax, together with the oldest target
+/// which supports them
+const VERSIONED_KEYS: &[(&str, ExportTarget, &str)] = &[
+    (
+        "remove_all",
+        ExportTarget::Experimental,
+        "removing everything from a tile",
+    ),
+    (
+        "place_remove_all",
+        ExportTarget::Experimental,
+        "removing everything from an area",
+    ),
+    (
+        "remove_vehicles",
+        ExportTarget::Experimental,
+        "removing vehicles",
+    ),
+    (
+        "place_remove_vehicles",
+        ExportTarget::Experimental,
+        "removing vehicles from an area",
+    ),
+    (
+        "joins",
+        ExportTarget::Experimental,
+        "nested chunks which depend on overmap joins",
+    ),
+];
+
+fn collect_incompatibilities(
+    value: &Value,
+    path: &str,
+    target: ExportTarget,
+    issues: &mut Vec<String>,
+) {
+    match value {
+        Value::Object(object) => {
+            for (key, v) in object {
+                let key_path = match path.is_empty() {
+                    true => key.clone(),
+                    false => format!("{}.{}", path, key),
+                };
+
+                let unsupported = VERSIONED_KEYS.iter().find(|(k, since, _)| {
+                    *k == key.as_str() && *since > target
+                });
+
+                if let Some((_, _, description)) = unsupported {
+                    issues.push(format!("{} at {}", description, key_path));
+                }
+
+                collect_incompatibilities(v, &key_path, target, issues);
+            }
+        },
+        Value::Array(values) => {
+            for (i, v) in values.iter().enumerate() {
+                let index_path = format!("{}[{}]", path, i);
+                collect_incompatibilities(v, &index_path, target, issues);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Fails if the `mapgen` uses anything the `target` does not understand,
+/// listing where every unsupported construct is used
+pub fn check_compatibility(
+    mapgen: &Value,
+    target: ExportTarget,
+) -> Result<(), ExportMapgenError> {
+    let mut issues = vec![];
+    collect_incompatibilities(mapgen, "", target, &mut issues);
+
+    match issues.is_empty() {
+        true => Ok(()),
+        false => Err(ExportMapgenError::Incompatible { target, issues }),
+    }
+}
+
+/// Removes all null values from objects, since CDDA treats a missing field
+/// and a null field differently
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.retain(|_, v| !v.is_null());
+            object.values_mut().for_each(remove_nulls);
+        },
+        Value::Array(values) => values.iter_mut().for_each(remove_nulls),
+        _ => {},
+    }
+}
+
+/// Sets the `key` of the `object` to the `value`, or removes it if there is
+/// no `value`. The existing value is kept if it describes the same thing, so
+/// comments and the formatting of the original file are not lost.
+fn set_field<T: Serialize + DeserializeOwned>(
+    object: &mut Map<String, Value>,
+    key: &str,
+    value: Option<&T>,
+) -> Result<(), serde_json::Error> {
+    let Some(value) = value else {
+        object.remove(key);
+        return Ok(());
+    };
+
+    let mut new_value = serde_json::to_value(value)?;
+    remove_nulls(&mut new_value);
+
+    let unchanged = object
+        .get(key)
+        .and_then(|v| serde_json::from_value::<T>(v.clone()).ok())
+        .and_then(|v| serde_json::to_value(v).ok())
+        .is_some_and(|mut v| {
+            remove_nulls(&mut v);
+            v == new_value
+        });
+
+    if !unchanged {
+        object.insert(key.to_string(), new_value);
+    }
+
+    Ok(())
+}
+
+fn place_to_json(
+    place: &PlaceOuter<Arc<dyn Place>>,
+) -> Result<Value, serde_json::Error> {
+    let mut value = place.inner.to_json()?;
+
+    let fields = [
+        ("x", serde_json::to_value(&place.x)?),
+        ("y", serde_json::to_value(&place.y)?),
+        ("repeat", serde_json::to_value(&place.repeat)?),
+        ("chance", serde_json::to_value(place.chance)?),
+    ];
+
+    if let Value::Object(object) = &mut value {
+        for (key, field) in fields {
+            object.insert(key.to_string(), field);
+        }
+    }
+
+    Ok(value)
+}
+
+/// Sets the `place_*` entries at the `key` of the `object` to the places of
+/// the `kind`, read back as `T` so unchanged entries keep their formatting
+fn set_places<T: Serialize + DeserializeOwned>(
+    object: &mut Map<String, Value>,
+    key: &str,
+    map_data: &MapData,
+    kind: MappingKind,
+) -> Result<(), serde_json::Error> {
+    let values = map_data
+        .place
+        .get(&kind)
+        .into_iter()
+        .flatten()
+        .map(place_to_json)
+        .collect::<Result<Vec<Value>, serde_json::Error>>()?;
+
+    let places: Vec<PlaceOuter<T>> =
+        serde_json::from_value(Value::Array(values))?;
+
+    set_field(object, key, Some(&places).filter(|p| !p.is_empty()))
+}
+
+/// Sets the mappings at the `key` of the `object` to the mappings of the
+/// `kind` in the map itself, read back as `T` so unchanged mappings keep their
+/// formatting
+fn set_mappings<T: Serialize + DeserializeOwned>(
+    object: &mut Map<String, Value>,
+    key: &str,
+    map_data: &MapData,
+    kind: MappingKind,
+) -> Result<(), serde_json::Error> {
+    let values = map_data
+        .properties
+        .get(&kind)
+        .into_iter()
+        .flatten()
+        .map(|(character, property)| {
+            Ok((character.to_string(), property.to_json()?))
+        })
+        .collect::<Result<Map<String, Value>, serde_json::Error>>()?;
+
+    let mappings: BTreeMap<char, T> =
+        serde_json::from_value(Value::Object(values))?;
+
+    set_field(object, key, Some(&mappings).filter(|m| !m.is_empty()))
+}
+
+/// Writes the `map_data` into the `object` of a mapgen. Fields of the `object`
+/// which the map data does not know about are kept as they are.
+pub fn write_mapgen_object(
+    object: &mut Map<String, Value>,
+    map_data: &MapData,
+) -> Result<(), serde_json::Error> {
+    let map_size = match map_data.map_size == DEFAULT_MAP_DATA_SIZE
+        && !object.contains_key("mapgensize")
+    {
+        true => None,
+        false => Some([map_data.map_size.x, map_data.map_size.y]),
+    };
+
+    set_field(object, "rows", Some(&map_data.get_rows()))?;
+    set_field(object, "mapgensize", map_size.as_ref())?;
+    set_field(object, "fill_ter", map_data.fill.as_ref())?;
+    set_field(object, "predecessor_mapgen", map_data.predecessor.as_ref())?;
+    set_field(
+        object,
+        "palettes",
+        Some(&map_data.palettes).filter(|p| !p.is_empty()),
+    )?;
+    set_field(
+        object,
+        "parameters",
+        Some(&map_data.parameters).filter(|p| !p.is_empty()),
+    )?;
+
+    macro_rules! set_all_mappings {
+        ($($mapping: ty => $kind: ident, $key: literal),*) => {
+            $(set_mappings::<$mapping>(object, $key, map_data, MappingKind::$kind)?;)*
+        };
+    }
+
+    set_all_mappings!(
+        MapGenValue => Terrain, "terrain",
+        MapGenValue => Furniture, "furniture",
+        MeabyVec<MeabyWeighted<MapGenItem>> => ItemGroups, "items",
+        MeabyVec<MeabyWeighted<MapGenMonsters>> => Monsters, "monsters",
+        MeabyVec<MeabyWeighted<MapGenMonsters>> => Monster, "monster",
+        MeabyVec<MeabyWeighted<MapGenNestedIntermediate>> => Nested, "nested",
+        Value => Toilet, "toilets",
+        MeabyVec<MeabyWeighted<MapGenField>> => Field, "fields",
+        MeabyVec<MeabyWeighted<MapGenComputer>> => Computer, "computers",
+        MeabyVec<MeabyWeighted<MapGenSign>> => Sign, "signs",
+        MeabyVec<MeabyWeighted<MapGenGaspump>> => Gaspump, "gaspumps",
+        MeabyVec<MeabyWeighted<MapGenTrap>> => Trap, "traps",
+        MeabyVec<MeabyWeighted<MapGenVehicle>> => Vehicle, "vehicles",
+        MeabyVec<MeabyWeighted<MapGenCorpse>> => Corpse, "corpses",
+        MeabyVec<MeabyWeighted<MapGenAppliance>> => Appliance, "appliances",
+        MeabyVec<MeabyWeighted<MapGenNpc>> => Npc, "npcs",
+        MeabyVec<MeabyWeighted<MapGenGraffiti>> => Graffiti, "graffiti",
+        MeabyVec<MeabyWeighted<MapGenRubble>> => Rubble, "rubble",
+        MeabyVec<MeabyWeighted<MapGenLiquid>> => Liquid, "liquids",
+        MeabyVec<MeabyWeighted<MapGenSealedItem>> => SealedItem, "sealed_item",
+        MeabyVec<MeabyWeighted<MapGenLoot>> => Loot, "loot"
+    );
+
+    let sets = map_data
+        .set
+        .iter()
+        .map(|set| set.to_json())
+        .collect::<Result<Vec<Value>, serde_json::Error>>()?;
+    let sets: Vec<SetIntermediate> =
+        serde_json::from_value(Value::Array(sets))?;
+
+    set_field(object, "set", Some(&sets).filter(|s| !s.is_empty()))?;
+
+    // Sort the flags so exporting the same map twice gives the same result
+    let mut flags: Vec<MapDataFlag> = map_data.flags.iter().cloned().collect();
+    flags.sort_by_key(|f| format!("{:?}", f));
+
+    set_field(object, "flags", Some(&flags).filter(|f| !f.is_empty()))?;
+
+    macro_rules! set_all_places {
+        ($($inner: ty => $kind: ident, $key: literal),*) => {
+            $(set_places::<$inner>(object, $key, map_data, MappingKind::$kind)?;)*
+        };
+    }
+
+    set_all_places!(
+        PlaceInnerTerrain => Terrain, "place_terrain",
+        PlaceInnerFurniture => Furniture, "place_furniture",
+        PlaceInnerTraps => Trap, "place_traps",
+        PlaceInnerItems => ItemGroups, "place_items",
+        PlaceInnerComputers => Computer, "place_computers",
+        PlaceInnerSigns => Sign, "place_signs",
+        PlaceInnerToilets => Toilet, "place_toilets",
+        PlaceInnerGaspumps => Gaspump, "place_gaspumps",
+        PlaceInnerMonsters => Monsters, "place_monsters",
+        PlaceInnerMonster => Monster, "place_monster",
+        PlaceInnerFields => Field, "place_fields",
+        PlaceInnerNested => Nested, "place_nested",
+        PlaceInnerVehicles => Vehicle, "place_vehicles",
+        PlaceInnerCorpses => Corpse, "place_corpses",
+        PlaceInnerAppliances => Appliance, "place_appliances",
+        PlaceInnerNpcs => Npc, "place_npcs",
+        PlaceInnerGraffiti => Graffiti, "place_graffiti",
+        PlaceInnerRubble => Rubble, "place_rubble",
+        PlaceInnerLiquids => Liquid, "place_liquids",
+        PlaceInnerSealedItem => SealedItem, "place_sealed_item",
+        PlaceInnerLoot => Loot, "place_loot"
+    );
+
+    Ok(())
+}
+
+/// Converts the `map_data` into a CDDA mapgen object for the `om_terrain`.
+///
+/// Every field of the `original` mapgen object which cannot be represented by
+/// the map data, like the weight of the mapgen, is kept as it is.
+pub fn map_data_to_mapgen_json(
+    map_data: &MapData,
+    om_terrain: &CDDAIdentifier,
+    original: Option<Value>,
+) -> Result<Value, ExportMapgenError> {
+    let mut mapgen = match original {
+        None => json!({
+            "type": "mapgen",
+            "method": "json",
+            "om_terrain": om_terrain,
+            "object": {}
+        }),
+        Some(original) => original,
+    };
+
+    let nested = mapgen
+        .get("om_terrain")
+        .and_then(Value::as_array)
+        .is_some_and(|values| values.iter().any(Value::is_array));
+
+    if nested {
+        return Err(ExportMapgenError::NestedMapgen(om_terrain.clone()));
+    }
+
+    let object = mapgen
+        .as_object_mut()
+        .ok_or(ExportMapgenError::InvalidMapgen(om_terrain.clone()))?
+        .entry("object")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or(ExportMapgenError::InvalidMapgen(om_terrain.clone()))?;
+
+    write_mapgen_object(object, map_data)?;
+
+    Ok(mapgen)
+}
+
+fn is_mapgen_of(value: &Value, om_terrain: &CDDAIdentifier) -> bool {
+    let is_mapgen = value
+        .get("type")
+        .and_then(Value::as_str)
+        .is_some_and(|t| t.eq_ignore_ascii_case("mapgen"));
+
+    is_mapgen
+        && value
+            .get("om_terrain")
+            .is_some_and(|v| value_contains_id(v, om_terrain))
+}
+
+/// Reads the CDDA json file at `path`, or an empty list if it does not exist
+/// yet
+pub fn read_json_file(path: &Path) -> Result<Vec<Value>, ExportMapgenError> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| ExportMapgenError::Io(path.to_path_buf(), e))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| ExportMapgenError::InvalidJson(path.to_path_buf(), e))
+}
+
+/// Returns the mapgen object for the `om_terrain` in the `values`
+pub fn find_mapgen<'a>(
+    values: &'a [Value],
+    om_terrain: &CDDAIdentifier,
+) -> Option<&'a Value> {
+    values.iter().find(|v| is_mapgen_of(v, om_terrain))
+}
+
+/// Writes the `mapgen` for the `om_terrain` into the CDDA json file at `path`,
+/// replacing the existing mapgen of the `om_terrain` in that file
+pub fn write_mapgen(
+    path: &Path,
+    om_terrain: &CDDAIdentifier,
+    mapgen: Value,
+) -> Result<(), ExportMapgenError> {
+    let mut values = read_json_file(path)?;
+
+    match values.iter_mut().find(|v| is_mapgen_of(v, om_terrain)) {
+        None => values.push(mapgen),
+        Some(existing) => *existing = mapgen,
+    }
+
+    fs::write(path, serde_json::to_string_pretty(&values)?)
+        .map_err(|e| ExportMapgenError::Io(path.to_path_buf(), e))
+}
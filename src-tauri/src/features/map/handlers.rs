@@ -0,0 +1,140 @@
+use crate::features::jobs::Jobs;
+use crate::features::map::export::{
+    check_compatibility, find_mapgen, map_data_to_mapgen_json, read_json_file,
+    write_mapgen, ExportMapgenError, ExportTarget,
+};
+use crate::features::program_data::{
+    EditorData, LiveViewerData, Project, ProjectType, ZLevel,
+};
+use crate::features::raw_json::find_raw_json;
+use crate::impl_serialize_for_error;
+use crate::util::{get_current_project, GetCurrentProjectError, UVec2JsonKey};
+use cdda_lib::types::CDDAIdentifier;
+use glam::UVec2;
+use log::info;
+use serde::{Serialize, Serializer};
+use std::path::{Path, PathBuf};
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, State};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SaveMapgenError {
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    ExportError(#[from] ExportMapgenError),
+
+    #[error("There is no map at {0} on the z-level {1}")]
+    NoMap(String, ZLevel),
+
+    #[error("Exporting the mapgen was canceled")]
+    Canceled,
+}
+
+impl_serialize_for_error!(SaveMapgenError);
+
+/// Exports the map at the `coordinates` of the `project` as the mapgen of the
+/// `om_terrain` into the CDDA json file at `path`.
+///
+/// If the file or the files of a live viewer already contain a mapgen for the
+/// `om_terrain`, every field which can not be edited is taken from it. The
+/// mapgen is only written if the `export_target` supports it.
+pub fn export_mapgen(
+    project: &Project,
+    path: &Path,
+    z: ZLevel,
+    coordinates: UVec2,
+    om_terrain: &CDDAIdentifier,
+    export_target: ExportTarget,
+) -> Result<(), SaveMapgenError> {
+    let map_data = project
+        .maps
+        .get(&z)
+        .and_then(|c| c.maps.get(&coordinates))
+        .ok_or(SaveMapgenError::NoMap(
+            format!("{},{}", coordinates.x, coordinates.y),
+            z,
+        ))?;
+
+    let mut original = find_mapgen(&read_json_file(path)?, om_terrain).cloned();
+
+    if original.is_none() {
+        let mapgen_file_paths = match &project.ty {
+            ProjectType::MapEditor(_) => None,
+            ProjectType::LiveViewer(LiveViewerData::Terrain {
+                mapgen_file_paths,
+                ..
+            })
+            | ProjectType::LiveViewer(LiveViewerData::Special {
+                mapgen_file_paths,
+                ..
+            })
+            | ProjectType::LiveViewer(LiveViewerData::Linear {
+                mapgen_file_paths,
+                ..
+            })
+            | ProjectType::LiveViewer(LiveViewerData::Nested {
+                mapgen_file_paths,
+                ..
+            })
+            | ProjectType::LiveViewer(LiveViewerData::Update {
+                mapgen_file_paths,
+                ..
+            }) => Some(mapgen_file_paths),
+        };
+
+        original = mapgen_file_paths
+            .and_then(|p| find_raw_json(p, "mapgen", om_terrain).ok())
+            .and_then(|r| serde_json::from_str(&r.json).ok());
+    }
+
+    let mapgen = map_data_to_mapgen_json(map_data, om_terrain, original)?;
+    check_compatibility(&mapgen, export_target)?;
+    write_mapgen(path, om_terrain, mapgen)?;
+
+    info!("Saved mapgen of {} to {}", om_terrain, path.display());
+
+    Ok(())
+}
+
+/// Exports the map at the `coordinates` of the current project with
+/// [`export_mapgen`] and the configured export target. The export is tracked
+/// as a job and nothing is written if it is canceled before the mapgen was
+/// generated.
+#[tauri::command]
+pub async fn save_mapgen(
+    path: PathBuf,
+    z: ZLevel,
+    coordinates: UVec2JsonKey,
+    om_terrain: CDDAIdentifier,
+    app: AppHandle,
+    jobs: State<'_, Jobs>,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<(), SaveMapgenError> {
+    let job = jobs.start(&app, format!("Exporting mapgen of {}", om_terrain));
+
+    let result = async {
+        let editor_data_lock = editor_data.lock().await;
+
+        if job.is_canceled() {
+            return Err(SaveMapgenError::Canceled);
+        }
+
+        let project = get_current_project(&editor_data_lock)?;
+
+        export_mapgen(
+            project,
+            &path,
+            z,
+            coordinates.0,
+            &om_terrain,
+            editor_data_lock.config.export_target,
+        )
+    }
+    .await;
+
+    job.finish(&result);
+    result
+}
@@ -10,8 +10,10 @@ use crate::features::map::{MapData, MapDataRotation};
 use crate::features::program_data::{MapDataCollection, ZLevel};
 use crate::util::Load;
 use cdda_lib::types::CDDAIdentifier;
-use glam::UVec2;
+use glam::{IVec2, UVec2};
 use log::warn;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon::ThreadPoolBuilder;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -30,6 +32,8 @@ pub enum MapDataImporterError {
     #[error("The file at {0} is not a valid CDDA json file. CDDA Json files must have a top level array"
     )]
     InvalidJson(PathBuf),
+    #[error("The import of the map data was interrupted")]
+    Interrupted,
 }
 
 pub struct MapDataImporter {
@@ -37,14 +41,123 @@ pub struct MapDataImporter {
     pub om_ids: Vec<CDDAIdentifier>,
 }
 
+/// Maximum amount of map datas which are converted at the same time. The
+/// overmap terrains of a special only share their palettes, so they can be
+/// converted independently of each other.
+const MAX_PARALLEL_MAP_DATA_IMPORTS: usize = 8;
+
+/// The overmap terrains a found map data is used for
+enum MapDataImportTarget {
+    /// The map data at 0,0 is used for a single overmap terrain
+    Single(CDDAIdentifier),
+    /// Every map data is used for the overmap terrain at the same position
+    /// in the nested list
+    Nested(Vec<Vec<String>>),
+}
+
+impl MapDataImporter {
+    fn find_import_target(
+        &self,
+        om_terrain: &OmTerrain,
+    ) -> Option<MapDataImportTarget> {
+        for om_id_to_find in self.om_ids.iter() {
+            match om_terrain {
+                OmTerrain::Single(s) => {
+                    if om_id_to_find == &CDDAIdentifier(s.clone()) {
+                        return Some(MapDataImportTarget::Single(
+                            om_id_to_find.clone(),
+                        ));
+                    }
+                },
+                OmTerrain::Duplicate(duplicate) => {
+                    let any_matches = duplicate.iter().any(|d| {
+                        &CDDAIdentifier((*d).clone()) == om_id_to_find
+                    });
+
+                    if any_matches {
+                        return Some(MapDataImportTarget::Single(
+                            om_id_to_find.clone(),
+                        ));
+                    }
+                },
+                OmTerrain::Nested(n) => {
+                    let any_matches = n.iter().flatten().any(|s| {
+                        &CDDAIdentifier((*s).clone()) == om_id_to_find
+                    });
+
+                    if any_matches {
+                        return Some(MapDataImportTarget::Nested(n.clone()));
+                    }
+                },
+            }
+        }
+
+        None
+    }
+}
+
+fn import_map_data(
+    mdi: CDDAMapDataIntermediate,
+    target: MapDataImportTarget,
+) -> Vec<(CDDAIdentifier, MapData)> {
+    let map_data = match <CDDAMapDataIntermediate as TryInto<
+        MapDataCollection,
+    >>::try_into(mdi)
+    {
+        Ok(map_data) => map_data,
+        Err(e) => {
+            warn!("{}", e);
+            return vec![];
+        },
+    };
+
+    match target {
+        MapDataImportTarget::Single(om_id) => {
+            let mut map_data = map_data;
+
+            match map_data.maps.remove(&UVec2::ZERO) {
+                None => {
+                    warn!(
+                        "Missing map data at 0,0 for duplicate terrain {}",
+                        om_id
+                    );
+                    vec![]
+                },
+                Some(v) => vec![(om_id, v)],
+            }
+        },
+        MapDataImportTarget::Nested(n) => {
+            let mut found = vec![];
+
+            for (k, v) in map_data.maps {
+                let id = n
+                    .get(k.y as usize)
+                    .and_then(|id_list| id_list.get(k.x as usize));
+
+                match id {
+                    None => {
+                        warn!(
+                            "Missing nested terrain identifier list for map data at {}",
+                            k
+                        );
+                        break;
+                    },
+                    Some(id) => found.push((CDDAIdentifier(id.clone()), v)),
+                }
+            }
+
+            found
+        },
+    }
+}
+
 impl Load<HashMap<CDDAIdentifier, MapData>, MapDataImporterError>
     for MapDataImporter
 {
     async fn load(
         &mut self,
     ) -> Result<HashMap<CDDAIdentifier, MapData>, MapDataImporterError> {
-        let mut found_map_datas: HashMap<CDDAIdentifier, MapData> =
-            HashMap::new();
+        let mut to_import = vec![];
 
         for path in self.paths.iter() {
             let mut file = File::open(path).await.map_err(|e| {
@@ -72,139 +185,50 @@ impl Load<HashMap<CDDAIdentifier, MapData>, MapDataImporterError>
                     .collect();
 
             for mdi in importing_map_datas {
-                if let Some(om_terrain) = mdi.om_terrain.clone() {
-                    for om_id_to_find in self.om_ids.iter() {
-                        match &om_terrain {
-                            OmTerrain::Single(s) => {
-                                if om_id_to_find == &CDDAIdentifier(s.clone()) {
-                                    match <CDDAMapDataIntermediate as TryInto<
-                                        MapDataCollection,
-                                    >>::try_into(
-                                        mdi
-                                    ) {
-                                        Ok(mut map_data) => {
-                                            match map_data
-                                                .maps
-                                                .remove(&UVec2::ZERO)
-                                            {
-                                                None => {
-                                                    warn!("Missing map data at 0,0 for duplicate terrain {}", om_id_to_find);
-                                                    break;
-                                                },
-                                                Some(v) => {
-                                                    found_map_datas.insert(
-                                                        om_id_to_find.clone(),
-                                                        v,
-                                                    );
-                                                },
-                                            }
-                                        },
-                                        Err(e) => {
-                                            warn!("{}", e);
-                                        },
-                                    }
-
-                                    break;
-                                }
-                            },
-                            OmTerrain::Duplicate(duplicate) => {
-                                let any_matches = duplicate
-                                    .iter()
-                                    .find(|d| {
-                                        &CDDAIdentifier((*d).clone())
-                                            == om_id_to_find
-                                    })
-                                    .is_some();
-
-                                if any_matches {
-                                    match <CDDAMapDataIntermediate as TryInto<
-                                        MapDataCollection,
-                                    >>::try_into(
-                                        mdi
-                                    ) {
-                                        Ok(mut map_data) => {
-                                            match map_data
-                                                .maps
-                                                .remove(&UVec2::ZERO)
-                                            {
-                                                None => {
-                                                    warn!("Missing map data at 0,0 for duplicate terrain {}", om_id_to_find);
-                                                    break;
-                                                },
-                                                Some(v) => {
-                                                    found_map_datas.insert(
-                                                        om_id_to_find.clone(),
-                                                        v,
-                                                    );
-                                                },
-                                            }
-                                        },
-                                        Err(e) => {
-                                            warn!("{}", e);
-                                        },
-                                    }
-                                    break;
-                                }
-                            },
-                            OmTerrain::Nested(n) => {
-                                let any_matches = n
-                                    .iter()
-                                    .flatten()
-                                    .find(|s| {
-                                        &CDDAIdentifier((*s).clone())
-                                            == om_id_to_find
-                                    })
-                                    .is_some();
-
-                                if any_matches {
-                                    match <CDDAMapDataIntermediate as TryInto<
-                                        MapDataCollection,
-                                    >>::try_into(
-                                        mdi
-                                    ) {
-                                        Ok(map_data) => {
-                                            for (k, v) in map_data.maps {
-                                                let id_list = match n
-                                                    .get(k.y as usize)
-                                                {
-                                                    None => {
-                                                        warn!("Missing nested terrain identifier list for map data {}", om_id_to_find);
-                                                        break;
-                                                    },
-                                                    Some(id_list) => id_list,
-                                                };
-
-                                                match id_list.get(k.x as usize)
-                                                {
-                                                    None => {
-                                                        warn!("Missing nested terrain identifier list for map data {}", om_id_to_find);
-                                                        break;
-                                                    },
-                                                    Some(id) => {
-                                                        found_map_datas.insert(
-                                                            CDDAIdentifier(
-                                                                id.clone(),
-                                                            ),
-                                                            v,
-                                                        );
-                                                    },
-                                                }
-                                            }
-                                        },
-                                        Err(e) => {
-                                            warn!("{}", e);
-                                        },
-                                    }
-
-                                    break;
-                                }
-                            },
-                        };
-                    }
+                let target = match &mdi.om_terrain {
+                    None => continue,
+                    Some(om_terrain) => self.find_import_target(om_terrain),
+                };
+
+                if let Some(target) = target {
+                    to_import.push((mdi, target));
                 }
             }
         }
 
+        let import = move || {
+            to_import
+                .into_par_iter()
+                .map(|(mdi, target)| import_map_data(mdi, target))
+                .collect::<Vec<Vec<(CDDAIdentifier, MapData)>>>()
+        };
+
+        // The conversion is cpu bound, so it runs on a bounded thread pool
+        // instead of blocking the async runtime
+        let imported = tokio::task::spawn_blocking(move || {
+            match ThreadPoolBuilder::new()
+                .num_threads(MAX_PARALLEL_MAP_DATA_IMPORTS)
+                .build()
+            {
+                Ok(pool) => pool.install(import),
+                Err(e) => {
+                    warn!("Failed to create import thread pool, {}", e);
+                    import()
+                },
+            }
+        })
+        .await
+        .map_err(|_| MapDataImporterError::Interrupted)?;
+
+        // Insert in the order the map datas were found, so later definitions
+        // still replace earlier ones
+        let mut found_map_datas: HashMap<CDDAIdentifier, MapData> =
+            HashMap::new();
+
+        for (om_id, map_data) in imported.into_iter().flatten() {
+            found_map_datas.insert(om_id, map_data);
+        }
+
         Ok(found_map_datas)
     }
 }
@@ -230,11 +254,10 @@ pub struct SingleMapDataImporter {
     pub om_terrain: CDDAIdentifier,
 }
 
-impl Load<MapDataCollection, SingleMapDataImporterError>
-    for SingleMapDataImporter
-{
-    async fn load(
-        &mut self,
+impl SingleMapDataImporter {
+    async fn load_id(
+        &self,
+        om_terrain: &CDDAIdentifier,
     ) -> Result<MapDataCollection, SingleMapDataImporterError> {
         for path in &self.paths {
             let mut file = File::open(path).await.map_err(|e| {
@@ -268,12 +291,12 @@ impl Load<MapDataCollection, SingleMapDataImporterError>
                     serde_json::from_value(v);
 
                 if let Some(update_terrain) = &id_collection.update_mapgen_id {
-                    if self.om_terrain == *update_terrain {
+                    if *om_terrain == *update_terrain {
                         return match mdi {
                             Ok(mdi) => Ok(mdi.try_into()?),
                             Err(e) => {
                                 Err(SingleMapDataImporterError::InvalidMapData(
-                                    self.om_terrain.clone(),
+                                    om_terrain.clone(),
                                     e,
                                 ))
                             },
@@ -282,12 +305,12 @@ impl Load<MapDataCollection, SingleMapDataImporterError>
                 }
 
                 if let Some(nested_terrain) = &id_collection.nested_mapgen_id {
-                    if self.om_terrain == *nested_terrain {
+                    if *om_terrain == *nested_terrain {
                         return match mdi {
                             Ok(mdi) => Ok(mdi.try_into()?),
                             Err(e) => {
                                 Err(SingleMapDataImporterError::InvalidMapData(
-                                    self.om_terrain.clone(),
+                                    om_terrain.clone(),
                                     e,
                                 ))
                             },
@@ -295,13 +318,13 @@ impl Load<MapDataCollection, SingleMapDataImporterError>
                     }
                 }
 
-                if let Some(om_terrain) = &id_collection.om_terrain {
-                    match om_terrain {
+                if let Some(mapgen_om_terrain) = &id_collection.om_terrain {
+                    match mapgen_om_terrain {
                         OmTerrain::Single(s) => {
-                            if self.om_terrain == CDDAIdentifier((*s).clone()) {
+                            if *om_terrain == CDDAIdentifier((*s).clone()) {
                                 return match mdi {
                                     Ok(mdi) => Ok(mdi.try_into()?),
-                                    Err(e) => Err(SingleMapDataImporterError::InvalidMapData(self.om_terrain.clone(), e))
+                                    Err(e) => Err(SingleMapDataImporterError::InvalidMapData(om_terrain.clone(), e))
                                 };
                             }
                         },
@@ -310,13 +333,13 @@ impl Load<MapDataCollection, SingleMapDataImporterError>
                                 .iter()
                                 .find(|d| {
                                     CDDAIdentifier((*d).clone())
-                                        == self.om_terrain
+                                        == *om_terrain
                                 })
                                 .is_some()
                             {
                                 return match mdi {
                                     Ok(mdi) => Ok(mdi.try_into()?),
-                                    Err(e) => Err(SingleMapDataImporterError::InvalidMapData(self.om_terrain.clone(), e))
+                                    Err(e) => Err(SingleMapDataImporterError::InvalidMapData(om_terrain.clone(), e))
                                 };
                             }
                         },
@@ -325,13 +348,13 @@ impl Load<MapDataCollection, SingleMapDataImporterError>
                                 .flatten()
                                 .find(|s| {
                                     CDDAIdentifier((*s).clone())
-                                        == self.om_terrain
+                                        == *om_terrain
                                 })
                                 .is_some()
                             {
                                 return match mdi {
                                     Ok(mdi) => Ok(mdi.try_into()?),
-                                    Err(e) => Err(SingleMapDataImporterError::InvalidMapData(self.om_terrain.clone(), e))
+                                    Err(e) => Err(SingleMapDataImporterError::InvalidMapData(om_terrain.clone(), e))
                                 };
                             }
                         },
@@ -344,33 +367,217 @@ impl Load<MapDataCollection, SingleMapDataImporterError>
     }
 }
 
-fn remove_orientation_suffix_and_get_rotation(
-    om_id: CDDAIdentifier,
-) -> (CDDAIdentifier, MapDataRotation) {
-    let mut rotation = MapDataRotation::Deg0;
-    let mut final_overmap_id = om_id;
+impl Load<MapDataCollection, SingleMapDataImporterError>
+    for SingleMapDataImporter
+{
+    async fn load(
+        &mut self,
+    ) -> Result<MapDataCollection, SingleMapDataImporterError> {
+        match self.load_id(&self.om_terrain).await {
+            Err(SingleMapDataImporterError::NoMapDataFound) => {
+                // Rotated and linear overmap terrains use the mapgen of their
+                // base id
+                let (base_id, rotation) =
+                    resolve_om_terrain_id(self.om_terrain.clone());
+
+                if base_id == self.om_terrain {
+                    return Err(SingleMapDataImporterError::NoMapDataFound);
+                }
+
+                let mut collection = self.load_id(&base_id).await?;
 
-    if let Some(final_id) = final_overmap_id.0.strip_suffix("_north") {
-        final_overmap_id = final_id.into();
-        rotation = MapDataRotation::Deg0;
+                for map_data in collection.maps.values_mut() {
+                    map_data.rotation = rotation.clone();
+                }
+
+                Ok(rotate_map_coordinates(collection, &rotation))
+            },
+            result => result,
+        }
     }
+}
+
+/// Moves the maps of a mapgen which spans multiple overmap terrains to the
+/// positions they have when the whole mapgen is rotated by `rotation`, since
+/// rotating every map on its own would keep them next to the wrong neighbors
+pub fn rotate_map_coordinates(
+    collection: MapDataCollection,
+    rotation: &MapDataRotation,
+) -> MapDataCollection {
+    let size = collection
+        .maps
+        .keys()
+        .fold(UVec2::ZERO, |size, coordinates| size.max(*coordinates + 1));
+
+    let maps = collection
+        .maps
+        .into_iter()
+        .map(|(coordinates, map_data)| {
+            let rotated = match rotation {
+                MapDataRotation::Deg0 => coordinates,
+                MapDataRotation::Deg90 => {
+                    UVec2::new(size.y - 1 - coordinates.y, coordinates.x)
+                },
+                MapDataRotation::Deg180 => UVec2::new(
+                    size.x - 1 - coordinates.x,
+                    size.y - 1 - coordinates.y,
+                ),
+                MapDataRotation::Deg270 => {
+                    UVec2::new(coordinates.y, size.x - 1 - coordinates.x)
+                },
+            };
+
+            (rotated, map_data)
+        })
+        .collect();
 
-    if let Some(final_id) = final_overmap_id.0.strip_suffix("_east") {
-        final_overmap_id = final_id.into();
-        rotation = MapDataRotation::Deg90;
+    MapDataCollection { maps }
+}
+
+/// Suffixes of linear overmap terrains like roads, the suffix of the mapgen
+/// which is used for them and the rotation of that mapgen
+const LINEAR_SUFFIXES: [(&str, &str, MapDataRotation); 15] = [
+    ("_nesw", "_four_way", MapDataRotation::Deg0),
+    ("_end_north", "_end", MapDataRotation::Deg0),
+    ("_end_east", "_end", MapDataRotation::Deg90),
+    ("_end_south", "_end", MapDataRotation::Deg180),
+    ("_end_west", "_end", MapDataRotation::Deg270),
+    ("_nes", "_tee", MapDataRotation::Deg0),
+    ("_esw", "_tee", MapDataRotation::Deg90),
+    ("_nsw", "_tee", MapDataRotation::Deg180),
+    ("_new", "_tee", MapDataRotation::Deg270),
+    ("_ns", "_straight", MapDataRotation::Deg0),
+    ("_ew", "_straight", MapDataRotation::Deg90),
+    ("_ne", "_curved", MapDataRotation::Deg0),
+    ("_es", "_curved", MapDataRotation::Deg90),
+    ("_sw", "_curved", MapDataRotation::Deg180),
+    ("_wn", "_curved", MapDataRotation::Deg270),
+];
+
+/// Suffixes of rotated overmap terrains and their rotation
+const ROTATION_SUFFIXES: [(&str, MapDataRotation); 4] = [
+    ("_north", MapDataRotation::Deg0),
+    ("_east", MapDataRotation::Deg90),
+    ("_south", MapDataRotation::Deg180),
+    ("_west", MapDataRotation::Deg270),
+];
+
+/// Resolves an overmap terrain id with a rotation suffix like `_east` or a
+/// linear suffix like `_end_south` to the id of its base mapgen and the
+/// rotation which has to be applied to that mapgen. Ids without a suffix are
+/// returned unchanged.
+pub fn resolve_om_terrain_id(
+    om_id: CDDAIdentifier,
+) -> (CDDAIdentifier, MapDataRotation) {
+    for (suffix, mapgen_suffix, rotation) in LINEAR_SUFFIXES.iter() {
+        if let Some(base_id) = om_id.0.strip_suffix(suffix) {
+            return (
+                CDDAIdentifier(format!("{}{}", base_id, mapgen_suffix)),
+                rotation.clone(),
+            );
+        }
     }
 
-    if let Some(final_id) = final_overmap_id.0.strip_suffix("_south") {
-        final_overmap_id = final_id.into();
-        rotation = MapDataRotation::Deg180;
+    for (suffix, rotation) in ROTATION_SUFFIXES.iter() {
+        if let Some(base_id) = om_id.0.strip_suffix(suffix) {
+            return (base_id.into(), rotation.clone());
+        }
     }
 
-    if let Some(final_id) = final_overmap_id.0.strip_suffix("_west") {
-        final_overmap_id = final_id.into();
-        rotation = MapDataRotation::Deg270;
+    (om_id, MapDataRotation::Deg0)
+}
+
+/// Imports every connection variant of a linear overmap terrain like a road
+/// or a sewer from the mapgens of its base id. Every kind of connection gets
+/// its own row, with the rotated variants of it placed side by side.
+pub struct LinearTerrainImporter {
+    pub paths: Vec<PathBuf>,
+    pub om_id: CDDAIdentifier,
+}
+
+impl LinearTerrainImporter {
+    /// The ids of the mapgens which are used for the variants of the linear
+    /// overmap terrain, in the order of the rows of the preview
+    pub fn mapgen_ids(om_id: &CDDAIdentifier) -> Vec<CDDAIdentifier> {
+        let mut mapgen_ids = vec![];
+
+        for (_, mapgen_suffix, _) in LINEAR_SUFFIXES.iter() {
+            let mapgen_id =
+                CDDAIdentifier(format!("{}{}", om_id.0, mapgen_suffix));
+
+            if !mapgen_ids.contains(&mapgen_id) {
+                mapgen_ids.push(mapgen_id);
+            }
+        }
+
+        mapgen_ids
     }
+}
+
+impl Load<MapDataCollection, SingleMapDataImporterError>
+    for LinearTerrainImporter
+{
+    async fn load(
+        &mut self,
+    ) -> Result<MapDataCollection, SingleMapDataImporterError> {
+        let importer = SingleMapDataImporter {
+            paths: self.paths.clone(),
+            om_terrain: self.om_id.clone(),
+        };
+
+        let mapgen_ids = Self::mapgen_ids(&self.om_id);
+        let mut base_maps = HashMap::new();
+
+        for mapgen_id in mapgen_ids.iter() {
+            match importer.load_id(mapgen_id).await {
+                Ok(mut collection) => {
+                    if let Some(map_data) =
+                        collection.maps.remove(&UVec2::ZERO)
+                    {
+                        base_maps.insert(mapgen_id.clone(), map_data);
+                    }
+                },
+                // Not every linear terrain defines all kinds of connections
+                Err(SingleMapDataImporterError::NoMapDataFound) => {
+                    warn!("No mapgen found for linear terrain {}", mapgen_id);
+                },
+                Err(e) => return Err(e),
+            }
+        }
+
+        if base_maps.is_empty() {
+            return Err(SingleMapDataImporterError::NoMapDataFound);
+        }
 
-    (final_overmap_id, rotation)
+        let mut collection = MapDataCollection {
+            maps: HashMap::new(),
+        };
+        let mut row_lengths: HashMap<usize, u32> = HashMap::new();
+
+        for (_, mapgen_suffix, rotation) in LINEAR_SUFFIXES.iter() {
+            let mapgen_id =
+                CDDAIdentifier(format!("{}{}", self.om_id.0, mapgen_suffix));
+
+            let Some(base_map) = base_maps.get(&mapgen_id) else {
+                continue;
+            };
+
+            // Safe since the mapgen ids are built from the same suffixes
+            let row =
+                mapgen_ids.iter().position(|id| id == &mapgen_id).unwrap();
+            let column = row_lengths.entry(row).or_default();
+
+            let mut map_data = base_map.clone();
+            map_data.rotation = rotation.clone();
+
+            collection
+                .maps
+                .insert(UVec2::new(*column, row as u32), map_data);
+            *column += 1;
+        }
+
+        Ok(collection)
+    }
 }
 
 #[derive(Debug, Error)]
@@ -395,15 +602,24 @@ pub struct OvermapSpecialImporter {
     pub mapgen_entry_paths: Vec<PathBuf>,
 }
 
-impl Load<HashMap<ZLevel, MapDataCollection>, OvermapSpecialImporterError>
-    for OvermapSpecialImporter
-{
-    async fn load(
-        &mut self,
-    ) -> Result<HashMap<ZLevel, MapDataCollection>, OvermapSpecialImporterError>
-    {
-        let mut aggregated_map_data: HashMap<ZLevel, MapDataCollection> =
-            HashMap::new();
+/// The position of the overmap with the smallest x and y coordinates of a
+/// special. Specials can extend into negative coordinates, so every overmap
+/// is placed relative to this origin
+pub fn overmap_special_origin(overmaps: &[OvermapSpecialOvermap]) -> IVec2 {
+    overmaps
+        .iter()
+        .map(|o| o.point.truncate())
+        .reduce(|a, b| a.min(b))
+        .unwrap_or(IVec2::ZERO)
+}
+
+impl OvermapSpecialImporter {
+    /// Reads the overmaps of the fixed overmap special from all the
+    /// `overmap_special_paths`
+    pub async fn load_overmaps(
+        &self,
+    ) -> Result<Vec<OvermapSpecialOvermap>, OvermapSpecialImporterError> {
+        let mut overmaps = Vec::new();
 
         for path in &self.overmap_special_paths {
             let mut file = File::open(path).await.map_err(|e| {
@@ -428,84 +644,86 @@ impl Load<HashMap<ZLevel, MapDataCollection>, OvermapSpecialImporterError>
                         serde_json::from_value::<CDDAOvermapSpecialIntermediate>(v)
                             .ok()
                     })
-                    .find_map(|t| t.id.clone().into_vec().into_iter().find_map(|ident| {
-                        if ident == self.om_special_id {
-                            return Some(t.clone());
-                        }
-
-                        None
-                    }))
+                    .find(|t| t.id.iter().any(|ident| *ident == self.om_special_id))
                     .ok_or(OvermapSpecialImporterError::NoOvermapSpecialFound(self.om_special_id.0.clone()))?;
 
             let overmap_special: CDDAOvermapSpecial = overmap_special.into();
 
-            let om_specials: Vec<OvermapSpecialOvermap> =
-                match overmap_special.ty {
-                    OvermapSpecialSubType::Fixed { overmaps, .. } => overmaps,
-                    OvermapSpecialSubType::Mutable { .. } => return Err(
+            match overmap_special.ty {
+                OvermapSpecialSubType::Fixed {
+                    overmaps: special_overmaps,
+                } => overmaps.extend(special_overmaps),
+                OvermapSpecialSubType::Mutable { .. } => {
+                    return Err(
                         OvermapSpecialImporterError::MutableOvermapNotSupported,
-                    ),
-                };
-
-            let mut importer = MapDataImporter {
-                paths: self.mapgen_entry_paths.clone(),
-                om_ids: om_specials
-                    .clone()
-                    .into_iter()
-                    .map(|s| {
-                        remove_orientation_suffix_and_get_rotation(
-                            s.overmap.unwrap_or("null".into()),
-                        )
-                        .0
-                    })
-                    .collect(),
-            };
-
-            let data = importer.load().await?;
+                    )
+                },
+            }
+        }
 
-            for om_special in om_specials {
-                let (final_id, rotation) =
-                    remove_orientation_suffix_and_get_rotation(
-                        om_special.overmap.unwrap_or("null".into()),
-                    );
+        Ok(overmaps)
+    }
+}
 
-                let mut map_data = match data.get(&final_id) {
-                    None => continue,
-                    Some(md) => md.clone(),
-                };
-                map_data.rotation = rotation;
+impl Load<HashMap<ZLevel, MapDataCollection>, OvermapSpecialImporterError>
+    for OvermapSpecialImporter
+{
+    async fn load(
+        &mut self,
+    ) -> Result<HashMap<ZLevel, MapDataCollection>, OvermapSpecialImporterError>
+    {
+        let mut aggregated_map_data: HashMap<ZLevel, MapDataCollection> =
+            HashMap::new();
 
-                match aggregated_map_data.get_mut(&om_special.point.z) {
-                    None => {
-                        aggregated_map_data.insert(
-                            om_special.point.z,
-                            MapDataCollection::default(),
-                        );
+        let om_specials = self.load_overmaps().await?;
+        let origin = overmap_special_origin(&om_specials);
+
+        let mut importer = MapDataImporter {
+            paths: self.mapgen_entry_paths.clone(),
+            // Search for both the full ids and their base ids, since
+            // some mapgens are written for the rotated id directly
+            om_ids: om_specials
+                .iter()
+                .flat_map(|s| {
+                    let om_id = s.overmap.clone().unwrap_or("null".into());
+                    let (base_id, _) = resolve_om_terrain_id(om_id.clone());
+                    [om_id, base_id]
+                })
+                .collect(),
+        };
+
+        let data = importer.load().await?;
+
+        for om_special in om_specials {
+            let om_id = om_special.overmap.unwrap_or("null".into());
+
+            let map_data = match data.get(&om_id) {
+                Some(md) => md.clone(),
+                None => {
+                    let (base_id, rotation) = resolve_om_terrain_id(om_id);
+
+                    match data.get(&base_id) {
+                        None => continue,
+                        Some(md) => {
+                            let mut md = md.clone();
+                            md.rotation = rotation;
+                            md
+                        },
+                    }
+                },
+            };
 
-                        let map_data_collection = aggregated_map_data
-                            .get_mut(&om_special.point.z)
-                            // Safe since we just inserted it
-                            .unwrap();
-
-                        map_data_collection.maps.insert(
-                            UVec2::new(
-                                om_special.point.x as u32,
-                                om_special.point.y as u32,
-                            ),
-                            map_data,
-                        );
-                    },
-                    Some(s) => {
-                        s.maps.insert(
-                            UVec2::new(
-                                om_special.point.x as u32,
-                                om_special.point.y as u32,
-                            ),
-                            map_data,
-                        );
-                    },
-                }
-            }
+            let position = om_special.point.truncate() - origin;
+
+            aggregated_map_data
+                .entry(om_special.point.z)
+                // Not the default collection, which would place an empty map
+                // at the origin even if the special has no overmap there
+                .or_insert_with(|| MapDataCollection {
+                    maps: HashMap::new(),
+                })
+                .maps
+                .insert(position.as_uvec2(), map_data);
         }
 
         Ok(aggregated_map_data)
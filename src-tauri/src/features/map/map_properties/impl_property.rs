@@ -2,23 +2,23 @@ use crate::data::item::{ItemEntry, ItemGroupSubtype};
 use crate::data::map_data::{
     MapGenGaspumpFuelType, VehicleStatus,
 };
-use crate::data::vehicle_parts::{CDDAVehiclePart, Location};
-use crate::data::vehicles::VehiclePart;
+use crate::data::vehicles::get_part_sprite_rotation;
 use crate::features::map::map_properties::{
-    ComputersProperty, CorpsesProperty, FieldsProperty, FurnitureProperty,
-    GaspumpsProperty, ItemsProperty, MonstersProperty, NestedProperty,
+    weighted_to_json, AppliancesProperty, ComputersProperty, CorpsesProperty,
+    FieldsProperty, FurnitureProperty, GaspumpsProperty, GraffitiProperty,
+    ItemsProperty, LiquidsProperty, LootProperty, MonstersProperty,
+    NestedProperty, NpcsProperty, RubbleProperty, SealedItemProperty,
     SignsProperty, TerrainProperty, ToiletsProperty, TrapsProperty,
     VehiclesProperty,
 };
 use crate::features::map::*;
+use crate::features::tileset::legacy_tileset::CORPSE_PREFIX;
 use crate::util::GetRandom;
 use cdda_lib::{NULL_FIELD, NULL_NESTED, NULL_TRAP};
 use log::error;
 use num_traits::real::Real;
 use rand::prelude::IndexedRandom;
-use rand::random_range;
-use std::fmt::{Display, Formatter};
-use std::str::FromStr;
+use serde_json::{Map, Value};
 
 impl Property for TerrainProperty {
     fn get_commands(
@@ -26,10 +26,11 @@ impl Property for TerrainProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
         let ident = self
             .mapgen_value
-            .get_identifier(&map_data.calculated_parameters)
+            .get_identifier_with(&map_data.calculated_parameters, rng)
             .ok()?;
 
         if ident == CDDAIdentifier::from(NULL_TERRAIN) {
@@ -45,6 +46,10 @@ impl Property for TerrainProperty {
 
         Some(vec![command])
     }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(&self.mapgen_value)
+    }
 }
 
 impl Property for MonstersProperty {
@@ -53,22 +58,26 @@ impl Property for MonstersProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
-        let monster = self.monster.get_random();
+        let monster = self.monster.get_random_with(rng);
 
         let ident = match monster
             .chance
             .clone()
             .unwrap_or(NumberOrRange::Number(1))
-            .is_random_hit(100)
+            .is_random_hit(100, rng)
         {
             true => match &monster.id {
-                MapGenMonsterType::Monster { monster } => {
-                    monster.get_identifier(&map_data.calculated_parameters).ok()
-                },
+                MapGenMonsterType::Monster { monster } => monster
+                    .get_identifier_with(&map_data.calculated_parameters, rng)
+                    .ok(),
                 MapGenMonsterType::MonsterGroup { group } => {
                     let id = group
-                        .get_identifier(&map_data.calculated_parameters)
+                        .get_identifier_with(
+                            &map_data.calculated_parameters,
+                            rng,
+                        )
                         .ok()?;
                     let mon_group = json_data.monster_groups.get(&id)?;
 
@@ -76,11 +85,15 @@ impl Property for MonstersProperty {
                         .get_random_monster(
                             &json_data.monster_groups,
                             &map_data.calculated_parameters,
+                            rng,
                         )
                         .ok();
 
                     rand_monster?
-                        .get_identifier(&map_data.calculated_parameters)
+                        .get_identifier_with(
+                            &map_data.calculated_parameters,
+                            rng,
+                        )
                         .ok()
                 },
             },
@@ -103,6 +116,10 @@ impl Property for MonstersProperty {
 
         None
     }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.monster)
+    }
 }
 
 impl Property for FurnitureProperty {
@@ -111,10 +128,11 @@ impl Property for FurnitureProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
         let ident = self
             .mapgen_value
-            .get_identifier(&map_data.calculated_parameters)
+            .get_identifier_with(&map_data.calculated_parameters, rng)
             .ok()?;
 
         if ident == CDDAIdentifier::from(NULL_FURNITURE) {
@@ -130,6 +148,10 @@ impl Property for FurnitureProperty {
 
         Some(vec![command])
     }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(&self.mapgen_value)
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -144,6 +166,7 @@ impl Property for SignsProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
         let command = SetTile::furniture(
             TilesheetCDDAId::simple("f_sign"),
@@ -153,6 +176,10 @@ impl Property for SignsProperty {
         );
         Some(vec![command])
     }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.signs)
+    }
 }
 
 impl Property for NestedProperty {
@@ -161,51 +188,29 @@ impl Property for NestedProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
-        let rng = rng();
-        let nested_chunk = self.nested.get_random();
-
-        let should_place = match &nested_chunk.neighbors {
-            None => true,
-            Some(neighbors) => {
-                neighbors.iter().all(|(dir, om_terrain_match)| {
-                    let simulated_neighbor = map_data
-                        .config
-                        .simulated_neighbors
-                        .get(dir)
-                        .expect("Simulated neighbor must always exist");
-
-                    om_terrain_match.iter().all(|om_terrain| {
-                        if simulated_neighbor.is_empty() {
-                            return false;
-                        }
-
-                        simulated_neighbor
-                            .iter()
-                            .all(|id| om_terrain.matches_identifier(id))
-                    })
-                })
-            },
-        };
+        let nested_chunk = self.nested.get_random_with(rng);
+        let chunks = nested_chunk.chunks_for(&map_data.config);
 
-        if nested_chunk.invert_condition {
-            if should_place {
-                return None;
-            }
-        } else if !should_place {
+        if chunks.is_empty() {
             return None;
         }
 
-        let selected_chunk = nested_chunk
-            .chunks
-            .get_random()
-            .get_identifier(&map_data.calculated_parameters)
+        let selected_chunk = chunks
+            .get_random_with(rng)
+            .get_identifier_with(&map_data.calculated_parameters, rng)
             .ok()?;
 
         if selected_chunk == CDDAIdentifier::from(NULL_NESTED) {
             return None;
         }
 
+        if map_data.config.nesting_depth >= MAX_NESTING_DEPTH {
+            error!("Nested Mapgen {} is nested too deep", selected_chunk);
+            return None;
+        }
+
         let nested_mapgen = match json_data.map_data.get(&selected_chunk) {
             None => {
                 error!("Nested Mapgen {} not found", selected_chunk);
@@ -214,14 +219,60 @@ impl Property for NestedProperty {
             Some(v) => v,
         };
 
-        let mut commands = nested_mapgen.get_commands(json_data);
+        let nested_mapgen =
+            match nested_mapgen.nested_in(map_data, &json_data.palettes, rng) {
+                Ok(n) => n,
+                Err(e) => {
+                    error!("Nested Mapgen {}: {}", selected_chunk, e);
+                    return None;
+                },
+            };
 
-        commands.iter_mut().for_each(|c| {
-            c.coordinates.x += position.x;
-            c.coordinates.y = position.y + c.coordinates.y;
-        });
+        Some(map_data.get_nested_commands(
+            &nested_mapgen,
+            position,
+            json_data,
+            rng,
+        ))
+    }
 
-        Some(commands)
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        let nested = self
+            .nested
+            .iter()
+            .map(|nested| {
+                let mut object = Map::new();
+                object.insert(
+                    "chunks".to_string(),
+                    serde_json::to_value(&nested.data.chunks)?,
+                );
+
+                if !nested.data.else_chunks.is_empty() {
+                    object.insert(
+                        "else_chunks".to_string(),
+                        serde_json::to_value(&nested.data.else_chunks)?,
+                    );
+                }
+
+                if let Some(neighbors) = &nested.data.neighbors {
+                    object.insert(
+                        "neighbors".to_string(),
+                        serde_json::to_value(neighbors)?,
+                    );
+                }
+
+                if let Some(joins) = &nested.data.joins {
+                    object.insert(
+                        "joins".to_string(),
+                        serde_json::to_value(joins)?,
+                    );
+                }
+
+                Ok(Weighted::new(Value::Object(object), nested.weight))
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>()?;
+
+        weighted_to_json(&nested)
     }
 }
 
@@ -231,8 +282,9 @@ impl Property for FieldsProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
-        let field = self.field.get_random();
+        let field = self.field.get_random_with(rng);
 
         if field.field == CDDAIdentifier::from(NULL_FIELD) {
             return None;
@@ -246,6 +298,10 @@ impl Property for FieldsProperty {
         );
         Some(vec![command])
     }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.field)
+    }
 }
 
 impl Property for GaspumpsProperty {
@@ -254,8 +310,9 @@ impl Property for GaspumpsProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
-        let gaspump = self.gaspumps.get_random();
+        let gaspump = self.gaspumps.get_random_with(rng);
 
         let id = match &gaspump.fuel {
             None => "t_gas_pump",
@@ -275,6 +332,10 @@ impl Property for GaspumpsProperty {
         );
         Some(vec![command])
     }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.gaspumps)
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -315,6 +376,7 @@ impl ItemsProperty {
         &self,
         entries: &Vec<ItemEntry>,
         json_data: &DeserializedCDDAJsonData,
+        region: Option<&CDDAIdentifier>,
         group_probability: f32,
     ) -> Vec<DisplayItemGroup> {
         let mut display_item_groups: Vec<DisplayItemGroup> = Vec::new();
@@ -341,9 +403,11 @@ impl ItemsProperty {
                     display_item_groups.push(display_item);
                 },
                 ItemEntry::Group(g) => {
+                    let group_id =
+                        json_data.resolve_region_setting(&g.group, region);
                     let other_group =
-                        &json_data.item_groups.get(&g.group).expect(
-                            format!("Item Group {} to exist", &g.group)
+                        &json_data.item_groups.get(&group_id).expect(
+                            format!("Item Group {} to exist", &group_id)
                                 .as_str(),
                         );
 
@@ -353,6 +417,7 @@ impl ItemsProperty {
                     let display_items = self.get_display_items_from_entries(
                         &other_group.common.entries,
                         json_data,
+                        region,
                         probability,
                     );
 
@@ -390,6 +455,7 @@ impl ItemsProperty {
                     let display_items = self.get_display_items_from_entries(
                         distribution,
                         json_data,
+                        region,
                         probability,
                     );
 
@@ -412,6 +478,7 @@ impl ItemsProperty {
                     let display_items = self.get_display_items_from_entries(
                         collection,
                         json_data,
+                        region,
                         probability,
                     );
 
@@ -434,7 +501,17 @@ impl ItemsProperty {
     }
 }
 
-impl Property for ItemsProperty {}
+impl Property for ItemsProperty {
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.items)
+    }
+}
+
+impl Property for LootProperty {
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.loot)
+    }
+}
 
 impl Property for ComputersProperty {
     fn get_commands(
@@ -442,6 +519,7 @@ impl Property for ComputersProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
         let command = SetTile::furniture(
             TilesheetCDDAId::simple("f_console"),
@@ -452,6 +530,10 @@ impl Property for ComputersProperty {
 
         Some(vec![command])
     }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.computer)
+    }
 }
 
 impl Property for ToiletsProperty {
@@ -460,6 +542,7 @@ impl Property for ToiletsProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
         let command = SetTile::furniture(
             TilesheetCDDAId::simple("f_toilet"),
@@ -470,6 +553,10 @@ impl Property for ToiletsProperty {
 
         Some(vec![command])
     }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        Ok(self.toilet.clone())
+    }
 }
 
 impl Property for TrapsProperty {
@@ -478,16 +565,18 @@ impl Property for TrapsProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
-        let trap = self.trap.get_random();
-        let ident =
-            trap.get_identifier(&map_data.calculated_parameters).ok()?;
+        let trap = self.trap.get_random_with(rng);
+        let ident = trap
+            .get_identifier_with(&map_data.calculated_parameters, rng)
+            .ok()?;
 
         if ident == CDDAIdentifier::from(NULL_TRAP) {
             return None;
         }
 
-        let command = SetTile::furniture(
+        let command = SetTile::trap(
             TilesheetCDDAId::simple(ident),
             position.clone(),
             Rotation::Deg0,
@@ -496,24 +585,9 @@ impl Property for TrapsProperty {
 
         Some(vec![command])
     }
-}
-
-#[derive(Debug, Clone)]
-struct VehiclePartSpriteVariant {
-    pub variant: String,
-}
 
-impl VehiclePartSpriteVariant {
-    pub fn new(variant: impl Into<String>) -> Self {
-        Self {
-            variant: variant.into(),
-        }
-    }
-}
-
-impl Display for VehiclePartSpriteVariant {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.variant.as_str())
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.trap)
     }
 }
 
@@ -523,8 +597,9 @@ impl Property for VehiclesProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
-        let mapgen_vehicle = self.vehicles.get_random();
+        let mapgen_vehicle = self.vehicles.get_random_with(rng);
 
         let vehicle = match json_data.vehicles.get(&mapgen_vehicle.vehicle) {
             None => {
@@ -536,115 +611,33 @@ impl Property for VehiclesProperty {
 
         let mut commands = Vec::new();
 
-        let mut highest_priority_parts: HashMap<
-            IVec2,
-            (&CDDAVehiclePart, Option<VehiclePartSpriteVariant>, usize),
-        > = HashMap::new();
-
         let random_rotation = mapgen_vehicle
             .rotation
-            .clone()
-            .into_vec()
-            .choose(&mut rng())
+            .as_slice()
+            .choose(rng)
             .map(Clone::clone)
             .unwrap_or(0);
 
-        let rotation_radians = (random_rotation as f32).to_radians();
-
-        for part in vehicle.parts.iter() {
-            // Positive y -> right
-            // Negative y -> left
-            // Positive x -> up
-            // Negative x -> down
-            let base_position = IVec2::new(part.x, part.y);
-
-            // TODO: This rotation looks pretty munted for any rotation other than 0, 90, 180 and 270 degrees
-            let rotated_x = (base_position.x as f32 * rotation_radians.cos()
-                - base_position.y as f32 * rotation_radians.sin())
-            .round() as i32;
-            let rotated_y = (base_position.x as f32 * rotation_radians.sin()
-                + base_position.y as f32 * rotation_radians.cos())
-            .round() as i32;
-
-            let part_position = IVec2::new(rotated_x, rotated_y);
-
-            for vp in part.parts.iter() {
-                let ident = match vp {
-                    VehiclePart::Inline(id) => id.clone(),
-                    VehiclePart::Object { part, .. } => part.clone(),
-                };
-
-                let (raw_ident, ty) = match ident.0.split_once('#') {
-                    None => (ident.clone(), None),
-                    Some((ident, ty)) => (
-                        ident.into(),
-                        Some(VehiclePartSpriteVariant::new(ty.to_string())),
-                    ),
-                };
-
-                let vp_entry = match json_data.vehicle_parts.get(&raw_ident) {
-                    None => {
-                        warn!(
-                            "Vehicle Part {} does not exist in cdda data",
-                            raw_ident
-                        );
-                        continue;
-                    },
-                    Some(vp) => vp,
-                };
-
-                let location: Location = Location::from_str(
-                    &vp_entry
-                        .location
-                        .clone()
-                        .unwrap_or("structure".to_string()),
-                )
-                .unwrap_or(Location::Structure);
-
-                let highest_priority_part =
-                    match highest_priority_parts.get(&part_position) {
-                        None => {
-                            highest_priority_parts.insert(
-                                part_position,
-                                (vp_entry, ty.clone(), location.priority()),
-                            );
-
-                            highest_priority_parts.get(&part_position).unwrap()
-                        },
-                        Some(p) => p,
-                    };
-
-                if location.priority() > highest_priority_part.2 {
-                    highest_priority_parts.insert(
-                        part_position,
-                        (vp_entry, ty, location.priority()),
-                    );
-                }
-            }
-        }
+        let visible_parts =
+            vehicle.get_visible_parts(&json_data.vehicle_parts, random_rotation);
 
         // Generate visible mapping commands
-        for (pos, (part, ty, _)) in highest_priority_parts {
-            let rotation = match random_rotation % 360 {
-                0..90 => Rotation::Deg270,
-                180..270 => Rotation::Deg90,
-                // TODO: dirty hack to make the rotation work "counter clockwise"
-                n => Rotation::from(n + 90),
-            };
+        for (pos, visible_part) in visible_parts {
+            let rotation = get_part_sprite_rotation(random_rotation);
 
             // TODO: Not that accurate to what it will look like in game since the status can also
             // remove tiles and do other things,
             // but for the purposes of this editor i think this i enough
             let tile_state = match mapgen_vehicle.status {
                 VehicleStatus::LightDamage => {
-                    if random_range(0..3) == 0 {
+                    if rng.random_range(0..3) == 0 {
                         TileState::Broken
                     } else {
                         TileState::Normal
                     }
                 },
                 VehicleStatus::HeavilyDamaged => {
-                    if random_range(0..5) == 0 {
+                    if rng.random_range(0..5) == 0 {
                         TileState::Normal
                     } else {
                         TileState::Broken
@@ -655,11 +648,11 @@ impl Property for VehiclesProperty {
                 },
             };
 
-            commands.push(SetTile::furniture(
+            commands.push(SetTile::vehicle(
                 TilesheetCDDAId {
-                    id: part.id.clone(),
+                    id: visible_part.part.id.clone(),
                     prefix: Some("vp".to_string()),
-                    postfix: ty.map(|t| t.variant),
+                    postfix: visible_part.variant,
                 },
                 position + pos,
                 rotation,
@@ -669,6 +662,10 @@ impl Property for VehiclesProperty {
 
         Some(commands)
     }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.vehicles)
+    }
 }
 
 impl Property for CorpsesProperty {
@@ -677,8 +674,9 @@ impl Property for CorpsesProperty {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
-        let mapgen_corpse = self.corpses.get_random();
+        let mapgen_corpse = self.corpses.get_random_with(rng);
 
         let group = match json_data.monster_groups.get(&mapgen_corpse.group) {
             None => {
@@ -691,6 +689,7 @@ impl Property for CorpsesProperty {
         let monster = match group.get_random_monster(
             &json_data.monster_groups,
             &map_data.calculated_parameters,
+            rng,
         ) {
             Ok(m) => m,
             Err(e) => {
@@ -699,16 +698,225 @@ impl Property for CorpsesProperty {
             },
         };
 
-        Some(vec![SetTile {
-            id: TilesheetCDDAId {
-                id: monster,
-                prefix: Some("corpse".into()),
+        Some(vec![
+            SetTile::monster(
+                TilesheetCDDAId {
+                    id: monster,
+                    prefix: Some(CORPSE_PREFIX.into()),
+                    postfix: None,
+                },
+                position.clone(),
+                Rotation::Deg0,
+                TileState::Normal,
+            ),
+            SetTile::annotation(
+                TileAnnotation::Corpse {
+                    group: mapgen_corpse.group.clone(),
+                    age: mapgen_corpse.age,
+                },
+                position.clone(),
+            ),
+        ])
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.corpses)
+    }
+}
+
+impl Property for AppliancesProperty {
+    fn get_commands(
+        &self,
+        position: &IVec2,
+        map_data: &MapData,
+        json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
+    ) -> Option<Vec<SetTile>> {
+        let mapgen_appliance = self.appliances.get_random_with(rng);
+
+        let part = match json_data.get_appliance_part(&mapgen_appliance.item) {
+            None => {
+                warn!("Appliance {} not found", mapgen_appliance.item);
+                return None;
+            },
+            Some(p) => p,
+        };
+
+        Some(vec![SetTile::vehicle(
+            TilesheetCDDAId {
+                id: part.id.clone(),
+                prefix: Some("vp".to_string()),
                 postfix: None,
             },
-            layer: TileLayer::Monster,
-            coordinates: position.clone(),
-            rotation: Rotation::Deg0,
-            state: TileState::Normal,
-        }])
+            position.clone(),
+            Rotation::Deg0,
+            TileState::Normal,
+        )])
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.appliances)
+    }
+}
+
+impl Property for NpcsProperty {
+    fn get_commands(
+        &self,
+        position: &IVec2,
+        map_data: &MapData,
+        json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
+    ) -> Option<Vec<SetTile>> {
+        let mapgen_npc = self.npcs.get_random_with(rng);
+
+        // NPCs are drawn with the sprite of their class. Templates which do
+        // not exist or do not have a class are drawn with the generic npc
+        // sprite by the tilesheet
+        let id = match json_data.npcs.get(&mapgen_npc.class) {
+            None => {
+                warn!("Could not find npc {}", mapgen_npc.class);
+                mapgen_npc.class.clone()
+            },
+            Some(npc) => npc.class.clone().unwrap_or(npc.id.clone()),
+        };
+
+        Some(vec![SetTile::monster(
+            TilesheetCDDAId::simple(id),
+            position.clone(),
+            Rotation::Deg0,
+            TileState::Normal,
+        )])
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.npcs)
+    }
+}
+
+impl Property for GraffitiProperty {
+    fn get_commands(
+        &self,
+        position: &IVec2,
+        map_data: &MapData,
+        json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
+    ) -> Option<Vec<SetTile>> {
+        let graffiti = self.graffiti.get_random_with(rng);
+
+        Some(vec![SetTile::annotation(
+            TileAnnotation::Graffiti {
+                text: graffiti.text.clone(),
+                snippet: graffiti.snippet.clone(),
+            },
+            position.clone(),
+        )])
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.graffiti)
+    }
+}
+
+impl Property for RubbleProperty {
+    fn get_commands(
+        &self,
+        position: &IVec2,
+        map_data: &MapData,
+        json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
+    ) -> Option<Vec<SetTile>> {
+        let rubble = self.rubble.get_random_with(rng);
+        let mut commands = vec![];
+
+        if let Some(floor_type) = &rubble.floor_type {
+            commands.push(SetTile::terrain(
+                TilesheetCDDAId::simple(floor_type.clone()),
+                position.clone(),
+                Rotation::Deg0,
+                TileState::Normal,
+            ));
+        }
+
+        commands.push(SetTile::furniture(
+            TilesheetCDDAId::simple(rubble.rubble_type.clone()),
+            position.clone(),
+            Rotation::Deg0,
+            TileState::Normal,
+        ));
+        commands.push(SetTile::annotation(
+            TileAnnotation::Rubble {
+                rubble_type: rubble.rubble_type.clone(),
+                floor_type: rubble.floor_type.clone(),
+                items: rubble.items,
+            },
+            position.clone(),
+        ));
+
+        Some(commands)
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.rubble)
+    }
+}
+
+impl Property for LiquidsProperty {
+    fn get_commands(
+        &self,
+        position: &IVec2,
+        map_data: &MapData,
+        json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
+    ) -> Option<Vec<SetTile>> {
+        let liquid = self.liquids.get_random_with(rng);
+
+        // Liquids are items, so they do not have a sprite on the map
+        Some(vec![SetTile::annotation(
+            TileAnnotation::Liquid {
+                liquid: liquid.liquid.clone(),
+                amount: liquid.amount.as_ref().map(|a| (a.min(), a.max())),
+            },
+            position.clone(),
+        )])
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.liquids)
+    }
+}
+
+impl Property for SealedItemProperty {
+    fn get_commands(
+        &self,
+        position: &IVec2,
+        map_data: &MapData,
+        json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
+    ) -> Option<Vec<SetTile>> {
+        let sealed_item = self.sealed_item.get_random_with(rng);
+
+        Some(vec![
+            SetTile::furniture(
+                TilesheetCDDAId::simple(sealed_item.furniture.clone()),
+                position.clone(),
+                Rotation::Deg0,
+                TileState::Normal,
+            ),
+            SetTile::annotation(
+                TileAnnotation::SealedItem {
+                    furniture: sealed_item.furniture.clone(),
+                    item: sealed_item.item.as_ref().map(|i| i.item.clone()),
+                    item_group: sealed_item
+                        .items
+                        .as_ref()
+                        .map(|i| i.item.clone()),
+                },
+                position.clone(),
+            ),
+        ])
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        weighted_to_json(&self.sealed_item)
     }
 }
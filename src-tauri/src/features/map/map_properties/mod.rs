@@ -6,12 +6,33 @@ use crate::data::map_data::{
     PlaceInnerToilets, PlaceInnerTraps, PlaceInnerVehicles,
 };
 use crate::data::map_data::{MapGenCorpse, MapGenVehicle, PlaceInnerCorpses};
+use crate::data::map_data::{MapGenAppliance, PlaceInnerAppliances};
+use crate::data::map_data::{MapGenNpc, PlaceInnerNpcs};
+use crate::data::map_data::{
+    MapGenGraffiti, MapGenLiquid, MapGenRubble, PlaceInnerGraffiti,
+    PlaceInnerLiquids, PlaceInnerRubble,
+};
+use crate::data::map_data::{MapGenLoot, PlaceInnerLoot};
+use crate::data::map_data::{MapGenSealedItem, PlaceInnerSealedItem};
 use crate::features::map::MapGenNested;
 use cdda_lib::types::MapGenValue;
 use cdda_lib::types::Weighted;
+use serde::Serialize;
+use serde_json::{Map, Value};
 
 pub(crate) mod impl_property;
 
+/// The json of weighted mapping `values` as they are written in a mapgen. A
+/// single value without a weight is written on its own.
+pub fn weighted_to_json<T: Serialize>(
+    values: &[Weighted<T>],
+) -> Result<Value, serde_json::Error> {
+    match values {
+        [value] if value.weight == 1 => serde_json::to_value(&value.data),
+        values => serde_json::to_value(values),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TerrainProperty {
     pub mapgen_value: MapGenValue,
@@ -118,7 +139,7 @@ impl From<PlaceInnerItems> for ItemsProperty {
 
 #[derive(Debug, Clone)]
 pub struct ComputersProperty {
-    computer: Vec<Weighted<MapGenComputer>>,
+    pub computer: Vec<Weighted<MapGenComputer>>,
 }
 
 impl From<PlaceInnerComputers> for ComputersProperty {
@@ -130,11 +151,16 @@ impl From<PlaceInnerComputers> for ComputersProperty {
 }
 
 #[derive(Debug, Clone)]
-pub struct ToiletsProperty;
+pub struct ToiletsProperty {
+    /// Toilets are not read any further, their json is kept as it is
+    pub toilet: Value,
+}
 
 impl From<PlaceInnerToilets> for ToiletsProperty {
     fn from(value: PlaceInnerToilets) -> Self {
-        Self
+        Self {
+            toilet: Value::Object(Map::new()),
+        }
     }
 }
 
@@ -181,3 +207,94 @@ impl From<PlaceInnerCorpses> for CorpsesProperty {
         }
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct AppliancesProperty {
+    pub appliances: Vec<Weighted<MapGenAppliance>>,
+}
+
+impl From<PlaceInnerAppliances> for AppliancesProperty {
+    fn from(value: PlaceInnerAppliances) -> Self {
+        Self {
+            appliances: vec![Weighted::new(value.value, 1)],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NpcsProperty {
+    pub npcs: Vec<Weighted<MapGenNpc>>,
+}
+
+impl From<PlaceInnerNpcs> for NpcsProperty {
+    fn from(value: PlaceInnerNpcs) -> Self {
+        Self {
+            npcs: vec![Weighted::new(value.value, 1)],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GraffitiProperty {
+    pub graffiti: Vec<Weighted<MapGenGraffiti>>,
+}
+
+impl From<PlaceInnerGraffiti> for GraffitiProperty {
+    fn from(value: PlaceInnerGraffiti) -> Self {
+        Self {
+            graffiti: vec![Weighted::new(value.value, 1)],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RubbleProperty {
+    pub rubble: Vec<Weighted<MapGenRubble>>,
+}
+
+impl From<PlaceInnerRubble> for RubbleProperty {
+    fn from(value: PlaceInnerRubble) -> Self {
+        Self {
+            rubble: vec![Weighted::new(value.value, 1)],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LiquidsProperty {
+    pub liquids: Vec<Weighted<MapGenLiquid>>,
+}
+
+impl From<PlaceInnerLiquids> for LiquidsProperty {
+    fn from(value: PlaceInnerLiquids) -> Self {
+        Self {
+            liquids: vec![Weighted::new(value.value, 1)],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SealedItemProperty {
+    pub sealed_item: Vec<Weighted<MapGenSealedItem>>,
+}
+
+impl From<PlaceInnerSealedItem> for SealedItemProperty {
+    fn from(value: PlaceInnerSealedItem) -> Self {
+        Self {
+            sealed_item: vec![Weighted::new(value.value, 1)],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LootProperty {
+    pub loot: Vec<Weighted<MapGenLoot>>,
+}
+
+impl From<PlaceInnerLoot> for LootProperty {
+    fn from(value: PlaceInnerLoot) -> Self {
+        Self {
+            loot: vec![Weighted::new(value.value, 1)],
+        }
+    }
+}
@@ -1,21 +1,32 @@
+pub(crate) mod export;
+pub(crate) mod handlers;
 pub(crate) mod importing;
 pub(crate) mod map_properties;
 pub(crate) mod place;
+pub(crate) mod probability;
+pub(crate) mod set;
 
 use crate::data::io::DeserializedCDDAJsonData;
 use crate::data::map_data::{
     MapGenMonsterType, NeighborDirection, OmTerrainMatch, PlaceOuter,
 };
-use crate::data::palettes::{CDDAPalette, Parameter};
+use crate::data::npc::CDDANpcClass;
+use crate::data::palettes::{CDDAPalette, PaletteScope, Palettes, Parameter};
+use crate::data::region_settings::CDDARegionSettings;
 use crate::data::{
     replace_region_setting, GetIdentifier, GetIdentifierError, GetRandomError,
     TileLayer,
 };
+use crate::features::map::map_properties::{
+    FurnitureProperty, TerrainProperty,
+};
+use crate::features::map::set::Set;
 use crate::features::program_data::ZLevel;
 use crate::features::tileset::legacy_tileset::TilesheetCDDAId;
-use crate::util::Rotation;
+use crate::features::map::importing::resolve_om_terrain_id;
+use crate::util::{GetRandom, Rotation};
 use cdda_lib::types::{
-    CDDAIdentifier, DistributionInner, MapGenValue, NumberOrRange,
+    CDDAIdentifier, CDDAString, DistributionInner, MapGenValue, NumberOrRange,
     ParameterIdentifier, Weighted,
 };
 use cdda_lib::{
@@ -27,20 +38,54 @@ use futures_lite::StreamExt;
 use glam::{IVec2, IVec3, UVec2};
 use indexmap::IndexMap;
 use log::warn;
-use rand::{rng, Rng};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
 use serde::ser::{SerializeMap, SerializeStruct};
 use serde::{Deserialize, Serialize, Serializer};
 use serde_json::Value;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Debug;
 use std::sync::Arc;
 use strum::IntoEnumIterator;
-use strum_macros::{EnumIter, EnumString};
+use strum_macros::EnumIter;
 use thiserror::Error;
 
 pub const SPECIAL_EMPTY_CHAR: char = ' ';
 pub const DEFAULT_MAP_DATA_SIZE: UVec2 = UVec2::new(24, 24);
 
+/// Nested mapgens which are nested deeper than this are not placed, so
+/// nested mapgens which place themselves do not recurse forever
+pub const MAX_NESTING_DEPTH: u8 = 16;
+
+/// Predecessor mapgens can have predecessors themselves. Chains which are
+/// longer than this are cut off, so cyclic predecessors do not recurse forever
+pub const MAX_PREDECESSOR_DEPTH: usize = 8;
+
+/// The id of the map data which is generated for the predecessor overmap
+/// terrain `predecessor_id`. Hardcoded overmap terrains use the map data of
+/// their builtin mapgen, rotated ids use the map data of their base id
+pub fn predecessor_mapgen_id(
+    predecessor_id: &CDDAIdentifier,
+    json_data: &DeserializedCDDAJsonData,
+) -> Option<CDDAIdentifier> {
+    let (base_id, _) = resolve_om_terrain_id(predecessor_id.clone());
+
+    [predecessor_id, &base_id].into_iter().find_map(|id| {
+        let mapgen_id = json_data
+            .overmap_terrains
+            .get(id)
+            .and_then(|t| t.mapgen.as_ref())
+            .and_then(|m| m.first())
+            .map(|m| m.builtin.clone())
+            .unwrap_or(id.clone());
+
+        json_data
+            .map_data
+            .contains_key(&mapgen_id)
+            .then_some(mapgen_id)
+    })
+}
+
 pub trait Place:
     Debug + DynClone + Send + Sync + Downcast + DowncastSync + DowncastSend
 {
@@ -49,9 +94,13 @@ pub trait Place:
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
         None
     }
+
+    /// The json of the place without its coordinates, `repeat` and `chance`
+    fn to_json(&self) -> Result<Value, serde_json::Error>;
 }
 
 clone_trait_object!(Place);
@@ -66,9 +115,13 @@ pub trait Property:
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
         None
     }
+
+    /// The json of the mapping as it is written in the object of a mapgen
+    fn to_json(&self) -> Result<Value, serde_json::Error>;
 }
 
 clone_trait_object!(Property);
@@ -102,6 +155,13 @@ pub enum MappingKind {
     Nested,
     Vehicle,
     Corpse,
+    Appliance,
+    Npc,
+    Graffiti,
+    Rubble,
+    Liquid,
+    SealedItem,
+    Loot,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -133,6 +193,39 @@ pub enum TileState {
     Normal,
     Broken,
     Open,
+    /// The tile is removed from its layer
+    Removed,
+}
+
+/// Something placed on a tile which does not have a sprite of its own, but
+/// should still be shown when the cell is inspected
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TileAnnotation {
+    Graffiti {
+        text: Option<String>,
+        snippet: Option<CDDAIdentifier>,
+    },
+    Rubble {
+        rubble_type: CDDAIdentifier,
+        floor_type: Option<CDDAIdentifier>,
+        items: bool,
+    },
+    Liquid {
+        liquid: CDDAIdentifier,
+        /// The minimum and maximum amount of the liquid
+        amount: Option<(u32, u32)>,
+    },
+    Corpse {
+        /// The monster group the corpse is picked from
+        group: CDDAIdentifier,
+        age: Option<i32>,
+    },
+    SealedItem {
+        furniture: CDDAIdentifier,
+        item: Option<CDDAIdentifier>,
+        item_group: Option<CDDAIdentifier>,
+    },
 }
 
 #[derive(Debug, Serialize, Eq, PartialEq)]
@@ -142,6 +235,9 @@ pub struct SetTile {
     coordinates: IVec2,
     rotation: Rotation,
     state: TileState,
+    /// Set for commands which only annotate the tile. They do not change the
+    /// tile on the `layer`.
+    annotation: Option<TileAnnotation>,
 }
 
 impl SetTile {
@@ -157,6 +253,7 @@ impl SetTile {
             rotation: rotation.into(),
             coordinates,
             state,
+            annotation: None,
         }
     }
 
@@ -172,6 +269,23 @@ impl SetTile {
             rotation: rotation.into(),
             coordinates,
             state,
+            annotation: None,
+        }
+    }
+
+    pub fn trap(
+        id: impl Into<TilesheetCDDAId>,
+        coordinates: IVec2,
+        rotation: impl Into<Rotation>,
+        state: TileState,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            layer: TileLayer::Trap,
+            rotation: rotation.into(),
+            coordinates,
+            state,
+            annotation: None,
         }
     }
 
@@ -187,6 +301,23 @@ impl SetTile {
             rotation: rotation.into(),
             coordinates,
             state,
+            annotation: None,
+        }
+    }
+
+    pub fn vehicle(
+        id: impl Into<TilesheetCDDAId>,
+        coordinates: IVec2,
+        rotation: impl Into<Rotation>,
+        state: TileState,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            layer: TileLayer::Vehicle,
+            rotation: rotation.into(),
+            coordinates,
+            state,
+            annotation: None,
         }
     }
 
@@ -202,8 +333,51 @@ impl SetTile {
             rotation: rotation.into(),
             coordinates,
             state,
+            annotation: None,
+        }
+    }
+
+    pub fn remove(
+        id: impl Into<TilesheetCDDAId>,
+        layer: TileLayer,
+        coordinates: IVec2,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            layer,
+            rotation: Rotation::Deg0,
+            coordinates,
+            state: TileState::Removed,
+            annotation: None,
         }
     }
+
+    pub fn annotation(annotation: TileAnnotation, coordinates: IVec2) -> Self {
+        let id = match &annotation {
+            TileAnnotation::Graffiti { .. } => CDDAIdentifier::from("graffiti"),
+            TileAnnotation::Rubble { rubble_type, .. } => rubble_type.clone(),
+            TileAnnotation::Liquid { liquid, .. } => liquid.clone(),
+            TileAnnotation::Corpse { group, .. } => group.clone(),
+            TileAnnotation::SealedItem { furniture, .. } => furniture.clone(),
+        };
+
+        Self {
+            id: TilesheetCDDAId::simple(id),
+            layer: TileLayer::Field,
+            rotation: Rotation::Deg0,
+            coordinates,
+            state: TileState::Normal,
+            annotation: Some(annotation),
+        }
+    }
+
+    pub fn id(&self) -> &TilesheetCDDAId {
+        &self.id
+    }
+
+    pub fn layer(&self) -> &TileLayer {
+        &self.layer
+    }
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Deserialize, Serialize)]
@@ -216,21 +390,130 @@ pub enum MapDataFlag {
     Other,
 }
 
+/// The terrain or furniture a `property` maps to. Other mapping kinds can not
+/// be represented by a single value.
+fn property_mapgen_value(
+    mapping_kind: &MappingKind,
+    property: &Arc<dyn Property>,
+) -> Option<MapGenValue> {
+    match mapping_kind {
+        MappingKind::Terrain => Some(
+            property
+                .downcast_ref::<TerrainProperty>()?
+                .mapgen_value
+                .clone(),
+        ),
+        MappingKind::Furniture => Some(
+            property
+                .downcast_ref::<FurnitureProperty>()?
+                .mapgen_value
+                .clone(),
+        ),
+        _ => None,
+    }
+}
+
+/// Where a character of the rows is mapped
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum MappingSource {
+    /// The mapping is defined in the map itself
+    Map,
+    /// The mapping is defined in the palette with the `id`
+    Palette { id: CDDAIdentifier },
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct MapGenNested {
     pub neighbors: Option<HashMap<NeighborDirection, Vec<OmTerrainMatch>>>,
-    pub joins: Option<HashMap<NeighborDirection, Vec<OmTerrainMatch>>>,
+    pub joins: Option<HashMap<NeighborDirection, Vec<CDDAIdentifier>>>,
 
     pub chunks: Vec<Weighted<MapGenValue>>,
 
+    /// The chunks which are placed instead of the `chunks` if the conditions
+    /// are not fulfilled
+    #[serde(default)]
+    pub else_chunks: Vec<Weighted<MapGenValue>>,
+}
+
+impl MapGenNested {
+    /// The `chunks` if the neighbors and joins which are simulated by the
+    /// `config` fulfill the conditions, otherwise the `else_chunks`
+    pub fn chunks_for(
+        &self,
+        config: &MapDataConfig,
+    ) -> &Vec<Weighted<MapGenValue>> {
+        match self.conditions_match(config) {
+            true => &self.chunks,
+            false => &self.else_chunks,
+        }
+    }
+
+    /// Whether the neighbors and joins which are simulated by the `config`
+    /// fulfill the conditions of the chunks
+    pub fn conditions_match(&self, config: &MapDataConfig) -> bool {
+        simulated_match(
+            &self.neighbors,
+            &config.simulated_neighbors,
+            OmTerrainMatch::matches_identifier,
+        ) && simulated_match(
+            &self.joins,
+            &config.simulated_joins,
+            |join, simulated_join| join == simulated_join,
+        )
+    }
+}
+
+/// Like in the game, every direction of the `conditions` has to be fulfilled
+/// and a direction is fulfilled if any of its conditions matches any of the
+/// `simulated` ids in that direction
+fn simulated_match<T>(
+    conditions: &Option<HashMap<NeighborDirection, Vec<T>>>,
+    simulated: &HashMap<NeighborDirection, Vec<CDDAIdentifier>>,
+    matches: impl Fn(&T, &CDDAIdentifier) -> bool,
+) -> bool {
+    let Some(conditions) = conditions else {
+        return true;
+    };
+
+    conditions.iter().all(|(dir, conditions)| {
+        // Directions without a simulated neighbor never match
+        let simulated =
+            simulated.get(dir).map(Vec::as_slice).unwrap_or_default();
+
+        conditions
+            .iter()
+            .any(|condition| simulated.iter().any(|id| matches(condition, id)))
+    })
+}
+
+/// The overmap terrains and joins which are simulated next to the maps of a
+/// project, they decide whether nested chunks with `neighbors` or `joins`
+/// conditions are placed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimulatedNeighbors {
+    #[serde(default)]
+    pub neighbors: HashMap<NeighborDirection, Vec<CDDAIdentifier>>,
     #[serde(default)]
-    // This is basically just any "else_chunks"
-    pub invert_condition: bool,
+    pub joins: HashMap<NeighborDirection, Vec<CDDAIdentifier>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct MapDataConfig {
     pub simulated_neighbors: HashMap<NeighborDirection, Vec<CDDAIdentifier>>,
+
+    /// The joins of the overmap terrains next to the map, by the direction
+    #[serde(default)]
+    pub simulated_joins: HashMap<NeighborDirection, Vec<CDDAIdentifier>>,
+
+    /// Seed of every random choice made while generating the map, so the
+    /// preview only changes when the seed does
+    #[serde(default)]
+    pub seed: u64,
+
+    /// How many nested mapgens this map is nested in
+    #[serde(default)]
+    pub nesting_depth: u8,
 }
 
 impl Default for MapDataConfig {
@@ -249,6 +532,9 @@ impl Default for MapDataConfig {
 
         MapDataConfig {
             simulated_neighbors,
+            simulated_joins: HashMap::new(),
+            seed: 0,
+            nesting_depth: 0,
         }
     }
 }
@@ -277,13 +563,25 @@ pub struct MapData {
     pub palettes: Vec<MapGenValue>,
     pub flags: HashSet<MapDataFlag>,
 
+    /// Sparse maps only contain the cells which are actually mapped. Missing
+    /// cells are left untouched instead of being filled, which is how nested
+    /// mapgens and update mapgens behave
+    #[serde(default)]
+    pub sparse: bool,
+
     #[serde(skip)]
     pub properties: HashMap<MappingKind, HashMap<char, Arc<dyn Property>>>,
 
-    // #[serde(skip)]
-    // pub set: Vec<Arc<dyn Set>>,
+    #[serde(skip)]
+    pub set: Vec<Arc<dyn Set>>,
+
     #[serde(skip)]
     pub place: HashMap<MappingKind, Vec<PlaceOuter<Arc<dyn Place>>>>,
+
+    /// The palettes of the project the map belongs to, which it can use in
+    /// addition to the palettes of the loaded CDDA data
+    #[serde(skip)]
+    pub project_palettes: Arc<Palettes>,
 }
 
 impl Default for MapData {
@@ -313,7 +611,10 @@ impl Default for MapData {
             properties: Default::default(),
             palettes: Default::default(),
             place: Default::default(),
+            set: Default::default(),
             flags: Default::default(),
+            sparse: false,
+            project_palettes: Default::default(),
         }
     }
 }
@@ -323,6 +624,9 @@ pub enum CalculateParametersError {
     #[error("Missing Palette {0} in Loaded CDDA Palettes")]
     MissingPalette(String),
 
+    #[error("The palettes {0} include each other")]
+    CyclicPalette(String),
+
     #[error(transparent)]
     GetRandomError(#[from] GetRandomError),
 
@@ -332,41 +636,181 @@ pub enum CalculateParametersError {
 
 #[derive(Debug, Error)]
 pub enum GetMappedCDDAIdsError {
-    #[error("Missing default Region Settings in Loaded CDDA Data")]
+    #[error("Missing the selected and default Region Settings in Loaded CDDA Data")]
     MissingRegionSettings,
 
-    #[error("Missing Overmap Terrain in loaded CDDA Data for predecessor {0}")]
-    MissingOvermapTerrainForPredecessor(String),
+    #[error("Missing Update Mapgen Entry {0}")]
+    MissingUpdateMapgen(String),
+}
 
-    #[error("Missing Mapgen Entry for Predecessor {0}")]
-    MissingMapgenEntryForPredecessor(String),
+/// Writes the ids of the `commands` into `mapped_cdda_ids`, replacing any id
+/// that was on the same layer before
+fn apply_commands(
+    commands: Vec<SetTile>,
+    mapped_cdda_ids: &mut HashMap<IVec3, MappedCDDAIdsForTile>,
+    region_settings: &CDDARegionSettings,
+    json_data: &DeserializedCDDAJsonData,
+    z: ZLevel,
+) {
+    for command in commands {
+        let command_3d_coords =
+            IVec3::new(command.coordinates.x, command.coordinates.y, z);
+
+        if let Some(annotation) = command.annotation {
+            mapped_cdda_ids
+                .entry(command_3d_coords)
+                .or_default()
+                .annotations
+                .push(annotation);
+
+            continue;
+        }
+
+        if command.state == TileState::Removed {
+            if let Some(ident_mut) = mapped_cdda_ids.get_mut(&command_3d_coords)
+            {
+                match command.layer {
+                    TileLayer::Terrain => ident_mut.terrain = None,
+                    TileLayer::Furniture => ident_mut.furniture = None,
+                    TileLayer::Trap => ident_mut.trap = None,
+                    TileLayer::Vehicle => ident_mut.vehicle = None,
+                    TileLayer::Monster => {
+                        ident_mut.monster = None;
+                        ident_mut.npc_class = None;
+                    },
+                    TileLayer::Field => ident_mut.field = None,
+                }
+            }
+
+            continue;
+        }
+
+        let id = TilesheetCDDAId {
+            id: replace_region_setting(&command.id.id, region_settings),
+            prefix: command.id.prefix,
+            postfix: command.id.postfix,
+        };
+
+        let mut mapped_id = MappedCDDAId::simple(id);
+        mapped_id.rotation = command.rotation;
+
+        match command.state {
+            TileState::Normal => {},
+            TileState::Broken => mapped_id.is_broken = true,
+            TileState::Open => mapped_id.is_open = true,
+            TileState::Removed => {},
+        }
+
+        let ident_mut = match mapped_cdda_ids.get_mut(&command_3d_coords) {
+            None => {
+                mapped_cdda_ids.insert(
+                    command_3d_coords.clone(),
+                    MappedCDDAIdsForTile::default(),
+                );
+                mapped_cdda_ids
+                    .get_mut(&command_3d_coords)
+                    // Safe
+                    .unwrap()
+            },
+            Some(i) => i,
+        };
+
+        match command.layer {
+            TileLayer::Terrain => {
+                ident_mut.terrain = Some(mapped_id.clone());
+            },
+            TileLayer::Furniture => {
+                ident_mut.furniture = Some(mapped_id.clone());
+            },
+            TileLayer::Trap => {
+                ident_mut.trap = Some(mapped_id.clone());
+            },
+            TileLayer::Vehicle => {
+                ident_mut.vehicle = Some(mapped_id.clone());
+            },
+            TileLayer::Monster => {
+                ident_mut.npc_class = json_data
+                    .npc_classes
+                    .get(&mapped_id.tilesheet_id.id)
+                    .map(NpcClassInfo::from);
+                ident_mut.monster = Some(mapped_id.clone());
+            },
+            TileLayer::Field => {
+                ident_mut.field = Some(mapped_id.clone());
+            },
+        }
+    }
 }
 
 impl MapData {
+    /// Simulates the neighbors and joins of the `simulated` neighbors next to
+    /// the map
+    pub fn set_simulated_neighbors(&mut self, simulated: &SimulatedNeighbors) {
+        self.config.simulated_neighbors = simulated.neighbors.clone();
+        self.config.simulated_joins = simulated.joins.clone();
+    }
+
+    /// The random number generator the map is generated with, it only depends
+    /// on the seed of the map
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.config.seed)
+    }
+
+    /// The palettes the map can use, the `cdda` palettes together with the
+    /// palettes of its project
+    pub fn palette_scope<'a>(&'a self, cdda: &'a Palettes) -> PaletteScope<'a> {
+        PaletteScope {
+            cdda,
+            project: &self.project_palettes,
+        }
+    }
+
     pub fn calculate_parameters(
         &mut self,
         all_palettes: &HashMap<CDDAIdentifier, CDDAPalette>,
     ) -> Result<(), CalculateParametersError> {
-        let mut calculated_parameters = IndexMap::new();
+        let mut rng = self.rng();
+
+        self.calculate_parameters_with(all_palettes, &IndexMap::new(), &mut rng)
+    }
+
+    /// Calculates the parameters with the `rng`. The `inherited` parameters
+    /// are kept as they are, like the parameters a nested mapgen shares with
+    /// the map it is placed in.
+    pub fn calculate_parameters_with(
+        &mut self,
+        all_palettes: &HashMap<CDDAIdentifier, CDDAPalette>,
+        inherited: &IndexMap<ParameterIdentifier, CDDAIdentifier>,
+        rng: &mut dyn RngCore,
+    ) -> Result<(), CalculateParametersError> {
+        let mut calculated_parameters = inherited.clone();
 
         for (id, parameter) in self.parameters.iter() {
+            if inherited.contains_key(id) {
+                continue;
+            }
+
             let calculated_value = parameter
                 .default
                 .distribution
-                .get_identifier(&calculated_parameters)?;
+                .get_identifier_with(&calculated_parameters, rng)?;
 
             calculated_parameters.insert(id.clone(), calculated_value);
         }
 
+        let scope = self.palette_scope(all_palettes);
+
         for mapgen_value in self.palettes.iter() {
-            let id = mapgen_value.get_identifier(&calculated_parameters)?;
-            let palette = all_palettes.get(&id).ok_or(
+            let id = mapgen_value
+                .get_identifier_with(&calculated_parameters, rng)?;
+            let palette = scope.get(&id).ok_or(
                 CalculateParametersError::MissingPalette(id.to_string()),
             )?;
 
             palette
-                .calculate_parameters(all_palettes)?
+                .calculate_parameters(scope, rng)?
                 .into_iter()
+                .filter(|(palette_id, _)| !inherited.contains_key(palette_id))
                 .for_each(|(palette_id, ident)| {
                     calculated_parameters.insert(palette_id, ident);
                 });
@@ -377,20 +821,57 @@ impl MapData {
         Ok(())
     }
 
+    /// A copy of this nested mapgen which is placed in the `parent`. It sees
+    /// the same neighbors as the `parent` and shares its parameters, its own
+    /// parameters are calculated with the `rng`.
+    pub fn nested_in(
+        &self,
+        parent: &MapData,
+        all_palettes: &HashMap<CDDAIdentifier, CDDAPalette>,
+        rng: &mut dyn RngCore,
+    ) -> Result<MapData, CalculateParametersError> {
+        let mut nested = self.clone();
+        nested.config = parent.config.clone();
+        nested.config.nesting_depth += 1;
+
+        nested.calculate_parameters_with(
+            all_palettes,
+            &parent.calculated_parameters,
+            rng,
+        )?;
+
+        Ok(nested)
+    }
+
+    /// Maps the ids of this map with the region settings of the `region` the
+    /// project selected
     pub fn get_mapped_cdda_ids(
         &self,
         json_data: &DeserializedCDDAJsonData,
+        region: Option<&CDDAIdentifier>,
+        z: ZLevel,
+    ) -> Result<HashMap<IVec3, MappedCDDAIdsForTile>, GetMappedCDDAIdsError>
+    {
+        self.get_mapped_cdda_ids_with_depth(json_data, region, z, 0)
+    }
+
+    /// Maps the ids of this map on top of its predecessor, which is itself
+    /// mapped on top of its own predecessor up to the `MAX_PREDECESSOR_DEPTH`
+    fn get_mapped_cdda_ids_with_depth(
+        &self,
+        json_data: &DeserializedCDDAJsonData,
+        region: Option<&CDDAIdentifier>,
         z: ZLevel,
+        predecessor_depth: usize,
     ) -> Result<HashMap<IVec3, MappedCDDAIdsForTile>, GetMappedCDDAIdsError>
     {
         let mut local_mapped_cdda_ids = HashMap::new();
 
         let region_settings = json_data
-            .region_settings
-            .get(&CDDAIdentifier("default".into()))
+            .get_region_settings(region)
             .ok_or(GetMappedCDDAIdsError::MissingRegionSettings)?;
 
-        let fill_terrain_sprite = match &self.fill {
+        let mut fill_terrain_sprite = match &self.fill {
             None => None,
             Some(id) => {
                 Some(id.get_identifier(&self.calculated_parameters).unwrap())
@@ -398,35 +879,75 @@ impl MapData {
         };
 
         // we need to calculate the predecessor_mapgen here before so we can replace it later
-        match &self.predecessor {
-            None => {},
-            Some(predecessor_id) => {
-                let predecessor =
-                    json_data.overmap_terrains.get(predecessor_id)
-                        .ok_or(GetMappedCDDAIdsError::MissingOvermapTerrainForPredecessor(predecessor_id.0.clone()))?;
-
-                let predecessor_map_data = match &predecessor
-                    .mapgen
-                    .clone()
-                    .unwrap_or_default()
-                    .first()
-                {
-                    None => {
-                        // This terrain is defined in a json file, so we can just search for it
-                        json_data.map_data.get(predecessor_id).ok_or(GetMappedCDDAIdsError::MissingMapgenEntryForPredecessor(predecessor_id.0.clone()))?
-                    },
-                    Some(omtm) => json_data.map_data.get(&omtm.builtin).expect(
-                        format!(
-                            "Hardcoded Map data for predecessor {} to exist",
-                            omtm.builtin
-                        )
-                        .as_str(),
-                    ),
-                };
+        if let Some(predecessor_id) = &self.predecessor {
+            let predecessor_map_data = predecessor_mapgen_id(
+                predecessor_id,
+                json_data,
+            )
+            .and_then(|id| json_data.map_data.get(&id));
 
-                local_mapped_cdda_ids =
-                    predecessor_map_data.get_mapped_cdda_ids(json_data, z)?;
-            },
+            match predecessor_map_data {
+                Some(predecessor_map_data)
+                    if predecessor_depth < MAX_PREDECESSOR_DEPTH =>
+                {
+                    local_mapped_cdda_ids = predecessor_map_data
+                        .get_mapped_cdda_ids_with_depth(
+                            json_data,
+                            region,
+                            z,
+                            predecessor_depth + 1,
+                        )?;
+                },
+                // The chain is cut off here, the fill terrain of the last
+                // predecessor stands in for the rest of it
+                Some(predecessor_map_data) => {
+                    warn!(
+                        "Predecessor chain is longer than {} at {}",
+                        MAX_PREDECESSOR_DEPTH, predecessor_id
+                    );
+
+                    fill_terrain_sprite = fill_terrain_sprite.or_else(|| {
+                        predecessor_map_data.fill.as_ref().and_then(|id| {
+                            id.get_identifier(
+                                &predecessor_map_data.calculated_parameters,
+                            )
+                            .ok()
+                        })
+                    });
+                },
+                None => {
+                    warn!(
+                        "Missing map data for predecessor {}, using the \
+                         default groundcover of the region",
+                        predecessor_id
+                    );
+
+                    if fill_terrain_sprite.is_none()
+                        && !region_settings.default_groundcover.is_empty()
+                    {
+                        for (p, _) in self.cells.iter() {
+                            let position =
+                                self.transform_coordinates(&p.as_ivec2());
+
+                            let mut mapped_ids =
+                                MappedCDDAIdsForTile::default();
+                            mapped_ids.terrain = Some(MappedCDDAId::simple(
+                                TilesheetCDDAId::simple(
+                                    region_settings
+                                        .default_groundcover
+                                        .get_random()
+                                        .clone(),
+                                ),
+                            ));
+
+                            local_mapped_cdda_ids.insert(
+                                IVec3::new(position.x, position.y, z),
+                                mapped_ids,
+                            );
+                        }
+                    }
+                },
+            }
         }
 
         self.cells.iter().for_each(|(p, _)| {
@@ -441,12 +962,7 @@ impl MapData {
 
                     mapped_ids.terrain = fill_terrain_sprite.clone().map(|s| {
                         MappedCDDAId::simple(TilesheetCDDAId::simple(
-                            replace_region_setting(
-                                &s,
-                                region_settings,
-                                &json_data.terrain,
-                                &json_data.furniture,
-                            ),
+                            replace_region_setting(&s, region_settings),
                         ))
                     });
 
@@ -457,12 +973,7 @@ impl MapData {
                         mapped_ids.terrain =
                             fill_terrain_sprite.clone().map(|s| {
                                 MappedCDDAId::simple(TilesheetCDDAId::simple(
-                                    replace_region_setting(
-                                        &s,
-                                        region_settings,
-                                        &json_data.terrain,
-                                        &json_data.furniture,
-                                    ),
+                                    replace_region_setting(&s, region_settings),
                                 ))
                             })
                     }
@@ -471,63 +982,40 @@ impl MapData {
         });
 
         let all_commands = self.get_commands(&json_data);
+        apply_commands(
+            all_commands,
+            &mut local_mapped_cdda_ids,
+            region_settings,
+            json_data,
+            z,
+        );
 
-        for command in all_commands {
-            let command_3d_coords =
-                IVec3::new(command.coordinates.x, command.coordinates.y, z);
-
-            let id = TilesheetCDDAId {
-                id: replace_region_setting(
-                    &command.id.id,
-                    region_settings,
-                    &json_data.terrain,
-                    &json_data.furniture,
-                ),
-                prefix: command.id.prefix,
-                postfix: command.id.postfix,
-            };
-
-            let mut mapped_id = MappedCDDAId::simple(id);
-            mapped_id.rotation = command.rotation;
-
-            match command.state {
-                TileState::Normal => {},
-                TileState::Broken => mapped_id.is_broken = true,
-                TileState::Open => mapped_id.is_open = true,
-            }
+        Ok(local_mapped_cdda_ids)
+    }
 
-            let ident_mut =
-                match local_mapped_cdda_ids.get_mut(&command_3d_coords) {
-                    None => {
-                        local_mapped_cdda_ids.insert(
-                            command_3d_coords.clone(),
-                            MappedCDDAIdsForTile::default(),
-                        );
-                        local_mapped_cdda_ids
-                            .get_mut(&command_3d_coords)
-                            // Safe
-                            .unwrap()
-                    },
-                    Some(i) => i,
-                };
+    /// Applies this map data as an update mapgen on top of the already mapped
+    /// `mapped_cdda_ids`. Update mapgens do not have a fill terrain, so only the
+    /// cells which are explicitly changed will be replaced
+    pub fn apply_as_update(
+        &self,
+        mapped_cdda_ids: &mut HashMap<IVec3, MappedCDDAIdsForTile>,
+        json_data: &DeserializedCDDAJsonData,
+        region: Option<&CDDAIdentifier>,
+        z: ZLevel,
+    ) -> Result<(), GetMappedCDDAIdsError> {
+        let region_settings = json_data
+            .get_region_settings(region)
+            .ok_or(GetMappedCDDAIdsError::MissingRegionSettings)?;
 
-            match command.layer {
-                TileLayer::Terrain => {
-                    ident_mut.terrain = Some(mapped_id.clone());
-                },
-                TileLayer::Furniture => {
-                    ident_mut.furniture = Some(mapped_id.clone());
-                },
-                TileLayer::Monster => {
-                    ident_mut.monster = Some(mapped_id.clone());
-                },
-                TileLayer::Field => {
-                    ident_mut.field = Some(mapped_id.clone());
-                },
-            }
-        }
+        apply_commands(
+            self.get_commands(json_data),
+            mapped_cdda_ids,
+            region_settings,
+            json_data,
+            z,
+        );
 
-        Ok(local_mapped_cdda_ids)
+        Ok(())
     }
 
     /// Transform 2d coordinates based on the rotation of the map
@@ -550,9 +1038,50 @@ impl MapData {
         }
     }
 
+    /// Rotates the `offset` from a position like the rotation of the map
+    /// rotates the positions in it, so chunks which are nested in the map
+    /// rotate with it
+    fn rotate_offset(&self, offset: &IVec2) -> IVec2 {
+        match self.rotation {
+            MapDataRotation::Deg0 => offset.clone(),
+            MapDataRotation::Deg90 => IVec2::new(-offset.y, offset.x),
+            MapDataRotation::Deg180 => IVec2::new(-offset.x, -offset.y),
+            MapDataRotation::Deg270 => IVec2::new(offset.y, -offset.x),
+        }
+    }
+
+    /// The commands of the `nested` mapgen, moved to the `anchor` in this
+    /// map
+    pub fn get_nested_commands(
+        &self,
+        nested: &MapData,
+        anchor: &IVec2,
+        json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
+    ) -> Vec<SetTile> {
+        let mut commands = nested.get_commands_with(json_data, rng);
+
+        commands.iter_mut().for_each(|c| {
+            c.coordinates = *anchor + self.rotate_offset(&c.coordinates);
+        });
+
+        commands
+    }
+
     pub fn get_commands(
         &self,
         json_data: &DeserializedCDDAJsonData,
+    ) -> Vec<SetTile> {
+        self.get_commands_with(json_data, &mut self.rng())
+    }
+
+    /// Like [`MapData::get_commands`], but every random choice is made with
+    /// the `rng`. Nested mapgens use the generator of the map they are
+    /// placed in.
+    pub fn get_commands_with(
+        &self,
+        json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Vec<SetTile> {
         // We need to store all commands in this list here so we can sort it and act them out in
         // the order the VisibleMappingCommandKind enum has
@@ -569,6 +1098,7 @@ impl MapData {
                 &cell.character,
                 &transformed_position,
                 &json_data,
+                rng,
             );
 
             all_commands.extend(ident_commands)
@@ -576,15 +1106,15 @@ impl MapData {
 
         for (_, place_vec) in self.place.iter() {
             for place in place_vec {
-                let upper_bound = place.repeat.rand_number();
+                let upper_bound = place.repeat.sample(rng);
 
                 for _ in 0..upper_bound {
-                    let position = place.coordinates();
+                    let position = place.coordinates(rng);
                     let transformed_position =
                         self.transform_coordinates(&position);
 
                     // We only want to place one in place.chance times
-                    let rand_chance_num = rng().random_range(0..=100);
+                    let rand_chance_num = rng.random_range(0..=100);
                     if rand_chance_num > place.chance {
                         continue;
                     }
@@ -593,6 +1123,7 @@ impl MapData {
                         &transformed_position,
                         self,
                         json_data,
+                        rng,
                     ) {
                         None => {},
                         Some(commands) => {
@@ -603,6 +1134,45 @@ impl MapData {
             }
         }
 
+        // Sets are applied after everything else, since bashing and removing
+        // depend on the tiles which were placed before
+        for set in self.set.iter() {
+            // Sets on other z-levels change the maps above or below this one
+            if set.z() != 0 {
+                continue;
+            }
+
+            for _ in 0..set.repeat().sample(rng) {
+                if !rng.random_ratio(1, set.chance().max(1)) {
+                    continue;
+                }
+
+                for position in set.coordinates(rng) {
+                    // Sets of multi tile mapgens can reach into other maps
+                    if position.x < 0
+                        || position.y < 0
+                        || position.x >= self.map_size.x as i32
+                        || position.y >= self.map_size.y as i32
+                    {
+                        continue;
+                    }
+
+                    let transformed_position =
+                        self.transform_coordinates(&position);
+                    let commands = set.operation().get_commands(
+                        &transformed_position,
+                        &all_commands,
+                        self,
+                        json_data,
+                    );
+
+                    all_commands.extend(commands);
+                }
+            }
+        }
+
+        // The sort is stable, so the commands of each layer stay in the order
+        // they were applied in
         all_commands.sort_by(|a, b| a.layer.cmp(&b.layer));
         all_commands
     }
@@ -613,20 +1183,22 @@ impl MapData {
         character: &char,
         position: &IVec2,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
         let mapping = self.properties.get(mapping_kind)?;
 
         if let Some(id) = mapping.get(character) {
-            return id.get_commands(position, self, json_data);
+            return id.get_commands(position, self, json_data, rng);
         }
 
         // If we don't find it, search the palettes from top to bottom
         for mapgen_value in self.palettes.iter() {
             let palette_id = mapgen_value
-                .get_identifier(&self.calculated_parameters)
+                .get_identifier_with(&self.calculated_parameters, rng)
                 .ok()?;
 
-            let palette = json_data.palettes.get(&palette_id)?;
+            let palette =
+                self.palette_scope(&json_data.palettes).get(&palette_id)?;
 
             if let Some(id) = palette.get_visible_mapping(
                 mapping_kind,
@@ -634,6 +1206,7 @@ impl MapData {
                 position,
                 self,
                 json_data,
+                rng,
             ) {
                 return Some(id);
             }
@@ -642,17 +1215,154 @@ impl MapData {
         None
     }
 
+    /// Finds where the `character` is mapped for the `mapping_kind` without
+    /// generating any tiles
+    pub fn get_mapping_source(
+        &self,
+        mapping_kind: &MappingKind,
+        character: &char,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Option<MappingSource> {
+        let is_mapped = self
+            .properties
+            .get(mapping_kind)
+            .is_some_and(|m| m.contains_key(character));
+
+        if is_mapped {
+            return Some(MappingSource::Map);
+        }
+
+        let scope = self.palette_scope(&json_data.palettes);
+
+        self.palettes.iter().find_map(|mapgen_value| {
+            let palette_id = mapgen_value
+                .get_identifier(&self.calculated_parameters)
+                .ok()?;
+
+            scope
+                .get(&palette_id)?
+                .find_mapping_palette(
+                    mapping_kind,
+                    character,
+                    &self.calculated_parameters,
+                    scope,
+                )
+                .map(|id| MappingSource::Palette { id })
+        })
+    }
+
+    /// The property the `character` is mapped to, either by the map itself or
+    /// by the first palette which maps it
+    pub fn get_property(
+        &self,
+        mapping_kind: &MappingKind,
+        character: &char,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Option<Arc<dyn Property>> {
+        let properties = match self.get_mapping_source(
+            mapping_kind,
+            character,
+            json_data,
+        )? {
+            MappingSource::Map => self.properties.get(mapping_kind)?,
+            MappingSource::Palette { id } => self
+                .palette_scope(&json_data.palettes)
+                .get(&id)?
+                .properties
+                .get(mapping_kind)?,
+        };
+
+        properties.get(character).cloned()
+    }
+
+    /// The terrain or furniture the `character` is mapped to, either by the map
+    /// itself or by the first palette which maps it
+    pub fn get_mapgen_value(
+        &self,
+        mapping_kind: &MappingKind,
+        character: &char,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Option<MapGenValue> {
+        let property = self.get_property(mapping_kind, character, json_data)?;
+
+        property_mapgen_value(mapping_kind, &property)
+    }
+
+    /// The rows of the map as they are written in the mapgen json. Cells
+    /// which are missing in sparse maps are padded with spaces
+    pub fn get_rows(&self) -> Vec<String> {
+        (0..self.map_size.y)
+            .map(|y| {
+                (0..self.map_size.x)
+                    .map(|x| {
+                        self.cells
+                            .get(&UVec2::new(x, y))
+                            .map(|c| c.character)
+                            .unwrap_or(' ')
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The terrain or furniture each character is mapped to by the map itself,
+    /// without the mappings of its palettes. Other mapping kinds can not be
+    /// represented by a single value and are always empty.
+    pub fn get_local_mapgen_values(
+        &self,
+        mapping_kind: &MappingKind,
+    ) -> BTreeMap<char, MapGenValue> {
+        let Some(properties) = self.properties.get(mapping_kind) else {
+            return BTreeMap::new();
+        };
+
+        properties
+            .iter()
+            .filter_map(|(character, property)| {
+                Some((
+                    *character,
+                    property_mapgen_value(mapping_kind, property)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Maps the `character` to the terrain or furniture in the map itself.
+    /// Returns false for mapping kinds other than terrain and furniture.
+    pub fn set_local_mapgen_value(
+        &mut self,
+        mapping_kind: MappingKind,
+        character: char,
+        mapgen_value: MapGenValue,
+    ) -> bool {
+        let property: Arc<dyn Property> = match mapping_kind {
+            MappingKind::Terrain => Arc::new(TerrainProperty { mapgen_value }),
+            MappingKind::Furniture => {
+                Arc::new(FurnitureProperty { mapgen_value })
+            },
+            _ => return false,
+        };
+
+        self.properties
+            .entry(mapping_kind)
+            .or_default()
+            .insert(character, property);
+
+        true
+    }
+
     pub fn get_identifier_change_commands(
         &self,
         character: &char,
         position: &IVec2,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Vec<SetTile> {
         let mut commands = Vec::new();
 
         for kind in MappingKind::iter() {
             let kind_commands = self
-                .get_visible_mapping(&kind, character, position, json_data)
+                .get_visible_mapping(&kind, character, position, json_data, rng)
                 .unwrap_or_default();
 
             commands.extend(kind_commands)
@@ -683,93 +1393,39 @@ impl Serialize for MapData {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, EnumString)]
-#[strum(serialize_all = "snake_case")]
-pub enum PlaceableSetType {
-    Terrain,
-    Furniture,
-    Trap,
-}
-
-#[derive(Debug, Clone, Deserialize, Serialize, EnumString)]
-#[strum(serialize_all = "snake_case")]
-pub enum RemovableSetType {
-    ItemRemove,
-    FieldRemove,
-    TrapRemove,
-    CreatureRemove,
-}
-
-#[derive(Debug, Clone)]
-pub enum SetOperation {
-    Place {
-        id: CDDAIdentifier,
-        ty: PlaceableSetType,
-    },
-    Remove {
-        ty: RemovableSetType,
-    },
-    Radiation {
-        amount: NumberOrRange<u32>,
-    },
-    Variable {
-        id: CDDAIdentifier,
-    },
-    Bash {},
-    Burn {},
-}
-
-#[derive(Debug, Clone)]
-pub struct SetPoint {
-    pub x: NumberOrRange<u32>,
-    pub y: NumberOrRange<u32>,
-    pub z: i32,
-    pub chance: u32,
-    pub repeat: (u32, u32),
-    pub operation: SetOperation,
-}
-
-#[derive(Debug, Clone)]
-pub struct SetLine {
-    pub from_x: NumberOrRange<u32>,
-    pub from_y: NumberOrRange<u32>,
-
-    pub to_x: NumberOrRange<u32>,
-    pub to_y: NumberOrRange<u32>,
-
-    pub z: i32,
-    pub chance: u32,
-    pub repeat: (u32, u32),
-    pub operation: SetOperation,
-}
-
-#[derive(Debug, Clone)]
-pub struct SetSquare {
-    pub top_left_x: NumberOrRange<u32>,
-    pub top_left_y: NumberOrRange<u32>,
-
-    pub bottom_right_x: NumberOrRange<u32>,
-    pub bottom_right_y: NumberOrRange<u32>,
-
-    pub z: i32,
-    pub chance: u32,
-    pub repeat: (u32, u32),
-    pub operation: SetOperation,
-}
-
 #[cfg(test)]
 mod tests {
-    use crate::features::map::importing::SingleMapDataImporter;
+    use crate::features::map::export::{
+        check_compatibility, find_mapgen, map_data_to_mapgen_json,
+        read_json_file, ExportMapgenError, ExportTarget,
+    };
+    use crate::features::map::importing::{
+        resolve_om_terrain_id, SingleMapDataImporter,
+    };
     use crate::features::map::map_properties::TerrainProperty;
-    use crate::features::map::MappingKind;
+    use crate::features::map::probability::{
+        cell_breakdown, collect_parameters, probability_breakdown,
+        IdProbability,
+    };
+    use crate::features::map::set::{
+        PlaceableSetType, RemovableSetType, SetOperation,
+    };
+    use crate::data::io::DeserializedCDDAJsonData;
+    use crate::data::map_data::{IntoArcDyn, PlaceInnerItems, PlaceOuter};
+    use crate::data::region_settings::{
+        CDDARegionSettings, RegionTerrainAndFurniture,
+    };
+    use crate::features::map::{Cell, MapData, MapDataRotation, MappingKind};
     use crate::util::Load;
     use crate::TEST_CDDA_DATA;
     use cdda_lib::types::{
         CDDADistributionInner, CDDAIdentifier, Distribution, DistributionInner,
-        MapGenValue, MeabyVec, MeabyWeighted, ParameterIdentifier, Switch,
-        Weighted,
+        MapGenValue, MeabyVec, MeabyWeighted, NumberOrRange,
+        ParameterIdentifier, Switch, Weighted,
     };
-    use glam::UVec2;
+    use glam::{IVec2, IVec3, UVec2};
+    use indexmap::IndexMap;
+    use serde_json::json;
     use std::collections::HashMap;
     use std::path::PathBuf;
     use tokio;
@@ -804,6 +1460,327 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_export_mapgen() {
+        let path = PathBuf::from(TEST_DATA_PATH).join("test_fill_ter.json");
+        let mut map_loader = SingleMapDataImporter {
+            paths: vec![path.clone()],
+            om_terrain: "test_fill_ter".into(),
+        };
+
+        let mut map_data = map_loader
+            .load()
+            .await
+            .unwrap()
+            .maps
+            .remove(&UVec2::ZERO)
+            .unwrap();
+
+        let original = find_mapgen(
+            &read_json_file(&path).unwrap(),
+            &"test_fill_ter".into(),
+        )
+        .cloned()
+        .unwrap();
+
+        // Exporting an unchanged map must not change the mapgen
+        let exported = map_data_to_mapgen_json(
+            &map_data,
+            &"test_fill_ter".into(),
+            Some(original.clone()),
+        )
+        .unwrap();
+        assert_eq!(exported, original);
+
+        map_data.fill = None;
+        map_data
+            .cells
+            .insert(UVec2::new(0, 0), Cell { character: '.' });
+
+        let exported = map_data_to_mapgen_json(
+            &map_data,
+            &"test_fill_ter".into(),
+            Some(original),
+        )
+        .unwrap();
+        let object = exported.get("object").unwrap();
+
+        assert_eq!(object.get("//").unwrap(), "Test the fill_ter");
+        assert!(object.get("fill_ter").is_none());
+        assert!(object["rows"][0].as_str().unwrap().starts_with('.'));
+    }
+
+    #[tokio::test]
+    async fn test_export_places() {
+        let path = PathBuf::from(TEST_DATA_PATH).join("test_fill_ter.json");
+        let mut map_loader = SingleMapDataImporter {
+            paths: vec![path.clone()],
+            om_terrain: "test_fill_ter".into(),
+        };
+
+        let mut map_data = map_loader
+            .load()
+            .await
+            .unwrap()
+            .maps
+            .remove(&UVec2::ZERO)
+            .unwrap();
+
+        let original = find_mapgen(
+            &read_json_file(&path).unwrap(),
+            &"test_fill_ter".into(),
+        )
+        .cloned()
+        .unwrap();
+
+        let items: PlaceOuter<PlaceInnerItems> = serde_json::from_value(
+            json!({ "item": "trash", "x": 3, "y": [1, 4], "chance": 50 }),
+        )
+        .unwrap();
+
+        map_data.place.insert(
+            MappingKind::ItemGroups,
+            vec![PlaceOuter::into_arc_dyn_place(
+                items,
+                NumberOrRange::Number(3),
+                NumberOrRange::Range((1, 4)),
+            )],
+        );
+
+        let exported = map_data_to_mapgen_json(
+            &map_data,
+            &"test_fill_ter".into(),
+            Some(original.clone()),
+        )
+        .unwrap();
+        let place_items = &exported["object"]["place_items"];
+
+        assert_eq!(place_items[0]["item"], "trash");
+        assert_eq!(place_items[0]["x"], 3);
+        assert_eq!(place_items[0]["y"], json!([1, 4]));
+        assert_eq!(place_items[0]["chance"], 50);
+
+        // Exporting the exported mapgen again must not change it
+        let reexported = map_data_to_mapgen_json(
+            &map_data,
+            &"test_fill_ter".into(),
+            Some(exported.clone()),
+        )
+        .unwrap();
+        assert_eq!(reexported, exported);
+
+        map_data.place.clear();
+
+        let exported = map_data_to_mapgen_json(
+            &map_data,
+            &"test_fill_ter".into(),
+            Some(exported),
+        )
+        .unwrap();
+        assert_eq!(exported, original);
+    }
+
+    #[test]
+    fn test_check_compatibility() {
+        let mapgen = json!({
+            "type": "mapgen",
+            "om_terrain": "test",
+            "object": {
+                "rows": ["."],
+                "place_remove_all": [{ "x": 0, "y": 0 }]
+            }
+        });
+
+        assert!(
+            check_compatibility(&mapgen, ExportTarget::Experimental).is_ok()
+        );
+        assert!(matches!(
+            check_compatibility(&mapgen, ExportTarget::Stable),
+            Err(ExportMapgenError::Incompatible { issues, .. })
+                if issues.len() == 1
+        ));
+    }
+
+    #[test]
+    fn test_resolve_om_terrain_id() {
+        let (id, rotation) = resolve_om_terrain_id("house_east".into());
+        assert_eq!(id, "house".into());
+        assert!(matches!(rotation, MapDataRotation::Deg90));
+
+        let (id, rotation) = resolve_om_terrain_id("road_end_south".into());
+        assert_eq!(id, "road_end".into());
+        assert!(matches!(rotation, MapDataRotation::Deg180));
+
+        let (id, rotation) = resolve_om_terrain_id("road_ew".into());
+        assert_eq!(id, "road_straight".into());
+        assert!(matches!(rotation, MapDataRotation::Deg90));
+
+        let (id, rotation) = resolve_om_terrain_id("field".into());
+        assert_eq!(id, "field".into());
+        assert!(matches!(rotation, MapDataRotation::Deg0));
+    }
+
+    #[tokio::test]
+    async fn test_sparse_nested() {
+        let mut map_loader = SingleMapDataImporter {
+            paths: vec![
+                PathBuf::from(TEST_DATA_PATH).join("test_sparse_nested.json"),
+            ],
+            om_terrain: "test_sparse_nested".into(),
+        };
+
+        let map_data = map_loader
+            .load()
+            .await
+            .unwrap()
+            .maps
+            .remove(&UVec2::ZERO)
+            .unwrap();
+
+        assert!(map_data.sparse);
+        assert_eq!(map_data.cells.len(), 2);
+        assert!(map_data.cells.contains_key(&UVec2::new(1, 1)));
+        assert!(map_data.cells.contains_key(&UVec2::new(2, 2)));
+        assert_eq!(map_data.get_rows(), vec!["    ", " .  ", "  . ", "    "]);
+    }
+
+    #[tokio::test]
+    async fn test_multi_om_terrain() {
+        let path =
+            PathBuf::from(TEST_DATA_PATH).join("test_multi_om_terrain.json");
+
+        let mut map_loader = SingleMapDataImporter {
+            paths: vec![path.clone()],
+            om_terrain: "test_multi_b".into(),
+        };
+
+        let collection = map_loader.load().await.unwrap();
+        assert_eq!(collection.maps.len(), 4);
+
+        let character_at = |coordinates: UVec2, cell: UVec2| {
+            collection.maps[&coordinates].cells[&cell].character
+        };
+
+        assert_eq!(character_at(UVec2::new(0, 0), UVec2::new(23, 23)), 'a');
+        assert_eq!(character_at(UVec2::new(1, 0), UVec2::ZERO), 'b');
+        assert_eq!(character_at(UVec2::new(0, 1), UVec2::new(0, 23)), 'c');
+        assert_eq!(character_at(UVec2::new(1, 1), UVec2::ZERO), 'd');
+
+        // The missing part of the cut short row is left blank
+        assert_eq!(character_at(UVec2::new(1, 1), UVec2::new(0, 23)), ' ');
+        assert_eq!(collection.maps[&UVec2::new(1, 1)].cells.len(), 24 * 24);
+
+        // Rotating the whole mapgen moves the top left map to the top right
+        let mut map_loader = SingleMapDataImporter {
+            paths: vec![path],
+            om_terrain: "test_multi_a_east".into(),
+        };
+
+        let collection = map_loader.load().await.unwrap();
+        let map_data = &collection.maps[&UVec2::new(1, 0)];
+
+        assert_eq!(map_data.cells[&UVec2::ZERO].character, 'a');
+        assert!(matches!(map_data.rotation, MapDataRotation::Deg90));
+    }
+
+    #[test]
+    fn test_predecessor_fallbacks() {
+        let mut json_data = DeserializedCDDAJsonData::default();
+        json_data.region_settings.insert(
+            "default".into(),
+            CDDARegionSettings {
+                id: "default".into(),
+                default_oter: vec![],
+                default_groundcover: vec![Weighted::new("t_dirt", 1)],
+                region_terrain_and_furniture: RegionTerrainAndFurniture {
+                    terrain: IndexMap::new(),
+                    furniture: IndexMap::new(),
+                },
+                region_item_groups: IndexMap::new(),
+                region_vehicles: IndexMap::new(),
+                river_scale: None,
+            },
+        );
+
+        // A cyclic predecessor stops at the maximum depth
+        json_data.map_data.insert(
+            "test_cyclic".into(),
+            MapData {
+                predecessor: Some("test_cyclic".into()),
+                ..MapData::default()
+            },
+        );
+
+        let map_data = MapData {
+            fill: None,
+            predecessor: Some("test_cyclic".into()),
+            ..MapData::default()
+        };
+        let ids = map_data.get_mapped_cdda_ids(&json_data, None, 0).unwrap();
+        assert_eq!(
+            ids[&IVec3::ZERO].terrain.as_ref().unwrap().tilesheet_id.id,
+            "t_grass".into()
+        );
+
+        // A missing predecessor is replaced with the default groundcover
+        let map_data = MapData {
+            fill: None,
+            predecessor: Some("test_missing".into()),
+            ..MapData::default()
+        };
+        let ids = map_data.get_mapped_cdda_ids(&json_data, None, 0).unwrap();
+        assert_eq!(ids.len(), 24 * 24);
+        assert_eq!(
+            ids[&IVec3::ZERO].terrain.as_ref().unwrap().tilesheet_id.id,
+            "t_dirt".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set() {
+        let mut map_loader = SingleMapDataImporter {
+            paths: vec![PathBuf::from(TEST_DATA_PATH).join("test_set.json")],
+            om_terrain: "test_set".into(),
+        };
+
+        let map_data = map_loader
+            .load()
+            .await
+            .unwrap()
+            .maps
+            .remove(&UVec2::ZERO)
+            .unwrap();
+
+        // The terrain point without an id is skipped
+        assert_eq!(map_data.set.len(), 5);
+
+        let mut rng = rand::rng();
+        let point = &map_data.set[0];
+        assert_eq!(point.coordinates(&mut rng), vec![IVec2::new(1, 2)]);
+        assert!(matches!(
+            point.operation(),
+            SetOperation::Place {
+                ty: PlaceableSetType::Terrain,
+                ..
+            }
+        ));
+
+        let line = &map_data.set[1];
+        assert_eq!(line.coordinates(&mut rng).len(), 4);
+
+        let square = &map_data.set[2];
+        assert_eq!(square.coordinates(&mut rng).len(), 6);
+        assert_eq!(square.repeat().get_from_to(), (1, 2));
+        assert!(matches!(square.operation(), SetOperation::Bash {}));
+
+        assert!(matches!(
+            map_data.set[4].operation(),
+            SetOperation::Remove {
+                ty: RemovableSetType::FieldRemove
+            }
+        ));
+    }
+
     #[tokio::test]
     async fn test_parameters() {
         let cdda_data = TEST_CDDA_DATA.get().await;
@@ -850,6 +1827,34 @@ mod tests {
         )
     }
 
+    #[tokio::test]
+    async fn test_same_seed_generates_the_same_map() {
+        let cdda_data = TEST_CDDA_DATA.get().await;
+
+        let mut map_loader = SingleMapDataImporter {
+            paths: vec![PathBuf::from(TEST_DATA_PATH).join("test_terrain.json")],
+            om_terrain: "test_terrain".into(),
+        };
+
+        let mut map_data = map_loader
+            .load()
+            .await
+            .unwrap()
+            .maps
+            .remove(&UVec2::ZERO)
+            .unwrap();
+        map_data.config.seed = 42;
+
+        map_data.calculate_parameters(&cdda_data.palettes).unwrap();
+        let calculated_parameters = map_data.calculated_parameters.clone();
+        let commands = map_data.get_commands(cdda_data);
+
+        map_data.calculate_parameters(&cdda_data.palettes).unwrap();
+
+        assert_eq!(map_data.calculated_parameters, calculated_parameters);
+        assert_eq!(map_data.get_commands(cdda_data), commands);
+    }
+
     #[tokio::test]
     async fn test_terrain() {
         const SINGLE_CHAR: char = '.';
@@ -1052,6 +2057,88 @@ mod tests {
             assert_eq!(terrain_property.mapgen_value, to_eq);
         }
     }
+
+    #[tokio::test]
+    async fn test_probability_breakdown() {
+        let cdda_data = TEST_CDDA_DATA.get().await;
+
+        let mut map_loader = SingleMapDataImporter {
+            paths: vec![PathBuf::from(TEST_DATA_PATH).join("test_terrain.json")],
+            om_terrain: "test_terrain".into(),
+        };
+
+        let mut map_data = map_loader
+            .load()
+            .await
+            .unwrap()
+            .maps
+            .remove(&UVec2::ZERO)
+            .unwrap();
+
+        map_data.calculate_parameters(&cdda_data.palettes).unwrap();
+
+        let parameters = collect_parameters(&map_data, cdda_data);
+        let value = map_data
+            .get_mapgen_value(&MappingKind::Terrain, &'5', cdda_data)
+            .unwrap();
+
+        let breakdown = probability_breakdown(&value, &parameters);
+
+        assert_eq!(breakdown.len(), 2);
+        assert_eq!(
+            breakdown[0].id,
+            CDDAIdentifier::from("t_concrete_railing")
+        );
+        assert!((breakdown[0].probability - 10. / 11.).abs() < 1e-9);
+        assert_eq!(breakdown[1].id, CDDAIdentifier::from("t_concrete_wall"));
+        assert!((breakdown[1].probability - 1. / 11.).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_cell_breakdown_uses_the_fill_ter() {
+        let cdda_data = TEST_CDDA_DATA.get().await;
+
+        let mut map_loader = SingleMapDataImporter {
+            paths: vec![
+                PathBuf::from(TEST_DATA_PATH).join("test_fill_ter.json")
+            ],
+            om_terrain: "test_fill_ter".into(),
+        };
+
+        let map_data = map_loader
+            .load()
+            .await
+            .unwrap()
+            .maps
+            .remove(&UVec2::ZERO)
+            .unwrap();
+
+        let parameters = collect_parameters(&map_data, cdda_data);
+
+        let terrain = cell_breakdown(
+            &map_data,
+            &MappingKind::Terrain,
+            &' ',
+            cdda_data,
+            &parameters,
+        );
+        let furniture = cell_breakdown(
+            &map_data,
+            &MappingKind::Furniture,
+            &' ',
+            cdda_data,
+            &parameters,
+        );
+
+        assert_eq!(
+            terrain,
+            vec![IdProbability {
+                id: "t_grass".into(),
+                probability: 1.
+            }]
+        );
+        assert!(furniture.is_empty());
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
@@ -1115,12 +2202,39 @@ impl MappedCDDAId {
     }
 }
 
+/// The class of an npc which is placed on a tile
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NpcClassInfo {
+    pub id: CDDAIdentifier,
+    pub name: Option<CDDAString>,
+    pub job_description: Option<CDDAString>,
+}
+
+impl From<&CDDANpcClass> for NpcClassInfo {
+    fn from(value: &CDDANpcClass) -> Self {
+        Self {
+            id: value.id.clone(),
+            name: value.name.clone(),
+            job_description: value.job_description.clone(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct MappedCDDAIdsForTile {
     pub terrain: Option<MappedCDDAId>,
     pub furniture: Option<MappedCDDAId>,
+    #[serde(default)]
+    pub trap: Option<MappedCDDAId>,
+    #[serde(default)]
+    pub vehicle: Option<MappedCDDAId>,
     pub monster: Option<MappedCDDAId>,
     pub field: Option<MappedCDDAId>,
+    /// Set if the monster of the tile is an npc
+    #[serde(default)]
+    pub npc_class: Option<NpcClassInfo>,
+    #[serde(default)]
+    pub annotations: Vec<TileAnnotation>,
 }
 
 impl MappedCDDAIdsForTile {
@@ -1133,12 +2247,23 @@ impl MappedCDDAIdsForTile {
             self.furniture = other.furniture;
         }
 
+        if other.trap.is_some() {
+            self.trap = other.trap;
+        }
+
+        if other.vehicle.is_some() {
+            self.vehicle = other.vehicle;
+        }
+
         if other.monster.is_some() {
             self.monster = other.monster;
+            self.npc_class = other.npc_class;
         }
 
         if other.field.is_some() {
             self.field = other.field;
         }
+
+        self.annotations.extend(other.annotations);
     }
 }
@@ -1,13 +1,19 @@
 use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::map_data::{
+    PlaceInnerFurniture, PlaceInnerNested, PlaceInnerTerrain,
+};
 use crate::features::map::map_properties::{
     FurnitureProperty, NestedProperty, TerrainProperty,
 };
 use crate::features::map::{MapData, Place, Property, SetTile};
 use glam::IVec2;
+use rand::RngCore;
+use serde_json::Value;
 
 #[derive(Debug, Clone)]
 pub struct PlaceTerrain {
     pub visible: TerrainProperty,
+    pub inner: PlaceInnerTerrain,
 }
 
 impl Place for PlaceTerrain {
@@ -16,14 +22,21 @@ impl Place for PlaceTerrain {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
-        self.visible.get_commands(position, map_data, json_data)
+        self.visible
+            .get_commands(position, map_data, json_data, rng)
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(&self.inner)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct PlaceFurniture {
     pub visible: FurnitureProperty,
+    pub inner: PlaceInnerFurniture,
 }
 
 impl Place for PlaceFurniture {
@@ -32,14 +45,21 @@ impl Place for PlaceFurniture {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
-        self.visible.get_commands(position, map_data, json_data)
+        self.visible
+            .get_commands(position, map_data, json_data, rng)
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(&self.inner)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct PlaceNested {
     pub nested_property: NestedProperty,
+    pub inner: PlaceInnerNested,
 }
 
 impl Place for PlaceNested {
@@ -48,8 +68,13 @@ impl Place for PlaceNested {
         position: &IVec2,
         map_data: &MapData,
         json_data: &DeserializedCDDAJsonData,
+        rng: &mut dyn RngCore,
     ) -> Option<Vec<SetTile>> {
         self.nested_property
-            .get_commands(position, map_data, json_data)
+            .get_commands(position, map_data, json_data, rng)
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(&self.inner)
     }
 }
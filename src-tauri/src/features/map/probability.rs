@@ -0,0 +1,339 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::palettes::{PaletteScope, Parameter};
+use crate::features::map::{
+    property_mapgen_value, MapData, MappingKind, Property,
+};
+use cdda_lib::types::{
+    CDDADistributionInner, CDDAIdentifier, MapGenValue, MeabyVec,
+    MeabyWeighted, ParameterIdentifier, Switch,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// The chance that a mapgen value resolves to the `id`, between 0 and 1
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct IdProbability {
+    pub id: CDDAIdentifier,
+    pub probability: f64,
+}
+
+/// All parameters which are visible to the map, including the ones of every
+/// palette it can include. Like when calculating the parameters, palettes
+/// override the parameters of the map.
+pub fn collect_parameters(
+    map_data: &MapData,
+    json_data: &DeserializedCDDAJsonData,
+) -> HashMap<ParameterIdentifier, Parameter> {
+    let mut parameters: HashMap<ParameterIdentifier, Parameter> = map_data
+        .parameters
+        .iter()
+        .map(|(id, p)| (id.clone(), p.clone()))
+        .collect();
+
+    let scope = map_data.palette_scope(&json_data.palettes);
+    let mut visited = HashSet::new();
+    let mut to_visit: Vec<CDDAIdentifier> = map_data
+        .palettes
+        .iter()
+        .flat_map(|p| possible_ids(p, &parameters))
+        .collect();
+
+    while let Some(palette_id) = to_visit.pop() {
+        if !visited.insert(palette_id.clone()) {
+            continue;
+        }
+
+        let Some(palette) = scope.get(&palette_id) else {
+            continue;
+        };
+
+        palette.parameters.iter().for_each(|(id, p)| {
+            parameters.insert(id.clone(), p.clone());
+        });
+
+        to_visit.extend(
+            palette
+                .palettes
+                .iter()
+                .flat_map(|p| possible_ids(p, &parameters)),
+        );
+    }
+
+    parameters
+}
+
+/// The normalized chances of every id the `value` can resolve to, sorted from
+/// the most to the least likely. Parameters are resolved through their
+/// default distribution and switches through the cases of every id the
+/// switched parameter can have.
+pub fn probability_breakdown(
+    value: &MapGenValue,
+    parameters: &HashMap<ParameterIdentifier, Parameter>,
+) -> Vec<IdProbability> {
+    let mut probabilities = HashMap::new();
+    add_value(&mut probabilities, value, parameters, 1.);
+
+    into_breakdown(probabilities)
+}
+
+/// The normalized chances of every terrain or furniture the cell with the
+/// `character` can turn into. Unlike [`probability_breakdown`] of the first
+/// mapping, every palette the palettes of the map can resolve to is followed,
+/// and the chance that no palette maps the character goes to the next one or,
+/// for terrain, to the fill terrain of the map.
+pub fn cell_breakdown(
+    map_data: &MapData,
+    mapping_kind: &MappingKind,
+    character: &char,
+    json_data: &DeserializedCDDAJsonData,
+    parameters: &HashMap<ParameterIdentifier, Parameter>,
+) -> Vec<IdProbability> {
+    let mut probabilities = HashMap::new();
+
+    let mapped = add_mapping(
+        &mut probabilities,
+        &Mapping {
+            kind: mapping_kind,
+            character,
+            palettes: map_data.palette_scope(&json_data.palettes),
+            parameters,
+        },
+        map_data.properties.get(mapping_kind),
+        &map_data.palettes,
+        1.,
+        &mut vec![],
+    );
+
+    let unmapped = 1. - mapped;
+
+    if *mapping_kind == MappingKind::Terrain && unmapped > f64::EPSILON {
+        if let Some(fill) = &map_data.fill {
+            add_value(
+                &mut probabilities,
+                &fill.clone().into(),
+                parameters,
+                unmapped,
+            );
+        }
+    }
+
+    into_breakdown(probabilities)
+}
+
+fn into_breakdown(
+    probabilities: HashMap<CDDAIdentifier, f64>,
+) -> Vec<IdProbability> {
+    let mut breakdown: Vec<IdProbability> = probabilities
+        .into_iter()
+        .map(|(id, probability)| IdProbability { id, probability })
+        .collect();
+
+    breakdown.sort_by(|a, b| {
+        b.probability
+            .total_cmp(&a.probability)
+            .then_with(|| a.id.0.cmp(&b.id.0))
+    });
+
+    breakdown
+}
+
+/// What is looked up while following the palettes of a map
+struct Mapping<'a> {
+    kind: &'a MappingKind,
+    character: &'a char,
+    palettes: PaletteScope<'a>,
+    parameters: &'a HashMap<ParameterIdentifier, Parameter>,
+}
+
+/// Adds the outcomes of the `properties` or, if they do not map the
+/// character, of the `palettes` and returns the part of the `scale` which is
+/// mapped. `chain` contains the palettes which are currently followed so
+/// palettes including themselves are not followed forever.
+fn add_mapping(
+    probabilities: &mut HashMap<CDDAIdentifier, f64>,
+    mapping: &Mapping,
+    properties: Option<&HashMap<char, Arc<dyn Property>>>,
+    palettes: &[MapGenValue],
+    scale: f64,
+    chain: &mut Vec<CDDAIdentifier>,
+) -> f64 {
+    if let Some(property) = properties.and_then(|p| p.get(mapping.character)) {
+        if let Some(value) = property_mapgen_value(mapping.kind, property) {
+            add_value(probabilities, &value, mapping.parameters, scale);
+        }
+
+        return scale;
+    }
+
+    let mut remaining = scale;
+
+    for palette_value in palettes {
+        let mut mapped = 0.;
+
+        for palette_id in
+            probability_breakdown(palette_value, mapping.parameters)
+        {
+            if chain.contains(&palette_id.id) {
+                continue;
+            }
+
+            let Some(palette) = mapping.palettes.get(&palette_id.id) else {
+                continue;
+            };
+
+            chain.push(palette_id.id);
+            mapped += add_mapping(
+                probabilities,
+                mapping,
+                palette.properties.get(mapping.kind),
+                &palette.palettes,
+                remaining * palette_id.probability,
+                chain,
+            );
+            chain.pop();
+        }
+
+        remaining -= mapped;
+    }
+
+    scale - remaining
+}
+
+fn add_value(
+    probabilities: &mut HashMap<CDDAIdentifier, f64>,
+    value: &MapGenValue,
+    parameters: &HashMap<ParameterIdentifier, Parameter>,
+    scale: f64,
+) {
+    match value {
+        MapGenValue::String(id) => add_probability(probabilities, id, scale),
+        MapGenValue::Param { param, fallback } => {
+            add_param(probabilities, param, fallback, parameters, scale)
+        },
+        MapGenValue::Switch { switch, cases } => {
+            add_switch(probabilities, switch, cases, parameters, scale)
+        },
+        MapGenValue::Distribution(distribution) => {
+            add_distribution(probabilities, distribution, parameters, scale)
+        },
+    }
+}
+
+/// Every id the `value` can resolve to
+fn possible_ids(
+    value: &MapGenValue,
+    parameters: &HashMap<ParameterIdentifier, Parameter>,
+) -> Vec<CDDAIdentifier> {
+    probability_breakdown(value, parameters)
+        .into_iter()
+        .map(|p| p.id)
+        .collect()
+}
+
+fn add_probability(
+    probabilities: &mut HashMap<CDDAIdentifier, f64>,
+    id: &CDDAIdentifier,
+    scale: f64,
+) {
+    *probabilities.entry(id.clone()).or_default() += scale;
+}
+
+/// Splits `scale` between the entries of the distribution by their weight
+fn weighted_entries<'a, T>(
+    distribution: &'a MeabyVec<MeabyWeighted<T>>,
+    scale: f64,
+) -> Vec<(&'a T, f64)> {
+    let entries: Vec<&MeabyWeighted<T>> = match distribution {
+        MeabyVec::Single(single) => vec![single],
+        MeabyVec::Vec(vec) => vec.iter().collect(),
+    };
+
+    let total: i32 = entries.iter().map(|e| e.weight_or_one().max(0)).sum();
+
+    if total <= 0 {
+        return vec![];
+    }
+
+    entries
+        .into_iter()
+        .map(|e| {
+            let weight = e.weight_or_one().max(0) as f64;
+            (e.as_data(), scale * weight / total as f64)
+        })
+        .collect()
+}
+
+fn add_param(
+    probabilities: &mut HashMap<CDDAIdentifier, f64>,
+    param: &ParameterIdentifier,
+    fallback: &Option<CDDAIdentifier>,
+    parameters: &HashMap<ParameterIdentifier, Parameter>,
+    scale: f64,
+) {
+    match (parameters.get(param), fallback) {
+        (Some(parameter), _) => {
+            weighted_entries(&parameter.default.distribution, scale)
+                .into_iter()
+                .for_each(|(id, chance)| {
+                    add_probability(probabilities, id, chance)
+                })
+        },
+        (None, Some(fallback)) => {
+            add_probability(probabilities, fallback, scale)
+        },
+        (None, None) => {},
+    }
+}
+
+fn add_switch(
+    probabilities: &mut HashMap<CDDAIdentifier, f64>,
+    switch: &Switch,
+    cases: &HashMap<CDDAIdentifier, CDDAIdentifier>,
+    parameters: &HashMap<ParameterIdentifier, Parameter>,
+    scale: f64,
+) {
+    let mut switched = HashMap::new();
+    add_param(
+        &mut switched,
+        &switch.param,
+        &Some(switch.fallback.clone()),
+        parameters,
+        scale,
+    );
+
+    // Values without a case can not be resolved and are left out
+    switched.into_iter().for_each(|(value, chance)| {
+        if let Some(id) = cases.get(&value) {
+            add_probability(probabilities, id, chance)
+        }
+    });
+}
+
+fn add_distribution(
+    probabilities: &mut HashMap<CDDAIdentifier, f64>,
+    distribution: &MeabyVec<MeabyWeighted<CDDADistributionInner>>,
+    parameters: &HashMap<ParameterIdentifier, Parameter>,
+    scale: f64,
+) {
+    for (inner, chance) in weighted_entries(distribution, scale) {
+        match inner {
+            CDDADistributionInner::String(id) => {
+                add_probability(probabilities, id, chance)
+            },
+            CDDADistributionInner::Param { param, fallback } => {
+                add_param(probabilities, param, fallback, parameters, chance)
+            },
+            CDDADistributionInner::Switch { switch, cases } => {
+                add_switch(probabilities, switch, cases, parameters, chance)
+            },
+            CDDADistributionInner::Distribution(distribution) => {
+                weighted_entries(&distribution.distribution, chance)
+                    .into_iter()
+                    .for_each(|(id, chance)| {
+                        add_probability(probabilities, id, chance)
+                    })
+            },
+        }
+    }
+}
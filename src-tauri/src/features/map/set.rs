@@ -0,0 +1,449 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::{GetIdentifier, TileLayer};
+use crate::features::map::{MapData, SetTile, TileState};
+use crate::features::tileset::legacy_tileset::TilesheetCDDAId;
+use crate::util::{bresenham_line, Rotation};
+use cdda_lib::types::{CDDAIdentifier, NumberOrRange};
+use dyn_clone::{clone_trait_object, DynClone};
+use glam::IVec2;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt::Debug;
+use strum_macros::{AsRefStr, EnumString};
+
+/// A single entry of the `set` array of a mapgen
+pub trait Set: Debug + DynClone + Send + Sync {
+    /// The local coordinates a single repetition of the set is applied to
+    fn coordinates(&self, rng: &mut dyn RngCore) -> Vec<IVec2>;
+
+    fn z(&self) -> i32;
+
+    /// The set is applied with a one in `chance` chance for each repetition
+    fn chance(&self) -> u32;
+
+    fn repeat(&self) -> &NumberOrRange<u32>;
+
+    fn operation(&self) -> &SetOperation;
+
+    /// The json of the set as it is written in the `set` array of a mapgen
+    fn to_json(&self) -> Result<Value, serde_json::Error>;
+}
+
+clone_trait_object!(Set);
+
+#[derive(Debug, Clone, Deserialize, Serialize, EnumString, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum PlaceableSetType {
+    Terrain,
+    Furniture,
+    Trap,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, EnumString, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum RemovableSetType {
+    ItemRemove,
+    FieldRemove,
+    TrapRemove,
+    CreatureRemove,
+}
+
+#[derive(Debug, Clone)]
+pub enum SetOperation {
+    Place {
+        id: CDDAIdentifier,
+        ty: PlaceableSetType,
+    },
+    Remove {
+        ty: RemovableSetType,
+    },
+    Radiation {
+        amount: NumberOrRange<u32>,
+    },
+    Variable {
+        id: CDDAIdentifier,
+    },
+    Bash {},
+    Burn {},
+}
+
+/// The id of the tile which was last set on the `layer` at the `position`
+fn current_id(
+    position: &IVec2,
+    layer: &TileLayer,
+    previous: &[SetTile],
+) -> Option<CDDAIdentifier> {
+    let command = previous
+        .iter()
+        .rev()
+        .filter(|c| c.annotation.is_none())
+        .find(|c| &c.coordinates == position && &c.layer == layer)?;
+
+    match command.state {
+        TileState::Removed => None,
+        _ => Some(command.id.id.clone()),
+    }
+}
+
+fn is_flammable(flags: &[String]) -> bool {
+    flags.iter().any(|f| f.starts_with("FLAMMABLE"))
+}
+
+/// Replaces the furniture at the `position` with what it turns into when it
+/// is destroyed, or the terrain if there is no furniture. If
+/// `only_flammable` is set, tiles which can not burn are left as they are.
+fn destroy(
+    position: &IVec2,
+    previous: &[SetTile],
+    map_data: &MapData,
+    json_data: &DeserializedCDDAJsonData,
+    only_flammable: bool,
+) -> Vec<SetTile> {
+    let furniture = current_id(position, &TileLayer::Furniture, previous)
+        .and_then(|id| json_data.furniture.get(&id));
+
+    if let Some(furniture) = furniture {
+        if only_flammable && !is_flammable(&furniture.flags) {
+            return vec![];
+        }
+
+        let Some(bash) = &furniture.bash else {
+            return vec![];
+        };
+
+        let command = match &bash.furn_set {
+            None => SetTile::remove(
+                TilesheetCDDAId::simple(furniture.id.clone()),
+                TileLayer::Furniture,
+                position.clone(),
+            ),
+            Some(furn_set) => SetTile::furniture(
+                TilesheetCDDAId::simple(furn_set.clone()),
+                position.clone(),
+                Rotation::Deg0,
+                TileState::Normal,
+            ),
+        };
+
+        return vec![command];
+    }
+
+    // Cells which were never set still have the fill terrain
+    let terrain_id = current_id(position, &TileLayer::Terrain, previous)
+        .or_else(|| {
+            map_data
+                .fill
+                .as_ref()?
+                .get_identifier(&map_data.calculated_parameters)
+                .ok()
+        });
+
+    let Some(terrain) = terrain_id.and_then(|id| json_data.terrain.get(&id))
+    else {
+        return vec![];
+    };
+
+    if only_flammable && !is_flammable(&terrain.flags) {
+        return vec![];
+    }
+
+    match terrain.bash.as_ref().and_then(|b| b.ter_set.clone()) {
+        None => vec![],
+        Some(ter_set) => vec![SetTile::terrain(
+            TilesheetCDDAId::simple(ter_set),
+            position.clone(),
+            Rotation::Deg0,
+            TileState::Normal,
+        )],
+    }
+}
+
+/// Removes the tile on the `layer` at the `position` if there is one
+fn remove(
+    position: &IVec2,
+    layer: TileLayer,
+    previous: &[SetTile],
+    filter: impl Fn(&CDDAIdentifier) -> bool,
+) -> Vec<SetTile> {
+    match current_id(position, &layer, previous) {
+        Some(id) if filter(&id) => vec![SetTile::remove(
+            TilesheetCDDAId::simple(id),
+            layer,
+            position.clone(),
+        )],
+        _ => vec![],
+    }
+}
+
+impl SetOperation {
+    /// The commands which apply the operation at the `position`. Bashing,
+    /// burning and removing depend on the tiles which were set before, so the
+    /// `previous` commands have to be in the order they were applied in.
+    pub fn get_commands(
+        &self,
+        position: &IVec2,
+        previous: &[SetTile],
+        map_data: &MapData,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Vec<SetTile> {
+        match self {
+            SetOperation::Place { id, ty } => {
+                let id = TilesheetCDDAId::simple(id.clone());
+                let position = position.clone();
+
+                let command = match ty {
+                    PlaceableSetType::Terrain => SetTile::terrain(
+                        id,
+                        position,
+                        Rotation::Deg0,
+                        TileState::Normal,
+                    ),
+                    PlaceableSetType::Furniture => SetTile::furniture(
+                        id,
+                        position,
+                        Rotation::Deg0,
+                        TileState::Normal,
+                    ),
+                    PlaceableSetType::Trap => SetTile::trap(
+                        id,
+                        position,
+                        Rotation::Deg0,
+                        TileState::Normal,
+                    ),
+                };
+
+                vec![command]
+            },
+            SetOperation::Remove { ty } => match ty {
+                // Items are not drawn, so there is nothing to remove
+                RemovableSetType::ItemRemove => vec![],
+                RemovableSetType::FieldRemove => {
+                    remove(position, TileLayer::Field, previous, |_| true)
+                },
+                RemovableSetType::CreatureRemove => {
+                    remove(position, TileLayer::Monster, previous, |_| true)
+                },
+                RemovableSetType::TrapRemove => {
+                    remove(position, TileLayer::Trap, previous, |_| true)
+                },
+            },
+            // Radiation and variables do not change what the tile looks like
+            SetOperation::Radiation { .. } | SetOperation::Variable { .. } => {
+                vec![]
+            },
+            SetOperation::Bash {} => {
+                destroy(position, previous, map_data, json_data, false)
+            },
+            SetOperation::Burn {} => {
+                destroy(position, previous, map_data, json_data, true)
+            },
+        }
+    }
+}
+
+/// The json of a set with the `shape`, which is either `point`, `line` or
+/// `square`, without its coordinates. Fields with their default value are
+/// left out like in the mapgen files.
+fn set_to_json(
+    shape: &str,
+    set: &dyn Set,
+) -> Result<Map<String, Value>, serde_json::Error> {
+    let mut object = Map::new();
+
+    let ty = match set.operation() {
+        SetOperation::Place { id, ty } => {
+            object.insert("id".to_string(), serde_json::to_value(id)?);
+            ty.as_ref()
+        },
+        SetOperation::Remove { ty } => ty.as_ref(),
+        SetOperation::Radiation { amount } => {
+            object.insert("amount".to_string(), serde_json::to_value(amount)?);
+            "radiation"
+        },
+        SetOperation::Variable { id } => {
+            object.insert("id".to_string(), serde_json::to_value(id)?);
+            "variable"
+        },
+        SetOperation::Bash {} => "bash",
+        SetOperation::Burn {} => "burn",
+    };
+
+    object.insert(shape.to_string(), Value::String(ty.to_string()));
+
+    if set.z() != 0 {
+        object.insert("z".to_string(), Value::from(set.z()));
+    }
+
+    if set.chance() != 1 {
+        object.insert("chance".to_string(), Value::from(set.chance()));
+    }
+
+    if !matches!(set.repeat(), NumberOrRange::Number(1)) {
+        object
+            .insert("repeat".to_string(), serde_json::to_value(set.repeat())?);
+    }
+
+    Ok(object)
+}
+
+#[derive(Debug, Clone)]
+pub struct SetPoint {
+    pub x: NumberOrRange<i32>,
+    pub y: NumberOrRange<i32>,
+    pub z: i32,
+    pub chance: u32,
+    pub repeat: NumberOrRange<u32>,
+    pub operation: SetOperation,
+}
+
+impl Set for SetPoint {
+    fn coordinates(&self, rng: &mut dyn RngCore) -> Vec<IVec2> {
+        vec![IVec2::new(self.x.sample(rng), self.y.sample(rng))]
+    }
+
+    fn z(&self) -> i32 {
+        self.z
+    }
+
+    fn chance(&self) -> u32 {
+        self.chance
+    }
+
+    fn repeat(&self) -> &NumberOrRange<u32> {
+        &self.repeat
+    }
+
+    fn operation(&self) -> &SetOperation {
+        &self.operation
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        let mut object = set_to_json("point", self)?;
+
+        object.insert("x".to_string(), serde_json::to_value(&self.x)?);
+        object.insert("y".to_string(), serde_json::to_value(&self.y)?);
+
+        Ok(Value::Object(object))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SetLine {
+    pub from_x: NumberOrRange<i32>,
+    pub from_y: NumberOrRange<i32>,
+
+    pub to_x: NumberOrRange<i32>,
+    pub to_y: NumberOrRange<i32>,
+
+    pub z: i32,
+    pub chance: u32,
+    pub repeat: NumberOrRange<u32>,
+    pub operation: SetOperation,
+}
+
+impl Set for SetLine {
+    fn coordinates(&self, rng: &mut dyn RngCore) -> Vec<IVec2> {
+        bresenham_line(
+            self.from_x.sample(rng),
+            self.from_y.sample(rng),
+            self.to_x.sample(rng),
+            self.to_y.sample(rng),
+        )
+        .into_iter()
+        .map(|(x, y)| IVec2::new(x, y))
+        .collect()
+    }
+
+    fn z(&self) -> i32 {
+        self.z
+    }
+
+    fn chance(&self) -> u32 {
+        self.chance
+    }
+
+    fn repeat(&self) -> &NumberOrRange<u32> {
+        &self.repeat
+    }
+
+    fn operation(&self) -> &SetOperation {
+        &self.operation
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        let mut object = set_to_json("line", self)?;
+
+        object.insert("x".to_string(), serde_json::to_value(&self.from_x)?);
+        object.insert("y".to_string(), serde_json::to_value(&self.from_y)?);
+        object.insert("x2".to_string(), serde_json::to_value(&self.to_x)?);
+        object.insert("y2".to_string(), serde_json::to_value(&self.to_y)?);
+
+        Ok(Value::Object(object))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SetSquare {
+    pub top_left_x: NumberOrRange<i32>,
+    pub top_left_y: NumberOrRange<i32>,
+
+    pub bottom_right_x: NumberOrRange<i32>,
+    pub bottom_right_y: NumberOrRange<i32>,
+
+    pub z: i32,
+    pub chance: u32,
+    pub repeat: NumberOrRange<u32>,
+    pub operation: SetOperation,
+}
+
+impl Set for SetSquare {
+    fn coordinates(&self, rng: &mut dyn RngCore) -> Vec<IVec2> {
+        let from = IVec2::new(
+            self.top_left_x.sample(rng),
+            self.top_left_y.sample(rng),
+        );
+        let to = IVec2::new(
+            self.bottom_right_x.sample(rng),
+            self.bottom_right_y.sample(rng),
+        );
+        let (min, max) = (from.min(to), from.max(to));
+
+        (min.y..=max.y)
+            .flat_map(|y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .collect()
+    }
+
+    fn z(&self) -> i32 {
+        self.z
+    }
+
+    fn chance(&self) -> u32 {
+        self.chance
+    }
+
+    fn repeat(&self) -> &NumberOrRange<u32> {
+        &self.repeat
+    }
+
+    fn operation(&self) -> &SetOperation {
+        &self.operation
+    }
+
+    fn to_json(&self) -> Result<Value, serde_json::Error> {
+        let mut object = set_to_json("square", self)?;
+
+        object.insert("x".to_string(), serde_json::to_value(&self.top_left_x)?);
+        object.insert("y".to_string(), serde_json::to_value(&self.top_left_y)?);
+        object.insert(
+            "x2".to_string(),
+            serde_json::to_value(&self.bottom_right_x)?,
+        );
+        object.insert(
+            "y2".to_string(),
+            serde_json::to_value(&self.bottom_right_y)?,
+        );
+
+        Ok(Value::Object(object))
+    }
+}
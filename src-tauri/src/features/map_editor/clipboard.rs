@@ -0,0 +1,358 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::map_data::PlaceOuter;
+use crate::features::map::{Cell, MapData, MappingKind, Place, Property};
+use crate::features::map_editor::{
+    get_map_data_mut, resolve_cell, CellChange, EditMapError, PAINT_CHARACTERS,
+};
+use crate::features::program_data::{MapCoordinates, Project, ZLevel};
+use crate::util::IVec3JsonKey;
+use cdda_lib::types::{MapGenValue, NumberOrRange};
+use glam::{IVec3, UVec2};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+
+/// A copied region of a map. Palette mappings of the copied characters are
+/// resolved, so the region can be pasted into maps which do not use the same
+/// palettes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MapClipboard {
+    pub size: UVec2,
+    pub rows: Vec<String>,
+    /// Terrain and furniture the copied characters are mapped to
+    pub terrain: BTreeMap<char, MapGenValue>,
+    pub furniture: BTreeMap<char, MapGenValue>,
+
+    #[serde(skip)]
+    pub properties: HashMap<MappingKind, HashMap<char, Arc<dyn Property>>>,
+
+    /// Place entries which lie completely inside the region, relative to the
+    /// top left corner of the region
+    #[serde(skip)]
+    pub place: HashMap<MappingKind, Vec<PlaceOuter<Arc<dyn Place>>>>,
+}
+
+pub(super) fn number_or_range_bounds(value: &NumberOrRange<i32>) -> (i32, i32) {
+    match value {
+        NumberOrRange::Number(n) => (*n, *n),
+        NumberOrRange::Range((from, to)) => (*from.min(to), *from.max(to)),
+    }
+}
+
+/// Resolves the rectangle spanned by `from` and `to`, which has to lie inside
+/// a single map
+pub(super) fn resolve_region(
+    project: &Project,
+    from: IVec3,
+    to: IVec3,
+) -> Result<(ZLevel, MapCoordinates, UVec2, UVec2), EditMapError> {
+    if from.z != to.z {
+        return Err(EditMapError::DifferentZLevels(from.z, to.z));
+    }
+
+    let (z, map_coordinates, min) = resolve_cell(project, from.min(to))?;
+    let (_, max_map_coordinates, max) = resolve_cell(project, from.max(to))?;
+
+    if map_coordinates != max_map_coordinates {
+        return Err(EditMapError::RegionSpansMaps);
+    }
+
+    Ok((z, map_coordinates, min, max))
+}
+
+/// Copies the cells, mappings and place entries of the rectangle spanned by
+/// `from` and `to`
+pub fn copy_region(
+    project: &Project,
+    from: IVec3,
+    to: IVec3,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<MapClipboard, EditMapError> {
+    let (z, map_coordinates, min, max) = resolve_region(project, from, to)?;
+    // Safe since the region was resolved above
+    let map_data = &project.maps[&z].maps[&map_coordinates];
+
+    let rows: Vec<String> = (min.y..=max.y)
+        .map(|y| {
+            (min.x..=max.x)
+                .map(|x| {
+                    map_data
+                        .cells
+                        .get(&UVec2::new(x, y))
+                        .map(|c| c.character)
+                        .unwrap_or(' ')
+                })
+                .collect()
+        })
+        .collect();
+
+    let characters: HashSet<char> =
+        rows.iter().flat_map(|row| row.chars()).collect();
+
+    let mut clipboard = MapClipboard {
+        size: max - min + UVec2::ONE,
+        rows,
+        ..Default::default()
+    };
+
+    for kind in MappingKind::iter() {
+        for character in characters.iter() {
+            let Some(property) =
+                map_data.get_property(&kind, character, json_data)
+            else {
+                continue;
+            };
+
+            let mapgen_value =
+                map_data.get_mapgen_value(&kind, character, json_data);
+
+            match (&kind, mapgen_value) {
+                (MappingKind::Terrain, Some(value)) => {
+                    clipboard.terrain.insert(*character, value);
+                },
+                (MappingKind::Furniture, Some(value)) => {
+                    clipboard.furniture.insert(*character, value);
+                },
+                _ => {},
+            }
+
+            clipboard
+                .properties
+                .entry(kind.clone())
+                .or_default()
+                .insert(*character, property);
+        }
+    }
+
+    let (min_x, min_y) = (min.x as i32, min.y as i32);
+    let (max_x, max_y) = (max.x as i32, max.y as i32);
+
+    for (kind, entries) in map_data.place.iter() {
+        for entry in entries {
+            let (from_x, to_x) = number_or_range_bounds(&entry.x);
+            let (from_y, to_y) = number_or_range_bounds(&entry.y);
+
+            if from_x < min_x || to_x > max_x || from_y < min_y || to_y > max_y
+            {
+                continue;
+            }
+
+            let mut entry = entry.clone();
+            entry.x = entry.x - min_x;
+            entry.y = entry.y - min_y;
+
+            clipboard.place.entry(kind.clone()).or_default().push(entry);
+        }
+    }
+
+    Ok(clipboard)
+}
+
+/// Finds the character a copied `character` is pasted as. The character is
+/// kept if the target map does not use or map it yet, otherwise a free one is
+/// chosen.
+fn find_paste_character(
+    map_data: &MapData,
+    character: char,
+    used: &HashSet<char>,
+    json_data: &DeserializedCDDAJsonData,
+) -> Option<char> {
+    let is_free = |character: &char| {
+        !used.contains(character)
+            && MappingKind::iter().all(|k| {
+                map_data
+                    .get_mapping_source(&k, character, json_data)
+                    .is_none()
+            })
+    };
+
+    if is_free(&character) {
+        return Some(character);
+    }
+
+    PAINT_CHARACTERS.chars().find(is_free)
+}
+
+/// Pastes the `clipboard` with its top left corner at the global `position`.
+/// Copied characters which would collide with the characters of the target
+/// map are mapped to free characters.
+///
+/// Returns the changed cells so they can be rendered again.
+pub fn paste_region(
+    project: &mut Project,
+    clipboard: &MapClipboard,
+    position: IVec3,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<Vec<CellChange>, EditMapError> {
+    let size = clipboard.size;
+    let end = position + (size.as_ivec2() - 1).extend(0);
+    let (z, map_coordinates, min, max) =
+        resolve_region(project, position, end)?;
+    let map_data = get_map_data_mut(project, z, &map_coordinates);
+
+    // Cells which are pasted over do not block their characters
+    let mut used: HashSet<char> = map_data
+        .cells
+        .iter()
+        .filter(|(c, _)| {
+            c.x < min.x || c.y < min.y || c.x > max.x || c.y > max.y
+        })
+        .map(|(_, cell)| cell.character)
+        .collect();
+
+    let characters: BTreeSet<char> =
+        clipboard.rows.iter().flat_map(|row| row.chars()).collect();
+
+    let is_mapped = |character: &char| {
+        clipboard
+            .properties
+            .values()
+            .any(|properties| properties.contains_key(character))
+    };
+    let is_mapped_in_target = |character: &char| {
+        MappingKind::iter().any(|k| {
+            map_data
+                .get_mapping_source(&k, character, json_data)
+                .is_some()
+        })
+    };
+
+    // Characters the target map does not map keep themselves. Unmapped
+    // characters are left to the fill terrain of the target map, so they may
+    // already be used by it. These characters are reserved before any other
+    // character is moved, so no two copied characters end up the same.
+    let mut remapped: HashMap<char, char> = characters
+        .iter()
+        .filter(|c| {
+            !is_mapped_in_target(*c) && (!is_mapped(*c) || !used.contains(*c))
+        })
+        .map(|c| (*c, *c))
+        .collect();
+    used.extend(remapped.keys());
+
+    let moved: Vec<char> = characters
+        .iter()
+        .filter(|c| !remapped.contains_key(*c))
+        .copied()
+        .collect();
+
+    for character in moved {
+        let target =
+            find_paste_character(map_data, character, &used, json_data)
+                .ok_or(EditMapError::NoFreePasteCharacter(character))?;
+
+        used.insert(target);
+        remapped.insert(character, target);
+    }
+
+    // Only change the map once every character has a free target, so a
+    // failed paste leaves the map untouched
+    for (character, target) in remapped.iter() {
+        for (kind, properties) in clipboard.properties.iter() {
+            if let Some(property) = properties.get(character) {
+                map_data
+                    .properties
+                    .entry(kind.clone())
+                    .or_default()
+                    .insert(*target, property.clone());
+            }
+        }
+    }
+
+    let mut changes = vec![];
+
+    for (y, row) in clipboard.rows.iter().enumerate() {
+        for (x, character) in row.chars().enumerate() {
+            let cell_coordinates = min + UVec2::new(x as u32, y as u32);
+            let character = remapped[&character];
+
+            map_data.cells.insert(cell_coordinates, Cell { character });
+            changes.push(CellChange {
+                coordinates: IVec3JsonKey(
+                    position + IVec3::new(x as i32, y as i32, 0),
+                ),
+                character,
+            });
+        }
+    }
+
+    for (kind, entries) in clipboard.place.iter() {
+        for entry in entries {
+            let mut entry = entry.clone();
+            entry.x = entry.x + min.x as i32;
+            entry.y = entry.y + min.y as i32;
+
+            map_data.place.entry(kind.clone()).or_default().push(entry);
+        }
+    }
+
+    project.dirty = true;
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::map::map_properties::TerrainProperty;
+    use crate::features::map::DEFAULT_MAP_DATA_SIZE;
+    use crate::features::program_data::{
+        MapDataCollection, ProjectSaveState, ProjectType,
+    };
+
+    #[test]
+    fn test_paste_does_not_move_onto_kept_characters() {
+        let json_data = DeserializedCDDAJsonData::default();
+
+        let mut target = MapData::default();
+        target.set_local_mapgen_value(
+            MappingKind::Terrain,
+            'a',
+            MapGenValue::String("t_wall".into()),
+        );
+
+        let mut project = Project::new(
+            "test_paste".into(),
+            DEFAULT_MAP_DATA_SIZE,
+            ProjectType::MapEditor(ProjectSaveState::Unsaved),
+        );
+        project.maps.insert(
+            0,
+            MapDataCollection {
+                maps: HashMap::from([(UVec2::ZERO, target)]),
+            },
+        );
+
+        // 'a' collides with the target map and has to be moved, while the
+        // unmapped 'b' keeps itself, even though it is the first free
+        // character 'a' could be moved to
+        let terrain = Arc::new(TerrainProperty {
+            mapgen_value: MapGenValue::String("t_floor".into()),
+        }) as Arc<dyn Property>;
+        let clipboard = MapClipboard {
+            size: UVec2::new(2, 1),
+            rows: vec!["ab".to_string()],
+            properties: HashMap::from([(
+                MappingKind::Terrain,
+                HashMap::from([('a', terrain)]),
+            )]),
+            ..Default::default()
+        };
+
+        let changes =
+            paste_region(&mut project, &clipboard, IVec3::ZERO, &json_data)
+                .unwrap();
+
+        let pasted_a = changes[0].character;
+        assert_ne!(pasted_a, 'a');
+        assert_ne!(pasted_a, 'b');
+        assert_eq!(changes[1].character, 'b');
+
+        let terrain = project.maps[&0].maps[&UVec2::ZERO]
+            .get_local_mapgen_values(&MappingKind::Terrain);
+        assert_eq!(terrain[&'a'], MapGenValue::String("t_wall".into()));
+        assert_eq!(terrain[&pasted_a], MapGenValue::String("t_floor".into()));
+        assert!(!terrain.contains_key(&'b'));
+    }
+}
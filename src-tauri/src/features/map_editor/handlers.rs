@@ -0,0 +1,488 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::events::{EmitEvent, ProjectChangedEvent};
+use crate::features::demo::{lock_json_data, DemoJsonData};
+use crate::features::map::{MappingKind, DEFAULT_MAP_DATA_SIZE};
+use crate::features::map_editor;
+use crate::features::map_editor::clipboard::MapClipboard;
+use crate::features::map_editor::script::{EditOperation, EditScriptError};
+use crate::features::map_editor::{
+    apply_cell_changes, flood_fill_positions, line_positions, rect_positions,
+    CellChange, EditMapError, MapEditorSaver, NewPlaceEntryError,
+    NewPlaceEntryKind,
+};
+use crate::features::map_editor::{clipboard, script};
+use crate::features::program_data::changes::{
+    EditorDataChanges, EditorDataScope,
+};
+use crate::features::program_data::generations::ProjectGenerations;
+use crate::features::program_data::io::ProgramDataSaver;
+use crate::features::program_data::{
+    EditorData, Project, ProjectName, ProjectSaveState, ProjectType,
+    RecentProject, Tab, TabType, ZLevel,
+};
+use crate::features::viewer::cache::RenderCache;
+use crate::impl_serialize_for_error;
+use crate::util::{
+    get_current_project, get_current_project_mut, get_json_data, CDDADataError,
+    GetCurrentProjectError, IVec3JsonKey, Save, SaveError,
+};
+use cdda_lib::types::{CDDAIdentifier, NumberOrRange};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::ops::Deref;
+use std::path::PathBuf;
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, Manager, State};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NewMapEditorProjectError {
+    #[error("Another project with the same name already exists")]
+    ProjectAlreadyExists,
+
+    #[error(transparent)]
+    TauriError(#[from] tauri::Error),
+
+    #[error("Failed to save the editor data, {0}")]
+    SaveError(#[from] SaveError),
+}
+
+impl_serialize_for_error!(NewMapEditorProjectError);
+
+/// Creates a new map editor project with a single empty map and opens it
+#[tauri::command]
+pub async fn new_map_editor_project(
+    project_name: ProjectName,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+) -> Result<(), NewMapEditorProjectError> {
+    let mut editor_data_lock = editor_data.lock().await;
+
+    if editor_data_lock.loaded_projects.contains_key(&project_name) {
+        return Err(NewMapEditorProjectError::ProjectAlreadyExists);
+    }
+
+    let project = Project::new(
+        project_name.clone(),
+        DEFAULT_MAP_DATA_SIZE,
+        ProjectType::MapEditor(ProjectSaveState::Unsaved),
+    );
+
+    editor_data_lock
+        .loaded_projects
+        .insert(project_name.clone(), project);
+    editor_data_lock
+        .openable_projects
+        .insert(project_name.clone());
+    editor_data_lock.opened_project = Some(project_name.clone());
+
+    let recent_project = RecentProject {
+        path: editor_data_lock.config.config_path.clone(),
+        name: project_name.clone(),
+        thumbnail: None,
+    };
+    editor_data_lock.recent_projects.insert(recent_project);
+
+    app.emit_event(Tab {
+        name: project_name,
+        tab_type: TabType::MapEditor,
+    })?;
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    saver.save(editor_data_lock.deref()).await?;
+
+    editor_data_changes.notify(
+        &app,
+        &[EditorDataScope::ProjectList, EditorDataScope::OpenedProject],
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum EditProjectError {
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error(transparent)]
+    EditMapError(#[from] EditMapError),
+
+    #[error(transparent)]
+    EditScriptError(#[from] EditScriptError),
+
+    #[error(transparent)]
+    NewPlaceEntryError(#[from] NewPlaceEntryError),
+
+    #[error("Only map editor projects can be edited")]
+    NotAMapEditor,
+
+    #[error("Nothing has been copied yet")]
+    EmptyClipboard,
+
+    #[error("The project has never been saved, so a path has to be given")]
+    NoSavePath,
+
+    #[error("Failed to save the project, {0}")]
+    SaveError(#[from] SaveError),
+}
+
+impl_serialize_for_error!(EditProjectError);
+
+/// Changes the characters of cells in the currently opened map editor project
+#[tauri::command]
+pub async fn set_cells(
+    changes: Vec<CellChange>,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<(), EditProjectError> {
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    if !matches!(project.ty, ProjectType::MapEditor(_)) {
+        return Err(EditProjectError::NotAMapEditor);
+    }
+
+    apply_cell_changes(project, &changes)?;
+    render_cache.invalidate_project(&project.name);
+
+    app.emit_event(ProjectChangedEvent {
+        name: project.name.clone(),
+        dirty: project.dirty,
+    })
+    .ok();
+
+    Ok(())
+}
+
+/// Paints the terrain or furniture `id` onto the positions returned by
+/// `get_positions`. The positions are computed while the project is locked for
+/// painting, so the project can not change in between.
+async fn paint_positions(
+    get_positions: impl FnOnce(
+        &Project,
+        &DeserializedCDDAJsonData,
+    ) -> Result<Vec<IVec3JsonKey>, EditMapError>,
+    mapping_kind: MappingKind,
+    id: CDDAIdentifier,
+    app: &AppHandle,
+    editor_data: &Mutex<EditorData>,
+    json_data: &Mutex<Option<DeserializedCDDAJsonData>>,
+    render_cache: &RenderCache,
+) -> Result<Vec<CellChange>, EditProjectError> {
+    let demo_json_data = app.state::<DemoJsonData>();
+    let json_data_lock = lock_json_data(json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    if !matches!(project.ty, ProjectType::MapEditor(_)) {
+        return Err(EditProjectError::NotAMapEditor);
+    }
+
+    let positions = get_positions(project, json_data)?;
+    let changes = map_editor::paint_cells(
+        project,
+        &positions,
+        mapping_kind,
+        id,
+        json_data,
+    )?;
+    render_cache.invalidate_project(&project.name);
+
+    app.emit_event(ProjectChangedEvent {
+        name: project.name.clone(),
+        dirty: project.dirty,
+    })
+    .ok();
+
+    Ok(changes)
+}
+
+/// Paints the terrain or furniture `id` onto the cells at `positions` in the
+/// currently opened map editor project
+#[tauri::command]
+pub async fn paint_cells(
+    positions: Vec<IVec3JsonKey>,
+    mapping_kind: MappingKind,
+    id: CDDAIdentifier,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<Vec<CellChange>, EditProjectError> {
+    paint_positions(
+        |_, _| Ok(positions),
+        mapping_kind,
+        id,
+        &app,
+        &editor_data,
+        &json_data,
+        &render_cache,
+    )
+    .await
+}
+
+/// Paints the terrain or furniture `id` onto a line from `from` to `to`
+#[tauri::command]
+pub async fn draw_line(
+    from: IVec3JsonKey,
+    to: IVec3JsonKey,
+    mapping_kind: MappingKind,
+    id: CDDAIdentifier,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<Vec<CellChange>, EditProjectError> {
+    let positions = line_positions(from.0, to.0)?;
+
+    paint_positions(
+        |_, _| Ok(positions),
+        mapping_kind,
+        id,
+        &app,
+        &editor_data,
+        &json_data,
+        &render_cache,
+    )
+    .await
+}
+
+/// Paints the terrain or furniture `id` onto the rectangle spanned by `from`
+/// and `to`, or only onto its outline if it is not `filled`
+#[tauri::command]
+pub async fn draw_rect(
+    from: IVec3JsonKey,
+    to: IVec3JsonKey,
+    filled: bool,
+    mapping_kind: MappingKind,
+    id: CDDAIdentifier,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<Vec<CellChange>, EditProjectError> {
+    let positions = rect_positions(from.0, to.0, filled)?;
+
+    paint_positions(
+        |_, _| Ok(positions),
+        mapping_kind,
+        id,
+        &app,
+        &editor_data,
+        &json_data,
+        &render_cache,
+    )
+    .await
+}
+
+/// Replaces the terrain or furniture of all cells which are connected to the
+/// `start` and share its terrain or furniture with the `id`
+#[tauri::command]
+pub async fn flood_fill(
+    start: IVec3JsonKey,
+    mapping_kind: MappingKind,
+    id: CDDAIdentifier,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<Vec<CellChange>, EditProjectError> {
+    paint_positions(
+        |project, json_data| {
+            flood_fill_positions(project, start.0, &mapping_kind, json_data)
+        },
+        mapping_kind.clone(),
+        id,
+        &app,
+        &editor_data,
+        &json_data,
+        &render_cache,
+    )
+    .await
+}
+
+/// Runs a list of edit operations against the currently opened map editor
+/// project. Either all operations are applied or none of them.
+#[tauri::command]
+pub async fn run_edit_script(
+    operations: Vec<EditOperation>,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<Vec<CellChange>, EditProjectError> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    if !matches!(project.ty, ProjectType::MapEditor(_)) {
+        return Err(EditProjectError::NotAMapEditor);
+    }
+
+    let changes = script::run_edit_script(project, &operations, json_data)?;
+    render_cache.invalidate_project(&project.name);
+
+    app.emit_event(ProjectChangedEvent {
+        name: project.name.clone(),
+        dirty: project.dirty,
+    })
+    .ok();
+
+    Ok(changes)
+}
+
+/// Copies the rectangle spanned by `from` and `to` of the currently opened
+/// project into the clipboard
+#[tauri::command]
+pub async fn copy_region(
+    from: IVec3JsonKey,
+    to: IVec3JsonKey,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+    map_clipboard: State<'_, Mutex<Option<MapClipboard>>>,
+) -> Result<MapClipboard, EditProjectError> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let editor_data_lock = editor_data.lock().await;
+    let project = get_current_project(&editor_data_lock)?;
+
+    let copied = clipboard::copy_region(project, from.0, to.0, json_data)?;
+    map_clipboard.lock().await.replace(copied.clone());
+
+    Ok(copied)
+}
+
+/// Pastes the clipboard into the currently opened map editor project with its
+/// top left corner at the `position`
+#[tauri::command]
+pub async fn paste_region(
+    position: IVec3JsonKey,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+    map_clipboard: State<'_, Mutex<Option<MapClipboard>>>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<Vec<CellChange>, EditProjectError> {
+    // The clipboard is locked after the json data and editor data, following
+    // the lock order of the state
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    let map_clipboard_lock = map_clipboard.lock().await;
+    let copied = map_clipboard_lock
+        .as_ref()
+        .ok_or(EditProjectError::EmptyClipboard)?;
+
+    if !matches!(project.ty, ProjectType::MapEditor(_)) {
+        return Err(EditProjectError::NotAMapEditor);
+    }
+
+    let changes =
+        clipboard::paste_region(project, copied, position.0, json_data)?;
+    render_cache.invalidate_project(&project.name);
+
+    app.emit_event(ProjectChangedEvent {
+        name: project.name.clone(),
+        dirty: project.dirty,
+    })
+    .ok();
+
+    Ok(changes)
+}
+
+/// Adds a new `place_*` entry with the configured place defaults to the map
+/// of the currently opened map editor project at the global `x`, `y`
+#[tauri::command]
+pub async fn new_place_entry(
+    kind: NewPlaceEntryKind,
+    z: ZLevel,
+    x: NumberOrRange<i32>,
+    y: NumberOrRange<i32>,
+    group: Option<CDDAIdentifier>,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+) -> Result<Value, EditProjectError> {
+    let mut editor_data_lock = editor_data.lock().await;
+    let defaults = editor_data_lock.config.place_defaults.clone();
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    if !matches!(project.ty, ProjectType::MapEditor(_)) {
+        return Err(EditProjectError::NotAMapEditor);
+    }
+
+    let entry =
+        map_editor::new_place_entry(project, &defaults, kind, z, x, y, group)?;
+    render_cache.invalidate_project(&project.name);
+    project_generations.advance(&project.name);
+
+    app.emit_event(ProjectChangedEvent {
+        name: project.name.clone(),
+        dirty: project.dirty,
+    })
+    .ok();
+
+    Ok(entry)
+}
+
+/// Saves the maps of the currently opened map editor project. If no `path` is
+/// given, the project is saved to the file it was last saved to.
+#[tauri::command]
+pub async fn save_map_editor_project(
+    path: Option<PathBuf>,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<(), EditProjectError> {
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    let path = match (&project.ty, path) {
+        (ProjectType::LiveViewer(_), _) => {
+            return Err(EditProjectError::NotAMapEditor);
+        },
+        (_, Some(path)) => path,
+        (ProjectType::MapEditor(ProjectSaveState::Saved { path }), None) => {
+            path.clone()
+        },
+        (ProjectType::MapEditor(ProjectSaveState::Unsaved), None) => {
+            return Err(EditProjectError::NoSavePath);
+        },
+    };
+
+    let saver = MapEditorSaver { path: path.clone() };
+    saver.save(project).await?;
+
+    project.ty = ProjectType::MapEditor(ProjectSaveState::Saved { path });
+    project.dirty = false;
+
+    app.emit_event(ProjectChangedEvent {
+        name: project.name.clone(),
+        dirty: project.dirty,
+    })
+    .ok();
+
+    // The project file has to know where the maps were saved to
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    saver.save(editor_data_lock.deref()).await?;
+
+    Ok(())
+}
@@ -0,0 +1,875 @@
+pub(crate) mod clipboard;
+pub(crate) mod handlers;
+pub(crate) mod script;
+
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::map_data::{
+    CDDAMapDataIntermediate, IntoArcDyn, IntoMapDataCollectionError,
+    PlaceInnerItems, PlaceInnerMonsters, PlaceOuter,
+};
+use crate::data::GetIdentifier;
+use crate::features::map::export::write_mapgen_object;
+use crate::features::map::{
+    Cell, MapData, MappingKind, Place, DEFAULT_MAP_DATA_SIZE,
+};
+use crate::features::program_data::{
+    MapCoordinates, MapDataCollection, PlaceDefaults, Project,
+    ProjectSaveState, ZLevel,
+};
+use crate::impl_serialize_for_error;
+use crate::util::{bresenham_line, IVec3JsonKey, Load, Save, SaveError};
+use cdda_lib::types::{CDDAIdentifier, MapGenValue, NumberOrRange};
+use glam::{IVec3, UVec2};
+use log::info;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+/// Version of the save file format of map editor projects
+pub const MAP_EDITOR_SAVE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedMap {
+    pub z: ZLevel,
+    pub coordinates: MapCoordinates,
+    /// The map written like the `object` of a mapgen, so everything a mapgen
+    /// can contain is kept between saves
+    pub object: Value,
+}
+
+impl SavedMap {
+    pub fn new(
+        z: ZLevel,
+        coordinates: MapCoordinates,
+        map_data: &MapData,
+    ) -> Result<Self, serde_json::Error> {
+        let mut object = Map::new();
+        write_mapgen_object(&mut object, map_data)?;
+
+        Ok(Self {
+            z,
+            coordinates,
+            object: Value::Object(object),
+        })
+    }
+
+    /// Reads the map back in the same way the map of a mapgen is read
+    pub fn into_map_data(self) -> Result<MapData, MapEditorLoadError> {
+        let intermediate = CDDAMapDataIntermediate {
+            update_mapgen_id: None,
+            om_terrain: None,
+            nested_mapgen_id: None,
+            weight: None,
+            object: serde_json::from_value(self.object)?,
+        };
+
+        let mut collection: MapDataCollection = intermediate.try_into()?;
+
+        Ok(collection.maps.remove(&UVec2::ZERO).unwrap_or_default())
+    }
+}
+
+/// The file a map editor project is saved to
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MapEditorSaveFile {
+    pub version: u32,
+    pub maps: Vec<SavedMap>,
+}
+
+#[derive(Debug, Error)]
+pub enum MapEditorLoadError {
+    #[error("Failed to read the project file, {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The project file is not valid, {0}")]
+    InvalidContent(#[from] serde_json::Error),
+
+    #[error("The project file was saved with the unsupported version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("A map in the project file is not valid, {0}")]
+    InvalidMap(#[from] IntoMapDataCollectionError),
+}
+
+pub struct MapEditorSaver {
+    pub path: PathBuf,
+}
+
+impl Save<Project> for MapEditorSaver {
+    async fn save(&self, data: &Project) -> Result<(), SaveError> {
+        let mut maps = vec![];
+
+        for (z, collection) in data.maps.iter() {
+            for (coordinates, map_data) in collection.maps.iter() {
+                maps.push(SavedMap::new(*z, *coordinates, map_data)?);
+            }
+        }
+
+        // Keep the file stable between saves so it can be put under version
+        // control
+        maps.sort_by_key(|m| (m.z, m.coordinates.y, m.coordinates.x));
+
+        let save_file = MapEditorSaveFile {
+            version: MAP_EDITOR_SAVE_VERSION,
+            maps,
+        };
+
+        fs::write(&self.path, serde_json::to_string_pretty(&save_file)?)?;
+        info!(
+            "Saved map editor project {} to {}",
+            data.name,
+            self.path.display()
+        );
+
+        Ok(())
+    }
+}
+
+pub struct MapEditorLoader {
+    pub save_state: ProjectSaveState,
+}
+
+impl Load<HashMap<ZLevel, MapDataCollection>, MapEditorLoadError>
+    for MapEditorLoader
+{
+    async fn load(
+        &mut self,
+    ) -> Result<HashMap<ZLevel, MapDataCollection>, MapEditorLoadError> {
+        let path = match &self.save_state {
+            // Projects which were never saved start with a single empty map
+            ProjectSaveState::Unsaved => {
+                return Ok(HashMap::from([(0, MapDataCollection::default())]));
+            },
+            ProjectSaveState::Saved { path } => path,
+        };
+
+        let save_file: MapEditorSaveFile =
+            serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        if save_file.version > MAP_EDITOR_SAVE_VERSION {
+            return Err(MapEditorLoadError::UnsupportedVersion(
+                save_file.version,
+            ));
+        }
+
+        let mut maps: HashMap<ZLevel, MapDataCollection> = HashMap::new();
+
+        for saved_map in save_file.maps {
+            let z = saved_map.z;
+            let coordinates = saved_map.coordinates;
+
+            maps.entry(z)
+                .or_insert_with(|| MapDataCollection {
+                    maps: HashMap::new(),
+                })
+                .maps
+                .insert(coordinates, saved_map.into_map_data()?);
+        }
+
+        Ok(maps)
+    }
+}
+
+/// A single cell which should be changed to the `character`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CellChange {
+    /// Global coordinates of the cell
+    pub coordinates: IVec3JsonKey,
+    pub character: char,
+}
+
+#[derive(Debug, Error)]
+pub enum EditMapError {
+    #[error("The cell at {0} is outside of the project")]
+    OutOfBounds(IVec3),
+
+    #[error("Only terrain and furniture can be painted, not {0:?}")]
+    UnsupportedMappingKind(MappingKind),
+
+    #[error("There is no free character left to map {0} to")]
+    NoFreeCharacter(CDDAIdentifier),
+
+    #[error("Shapes can not span multiple z-levels, got {0} and {1}")]
+    DifferentZLevels(ZLevel, ZLevel),
+
+    #[error("Regions can only be copied from and pasted into a single map")]
+    RegionSpansMaps,
+
+    #[error("There is no free character left to paste {0} as")]
+    NoFreePasteCharacter(char),
+}
+
+/// The kinds of `place_*` entries which can be created with the
+/// [`PlaceDefaults`]
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewPlaceEntryKind {
+    Items,
+    Monsters,
+}
+
+#[derive(Debug, Error)]
+pub enum NewPlaceEntryError {
+    #[error("No {0} was given and no default is configured")]
+    NoGroup(&'static str),
+
+    #[error(transparent)]
+    EditMapError(#[from] EditMapError),
+
+    #[error("The place entry is not valid, {0}")]
+    InvalidEntry(#[from] serde_json::Error),
+}
+
+impl_serialize_for_error!(NewPlaceEntryError);
+
+/// Creates a new `place_items` or `place_monsters` entry at the global `x`,
+/// `y` on the z-level `z` and adds it to the map it lies in. Every value which
+/// is not given is taken from the `defaults`.
+///
+/// Returns the entry as it is written in the mapgen of the map.
+pub fn new_place_entry(
+    project: &mut Project,
+    defaults: &PlaceDefaults,
+    kind: NewPlaceEntryKind,
+    z: ZLevel,
+    x: NumberOrRange<i32>,
+    y: NumberOrRange<i32>,
+    group: Option<CDDAIdentifier>,
+) -> Result<Value, NewPlaceEntryError> {
+    let (key, group, mapping_kind) = match kind {
+        NewPlaceEntryKind::Items => (
+            "item",
+            group
+                .or(defaults.item_group.clone())
+                .ok_or(NewPlaceEntryError::NoGroup("item group"))?,
+            MappingKind::ItemGroups,
+        ),
+        NewPlaceEntryKind::Monsters => (
+            "monster",
+            group
+                .or(defaults.monster_group.clone())
+                .ok_or(NewPlaceEntryError::NoGroup("monster group"))?,
+            MappingKind::Monsters,
+        ),
+    };
+
+    let (min_x, max_x) = clipboard::number_or_range_bounds(&x);
+    let (min_y, max_y) = clipboard::number_or_range_bounds(&y);
+    let (z, map_coordinates, _, _) = clipboard::resolve_region(
+        project,
+        IVec3::new(min_x, min_y, z),
+        IVec3::new(max_x, max_y, z),
+    )?;
+
+    // Place entries are relative to the map they are in
+    let offset = (map_coordinates * DEFAULT_MAP_DATA_SIZE).as_ivec2();
+    let x = x - offset.x;
+    let y = y - offset.y;
+
+    let entry = json!({
+        key: group,
+        "x": x,
+        "y": y,
+        "chance": defaults.chance,
+        "repeat": defaults.repeat,
+    });
+
+    let place: PlaceOuter<Arc<dyn Place>> = match kind {
+        NewPlaceEntryKind::Items => PlaceOuter::into_arc_dyn_place(
+            serde_json::from_value::<PlaceOuter<PlaceInnerItems>>(
+                entry.clone(),
+            )?,
+            x,
+            y,
+        ),
+        NewPlaceEntryKind::Monsters => PlaceOuter::into_arc_dyn_place(
+            serde_json::from_value::<PlaceOuter<PlaceInnerMonsters>>(
+                entry.clone(),
+            )?,
+            x,
+            y,
+        ),
+    };
+
+    get_map_data_mut(project, z, &map_coordinates)
+        .place
+        .entry(mapping_kind)
+        .or_default()
+        .push(place);
+    project.dirty = true;
+
+    Ok(entry)
+}
+
+/// Characters which are tried in order when a new character has to be mapped
+/// while painting
+const PAINT_CHARACTERS: &str = concat!(
+    "abcdefghijklmnopqrstuvwxyz",
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+    "0123456789#$%&*+-=?@^~!;:<>/|",
+);
+
+/// The z-level, map coordinates and cell coordinates of the cell at the global
+/// `position` in the project
+fn resolve_cell(
+    project: &Project,
+    position: IVec3,
+) -> Result<(ZLevel, MapCoordinates, UVec2), EditMapError> {
+    if position.x < 0 || position.y < 0 {
+        return Err(EditMapError::OutOfBounds(position));
+    }
+
+    let map_coordinates = UVec2::new(
+        position.x as u32 / DEFAULT_MAP_DATA_SIZE.x,
+        position.y as u32 / DEFAULT_MAP_DATA_SIZE.y,
+    );
+    let cell_coordinates = UVec2::new(
+        position.x as u32 % DEFAULT_MAP_DATA_SIZE.x,
+        position.y as u32 % DEFAULT_MAP_DATA_SIZE.y,
+    );
+
+    let exists = project
+        .maps
+        .get(&position.z)
+        .and_then(|c| c.maps.get(&map_coordinates))
+        .is_some_and(|m| {
+            cell_coordinates.x < m.map_size.x
+                && cell_coordinates.y < m.map_size.y
+        });
+
+    if !exists {
+        return Err(EditMapError::OutOfBounds(position));
+    }
+
+    Ok((position.z, map_coordinates, cell_coordinates))
+}
+
+fn get_map_data_mut<'a>(
+    project: &'a mut Project,
+    z: ZLevel,
+    map_coordinates: &MapCoordinates,
+) -> &'a mut MapData {
+    // Safe since every cell is resolved before it is changed
+    project
+        .maps
+        .get_mut(&z)
+        .unwrap()
+        .maps
+        .get_mut(map_coordinates)
+        .unwrap()
+}
+
+/// Applies the `changes` to the maps of the `project`. Nothing is changed if
+/// any of the cells are outside the project.
+pub fn apply_cell_changes(
+    project: &mut Project,
+    changes: &[CellChange],
+) -> Result<(), EditMapError> {
+    let mut resolved = Vec::with_capacity(changes.len());
+
+    for change in changes {
+        let (z, map_coordinates, cell_coordinates) =
+            resolve_cell(project, change.coordinates.0)?;
+
+        resolved.push((z, map_coordinates, cell_coordinates, change));
+    }
+
+    for (z, map_coordinates, cell_coordinates, change) in resolved {
+        get_map_data_mut(project, z, &map_coordinates).cells.insert(
+            cell_coordinates,
+            Cell {
+                character: change.character,
+            },
+        );
+    }
+
+    project.dirty = true;
+
+    Ok(())
+}
+
+/// Finds a character which maps to `mapgen_value` for the `kind` and to
+/// `other_value` for the `other_kind` without mapping anything else. A new
+/// character is mapped if no such character exists yet.
+fn find_paint_character(
+    map_data: &mut MapData,
+    kind: &MappingKind,
+    mapgen_value: &MapGenValue,
+    other_kind: &MappingKind,
+    other_value: Option<&MapGenValue>,
+    json_data: &DeserializedCDDAJsonData,
+) -> Option<char> {
+    let is_mapped = |map_data: &MapData, character: &char, k: &MappingKind| {
+        map_data
+            .get_mapping_source(k, character, json_data)
+            .is_some()
+    };
+
+    let others = map_data.get_local_mapgen_values(other_kind);
+
+    let existing = map_data
+        .get_local_mapgen_values(kind)
+        .into_iter()
+        .filter(|(_, value)| value == mapgen_value)
+        .map(|(character, _)| character)
+        .find(|character| {
+            others.get(character) == other_value
+                && (other_value.is_some()
+                    || !is_mapped(map_data, character, other_kind))
+                && MappingKind::iter()
+                    .filter(|k| k != kind && k != other_kind)
+                    .all(|k| !is_mapped(map_data, character, &k))
+        });
+
+    if existing.is_some() {
+        return existing;
+    }
+
+    let used: HashSet<char> =
+        map_data.cells.values().map(|c| c.character).collect();
+
+    let character = PAINT_CHARACTERS.chars().find(|character| {
+        !used.contains(character)
+            && MappingKind::iter().all(|k| !is_mapped(map_data, character, &k))
+    })?;
+
+    map_data.set_local_mapgen_value(
+        kind.clone(),
+        character,
+        mapgen_value.clone(),
+    );
+
+    if let Some(other_value) = other_value {
+        map_data.set_local_mapgen_value(
+            other_kind.clone(),
+            character,
+            other_value.clone(),
+        );
+    }
+
+    Some(character)
+}
+
+/// Paints the terrain or furniture `id` onto the cells at the global
+/// `positions`. Characters which are already mapped to the `id` by the map are
+/// reused, otherwise a free character is mapped to it. Terrain and furniture
+/// which the map itself mapped a painted cell to are kept.
+///
+/// Returns the changed cells so they can be rendered again.
+pub fn paint_cells(
+    project: &mut Project,
+    positions: &[IVec3JsonKey],
+    kind: MappingKind,
+    id: CDDAIdentifier,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<Vec<CellChange>, EditMapError> {
+    let other_kind = match &kind {
+        MappingKind::Terrain => MappingKind::Furniture,
+        MappingKind::Furniture => MappingKind::Terrain,
+        kind => return Err(EditMapError::UnsupportedMappingKind(kind.clone())),
+    };
+
+    let resolved = positions
+        .iter()
+        .map(|p| resolve_cell(project, p.0))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mapgen_value = MapGenValue::String(id.clone());
+    let mut changes = Vec::with_capacity(resolved.len());
+
+    for (position, (z, map_coordinates, cell_coordinates)) in
+        positions.iter().zip(resolved)
+    {
+        let map_data = get_map_data_mut(project, z, &map_coordinates);
+
+        let current = map_data
+            .cells
+            .get(&cell_coordinates)
+            .map(|c| c.character)
+            .unwrap_or(' ');
+        let other_value = map_data
+            .get_local_mapgen_values(&other_kind)
+            .remove(&current);
+
+        let character = find_paint_character(
+            map_data,
+            &kind,
+            &mapgen_value,
+            &other_kind,
+            other_value.as_ref(),
+            json_data,
+        )
+        .ok_or(EditMapError::NoFreeCharacter(id.clone()))?;
+
+        map_data.cells.insert(cell_coordinates, Cell { character });
+        changes.push(CellChange {
+            coordinates: position.clone(),
+            character,
+        });
+    }
+
+    project.dirty = true;
+
+    Ok(changes)
+}
+
+/// The global coordinates of the cells on the line from `from` to `to`
+pub fn line_positions(
+    from: IVec3,
+    to: IVec3,
+) -> Result<Vec<IVec3JsonKey>, EditMapError> {
+    if from.z != to.z {
+        return Err(EditMapError::DifferentZLevels(from.z, to.z));
+    }
+
+    Ok(bresenham_line(from.x, from.y, to.x, to.y)
+        .into_iter()
+        .map(|(x, y)| IVec3JsonKey(IVec3::new(x, y, from.z)))
+        .collect())
+}
+
+/// The global coordinates of the cells of the rectangle spanned by `from` and
+/// `to`. If the rectangle is not `filled`, only its outline is returned.
+pub fn rect_positions(
+    from: IVec3,
+    to: IVec3,
+    filled: bool,
+) -> Result<Vec<IVec3JsonKey>, EditMapError> {
+    if from.z != to.z {
+        return Err(EditMapError::DifferentZLevels(from.z, to.z));
+    }
+
+    let min = from.min(to);
+    let max = from.max(to);
+    let mut positions = vec![];
+
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            let is_outline =
+                x == min.x || x == max.x || y == min.y || y == max.y;
+
+            if filled || is_outline {
+                positions.push(IVec3JsonKey(IVec3::new(x, y, from.z)));
+            }
+        }
+    }
+
+    Ok(positions)
+}
+
+/// Resolves the parameters and switches of the `value` with the parameters of
+/// the `map_data`, so values which are written differently but place the same
+/// terrain or furniture are equal. Distributions pick a random id, so they are
+/// kept and only equal the same distribution.
+fn resolve_mapgen_value(value: MapGenValue, map_data: &MapData) -> MapGenValue {
+    if matches!(value, MapGenValue::Distribution(_)) {
+        return value;
+    }
+
+    match value.get_identifier(&map_data.calculated_parameters) {
+        Ok(id) => MapGenValue::String(id),
+        Err(_) => value,
+    }
+}
+
+/// The global coordinates of the cells which are connected to the `start` and
+/// resolve to the same terrain or furniture as it. The fill spreads across
+/// the borders of the maps, but never leaves the z-level of the `start`.
+pub fn flood_fill_positions(
+    project: &Project,
+    start: IVec3,
+    kind: &MappingKind,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<Vec<IVec3JsonKey>, EditMapError> {
+    // The resolved values are cached per map, since resolving them through
+    // the palettes is expensive
+    let mut resolved_values = HashMap::new();
+    let mut resolve_value = |position: IVec3| {
+        let (z, map_coordinates, cell_coordinates) =
+            resolve_cell(project, position).ok()?;
+        let map_data = project.maps.get(&z)?.maps.get(&map_coordinates)?;
+        let character = map_data
+            .cells
+            .get(&cell_coordinates)
+            .map(|c| c.character)
+            .unwrap_or(' ');
+
+        let value = resolved_values
+            .entry((map_coordinates, character))
+            .or_insert_with(|| {
+                map_data
+                    .get_mapgen_value(kind, &character, json_data)
+                    .map(|v| resolve_mapgen_value(v, map_data))
+            })
+            .clone();
+
+        Some(value)
+    };
+
+    resolve_cell(project, start)?;
+    // Safe since the start was resolved above
+    let target = resolve_value(start).unwrap();
+
+    let mut visited = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    let mut positions = vec![];
+
+    while let Some(position) = queue.pop_front() {
+        positions.push(IVec3JsonKey(position));
+
+        for offset in [IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y] {
+            let neighbor = position + offset;
+
+            if visited.contains(&neighbor) {
+                continue;
+            }
+
+            if resolve_value(neighbor).is_some_and(|v| v == target) {
+                visited.insert(neighbor);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    Ok(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::program_data::ProjectType;
+
+    /// A map whose cells are all the `character`, with the `mappings` of the
+    /// characters to terrain
+    fn terrain_map(
+        character: char,
+        mappings: Vec<(char, MapGenValue)>,
+    ) -> MapData {
+        let mut map_data = MapData::default();
+
+        for cell in map_data.cells.values_mut() {
+            cell.character = character;
+        }
+
+        for (character, value) in mappings {
+            map_data.set_local_mapgen_value(
+                MappingKind::Terrain,
+                character,
+                value,
+            );
+        }
+
+        map_data
+    }
+
+    fn map_editor_project(maps: Vec<(MapCoordinates, MapData)>) -> Project {
+        let mut project = Project::new(
+            "test_map_editor".into(),
+            DEFAULT_MAP_DATA_SIZE,
+            ProjectType::MapEditor(ProjectSaveState::Unsaved),
+        );
+        project.maps.insert(
+            0,
+            MapDataCollection {
+                maps: HashMap::from_iter(maps),
+            },
+        );
+
+        project
+    }
+
+    #[test]
+    fn test_new_place_entry_uses_defaults() {
+        let mut project = map_editor_project(vec![
+            (UVec2::ZERO, MapData::default()),
+            (UVec2::new(1, 0), MapData::default()),
+        ]);
+        let defaults = PlaceDefaults {
+            chance: 50,
+            repeat: NumberOrRange::Range((1, 3)),
+            item_group: Some("trash".into()),
+            monster_group: None,
+        };
+
+        let entry = new_place_entry(
+            &mut project,
+            &defaults,
+            NewPlaceEntryKind::Items,
+            0,
+            NumberOrRange::Number(25),
+            NumberOrRange::Range((1, 2)),
+            None,
+        )
+        .unwrap();
+
+        // The entry is relative to the second map
+        assert_eq!(
+            entry,
+            json!({
+                "item": "trash",
+                "x": 1,
+                "y": [ 1, 2 ],
+                "chance": 50,
+                "repeat": [ 1, 3 ]
+            })
+        );
+
+        let map_data = &project.maps[&0].maps[&UVec2::new(1, 0)];
+        let places = &map_data.place[&MappingKind::ItemGroups];
+        assert_eq!(places.len(), 1);
+        assert_eq!(places[0].chance, 50);
+        assert!(project.maps[&0].maps[&UVec2::ZERO].place.is_empty());
+        assert!(project.dirty);
+
+        let result = new_place_entry(
+            &mut project,
+            &defaults,
+            NewPlaceEntryKind::Monsters,
+            0,
+            NumberOrRange::Number(0),
+            NumberOrRange::Number(0),
+            None,
+        );
+        assert!(matches!(result, Err(NewPlaceEntryError::NoGroup(_))));
+    }
+
+    #[test]
+    fn test_flood_fill_compares_resolved_ids() {
+        let json_data = DeserializedCDDAJsonData::default();
+
+        let mut map_data = terrain_map(
+            'c',
+            vec![
+                ('a', MapGenValue::String("t_floor".into())),
+                (
+                    'b',
+                    MapGenValue::Param {
+                        param: "floor".into(),
+                        fallback: None,
+                    },
+                ),
+                ('c', MapGenValue::String("t_wall".into())),
+            ],
+        );
+        map_data
+            .calculated_parameters
+            .insert("floor".into(), "t_floor".into());
+        map_data
+            .cells
+            .insert(UVec2::new(0, 0), Cell { character: 'a' });
+        map_data
+            .cells
+            .insert(UVec2::new(1, 0), Cell { character: 'b' });
+
+        let project = map_editor_project(vec![(UVec2::ZERO, map_data)]);
+
+        let positions = flood_fill_positions(
+            &project,
+            IVec3::ZERO,
+            &MappingKind::Terrain,
+            &json_data,
+        )
+        .unwrap();
+
+        assert_eq!(
+            positions,
+            vec![
+                IVec3JsonKey(IVec3::new(0, 0, 0)),
+                IVec3JsonKey(IVec3::new(1, 0, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flood_fill_spreads_across_maps() {
+        let json_data = DeserializedCDDAJsonData::default();
+
+        let floor = MapGenValue::String("t_floor".into());
+        let project = map_editor_project(vec![
+            (UVec2::ZERO, terrain_map('a', vec![('a', floor.clone())])),
+            (UVec2::new(1, 0), terrain_map('b', vec![('b', floor)])),
+        ]);
+
+        let positions = flood_fill_positions(
+            &project,
+            IVec3::ZERO,
+            &MappingKind::Terrain,
+            &json_data,
+        )
+        .unwrap();
+
+        let cells =
+            (DEFAULT_MAP_DATA_SIZE.x * DEFAULT_MAP_DATA_SIZE.y) as usize;
+        assert_eq!(positions.len(), 2 * cells);
+        assert!(positions.contains(&IVec3JsonKey(IVec3::new(47, 23, 0))));
+    }
+
+    #[tokio::test]
+    async fn test_save_keeps_every_mapping() {
+        let saved_map = SavedMap {
+            z: 0,
+            coordinates: UVec2::ZERO,
+            object: json!({
+                "fill_ter": "t_grass",
+                "rows": [ ".I", "M." ],
+                "terrain": { ".": "t_floor" },
+                "items": { "I": { "item": "office", "chance": 50 } },
+                "monsters": { "M": { "monster": "mon_zombie", "chance": 10 } },
+                "place_items": [
+                    { "item": "trash", "x": 0, "y": [ 0, 1 ], "chance": 75 }
+                ],
+                "set": [ { "point": "terrain", "id": "t_dirt", "x": 1, "y": 1 } ]
+            }),
+        };
+
+        let path = std::env::temp_dir().join("test_map_editor_save.json");
+        let save_state = ProjectSaveState::Saved { path: path.clone() };
+        let saver = MapEditorSaver { path: path.clone() };
+
+        let mut project = Project::new(
+            "test_map_editor_save".into(),
+            DEFAULT_MAP_DATA_SIZE,
+            ProjectType::MapEditor(save_state.clone()),
+        );
+        project.maps.insert(
+            0,
+            MapDataCollection {
+                maps: HashMap::from([(
+                    UVec2::ZERO,
+                    saved_map.into_map_data().unwrap(),
+                )]),
+            },
+        );
+
+        saver.save(&project).await.unwrap();
+        let first_save = fs::read_to_string(&path).unwrap();
+
+        project.maps = MapEditorLoader { save_state }.load().await.unwrap();
+
+        saver.save(&project).await.unwrap();
+        let second_save = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(first_save, second_save);
+
+        let save_file: MapEditorSaveFile =
+            serde_json::from_str(&second_save).unwrap();
+        let object = &save_file.maps[0].object;
+
+        assert_eq!(object["fill_ter"], "t_grass");
+        assert_eq!(object["terrain"]["."], "t_floor");
+        assert_eq!(object["items"]["I"]["item"], "office");
+        assert_eq!(object["items"]["I"]["chance"], 50);
+        assert_eq!(object["monsters"]["M"]["monster"], "mon_zombie");
+        assert_eq!(object["place_items"][0]["item"], "trash");
+        assert_eq!(object["place_items"][0]["y"], json!([0, 1]));
+        assert_eq!(object["place_items"][0]["chance"], 75);
+        assert_eq!(object["set"][0]["point"], "terrain");
+        assert_eq!(object["set"][0]["id"], "t_dirt");
+    }
+}
@@ -0,0 +1,217 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::map::{MappingKind, DEFAULT_MAP_DATA_SIZE};
+use crate::features::map_editor::{
+    flood_fill_positions, line_positions, paint_cells, rect_positions,
+    CellChange, EditMapError,
+};
+use crate::features::program_data::{Project, ZLevel};
+use crate::impl_serialize_for_error;
+use crate::util::IVec3JsonKey;
+use cdda_lib::types::{CDDAIdentifier, MapGenValue};
+use glam::IVec3;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
+
+fn default_filled() -> bool {
+    true
+}
+
+/// A single step of an edit script. Every operation paints the terrain or
+/// furniture `id` onto the cells it selects.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum EditOperation {
+    /// Replaces every cell which resolves to `from` with `to`, optionally
+    /// only on a single z-level
+    ReplaceId {
+        mapping_kind: MappingKind,
+        from: CDDAIdentifier,
+        to: CDDAIdentifier,
+        z: Option<ZLevel>,
+    },
+    /// Paints each cell of the rectangle from `from` to `to` with a `chance`
+    /// in percent
+    Scatter {
+        mapping_kind: MappingKind,
+        id: CDDAIdentifier,
+        from: IVec3JsonKey,
+        to: IVec3JsonKey,
+        chance: u32,
+        seed: Option<u64>,
+    },
+    Rect {
+        mapping_kind: MappingKind,
+        id: CDDAIdentifier,
+        from: IVec3JsonKey,
+        to: IVec3JsonKey,
+        #[serde(default = "default_filled")]
+        filled: bool,
+    },
+    Line {
+        mapping_kind: MappingKind,
+        id: CDDAIdentifier,
+        from: IVec3JsonKey,
+        to: IVec3JsonKey,
+    },
+    FloodFill {
+        mapping_kind: MappingKind,
+        id: CDDAIdentifier,
+        start: IVec3JsonKey,
+    },
+}
+
+#[derive(Debug, Error)]
+#[error("Operation {index} of the edit script failed, {source}")]
+pub struct EditScriptError {
+    pub index: usize,
+    pub source: EditMapError,
+}
+
+impl_serialize_for_error!(EditScriptError);
+
+/// The global coordinates of every cell which resolves to the `id`
+fn replace_id_positions(
+    project: &Project,
+    mapping_kind: &MappingKind,
+    id: &CDDAIdentifier,
+    z: Option<ZLevel>,
+    json_data: &DeserializedCDDAJsonData,
+) -> Vec<IVec3JsonKey> {
+    let mapgen_value = MapGenValue::String(id.clone());
+    let mut positions = vec![];
+
+    for (map_z, collection) in project.maps.iter() {
+        if z.is_some_and(|z| z != *map_z) {
+            continue;
+        }
+
+        for (map_coordinates, map_data) in collection.maps.iter() {
+            let origin = *map_coordinates * DEFAULT_MAP_DATA_SIZE;
+
+            for (cell_coordinates, cell) in map_data.cells.iter() {
+                let value = map_data.get_mapgen_value(
+                    mapping_kind,
+                    &cell.character,
+                    json_data,
+                );
+
+                if value.as_ref() != Some(&mapgen_value) {
+                    continue;
+                }
+
+                let position = (origin + *cell_coordinates).as_ivec2();
+                positions.push(IVec3JsonKey(position.extend(*map_z)));
+            }
+        }
+    }
+
+    positions
+}
+
+fn scatter_positions(
+    from: IVec3,
+    to: IVec3,
+    chance: u32,
+    seed: Option<u64>,
+) -> Result<Vec<IVec3JsonKey>, EditMapError> {
+    let mut rng = match seed {
+        None => StdRng::from_os_rng(),
+        Some(seed) => StdRng::seed_from_u64(seed),
+    };
+
+    Ok(rect_positions(from, to, true)?
+        .into_iter()
+        .filter(|_| rng.random_range(0..100) < chance)
+        .collect())
+}
+
+fn run_operation(
+    project: &mut Project,
+    operation: &EditOperation,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<Vec<CellChange>, EditMapError> {
+    let (mapping_kind, id, positions) = match operation {
+        EditOperation::ReplaceId {
+            mapping_kind,
+            from,
+            to,
+            z,
+        } => (
+            mapping_kind,
+            to,
+            replace_id_positions(project, mapping_kind, from, *z, json_data),
+        ),
+        EditOperation::Scatter {
+            mapping_kind,
+            id,
+            from,
+            to,
+            chance,
+            seed,
+        } => (
+            mapping_kind,
+            id,
+            scatter_positions(from.0, to.0, *chance, *seed)?,
+        ),
+        EditOperation::Rect {
+            mapping_kind,
+            id,
+            from,
+            to,
+            filled,
+        } => (mapping_kind, id, rect_positions(from.0, to.0, *filled)?),
+        EditOperation::Line {
+            mapping_kind,
+            id,
+            from,
+            to,
+        } => (mapping_kind, id, line_positions(from.0, to.0)?),
+        EditOperation::FloodFill {
+            mapping_kind,
+            id,
+            start,
+        } => (
+            mapping_kind,
+            id,
+            flood_fill_positions(project, start.0, mapping_kind, json_data)?,
+        ),
+    };
+
+    paint_cells(
+        project,
+        &positions,
+        mapping_kind.clone(),
+        id.clone(),
+        json_data,
+    )
+}
+
+/// Runs the `operations` in order against the `project`. If any operation
+/// fails, the maps of the project are left as they were before.
+///
+/// Returns every changed cell in the order the changes were made.
+pub fn run_edit_script(
+    project: &mut Project,
+    operations: &[EditOperation],
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<Vec<CellChange>, EditScriptError> {
+    let previous_maps = project.maps.clone();
+    let previous_dirty = project.dirty;
+    let mut changes = vec![];
+
+    for (index, operation) in operations.iter().enumerate() {
+        match run_operation(project, operation, json_data) {
+            Ok(operation_changes) => changes.extend(operation_changes),
+            Err(source) => {
+                project.maps = previous_maps;
+                project.dirty = previous_dirty;
+
+                return Err(EditScriptError { index, source });
+            },
+        }
+    }
+
+    Ok(changes)
+}
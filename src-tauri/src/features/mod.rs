@@ -1,6 +1,27 @@
+pub mod analysis;
+pub mod archive;
+pub mod catalog;
+pub mod connect_groups;
+pub mod demo;
+pub mod diagnostics;
+pub mod field;
+pub mod jobs;
+pub mod legend;
+pub mod map_editor;
+pub mod mods;
+pub mod overlay;
+pub mod palette;
 pub mod program_data;
+pub mod raw_json;
+pub mod refactor;
+pub mod search;
+pub mod third_party;
+pub mod thumbnail;
 pub mod tileset;
 pub mod toast;
+pub mod validation;
+pub mod vehicle;
 pub mod viewer;
+pub mod watcher;
 
 pub mod map;
@@ -0,0 +1,179 @@
+use crate::features::mods::{
+    find_mods, load_project_mods, mod_directories, resolve_load_order, CDDAMod,
+    LoadProjectModsError, ModLoadOrderError,
+};
+use crate::features::program_data::changes::{
+    EditorDataChanges, EditorDataScope,
+};
+use crate::features::program_data::io::ProgramDataSaver;
+use crate::features::program_data::{CDDAPathError, EditorData};
+use crate::impl_serialize_for_error;
+use crate::util::{
+    get_current_project, get_current_project_mut, GetCurrentProjectError, Save,
+};
+use cdda_lib::types::CDDAIdentifier;
+use serde::{Serialize, Serializer};
+use std::ops::Deref;
+use std::path::PathBuf;
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, State};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModListEntry {
+    #[serde(flatten)]
+    pub cdda_mod: CDDAMod,
+
+    /// Whether the opened project activated the mod itself. Mods which are
+    /// only loaded as a dependency of an active mod are not active.
+    pub active: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum GetModsError {
+    #[error(transparent)]
+    CDDAPathError(#[from] CDDAPathError),
+
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+}
+
+impl_serialize_for_error!(GetModsError);
+
+/// Lists the mods of the CDDA installation and the user mod directories
+/// together with whether the opened project uses them
+#[tauri::command]
+pub async fn get_mods(
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<Vec<ModListEntry>, GetModsError> {
+    let editor_data_lock = editor_data.lock().await;
+    let project = get_current_project(&editor_data_lock)?;
+    let directories = mod_directories(&editor_data_lock.config)?;
+
+    Ok(find_mods(&directories)
+        .into_values()
+        .map(|cdda_mod| ModListEntry {
+            active: project.active_mods.contains(&cdda_mod.info.id),
+            cdda_mod,
+        })
+        .collect())
+}
+
+#[derive(Debug, Error)]
+pub enum SetModActiveError {
+    #[error(transparent)]
+    CDDAPathError(#[from] CDDAPathError),
+
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    ModLoadOrderError(#[from] ModLoadOrderError),
+
+    #[error(transparent)]
+    LoadProjectModsError(#[from] LoadProjectModsError),
+
+    #[error("The mod {0} is obsolete and can not be activated anymore")]
+    ObsoleteMod(CDDAIdentifier),
+
+    #[error("Failed to save the project: {0}")]
+    SaveFailed(String),
+}
+
+impl_serialize_for_error!(SetModActiveError);
+
+/// Activates or deactivates the mod `id` for the opened project and loads the
+/// CDDA data again with the new mods
+#[tauri::command]
+pub async fn set_mod_active(
+    id: CDDAIdentifier,
+    active: bool,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<(), SetModActiveError> {
+    {
+        let mut editor_data_lock = editor_data.lock().await;
+        let directories = mod_directories(&editor_data_lock.config)?;
+        let available = find_mods(&directories);
+
+        let project = get_current_project_mut(&mut editor_data_lock)?;
+        let mut active_mods = project.active_mods.clone();
+
+        match active {
+            true => {
+                let cdda_mod = available
+                    .get(&id)
+                    .ok_or_else(|| ModLoadOrderError::MissingMod(id.clone()))?;
+
+                if cdda_mod.info.obsolete {
+                    return Err(SetModActiveError::ObsoleteMod(id));
+                }
+
+                if !active_mods.contains(&id) {
+                    active_mods.push(id);
+                }
+            },
+            false => active_mods.retain(|m| m != &id),
+        }
+
+        // Fail before the project is changed if the dependencies of the new
+        // mods are missing
+        resolve_load_order(&available, &active_mods)?;
+        project.active_mods = active_mods;
+
+        let saver = ProgramDataSaver {
+            path: editor_data_lock.config.config_path.clone(),
+        };
+
+        saver
+            .save(editor_data_lock.deref())
+            .await
+            .map_err(|e| SetModActiveError::SaveFailed(e.to_string()))?;
+    }
+
+    load_project_mods(&app).await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum SetUserModPathsError {
+    #[error(transparent)]
+    LoadProjectModsError(#[from] LoadProjectModsError),
+
+    #[error("Failed to save the editor data: {0}")]
+    SaveFailed(String),
+}
+
+impl_serialize_for_error!(SetUserModPathsError);
+
+/// Changes the directories with mods which are not part of the CDDA
+/// installation
+#[tauri::command]
+pub async fn set_user_mod_paths(
+    paths: Vec<PathBuf>,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+) -> Result<(), SetUserModPathsError> {
+    {
+        let mut editor_data_lock = editor_data.lock().await;
+        editor_data_lock.config.user_mod_paths = paths;
+
+        let saver = ProgramDataSaver {
+            path: editor_data_lock.config.config_path.clone(),
+        };
+
+        saver
+            .save(editor_data_lock.deref())
+            .await
+            .map_err(|e| SetUserModPathsError::SaveFailed(e.to_string()))?;
+    }
+
+    editor_data_changes.notify(&app, &[EditorDataScope::Config]);
+
+    // Mods of the opened project which were missing before can be loaded now
+    load_project_mods(&app).await?;
+
+    Ok(())
+}
@@ -0,0 +1,412 @@
+pub(crate) mod handlers;
+
+use crate::data::cache::get_cdda_data_cache_path;
+use crate::data::io::{load_cdda_json_data, DeserializedCDDAJsonData};
+use crate::events::{EditorEvent, EmitEvent, EventPayload};
+use crate::features::jobs::Jobs;
+use crate::features::program_data::generations::ProjectGenerations;
+use crate::features::program_data::{CDDAPathError, EditorConfig, EditorData};
+use crate::features::viewer::cache::RenderCache;
+use crate::features::watcher::cdda_data::CDDADataWatcher;
+use cdda_lib::types::CDDAIdentifier;
+use indexmap::IndexMap;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+
+/// The mod of the core data, which is always loaded from the json data path
+/// of the editor config and is never loaded as a mod
+pub const CORE_MOD_ID: &str = "dda";
+
+/// The directory of the CDDA installation the bundled mods are in
+pub const DEFAULT_CDDA_MODS_PATH: &str = "data/mods";
+
+const MOD_INFO_FILE: &str = "modinfo.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CDDAModInfo {
+    pub id: CDDAIdentifier,
+    pub name: String,
+
+    #[serde(default)]
+    pub authors: Vec<String>,
+
+    #[serde(default)]
+    pub description: Option<String>,
+
+    #[serde(default)]
+    pub category: Option<String>,
+
+    #[serde(default)]
+    pub dependencies: Vec<CDDAIdentifier>,
+
+    /// Directory of the json files relative to the directory of the mod info
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+
+    /// Obsolete mods are no longer maintained and can not be activated
+    /// anymore. Projects which already use them keep loading them.
+    #[serde(default)]
+    pub obsolete: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum ModInfoEntry {
+    #[serde(rename = "MOD_INFO")]
+    ModInfo(CDDAModInfo),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CDDAMod {
+    #[serde(flatten)]
+    pub info: CDDAModInfo,
+
+    /// The directory the json files of the mod are loaded from
+    pub json_path: PathBuf,
+}
+
+/// The directories which contain the mods that can be activated, the bundled
+/// mods of the CDDA installation first and the user mod directories after
+/// them
+pub fn mod_directories(
+    config: &EditorConfig,
+) -> Result<Vec<PathBuf>, CDDAPathError> {
+    let mut directories =
+        vec![config.get_cdda_path()?.join(DEFAULT_CDDA_MODS_PATH)];
+    directories.extend(config.user_mod_paths.iter().cloned());
+
+    Ok(directories)
+}
+
+fn read_mod_info(path: &Path) -> Result<Option<CDDAModInfo>, anyhow::Error> {
+    let entries: Vec<ModInfoEntry> = serde_json::from_slice(&fs::read(path)?)?;
+
+    Ok(entries.into_iter().find_map(|e| match e {
+        ModInfoEntry::ModInfo(info) => Some(info),
+        ModInfoEntry::Other => None,
+    }))
+}
+
+/// Finds the mods in every subdirectory of the `directories`. A mod in a later
+/// directory replaces a mod with the same id in an earlier one, so user mods
+/// can replace the bundled mods.
+pub fn find_mods(directories: &[PathBuf]) -> IndexMap<CDDAIdentifier, CDDAMod> {
+    let mut mods = IndexMap::new();
+
+    for directory in directories {
+        let entries = match fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(e) => {
+                info!("Skipping mod directory {}: {}", directory.display(), e);
+                continue;
+            },
+        };
+
+        for entry in entries.flatten() {
+            let mod_path = entry.path();
+            let mod_info_path = mod_path.join(MOD_INFO_FILE);
+
+            if !mod_info_path.is_file() {
+                continue;
+            }
+
+            let info = match read_mod_info(&mod_info_path) {
+                Ok(Some(info)) => info,
+                Ok(None) => {
+                    warn!("{} contains no mod info", mod_info_path.display());
+                    continue;
+                },
+                Err(e) => {
+                    warn!("Failed to read {}: {}", mod_info_path.display(), e);
+                    continue;
+                },
+            };
+
+            let json_path = match &info.path {
+                None => mod_path.clone(),
+                Some(path) => mod_path.join(path),
+            };
+
+            mods.insert(info.id.clone(), CDDAMod { info, json_path });
+        }
+    }
+
+    mods.sort_by(|_, a, _, b| a.info.name.cmp(&b.info.name));
+    mods
+}
+
+#[derive(Debug, Error)]
+pub enum ModLoadOrderError {
+    #[error("The mod {0} does not exist")]
+    MissingMod(CDDAIdentifier),
+
+    #[error("The mod {mod_id} depends on {dependency}, which does not exist")]
+    MissingDependency {
+        mod_id: CDDAIdentifier,
+        dependency: CDDAIdentifier,
+    },
+
+    #[error("The dependencies of the mod {0} depend on it again")]
+    DependencyCycle(CDDAIdentifier),
+}
+
+/// Sorts the `active` mods and the mods they depend on, so every mod is loaded
+/// after its dependencies. Apart from that, the mods are loaded in the order
+/// they were activated in.
+pub fn resolve_load_order(
+    available: &IndexMap<CDDAIdentifier, CDDAMod>,
+    active: &[CDDAIdentifier],
+) -> Result<Vec<CDDAMod>, ModLoadOrderError> {
+    fn visit(
+        available: &IndexMap<CDDAIdentifier, CDDAMod>,
+        id: &CDDAIdentifier,
+        visiting: &mut HashSet<CDDAIdentifier>,
+        load_order: &mut Vec<CDDAMod>,
+    ) -> Result<(), ModLoadOrderError> {
+        if id.0 == CORE_MOD_ID || load_order.iter().any(|m| &m.info.id == id) {
+            return Ok(());
+        }
+
+        if !visiting.insert(id.clone()) {
+            return Err(ModLoadOrderError::DependencyCycle(id.clone()));
+        }
+
+        let cdda_mod = available
+            .get(id)
+            .ok_or_else(|| ModLoadOrderError::MissingMod(id.clone()))?;
+
+        for dependency in cdda_mod.info.dependencies.iter() {
+            visit(available, dependency, visiting, load_order).map_err(
+                |e| match e {
+                    ModLoadOrderError::MissingMod(missing)
+                        if &missing == dependency =>
+                    {
+                        ModLoadOrderError::MissingDependency {
+                            mod_id: id.clone(),
+                            dependency: missing,
+                        }
+                    },
+                    e => e,
+                },
+            )?;
+        }
+
+        visiting.remove(id);
+        load_order.push(cdda_mod.clone());
+
+        Ok(())
+    }
+
+    let mut load_order = vec![];
+    let mut visiting = HashSet::new();
+
+    for id in active {
+        visit(available, id, &mut visiting, &mut load_order)?;
+    }
+
+    Ok(load_order)
+}
+
+/// The mods the opened project uses, in the order they have to be loaded in.
+/// Without a CDDA installation or an opened project, no mods are used.
+pub fn opened_project_mods(
+    editor_data: &EditorData,
+) -> Result<Vec<CDDAMod>, ModLoadOrderError> {
+    let Ok(directories) = mod_directories(&editor_data.config) else {
+        return Ok(vec![]);
+    };
+
+    let Some(project) = editor_data
+        .opened_project
+        .as_ref()
+        .and_then(|name| editor_data.loaded_projects.get(name))
+    else {
+        return Ok(vec![]);
+    };
+
+    let load_order =
+        resolve_load_order(&find_mods(&directories), &project.active_mods)?;
+
+    for cdda_mod in load_order.iter().filter(|m| m.info.obsolete) {
+        warn!("Loading obsolete mod {}", cdda_mod.info.id);
+    }
+
+    Ok(load_order)
+}
+
+/// The directories of the CDDA data which is loaded with the `mods`, which
+/// are watched for changes
+pub fn cdda_data_paths(
+    cdda_path: &Path,
+    json_data_path: &Path,
+    mods: &[CDDAMod],
+) -> Vec<PathBuf> {
+    let mut paths = vec![cdda_path.join(json_data_path)];
+    paths.extend(mods.iter().map(|m| m.json_path.clone()));
+    paths
+}
+
+/// Sent after the CDDA data was loaded again with the mods of the opened
+/// project
+#[derive(Debug, Clone, Serialize)]
+pub struct ModsLoadedEvent {
+    pub mods: Vec<CDDAIdentifier>,
+}
+
+impl EventPayload for ModsLoadedEvent {
+    const EVENT: EditorEvent = EditorEvent::ModsLoaded;
+}
+
+#[derive(Debug, Error)]
+pub enum LoadProjectModsError {
+    #[error(transparent)]
+    ModLoadOrderError(#[from] ModLoadOrderError),
+
+    #[error("Failed to load the CDDA data: {0}")]
+    LoadFailed(anyhow::Error),
+}
+
+/// Loads the CDDA data again if the mods of the opened project are not the
+/// mods the loaded data was loaded with. The parameters of all loaded projects
+/// are recalculated with the new data.
+pub async fn load_project_mods(
+    app: &AppHandle,
+) -> Result<(), LoadProjectModsError> {
+    let json_data = app.state::<Mutex<Option<DeserializedCDDAJsonData>>>();
+    let editor_data = app.state::<Mutex<EditorData>>();
+
+    let mut json_data_lock = json_data.lock().await;
+    let mut editor_data_lock = editor_data.lock().await;
+
+    let Some(cdda_path) = editor_data_lock.config.cdda_path.clone() else {
+        return Ok(());
+    };
+
+    let Some(loaded_data) = json_data_lock.as_ref() else {
+        return Ok(());
+    };
+
+    let mods = opened_project_mods(&editor_data_lock)?;
+    let mod_ids: Vec<CDDAIdentifier> =
+        mods.iter().map(|m| m.info.id.clone()).collect();
+
+    if loaded_data.mods == mod_ids {
+        return Ok(());
+    }
+
+    info!("Loading CDDA data with the mods {:?}", mod_ids);
+
+    let json_data_path = editor_data_lock.config.json_data_path.clone();
+    let jobs = app.state::<Jobs>();
+
+    let data = load_cdda_json_data(
+        &cdda_path,
+        &json_data_path,
+        mods.clone(),
+        Some(get_cdda_data_cache_path(&editor_data_lock.config)),
+        Some(jobs.start(app, "Loading mods")),
+    )
+    .await
+    .map_err(LoadProjectModsError::LoadFailed)?;
+
+    let project_generations = app.state::<ProjectGenerations>();
+
+    for project in editor_data_lock.loaded_projects.values_mut() {
+        project_generations.advance(&project.name);
+
+        for (_, collection) in project.maps.iter_mut() {
+            if let Err(e) = collection.calculate_parameters(&data.palettes) {
+                let name = &project.name;
+                warn!("Failed to recalculate parameters of {}: {}", name, e);
+            }
+        }
+    }
+
+    json_data_lock.replace(data);
+    app.state::<RenderCache>().invalidate_all();
+    app.state::<CDDADataWatcher>().watch(
+        app.clone(),
+        cdda_data_paths(&cdda_path, &json_data_path, &mods),
+    );
+
+    app.emit_event(ModsLoadedEvent { mods: mod_ids }).unwrap();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_mod(id: &str, dependencies: &[&str]) -> CDDAMod {
+        CDDAMod {
+            info: CDDAModInfo {
+                id: id.into(),
+                name: id.to_string(),
+                authors: vec![],
+                description: None,
+                category: None,
+                dependencies: dependencies
+                    .iter()
+                    .map(|d| (*d).into())
+                    .collect(),
+                path: None,
+                obsolete: false,
+            },
+            json_path: PathBuf::from(id),
+        }
+    }
+
+    fn available(mods: Vec<CDDAMod>) -> IndexMap<CDDAIdentifier, CDDAMod> {
+        mods.into_iter().map(|m| (m.info.id.clone(), m)).collect()
+    }
+
+    #[test]
+    fn test_dependencies_are_loaded_first() {
+        let available = available(vec![
+            test_mod("aftershock", &["dda", "magiclysm"]),
+            test_mod("magiclysm", &["dda"]),
+            test_mod("no_hope", &["dda"]),
+        ]);
+
+        let load_order = resolve_load_order(
+            &available,
+            &["no_hope".into(), "aftershock".into()],
+        )
+        .unwrap();
+
+        let ids: Vec<&str> =
+            load_order.iter().map(|m| m.info.id.0.as_str()).collect();
+        assert_eq!(ids, vec!["no_hope", "magiclysm", "aftershock"]);
+    }
+
+    #[test]
+    fn test_missing_dependency() {
+        let available = available(vec![test_mod("aftershock", &["magiclysm"])]);
+
+        let result = resolve_load_order(&available, &["aftershock".into()]);
+
+        assert!(matches!(
+            result,
+            Err(ModLoadOrderError::MissingDependency { .. })
+        ));
+    }
+
+    #[test]
+    fn test_dependency_cycle() {
+        let available =
+            available(vec![test_mod("a", &["b"]), test_mod("b", &["a"])]);
+
+        let result = resolve_load_order(&available, &["a".into()]);
+
+        assert!(matches!(result, Err(ModLoadOrderError::DependencyCycle(_))));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::overlay::items::{item_overlay, ItemDensity};
+use crate::features::overlay::monsters::{monster_overlay, MonsterDensity};
+use crate::features::overlay::{get_visible_cells, is_transparent};
+use crate::features::program_data::{
+    EditorData, MappedCDDAIdContainer, ZLevel,
+};
+use crate::util::{
+    get_current_project, get_json_data, CDDADataError, GetCurrentProjectError,
+    IVec3JsonKey,
+};
+use cdda_lib::types::CDDAIdentifier;
+use glam::IVec3;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+use tauri::async_runtime::Mutex;
+use tauri::State;
+use thiserror::Error;
+
+/// The default view distance of the player in CDDA
+pub const DEFAULT_SIGHT_RANGE: i32 = 60;
+
+#[derive(Debug, Error, Serialize)]
+pub enum GetOverlayError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error("No map is opened")]
+    NoMapOpened,
+}
+
+fn get_opaque_cells(
+    mapped_cdda_ids: &HashMap<ZLevel, MappedCDDAIdContainer>,
+    json_data: &DeserializedCDDAJsonData,
+    region: Option<&CDDAIdentifier>,
+) -> HashSet<IVec3> {
+    let mut opaque = HashSet::new();
+
+    for (_, container) in mapped_cdda_ids.iter() {
+        for (position, ids) in container.ids.iter() {
+            if !is_transparent(ids, json_data, region) {
+                opaque.insert(position.clone());
+            }
+        }
+    }
+
+    opaque
+}
+
+/// Returns for every cell of the opened project whether it can be seen
+/// through
+#[tauri::command]
+pub async fn get_transparency_overlay(
+    mapped_cdda_ids: State<
+        '_,
+        Mutex<Option<HashMap<ZLevel, MappedCDDAIdContainer>>>,
+    >,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<HashMap<IVec3JsonKey, bool>, GetOverlayError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let editor_data_lock = editor_data.lock().await;
+    let project = get_current_project(&editor_data_lock)?;
+
+    let mapped_cdda_ids_lock = mapped_cdda_ids.lock().await;
+    let mapped_cdda_ids = match mapped_cdda_ids_lock.deref() {
+        None => return Err(GetOverlayError::NoMapOpened),
+        Some(m) => m,
+    };
+
+    let mut overlay = HashMap::new();
+
+    for (_, container) in mapped_cdda_ids.iter() {
+        for (position, ids) in container.ids.iter() {
+            overlay.insert(
+                IVec3JsonKey(position.clone()),
+                is_transparent(ids, json_data, project.region.as_ref()),
+            );
+        }
+    }
+
+    Ok(overlay)
+}
+
+/// Returns all cells which can be seen from the cell at `from`
+#[tauri::command]
+pub async fn get_sightlines(
+    from: IVec3JsonKey,
+    range: Option<i32>,
+    mapped_cdda_ids: State<
+        '_,
+        Mutex<Option<HashMap<ZLevel, MappedCDDAIdContainer>>>,
+    >,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<Vec<IVec3JsonKey>, GetOverlayError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let editor_data_lock = editor_data.lock().await;
+    let project = get_current_project(&editor_data_lock)?;
+
+    let mapped_cdda_ids_lock = mapped_cdda_ids.lock().await;
+    let mapped_cdda_ids = match mapped_cdda_ids_lock.deref() {
+        None => return Err(GetOverlayError::NoMapOpened),
+        Some(m) => m,
+    };
+
+    let opaque =
+        get_opaque_cells(mapped_cdda_ids, json_data, project.region.as_ref());
+    let cells = match mapped_cdda_ids.get(&from.0.z) {
+        None => HashSet::new(),
+        Some(container) => container.ids.keys().cloned().collect(),
+    };
+
+    let visible = get_visible_cells(
+        from.0,
+        range.unwrap_or(DEFAULT_SIGHT_RANGE),
+        &cells,
+        &opaque,
+    );
+
+    Ok(visible.into_iter().map(IVec3JsonKey).collect())
+}
+
+/// Returns a summary of the items which can spawn on every cell of the opened
+/// project. Cells without any items are left out.
+#[tauri::command]
+pub async fn get_item_overlay(
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<HashMap<IVec3JsonKey, ItemDensity>, GetOverlayError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let editor_data_lock = editor_data.lock().await;
+    let project = get_current_project(&editor_data_lock)?;
+
+    Ok(item_overlay(project, json_data))
+}
+
+/// Returns a summary of the monsters which can spawn on every cell of the
+/// opened project, which can be drawn as a heatmap to balance spawns. Cells
+/// without any monsters are left out.
+#[tauri::command]
+pub async fn get_monster_overlay(
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<HashMap<IVec3JsonKey, MonsterDensity>, GetOverlayError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let editor_data_lock = editor_data.lock().await;
+    let project = get_current_project(&editor_data_lock)?;
+
+    Ok(monster_overlay(project, json_data))
+}
@@ -0,0 +1,181 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::map_data::{
+    MapGenItem, MapGenLoot, PlaceItems, PlaceLoot, ReferenceOrInPlace,
+};
+use crate::features::map::map_properties::{ItemsProperty, LootProperty};
+use crate::features::map::{MapData, MappingKind};
+use crate::features::overlay::{average, place_cells, push_unique};
+use crate::features::program_data::{MapDataCollection, Project};
+use crate::util::IVec3JsonKey;
+use cdda_lib::types::{CDDAIdentifier, NumberOrRange};
+use glam::UVec2;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Summary of the items which can spawn on a single tile
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ItemDensity {
+    /// The amount of item spawns which are expected every time the map is
+    /// generated. An item group counts as a single spawn.
+    pub expected_spawns: f32,
+    /// The item groups which can spawn on the tile
+    pub groups: Vec<CDDAIdentifier>,
+    /// The single items which can spawn on the tile
+    pub items: Vec<CDDAIdentifier>,
+    /// Amount of item groups which are defined in place and have no id
+    pub in_place_groups: u32,
+}
+
+/// The chance of a percent `chance`, which is 100 if it is not set
+fn percent(chance: &Option<NumberOrRange<u32>>) -> f32 {
+    chance
+        .as_ref()
+        .map_or(1., |c| (average(c) / 100.).clamp(0., 1.))
+}
+
+impl ItemDensity {
+    fn add_item(&mut self, item: &MapGenItem, scale: f32) {
+        let repeat = item.repeat.as_ref().map_or(1., average);
+        self.expected_spawns += scale * percent(&item.chance) * repeat;
+
+        match &item.item {
+            ReferenceOrInPlace::Reference(id) => {
+                push_unique(&mut self.groups, id)
+            },
+            ReferenceOrInPlace::InPlace(_) => self.in_place_groups += 1,
+        }
+    }
+
+    fn add_loot(&mut self, loot: &MapGenLoot, scale: f32) {
+        self.expected_spawns += scale * percent(&loot.chance);
+
+        if let Some(group) = &loot.group {
+            push_unique(&mut self.groups, group);
+        }
+
+        if let Some(item) = &loot.item {
+            push_unique(&mut self.items, item);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+            && self.items.is_empty()
+            && self.in_place_groups == 0
+    }
+}
+
+/// The items and loot which are mapped to the `character`
+fn character_density(
+    map_data: &MapData,
+    character: &char,
+    json_data: &DeserializedCDDAJsonData,
+) -> ItemDensity {
+    let mut density = ItemDensity::default();
+
+    if let Some(property) =
+        map_data.get_property(&MappingKind::ItemGroups, character, json_data)
+    {
+        if let Some(items) = property.downcast_ref::<ItemsProperty>() {
+            items
+                .items
+                .iter()
+                .for_each(|i| density.add_item(&i.data, 1.));
+        }
+    }
+
+    if let Some(property) =
+        map_data.get_property(&MappingKind::Loot, character, json_data)
+    {
+        if let Some(loot) = property.downcast_ref::<LootProperty>() {
+            loot.loot.iter().for_each(|l| density.add_loot(&l.data, 1.));
+        }
+    }
+
+    density
+}
+
+/// Summarizes the items of every tile of the `project` which can spawn any
+/// items through `items`, `loot`, `place_items` or `place_loot`. Entries which
+/// are placed on a range of coordinates are spread evenly over the range.
+pub fn item_overlay(
+    project: &Project,
+    json_data: &DeserializedCDDAJsonData,
+) -> HashMap<IVec3JsonKey, ItemDensity> {
+    let mut overlay: HashMap<IVec3JsonKey, ItemDensity> = HashMap::new();
+
+    for (z, collection) in project.maps.iter() {
+        for (map_coordinates, map_data) in collection.maps.iter() {
+            let mut local: HashMap<UVec2, ItemDensity> = HashMap::new();
+            let mut by_character: HashMap<char, ItemDensity> = HashMap::new();
+
+            for (coordinates, cell) in map_data.cells.iter() {
+                let density =
+                    by_character.entry(cell.character).or_insert_with(|| {
+                        character_density(map_data, &cell.character, json_data)
+                    });
+
+                if !density.is_empty() {
+                    local.insert(coordinates.clone(), density.clone());
+                }
+            }
+
+            let place_items = map_data
+                .place
+                .get(&MappingKind::ItemGroups)
+                .into_iter()
+                .flatten();
+
+            for entry in place_items {
+                let Some(place) = entry.inner.downcast_ref::<PlaceItems>()
+                else {
+                    continue;
+                };
+
+                for (coordinates, scale) in
+                    place_cells(entry, &map_data.map_size)
+                {
+                    let density = local.entry(coordinates).or_default();
+                    place
+                        .property
+                        .items
+                        .iter()
+                        .for_each(|i| density.add_item(&i.data, scale));
+                }
+            }
+
+            let place_loot =
+                map_data.place.get(&MappingKind::Loot).into_iter().flatten();
+
+            for entry in place_loot {
+                let Some(place) = entry.inner.downcast_ref::<PlaceLoot>()
+                else {
+                    continue;
+                };
+
+                for (coordinates, scale) in
+                    place_cells(entry, &map_data.map_size)
+                {
+                    let density = local.entry(coordinates).or_default();
+                    place
+                        .property
+                        .loot
+                        .iter()
+                        .for_each(|l| density.add_loot(&l.data, scale));
+                }
+            }
+
+            for (coordinates, density) in local {
+                let position = MapDataCollection::map_to_global_cell_coords(
+                    map_coordinates,
+                    &coordinates,
+                    *z,
+                );
+
+                overlay.insert(IVec3JsonKey(position), density);
+            }
+        }
+    }
+
+    overlay
+}
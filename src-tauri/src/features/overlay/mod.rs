@@ -0,0 +1,124 @@
+pub mod handlers;
+pub mod items;
+pub mod monsters;
+
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::map_data::PlaceOuter;
+use crate::features::map::MappedCDDAId;
+use crate::features::map::MappedCDDAIdsForTile;
+use crate::features::map::Place;
+use crate::util::bresenham_line;
+use cdda_lib::types::{CDDAIdentifier, NumberOrRange};
+use glam::{IVec3, UVec2};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+pub const TRANSPARENT_FLAG: &str = "TRANSPARENT";
+
+/// Returns true if the terrain or furniture with the `mapped_id` has the
+/// TRANSPARENT flag. Ids which could not be found are treated as transparent
+fn has_transparent_flag(
+    mapped_id: &MappedCDDAId,
+    json_data: &DeserializedCDDAJsonData,
+    region: Option<&CDDAIdentifier>,
+) -> bool {
+    let id =
+        json_data.resolve_region_setting(&mapped_id.tilesheet_id.id, region);
+
+    if let Some(terrain) = json_data.terrain.get(&id) {
+        return terrain.flags.iter().any(|f| f == TRANSPARENT_FLAG);
+    }
+
+    if let Some(furniture) = json_data.furniture.get(&id) {
+        return furniture.flags.iter().any(|f| f == TRANSPARENT_FLAG);
+    }
+
+    true
+}
+
+/// A tile can be seen through if both its terrain and its furniture are
+/// transparent in the `region` of the project
+pub fn is_transparent(
+    ids: &MappedCDDAIdsForTile,
+    json_data: &DeserializedCDDAJsonData,
+    region: Option<&CDDAIdentifier>,
+) -> bool {
+    let terrain_transparent = match &ids.terrain {
+        None => true,
+        Some(t) => has_transparent_flag(t, json_data, region),
+    };
+
+    let furniture_transparent = match &ids.furniture {
+        None => true,
+        Some(f) => has_transparent_flag(f, json_data, region),
+    };
+
+    terrain_transparent && furniture_transparent
+}
+
+/// Calculate all cells on the z-level of `from` which can be seen from `from`
+/// inside of `range`. A cell is visible when no opaque cell is between it and
+/// `from`. Opaque cells themselves are visible, since you can see the wall
+/// blocking your sight.
+pub fn get_visible_cells(
+    from: IVec3,
+    range: i32,
+    cells: &HashSet<IVec3>,
+    opaque: &HashSet<IVec3>,
+) -> HashSet<IVec3> {
+    let mut visible = HashSet::new();
+
+    for target in cells.iter().filter(|c| c.z == from.z) {
+        let distance = (target.x - from.x).abs().max((target.y - from.y).abs());
+
+        if distance > range {
+            continue;
+        }
+
+        let line = bresenham_line(from.x, from.y, target.x, target.y);
+        let is_blocked = line
+            .iter()
+            .skip(1)
+            .take(line.len().saturating_sub(2))
+            .any(|(x, y)| opaque.contains(&IVec3::new(*x, *y, from.z)));
+
+        if !is_blocked {
+            visible.insert(target.clone());
+        }
+    }
+
+    visible
+}
+
+fn average(range: &NumberOrRange<u32>) -> f32 {
+    (range.min() as f32 + range.max() as f32) / 2.
+}
+
+fn push_unique(ids: &mut Vec<CDDAIdentifier>, id: &CDDAIdentifier) {
+    if !ids.contains(id) {
+        ids.push(id.clone());
+    }
+}
+
+/// Every local cell the `entry` can be placed on together with the share of
+/// its spawns that end up on the cell
+fn place_cells(
+    entry: &PlaceOuter<Arc<dyn Place>>,
+    map_size: &UVec2,
+) -> Vec<(UVec2, f32)> {
+    let (min_x, max_x) = (entry.x.min().max(0), entry.x.max().max(0));
+    let (min_y, max_y) = (entry.y.min().max(0), entry.y.max().max(0));
+
+    let area = ((max_x - min_x + 1) * (max_y - min_y + 1)) as f32;
+    let repeat =
+        (entry.repeat.min().max(0) + entry.repeat.max().max(0)) as f32 / 2.;
+    let scale = (entry.chance as f32 / 100.).clamp(0., 1.) * repeat / area;
+
+    (min_y..=max_y)
+        .flat_map(|y| {
+            (min_x..=max_x).map(move |x| UVec2::new(x as u32, y as u32))
+        })
+        .filter(|c| c.x < map_size.x && c.y < map_size.y)
+        .map(|c| (c, scale))
+        .collect()
+}
@@ -0,0 +1,193 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::map_data::{MapGenMonsterType, MapGenMonsters, PlaceMonsters};
+use crate::data::GetIdentifier;
+use crate::features::map::map_properties::MonstersProperty;
+use crate::features::map::{MapData, MappingKind};
+use crate::features::overlay::{average, place_cells, push_unique};
+use crate::features::program_data::{MapDataCollection, Project};
+use crate::util::IVec3JsonKey;
+use cdda_lib::types::CDDAIdentifier;
+use glam::UVec2;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// CDDA spawns between 10 and 50 monsters of a group for every point of
+/// density
+const GROUP_SPAWNS_PER_DENSITY: f32 = 30.;
+
+/// Monster groups without a density use the density of the overmap terrain,
+/// which the editor does not know, so they are counted with this density
+const DEFAULT_GROUP_DENSITY: f32 = 1.;
+
+/// Summary of the monsters which can spawn on a single tile
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MonsterDensity {
+    /// The amount of monsters which are expected to spawn every time the map
+    /// is generated, without the pack sizes of the groups
+    pub expected_spawns: f32,
+    /// The monster groups which can spawn on the tile
+    pub groups: Vec<CDDAIdentifier>,
+    /// The single monsters which can spawn on the tile
+    pub monsters: Vec<CDDAIdentifier>,
+}
+
+impl MonsterDensity {
+    /// Adds a monster group spawn, which is placed with a one in `chance`
+    /// chance
+    fn add_group(
+        &mut self,
+        spawn: &MapGenMonsters,
+        map_data: &MapData,
+        scale: f32,
+    ) {
+        // `monster` holds the id of the group for group spawns
+        let value = match &spawn.id {
+            MapGenMonsterType::Monster { monster } => monster,
+            MapGenMonsterType::MonsterGroup { group } => group,
+        };
+
+        let chance = spawn.chance.as_ref().map_or(1., average).max(1.);
+        let density = spawn.density.unwrap_or(DEFAULT_GROUP_DENSITY);
+        self.expected_spawns +=
+            scale * density * GROUP_SPAWNS_PER_DENSITY / chance;
+
+        if let Ok(id) = value.get_identifier(&map_data.calculated_parameters) {
+            push_unique(&mut self.groups, &id);
+        }
+    }
+
+    /// Adds a single monster spawn, which is placed with a percent `chance`
+    fn add_monster(
+        &mut self,
+        spawn: &MapGenMonsters,
+        map_data: &MapData,
+        scale: f32,
+    ) {
+        let chance = spawn
+            .chance
+            .as_ref()
+            .map_or(1., |c| (average(c) / 100.).clamp(0., 1.));
+        let pack_size = spawn.pack_size.as_ref().map_or(1., average);
+        self.expected_spawns += scale * chance * pack_size;
+
+        let (ids, value) = match &spawn.id {
+            MapGenMonsterType::Monster { monster } => {
+                (&mut self.monsters, monster)
+            },
+            MapGenMonsterType::MonsterGroup { group } => {
+                (&mut self.groups, group)
+            },
+        };
+
+        if let Ok(id) = value.get_identifier(&map_data.calculated_parameters) {
+            push_unique(ids, &id);
+        }
+    }
+
+    fn add(
+        &mut self,
+        kind: &MappingKind,
+        monsters: &MapGenMonsters,
+        map_data: &MapData,
+        scale: f32,
+    ) {
+        match kind {
+            MappingKind::Monster => self.add_monster(monsters, map_data, scale),
+            _ => self.add_group(monsters, map_data, scale),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.groups.is_empty() && self.monsters.is_empty()
+    }
+}
+
+/// `monsters` and `place_monsters` spawn monster groups, while `monster` and
+/// `place_monster` spawn single monsters
+const MONSTER_KINDS: [MappingKind; 2] =
+    [MappingKind::Monsters, MappingKind::Monster];
+
+/// The monsters which are mapped to the `character`
+fn character_density(
+    map_data: &MapData,
+    character: &char,
+    json_data: &DeserializedCDDAJsonData,
+) -> MonsterDensity {
+    let mut density = MonsterDensity::default();
+
+    for kind in MONSTER_KINDS.iter() {
+        let Some(property) = map_data.get_property(kind, character, json_data)
+        else {
+            continue;
+        };
+
+        if let Some(monsters) = property.downcast_ref::<MonstersProperty>() {
+            monsters
+                .monster
+                .iter()
+                .for_each(|m| density.add(kind, &m.data, map_data, 1.));
+        }
+    }
+
+    density
+}
+
+/// Summarizes the monsters which can spawn on every tile of the `project`.
+/// Entries which are placed on a range of coordinates are spread evenly over
+/// the range, like CDDA picks a random cell of the range for every monster.
+pub fn monster_overlay(
+    project: &Project,
+    json_data: &DeserializedCDDAJsonData,
+) -> HashMap<IVec3JsonKey, MonsterDensity> {
+    let mut overlay: HashMap<IVec3JsonKey, MonsterDensity> = HashMap::new();
+
+    for (z, collection) in project.maps.iter() {
+        for (map_coordinates, map_data) in collection.maps.iter() {
+            let mut local: HashMap<UVec2, MonsterDensity> = HashMap::new();
+            let mut by_character: HashMap<char, MonsterDensity> =
+                HashMap::new();
+
+            for (coordinates, cell) in map_data.cells.iter() {
+                let density =
+                    by_character.entry(cell.character).or_insert_with(|| {
+                        character_density(map_data, &cell.character, json_data)
+                    });
+
+                if !density.is_empty() {
+                    local.insert(coordinates.clone(), density.clone());
+                }
+            }
+
+            for kind in MONSTER_KINDS.iter() {
+                for entry in map_data.place.get(kind).into_iter().flatten() {
+                    let Some(place) =
+                        entry.inner.downcast_ref::<PlaceMonsters>()
+                    else {
+                        continue;
+                    };
+
+                    for (coordinates, scale) in
+                        place_cells(entry, &map_data.map_size)
+                    {
+                        let density = local.entry(coordinates).or_default();
+                        place.property.monster.iter().for_each(|m| {
+                            density.add(kind, &m.data, map_data, scale)
+                        });
+                    }
+                }
+            }
+
+            for (coordinates, density) in local {
+                let position = MapDataCollection::map_to_global_cell_coords(
+                    map_coordinates,
+                    &coordinates,
+                    *z,
+                );
+
+                overlay.insert(IVec3JsonKey(position), density);
+            }
+        }
+    }
+
+    overlay
+}
@@ -0,0 +1,238 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::palettes::CDDAPaletteIntermediate;
+use crate::features::demo::{lock_json_data, DemoJsonData};
+use crate::features::map::MappingKind;
+use crate::features::palette::{
+    import_palette, new_palette, project_palette_mut, read_palettes_from_file,
+    refresh_project_palettes, remove_mapping, set_mapping, write_palette,
+    EditPaletteError, ImportPaletteError, ImportedPalette,
+};
+use crate::features::program_data::changes::{
+    EditorDataChanges, EditorDataScope,
+};
+use crate::features::program_data::generations::ProjectGenerations;
+use crate::features::program_data::io::ProgramDataSaver;
+use crate::features::program_data::EditorData;
+use crate::features::viewer::cache::RenderCache;
+use crate::impl_serialize_for_error;
+use crate::util::{
+    get_current_project_mut, CDDADataError, GetCurrentProjectError, Save,
+};
+use cdda_lib::types::CDDAIdentifier;
+use log::{info, warn};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::ops::Deref;
+use std::path::PathBuf;
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, Manager, State};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ImportPaletteFromFileError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    ImportPaletteError(#[from] ImportPaletteError),
+}
+
+impl_serialize_for_error!(ImportPaletteFromFileError);
+
+/// Imports the palette with the `palette_id` from a vanilla or mod json file
+/// into the current project so it can be used as a starting point for its own
+/// palettes
+#[tauri::command]
+pub async fn import_palette_from_file(
+    path: PathBuf,
+    palette_id: CDDAIdentifier,
+    new_id: Option<CDDAIdentifier>,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+) -> Result<Vec<ImportedPalette>, ImportPaletteFromFileError> {
+    let file_palettes = read_palettes_from_file(&path)?;
+
+    let json_data_lock = json_data.lock().await;
+    let json_data = match json_data_lock.deref() {
+        None => return Err(CDDADataError::NotLoaded.into()),
+        Some(d) => d,
+    };
+
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    let imported =
+        import_palette(file_palettes, &palette_id, new_id, project, json_data)?;
+
+    info!(
+        "Imported palettes {:?} from {:?} into project {}",
+        imported, path, project.name
+    );
+
+    refresh_project_palettes(
+        project,
+        json_data,
+        &render_cache,
+        &project_generations,
+    );
+
+    save_editor_data(editor_data_lock.deref()).await;
+    editor_data_changes.notify(&app, &[EditorDataScope::OpenedProject]);
+
+    Ok(imported)
+}
+
+#[derive(Debug, Error)]
+pub enum PaletteEditorError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    EditPaletteError(#[from] EditPaletteError),
+}
+
+impl_serialize_for_error!(PaletteEditorError);
+
+async fn save_editor_data(editor_data: &EditorData) {
+    let saver = ProgramDataSaver {
+        path: editor_data.config.config_path.clone(),
+    };
+
+    if let Err(e) = saver.save(editor_data).await {
+        warn!("Failed to save the palettes of the project, {}", e);
+    }
+}
+
+/// Creates an empty palette with the `id` in the current project, which can be
+/// filled with [`set_palette_mapping`] and saved with [`save_palette`]
+#[tauri::command]
+pub async fn create_palette(
+    id: CDDAIdentifier,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<(), PaletteEditorError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = match json_data_lock.deref() {
+        None => return Err(CDDADataError::NotLoaded.into()),
+        Some(d) => d,
+    };
+
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    new_palette(id.clone(), project, json_data)?;
+
+    info!("Created palette {} in project {}", id, project.name);
+
+    save_editor_data(editor_data_lock.deref()).await;
+    editor_data_changes.notify(&app, &[EditorDataScope::OpenedProject]);
+
+    Ok(())
+}
+
+/// Applies `edit` to the palette `palette_id` of the current project and makes
+/// the changed palette available to the maps of the project
+async fn edit_palette<T, F>(
+    app: &AppHandle,
+    palette_id: &CDDAIdentifier,
+    edit: F,
+) -> Result<T, PaletteEditorError>
+where
+    F: FnOnce(&mut CDDAPaletteIntermediate) -> Result<T, EditPaletteError>,
+{
+    let json_data = app.state::<Mutex<Option<DeserializedCDDAJsonData>>>();
+    let demo_json_data = app.state::<DemoJsonData>();
+    let editor_data = app.state::<Mutex<EditorData>>();
+
+    // The palettes of the demo project can also be edited without CDDA data
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = match json_data_lock.deref() {
+        None => return Err(CDDADataError::NotLoaded.into()),
+        Some(d) => d,
+    };
+
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+    let palette = project_palette_mut(project, palette_id)?;
+
+    let result = edit(palette)?;
+
+    refresh_project_palettes(
+        project,
+        json_data,
+        &app.state::<RenderCache>(),
+        &app.state::<ProjectGenerations>(),
+    );
+
+    save_editor_data(editor_data_lock.deref()).await;
+    app.state::<EditorDataChanges>()
+        .notify(app, &[EditorDataScope::OpenedProject]);
+
+    Ok(result)
+}
+
+/// Maps the `character` to the `value` for the mapping `kind` of the palette
+/// `palette_id` of the current project. The `value` has the same format as the
+/// mapping in a CDDA palette file.
+#[tauri::command]
+pub async fn set_palette_mapping(
+    palette_id: CDDAIdentifier,
+    kind: MappingKind,
+    character: char,
+    value: Value,
+    app: AppHandle,
+) -> Result<(), PaletteEditorError> {
+    edit_palette(&app, &palette_id, |palette| {
+        set_mapping(palette, &kind, character, value)
+    })
+    .await
+}
+
+/// Removes the mapping `kind` of the `character` from the palette
+/// `palette_id` of the current project. Returns whether the character was
+/// mapped.
+#[tauri::command]
+pub async fn remove_palette_mapping(
+    palette_id: CDDAIdentifier,
+    kind: MappingKind,
+    character: char,
+    app: AppHandle,
+) -> Result<bool, PaletteEditorError> {
+    edit_palette(&app, &palette_id, |palette| {
+        remove_mapping(palette, &kind, character)
+    })
+    .await
+}
+
+/// Writes the palette `palette_id` of the current project into the json file
+/// at `path`, which is usually a file in the directory of a mod
+#[tauri::command]
+pub async fn save_palette(
+    palette_id: CDDAIdentifier,
+    path: PathBuf,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<(), PaletteEditorError> {
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+    let palette = project_palette_mut(project, &palette_id)?;
+
+    // The palette stays a palette of the project, changes to the written
+    // file are not loaded back into it
+    write_palette(&path, palette)?;
+
+    info!("Saved palette {} to {}", palette_id, path.display());
+
+    Ok(())
+}
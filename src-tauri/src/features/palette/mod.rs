@@ -0,0 +1,835 @@
+pub(crate) mod handlers;
+
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::palettes::{CDDAPaletteIntermediate, Palettes, Parameter};
+use crate::data::GetIdentifier;
+use crate::features::map::probability::probability_breakdown;
+use crate::features::map::{MapData, MappingKind};
+use crate::features::program_data::generations::ProjectGenerations;
+use crate::features::program_data::{EditorData, Project};
+use crate::features::viewer::cache::RenderCache;
+use crate::impl_serialize_for_error;
+use cdda_lib::types::{CDDAIdentifier, MapGenValue, ParameterIdentifier};
+use log::warn;
+use serde::Serializer;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ImportPaletteError {
+    #[error("Failed to read the mod file, {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse the mod file, {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("The mod file does not contain a palette with the id {0}")]
+    UnknownPalette(CDDAIdentifier),
+
+    #[error("A palette with the id {0} already exists")]
+    IdTaken(CDDAIdentifier),
+}
+
+impl_serialize_for_error!(ImportPaletteError);
+
+/// A palette which was imported into a project and the id it had in the mod
+/// file it was imported from
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ImportedPalette {
+    pub original_id: CDDAIdentifier,
+    pub id: CDDAIdentifier,
+}
+
+/// Reads all palettes which are defined in a CDDA json file. The file either
+/// contains a list of entries or a single entry.
+pub fn read_palettes_from_file(
+    path: &Path,
+) -> Result<HashMap<CDDAIdentifier, CDDAPaletteIntermediate>, ImportPaletteError>
+{
+    let reader = BufReader::new(File::open(path)?);
+
+    Ok(read_palettes(serde_json::from_reader(reader)?)?)
+}
+
+/// Reads all palettes of the CDDA json `value`, which is either a list of
+/// entries or a single entry
+pub fn read_palettes(
+    value: Value,
+) -> Result<HashMap<CDDAIdentifier, CDDAPaletteIntermediate>, serde_json::Error>
+{
+    let entries = match value {
+        Value::Array(entries) => entries,
+        entry => vec![entry],
+    };
+
+    let mut palettes = HashMap::new();
+
+    for entry in entries {
+        if entry.get("type").and_then(Value::as_str) != Some("palette") {
+            continue;
+        }
+
+        let palette: CDDAPaletteIntermediate = serde_json::from_value(entry)?;
+        palettes.insert(palette.id.clone(), palette);
+    }
+
+    Ok(palettes)
+}
+
+/// Returns the `id` or, if it is already taken, the first free id of the form
+/// `<id>_<n>`
+pub fn unique_palette_id(
+    id: &CDDAIdentifier,
+    is_taken: impl Fn(&CDDAIdentifier) -> bool,
+) -> CDDAIdentifier {
+    if !is_taken(id) {
+        return id.clone();
+    }
+
+    (1..)
+        .map(|n| CDDAIdentifier(format!("{}_{}", id, n)))
+        .find(|id| !is_taken(id))
+        .unwrap()
+}
+
+/// Imports the palette with the `palette_id` from the `file_palettes` into the
+/// `project`. The imported palettes are only available to the maps of the
+/// project.
+///
+/// Palettes which are referenced by the imported palette and only exist in
+/// the mod file are imported as well. Every palette whose id collides with a
+/// loaded palette or a palette of the project is renamed and the references to
+/// it are updated. The `new_id` is used for the imported palette if it is set.
+pub fn import_palette(
+    mut file_palettes: HashMap<CDDAIdentifier, CDDAPaletteIntermediate>,
+    palette_id: &CDDAIdentifier,
+    new_id: Option<CDDAIdentifier>,
+    project: &mut Project,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<Vec<ImportedPalette>, ImportPaletteError> {
+    if !file_palettes.contains_key(palette_id) {
+        return Err(ImportPaletteError::UnknownPalette(palette_id.clone()));
+    }
+
+    let mut taken: HashSet<CDDAIdentifier> = json_data
+        .palettes
+        .keys()
+        .chain(project.palettes.iter().map(|p| &p.id))
+        .cloned()
+        .collect();
+
+    if let Some(new_id) = &new_id {
+        if taken.contains(new_id) {
+            return Err(ImportPaletteError::IdTaken(new_id.clone()));
+        }
+    }
+
+    // Collect the palette and all palettes it depends on which are not loaded
+    let mut to_import = vec![];
+    let mut queue = VecDeque::from([palette_id.clone()]);
+
+    while let Some(id) = queue.pop_front() {
+        let palette = match file_palettes.remove(&id) {
+            None => continue,
+            Some(p) => p,
+        };
+
+        // Only constant references can be followed since parameters and
+        // switches are only known once a map is generated
+        for mapgen_value in palette.palettes.iter() {
+            if let MapGenValue::String(child) = mapgen_value {
+                if !json_data.palettes.contains_key(child) {
+                    queue.push_back(child.clone());
+                }
+            }
+        }
+
+        to_import.push(palette);
+    }
+
+    let mut renamed = HashMap::new();
+    let mut imported = vec![];
+
+    for palette in to_import.iter() {
+        let id = match (&palette.id == palette_id, &new_id) {
+            (true, Some(new_id)) => new_id.clone(),
+            _ => unique_palette_id(&palette.id, |id| taken.contains(id)),
+        };
+
+        taken.insert(id.clone());
+        renamed.insert(palette.id.clone(), id.clone());
+        imported.push(ImportedPalette {
+            original_id: palette.id.clone(),
+            id,
+        });
+    }
+
+    for mut palette in to_import {
+        palette.id = renamed[&palette.id].clone();
+
+        for mapgen_value in palette.palettes.iter_mut() {
+            if let MapGenValue::String(child) = mapgen_value {
+                if let Some(new_child) = renamed.get(child) {
+                    *child = new_child.clone();
+                }
+            }
+        }
+
+        project.palettes.push(palette);
+    }
+
+    Ok(imported)
+}
+
+/// Makes the changed palettes of the `project` available to its maps,
+/// recalculates the parameters of the maps and invalidates their renders
+pub fn refresh_project_palettes(
+    project: &mut Project,
+    json_data: &DeserializedCDDAJsonData,
+    render_cache: &RenderCache,
+    project_generations: &ProjectGenerations,
+) {
+    project.share_palettes();
+
+    project_generations.advance(&project.name);
+    render_cache.invalidate_project(&project.name);
+
+    for (_, collection) in project.maps.iter_mut() {
+        if let Err(e) = collection.calculate_parameters(&json_data.palettes) {
+            let name = &project.name;
+            warn!("Failed to recalculate parameters of {}: {}", name, e);
+        }
+    }
+}
+
+/// The palettes the `value` can resolve to when none of the parameters are
+/// known, which are the fallbacks of parameters and switches
+fn possible_palettes(value: &MapGenValue) -> Vec<CDDAIdentifier> {
+    probability_breakdown(value, &HashMap::new())
+        .into_iter()
+        .map(|p| p.id)
+        .collect()
+}
+
+/// The files of all loaded palettes which are used by the mapgen in the
+/// files at `mapgen_paths`, including the palettes which are used by other
+/// palettes. Files which can not be read are skipped.
+pub fn palette_source_paths(
+    mapgen_paths: &[PathBuf],
+    json_data: &DeserializedCDDAJsonData,
+) -> BTreeSet<PathBuf> {
+    let mut to_visit = vec![];
+
+    for path in mapgen_paths {
+        let Ok(file) = File::open(path) else {
+            continue;
+        };
+        let Ok(entries) =
+            serde_json::from_reader::<_, Vec<Value>>(BufReader::new(file))
+        else {
+            continue;
+        };
+
+        let values = entries
+            .iter()
+            .filter_map(|e| e.get("object")?.get("palettes").cloned())
+            .filter_map(|p| serde_json::from_value::<Vec<MapGenValue>>(p).ok())
+            .flatten();
+
+        for value in values {
+            to_visit.extend(possible_palettes(&value));
+        }
+    }
+
+    let sources = json_data.sources.get("palette");
+    let mut visited = HashSet::new();
+    let mut paths = BTreeSet::new();
+
+    while let Some(id) = to_visit.pop() {
+        if !visited.insert(id.clone()) {
+            continue;
+        }
+
+        if let Some(path) = sources.and_then(|s| s.get(&id)) {
+            paths.insert(path.clone());
+        }
+
+        if let Some(palette) = json_data.palettes.get(&id) {
+            palette
+                .palettes
+                .iter()
+                .for_each(|p| to_visit.extend(possible_palettes(p)));
+        }
+    }
+
+    paths
+}
+
+/// A palette reference of a mapgen which can not be resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteReferenceProblem {
+    /// The palette `id` does not exist. The `chain` contains the palettes it
+    /// is used through, starting with the palette the mapgen uses.
+    Missing {
+        id: CDDAIdentifier,
+        chain: Vec<CDDAIdentifier>,
+    },
+    /// The palettes in the `chain` include each other, the last palette of
+    /// the chain is the first one again
+    Cycle { chain: Vec<CDDAIdentifier> },
+}
+
+fn format_chain(chain: &[CDDAIdentifier]) -> String {
+    chain
+        .iter()
+        .map(|id| id.0.as_str())
+        .collect::<Vec<&str>>()
+        .join(" -> ")
+}
+
+impl Display for PaletteReferenceProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaletteReferenceProblem::Missing { id, chain }
+                if chain.is_empty() =>
+            {
+                write!(f, "The palette {} does not exist", id)
+            },
+            PaletteReferenceProblem::Missing { id, chain } => write!(
+                f,
+                "The palette {} used by {} does not exist",
+                id,
+                format_chain(chain)
+            ),
+            PaletteReferenceProblem::Cycle { chain } => {
+                write!(
+                    f,
+                    "The palettes {} include each other",
+                    format_chain(chain)
+                )
+            },
+        }
+    }
+}
+
+/// Every palette reference of the `palettes` values of a mapgen which can not
+/// be resolved, including the references of the palettes it uses. Parameter
+/// driven references are checked for every palette the parameter can resolve
+/// to.
+pub fn check_palette_references(
+    palettes: &[MapGenValue],
+    parameters: &HashMap<ParameterIdentifier, Parameter>,
+    all_palettes: &Palettes,
+) -> Vec<PaletteReferenceProblem> {
+    let mut problems = vec![];
+    let mut checked = HashSet::new();
+
+    for value in palettes {
+        for id in referenced_palettes(value, parameters) {
+            check_palette(
+                &id,
+                all_palettes,
+                &mut vec![],
+                &mut checked,
+                &mut problems,
+            );
+        }
+    }
+
+    problems
+}
+
+fn referenced_palettes(
+    value: &MapGenValue,
+    parameters: &HashMap<ParameterIdentifier, Parameter>,
+) -> Vec<CDDAIdentifier> {
+    probability_breakdown(value, parameters)
+        .into_iter()
+        .map(|p| p.id)
+        .collect()
+}
+
+/// Checks the palette `id` and the palettes it uses. The `chain` contains the
+/// palettes which use the palette, palettes in `checked` are not checked
+/// again.
+fn check_palette(
+    id: &CDDAIdentifier,
+    all_palettes: &Palettes,
+    chain: &mut Vec<CDDAIdentifier>,
+    checked: &mut HashSet<CDDAIdentifier>,
+    problems: &mut Vec<PaletteReferenceProblem>,
+) {
+    if let Some(start) = chain.iter().position(|c| c == id) {
+        let mut cycle = chain[start..].to_vec();
+        cycle.push(id.clone());
+
+        problems.push(PaletteReferenceProblem::Cycle { chain: cycle });
+        return;
+    }
+
+    if !checked.insert(id.clone()) {
+        return;
+    }
+
+    let Some(palette) = all_palettes.get(id) else {
+        problems.push(PaletteReferenceProblem::Missing {
+            id: id.clone(),
+            chain: chain.clone(),
+        });
+        return;
+    };
+
+    chain.push(id.clone());
+
+    for value in palette.palettes.iter() {
+        for child in referenced_palettes(value, &palette.parameters) {
+            check_palette(&child, all_palettes, chain, checked, problems);
+        }
+    }
+
+    chain.pop();
+}
+
+/// Deserializes the palettes in the file at `path` again and replaces the
+/// loaded palettes with them, without reloading the rest of the CDDA data.
+/// Returns the ids of the reloaded palettes.
+pub fn reload_palettes_from_file(
+    path: &Path,
+    json_data: &mut DeserializedCDDAJsonData,
+) -> Result<Vec<CDDAIdentifier>, ImportPaletteError> {
+    let palettes = read_palettes_from_file(path)?;
+    let sources = json_data.sources.entry("palette".to_string()).or_default();
+
+    let mut reloaded = vec![];
+
+    for (id, palette) in palettes {
+        sources.insert(id.clone(), path.to_path_buf());
+        json_data.palettes.insert(id.clone(), palette.into());
+        reloaded.push(id);
+    }
+
+    Ok(reloaded)
+}
+
+/// The ids of all palettes the `map_data` uses, directly or through another
+/// palette
+fn map_palettes(
+    map_data: &MapData,
+    json_data: &DeserializedCDDAJsonData,
+) -> HashSet<CDDAIdentifier> {
+    let parameters = &map_data.calculated_parameters;
+    let scope = map_data.palette_scope(&json_data.palettes);
+
+    let mut visited = HashSet::new();
+    let mut to_visit: Vec<CDDAIdentifier> = map_data
+        .palettes
+        .iter()
+        .filter_map(|p| p.get_identifier(parameters).ok())
+        .collect();
+
+    while let Some(id) = to_visit.pop() {
+        if !visited.insert(id.clone()) {
+            continue;
+        }
+
+        if let Some(palette) = scope.get(&id) {
+            to_visit.extend(
+                palette
+                    .palettes
+                    .iter()
+                    .filter_map(|p| p.get_identifier(parameters).ok()),
+            );
+        }
+    }
+
+    visited
+}
+
+/// Whether any map of the `project` uses one of the `palettes`
+pub fn project_uses_palettes(
+    project: &Project,
+    palettes: &HashSet<CDDAIdentifier>,
+    json_data: &DeserializedCDDAJsonData,
+) -> bool {
+    project
+        .maps
+        .values()
+        .flat_map(|collection| collection.maps.values())
+        .any(|map_data| {
+            !map_palettes(map_data, json_data).is_disjoint(palettes)
+        })
+}
+
+/// Recalculates the parameters of the maps of all loaded projects which use
+/// one of the `palettes` and invalidates their renders
+pub fn refresh_palette_users(
+    editor_data: &mut EditorData,
+    palettes: &HashSet<CDDAIdentifier>,
+    json_data: &DeserializedCDDAJsonData,
+    render_cache: &RenderCache,
+    project_generations: &ProjectGenerations,
+) {
+    for project in editor_data.loaded_projects.values_mut() {
+        if !project_uses_palettes(project, palettes, json_data) {
+            continue;
+        }
+
+        project_generations.advance(&project.name);
+        render_cache.invalidate_project(&project.name);
+
+        let maps = project.maps.values_mut().flat_map(|c| c.maps.values_mut());
+
+        for map_data in maps {
+            if let Err(e) = map_data.calculate_parameters(&json_data.palettes) {
+                let name = &project.name;
+                warn!("Failed to recalculate parameters of {}: {}", name, e);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EditPaletteError {
+    #[error("The project does not contain a palette with the id {0}")]
+    UnknownPalette(CDDAIdentifier),
+
+    #[error("A palette with the id {0} already exists")]
+    IdTaken(CDDAIdentifier),
+
+    #[error("The {1:?} mapping of '{0}' is invalid, {2}")]
+    InvalidMapping(char, MappingKind, serde_json::Error),
+
+    #[error("Failed to convert the palette, {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Failed to write the palette to {0}, {1}")]
+    Io(PathBuf, std::io::Error),
+}
+
+impl_serialize_for_error!(EditPaletteError);
+
+/// The key the mappings of the `kind` are stored under in a palette
+pub fn palette_mapping_key(kind: &MappingKind) -> &'static str {
+    match kind {
+        MappingKind::Terrain => "terrain",
+        MappingKind::Furniture => "furniture",
+        MappingKind::Trap => "traps",
+        MappingKind::ItemGroups => "items",
+        MappingKind::Computer => "computers",
+        MappingKind::Sign => "signs",
+        MappingKind::Toilet => "toilets",
+        MappingKind::Gaspump => "gaspumps",
+        MappingKind::Monsters => "monsters",
+        MappingKind::Monster => "monster",
+        MappingKind::Field => "fields",
+        MappingKind::Nested => "nested",
+        MappingKind::Vehicle => "vehicles",
+        MappingKind::Corpse => "corpses",
+        MappingKind::Appliance => "appliances",
+        MappingKind::Npc => "npcs",
+        MappingKind::Graffiti => "graffiti",
+        MappingKind::Rubble => "rubble",
+        MappingKind::Liquid => "liquids",
+        MappingKind::SealedItem => "sealed_item",
+        MappingKind::Loot => "loot",
+    }
+}
+
+/// Creates an empty palette with the `id` in the `project`
+pub fn new_palette(
+    id: CDDAIdentifier,
+    project: &mut Project,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<(), EditPaletteError> {
+    let is_taken = json_data.palettes.contains_key(&id)
+        || project.palettes.iter().any(|p| p.id == id);
+
+    if is_taken {
+        return Err(EditPaletteError::IdTaken(id));
+    }
+
+    let palette: CDDAPaletteIntermediate =
+        serde_json::from_value(serde_json::json!({ "id": id }))?;
+
+    project.palettes.push(palette);
+    project.share_palettes();
+
+    Ok(())
+}
+
+/// Returns the palette with the `id` which was created in or imported into the
+/// `project`. Palettes of the loaded CDDA data can not be edited.
+pub fn project_palette_mut<'a>(
+    project: &'a mut Project,
+    id: &CDDAIdentifier,
+) -> Result<&'a mut CDDAPaletteIntermediate, EditPaletteError> {
+    project
+        .palettes
+        .iter_mut()
+        .find(|p| &p.id == id)
+        .ok_or(EditPaletteError::UnknownPalette(id.clone()))
+}
+
+/// Changes the mappings of the `kind` in the `palette` with `edit`. The
+/// mappings are edited as json so every kind can be handled the same way, and
+/// deserialized again to check that they are still valid.
+fn edit_palette_mappings<T>(
+    palette: &mut CDDAPaletteIntermediate,
+    kind: &MappingKind,
+    character: char,
+    edit: impl FnOnce(&mut Map<String, Value>) -> T,
+) -> Result<T, EditPaletteError> {
+    let key = palette_mapping_key(kind);
+    let mut json = serde_json::to_value(&*palette)?;
+
+    let mut mappings = match json[key].take() {
+        Value::Object(m) => m,
+        _ => Map::new(),
+    };
+
+    let result = edit(&mut mappings);
+    json[key] = Value::Object(mappings);
+
+    *palette = serde_json::from_value(json).map_err(|e| {
+        EditPaletteError::InvalidMapping(character, kind.clone(), e)
+    })?;
+
+    Ok(result)
+}
+
+/// Maps the `character` to the `value` for the mapping `kind` of the
+/// `palette`, replacing the previous mapping of the character
+pub fn set_mapping(
+    palette: &mut CDDAPaletteIntermediate,
+    kind: &MappingKind,
+    character: char,
+    value: Value,
+) -> Result<(), EditPaletteError> {
+    edit_palette_mappings(palette, kind, character, |mappings| {
+        mappings.insert(character.to_string(), value);
+    })
+}
+
+/// Removes the mapping `kind` of the `character` from the `palette`. Returns
+/// whether the character was mapped.
+pub fn remove_mapping(
+    palette: &mut CDDAPaletteIntermediate,
+    kind: &MappingKind,
+    character: char,
+) -> Result<bool, EditPaletteError> {
+    edit_palette_mappings(palette, kind, character, |mappings| {
+        mappings.remove(&character.to_string()).is_some()
+    })
+}
+
+/// Removes the fields which are `null` from all objects in the `value`, since
+/// they are the same as leaving the field out
+fn remove_null_fields(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.retain(|_, v| !v.is_null());
+            object.values_mut().for_each(remove_null_fields);
+        },
+        Value::Array(array) => array.iter_mut().for_each(remove_null_fields),
+        _ => {},
+    }
+}
+
+/// The json object of the `palette` as it is written into a CDDA json file.
+/// Empty fields are left out and the mappings are sorted by their character,
+/// so saving the same palette twice gives the same result.
+pub fn palette_to_json(
+    palette: &CDDAPaletteIntermediate,
+) -> Result<Value, EditPaletteError> {
+    let mut object = Map::new();
+    object.insert("type".to_string(), Value::from("palette"));
+
+    let mut json = serde_json::to_value(palette)?;
+    remove_null_fields(&mut json);
+
+    let Value::Object(fields) = json else {
+        unreachable!("A palette is always serialized as an object");
+    };
+
+    for (key, value) in fields {
+        let value = match value {
+            Value::Null => continue,
+            Value::Array(a) if a.is_empty() => continue,
+            Value::Object(o) if o.is_empty() => continue,
+            Value::Object(o) if key != "parameters" => {
+                let mut mappings: Vec<(String, Value)> =
+                    o.into_iter().collect();
+                mappings.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                Value::Object(mappings.into_iter().collect())
+            },
+            value => value,
+        };
+
+        object.insert(key, value);
+    }
+
+    Ok(Value::Object(object))
+}
+
+/// Writes the `palette` into the CDDA json file at `path`, replacing the
+/// palette with the same id in that file. The file is created if it does not
+/// exist yet.
+pub fn write_palette(
+    path: &Path,
+    palette: &CDDAPaletteIntermediate,
+) -> Result<(), EditPaletteError> {
+    let mut values: Vec<Value> = match path.exists() {
+        false => vec![],
+        true => {
+            let content = fs::read_to_string(path)
+                .map_err(|e| EditPaletteError::Io(path.to_path_buf(), e))?;
+            serde_json::from_str(&content)?
+        },
+    };
+
+    let palette_json = palette_to_json(palette)?;
+
+    let existing = values.iter_mut().find(|v| {
+        v.get("type").and_then(Value::as_str) == Some("palette")
+            && v.get("id").and_then(Value::as_str)
+                == Some(palette.id.0.as_str())
+    });
+
+    match existing {
+        None => values.push(palette_json),
+        Some(existing) => *existing = palette_json,
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| EditPaletteError::Io(path.to_path_buf(), e))?;
+    }
+
+    fs::write(path, serde_json::to_string_pretty(&values)?)
+        .map_err(|e| EditPaletteError::Io(path.to_path_buf(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::program_data::MapCoordinates;
+    use serde_json::json;
+
+    fn empty_palette() -> CDDAPaletteIntermediate {
+        serde_json::from_value(json!({ "id": "test_palette" })).unwrap()
+    }
+
+    #[test]
+    fn test_edit_palette_mappings() {
+        let mut palette = empty_palette();
+
+        set_mapping(&mut palette, &MappingKind::Terrain, '.', json!("t_floor"))
+            .unwrap();
+        set_mapping(
+            &mut palette,
+            &MappingKind::ItemGroups,
+            '.',
+            json!({ "item": "trash", "chance": 10 }),
+        )
+        .unwrap();
+        set_mapping(&mut palette, &MappingKind::Terrain, '#', json!("t_wall"))
+            .unwrap();
+
+        assert_eq!(palette.terrain.len(), 2);
+        assert_eq!(palette.items.len(), 1);
+
+        assert!(
+            remove_mapping(&mut palette, &MappingKind::Terrain, '#').unwrap()
+        );
+        assert!(
+            !remove_mapping(&mut palette, &MappingKind::Terrain, '#').unwrap()
+        );
+        assert!(!palette.terrain.contains_key(&'#'));
+
+        // Invalid mappings are rejected and keep the palette unchanged
+        let result = set_mapping(
+            &mut palette,
+            &MappingKind::ItemGroups,
+            'x',
+            json!(["not", "an", "item"]),
+        );
+        assert!(matches!(
+            result,
+            Err(EditPaletteError::InvalidMapping(
+                'x',
+                MappingKind::ItemGroups,
+                _
+            ))
+        ));
+        assert_eq!(palette.items.len(), 1);
+
+        assert_eq!(
+            palette_to_json(&palette).unwrap(),
+            json!({
+                "type": "palette",
+                "id": "test_palette",
+                "terrain": { ".": "t_floor" },
+                "items": { ".": { "item": "trash", "chance": 10 } },
+            })
+        );
+    }
+
+    #[test]
+    fn test_import_palette_into_project() {
+        let path = std::env::temp_dir().join("cdda_single_palette.json");
+        fs::write(
+            &path,
+            json!({
+                "type": "palette",
+                "id": "single_palette",
+                "terrain": { ".": "t_floor" }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let file_palettes = read_palettes_from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let palette_id = CDDAIdentifier::from("single_palette");
+        assert!(file_palettes.contains_key(&palette_id));
+
+        let json_data = DeserializedCDDAJsonData::default();
+        let mut project = Project::default();
+        let other_project = Project::default();
+
+        project
+            .maps
+            .get_mut(&0)
+            .unwrap()
+            .maps
+            .insert(MapCoordinates::ZERO, MapData::default());
+
+        import_palette(
+            file_palettes,
+            &palette_id,
+            None,
+            &mut project,
+            &json_data,
+        )
+        .unwrap();
+        project.share_palettes();
+
+        // The palette is only available to the maps of the project it was
+        // imported into
+        assert!(json_data.palettes.is_empty());
+        assert!(project.maps[&0].maps[&MapCoordinates::ZERO]
+            .palette_scope(&json_data.palettes)
+            .get(&palette_id)
+            .is_some());
+        assert!(other_project.project_palettes().get(&palette_id).is_none());
+    }
+}
@@ -0,0 +1,87 @@
+use crate::events::{
+    ConfigChangedEvent, EmitEvent, OpenedProjectChangedEvent,
+    ProjectListChangedEvent,
+};
+use crate::features::program_data::EditorData;
+use log::warn;
+use std::collections::HashSet;
+use std::time::Duration;
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Time to wait after the first change before the change events are sent.
+/// All changes within this time are combined into a single event per scope.
+pub const EDITOR_DATA_CHANGE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Parts of the [`EditorData`] that are sent to the frontend independently of
+/// each other
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum EditorDataScope {
+    Config,
+    ProjectList,
+    OpenedProject,
+}
+
+/// Collects the scopes of the [`EditorData`] that changed and sends one event
+/// per scope once [`EDITOR_DATA_CHANGE_DEBOUNCE`] has passed.
+#[derive(Debug, Default)]
+pub struct EditorDataChanges {
+    pending: std::sync::Mutex<HashSet<EditorDataScope>>,
+}
+
+impl EditorDataChanges {
+    pub fn notify(&self, app: &AppHandle, scopes: &[EditorDataScope]) {
+        let mut pending = self.pending.lock().unwrap();
+
+        // A flush is already scheduled if there are pending scopes
+        let schedule_flush = pending.is_empty();
+        pending.extend(scopes.iter().copied());
+
+        if !schedule_flush {
+            return;
+        }
+
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(EDITOR_DATA_CHANGE_DEBOUNCE).await;
+            flush_editor_data_changes(&app).await;
+        });
+    }
+}
+
+async fn flush_editor_data_changes(app: &AppHandle) {
+    let scopes = {
+        let changes = app.state::<EditorDataChanges>();
+        let mut pending = changes.pending.lock().unwrap();
+        std::mem::take(&mut *pending)
+    };
+
+    let editor_data = app.state::<Mutex<EditorData>>();
+    let editor_data_lock = editor_data.lock().await;
+
+    for scope in scopes {
+        let result = match scope {
+            EditorDataScope::Config => app.emit_event(ConfigChangedEvent {
+                config: editor_data_lock.config.clone(),
+                available_tilesets: editor_data_lock.available_tilesets.clone(),
+            }),
+            EditorDataScope::ProjectList => {
+                app.emit_event(ProjectListChangedEvent {
+                    openable_projects: editor_data_lock
+                        .openable_projects
+                        .clone(),
+                    recent_projects: editor_data_lock.recent_projects.clone(),
+                })
+            },
+            EditorDataScope::OpenedProject => {
+                app.emit_event(OpenedProjectChangedEvent {
+                    opened_project: editor_data_lock.opened_project.clone(),
+                })
+            },
+        };
+
+        if let Err(e) = result {
+            warn!("Failed to send {:?} change event: {}", scope, e);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::features::program_data::ProjectName;
+use std::collections::HashMap;
+
+/// Counts how often every project was opened, reloaded or closed.
+///
+/// Long-running work like rendering remembers the generation of the project
+/// it was started for and drops its result if the project changed in the
+/// meantime, so closing a tab mid-render can not emit stale events.
+///
+/// The lock is never held across an await point, so it can be used while any
+/// of the async state locks are held.
+#[derive(Debug, Default)]
+pub struct ProjectGenerations {
+    generations: std::sync::Mutex<HashMap<ProjectName, u64>>,
+}
+
+impl ProjectGenerations {
+    pub fn current(&self, name: &ProjectName) -> u64 {
+        self.generations
+            .lock()
+            .unwrap()
+            .get(name)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Invalidates all work which was started for the project `name`
+    pub fn advance(&self, name: &ProjectName) -> u64 {
+        let mut generations = self.generations.lock().unwrap();
+        let generation = generations.entry(name.clone()).or_default();
+        *generation += 1;
+
+        *generation
+    }
+
+    pub fn is_current(&self, name: &ProjectName, generation: u64) -> bool {
+        self.current(name) == generation
+    }
+}
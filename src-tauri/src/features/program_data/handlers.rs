@@ -1,26 +1,40 @@
+use crate::data::cache::get_cdda_data_cache_path;
 use crate::data::io::{load_cdda_json_data, DeserializedCDDAJsonData};
-use crate::events;
-use crate::events::UPDATE_LIVE_VIEWER;
+use crate::events::{EmitEvent, TabRemovedEvent};
+use crate::features::jobs::Jobs;
+use crate::features::map::export::ExportTarget;
+use crate::features::mods::{
+    cdda_data_paths, load_project_mods, opened_project_mods,
+};
+use crate::features::program_data::changes::{
+    EditorDataChanges, EditorDataScope,
+};
+use crate::features::program_data::generations::ProjectGenerations;
 use crate::features::program_data::io::ProgramDataSaver;
 use crate::features::program_data::{
-    get_map_data_collection_from_live_viewer_data, EditorData, LiveViewerData,
-    Project, ProjectName, ProjectType, Tab, TabType,
+    load_project_maps, EditorData, PlaceDefaults, Project, ProjectName,
+    ProjectType, Tab,
 };
 use crate::features::tileset::legacy_tileset::{
-    load_tilesheet, LegacyTilesheet,
+    emit_embedded_fallback_warning, emit_tileset_changed, load_tileset,
+    LegacyTilesheet,
 };
+use crate::features::tileset::Season;
+use crate::features::thumbnail::update_project_thumbnail;
 use crate::features::toast::ToastMessage;
+use crate::features::viewer::cache::RenderCache;
+use crate::features::viewer::context::surround_with_neighbors;
+use crate::features::watcher::cdda_data::CDDADataWatcher;
+use crate::features::watcher::{spawn_project_watcher, ProjectWatchers};
 use crate::util::{get_json_data, CDDADataError, Save};
-use log::{error, info, warn};
-use notify_debouncer_full::new_debouncer;
+use log::{error, warn};
 use serde::Serialize;
 use std::fs;
 use std::ops::Deref;
 use std::path::PathBuf;
-use std::time::Duration;
+use std::sync::Arc;
 use tauri::async_runtime::Mutex;
-use tauri::{AppHandle, Emitter, Manager, State};
-use tokio_test::block_on;
+use tauri::{AppHandle, Manager, State};
 
 #[tauri::command]
 pub async fn get_editor_data(
@@ -42,8 +56,12 @@ pub enum InstallationPickedError {
 pub async fn cdda_installation_directory_picked(
     path: PathBuf,
     app: AppHandle,
+    jobs: State<'_, Jobs>,
     editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
     json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    render_cache: State<'_, RenderCache>,
+    cdda_data_watcher: State<'_, CDDADataWatcher>,
 ) -> Result<(), InstallationPickedError> {
     let gfx_dir = fs::read_dir(&path.join("gfx")).map_err(|_| {
         InstallationPickedError::InvalidCDDADirectory(
@@ -68,19 +86,38 @@ pub async fn cdda_installation_directory_picked(
             .push(entry.file_name().to_string_lossy().into_owned());
     }
 
+    let mut json_data_lock = json_data.lock().await;
     let mut editor_data_lock = editor_data.lock().await;
     editor_data_lock.available_tilesets = Some(available_tilesets);
     editor_data_lock.config.cdda_path = Some(path);
 
+    let mods = opened_project_mods(&editor_data_lock).unwrap_or_else(|e| {
+        warn!("Failed to load the mods of the project {}", e);
+        vec![]
+    });
+
     match load_cdda_json_data(
         &editor_data_lock.config.cdda_path.clone().unwrap(),
         &editor_data_lock.config.json_data_path,
+        mods.clone(),
+        Some(get_cdda_data_cache_path(&editor_data_lock.config)),
+        Some(jobs.start(&app, "Loading CDDA data")),
     )
     .await
     {
         Ok(data) => {
-            let mut json_data_lock = json_data.lock().await;
             json_data_lock.replace(data);
+            render_cache.invalidate_all();
+
+            let cdda_path = editor_data_lock.config.cdda_path.clone().unwrap();
+            cdda_data_watcher.watch(
+                app.clone(),
+                cdda_data_paths(
+                    &cdda_path,
+                    &editor_data_lock.config.json_data_path,
+                    &mods,
+                ),
+            );
         },
         Err(e) => {
             warn!("{}", e);
@@ -90,8 +127,7 @@ pub async fn cdda_installation_directory_picked(
         },
     }
 
-    app.emit(events::EDITOR_DATA_CHANGED, editor_data_lock.clone())
-        .unwrap();
+    editor_data_changes.notify(&app, &[EditorDataScope::Config]);
 
     Ok(())
 }
@@ -103,6 +139,9 @@ pub enum TilesetPickedError {
 
     #[error("No CDDA game directory has been picked")]
     NoCDDADirPicked,
+
+    #[error("Failed to render project {0}, {1}")]
+    RenderFailed(String, String),
 }
 
 #[tauri::command]
@@ -110,40 +149,173 @@ pub async fn tileset_picked(
     tileset: String,
     app: AppHandle,
     editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
     tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+    fallback_tilesheet: State<'_, Arc<LegacyTilesheet>>,
+    render_cache: State<'_, RenderCache>,
+    jobs: State<'_, Jobs>,
 ) -> Result<(), TilesetPickedError> {
-    let mut editor_data_lock = editor_data.lock().await;
-    let mut tilesheet_lock = tilesheet.lock().await;
+    {
+        let editor_data_lock = editor_data.lock().await;
 
-    let tilesets = match &editor_data_lock.available_tilesets {
-        None => return Err(TilesetPickedError::NoCDDADirPicked),
-        Some(t) => t,
-    };
+        let tilesets = match &editor_data_lock.available_tilesets {
+            None => return Err(TilesetPickedError::NoCDDADirPicked),
+            Some(t) => t,
+        };
 
-    // This is the default tileset
-    if tileset == "None" {
-        editor_data_lock.config.selected_tileset = None;
-        tilesheet_lock.take();
-    } else {
-        match tilesets.iter().find(|t| **t == tileset) {
-            None => return Err(TilesetPickedError::NotATileset),
-            Some(_) => {},
+        // "None" is the default tileset
+        if tileset != "None" && !tilesets.contains(&tileset) {
+            return Err(TilesetPickedError::NotATileset);
         }
+    }
+
+    let tileset = match tileset.as_str() {
+        "None" => None,
+        _ => Some(tileset),
+    };
+
+    swap_tileset(
+        tileset,
+        &app,
+        &editor_data,
+        &editor_data_changes,
+        &json_data,
+        &tilesheet,
+        &fallback_tilesheet,
+        &render_cache,
+        &jobs,
+    )
+    .await
+}
+
+/// Loads the selected tileset from disk again, so changes to its files show
+/// up without restarting the editor
+#[tauri::command]
+pub async fn reload_tileset(
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+    fallback_tilesheet: State<'_, Arc<LegacyTilesheet>>,
+    render_cache: State<'_, RenderCache>,
+    jobs: State<'_, Jobs>,
+) -> Result<(), TilesetPickedError> {
+    let tileset = editor_data.lock().await.config.selected_tileset.clone();
+
+    swap_tileset(
+        tileset,
+        &app,
+        &editor_data,
+        &editor_data_changes,
+        &json_data,
+        &tilesheet,
+        &fallback_tilesheet,
+        &render_cache,
+        &jobs,
+    )
+    .await
+}
 
-        editor_data_lock.config.selected_tileset = Some(tileset.clone());
-        *tilesheet_lock =
-            load_tilesheet(&editor_data_lock).await.map_err(|e| {
+/// Replaces the current tilesheet with the one of the `tileset`, or with the
+/// fallback tileset if it is [`None`], and renders all open projects with it
+async fn swap_tileset(
+    tileset: Option<String>,
+    app: &AppHandle,
+    editor_data: &Mutex<EditorData>,
+    editor_data_changes: &EditorDataChanges,
+    json_data: &Mutex<Option<DeserializedCDDAJsonData>>,
+    tilesheet: &Mutex<Option<LegacyTilesheet>>,
+    fallback_tilesheet: &LegacyTilesheet,
+    render_cache: &RenderCache,
+    jobs: &Jobs,
+) -> Result<(), TilesetPickedError> {
+    // The locks are not held while the new tileset loads, so the old tileset
+    // stays usable for rendering until the new one is ready
+    let (cdda_path, season) = {
+        let editor_data_lock = editor_data.lock().await;
+
+        (
+            editor_data_lock.config.cdda_path.clone(),
+            editor_data_lock.config.season,
+        )
+    };
+
+    let (selected_tileset, new_tilesheet) = match (tileset, cdda_path) {
+        (None, _) | (_, None) => (None, None),
+        (Some(tileset), Some(cdda_path)) => {
+            let job = jobs.start(app, format!("Loading tileset {}", tileset));
+            let result = load_tileset(&cdda_path, &tileset).await;
+            job.finish(&result);
+
+            let mut new_tilesheet = result.map_err(|e| {
                 error!("Failed to load tilesheet, `{0}`", e);
                 TilesetPickedError::NotATileset
             })?;
-    }
+            new_tilesheet.set_season(season);
+
+            (Some(tileset), Some(new_tilesheet))
+        },
+    };
+
+    let mut json_data_lock = json_data.lock().await;
+    let mut editor_data_lock = editor_data.lock().await;
+    let mut tilesheet_lock = tilesheet.lock().await;
+
+    // Swap both at once so sprites are never requested from a tileset which
+    // does not match the selected one. This drops the old tilesheet.
+    editor_data_lock.config.selected_tileset = selected_tileset.clone();
+    *tilesheet_lock = new_tilesheet;
+
+    emit_embedded_fallback_warning(app, tilesheet_lock.as_ref());
 
     let saver = ProgramDataSaver {
         path: editor_data_lock.config.config_path.clone(),
     };
 
     saver.save(&editor_data_lock).await.unwrap();
-    app.emit(events::TILESET_CHANGED, ()).unwrap();
+    render_cache.invalidate_all();
+
+    let rendered_tilesheet = editor_data_lock
+        .config
+        .rendered_tilesheet(tilesheet_lock.as_ref());
+
+    // The sprites of the open projects were picked from the old tileset
+    if let Some(json_data) = json_data_lock.as_mut() {
+        let job = jobs.start(app, "Rendering open projects");
+        let project_count = editor_data_lock.loaded_projects.len();
+
+        let result = editor_data_lock
+            .loaded_projects
+            .iter_mut()
+            .enumerate()
+            .try_for_each(|(i, (name, project))| {
+                render_cache
+                    .render(
+                        name.clone(),
+                        project,
+                        json_data,
+                        rendered_tilesheet,
+                        fallback_tilesheet,
+                    )
+                    .map_err(|e| {
+                        TilesetPickedError::RenderFailed(
+                            name.clone(),
+                            e.to_string(),
+                        )
+                    })?;
+
+                job.set_progress((i + 1) as f32 / project_count as f32);
+                Ok(())
+            });
+
+        job.finish(&result);
+        result?;
+    }
+
+    emit_tileset_changed(app, selected_tileset, rendered_tilesheet);
+    editor_data_changes.notify(app, &[EditorDataScope::Config]);
 
     Ok(())
 }
@@ -156,9 +328,27 @@ pub enum SaveEditorDataError {
 
 #[tauri::command]
 pub async fn save_editor_data(
+    app: AppHandle,
     editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
 ) -> Result<(), SaveEditorDataError> {
-    let lock = editor_data.lock().await;
+    let json_data_lock = json_data.lock().await;
+    let mut lock = editor_data.lock().await;
+
+    if let Some(json_data) = json_data_lock.deref() {
+        let names: Vec<ProjectName> =
+            lock.loaded_projects.keys().cloned().collect();
+
+        for name in names {
+            if let Err(e) = update_project_thumbnail(&mut lock, &name, json_data)
+            {
+                warn!("Failed to update thumbnail of {}: {}", name, e);
+            }
+        }
+
+        editor_data_changes.notify(&app, &[EditorDataScope::ProjectList]);
+    }
 
     let saver = ProgramDataSaver {
         path: lock.config.config_path.clone(),
@@ -172,18 +362,170 @@ pub async fn save_editor_data(
     Ok(())
 }
 
+/// Changes the game version exported mapgen has to be compatible with
+#[tauri::command]
+pub async fn set_export_target(
+    target: ExportTarget,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+) -> Result<(), SaveEditorDataError> {
+    let mut lock = editor_data.lock().await;
+    lock.config.export_target = target;
+
+    let saver = ProgramDataSaver {
+        path: lock.config.config_path.clone(),
+    };
+
+    saver
+        .save(&lock)
+        .await
+        .map_err(|e| SaveEditorDataError::SaveFailed(e.to_string()))?;
+
+    editor_data_changes.notify(&app, &[EditorDataScope::Config]);
+
+    Ok(())
+}
+
+/// Changes the values new `place_*` entries are created with
+#[tauri::command]
+pub async fn set_place_defaults(
+    defaults: PlaceDefaults,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+) -> Result<(), SaveEditorDataError> {
+    let mut lock = editor_data.lock().await;
+    lock.config.place_defaults = defaults;
+
+    let saver = ProgramDataSaver {
+        path: lock.config.config_path.clone(),
+    };
+
+    saver
+        .save(&lock)
+        .await
+        .map_err(|e| SaveEditorDataError::SaveFailed(e.to_string()))?;
+
+    editor_data_changes.notify(&app, &[EditorDataScope::Config]);
+
+    Ok(())
+}
+
+/// Changes the season whose variants of the tiles are drawn and renders the
+/// maps again with them
+#[tauri::command]
+pub async fn set_season(
+    season: Season,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<(), SaveEditorDataError> {
+    let mut lock = editor_data.lock().await;
+    lock.config.season = season;
+
+    let mut tilesheet_lock = tilesheet.lock().await;
+    if let Some(tilesheet) = tilesheet_lock.as_mut() {
+        tilesheet.set_season(season);
+    }
+
+    let saver = ProgramDataSaver {
+        path: lock.config.config_path.clone(),
+    };
+
+    saver
+        .save(&lock)
+        .await
+        .map_err(|e| SaveEditorDataError::SaveFailed(e.to_string()))?;
+
+    render_cache.invalidate_all();
+    emit_tileset_changed(
+        &app,
+        lock.config.selected_tileset.clone(),
+        lock.config.rendered_tilesheet(tilesheet_lock.as_ref()),
+    );
+    editor_data_changes.notify(&app, &[EditorDataScope::Config]);
+
+    Ok(())
+}
+
+/// Switches between drawing the maps with the selected tileset and drawing
+/// them with the symbols and colors of the tiles
+#[tauri::command]
+pub async fn set_ascii_mode(
+    enabled: bool,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<(), SaveEditorDataError> {
+    let mut lock = editor_data.lock().await;
+    lock.config.ascii_mode = enabled;
+
+    let saver = ProgramDataSaver {
+        path: lock.config.config_path.clone(),
+    };
+
+    saver
+        .save(&lock)
+        .await
+        .map_err(|e| SaveEditorDataError::SaveFailed(e.to_string()))?;
+
+    let tilesheet_lock = tilesheet.lock().await;
+
+    render_cache.invalidate_all();
+    emit_tileset_changed(
+        &app,
+        lock.config.selected_tileset.clone(),
+        lock.config.rendered_tilesheet(tilesheet_lock.as_ref()),
+    );
+    editor_data_changes.notify(&app, &[EditorDataScope::Config]);
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn close_project(
     app: AppHandle,
     name: ProjectName,
     editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+    project_watchers: State<'_, ProjectWatchers>,
+    file_watcher: State<'_, Mutex<Option<tokio::task::JoinHandle<()>>>>,
 ) -> Result<(), ()> {
+    // Renders which are still running for this project are discarded
+    project_generations.advance(&name);
+    project_watchers.remove(&name);
+
+    let json_data_lock = json_data.lock().await;
     let mut editor_data_lock = editor_data.lock().await;
+    render_cache.invalidate_project(&name);
+
+    // The live viewer must not request reloads after its tab is gone
+    if editor_data_lock.opened_project.as_ref() == Some(&name) {
+        if let Some(watcher) = file_watcher.lock().await.take() {
+            watcher.abort();
+        }
+    }
+
+    if let Some(json_data) = json_data_lock.deref() {
+        if let Err(e) =
+            update_project_thumbnail(&mut editor_data_lock, &name, json_data)
+        {
+            warn!("Failed to update thumbnail of {}: {}", name, e);
+        }
+    }
 
     match editor_data_lock.opened_project.clone() {
         None => {},
         Some(name) => {
-            app.emit(events::TAB_REMOVED, name).unwrap();
+            app.emit_event(TabRemovedEvent { name }).unwrap();
         },
     }
 
@@ -197,8 +539,10 @@ pub async fn close_project(
 
     saver.save(&editor_data_lock).await.unwrap();
 
-    app.emit(events::EDITOR_DATA_CHANGED, editor_data_lock.clone())
-        .unwrap();
+    editor_data_changes.notify(
+        &app,
+        &[EditorDataScope::OpenedProject, EditorDataScope::ProjectList],
+    );
 
     Ok(())
 }
@@ -220,11 +564,13 @@ pub async fn open_recent_project(
     name: ProjectName,
     app: AppHandle,
     editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
     json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    project_generations: State<'_, ProjectGenerations>,
 ) -> Result<(), OpenProjectError> {
-    let mut editor_data_lock = editor_data.lock().await;
     let json_data_lock = json_data.lock().await;
     let json_data = get_json_data(&json_data_lock)?;
+    let mut editor_data_lock = editor_data.lock().await;
 
     let recent_project = editor_data_lock
         .recent_projects
@@ -239,57 +585,62 @@ pub async fn open_recent_project(
     )
     .map_err(|_| OpenProjectError::InvalidContent)?;
 
-    match &project.ty {
-        ProjectType::MapEditor(_) => unimplemented!(),
-        ProjectType::LiveViewer(lvd) => {
-            let mut map_data_collection =
-                match get_map_data_collection_from_live_viewer_data(&lvd).await
-                {
-                    Ok(v) => v,
-                    Err(e) => {
-                        warn!("Failed to load map data for project; {}", e);
-                        return Err(OpenProjectError::InvalidContent);
-                    },
-                };
-
-            map_data_collection.iter_mut().for_each(|(_, m)| {
-                match m.calculate_parameters(&json_data.palettes) {
-                    Ok(_) => {},
-                    Err(e) => {
-                        warn!("{}", e);
-                    },
-                }
-            });
+    let mut map_data_collection = match load_project_maps(&project.ty).await {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Failed to load map data for project; {}", e);
+            return Err(OpenProjectError::InvalidContent);
+        },
+    };
 
-            project.maps = map_data_collection;
+    if let Some(context) = &project.neighbor_context {
+        if let Err(e) = surround_with_neighbors(
+            &mut map_data_collection,
+            context,
+            json_data,
+        ) {
+            warn!("Failed to surround the project with neighbors; {}", e);
+        }
+    }
 
-            app.emit(
-                events::TAB_CREATED,
-                Tab {
-                    name: project.name.clone(),
-                    tab_type: TabType::LiveViewer,
-                },
-            )
-            .unwrap();
+    let project_palettes = project.project_palettes();
 
-            editor_data_lock
-                .openable_projects
-                .insert(project.name.clone());
+    map_data_collection.iter_mut().for_each(|(_, m)| {
+        m.set_simulated_neighbors(&project.simulated_neighbors);
+        m.set_project_palettes(&project_palettes);
 
-            editor_data_lock
-                .loaded_projects
-                .insert(project.name.clone(), project);
+        match m.calculate_parameters(&json_data.palettes) {
+            Ok(_) => {},
+            Err(e) => {
+                warn!("{}", e);
+            },
+        }
+    });
 
-            let saver = ProgramDataSaver {
-                path: editor_data_lock.config.config_path.clone(),
-            };
+    project.maps = map_data_collection;
+    project_generations.advance(&project.name);
 
-            saver.save(&editor_data_lock).await.unwrap();
+    app.emit_event(Tab {
+        name: project.name.clone(),
+        tab_type: project.ty.tab_type(),
+    })
+    .unwrap();
 
-            app.emit(events::EDITOR_DATA_CHANGED, editor_data_lock.clone())
-                .unwrap();
-        },
-    }
+    editor_data_lock
+        .openable_projects
+        .insert(project.name.clone());
+
+    editor_data_lock
+        .loaded_projects
+        .insert(project.name.clone(), project);
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    saver.save(&editor_data_lock).await.unwrap();
+
+    editor_data_changes.notify(&app, &[EditorDataScope::ProjectList]);
 
     Ok(())
 }
@@ -299,19 +650,20 @@ pub async fn open_project(
     name: String,
     app: AppHandle,
     editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
     file_watcher: State<'_, Mutex<Option<tokio::task::JoinHandle<()>>>>,
 ) -> Result<(), ()> {
+    let mut editor_data_lock = editor_data.lock().await;
+
     let mut file_watcher_lock = file_watcher.lock().await;
     match file_watcher_lock.deref() {
         None => {},
         Some(s) => s.abort(),
     }
 
-    let mut editor_data_lock = editor_data.lock().await;
     editor_data_lock.opened_project = Some(name.clone());
 
-    app.emit(events::EDITOR_DATA_CHANGED, editor_data_lock.clone())
-        .unwrap();
+    editor_data_changes.notify(&app, &[EditorDataScope::OpenedProject]);
 
     let project = match editor_data_lock.loaded_projects.get(&name) {
         None => {
@@ -324,49 +676,23 @@ pub async fn open_project(
     match &project.ty {
         ProjectType::MapEditor(_) => {},
         ProjectType::LiveViewer(lvd) => {
-            app.emit(UPDATE_LIVE_VIEWER, {}).unwrap();
-
-            let lvd_clone = lvd.clone();
-
-            let join_handle = tokio::spawn(async move {
-                info!("Spawning File Watcher for Live Viewer");
-
-                let (tx, mut rx) = tokio::sync::mpsc::channel(1);
-
-                // Thx -> https://github.com/notify-rs/notify/blob/d7e22791faffb7bd9bd10f031c260ae019d7f474/examples/async_monitor.rs
-                // And -> https://docs.rs/notify-debouncer-full/latest/notify_debouncer_full/
-                let mut debouncer = new_debouncer(
-                    Duration::from_millis(100),
-                    None,
-                    move |res| {
-                        block_on(async { tx.send(res).await.unwrap() });
-                    },
-                )
-                .unwrap();
-
-                let mapgen_paths = match lvd_clone {
-                    LiveViewerData::Terrain {
-                        mapgen_file_paths, ..
-                    } => mapgen_file_paths,
-                    LiveViewerData::Special {
-                        mapgen_file_paths, ..
-                    } => mapgen_file_paths,
-                };
-
-                for path in mapgen_paths.iter() {
-                    debouncer
-                        .watch(path, notify::RecursiveMode::NonRecursive)
-                        .unwrap();
-                }
-
-                while let Some(Ok(_)) = rx.recv().await {
-                    info!("Reloading Project");
-                    app.emit(UPDATE_LIVE_VIEWER, {}).unwrap()
-                }
-            });
+            // The watcher reloads the project once it started
+            let join_handle = spawn_project_watcher(
+                app.clone(),
+                name.clone(),
+                lvd.watched_paths(),
+            );
             file_watcher_lock.replace(join_handle);
         },
     }
 
+    // The CDDA data has to be locked before the editor data, so the mods of
+    // the project are loaded after this command released its lock
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = load_project_mods(&app).await {
+            app.emit_event(ToastMessage::error(e.to_string())).unwrap();
+        }
+    });
+
     Ok(())
 }
@@ -1,30 +1,41 @@
+pub mod changes;
+pub mod generations;
 pub mod handlers;
 pub mod io;
 
 use crate::data::io::DeserializedCDDAJsonData;
-use crate::data::palettes::Palettes;
-use crate::data::TileLayer;
+use crate::data::palettes::{CDDAPaletteIntermediate, Palettes};
+use crate::data::region_settings::CDDARegionSettings;
+use crate::data::{replace_region_setting, TileLayer};
+use crate::features::connect_groups::CustomConnectGroups;
+use crate::features::map::export::ExportTarget;
 use crate::features::map::importing::{
-    OvermapSpecialImporter, OvermapSpecialImporterError, SingleMapDataImporter,
-    SingleMapDataImporterError,
+    LinearTerrainImporter, OvermapSpecialImporter, OvermapSpecialImporterError,
+    SingleMapDataImporter, SingleMapDataImporterError,
 };
 use crate::features::map::{
-    CalculateParametersError, GetMappedCDDAIdsError, MapData,
-    MappedCDDAIdsForTile, DEFAULT_MAP_DATA_SIZE,
+    predecessor_mapgen_id, CalculateParametersError, GetMappedCDDAIdsError,
+    MapData, MappedCDDAIdsForTile, SimulatedNeighbors, DEFAULT_MAP_DATA_SIZE,
+    MAX_PREDECESSOR_DEPTH,
 };
+use crate::features::map_editor::{MapEditorLoadError, MapEditorLoader};
+use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+use crate::features::tileset::Season;
+use crate::features::viewer::context::NeighborContext;
 use crate::impl_serialize_for_error;
 use crate::util::{IVec3JsonKey, Load, Save, SaveError};
-use cdda_lib::types::CDDAIdentifier;
+use cdda_lib::types::{CDDAIdentifier, DistributionInner, NumberOrRange};
 use futures_lite::StreamExt;
 use glam::{IVec3, UVec2};
-use log::info;
+use log::{info, warn};
 use serde::ser::SerializeMap;
 use serde::Serializer;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::hash::Hash;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tauri::Theme;
 use thiserror::Error;
 
@@ -45,6 +56,37 @@ pub enum GetLiveViewerDataError {
 
 impl_serialize_for_error!(GetLiveViewerDataError);
 
+#[derive(Debug, Error)]
+pub enum LoadProjectMapsError {
+    #[error(transparent)]
+    LiveViewerError(#[from] GetLiveViewerDataError),
+
+    #[error(transparent)]
+    MapEditorError(#[from] MapEditorLoadError),
+}
+
+impl_serialize_for_error!(LoadProjectMapsError);
+
+/// Loads the maps of a project from the files it was created from
+pub async fn load_project_maps(
+    ty: &ProjectType,
+) -> Result<HashMap<ZLevel, MapDataCollection>, LoadProjectMapsError> {
+    let maps = match ty {
+        ProjectType::MapEditor(save_state) => {
+            let mut loader = MapEditorLoader {
+                save_state: save_state.clone(),
+            };
+
+            loader.load().await?
+        },
+        ProjectType::LiveViewer(lvd) => {
+            get_map_data_collection_from_live_viewer_data(lvd).await?
+        },
+    };
+
+    Ok(maps)
+}
+
 pub async fn get_map_data_collection_from_live_viewer_data(
     data: &LiveViewerData,
 ) -> Result<HashMap<ZLevel, MapDataCollection>, GetLiveViewerDataError> {
@@ -54,16 +96,23 @@ pub async fn get_map_data_collection_from_live_viewer_data(
         LiveViewerData::Terrain {
             om_id,
             mapgen_file_paths,
+            stacked_om_ids,
             ..
         } => {
-            let mut overmap_terrain_importer = SingleMapDataImporter {
-                om_terrain: om_id.clone(),
-                paths: mapgen_file_paths.clone(),
-            };
-
-            let collection = overmap_terrain_importer.load().await?;
             let mut map_data_collection = HashMap::new();
-            map_data_collection.insert(0, collection);
+
+            for (z, om_id) in
+                std::iter::once((&0, om_id)).chain(stacked_om_ids.iter())
+            {
+                let mut overmap_terrain_importer = SingleMapDataImporter {
+                    om_terrain: om_id.clone(),
+                    paths: mapgen_file_paths.clone(),
+                };
+
+                let collection = overmap_terrain_importer.load().await?;
+                map_data_collection.insert(*z, collection);
+            }
+
             map_data_collection
         },
         LiveViewerData::Special {
@@ -80,6 +129,41 @@ pub async fn get_map_data_collection_from_live_viewer_data(
 
             om_special_importer.load().await?
         },
+        LiveViewerData::Linear {
+            om_id,
+            mapgen_file_paths,
+            ..
+        } => {
+            let mut linear_terrain_importer = LinearTerrainImporter {
+                om_id: om_id.clone(),
+                paths: mapgen_file_paths.clone(),
+            };
+
+            let collection = linear_terrain_importer.load().await?;
+            let mut map_data_collection = HashMap::new();
+            map_data_collection.insert(0, collection);
+            map_data_collection
+        },
+        LiveViewerData::Nested {
+            nested_id: id,
+            mapgen_file_paths,
+            ..
+        }
+        | LiveViewerData::Update {
+            update_id: id,
+            mapgen_file_paths,
+            ..
+        } => {
+            let mut mapgen_importer = SingleMapDataImporter {
+                om_terrain: id.clone(),
+                paths: mapgen_file_paths.clone(),
+            };
+
+            let collection = mapgen_importer.load().await?;
+            let mut map_data_collection = HashMap::new();
+            map_data_collection.insert(0, collection);
+            map_data_collection
+        },
     };
 
     Ok(map_data_collection)
@@ -120,6 +204,10 @@ impl MappedCDDAIdContainer {
                 TileLayer::Furniture => {
                     v.furniture.clone().map(|v| v.tilesheet_id.id)
                 },
+                TileLayer::Trap => v.trap.clone().map(|v| v.tilesheet_id.id),
+                TileLayer::Vehicle => {
+                    v.vehicle.clone().map(|v| v.tilesheet_id.id)
+                },
                 TileLayer::Monster => {
                     v.monster.clone().map(|v| v.tilesheet_id.id)
                 },
@@ -128,6 +216,39 @@ impl MappedCDDAIdContainer {
             .flatten()
     }
 
+    /// A copy of the container with every regional id like
+    /// `t_region_groundcover` replaced by an id of the `region_settings`
+    pub fn with_region_settings_replaced(
+        &self,
+        region_settings: &CDDARegionSettings,
+    ) -> Self {
+        let mut ids = self.ids.clone();
+
+        for tile in ids.values_mut() {
+            for mapped_id in [
+                &mut tile.terrain,
+                &mut tile.furniture,
+                &mut tile.trap,
+                &mut tile.vehicle,
+                &mut tile.monster,
+                &mut tile.field,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                mapped_id.tilesheet_id.id = replace_region_setting(
+                    &mapped_id.tilesheet_id.id,
+                    region_settings,
+                );
+            }
+        }
+
+        Self { ids }
+    }
+
+    /// The ids next to the `coordinates` on the `layer`. The ids of the whole
+    /// z-level are in the container, so tiles at the border of an overmap
+    /// terrain see the tiles of the adjacent overmap terrain
     pub fn get_adjacent_identifiers(
         &self,
         coordinates: IVec3,
@@ -161,12 +282,25 @@ pub enum ProjectType {
     LiveViewer(LiveViewerData),
 }
 
+impl ProjectType {
+    pub fn tab_type(&self) -> TabType {
+        match self {
+            ProjectType::MapEditor(_) => TabType::MapEditor,
+            ProjectType::LiveViewer(_) => TabType::LiveViewer,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LiveViewerData {
     Terrain {
         mapgen_file_paths: Vec<PathBuf>,
         project_name: String,
         om_id: CDDAIdentifier,
+        /// Overmap terrains which are stacked above or below the `om_id`,
+        /// like roofs and basements
+        #[serde(default)]
+        stacked_om_ids: BTreeMap<ZLevel, CDDAIdentifier>,
     },
     Special {
         mapgen_file_paths: Vec<PathBuf>,
@@ -174,6 +308,53 @@ pub enum LiveViewerData {
         project_name: String,
         om_id: CDDAIdentifier,
     },
+    /// Preview of every connection variant of a linear overmap terrain
+    Linear {
+        mapgen_file_paths: Vec<PathBuf>,
+        project_name: String,
+        om_id: CDDAIdentifier,
+    },
+    /// A nested mapgen on its own, without a map it is placed into
+    Nested {
+        mapgen_file_paths: Vec<PathBuf>,
+        project_name: String,
+        nested_id: CDDAIdentifier,
+    },
+    /// An update mapgen on its own, without a map it is applied to
+    Update {
+        mapgen_file_paths: Vec<PathBuf>,
+        project_name: String,
+        update_id: CDDAIdentifier,
+    },
+}
+
+impl LiveViewerData {
+    /// The files the live viewer is reloaded from when they change
+    pub fn watched_paths(&self) -> Vec<PathBuf> {
+        match self {
+            LiveViewerData::Terrain {
+                mapgen_file_paths, ..
+            } => mapgen_file_paths.clone(),
+            LiveViewerData::Special {
+                mapgen_file_paths,
+                om_file_paths,
+                ..
+            } => mapgen_file_paths
+                .iter()
+                .chain(om_file_paths.iter())
+                .cloned()
+                .collect(),
+            LiveViewerData::Linear {
+                mapgen_file_paths, ..
+            }
+            | LiveViewerData::Nested {
+                mapgen_file_paths, ..
+            }
+            | LiveViewerData::Update {
+                mapgen_file_paths, ..
+            } => mapgen_file_paths.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -186,6 +367,32 @@ pub enum ProjectSaveState {
     },
 }
 
+/// State that only affects how a project is rendered and not the map data
+/// itself
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProjectRenderState {
+    /// Seed which is used to pick the weighted sprite variants of every tile
+    pub variant_seed: u64,
+
+    /// Indices of the sprite variants which were pinned by the user for a
+    /// specific tile
+    #[serde(default)]
+    pub pinned_variants: HashMap<TileLayer, HashMap<IVec3JsonKey, usize>>,
+}
+
+impl ProjectRenderState {
+    pub fn get_pinned_variant(
+        &self,
+        layer: &TileLayer,
+        position: IVec3,
+    ) -> Option<usize> {
+        self.pinned_variants
+            .get(layer)
+            .and_then(|p| p.get(&IVec3JsonKey(position)))
+            .copied()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Project {
     pub name: String,
@@ -195,9 +402,177 @@ pub struct Project {
 
     pub size: UVec2,
     pub ty: ProjectType,
+
+    #[serde(default)]
+    pub render_state: ProjectRenderState,
+
+    /// Update mapgens, like the expansions of a faction camp, which are applied
+    /// in order on top of the maps of a z-level
+    #[serde(default)]
+    pub camp_upgrades: HashMap<ZLevel, Vec<CDDAIdentifier>>,
+
+    /// Camp upgrades which stay in the list of their z-level but are not
+    /// applied, so single upgrades can be toggled off and on again
+    #[serde(default)]
+    pub disabled_camp_upgrades: HashMap<ZLevel, HashSet<CDDAIdentifier>>,
+
+    /// Palettes which were imported from vanilla or mod files into this
+    /// project
+    #[serde(default)]
+    pub palettes: Vec<CDDAPaletteIntermediate>,
+
+    /// Whether the maps of a map editor project were changed since they were
+    /// last saved
+    #[serde(skip_deserializing)]
+    pub dirty: bool,
+
+    /// Typical neighbors the maps of a live viewer are surrounded with
+    #[serde(default)]
+    pub neighbor_context: Option<NeighborContext>,
+
+    /// The overmap terrains and joins next to the maps which decide the
+    /// conditions of nested chunks
+    #[serde(default)]
+    pub simulated_neighbors: SimulatedNeighbors,
+
+    /// The z-level which is currently shown and edited
+    #[serde(default)]
+    pub active_z_level: ZLevel,
+
+    #[serde(default)]
+    pub custom_connect_groups: CustomConnectGroups,
+
+    /// The region settings regional ids like `t_region_groundcover` are
+    /// resolved with, the `default` region is used if none is chosen
+    #[serde(default)]
+    pub region: Option<CDDAIdentifier>,
+
+    /// Ids of the mods which are loaded on top of the core data while the
+    /// project is opened
+    #[serde(default)]
+    pub active_mods: Vec<CDDAIdentifier>,
 }
 
 impl Project {
+    /// Maps the ids of the maps on every z-level of the project, with the
+    /// camp upgrades of each z-level applied
+    pub fn get_mapped_cdda_ids(
+        &self,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Result<HashMap<ZLevel, MappedCDDAIdContainer>, GetMappedCDDAIdsError>
+    {
+        let mut mapped_cdda_ids = HashMap::new();
+
+        for (z, map_collection) in self.maps.iter() {
+            let update_ids = self.enabled_camp_upgrades(z);
+
+            mapped_cdda_ids.insert(
+                *z,
+                map_collection.get_mapped_cdda_ids(
+                    json_data,
+                    self.region.as_ref(),
+                    *z,
+                    &update_ids,
+                )?,
+            );
+        }
+
+        Ok(mapped_cdda_ids)
+    }
+
+    /// The camp upgrades of the z-level `z` which are not disabled, in the
+    /// order they are applied in
+    pub fn enabled_camp_upgrades(&self, z: &ZLevel) -> Vec<CDDAIdentifier> {
+        let disabled = self.disabled_camp_upgrades.get(z);
+
+        self.camp_upgrades
+            .get(z)
+            .map(|upgrades| {
+                upgrades
+                    .iter()
+                    .filter(|id| !disabled.is_some_and(|d| d.contains(*id)))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The seed the maps of the project are generated with
+    pub fn seed(&self) -> u64 {
+        self.maps
+            .values()
+            .flat_map(|c| c.maps.values())
+            .next()
+            .map(|m| m.config.seed)
+            .unwrap_or_default()
+    }
+
+    /// Generates every map of the project with the `seed`, which also
+    /// calculates their parameters again
+    pub fn set_seed(
+        &mut self,
+        seed: u64,
+        all_palettes: &Palettes,
+    ) -> Result<(), CalculateParametersError> {
+        for (_, map_collection) in self.maps.iter_mut() {
+            map_collection.set_seed(seed);
+            map_collection.calculate_parameters(all_palettes)?;
+        }
+
+        Ok(())
+    }
+
+    /// The palettes which were created in or imported into the project
+    pub fn project_palettes(&self) -> Arc<Palettes> {
+        let palettes: Palettes = self
+            .palettes
+            .iter()
+            .map(|p| (p.id.clone(), p.clone().into()))
+            .collect();
+
+        Arc::new(palettes)
+    }
+
+    /// Makes the palettes of the project available to all of its maps, which
+    /// has to happen again whenever the palettes change
+    pub fn share_palettes(&mut self) {
+        let palettes = self.project_palettes();
+
+        for (_, collection) in self.maps.iter_mut() {
+            collection.set_project_palettes(&palettes);
+        }
+    }
+
+    /// Adds an empty z-level with the same maps as the z-level 0
+    pub fn add_z_level(&mut self, z: ZLevel) {
+        let coordinates: Vec<MapCoordinates> = self
+            .maps
+            .get(&0)
+            .map(|c| c.maps.keys().copied().collect())
+            .unwrap_or_else(|| vec![MapCoordinates::ZERO]);
+
+        let mut map_data = MapData::default();
+        map_data.config.seed = self.seed();
+        map_data.set_simulated_neighbors(&self.simulated_neighbors);
+        map_data.project_palettes = self.project_palettes();
+
+        // Everything above the ground is open air unless something is placed
+        if z > 0 {
+            map_data.fill = Some(DistributionInner::Normal(
+                CDDAIdentifier::from("t_open_air"),
+            ));
+        }
+
+        let collection = MapDataCollection {
+            maps: coordinates
+                .into_iter()
+                .map(|c| (c, map_data.clone()))
+                .collect(),
+        };
+
+        self.maps.entry(z).or_insert(collection);
+    }
+
     pub fn new(name: String, size: UVec2, ty: ProjectType) -> Self {
         let mut maps = HashMap::new();
         let map_collection = MapDataCollection::default();
@@ -208,6 +583,17 @@ impl Project {
             maps,
             size,
             ty,
+            render_state: ProjectRenderState::default(),
+            camp_upgrades: HashMap::new(),
+            disabled_camp_upgrades: HashMap::new(),
+            palettes: vec![],
+            dirty: false,
+            neighbor_context: None,
+            simulated_neighbors: SimulatedNeighbors::default(),
+            active_z_level: 0,
+            custom_connect_groups: CustomConnectGroups::default(),
+            region: None,
+            active_mods: vec![],
         }
     }
 }
@@ -223,6 +609,17 @@ impl Default for Project {
             maps,
             size: DEFAULT_MAP_DATA_SIZE,
             ty: ProjectType::MapEditor(ProjectSaveState::Unsaved),
+            render_state: ProjectRenderState::default(),
+            camp_upgrades: HashMap::new(),
+            disabled_camp_upgrades: HashMap::new(),
+            palettes: vec![],
+            dirty: false,
+            neighbor_context: None,
+            simulated_neighbors: SimulatedNeighbors::default(),
+            active_z_level: 0,
+            custom_connect_groups: CustomConnectGroups::default(),
+            region: None,
+            active_mods: vec![],
         }
     }
 }
@@ -247,67 +644,74 @@ impl MapDataCollection {
         )
     }
 
+    /// Calculates the parameters of the whole predecessor chain of every map
+    /// in this collection. Missing predecessors are skipped, since they are
+    /// replaced with a fallback terrain when the ids are mapped
     pub fn calculate_predecessor_parameters(
         &mut self,
         json_data: &mut DeserializedCDDAJsonData,
     ) {
         for (_, map) in self.maps.iter_mut() {
-            match &map.predecessor {
-                None => {},
-                Some(predecessor_id) => {
-                    let predecessor = json_data
-                        .overmap_terrains
-                        .get_mut(predecessor_id)
-                        .expect(
-                            format!(
-                                "Overmap terrain for Predecessor {} to exist",
-                                predecessor_id
-                            )
-                            .as_str(),
-                        );
-
-                    let predecessor_map_data = match &predecessor
-                        .mapgen
-                        .clone()
-                        .unwrap_or_default()
-                        .first_mut()
-                    {
-                        None => {
-                            // This terrain is defined in a json file, so we can just search for it
-                            json_data.map_data.get_mut(predecessor_id).expect(
-                                format!(
-                                    "Mapdata for Predecessor {} to exist",
-                                    predecessor_id
-                                )
-                                    .as_str(),
-                            )
-                        }
-                        Some(omtm) => json_data.map_data.get_mut(&omtm.builtin).expect(
-                            format!(
-                                "Hardcoded Map data for predecessor {} to exist",
-                                omtm.builtin
-                            )
-                                .as_str(),
-                        ),
-                    };
-
-                    predecessor_map_data
-                        .calculate_parameters(&json_data.palettes)
-                        .unwrap();
-                },
+            let mut predecessor = map.predecessor.clone();
+
+            for _ in 0..MAX_PREDECESSOR_DEPTH {
+                let Some(predecessor_id) = predecessor.take() else {
+                    break;
+                };
+
+                let Some(mapgen_id) =
+                    predecessor_mapgen_id(&predecessor_id, json_data)
+                else {
+                    warn!("Missing map data for predecessor {}", predecessor_id);
+                    break;
+                };
+
+                let Some(predecessor_map_data) =
+                    json_data.map_data.get_mut(&mapgen_id)
+                else {
+                    break;
+                };
+
+                if let Err(e) =
+                    predecessor_map_data.calculate_parameters(&json_data.palettes)
+                {
+                    warn!(
+                        "Could not calculate the parameters of predecessor {}, {}",
+                        predecessor_id, e
+                    );
+                }
+
+                predecessor = predecessor_map_data.predecessor.clone();
             }
         }
     }
 
+    /// Maps the ids of every map in this collection with the region settings
+    /// of the `region` and applies the update mapgens with the `update_ids` on
+    /// top of them in order
     pub fn get_mapped_cdda_ids(
         &self,
         json_data: &DeserializedCDDAJsonData,
+        region: Option<&CDDAIdentifier>,
         z: ZLevel,
+        update_ids: &[CDDAIdentifier],
     ) -> Result<MappedCDDAIdContainer, GetMappedCDDAIdsError> {
         let mut mapped_cdda_ids = HashMap::new();
 
         for (map_coords, map_data) in self.maps.iter() {
-            let mut ids = map_data.get_mapped_cdda_ids(json_data, z)?;
+            let mut ids = map_data.get_mapped_cdda_ids(json_data, region, z)?;
+
+            for update_id in update_ids {
+                let update_map_data =
+                    json_data.map_data.get(update_id).ok_or(
+                        GetMappedCDDAIdsError::MissingUpdateMapgen(
+                            update_id.0.clone(),
+                        ),
+                    )?;
+
+                update_map_data
+                    .apply_as_update(&mut ids, json_data, region, z)?;
+            }
 
             // Transform every coordinate in the hashmap
             let mut new_ids = HashMap::new();
@@ -339,6 +743,27 @@ impl MapDataCollection {
 
         Ok(())
     }
+
+    /// Generates every map of this collection with the `seed`
+    pub fn set_seed(&mut self, seed: u64) {
+        for (_, map_data) in self.maps.iter_mut() {
+            map_data.config.seed = seed;
+        }
+    }
+
+    pub fn set_simulated_neighbors(&mut self, simulated: &SimulatedNeighbors) {
+        for (_, map_data) in self.maps.iter_mut() {
+            map_data.set_simulated_neighbors(simulated);
+        }
+    }
+
+    /// Makes the `palettes` of the project available to every map of this
+    /// collection
+    pub fn set_project_palettes(&mut self, palettes: &Arc<Palettes>) {
+        for (_, map_data) in self.maps.iter_mut() {
+            map_data.project_palettes = palettes.clone();
+        }
+    }
 }
 
 impl Default for MapDataCollection {
@@ -356,6 +781,41 @@ pub struct EditorConfig {
     pub config_path: PathBuf,
     pub selected_tileset: Option<String>,
     pub theme: Theme,
+    #[serde(default)]
+    pub place_defaults: PlaceDefaults,
+    #[serde(default)]
+    pub export_target: ExportTarget,
+    /// Directories with mods which are not part of the CDDA installation
+    #[serde(default)]
+    pub user_mod_paths: Vec<PathBuf>,
+    /// The season whose variants of the tiles are drawn
+    #[serde(default)]
+    pub season: Season,
+    /// Draws every tile with the symbol and color of its definition, like
+    /// the ascii mode of the game, instead of the selected tileset
+    #[serde(default)]
+    pub ascii_mode: bool,
+}
+
+/// Values which new `place_*` entries are created with, so they do not have
+/// to be entered for every entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaceDefaults {
+    pub chance: i32,
+    pub repeat: NumberOrRange<i32>,
+    pub item_group: Option<CDDAIdentifier>,
+    pub monster_group: Option<CDDAIdentifier>,
+}
+
+impl Default for PlaceDefaults {
+    fn default() -> Self {
+        Self {
+            chance: 100,
+            repeat: NumberOrRange::Number(1),
+            item_group: None,
+            monster_group: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Error)]
@@ -384,6 +844,18 @@ impl EditorConfig {
             .ok_or(SelectedTilesetError::NoTilesetSelected)
             .map(Clone::clone)
     }
+
+    /// The tilesheet the sprites are picked from. In the ascii mode this is
+    /// [`None`], so every tile is drawn with the fallback tilesheet.
+    pub fn rendered_tilesheet<'a>(
+        &self,
+        tilesheet: Option<&'a LegacyTilesheet>,
+    ) -> Option<&'a LegacyTilesheet> {
+        match self.ascii_mode {
+            true => None,
+            false => tilesheet,
+        }
+    }
 }
 
 impl Default for EditorConfig {
@@ -394,6 +866,11 @@ impl Default for EditorConfig {
             selected_tileset: None,
             json_data_path: DEFAULT_CDDA_DATA_JSON_PATH.into(),
             theme: Theme::Dark,
+            place_defaults: PlaceDefaults::default(),
+            export_target: ExportTarget::default(),
+            user_mod_paths: vec![],
+            season: Season::default(),
+            ascii_mode: false,
         }
     }
 }
@@ -402,6 +879,11 @@ impl Default for EditorConfig {
 pub struct RecentProject {
     pub path: PathBuf,
     pub name: String,
+
+    /// Cached PNG preview of the project which is updated when the project is
+    /// saved or closed
+    #[serde(default)]
+    pub thumbnail: Option<PathBuf>,
 }
 
 impl Hash for RecentProject {
@@ -451,3 +933,79 @@ pub struct AdjacentSprites {
     pub bottom: Option<CDDAIdentifier>,
     pub left: Option<CDDAIdentifier>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::region_settings::{
+        RegionIdentifier, RegionTerrainAndFurniture,
+    };
+    use indexmap::IndexMap;
+
+    #[test]
+    fn test_adjacent_identifiers_across_map_borders() {
+        let mut terrain = IndexMap::new();
+        terrain.insert(
+            RegionIdentifier("t_region_wall".into()),
+            IndexMap::from([("t_wall".into(), 1)]),
+        );
+
+        let region_settings = CDDARegionSettings {
+            id: "default".into(),
+            default_oter: vec![],
+            default_groundcover: vec![],
+            region_terrain_and_furniture: RegionTerrainAndFurniture {
+                terrain,
+                furniture: IndexMap::new(),
+            },
+            region_item_groups: IndexMap::new(),
+            region_vehicles: IndexMap::new(),
+            river_scale: None,
+        };
+
+        let mut json_data = DeserializedCDDAJsonData::default();
+        json_data
+            .region_settings
+            .insert("default".into(), region_settings.clone());
+
+        let filled_map = |fill: &str| MapData {
+            fill: Some(DistributionInner::Normal(fill.into())),
+            ..MapData::default()
+        };
+
+        // The wall of the second map is a regional id, which only becomes a
+        // wall once the region settings are applied
+        let collection = MapDataCollection {
+            maps: HashMap::from([
+                (UVec2::new(0, 0), filled_map("t_wall")),
+                (UVec2::new(1, 0), filled_map("t_region_wall")),
+            ]),
+        };
+
+        let ids = collection
+            .get_mapped_cdda_ids(&json_data, None, 0, &[])
+            .unwrap()
+            .with_region_settings_replaced(&region_settings);
+
+        // The last column of the first map is next to the first column of the
+        // second map
+        let last_column = ids.get_adjacent_identifiers(
+            IVec3::new(23, 5, 0),
+            &TileLayer::Terrain,
+        );
+        assert_eq!(last_column.right, Some("t_wall".into()));
+
+        let first_column = ids.get_adjacent_identifiers(
+            IVec3::new(24, 5, 0),
+            &TileLayer::Terrain,
+        );
+        assert_eq!(first_column.left, Some("t_wall".into()));
+
+        // Nothing is next to the outer border of the collection
+        let outer_column = ids.get_adjacent_identifiers(
+            IVec3::new(47, 5, 0),
+            &TileLayer::Terrain,
+        );
+        assert_eq!(outer_column.right, None);
+    }
+}
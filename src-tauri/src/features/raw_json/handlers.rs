@@ -0,0 +1,128 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::map::importing::LinearTerrainImporter;
+use crate::features::program_data::{EditorData, LiveViewerData, ProjectType};
+use crate::features::raw_json::{
+    find_raw_json, RawJson, RawJsonError, RawJsonTarget,
+};
+use crate::impl_serialize_for_error;
+use crate::util::{
+    get_current_project, get_json_data, CDDADataError, GetCurrentProjectError,
+};
+use serde::{Serialize, Serializer};
+use tauri::async_runtime::Mutex;
+use tauri::State;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum GetRawJsonError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    RawJsonError(#[from] RawJsonError),
+
+    #[error("The map of the current project is not loaded from a json file")]
+    NotBackedByJson,
+
+    #[error("Failed to serialize the palette, {0}")]
+    SerializeError(#[from] serde_json::Error),
+}
+
+impl_serialize_for_error!(GetRawJsonError);
+
+/// Returns the json object backing the current map, a palette or the
+/// definition of an id exactly as it is written in its source file
+#[tauri::command]
+pub async fn get_raw_json(
+    target: RawJsonTarget,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<RawJson, GetRawJsonError> {
+    let editor_data_lock = editor_data.lock().await;
+
+    let (json_type, id) = match target {
+        RawJsonTarget::CurrentMap => {
+            let project = get_current_project(&editor_data_lock)?;
+
+            let raw_json = match &project.ty {
+                ProjectType::MapEditor(_) => {
+                    return Err(GetRawJsonError::NotBackedByJson);
+                },
+                ProjectType::LiveViewer(LiveViewerData::Terrain {
+                    mapgen_file_paths,
+                    om_id,
+                    ..
+                }) => find_raw_json(mapgen_file_paths, "mapgen", om_id)?,
+                ProjectType::LiveViewer(LiveViewerData::Special {
+                    om_file_paths,
+                    om_id,
+                    ..
+                }) => find_raw_json(om_file_paths, "overmap_special", om_id)?,
+                ProjectType::LiveViewer(LiveViewerData::Linear {
+                    mapgen_file_paths,
+                    om_id,
+                    ..
+                }) => LinearTerrainImporter::mapgen_ids(om_id)
+                    .iter()
+                    .map(|id| find_raw_json(mapgen_file_paths, "mapgen", id))
+                    // Show the first mapgen of the terrain which exists
+                    .find(|r| !matches!(r, Err(RawJsonError::NotFound { .. })))
+                    .unwrap_or_else(|| {
+                        Err(RawJsonError::NotFound {
+                            json_type: "mapgen".to_string(),
+                            id: om_id.clone(),
+                        })
+                    })?,
+                ProjectType::LiveViewer(LiveViewerData::Nested {
+                    mapgen_file_paths,
+                    nested_id: id,
+                    ..
+                })
+                | ProjectType::LiveViewer(LiveViewerData::Update {
+                    mapgen_file_paths,
+                    update_id: id,
+                    ..
+                }) => find_raw_json(mapgen_file_paths, "mapgen", id)?,
+            };
+
+            return Ok(raw_json);
+        },
+        RawJsonTarget::Palette { id } => {
+            // Palettes which were imported into the project do not exist in
+            // any file anymore
+            let imported_palette = get_current_project(&editor_data_lock)
+                .ok()
+                .and_then(|p| p.palettes.iter().find(|p| p.id == id));
+
+            if let Some(palette) = imported_palette {
+                return Ok(RawJson {
+                    source: None,
+                    json: serde_json::to_string_pretty(palette)?,
+                });
+            }
+
+            ("palette".to_string(), id)
+        },
+        RawJsonTarget::Definition { json_type, id } => (json_type, id),
+    };
+
+    // The json data has to be locked before the editor data
+    drop(editor_data_lock);
+
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let source = json_data
+        .sources
+        .get(&json_type)
+        .and_then(|sources| sources.get(&id))
+        .ok_or_else(|| RawJsonError::NotFound {
+            json_type: json_type.clone(),
+            id: id.clone(),
+        })?;
+
+    Ok(find_raw_json(&[source.clone()], &json_type, &id)?)
+}
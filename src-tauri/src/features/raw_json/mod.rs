@@ -0,0 +1,103 @@
+pub(crate) mod handlers;
+
+use cdda_lib::types::CDDAIdentifier;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Keys under which CDDA json objects store their ids
+pub const ID_KEYS: [&str; 4] =
+    ["id", "om_terrain", "nested_mapgen_id", "update_mapgen_id"];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum RawJsonTarget {
+    /// The mapgen or overmap special the currently opened project shows
+    CurrentMap,
+    Palette {
+        id: CDDAIdentifier,
+    },
+    /// The definition of any loaded object, like a terrain or a vehicle.
+    /// `json_type` is the `type` the object has in the json files.
+    Definition {
+        json_type: String,
+        id: CDDAIdentifier,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RawJson {
+    /// The file the object was found in. This is None for objects which only
+    /// exist in the project itself, like imported palettes
+    pub source: Option<PathBuf>,
+    /// The pretty-printed json object
+    pub json: String,
+}
+
+#[derive(Debug, Error)]
+pub enum RawJsonError {
+    #[error("Failed to read the file {0}, {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("The file {0} is not a valid CDDA json file, {1}")]
+    InvalidJson(PathBuf, serde_json::Error),
+
+    #[error("Could not find the {json_type} with the id {id}")]
+    NotFound {
+        json_type: String,
+        id: CDDAIdentifier,
+    },
+}
+
+pub fn value_contains_id(value: &Value, id: &CDDAIdentifier) -> bool {
+    match value {
+        Value::String(s) => s == &id.0,
+        Value::Array(values) => values.iter().any(|v| value_contains_id(v, id)),
+        _ => false,
+    }
+}
+
+/// Searches the `paths` for the object with the `json_type` which defines the
+/// `id` and returns it exactly as it is written in the file
+pub fn find_raw_json(
+    paths: &[PathBuf],
+    json_type: &str,
+    id: &CDDAIdentifier,
+) -> Result<RawJson, RawJsonError> {
+    for path in paths {
+        let content = fs::read_to_string(path)
+            .map_err(|e| RawJsonError::Io(path.clone(), e))?;
+        let values: Vec<Value> = serde_json::from_str(&content)
+            .map_err(|e| RawJsonError::InvalidJson(path.clone(), e))?;
+
+        let found = values.into_iter().find(|v| {
+            let matches_type = v
+                .get("type")
+                .and_then(Value::as_str)
+                .is_some_and(|t| t.eq_ignore_ascii_case(json_type));
+
+            matches_type
+                && ID_KEYS
+                    .iter()
+                    .filter_map(|key| v.get(key))
+                    .any(|v| value_contains_id(v, id))
+        });
+
+        if let Some(found) = found {
+            let json = serde_json::to_string_pretty(&found)
+                .map_err(|e| RawJsonError::InvalidJson(path.clone(), e))?;
+
+            return Ok(RawJson {
+                source: Some(path.clone()),
+                json,
+            });
+        }
+    }
+
+    Err(RawJsonError::NotFound {
+        json_type: json_type.to_string(),
+        id: id.clone(),
+    })
+}
@@ -0,0 +1,35 @@
+use crate::features::refactor::{
+    IdentifierRename, RenameIdentifierError, RenamedFile,
+};
+use cdda_lib::types::CDDAIdentifier;
+use log::info;
+use std::path::PathBuf;
+
+/// Renames the `old_id` to the `new_id` in every json file of the mod at
+/// `mod_path`. With `dry_run` only the affected files are returned.
+#[tauri::command]
+pub async fn rename_identifier(
+    mod_path: PathBuf,
+    old_id: CDDAIdentifier,
+    new_id: CDDAIdentifier,
+    dry_run: bool,
+) -> Result<Vec<RenamedFile>, RenameIdentifierError> {
+    let rename = IdentifierRename {
+        mod_path,
+        old_id,
+        new_id,
+    };
+
+    let files = rename.run(dry_run).await?;
+
+    if !dry_run {
+        info!(
+            "Renamed {} to {} in {} files",
+            rename.old_id,
+            rename.new_id,
+            files.len()
+        );
+    }
+
+    Ok(files)
+}
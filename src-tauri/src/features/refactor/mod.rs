@@ -0,0 +1,330 @@
+pub(crate) mod handlers;
+
+use crate::features::raw_json::{value_contains_id, ID_KEYS};
+use crate::impl_serialize_for_error;
+use async_walkdir::WalkDir;
+use cdda_lib::types::CDDAIdentifier;
+use futures_lite::StreamExt;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Suffixes with which ids are referenced besides the plain id, like the
+/// rotated overmap terrains in overmap specials
+const REFERENCE_SUFFIXES: [&str; 5] =
+    ["", "_north", "_east", "_south", "_west"];
+
+#[derive(Debug, Error)]
+pub enum RenameIdentifierError {
+    #[error("Failed to access the file {0}, {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("Failed to read the mod directory, {0}")]
+    WalkError(String),
+
+    #[error("The file {0} is not a valid CDDA json file, {1}")]
+    InvalidJson(PathBuf, serde_json::Error),
+
+    #[error("{0} is not a valid id")]
+    InvalidIdentifier(CDDAIdentifier),
+
+    #[error("The id {0} is not defined in the mod")]
+    NotDefined(CDDAIdentifier),
+
+    #[error("The id {0} is already defined in the mod")]
+    AlreadyDefined(CDDAIdentifier),
+}
+
+impl_serialize_for_error!(RenameIdentifierError);
+
+/// A file in which references to the renamed id were found
+#[derive(Debug, Clone, Serialize)]
+pub struct RenamedFile {
+    pub path: PathBuf,
+    pub occurrences: usize,
+}
+
+/// Renames an id which is defined in a mod, like a terrain, a palette, a
+/// nested chunk or an overmap terrain, and all references to it in the json
+/// files of the mod.
+///
+/// References are replaced textually so the formatting of the files is kept.
+/// Every json string which is exactly the id, or the id with a rotation
+/// suffix, counts as a reference.
+pub struct IdentifierRename {
+    pub mod_path: PathBuf,
+    pub old_id: CDDAIdentifier,
+    pub new_id: CDDAIdentifier,
+}
+
+impl IdentifierRename {
+    async fn json_files(&self) -> Result<Vec<PathBuf>, RenameIdentifierError> {
+        let mut walkdir = WalkDir::new(&self.mod_path);
+        let mut paths = vec![];
+
+        while let Some(entry) = walkdir.next().await {
+            let path = entry
+                .map_err(|e| RenameIdentifierError::WalkError(e.to_string()))?
+                .path();
+
+            if path.extension().is_some_and(|e| e == "json") {
+                paths.push(path);
+            }
+        }
+
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn defines(value: &Value, id: &CDDAIdentifier) -> bool {
+        match value {
+            Value::Array(values) => values.iter().any(|v| Self::defines(v, id)),
+            Value::Object(_) => ID_KEYS
+                .iter()
+                .filter_map(|key| value.get(key))
+                .any(|v| value_contains_id(v, id)),
+            _ => false,
+        }
+    }
+
+    fn replace_references(&self, content: &str) -> (String, usize) {
+        let mut content = content.to_string();
+        let mut occurrences = 0;
+
+        for suffix in REFERENCE_SUFFIXES {
+            let old = format!("\"{}{}\"", self.old_id.0, suffix);
+            let new = format!("\"{}{}\"", self.new_id.0, suffix);
+
+            occurrences += content.matches(&old).count();
+            content = content.replace(&old, &new);
+        }
+
+        (content, occurrences)
+    }
+
+    /// Returns the files which reference the id. The files are only changed
+    /// if `dry_run` is false.
+    pub async fn run(
+        &self,
+        dry_run: bool,
+    ) -> Result<Vec<RenamedFile>, RenameIdentifierError> {
+        let is_valid = |id: &CDDAIdentifier| {
+            !id.0.is_empty() && !id.0.contains(['"', '\\'])
+        };
+
+        for id in [&self.old_id, &self.new_id] {
+            if !is_valid(id) {
+                return Err(RenameIdentifierError::InvalidIdentifier(
+                    id.clone(),
+                ));
+            }
+        }
+
+        let mut changes = vec![];
+        let mut old_defined = false;
+
+        for path in self.json_files().await? {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| RenameIdentifierError::Io(path.clone(), e))?;
+            let value: Value = serde_json::from_str(&content).map_err(|e| {
+                RenameIdentifierError::InvalidJson(path.clone(), e)
+            })?;
+
+            if Self::defines(&value, &self.new_id) {
+                return Err(RenameIdentifierError::AlreadyDefined(
+                    self.new_id.clone(),
+                ));
+            }
+
+            old_defined |= Self::defines(&value, &self.old_id);
+
+            let (new_content, occurrences) = self.replace_references(&content);
+
+            if occurrences > 0 {
+                changes.push((path, new_content, occurrences));
+            }
+        }
+
+        if !old_defined {
+            return Err(RenameIdentifierError::NotDefined(self.old_id.clone()));
+        }
+
+        // Only write once every file was read, so a broken file does not
+        // leave the mod half renamed
+        if !dry_run {
+            for (path, content, _) in changes.iter() {
+                fs::write(path, content)
+                    .map_err(|e| RenameIdentifierError::Io(path.clone(), e))?;
+            }
+        }
+
+        Ok(changes
+            .into_iter()
+            .map(|(path, _, occurrences)| RenamedFile { path, occurrences })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TERRAIN: &str = r#"[
+  { "type": "terrain", "id": "t_old_wall", "flags": [ "WALL" ] },
+  { "type": "terrain", "id": "t_old_wall_2", "flags": [ "WALL" ] }
+]"#;
+
+    const MAPGEN: &str = r##"[
+  {
+    "type": "mapgen",
+    "om_terrain": "old_house",
+    "object": { "terrain": { "#": "t_old_wall", "|": "t_old_wall_2" } }
+  }
+]"##;
+
+    const SPECIAL: &str = r#"[
+  {
+    "type": "overmap_special",
+    "id": "old_special",
+    "overmaps": [ { "point": [ 0, 0, 0 ], "overmap": "old_house_north" } ]
+  }
+]"#;
+
+    /// Creates a mod in the temp directory which defines a terrain and the
+    /// overmap terrain `old_house` which is used by an overmap special
+    fn test_mod(name: &str) -> PathBuf {
+        let mod_path = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&mod_path);
+        fs::create_dir_all(mod_path.join("mapgen")).unwrap();
+
+        fs::write(mod_path.join("terrain.json"), TERRAIN).unwrap();
+        fs::write(mod_path.join("mapgen").join("house.json"), MAPGEN).unwrap();
+        fs::write(mod_path.join("special.json"), SPECIAL).unwrap();
+
+        mod_path
+    }
+
+    fn rename(
+        mod_path: &PathBuf,
+        old_id: &str,
+        new_id: &str,
+    ) -> IdentifierRename {
+        IdentifierRename {
+            mod_path: mod_path.clone(),
+            old_id: old_id.into(),
+            new_id: new_id.into(),
+        }
+    }
+
+    fn occurrences(files: &[RenamedFile]) -> Vec<(String, usize)> {
+        files
+            .iter()
+            .map(|f| {
+                (
+                    f.path.file_name().unwrap().to_string_lossy().into_owned(),
+                    f.occurrences,
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_does_not_change_files() {
+        let mod_path = test_mod("test_rename_dry_run");
+
+        let files = rename(&mod_path, "t_old_wall", "t_new_wall")
+            .run(true)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            occurrences(&files),
+            vec![("house.json".into(), 1), ("terrain.json".into(), 1)]
+        );
+        assert_eq!(
+            fs::read_to_string(mod_path.join("terrain.json")).unwrap(),
+            TERRAIN
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_replaces_references() {
+        let mod_path = test_mod("test_rename_references");
+
+        let files = rename(&mod_path, "old_house", "new_house")
+            .run(false)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            occurrences(&files),
+            vec![("house.json".into(), 1), ("special.json".into(), 1)]
+        );
+
+        // The rotated reference is renamed and the formatting is kept
+        assert_eq!(
+            fs::read_to_string(mod_path.join("special.json")).unwrap(),
+            SPECIAL.replace("old_house_north", "new_house_north")
+        );
+        assert_eq!(
+            fs::read_to_string(mod_path.join("mapgen").join("house.json"))
+                .unwrap(),
+            MAPGEN.replace("\"old_house\"", "\"new_house\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_only_matches_whole_ids() {
+        let mod_path = test_mod("test_rename_whole_ids");
+
+        rename(&mod_path, "t_old_wall", "t_new_wall")
+            .run(false)
+            .await
+            .unwrap();
+
+        let terrain =
+            fs::read_to_string(mod_path.join("terrain.json")).unwrap();
+        assert!(terrain.contains("\"t_new_wall\""));
+        assert!(terrain.contains("\"t_old_wall_2\""));
+    }
+
+    #[tokio::test]
+    async fn test_rename_errors() {
+        let mod_path = test_mod("test_rename_errors");
+
+        let result =
+            rename(&mod_path, "t_unknown", "t_new_wall").run(true).await;
+        assert!(matches!(result, Err(RenameIdentifierError::NotDefined(_))));
+
+        let result = rename(&mod_path, "t_old_wall", "t_old_wall_2")
+            .run(true)
+            .await;
+        assert!(matches!(
+            result,
+            Err(RenameIdentifierError::AlreadyDefined(_))
+        ));
+
+        let result =
+            rename(&mod_path, "t_old_wall", "t_\"wall").run(true).await;
+        assert!(matches!(
+            result,
+            Err(RenameIdentifierError::InvalidIdentifier(_))
+        ));
+
+        // Nothing is written if any file of the mod is invalid
+        fs::write(mod_path.join("broken.json"), "[").unwrap();
+        let result = rename(&mod_path, "t_old_wall", "t_new_wall")
+            .run(false)
+            .await;
+        assert!(matches!(
+            result,
+            Err(RenameIdentifierError::InvalidJson(..))
+        ));
+        assert_eq!(
+            fs::read_to_string(mod_path.join("terrain.json")).unwrap(),
+            TERRAIN
+        );
+    }
+}
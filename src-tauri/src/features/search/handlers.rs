@@ -0,0 +1,52 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::program_data::EditorData;
+use crate::features::search::{ProjectSearch, SearchHit, SearchTarget};
+use crate::util::{get_json_data, CDDADataError};
+use serde::Serialize;
+use tauri::async_runtime::Mutex;
+use tauri::State;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+pub enum SearchProjectsError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error("The search query must not be empty")]
+    EmptyQuery,
+}
+
+/// Searches the characters, identifiers and place entries of all open
+/// projects. If no `targets` are given, everything is searched.
+#[tauri::command]
+pub async fn search_projects(
+    query: String,
+    targets: Option<Vec<SearchTarget>>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<Vec<SearchHit>, SearchProjectsError> {
+    if query.is_empty() {
+        return Err(SearchProjectsError::EmptyQuery);
+    }
+
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let editor_data_lock = editor_data.lock().await;
+    let search = ProjectSearch::new(&query, targets.unwrap_or_default());
+
+    let mut hits: Vec<SearchHit> = editor_data_lock
+        .loaded_projects
+        .values()
+        .flat_map(|project| search.search_project(project, json_data))
+        .collect();
+
+    hits.sort_by(|a, b| {
+        a.project.cmp(&b.project).then_with(|| {
+            let (a, b) = (a.coordinates.0, b.coordinates.0);
+            (a.z, a.y, a.x).cmp(&(b.z, b.y, b.x))
+        })
+    });
+
+    Ok(hits)
+}
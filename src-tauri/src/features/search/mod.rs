@@ -0,0 +1,364 @@
+pub(crate) mod handlers;
+
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::TileLayer;
+use crate::features::map::{MappedCDDAId, MappedCDDAIdsForTile, MappingKind};
+use crate::features::program_data::{
+    MapDataCollection, Project, ProjectName, ZLevel,
+};
+use crate::util::IVec3JsonKey;
+use cdda_lib::types::CDDAIdentifier;
+use glam::IVec2;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum SearchTarget {
+    Character,
+    Identifier,
+    Place,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum SearchHitKind {
+    /// A cell in the rows of a map has the searched character
+    Character { character: char },
+
+    /// The identifier that a cell resolves to matches the search
+    Identifier {
+        layer: TileLayer,
+        id: CDDAIdentifier,
+    },
+
+    /// A `place_*` entry places an identifier which matches the search
+    Place {
+        mapping: MappingKind,
+        layer: TileLayer,
+        id: CDDAIdentifier,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub project: ProjectName,
+    pub coordinates: IVec3JsonKey,
+    pub kind: SearchHitKind,
+}
+
+/// A case-insensitive search over the maps of projects
+#[derive(Debug, Clone)]
+pub struct ProjectSearch {
+    query: String,
+    targets: Vec<SearchTarget>,
+}
+
+impl ProjectSearch {
+    pub fn new(query: &str, targets: Vec<SearchTarget>) -> Self {
+        Self {
+            query: query.to_lowercase(),
+            targets,
+        }
+    }
+
+    fn searches(&self, target: SearchTarget) -> bool {
+        self.targets.is_empty() || self.targets.contains(&target)
+    }
+
+    fn matches_id(&self, id: &CDDAIdentifier) -> bool {
+        id.0.to_lowercase().contains(&self.query)
+    }
+
+    /// Characters are only searched if the query is exactly one character
+    fn query_character(&self) -> Option<char> {
+        let mut chars = self.query.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    }
+
+    pub fn search_project(
+        &self,
+        project: &Project,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Vec<SearchHit> {
+        let mut hits = vec![];
+
+        for (z, collection) in project.maps.iter() {
+            if self.searches(SearchTarget::Character) {
+                self.search_characters(project, collection, *z, &mut hits);
+            }
+
+            if self.searches(SearchTarget::Identifier) {
+                let update_ids = project.enabled_camp_upgrades(z);
+
+                match collection.get_mapped_cdda_ids(
+                    json_data,
+                    project.region.as_ref(),
+                    *z,
+                    &update_ids,
+                ) {
+                    Ok(mapped) => {
+                        for (position, ids) in mapped.ids.iter() {
+                            for (layer, id) in tile_layers(ids) {
+                                if !self.matches_id(&id.tilesheet_id.id) {
+                                    continue;
+                                }
+
+                                hits.push(SearchHit {
+                                    project: project.name.clone(),
+                                    coordinates: IVec3JsonKey(*position),
+                                    kind: SearchHitKind::Identifier {
+                                        layer,
+                                        id: id.tilesheet_id.id.clone(),
+                                    },
+                                })
+                            }
+                        }
+                    },
+                    Err(e) => warn!(
+                        "Could not search identifiers of project {}: {}",
+                        project.name, e
+                    ),
+                }
+            }
+
+            if self.searches(SearchTarget::Place) {
+                self.search_places(
+                    project, collection, *z, json_data, &mut hits,
+                );
+            }
+        }
+
+        hits
+    }
+
+    fn search_characters(
+        &self,
+        project: &Project,
+        collection: &MapDataCollection,
+        z: ZLevel,
+        hits: &mut Vec<SearchHit>,
+    ) {
+        let character = match self.query_character() {
+            None => return,
+            Some(c) => c,
+        };
+
+        for (map_coords, map_data) in collection.maps.iter() {
+            for (cell_coords, cell) in map_data.cells.iter() {
+                if cell.character.to_lowercase().ne(character.to_lowercase()) {
+                    continue;
+                }
+
+                hits.push(SearchHit {
+                    project: project.name.clone(),
+                    coordinates: IVec3JsonKey(
+                        MapDataCollection::map_to_global_cell_coords(
+                            map_coords,
+                            cell_coords,
+                            z,
+                        ),
+                    ),
+                    kind: SearchHitKind::Character {
+                        character: cell.character,
+                    },
+                })
+            }
+        }
+    }
+
+    fn search_places(
+        &self,
+        project: &Project,
+        collection: &MapDataCollection,
+        z: ZLevel,
+        json_data: &DeserializedCDDAJsonData,
+        hits: &mut Vec<SearchHit>,
+    ) {
+        for (map_coords, map_data) in collection.maps.iter() {
+            for (mapping, places) in map_data.place.iter() {
+                for place in places {
+                    // Places are reported at the top left of their area
+                    let anchor = IVec2::new(place.x.min(), place.y.min());
+
+                    let commands = match place.inner.get_commands(
+                        &anchor,
+                        map_data,
+                        json_data,
+                        &mut map_data.rng(),
+                    ) {
+                        None => continue,
+                        Some(c) => c,
+                    };
+
+                    for command in commands {
+                        if !self.matches_id(&command.id().id) {
+                            continue;
+                        }
+
+                        hits.push(SearchHit {
+                            project: project.name.clone(),
+                            coordinates: IVec3JsonKey(
+                                MapDataCollection::map_to_global_cell_coords(
+                                    map_coords,
+                                    &anchor.max(IVec2::ZERO).as_uvec2(),
+                                    z,
+                                ),
+                            ),
+                            kind: SearchHitKind::Place {
+                                mapping: mapping.clone(),
+                                layer: command.layer().clone(),
+                                id: command.id().id.clone(),
+                            },
+                        })
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn tile_layers(
+    ids: &MappedCDDAIdsForTile,
+) -> impl Iterator<Item = (TileLayer, &MappedCDDAId)> {
+    [
+        (TileLayer::Terrain, ids.terrain.as_ref()),
+        (TileLayer::Furniture, ids.furniture.as_ref()),
+        (TileLayer::Trap, ids.trap.as_ref()),
+        (TileLayer::Vehicle, ids.vehicle.as_ref()),
+        (TileLayer::Monster, ids.monster.as_ref()),
+        (TileLayer::Field, ids.field.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(layer, id)| id.map(|id| (layer, id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::map_data::{IntoArcDyn, PlaceInnerFurniture, PlaceOuter};
+    use crate::features::map::{Cell, MapData};
+    use cdda_lib::types::{MapGenValue, NumberOrRange};
+    use glam::{IVec3, UVec2};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    /// A project whose only map has a wall at 2,3, the character 'A' at 4,3
+    /// and an armchair which is placed at 5,6 to 5,8
+    fn test_project() -> Project {
+        let mut map_data = MapData::default();
+        map_data
+            .cells
+            .insert(UVec2::new(2, 3), Cell { character: '#' });
+        map_data
+            .cells
+            .insert(UVec2::new(4, 3), Cell { character: 'A' });
+        map_data.set_local_mapgen_value(
+            MappingKind::Terrain,
+            '#',
+            MapGenValue::String("t_wall".into()),
+        );
+
+        let chair: PlaceOuter<PlaceInnerFurniture> = serde_json::from_value(
+            json!({ "furn": "f_armchair", "x": 5, "y": [6, 8] }),
+        )
+        .unwrap();
+
+        map_data.place.insert(
+            MappingKind::Furniture,
+            vec![PlaceOuter::into_arc_dyn_place(
+                chair,
+                NumberOrRange::Number(5),
+                NumberOrRange::Range((6, 8)),
+            )],
+        );
+
+        let mut project = Project::default();
+        project.maps.insert(
+            0,
+            MapDataCollection {
+                maps: HashMap::from([(UVec2::ZERO, map_data)]),
+            },
+        );
+
+        project
+    }
+
+    fn search(query: &str, targets: Vec<SearchTarget>) -> Vec<SearchHit> {
+        ProjectSearch::new(query, targets).search_project(
+            &test_project(),
+            &DeserializedCDDAJsonData::default(),
+        )
+    }
+
+    #[test]
+    fn test_search_characters() {
+        let hits = search("#", vec![SearchTarget::Character]);
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].coordinates.0, IVec3::new(2, 3, 0));
+        assert!(matches!(
+            hits[0].kind,
+            SearchHitKind::Character { character: '#' }
+        ));
+
+        // Characters are matched case-insensitively
+        let hits = search("a", vec![SearchTarget::Character]);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].coordinates.0, IVec3::new(4, 3, 0));
+
+        // Longer queries never match a single character
+        assert!(search("##", vec![SearchTarget::Character]).is_empty());
+    }
+
+    #[test]
+    fn test_search_identifiers() {
+        let hits = search("WALL", vec![SearchTarget::Identifier]);
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].coordinates.0, IVec3::new(2, 3, 0));
+        assert!(matches!(
+            &hits[0].kind,
+            SearchHitKind::Identifier {
+                layer: TileLayer::Terrain,
+                id,
+            } if id == &CDDAIdentifier::from("t_wall")
+        ));
+    }
+
+    #[test]
+    fn test_search_places() {
+        let hits = search("armchair", vec![SearchTarget::Place]);
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].coordinates.0, IVec3::new(5, 6, 0));
+        assert!(matches!(
+            &hits[0].kind,
+            SearchHitKind::Place {
+                mapping: MappingKind::Furniture,
+                layer: TileLayer::Furniture,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_search_only_selected_targets() {
+        assert!(search("#", vec![SearchTarget::Identifier]).is_empty());
+        assert!(search("t_wall", vec![SearchTarget::Place]).is_empty());
+
+        // Without targets everything is searched
+        let hits = search("armchair", vec![]);
+        assert!(
+            hits.iter()
+                .any(|h| matches!(h.kind, SearchHitKind::Place { .. }))
+        );
+        assert!(
+            hits.iter()
+                .any(|h| matches!(h.kind, SearchHitKind::Identifier { .. }))
+        );
+    }
+}
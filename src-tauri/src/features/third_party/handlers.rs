@@ -0,0 +1,121 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::jobs::Jobs;
+use crate::features::program_data::changes::EditorDataChanges;
+use crate::features::program_data::{EditorData, ProjectName};
+use crate::features::third_party::{
+    ThirdPartyImportError, ThirdPartyMapgenKind, ThirdPartyProjectImporter,
+};
+use crate::features::viewer::handlers::{
+    create_viewer, OpenViewerData, OpenViewerError,
+};
+use crate::impl_serialize_for_error;
+use crate::util::Load;
+use log::warn;
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, State};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ImportThirdPartyProjectError {
+    #[error(transparent)]
+    ImportError(#[from] ThirdPartyImportError),
+
+    #[error(transparent)]
+    OpenViewerError(#[from] OpenViewerError),
+
+    #[error("Importing the project was canceled")]
+    Canceled,
+}
+
+impl_serialize_for_error!(ImportThirdPartyProjectError);
+
+/// Converts the mapgen at `mapgen_path` which was exported by another map
+/// editor into a live viewer project and opens it. The name and overmap
+/// terrain of the project are taken from the metadata file at
+/// `metadata_path` if there is one. The converted mapgen is written into
+/// `import_dir`.
+///
+/// The import is tracked as a job. If it is canceled before the viewer was
+/// opened the converted mapgen is removed again.
+#[tauri::command]
+pub async fn import_third_party_project(
+    mapgen_path: PathBuf,
+    metadata_path: Option<PathBuf>,
+    import_dir: PathBuf,
+    app: AppHandle,
+    jobs: State<'_, Jobs>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<ProjectName, ImportThirdPartyProjectError> {
+    let job = jobs.start(&app, format!("Importing {}", mapgen_path.display()));
+
+    let mut importer = ThirdPartyProjectImporter {
+        mapgen_path,
+        metadata_path,
+        import_dir,
+    };
+    let project = match importer.load().await {
+        Ok(project) => project,
+        Err(e) => {
+            let result = Err(ImportThirdPartyProjectError::from(e));
+            job.finish(&result);
+            return result;
+        },
+    };
+
+    let mapgen_file_paths = vec![project.mapgen_path.clone()];
+    let project_name = project.name.clone();
+
+    let data = match project.kind {
+        ThirdPartyMapgenKind::Terrain => OpenViewerData::Terrain {
+            mapgen_file_paths,
+            project_name,
+            om_id: project.om_id,
+            stacked_om_ids: BTreeMap::new(),
+            update_ids: vec![],
+        },
+        ThirdPartyMapgenKind::Nested => OpenViewerData::Nested {
+            mapgen_file_paths,
+            project_name,
+            nested_id: project.om_id,
+        },
+        ThirdPartyMapgenKind::Update => OpenViewerData::Update {
+            mapgen_file_paths,
+            project_name,
+            update_id: project.om_id,
+        },
+    };
+
+    let result = match job.is_canceled() {
+        true => Err(ImportThirdPartyProjectError::Canceled),
+        false => create_viewer(
+            app,
+            data,
+            editor_data,
+            editor_data_changes,
+            json_data,
+        )
+        .await
+        .map_err(ImportThirdPartyProjectError::from),
+    };
+
+    // Without a viewer nothing refers to the converted file anymore
+    if result.is_err() {
+        if let Err(remove_error) = fs::remove_file(&project.mapgen_path) {
+            warn!(
+                "Failed to remove {}, error: {}",
+                project.mapgen_path.display(),
+                remove_error
+            );
+        }
+    }
+
+    let result = result.map(|_| project.name);
+    job.finish(&result);
+    result
+}
@@ -0,0 +1,170 @@
+pub(crate) mod handlers;
+
+use crate::data::map_data::{IdCollection, OmTerrain};
+use crate::features::program_data::ProjectName;
+use crate::util::{is_valid_file_name, Load};
+use cdda_lib::types::CDDAIdentifier;
+use log::info;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// The metadata other map editors like Hostile Architect store next to the
+/// mapgen they export. Every field is optional and unknown fields are
+/// ignored.
+#[derive(Debug, Default, Deserialize)]
+pub struct ThirdPartyMetadata {
+    pub name: Option<String>,
+    pub om_terrain: Option<CDDAIdentifier>,
+
+    pub author: Option<String>,
+    pub description: Option<String>,
+}
+
+/// The kind of mapgen a project of another map editor contains, which
+/// decides the live viewer it is opened with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThirdPartyMapgenKind {
+    Terrain,
+    Nested,
+    Update,
+}
+
+/// A project of another map editor which was converted so it can be opened
+/// as a live viewer
+#[derive(Debug)]
+pub struct ThirdPartyProject {
+    pub name: ProjectName,
+    pub om_id: CDDAIdentifier,
+    pub kind: ThirdPartyMapgenKind,
+    /// The converted mapgen file inside the import directory
+    pub mapgen_path: PathBuf,
+    pub metadata: ThirdPartyMetadata,
+}
+
+#[derive(Debug, Error)]
+pub enum ThirdPartyImportError {
+    #[error("Failed to read or write the project files, {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The project files are not valid json, {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    #[error("The exported file does not contain any mapgen")]
+    NoMapgen,
+
+    #[error("Could not find out which overmap terrain the project is for")]
+    UnknownOmTerrain,
+
+    #[error("The file {0} already exists")]
+    FileAlreadyExists(PathBuf),
+
+    #[error("The project name {0} can not be used as a file name")]
+    InvalidName(String),
+}
+
+/// The id and kind of the live viewer which has to be opened for the
+/// exported `mapgen`
+fn mapgen_id(mapgen: &Value) -> Option<(CDDAIdentifier, ThirdPartyMapgenKind)> {
+    let ids = serde_json::from_value::<IdCollection>(mapgen.clone()).ok()?;
+
+    let om_terrain = ids.om_terrain.and_then(|om_terrain| match om_terrain {
+        OmTerrain::Single(s) => Some(s),
+        OmTerrain::Duplicate(d) => d.into_iter().next(),
+        OmTerrain::Nested(n) => n.into_iter().flatten().next(),
+    });
+
+    om_terrain
+        .map(|id| (CDDAIdentifier(id), ThirdPartyMapgenKind::Terrain))
+        .or(ids
+            .nested_mapgen_id
+            .map(|id| (id, ThirdPartyMapgenKind::Nested)))
+        .or(ids
+            .update_mapgen_id
+            .map(|id| (id, ThirdPartyMapgenKind::Update)))
+}
+
+/// Converts the mapgen exported by another map editor and its optional
+/// metadata file into a mapgen file in `import_dir` which can be opened by a
+/// live viewer.
+///
+/// Some tools export a single object instead of a list, and some put other
+/// objects like their palettes into the same file. Only the mapgen objects
+/// are kept.
+pub struct ThirdPartyProjectImporter {
+    pub mapgen_path: PathBuf,
+    pub metadata_path: Option<PathBuf>,
+    pub import_dir: PathBuf,
+}
+
+impl Load<ThirdPartyProject, ThirdPartyImportError>
+    for ThirdPartyProjectImporter
+{
+    async fn load(
+        &mut self,
+    ) -> Result<ThirdPartyProject, ThirdPartyImportError> {
+        let exported: Value =
+            serde_json::from_str(&fs::read_to_string(&self.mapgen_path)?)?;
+
+        let objects = match exported {
+            Value::Array(objects) => objects,
+            object => vec![object],
+        };
+
+        let mapgens: Vec<Value> = objects
+            .into_iter()
+            .filter(|o| o.get("type").and_then(Value::as_str) == Some("mapgen"))
+            .collect();
+
+        if mapgens.is_empty() {
+            return Err(ThirdPartyImportError::NoMapgen);
+        }
+
+        let metadata = match &self.metadata_path {
+            None => ThirdPartyMetadata::default(),
+            Some(path) => serde_json::from_str(&fs::read_to_string(path)?)?,
+        };
+
+        let (om_id, kind) = metadata
+            .om_terrain
+            .clone()
+            .map(|id| (id, ThirdPartyMapgenKind::Terrain))
+            .or_else(|| mapgens.iter().find_map(mapgen_id))
+            .ok_or(ThirdPartyImportError::UnknownOmTerrain)?;
+
+        let name = metadata.name.clone().unwrap_or_else(|| om_id.0.clone());
+
+        // The name is used as the name of the converted file, so it must not
+        // point outside of the import directory
+        if !is_valid_file_name(&name) {
+            return Err(ThirdPartyImportError::InvalidName(name));
+        }
+
+        fs::create_dir_all(&self.import_dir)?;
+
+        let mapgen_path = self.import_dir.join(format!("{}.json", name));
+
+        if mapgen_path.exists() {
+            return Err(ThirdPartyImportError::FileAlreadyExists(mapgen_path));
+        }
+
+        fs::write(&mapgen_path, serde_json::to_string_pretty(&mapgens)?)?;
+
+        info!(
+            "Imported third party project {} from {} into {}",
+            name,
+            self.mapgen_path.display(),
+            mapgen_path.display()
+        );
+
+        Ok(ThirdPartyProject {
+            name,
+            om_id,
+            kind,
+            mapgen_path,
+            metadata,
+        })
+    }
+}
@@ -0,0 +1,39 @@
+use crate::features::program_data::{EditorData, ProjectName};
+use serde::Serialize;
+use std::fs;
+use tauri::async_runtime::Mutex;
+use tauri::ipc::Response;
+use tauri::State;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+pub enum GetProjectThumbnailError {
+    #[error("No recent project with the name {0} exists")]
+    UnknownProject(ProjectName),
+
+    #[error("The project {0} does not have a thumbnail yet")]
+    NoThumbnail(ProjectName),
+}
+
+/// Returns the cached PNG thumbnail of the recent project with the `name`
+#[tauri::command]
+pub async fn get_project_thumbnail(
+    name: ProjectName,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<Response, GetProjectThumbnailError> {
+    let editor_data_lock = editor_data.lock().await;
+
+    let recent_project = editor_data_lock
+        .recent_projects
+        .iter()
+        .find(|p| p.name == name)
+        .ok_or(GetProjectThumbnailError::UnknownProject(name.clone()))?;
+
+    let thumbnail = recent_project
+        .thumbnail
+        .as_ref()
+        .and_then(|path| fs::read(path).ok())
+        .ok_or(GetProjectThumbnailError::NoThumbnail(name))?;
+
+    Ok(Response::new(thumbnail))
+}
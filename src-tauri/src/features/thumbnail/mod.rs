@@ -0,0 +1,195 @@
+pub(crate) mod handlers;
+
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::map::GetMappedCDDAIdsError;
+use crate::features::program_data::{
+    EditorData, Project, ProjectName, RecentProject,
+};
+use crate::util::get_size;
+use cdda_lib::types::CDDAIdentifier;
+use glam::IVec3;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Size of a single cell in the thumbnail in pixels
+pub const THUMBNAIL_CELL_SIZE: u32 = 2;
+
+/// Color of cells where nothing is placed
+const EMPTY_CELL_COLOR: [u8; 3] = [0, 0, 0];
+
+#[derive(Debug, Error)]
+pub enum ThumbnailError {
+    #[error(transparent)]
+    GetMappedCDDAIdsError(#[from] GetMappedCDDAIdsError),
+
+    #[error("Failed to encode the thumbnail, {0}")]
+    Encoding(#[from] png::EncodingError),
+
+    #[error("Failed to write the thumbnail, {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The project does not contain any maps")]
+    NoMaps,
+}
+
+/// Converts a CDDA color name like `light_green` or `i_brown` into an RGB
+/// color. The values follow the default color scheme of the game.
+pub fn cdda_color_to_rgb(color: &str) -> [u8; 3] {
+    let color = color.to_lowercase();
+
+    // Inverted and highlighted colors use the base color for the foreground
+    let color = color
+        .strip_prefix("i_")
+        .or_else(|| color.strip_prefix("h_"))
+        .or_else(|| color.strip_prefix("c_"))
+        .unwrap_or(&color);
+
+    match color {
+        "black" => [0, 0, 0],
+        "red" => [255, 0, 0],
+        "green" => [0, 110, 0],
+        "brown" => [97, 56, 28],
+        "blue" => [10, 10, 220],
+        "magenta" | "pink" => [139, 58, 98],
+        "cyan" => [0, 150, 180],
+        "light_gray" | "light_grey" => [150, 150, 150],
+        "dark_gray" | "dark_grey" => [99, 99, 99],
+        "light_red" => [255, 150, 150],
+        "light_green" => [0, 255, 0],
+        "yellow" => [255, 255, 0],
+        "light_blue" => [100, 100, 255],
+        "light_magenta" | "light_pink" => [254, 0, 254],
+        "light_cyan" => [0, 240, 255],
+        "white" => [255, 255, 255],
+        _ => [150, 150, 150],
+    }
+}
+
+fn get_color(
+    id: &CDDAIdentifier,
+    json_data: &DeserializedCDDAJsonData,
+) -> Option<[u8; 3]> {
+    let color = match json_data.furniture.get(id) {
+        Some(f) => f.color.as_ref(),
+        None => json_data.terrain.get(id)?.color.as_ref(),
+    };
+
+    Some(cdda_color_to_rgb(color?.first()?))
+}
+
+/// Renders the ground level of the `project` into a PNG image where every cell
+/// is colored like its furniture or terrain
+pub fn render_thumbnail(
+    project: &Project,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<Vec<u8>, ThumbnailError> {
+    let z = match project.maps.contains_key(&0) {
+        true => 0,
+        false => *project.maps.keys().min().ok_or(ThumbnailError::NoMaps)?,
+    };
+
+    let update_ids = project.enabled_camp_upgrades(&z);
+
+    let mapped_ids = project.maps[&z].get_mapped_cdda_ids(
+        json_data,
+        project.region.as_ref(),
+        z,
+        &update_ids,
+    )?;
+
+    let size = get_size(&project.maps);
+    let width = size.x * THUMBNAIL_CELL_SIZE;
+    let height = size.y * THUMBNAIL_CELL_SIZE;
+    let mut pixels = vec![0u8; (width * height * 3) as usize];
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let color = mapped_ids
+                .ids
+                .get(&IVec3::new(x as i32, y as i32, z))
+                .and_then(|ids| {
+                    let furniture = ids.furniture.as_ref().and_then(|f| {
+                        get_color(&f.tilesheet_id.id, json_data)
+                    });
+
+                    furniture.or_else(|| {
+                        get_color(
+                            &ids.terrain.as_ref()?.tilesheet_id.id,
+                            json_data,
+                        )
+                    })
+                })
+                .unwrap_or(EMPTY_CELL_COLOR);
+
+            for py in 0..THUMBNAIL_CELL_SIZE {
+                for px in 0..THUMBNAIL_CELL_SIZE {
+                    let pixel_x = x * THUMBNAIL_CELL_SIZE + px;
+                    let pixel_y = y * THUMBNAIL_CELL_SIZE + py;
+                    let index = ((pixel_y * width + pixel_x) * 3) as usize;
+
+                    pixels[index..index + 3].copy_from_slice(&color);
+                }
+            }
+        }
+    }
+
+    let mut png_bytes = vec![];
+    let mut encoder = png::Encoder::new(&mut png_bytes, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+
+    Ok(png_bytes)
+}
+
+pub fn get_thumbnail_path(
+    editor_data: &EditorData,
+    name: &ProjectName,
+) -> PathBuf {
+    editor_data
+        .config
+        .config_path
+        .join("thumbnails")
+        .join(format!("{}.png", name))
+}
+
+/// Renders the thumbnail of the loaded project with the `name`, writes it to
+/// the thumbnail cache and links it to the recent project entry
+pub fn update_project_thumbnail(
+    editor_data: &mut EditorData,
+    name: &ProjectName,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<(), ThumbnailError> {
+    let project = match editor_data.loaded_projects.get(name) {
+        None => return Ok(()),
+        Some(p) => p,
+    };
+
+    let png_bytes = render_thumbnail(project, json_data)?;
+    let path = get_thumbnail_path(editor_data, name);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(&path, png_bytes)?;
+
+    let recent_project = editor_data
+        .recent_projects
+        .iter()
+        .find(|p| &p.name == name)
+        .cloned();
+
+    if let Some(recent_project) = recent_project {
+        editor_data.recent_projects.replace(RecentProject {
+            thumbnail: Some(path),
+            ..recent_project
+        });
+    }
+
+    Ok(())
+}
@@ -1,13 +1,19 @@
 use crate::features::program_data::{
     CDDAPathError, EditorData, SelectedTilesetError,
 };
+use crate::features::tileset::legacy_tileset::fallback::colors::{
+    ascii_colors, AsciiColor,
+};
 use crate::features::tileset::legacy_tileset::fallback::{
     get_fallback_config, FALLBACK_TILESHEET_IMAGE,
 };
 use crate::features::tileset::legacy_tileset::io::LegacyTilesheetConfigLoader;
+use crate::features::tileset::legacy_tileset::validation::TilesetValidationReport;
+use crate::features::tileset::legacy_tileset::{
+    LegacyTilesheet, TextureAtlas, TilesetInfo,
+};
 use log::info;
 use serde::Serialize;
-use serde_json::Value;
 use tauri::ipc::Response;
 use tauri::State;
 use tokio::sync::Mutex;
@@ -19,18 +25,22 @@ pub enum GetSpritesheetsError {
 
     #[error(transparent)]
     TilesetError(#[from] SelectedTilesetError),
+
+    #[error("Failed to load the tileset config: {0}")]
+    LoadConfigError(String),
 }
 #[tauri::command]
 pub async fn get_info_of_current_tileset(
     editor_data: State<'_, Mutex<EditorData>>,
-) -> Result<Value, GetSpritesheetsError> {
+) -> Result<TilesetInfo, GetSpritesheetsError> {
     let lock = editor_data.lock().await;
 
+    // The ascii mode draws every tile with the fallback tileset
     let selected_tileset = match lock.config.get_selected_tileset() {
-        Ok(s) => s,
-        Err(_) => {
+        Ok(s) if !lock.config.ascii_mode => s,
+        _ => {
             let config = get_fallback_config();
-            return Ok(serde_json::to_value(config).unwrap());
+            return Ok(TilesetInfo::from(&config));
         },
     };
 
@@ -39,11 +49,39 @@ pub async fn get_info_of_current_tileset(
     let tileset_path = cdda_path.join("gfx").join(selected_tileset);
 
     let mut config_reader = LegacyTilesheetConfigLoader::new(tileset_path);
-    let info = config_reader.load_value().await.unwrap();
+    let info = config_reader
+        .load_info()
+        .await
+        .map_err(|e| GetSpritesheetsError::LoadConfigError(e.to_string()))?;
 
     Ok(info)
 }
 
+fn is_embedded_fallback_spritesheet(name: &str) -> bool {
+    get_fallback_config()
+        .spritesheets
+        .iter()
+        .any(|s| s.file == name)
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum GetTilesetValidationReportError {
+    #[error("No tileset has been loaded")]
+    NoTilesetLoaded,
+}
+
+#[tauri::command]
+pub async fn get_tileset_validation_report(
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+) -> Result<TilesetValidationReport, GetTilesetValidationReportError> {
+    let lock = tilesheet.lock().await;
+
+    match lock.as_ref() {
+        None => Err(GetTilesetValidationReportError::NoTilesetLoaded),
+        Some(t) => Ok(t.validation_report().clone()),
+    }
+}
+
 #[derive(Debug, thiserror::Error, Serialize)]
 pub enum DownloadSpritesheetError {
     #[error("No Spritesheet has been selected")]
@@ -57,17 +95,30 @@ pub enum DownloadSpritesheetError {
 pub async fn download_spritesheet(
     name: String,
     editor_data: State<'_, Mutex<EditorData>>,
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
 ) -> Result<Response, DownloadSpritesheetError> {
     info!("Loading spritesheet {}", &name);
 
     let lock = editor_data.lock().await;
+
+    // The ascii mode draws every tile with the embedded fallback tileset
     let selected_tileset = match &lock.config.selected_tileset {
-        None => {
+        Some(s) if !lock.config.ascii_mode => s.clone(),
+        _ => {
             return Ok(Response::new(FALLBACK_TILESHEET_IMAGE.to_vec()));
         },
-        Some(s) => s.clone(),
     };
 
+    // Spritesheets of tileset source directories only exist in memory
+    let tilesheet_lock = tilesheet.lock().await;
+    let composed = tilesheet_lock
+        .as_ref()
+        .and_then(|t| t.composed_spritesheet(&name));
+
+    if let Some(image) = composed {
+        return Ok(Response::new(image.clone()));
+    }
+
     let path = lock
         .config
         .cdda_path
@@ -75,7 +126,13 @@ pub async fn download_spritesheet(
         .ok_or(DownloadSpritesheetError::NoSpritesheetSelected)?
         .join("gfx")
         .join(selected_tileset)
-        .join(name);
+        .join(&name);
+
+    // The tileset does not contain its own fallback spritesheet, so the
+    // embedded one is used
+    if !path.exists() && is_embedded_fallback_spritesheet(&name) {
+        return Ok(Response::new(FALLBACK_TILESHEET_IMAGE.to_vec()));
+    }
 
     let image_bytes = tokio::fs::read(&path)
         .await
@@ -83,3 +140,57 @@ pub async fn download_spritesheet(
 
     Ok(Response::new(image_bytes))
 }
+
+/// The layout of the texture atlas of the current tileset. The atlas is
+/// empty when only the fallback spritesheet is used.
+#[tauri::command]
+pub async fn get_texture_atlas(
+    editor_data: State<'_, Mutex<EditorData>>,
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+) -> Result<TextureAtlas, ()> {
+    let editor_data_lock = editor_data.lock().await;
+    let lock = tilesheet.lock().await;
+
+    Ok(editor_data_lock
+        .config
+        .rendered_tilesheet(lock.as_ref())
+        .map(|t| t.atlas().clone())
+        .unwrap_or_default())
+}
+
+/// The sprites and rgb values the colors of the game are drawn with in the
+/// ascii mode
+#[tauri::command]
+pub async fn get_ascii_colors() -> Result<Vec<AsciiColor>, ()> {
+    Ok(ascii_colors())
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum DownloadTextureAtlasPageError {
+    #[error("No tileset has been loaded")]
+    NoTilesetLoaded,
+
+    #[error("The texture atlas does not have a page {0}")]
+    PageNotFound(usize),
+}
+
+#[tauri::command]
+pub async fn download_texture_atlas_page(
+    page: usize,
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+) -> Result<Response, DownloadTextureAtlasPageError> {
+    info!("Loading texture atlas page {}", page);
+
+    let lock = tilesheet.lock().await;
+    let tilesheet = lock
+        .as_ref()
+        .ok_or(DownloadTextureAtlasPageError::NoTilesetLoaded)?;
+
+    let atlas_page = tilesheet
+        .atlas()
+        .pages
+        .get(page)
+        .ok_or(DownloadTextureAtlasPageError::PageNotFound(page))?;
+
+    Ok(Response::new(atlas_page.image().clone()))
+}
@@ -0,0 +1,307 @@
+use crate::features::tileset::legacy_tileset::compose::{
+    decode_png, encode_png, SpriteImage,
+};
+use crate::features::tileset::legacy_tileset::data::SpritesheetInfo;
+use crate::features::tileset::legacy_tileset::SpriteIndex;
+use anyhow::Error;
+use log::warn;
+use serde::Serialize;
+
+/// The largest width and height of an atlas page, textures of this size are
+/// supported by almost every gpu
+const MAX_PAGE_SIZE: u32 = 8192;
+
+/// A consecutive range of sprites which is stored in consecutive cells of an
+/// atlas page
+#[derive(Debug, Clone, Serialize)]
+pub struct AtlasRange {
+    /// The sprite indices, both ends are inclusive
+    pub range: (SpriteIndex, SpriteIndex),
+    /// The cell of the first sprite of the range, the cells are counted row
+    /// by row
+    pub first_cell: u32,
+}
+
+/// An image which contains the sprites of every spritesheet with the same
+/// sprite size
+#[derive(Debug, Clone, Serialize)]
+pub struct AtlasPage {
+    pub width: u32,
+    pub height: u32,
+    pub sprite_width: u32,
+    pub sprite_height: u32,
+    pub ranges: Vec<AtlasRange>,
+
+    /// The png image of the page, this is downloaded separately
+    #[serde(skip)]
+    image: Vec<u8>,
+}
+
+impl AtlasPage {
+    pub fn image(&self) -> &Vec<u8> {
+        &self.image
+    }
+
+    /// The cell of the sprite with the `index` in this page
+    pub fn cell_of(&self, index: SpriteIndex) -> Option<u32> {
+        self.ranges
+            .iter()
+            .find(|r| (r.range.0..=r.range.1).contains(&index))
+            .map(|r| r.first_cell + index - r.range.0)
+    }
+}
+
+/// The sprites of all normal spritesheets of a tileset stitched into as few
+/// images as possible, so the frontend does not need a texture for every
+/// spritesheet
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TextureAtlas {
+    pub pages: Vec<AtlasPage>,
+}
+
+impl TextureAtlas {
+    /// Builds the atlas from the `spritesheets`, `read_image` returns the png
+    /// image of a spritesheet by its file name. Spritesheets which can not be
+    /// read are left out of the atlas.
+    pub fn build(
+        spritesheets: &[SpritesheetInfo],
+        mut read_image: impl FnMut(&str) -> Result<Vec<u8>, Error>,
+    ) -> Result<Self, Error> {
+        // Every page only contains sprites of one size so the frontend can
+        // draw all of them with the same quad
+        let mut groups: Vec<((u32, u32), Vec<&SpritesheetInfo>)> = vec![];
+
+        for spritesheet in spritesheets.iter() {
+            if spritesheet.is_fallback || spritesheet.range.is_none() {
+                continue;
+            }
+
+            let size = (spritesheet.sprite_width, spritesheet.sprite_height);
+
+            match groups.iter_mut().find(|(s, _)| *s == size) {
+                None => groups.push((size, vec![spritesheet])),
+                Some((_, sheets)) => sheets.push(spritesheet),
+            }
+        }
+
+        let mut pages = vec![];
+
+        for ((sprite_width, sprite_height), sheets) in groups {
+            let mut builder = PageBuilder::new(sprite_width, sprite_height);
+
+            for sheet in sheets {
+                let image = match read_image(&sheet.file)
+                    .and_then(|bytes| decode_png(bytes.as_slice()))
+                {
+                    Ok(i) => i,
+                    Err(e) => {
+                        warn!(
+                            "Failed to read spritesheet {}: {}",
+                            sheet.file, e
+                        );
+                        continue;
+                    },
+                };
+
+                builder.add_sheet(sheet, &image, &mut pages)?;
+            }
+
+            builder.finish(&mut pages)?;
+        }
+
+        Ok(Self { pages })
+    }
+}
+
+/// Fills the pages of one sprite size, a new page is started whenever the
+/// current one is full
+struct PageBuilder {
+    sprite_width: u32,
+    sprite_height: u32,
+    columns: u32,
+    rows: u32,
+    /// The rgba pixels of every cell of the current page
+    cells: Vec<Vec<u8>>,
+    ranges: Vec<AtlasRange>,
+}
+
+impl PageBuilder {
+    fn new(sprite_width: u32, sprite_height: u32) -> Self {
+        Self {
+            sprite_width,
+            sprite_height,
+            columns: (MAX_PAGE_SIZE / sprite_width.max(1)).max(1),
+            rows: (MAX_PAGE_SIZE / sprite_height.max(1)).max(1),
+            cells: vec![],
+            ranges: vec![],
+        }
+    }
+
+    fn add_sheet(
+        &mut self,
+        sheet: &SpritesheetInfo,
+        image: &SpriteImage,
+        pages: &mut Vec<AtlasPage>,
+    ) -> Result<(), Error> {
+        let (from, to) = match sheet.range {
+            None => return Ok(()),
+            Some(r) => r,
+        };
+
+        let sheet_columns = image.width / self.sprite_width.max(1);
+
+        for index in from..=to {
+            if self.cells.len() as u32 == self.columns * self.rows {
+                self.finish(pages)?;
+            }
+
+            let cell = self.cells.len() as u32;
+
+            // Consecutive sprites of a sheet extend the last range instead of
+            // starting a new one
+            match self.ranges.last_mut() {
+                Some(r) if r.range.1 + 1 == index => r.range.1 = index,
+                _ => self.ranges.push(AtlasRange {
+                    range: (index, index),
+                    first_cell: cell,
+                }),
+            }
+
+            let local = index - from;
+            self.cells.push(self.copy_sprite(
+                image,
+                local % sheet_columns.max(1) * self.sprite_width,
+                local / sheet_columns.max(1) * self.sprite_height,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The pixels of the sprite whose top left corner is at `x` and `y` in
+    /// the `image`. Pixels outside of the image stay transparent.
+    fn copy_sprite(&self, image: &SpriteImage, x: u32, y: u32) -> Vec<u8> {
+        let mut pixels =
+            vec![0u8; (self.sprite_width * self.sprite_height * 4) as usize];
+
+        if x >= image.width || y >= image.height {
+            return pixels;
+        }
+
+        let width = self.sprite_width.min(image.width - x);
+        let height = self.sprite_height.min(image.height - y);
+        let row_length = (width * 4) as usize;
+
+        for row in 0..height {
+            let from = (((y + row) * image.width + x) * 4) as usize;
+            let to = (row * self.sprite_width * 4) as usize;
+
+            pixels[to..to + row_length]
+                .copy_from_slice(&image.pixels[from..from + row_length]);
+        }
+
+        pixels
+    }
+
+    /// Writes the current page to the `pages` and starts a new one
+    fn finish(&mut self, pages: &mut Vec<AtlasPage>) -> Result<(), Error> {
+        if self.cells.is_empty() {
+            return Ok(());
+        }
+
+        let cell_count = self.cells.len() as u32;
+        // Keep the page roughly square so it does not waste space, as long
+        // as its height stays within the maximum size
+        let columns = ((cell_count as f32).sqrt().ceil() as u32)
+            .max(cell_count.div_ceil(self.rows))
+            .clamp(1, self.columns);
+        let rows = cell_count.div_ceil(columns);
+
+        let width = columns * self.sprite_width;
+        let height = rows * self.sprite_height;
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        let cell_row_length = (self.sprite_width * 4) as usize;
+
+        for (cell, cell_pixels) in self.cells.iter().enumerate() {
+            let x = cell as u32 % columns * self.sprite_width;
+            let y = cell as u32 / columns * self.sprite_height;
+
+            for row in 0..self.sprite_height {
+                let from = row as usize * cell_row_length;
+                let to = (((y + row) * width + x) * 4) as usize;
+
+                pixels[to..to + cell_row_length].copy_from_slice(
+                    &cell_pixels[from..from + cell_row_length],
+                );
+            }
+        }
+
+        pages.push(AtlasPage {
+            width,
+            height,
+            sprite_width: self.sprite_width,
+            sprite_height: self.sprite_height,
+            ranges: std::mem::take(&mut self.ranges),
+            image: encode_png(width, height, &pixels)?,
+        });
+
+        self.cells.clear();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spritesheet(
+        file: &str,
+        size: (u32, u32),
+        range: (u32, u32),
+    ) -> SpritesheetInfo {
+        SpritesheetInfo {
+            file: file.to_string(),
+            sprite_width: size.0,
+            sprite_height: size.1,
+            sprite_offset_x: 0,
+            sprite_offset_y: 0,
+            range: Some(range),
+            is_fallback: false,
+        }
+    }
+
+    #[test]
+    fn test_sheets_with_the_same_sprite_size_share_a_page() {
+        let spritesheets = vec![
+            spritesheet("a.png", (1, 1), (0, 3)),
+            spritesheet("b.png", (2, 1), (4, 4)),
+            spritesheet("c.png", (1, 1), (5, 6)),
+        ];
+
+        let atlas = TextureAtlas::build(&spritesheets, |file| {
+            let (width, height) = match file {
+                "a.png" => (2, 2),
+                "b.png" => (2, 1),
+                _ => (2, 1),
+            };
+            let pixels = vec![file.as_bytes()[0]; (width * height * 4) as _];
+
+            encode_png(width, height, &pixels)
+        })
+        .unwrap();
+
+        assert_eq!(atlas.pages.len(), 2);
+
+        let page = &atlas.pages[0];
+        assert_eq!((page.sprite_width, page.sprite_height), (1, 1));
+        assert_eq!(page.cell_of(3), Some(3));
+        assert_eq!(page.cell_of(5), Some(4));
+        assert_eq!(page.cell_of(4), None);
+
+        let image = decode_png(page.image().as_slice()).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(image.pixels[4 * 4], b'c');
+        assert_eq!(atlas.pages[1].cell_of(4), Some(0));
+    }
+}
@@ -0,0 +1,467 @@
+use crate::features::tileset::legacy_tileset::data::{
+    FallbackSpritesheet, LegacyTileConfig, NormalSpritesheet, Spritesheet,
+    Tile, TileInfo,
+};
+use crate::features::tileset::legacy_tileset::fallback::get_fallback_config;
+use crate::features::tileset::legacy_tileset::SpriteIndex;
+use anyhow::{anyhow, Error};
+use async_walkdir::WalkDir;
+use cdda_lib::types::{CDDAIdentifier, MeabyVec};
+use futures_lite::StreamExt;
+use log::warn;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// The file which describes the spritesheets of a tileset source directory
+const TILE_INFO_FILE: &str = "tile_info.json";
+/// The amount of sprites in a row of a composited spritesheet, this is the
+/// same amount compose.py uses
+const SPRITES_PER_ROW: u32 = 16;
+
+/// Whether the `tileset_path` contains the sources of a tileset as they are
+/// used by compose.py instead of a composed tileset
+pub fn is_tileset_source(tileset_path: &Path) -> bool {
+    !tileset_path.join("tile_config.json").exists()
+        && tileset_path.join(TILE_INFO_FILE).exists()
+}
+
+#[derive(Debug, Deserialize)]
+struct SourceSheetInfo {
+    sprite_width: Option<u32>,
+    sprite_height: Option<u32>,
+    sprite_offset_x: Option<i32>,
+    sprite_offset_y: Option<i32>,
+
+    /// Sprites and tiles of filler sheets are only used when no other sheet
+    /// defines them
+    #[serde(default)]
+    filler: bool,
+
+    #[serde(default)]
+    fallback: bool,
+}
+
+struct SheetSprites {
+    file: String,
+    sprite_width: u32,
+    sprite_height: u32,
+    /// The images of the sprites, ordered by their index in the sheet
+    sprites: Vec<PathBuf>,
+}
+
+/// A tileset source directory with the sprite names of its tile entries
+/// resolved to sprite indices
+pub(super) struct TilesetSource {
+    pub config: LegacyTileConfig,
+    sheets: Vec<SheetSprites>,
+}
+
+impl TilesetSource {
+    /// Reads the tile entries and the names of the sprites of the source
+    /// directory at `path`. The sprite images themselves are only read when
+    /// the spritesheets are composited.
+    pub async fn read(path: &Path) -> Result<Self, Error> {
+        let tile_info_path = path.join(TILE_INFO_FILE);
+        let tile_info_file = File::open(&tile_info_path)?;
+        let mut tile_info: Vec<Value> =
+            serde_json::from_reader(BufReader::new(tile_info_file))?;
+
+        if tile_info.is_empty() {
+            return Err(anyhow!("{} is empty", tile_info_path.display()));
+        }
+
+        let info: TileInfo = serde_json::from_value(tile_info.remove(0))?;
+
+        let mut names: HashMap<String, SpriteIndex> = HashMap::new();
+        let mut next_index = 0;
+        let mut sheets = vec![];
+        let mut entries = vec![];
+
+        for sheet in tile_info {
+            let (file, sheet_info) = match sheet {
+                Value::Object(o) if o.len() == 1 => {
+                    let (file, sheet_info) = o.into_iter().next().unwrap();
+                    let sheet_info: SourceSheetInfo =
+                        serde_json::from_value(sheet_info)?;
+                    (file, sheet_info)
+                },
+                v => {
+                    return Err(anyhow!("Invalid spritesheet entry {}", v));
+                },
+            };
+
+            if sheet_info.fallback {
+                sheets.push((file, sheet_info, vec![]));
+                continue;
+            }
+
+            let sprite_width = sheet_info.sprite_width.unwrap_or(info.width);
+            let sprite_height = sheet_info.sprite_height.unwrap_or(info.height);
+
+            let stem = file.strip_suffix(".png").unwrap_or(&file);
+            let sheet_path = path.join(format!(
+                "pngs_{}_{}x{}",
+                stem, sprite_width, sprite_height
+            ));
+
+            let (sprite_paths, json_paths) = source_files(&sheet_path).await?;
+
+            let mut sprites = vec![];
+            for sprite_path in sprite_paths {
+                let name = sprite_path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default();
+
+                if names.contains_key(&name) {
+                    if !sheet_info.filler {
+                        warn!(
+                            "Sprite {} is defined more than once in {}",
+                            name,
+                            path.display()
+                        );
+                    }
+                    continue;
+                }
+
+                names.insert(name, next_index);
+                sprites.push(sprite_path);
+                next_index += 1;
+            }
+
+            for json_path in json_paths {
+                let content = File::open(&json_path)?;
+                let value: Value =
+                    match serde_json::from_reader(BufReader::new(content)) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            warn!(
+                                "Failed to read tile entries of {}: {}",
+                                json_path.display(),
+                                e
+                            );
+                            continue;
+                        },
+                    };
+
+                match value {
+                    Value::Array(values) => entries
+                        .extend(values.into_iter().map(|v| (sheets.len(), v))),
+                    v => entries.push((sheets.len(), v)),
+                }
+            }
+
+            sheets.push((file, sheet_info, sprites));
+        }
+
+        let mut tiles: Vec<Vec<Tile>> = sheets.iter().map(|_| vec![]).collect();
+        for (sheet, entry) in entries {
+            match resolve_entry(entry, &names) {
+                Ok(tile) => tiles[sheet].push(tile),
+                Err(e) => warn!(
+                    "Skipping invalid tile entry in {}: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        }
+
+        let defined_ids: HashSet<CDDAIdentifier> = sheets
+            .iter()
+            .zip(tiles.iter())
+            .filter(|((_, sheet_info, _), _)| !sheet_info.filler)
+            .flat_map(|(_, tiles)| tiles.iter())
+            .flat_map(|tile| tile.id.iter().cloned())
+            .collect();
+
+        let mut spritesheets = vec![];
+        let mut sheet_sprites = vec![];
+        let mut range_start = 0;
+
+        for ((file, sheet_info, sprites), mut tiles) in
+            sheets.into_iter().zip(tiles.into_iter())
+        {
+            if sheet_info.fallback {
+                let ascii = get_fallback_config()
+                    .spritesheets
+                    .pop()
+                    .map(|s| s.ascii)
+                    .unwrap_or_default();
+
+                spritesheets.push(Spritesheet::Fallback(FallbackSpritesheet {
+                    file,
+                    tiles: vec![],
+                    ascii,
+                }));
+                continue;
+            }
+
+            if sprites.is_empty() {
+                continue;
+            }
+
+            if sheet_info.filler {
+                for tile in tiles.iter_mut() {
+                    let ids = tile
+                        .id
+                        .iter()
+                        .filter(|id| !defined_ids.contains(id))
+                        .cloned()
+                        .collect();
+                    tile.id = MeabyVec::Vec(ids);
+                }
+
+                tiles.retain(|tile| !tile.id.is_empty());
+            }
+
+            let sprite_width = sheet_info.sprite_width.unwrap_or(info.width);
+            let sprite_height = sheet_info.sprite_height.unwrap_or(info.height);
+            let range_end = range_start + sprites.len() as u32 - 1;
+
+            spritesheets.push(Spritesheet::Normal(NormalSpritesheet {
+                file: file.clone(),
+                sprite_width: Some(sprite_width),
+                sprite_height: Some(sprite_height),
+                sprite_offset_x: sheet_info.sprite_offset_x,
+                sprite_offset_y: sheet_info.sprite_offset_y,
+                range: (range_start, range_end),
+                tiles,
+            }));
+
+            sheet_sprites.push(SheetSprites {
+                file,
+                sprite_width,
+                sprite_height,
+                sprites,
+            });
+
+            range_start = range_end + 1;
+        }
+
+        Ok(Self {
+            config: LegacyTileConfig {
+                tile_info: vec![info],
+                spritesheets,
+            },
+            sheets: sheet_sprites,
+        })
+    }
+
+    /// Composites the sprites of every spritesheet into png images, keyed by
+    /// the file name of the spritesheet
+    pub fn compose(&self) -> Result<HashMap<String, Vec<u8>>, Error> {
+        let mut images = HashMap::new();
+
+        for sheet in self.sheets.iter() {
+            images.insert(sheet.file.clone(), sheet.compose()?);
+        }
+
+        Ok(images)
+    }
+}
+
+impl SheetSprites {
+    fn compose(&self) -> Result<Vec<u8>, Error> {
+        let width = SPRITES_PER_ROW * self.sprite_width;
+        let rows = (self.sprites.len() as u32).div_ceil(SPRITES_PER_ROW);
+        let height = rows * self.sprite_height;
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+
+        for (index, path) in self.sprites.iter().enumerate() {
+            let sprite = match read_sprite(path) {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!("Failed to read sprite {}: {}", path.display(), e);
+                    continue;
+                },
+            };
+
+            if sprite.width != self.sprite_width
+                || sprite.height != self.sprite_height
+            {
+                warn!(
+                    "Sprite {} is {}x{} instead of {}x{}",
+                    path.display(),
+                    sprite.width,
+                    sprite.height,
+                    self.sprite_width,
+                    self.sprite_height
+                );
+            }
+
+            let x = index as u32 % SPRITES_PER_ROW * self.sprite_width;
+            let y = index as u32 / SPRITES_PER_ROW * self.sprite_height;
+            let row_length = (sprite.width.min(self.sprite_width) * 4) as usize;
+
+            for row in 0..sprite.height.min(self.sprite_height) {
+                let from = (row * sprite.width * 4) as usize;
+                let to = (((y + row) * width + x) * 4) as usize;
+
+                pixels[to..to + row_length]
+                    .copy_from_slice(&sprite.pixels[from..from + row_length]);
+            }
+        }
+
+        encode_png(width, height, &pixels)
+    }
+}
+
+/// The sprite images and tile entry files in the directory of a spritesheet,
+/// both sorted by their path
+async fn source_files(
+    sheet_path: &Path,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Error> {
+    let mut walkdir = WalkDir::new(sheet_path);
+    let mut sprites = vec![];
+    let mut entries = vec![];
+
+    while let Some(entry) = walkdir.next().await {
+        let path = entry?.path();
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("png") => sprites.push(path),
+            Some("json") => entries.push(path),
+            _ => {},
+        }
+    }
+
+    sprites.sort();
+    entries.sort();
+
+    Ok((sprites, entries))
+}
+
+/// Replaces the sprite names in the fg and bg of the tile `entry` and its
+/// additional tiles with the indices of the sprites
+fn resolve_entry(
+    mut entry: Value,
+    names: &HashMap<String, SpriteIndex>,
+) -> Result<Tile, Error> {
+    resolve_fore_back(&mut entry, names);
+
+    if let Some(Value::Array(additional_tiles)) =
+        entry.get_mut("additional_tiles")
+    {
+        for additional_tile in additional_tiles.iter_mut() {
+            resolve_fore_back(additional_tile, names);
+        }
+    }
+
+    Ok(serde_json::from_value(entry)?)
+}
+
+fn resolve_fore_back(entry: &mut Value, names: &HashMap<String, SpriteIndex>) {
+    let entry = match entry.as_object_mut() {
+        None => return,
+        Some(e) => e,
+    };
+
+    for key in ["fg", "bg"] {
+        let value = match entry.remove(key) {
+            None => continue,
+            Some(v) => v,
+        };
+
+        if let Some(resolved) = resolve_sprite_names(value, names) {
+            entry.insert(key.to_string(), resolved);
+        }
+    }
+}
+
+/// Unknown sprites are removed together with the weighted entry they are in
+fn resolve_sprite_names(
+    value: Value,
+    names: &HashMap<String, SpriteIndex>,
+) -> Option<Value> {
+    match value {
+        Value::String(name) => match names.get(&name) {
+            None => {
+                warn!("Tile entry references the unknown sprite {}", name);
+                None
+            },
+            Some(index) => Some(Value::from(*index)),
+        },
+        Value::Array(values) => Some(Value::Array(
+            values
+                .into_iter()
+                .filter_map(|v| resolve_sprite_names(v, names))
+                .collect(),
+        )),
+        Value::Object(object) => object
+            .into_iter()
+            .map(|(k, v)| Some((k, resolve_sprite_names(v, names)?)))
+            .collect::<Option<Map<String, Value>>>()
+            .map(Value::Object),
+        v => Some(v),
+    }
+}
+
+pub(super) struct SpriteImage {
+    pub width: u32,
+    pub height: u32,
+    /// The pixels of the image in rgba
+    pub pixels: Vec<u8>,
+}
+
+fn read_sprite(path: &Path) -> Result<SpriteImage, Error> {
+    decode_png(BufReader::new(File::open(path)?))
+}
+
+/// Decodes a png image of any color type into rgba pixels
+pub(super) fn decode_png(reader: impl Read) -> Result<SpriteImage, Error> {
+    let mut decoder = png::Decoder::new(reader);
+    decoder.set_transformations(
+        png::Transformations::EXPAND | png::Transformations::STRIP_16,
+    );
+
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer)?;
+    let data = &buffer[..frame.buffer_size()];
+
+    let pixels = match frame.color_type {
+        png::ColorType::Rgba => data.to_vec(),
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => {
+            data.iter().flat_map(|g| [*g, *g, *g, 255]).collect()
+        },
+        png::ColorType::Indexed => {
+            return Err(anyhow!("Indexed colors were not expanded"));
+        },
+    };
+
+    Ok(SpriteImage {
+        width: frame.width,
+        height: frame.height,
+        pixels,
+    })
+}
+
+/// Encodes the rgba `pixels` of an image as a png
+pub(super) fn encode_png(
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut png_bytes = vec![];
+    let mut encoder = png::Encoder::new(&mut png_bytes, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()?;
+
+    Ok(png_bytes)
+}
@@ -1,5 +1,9 @@
 use crate::features::tileset::data::AdditionalTileType;
+use crate::features::tileset::legacy_tileset::fallback::{
+    get_fallback_config, FallbackTileConfig,
+};
 use crate::features::tileset::legacy_tileset::SpriteIndex;
+use crate::features::tileset::Projection;
 use cdda_lib::types::{CDDAIdentifier, MeabyVec, MeabyWeighted};
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -104,7 +108,7 @@ pub struct AsciiCharGroup {
     pub color: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TileInfo {
     pub pixelscale: Option<u32>,
     pub width: u32,
@@ -115,6 +119,159 @@ pub struct TileInfo {
     pub retract_dist_max: Option<f32>,
 }
 
+impl TileInfo {
+    /// Fill in the values which CDDA assumes when they are not specified
+    pub fn with_defaults(self) -> Self {
+        Self {
+            pixelscale: Some(self.pixelscale.unwrap_or(1)),
+            width: self.width,
+            height: self.height,
+            zlevel_height: Some(self.zlevel_height.unwrap_or(0)),
+            iso: Some(self.iso.unwrap_or(false)),
+            retract_dist_min: Some(self.retract_dist_min.unwrap_or(-1.0)),
+            retract_dist_max: Some(self.retract_dist_max.unwrap_or(1.0)),
+        }
+    }
+
+    pub fn projection(&self) -> Projection {
+        Projection {
+            iso: self.iso.unwrap_or(false),
+            tile_width: self.width,
+            tile_height: self.height,
+            zlevel_height: self.zlevel_height.unwrap_or(0),
+        }
+    }
+}
+
+/// Metadata of a single spritesheet with all optional values resolved
+#[derive(Debug, Clone, Serialize)]
+pub struct SpritesheetInfo {
+    pub file: String,
+    pub sprite_width: u32,
+    pub sprite_height: u32,
+    pub sprite_offset_x: i32,
+    pub sprite_offset_y: i32,
+
+    /// The range of sprite indices in this spritesheet. This is [`None`] for
+    /// the fallback spritesheet
+    #[serde(rename = "//")]
+    pub range: Option<(u32, u32)>,
+
+    pub is_fallback: bool,
+}
+
+/// Everything the frontend needs to know about a tileset to render it
+#[derive(Debug, Clone, Serialize)]
+pub struct TilesetInfo {
+    pub tile_info: Vec<TileInfo>,
+
+    #[serde(rename = "tiles-new")]
+    pub spritesheets: Vec<SpritesheetInfo>,
+
+    /// How the tiles are placed on the screen, taken from the first tile info
+    pub projection: Projection,
+}
+
+impl TilesetInfo {
+    fn fallback_spritesheet_info(
+        spritesheet: &FallbackSpritesheet,
+        tile_info: Option<&TileInfo>,
+    ) -> SpritesheetInfo {
+        SpritesheetInfo {
+            file: spritesheet.file.clone(),
+            sprite_width: tile_info.map(|ti| ti.width).unwrap_or_default(),
+            sprite_height: tile_info.map(|ti| ti.height).unwrap_or_default(),
+            sprite_offset_x: 0,
+            sprite_offset_y: 0,
+            range: None,
+            is_fallback: true,
+        }
+    }
+}
+
+impl From<&LegacyTileConfig> for TilesetInfo {
+    fn from(value: &LegacyTileConfig) -> Self {
+        let tile_info: Vec<TileInfo> = value
+            .tile_info
+            .iter()
+            .cloned()
+            .map(TileInfo::with_defaults)
+            .collect();
+
+        let mut spritesheets: Vec<SpritesheetInfo> = value
+            .spritesheets
+            .iter()
+            .map(|s| match s {
+                Spritesheet::Normal(n) => SpritesheetInfo {
+                    file: n.file.clone(),
+                    sprite_width: n
+                        .sprite_width
+                        .unwrap_or(tile_info.first().map_or(0, |ti| ti.width)),
+                    sprite_height: n
+                        .sprite_height
+                        .unwrap_or(tile_info.first().map_or(0, |ti| ti.height)),
+                    sprite_offset_x: n.sprite_offset_x.unwrap_or(0),
+                    sprite_offset_y: n.sprite_offset_y.unwrap_or(0),
+                    range: Some(n.range),
+                    is_fallback: false,
+                },
+                Spritesheet::Fallback(f) => {
+                    TilesetInfo::fallback_spritesheet_info(f, tile_info.first())
+                },
+            })
+            .collect();
+
+        // The embedded fallback spritesheet is used by the loader when the
+        // tileset does not contain one
+        if !spritesheets.iter().any(|s| s.is_fallback) {
+            spritesheets.extend(
+                TilesetInfo::from(&get_fallback_config()).spritesheets,
+            );
+        }
+
+        let projection = tile_info
+            .first()
+            .map(TileInfo::projection)
+            .unwrap_or_default();
+
+        Self {
+            tile_info,
+            spritesheets,
+            projection,
+        }
+    }
+}
+
+impl From<&FallbackTileConfig> for TilesetInfo {
+    fn from(value: &FallbackTileConfig) -> Self {
+        let tile_info: Vec<TileInfo> = value
+            .tile_info
+            .iter()
+            .cloned()
+            .map(TileInfo::with_defaults)
+            .collect();
+
+        let spritesheets = value
+            .spritesheets
+            .iter()
+            .map(|f| {
+                TilesetInfo::fallback_spritesheet_info(f, tile_info.first())
+            })
+            .collect();
+
+        let projection = tile_info
+            .first()
+            .map(TileInfo::projection)
+            .unwrap_or_default();
+
+        Self {
+            tile_info,
+            spritesheets,
+            projection,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use cdda_lib::types::Weighted;
@@ -0,0 +1,125 @@
+use crate::features::tileset::data::FALLBACK_TILE_MAPPING;
+use crate::features::tileset::legacy_tileset::data::AsciiCharGroup;
+use crate::features::tileset::legacy_tileset::fallback::get_fallback_config;
+use crate::features::tileset::legacy_tileset::SpriteIndex;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The colors of the game with the color and boldness of the ascii group of
+/// the fallback spritesheet they are drawn with and their default rgb value
+const COLORS: &[(&str, &str, bool, [u8; 3])] = &[
+    ("black", "BLACK", false, [0, 0, 0]),
+    ("white", "WHITE", false, [255, 255, 255]),
+    ("light_gray", "GRAY", false, [150, 150, 150]),
+    ("dark_gray", "GRAY", true, [99, 99, 99]),
+    ("red", "RED", false, [255, 0, 0]),
+    ("light_red", "RED", true, [255, 150, 150]),
+    ("green", "GREEN", false, [0, 110, 0]),
+    ("light_green", "GREEN", true, [0, 255, 0]),
+    ("blue", "BLUE", false, [0, 0, 200]),
+    ("light_blue", "BLUE", true, [100, 100, 255]),
+    ("cyan", "CYAN", false, [0, 150, 180]),
+    ("light_cyan", "CYAN", true, [0, 240, 255]),
+    ("magenta", "MAGENTA", false, [139, 58, 98]),
+    ("pink", "MAGENTA", true, [254, 0, 254]),
+    ("brown", "BROWN", false, [92, 51, 23]),
+    ("yellow", "YELLOW", true, [255, 255, 0]),
+];
+
+/// A color of the game and how the ascii mode draws it
+#[derive(Debug, Clone, Serialize)]
+pub struct AsciiColor {
+    /// The name of the color as it is used in the json data
+    pub name: &'static str,
+    /// The index of the first sprite of the ascii group of the fallback
+    /// spritesheet with this color. The sprite of a symbol is at this offset
+    /// plus the code of the symbol.
+    pub offset: Option<SpriteIndex>,
+    pub rgb: [u8; 3],
+}
+
+/// Every color of the game with the sprites and the rgb value it is drawn
+/// with in the ascii mode
+pub fn ascii_colors() -> Vec<AsciiColor> {
+    let config = get_fallback_config();
+    let groups: Vec<_> = config
+        .spritesheets
+        .iter()
+        .flat_map(|s| s.ascii.iter())
+        .collect();
+
+    COLORS
+        .iter()
+        .map(|(name, color, bold, rgb)| {
+            let offset = find_group(&groups, color, *bold)
+                .map(|g| g.offset as SpriteIndex);
+
+            AsciiColor {
+                name,
+                offset,
+                rgb: *rgb,
+            }
+        })
+        .collect()
+}
+
+/// The ascii group with the `color` and `bold`ness. Spritesheets which only
+/// contain one brightness of a color use it for both.
+fn find_group<'a>(
+    groups: &[&'a AsciiCharGroup],
+    color: &str,
+    bold: bool,
+) -> Option<&'a AsciiCharGroup> {
+    groups
+        .iter()
+        .find(|g| g.color == color && g.bold == bold)
+        .or_else(|| groups.iter().find(|g| g.color == color))
+        .copied()
+}
+
+/// Maps `{symbol}_{COLOR}` to the sprite of the symbol in the ascii `groups`
+/// for every color of the game, including their light and dark variants.
+/// Groups with colors the game does not know are added by their own color.
+pub fn fallback_map(groups: &[AsciiCharGroup]) -> HashMap<String, SpriteIndex> {
+    let groups: Vec<_> = groups.iter().collect();
+    let mut fallback_map = HashMap::new();
+
+    let mut insert_group = |color: &str, group: &AsciiCharGroup| {
+        for (character, offset) in FALLBACK_TILE_MAPPING {
+            fallback_map
+                .entry(format!("{}_{}", character, color))
+                .or_insert(group.offset as SpriteIndex + offset);
+        }
+    };
+
+    for (name, color, bold, _) in COLORS {
+        if let Some(group) = find_group(&groups, color, *bold) {
+            insert_group(&name.to_uppercase(), group);
+        }
+    }
+
+    for group in groups.iter().copied() {
+        let group = find_group(&groups, &group.color, false).unwrap_or(group);
+        insert_group(&group.color, group);
+    }
+
+    fallback_map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_light_and_dark_colors_use_their_own_group() {
+        let config = get_fallback_config();
+        let map = fallback_map(&config.spritesheets[0].ascii);
+
+        assert_eq!(map.get("#_LIGHT_GRAY"), Some(&(256 + 35)));
+        assert_eq!(map.get("#_DARK_GRAY"), Some(&(512 + 35)));
+        assert_eq!(map.get("#_LIGHT_RED"), Some(&(1024 + 35)));
+        assert_eq!(map.get("#_RED"), Some(&(1280 + 35)));
+        assert_eq!(map.get("#_GRAY"), map.get("#_LIGHT_GRAY"));
+        assert_eq!(map.get("#_YELLOW"), Some(&(1536 + 35)));
+    }
+}
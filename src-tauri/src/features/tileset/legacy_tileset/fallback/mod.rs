@@ -1,11 +1,14 @@
-use crate::features::tileset::data::FALLBACK_TILE_MAPPING;
 use crate::features::tileset::legacy_tileset::data::{
     FallbackSpritesheet, TileInfo,
 };
-use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+use crate::features::tileset::legacy_tileset::validation::TilesetValidationReport;
+use crate::features::tileset::legacy_tileset::{LegacyTilesheet, TextureAtlas};
+use crate::features::tileset::Season;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod colors;
+
 const FALLBACK_TILESHEET_CONFIG: &'static [u8] =
     include_bytes!("tile_config.json");
 pub const FALLBACK_TILESHEET_IMAGE: &'static [u8] =
@@ -25,24 +28,29 @@ pub fn get_fallback_config() -> FallbackTileConfig {
 
 pub fn get_fallback_tilesheet() -> LegacyTilesheet {
     let mut config = get_fallback_config();
-    let mut fallback_map = HashMap::new();
 
     let fallback_spritesheet = config
         .spritesheets
         .pop()
         .expect("Fallback spritesheet to exist");
 
-    for ascii_group in fallback_spritesheet.ascii.into_iter() {
-        for (character, offset) in FALLBACK_TILE_MAPPING {
-            fallback_map.insert(
-                format!("{}_{}", character, ascii_group.color),
-                ascii_group.offset as u32 + offset,
-            );
-        }
-    }
+    let fallback_map = colors::fallback_map(&fallback_spritesheet.ascii);
+
+    let projection = config
+        .tile_info
+        .first()
+        .map(TileInfo::projection)
+        .unwrap_or_default();
 
     LegacyTilesheet {
         id_map: HashMap::new(),
         fallback_map,
+        projection,
+        spritesheets: vec![],
+        uses_embedded_fallback: true,
+        validation_report: TilesetValidationReport::default(),
+        season: Season::default(),
+        composed_spritesheets: HashMap::new(),
+        atlas: TextureAtlas::default(),
     }
 }
@@ -1,15 +1,23 @@
-use crate::features::tileset::data::FALLBACK_TILE_MAPPING;
+use crate::features::tileset::legacy_tileset::compose::{
+    is_tileset_source, TilesetSource,
+};
 use crate::features::tileset::legacy_tileset::data::{
-    LegacyTileConfig, Spritesheet,
+    LegacyTileConfig, Spritesheet, TileInfo, TilesetInfo,
+};
+use crate::features::tileset::legacy_tileset::fallback::{
+    colors, get_fallback_tilesheet,
 };
-use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+use crate::features::tileset::legacy_tileset::validation::{
+    validate_spritesheet, TilesetValidationReport,
+};
+use crate::features::tileset::legacy_tileset::{LegacyTilesheet, TextureAtlas};
 use crate::features::tileset::{
-    legacy_tileset, ForeBackIds, SingleSprite, Sprite,
+    legacy_tileset, ForeBackIds, Season, SingleSprite, Sprite,
 };
 use crate::util::Load;
 use anyhow::{anyhow, Error};
-use serde_json::Value;
-use std::collections::HashMap;
+use log::warn;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::BufReader;
 use std::path::PathBuf;
@@ -47,7 +55,7 @@ impl Load<LegacyTileConfig> for TileConfigLoader {
 impl Load<LegacyTilesheet> for LegacyTilesheetLoader {
     async fn load(&mut self) -> Result<LegacyTilesheet, Error> {
         let mut id_map = HashMap::new();
-        let mut fallback_map = HashMap::new();
+        let fallback_map;
 
         let mut normal_spritesheets = vec![];
         let mut fallback_spritesheet = None;
@@ -59,7 +67,16 @@ impl Load<LegacyTilesheet> for LegacyTilesheetLoader {
             }
         }
 
+        let mut validation_report = TilesetValidationReport::default();
+        let mut seen_ids = HashSet::new();
+
         for spritesheet in normal_spritesheets {
+            validate_spritesheet(
+                spritesheet,
+                &mut seen_ids,
+                &mut validation_report,
+            );
+
             for tile in spritesheet.tiles.iter() {
                 let is_multitile = tile.multitile.unwrap_or_else(|| false)
                     && tile.additional_tiles.is_some();
@@ -100,27 +117,65 @@ impl Load<LegacyTilesheet> for LegacyTilesheetLoader {
             }
         }
 
-        let fallback_spritesheet =
-            fallback_spritesheet.expect("Fallback spritesheet to exist");
-
-        for ascii_group in fallback_spritesheet.ascii.iter() {
-            for (character, offset) in FALLBACK_TILE_MAPPING {
-                fallback_map.insert(
-                    format!("{}_{}", character, ascii_group.color),
-                    ascii_group.offset as u32 + offset,
+        // Some tilesets do not ship their own ascii spritesheet, in that case
+        // we use the embedded fallback spritesheet instead
+        let uses_embedded_fallback = match fallback_spritesheet {
+            None => {
+                warn!(
+                    "Tileset does not contain a fallback spritesheet, using \
+                     the embedded one"
                 );
-            }
+                fallback_map = get_fallback_tilesheet().fallback_map;
+                true
+            },
+            Some(fallback_spritesheet) => {
+                fallback_map =
+                    colors::fallback_map(&fallback_spritesheet.ascii);
+                false
+            },
+        };
+
+        let projection = self
+            .config
+            .tile_info
+            .first()
+            .map(TileInfo::projection)
+            .unwrap_or_default();
+
+        let spritesheets = TilesetInfo::from(&self.config)
+            .spritesheets
+            .into_iter()
+            .filter(|s| !s.is_fallback)
+            .collect();
+
+        if !validation_report.is_empty() {
+            warn!(
+                "Found {} issues while validating the tileset",
+                validation_report.issues.len()
+            );
         }
 
         Ok(LegacyTilesheet {
             id_map,
             fallback_map,
+            projection,
+            spritesheets,
+            uses_embedded_fallback,
+            validation_report,
+            season: Season::default(),
+            composed_spritesheets: HashMap::new(),
+            atlas: TextureAtlas::default(),
         })
     }
 }
 
 impl Load<LegacyTileConfig> for LegacyTilesheetConfigLoader {
     async fn load(&mut self) -> Result<LegacyTileConfig, Error> {
+        if is_tileset_source(&self.tileset_path) {
+            let source = TilesetSource::read(&self.tileset_path).await?;
+            return Ok(source.config);
+        }
+
         let config_path = self.tileset_path.join("tile_config.json");
 
         let mut buffer = vec![];
@@ -143,16 +198,11 @@ impl LegacyTilesheetConfigLoader {
         Self { tileset_path }
     }
 
-    pub async fn load_value(&mut self) -> Result<Value, Error> {
+    pub async fn load_info(&mut self) -> Result<TilesetInfo, Error> {
         let legacy_tilesheet =
             <LegacyTilesheetConfigLoader as Load<LegacyTileConfig>>::load(self)
-                .await;
+                .await?;
 
-        match legacy_tilesheet {
-            Ok(v) => Ok(serde_json::to_value(v)?),
-            Err(e) => {
-                anyhow::bail!(e);
-            },
-        }
+        Ok(TilesetInfo::from(&legacy_tilesheet))
     }
 }
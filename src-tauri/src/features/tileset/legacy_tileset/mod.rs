@@ -1,4 +1,5 @@
 use crate::data::io::DeserializedCDDAJsonData;
+use crate::events::{EmitEvent, TilesetChangedEvent};
 use crate::data::vehicle_parts::CDDAVehiclePart;
 use crate::features::map::MappedCDDAId;
 use crate::features::program_data::EditorData;
@@ -6,25 +7,51 @@ use crate::features::tileset::data::{
     AdditionalTileType, FALLBACK_TILE_MAPPING,
 };
 use crate::features::tileset::legacy_tileset::io::TileConfigLoader;
-use crate::features::tileset::{ForeBackIds, SingleSprite, Sprite, Tilesheet};
+use crate::features::tileset::{
+    ForeBackIds, Projection, Season, SingleSprite, Sprite, Tilesheet,
+};
+use crate::features::toast::ToastMessage;
 use crate::util::{CardinalDirection, Load, Rotation};
 use anyhow::{anyhow, Error};
+pub use atlas::TextureAtlas;
 use cdda_lib::types::{CDDAIdentifier, MeabyVec, MeabyWeighted, Weighted};
+use compose::TilesetSource;
 use data::{AdditionalTile, Tile};
+pub use data::{SpritesheetInfo, TilesetInfo};
 use io::LegacyTilesheetLoader;
+use validation::TilesetValidationReport;
 use log::{debug, info, warn};
 use paste::paste;
 use rand::distr::Distribution;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
 
+mod atlas;
+mod compose;
 mod data;
 pub mod fallback;
 pub mod io;
+pub mod validation;
 
 pub type SpriteIndex = u32;
+
+/// The sprite of NPCs whose class does not have a sprite in the tileset
+pub const NPC_SPRITE_ID: &str = "npc_male";
+/// The ascii fallback of NPCs
+pub const NPC_FALLBACK_ID: &str = "@_WHITE";
+/// The prefix of the sprites of corpses, the sprite without a monster id is
+/// used for every corpse
+pub const CORPSE_PREFIX: &str = "corpse";
+/// The prefixes of the overlay sprites of mutations in the order they are
+/// looked up. NPCs are drawn with the male sprite, so the male overlays are
+/// preferred.
+const MUTATION_OVERLAY_PREFIXES: [&str; 2] =
+    ["overlay_male_mutation_", "overlay_mutation_"];
 pub type FinalIds = Option<Vec<Weighted<Rotates>>>;
 
 #[derive(Debug, Clone)]
@@ -245,9 +272,27 @@ fn get_multitile_sprite_from_additional_tiles(
     })
 }
 
+/// NPCs are placed with the id of their class, or the id of their template if
+/// it does not have a class
+fn is_npc(id: &CDDAIdentifier, json_data: &DeserializedCDDAJsonData) -> bool {
+    json_data.npc_classes.contains_key(id) || json_data.npcs.contains_key(id)
+}
+
 pub struct LegacyTilesheet {
     id_map: HashMap<CDDAIdentifier, Sprite>,
     fallback_map: HashMap<String, SpriteIndex>,
+    projection: Projection,
+    /// The normal spritesheets of the tileset, ordered by their ranges
+    spritesheets: Vec<SpritesheetInfo>,
+    uses_embedded_fallback: bool,
+    validation_report: TilesetValidationReport,
+    /// The season whose variants of the tiles are preferred
+    season: Season,
+    /// The spritesheets which were composited from the sources of the
+    /// tileset, keyed by their file name
+    composed_spritesheets: HashMap<String, Vec<u8>>,
+    /// The sprites of the normal spritesheets stitched into a few images
+    atlas: TextureAtlas,
 }
 
 impl Tilesheet for LegacyTilesheet {
@@ -256,72 +301,72 @@ impl Tilesheet for LegacyTilesheet {
         id: &MappedCDDAId,
         json_data: &DeserializedCDDAJsonData,
     ) -> SpriteIndex {
-        match json_data.terrain.get(&id.tilesheet_id.id) {
-            None => {},
-            Some(t) => {
-                // TODO: _LIGHT and _DARK should be handled, but right now i don't fully understand how they work
-
-                let color = t
-                    .color
-                    .clone()
-                    .unwrap_or(MeabyVec::Single("WHITE".to_string()))
-                    .into_single()
-                    .unwrap_or("WHITE".to_string())
-                    .to_uppercase()
-                    .replace("LIGHT_", "")
-                    .replace("DARK_", "");
-
-                let fallback_id =
-                    format!("{}_{}", t.symbol.unwrap_or('?'), color);
-
-                match self.fallback_map.get(&fallback_id).clone() {
-                    None => {
-                        info!("No fallback for {} found", fallback_id);
-                    },
-                    Some(_) => {},
-                }
+        let cdda_id = &id.tilesheet_id.id;
 
-                return self
-                    .fallback_map
-                    .get(&fallback_id)
-                    .unwrap_or(&FALLBACK_TILE_MAPPING.first().unwrap().1)
-                    .clone();
-            },
+        // Symbols which are defined as strings only use their first character
+        let first_char =
+            |symbol: &Option<String>| symbol.as_ref()?.chars().next();
+
+        if let Some(terrain) = json_data.terrain.get(cdda_id) {
+            let color = terrain.color.clone().and_then(|c| c.into_single());
+
+            return self.get_ascii_fallback(
+                terrain.symbol.unwrap_or('?'),
+                color.as_deref(),
+            );
         }
 
-        match json_data.furniture.get(&id.tilesheet_id.id) {
-            None => {},
-            Some(t) => {
-                // TODO: _LIGHT and _DARK should be handled, but right now i don't fully understand how they work
-
-                let color = t
-                    .color
-                    .clone()
-                    .unwrap_or(MeabyVec::Single("WHITE".to_string()))
-                    .into_single()
-                    .unwrap_or("WHITE".to_string())
-                    .to_uppercase()
-                    .replace("LIGHT_", "")
-                    .replace("DARK_", "");
-
-                let fallback_id =
-                    format!("{}_{}", t.symbol.unwrap_or('?'), color);
-
-                match self.fallback_map.get(&fallback_id).clone() {
-                    None => {
-                        info!("No fallback for {} found", fallback_id);
-                    },
-                    Some(_) => {},
-                }
+        if is_npc(cdda_id, json_data) {
+            return self
+                .fallback_map
+                .get(NPC_FALLBACK_ID)
+                .cloned()
+                .unwrap_or(FALLBACK_TILE_MAPPING.first().unwrap().1);
+        }
 
-                return self
-                    .fallback_map
-                    .get(&fallback_id)
-                    .unwrap_or(&FALLBACK_TILE_MAPPING.first().unwrap().1)
-                    .clone();
-            },
+        if let Some(furniture) = json_data.furniture.get(cdda_id) {
+            let color = furniture.color.clone().and_then(|c| c.into_single());
+
+            return self.get_ascii_fallback(
+                furniture.symbol.unwrap_or('?'),
+                color.as_deref(),
+            );
+        }
+
+        if let Some(trap) = json_data.traps.get(cdda_id) {
+            return self.get_ascii_fallback(
+                trap.symbol.unwrap_or('^'),
+                trap.color.as_deref(),
+            );
         }
 
+        if let Some(monster) = json_data.monsters.get(cdda_id) {
+            // Corpses are drawn like items in the color of their monster
+            let symbol = match id.tilesheet_id.prefix.as_deref() {
+                Some(CORPSE_PREFIX) => '%',
+                _ => first_char(&monster.symbol).unwrap_or('Z'),
+            };
+
+            return self.get_ascii_fallback(symbol, monster.color.as_deref());
+        }
+
+        if let Some(field) = json_data.field_types.get(cdda_id) {
+            let level = field.intensity_levels.first();
+
+            return self.get_ascii_fallback(
+                level.and_then(|l| first_char(&l.sym)).unwrap_or('&'),
+                level.and_then(|l| l.color.as_deref()),
+            );
+        }
+
+        if let Some(part) = json_data.vehicle_parts.get(cdda_id) {
+            return self.get_ascii_fallback(
+                first_char(&part.symbol).unwrap_or('='),
+                part.color.as_deref(),
+            );
+        }
+
+        // Items are not drawn on the map, so their ids never end up here
         FALLBACK_TILE_MAPPING.first().unwrap().1
     }
     fn get_sprite(
@@ -329,6 +374,20 @@ impl Tilesheet for LegacyTilesheet {
         id: &MappedCDDAId,
         json_data: &DeserializedCDDAJsonData,
     ) -> Option<&Sprite> {
+        let seasonal_id = CDDAIdentifier(format!(
+            "{}{}",
+            id.tilesheet_id.full(),
+            self.season.suffix()
+        ));
+
+        if let Some(sprite) = self.id_map.get(&seasonal_id) {
+            return Some(sprite);
+        }
+
+        if id.tilesheet_id.prefix.as_deref() == Some(CORPSE_PREFIX) {
+            return self.get_corpse_sprite(&id.tilesheet_id.id, json_data);
+        }
+
         match self.id_map.get(&id.tilesheet_id.full()) {
             None => {
                 debug!(
@@ -358,6 +417,12 @@ impl Tilesheet for LegacyTilesheet {
                     &sliced_postfix.tilesheet_id.id,
                     &json_data,
                 )
+                .or_else(|| {
+                    self.get_npc_sprite(
+                        &sliced_postfix.tilesheet_id.id,
+                        json_data,
+                    )
+                })
             },
             Some(s) => {
                 debug!("Found sprite with id {}", id.tilesheet_id.full());
@@ -368,6 +433,130 @@ impl Tilesheet for LegacyTilesheet {
 }
 
 impl LegacyTilesheet {
+    /// How the tiles of this tilesheet are placed on the screen
+    pub fn projection(&self) -> &Projection {
+        &self.projection
+    }
+
+    pub fn spritesheets(&self) -> &Vec<SpritesheetInfo> {
+        &self.spritesheets
+    }
+
+    /// The position in [`LegacyTilesheet::spritesheets`] of the spritesheet
+    /// which contains the sprite with the `index`
+    pub fn spritesheet_of(&self, index: SpriteIndex) -> Option<usize> {
+        self.spritesheets.iter().position(|s| match s.range {
+            None => false,
+            Some((from, to)) => (from..=to).contains(&index),
+        })
+    }
+
+    /// Whether the fallback sprites come from the embedded ascii spritesheet
+    /// because the tileset does not contain one
+    pub fn uses_embedded_fallback(&self) -> bool {
+        self.uses_embedded_fallback
+    }
+
+    pub fn validation_report(&self) -> &TilesetValidationReport {
+        &self.validation_report
+    }
+
+    pub fn set_season(&mut self, season: Season) {
+        self.season = season;
+    }
+
+    /// The png image of the spritesheet with the `file` name if it was
+    /// composited from the sources of the tileset
+    pub fn composed_spritesheet(&self, file: &str) -> Option<&Vec<u8>> {
+        self.composed_spritesheets.get(file)
+    }
+
+    pub fn atlas(&self) -> &TextureAtlas {
+        &self.atlas
+    }
+
+    /// Returns the generic npc sprite if the `id` is an npc class or an npc
+    /// template
+    fn get_npc_sprite(
+        &self,
+        id: &CDDAIdentifier,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Option<&Sprite> {
+        if !is_npc(id, json_data) {
+            return None;
+        }
+
+        self.id_map.get(&CDDAIdentifier::from(NPC_SPRITE_ID))
+    }
+
+    /// The overlay sprites which are drawn on top of the npc with the `id`, in
+    /// the order they are drawn. Every mutation the class of the npc always
+    /// gives its npcs has an overlay if the tileset defines one.
+    pub fn get_overlay_sprites(
+        &self,
+        id: &CDDAIdentifier,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Vec<&Sprite> {
+        let class = match json_data.npcs.get(id) {
+            None => json_data.npc_classes.get(id),
+            Some(npc) => npc
+                .class
+                .as_ref()
+                .and_then(|c| json_data.npc_classes.get(c)),
+        };
+
+        let Some(class) = class else {
+            return vec![];
+        };
+
+        class
+            .traits
+            .iter()
+            .filter(|t| t.prob.is_none_or(|prob| prob >= 100))
+            .filter_map(|t| t.id.as_ref())
+            .filter_map(|mutation| {
+                MUTATION_OVERLAY_PREFIXES.iter().find_map(|prefix| {
+                    self.id_map
+                        .get(&CDDAIdentifier(format!("{}{}", prefix, mutation)))
+                })
+            })
+            .collect()
+    }
+
+    /// Corpses without their own sprite use the corpse sprite of the monster
+    /// their monster looks like, or the generic corpse sprite
+    fn get_corpse_sprite(
+        &self,
+        monster: &CDDAIdentifier,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Option<&Sprite> {
+        let mut visited = HashSet::new();
+        let mut current = Some(monster.clone());
+
+        while let Some(monster) = current {
+            if !visited.insert(monster.clone()) {
+                break;
+            }
+
+            let corpse_id = TilesheetCDDAId {
+                id: monster.clone(),
+                prefix: Some(CORPSE_PREFIX.to_string()),
+                postfix: None,
+            };
+
+            if let Some(sprite) = self.id_map.get(&corpse_id.full()) {
+                return Some(sprite);
+            }
+
+            current = json_data
+                .monsters
+                .get(&monster)
+                .and_then(|m| m.looks_like.clone());
+        }
+
+        self.id_map.get(&CDDAIdentifier::from(CORPSE_PREFIX))
+    }
+
     fn get_looks_like_sprite(
         &self,
         id: &CDDAIdentifier,
@@ -427,9 +616,42 @@ impl LegacyTilesheet {
         get_looks_like_sprite!(json_data.terrain);
         get_looks_like_sprite!(json_data.furniture);
         get_looks_like_sprite!(json_data.vehicle_parts);
+        get_looks_like_sprite!(json_data.traps);
+        get_looks_like_sprite!(json_data.monsters);
 
         None
     }
+
+    /// The sprite of the fallback spritesheet with the `symbol` in the
+    /// `color`, the generic fallback tile is used if there is none
+    fn get_ascii_fallback(
+        &self,
+        symbol: char,
+        color: Option<&str>,
+    ) -> SpriteIndex {
+        let color = color.unwrap_or("WHITE").to_uppercase();
+        let fallback_id = format!("{}_{}", symbol, color);
+
+        if let Some(index) = self.fallback_map.get(&fallback_id) {
+            return *index;
+        }
+
+        // Light and dark variants which the fallback spritesheet does not
+        // contain are drawn in their base color
+        let base_id = format!(
+            "{}_{}",
+            symbol,
+            color.replace("LIGHT_", "").replace("DARK_", "")
+        );
+
+        match self.fallback_map.get(&base_id) {
+            None => {
+                info!("No fallback for {} found", fallback_id);
+                FALLBACK_TILE_MAPPING.first().unwrap().1
+            },
+            Some(index) => *index,
+        }
+    }
 }
 
 pub async fn load_tilesheet(
@@ -445,16 +667,84 @@ pub async fn load_tilesheet(
         Some(p) => p.clone(),
     };
 
-    let config_path = cdda_path
-        .join("gfx")
-        .join(&tileset)
-        .join("tile_config.json");
+    let mut tilesheet = load_tileset(&cdda_path, &tileset).await?;
+    tilesheet.set_season(editor_data.config.season);
+
+    Ok(Some(tilesheet))
+}
+
+/// Loads the `tileset` from the gfx directory of the CDDA installation at
+/// `cdda_path` without touching the editor data. Tileset source directories
+/// are composited in memory.
+pub async fn load_tileset(
+    cdda_path: &Path,
+    tileset: &str,
+) -> Result<LegacyTilesheet, Error> {
+    let tileset_path = cdda_path.join("gfx").join(tileset);
+
+    if compose::is_tileset_source(&tileset_path) {
+        let source = TilesetSource::read(&tileset_path).await?;
+        let composed_spritesheets = source.compose()?;
+
+        let mut tilesheet_loader = LegacyTilesheetLoader::new(source.config);
+        let mut tilesheet = tilesheet_loader.load().await?;
+
+        tilesheet.atlas =
+            TextureAtlas::build(&tilesheet.spritesheets, |file| {
+                composed_spritesheets
+                    .get(file)
+                    .cloned()
+                    .ok_or(anyhow!("Spritesheet {} was not composed", file))
+            })?;
+        tilesheet.composed_spritesheets = composed_spritesheets;
+
+        return Ok(tilesheet);
+    }
+
+    let config_path = tileset_path.join("tile_config.json");
 
     let mut tile_config_loader = TileConfigLoader::new(config_path);
     let config = tile_config_loader.load().await?;
 
     let mut tilesheet_loader = LegacyTilesheetLoader::new(config);
-    let tilesheet = tilesheet_loader.load().await?;
+    let mut tilesheet = tilesheet_loader.load().await?;
 
-    Ok(Some(tilesheet))
+    tilesheet.atlas = TextureAtlas::build(&tilesheet.spritesheets, |file| {
+        Ok(std::fs::read(tileset_path.join(file))?)
+    })?;
+
+    Ok(tilesheet)
+}
+
+pub fn emit_embedded_fallback_warning(
+    app: &AppHandle,
+    tilesheet: Option<&LegacyTilesheet>,
+) {
+    match tilesheet {
+        Some(t) if t.uses_embedded_fallback() => {
+            app.emit_event(ToastMessage::warning(
+                "The selected tileset does not contain an ascii \
+                 spritesheet, using the default one instead",
+            ))
+            .unwrap();
+        },
+        _ => {},
+    }
+}
+
+/// Tells the frontend to load the textures of the `tilesheet` again. Without
+/// a `tilesheet`, the projection of the fallback tileset is sent.
+pub fn emit_tileset_changed(
+    app: &AppHandle,
+    tileset: Option<String>,
+    tilesheet: Option<&LegacyTilesheet>,
+) {
+    let fallback_tilesheet = app.state::<Arc<LegacyTilesheet>>();
+    let projection = *tilesheet.unwrap_or(&fallback_tilesheet).projection();
+
+    app.emit_event(TilesetChangedEvent {
+        tileset,
+        projection,
+    })
+    .unwrap();
 }
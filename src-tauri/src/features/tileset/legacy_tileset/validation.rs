@@ -0,0 +1,150 @@
+use crate::features::tileset::legacy_tileset::data::{
+    NormalSpritesheet, Tile,
+};
+use crate::features::tileset::legacy_tileset::SpriteIndex;
+use cdda_lib::types::{CDDAIdentifier, MeabyVec, MeabyWeighted};
+use serde::Serialize;
+use std::collections::HashSet;
+
+type SpriteIndices = MeabyVec<MeabyWeighted<MeabyVec<SpriteIndex>>>;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum TilesetValidationIssue {
+    DuplicateId {
+        id: CDDAIdentifier,
+        file: String,
+    },
+    EmptySpriteArray {
+        id: CDDAIdentifier,
+        file: String,
+        /// Either "fg" or "bg"
+        field: &'static str,
+    },
+    AdditionalTilesOnNonMultitile {
+        id: CDDAIdentifier,
+        file: String,
+    },
+    IndexOutOfRange {
+        id: CDDAIdentifier,
+        file: String,
+        index: SpriteIndex,
+        range: (u32, u32),
+    },
+}
+
+/// Structural issues that were found while loading a `tile_config.json`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TilesetValidationReport {
+    pub issues: Vec<TilesetValidationIssue>,
+}
+
+impl TilesetValidationReport {
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+fn is_empty_entry(entry: &MeabyWeighted<MeabyVec<SpriteIndex>>) -> bool {
+    let indices = match entry {
+        MeabyWeighted::Weighted(w) => &w.data,
+        MeabyWeighted::NotWeighted(nw) => nw,
+    };
+
+    match indices {
+        MeabyVec::Single(_) => false,
+        MeabyVec::Vec(v) => v.is_empty(),
+    }
+}
+
+fn has_empty_array(indices: &SpriteIndices) -> bool {
+    match indices {
+        MeabyVec::Single(s) => is_empty_entry(s),
+        MeabyVec::Vec(v) => v.is_empty() || v.iter().any(is_empty_entry),
+    }
+}
+
+fn collect_indices(indices: &SpriteIndices) -> Vec<SpriteIndex> {
+    indices
+        .iter()
+        .flat_map(|mw| mw.as_data().iter().cloned())
+        .collect()
+}
+
+/// Validates the tiles of a single spritesheet. `seen_ids` contains the ids of
+/// all tiles that have been validated before and is used to detect duplicates
+/// across spritesheets.
+pub(super) fn validate_spritesheet(
+    spritesheet: &NormalSpritesheet,
+    seen_ids: &mut HashSet<CDDAIdentifier>,
+    report: &mut TilesetValidationReport,
+) {
+    for tile in spritesheet.tiles.iter() {
+        validate_tile(tile, spritesheet, seen_ids, report);
+    }
+}
+
+fn validate_tile(
+    tile: &Tile,
+    spritesheet: &NormalSpritesheet,
+    seen_ids: &mut HashSet<CDDAIdentifier>,
+    report: &mut TilesetValidationReport,
+) {
+    let first_id = match tile.id.first() {
+        None => return,
+        Some(id) => id.clone(),
+    };
+
+    for id in tile.id.iter() {
+        if !seen_ids.insert(id.clone()) {
+            report.issues.push(TilesetValidationIssue::DuplicateId {
+                id: id.clone(),
+                file: spritesheet.file.clone(),
+            });
+        }
+    }
+
+    if tile.additional_tiles.is_some() && !tile.multitile.unwrap_or(false) {
+        report
+            .issues
+            .push(TilesetValidationIssue::AdditionalTilesOnNonMultitile {
+                id: first_id.clone(),
+                file: spritesheet.file.clone(),
+            });
+    }
+
+    let mut sprite_fields = vec![("fg", &tile.fg), ("bg", &tile.bg)];
+
+    for additional_tile in tile.additional_tiles.iter().flatten() {
+        sprite_fields.push(("fg", &additional_tile.fg));
+        sprite_fields.push(("bg", &additional_tile.bg));
+    }
+
+    let (from, to) = spritesheet.range;
+
+    for (field, indices) in sprite_fields {
+        let indices = match indices {
+            None => continue,
+            Some(i) => i,
+        };
+
+        if has_empty_array(indices) {
+            report.issues.push(TilesetValidationIssue::EmptySpriteArray {
+                id: first_id.clone(),
+                file: spritesheet.file.clone(),
+                field,
+            });
+        }
+
+        for index in collect_indices(indices) {
+            if index < from || index > to {
+                report.issues.push(TilesetValidationIssue::IndexOutOfRange {
+                    id: first_id.clone(),
+                    file: spritesheet.file.clone(),
+                    index,
+                    range: (from, to),
+                });
+            }
+        }
+    }
+}
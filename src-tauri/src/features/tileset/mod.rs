@@ -4,6 +4,7 @@ pub mod legacy_tileset;
 
 use crate::data::io::DeserializedCDDAJsonData;
 use crate::data::TileLayer;
+use crate::features::connect_groups::CustomConnectGroups;
 use crate::features::map::MappedCDDAId;
 use crate::features::program_data::AdjacentSprites;
 use crate::features::tileset::data::AdditionalTileType;
@@ -14,11 +15,16 @@ use crate::features::tileset::legacy_tileset::{
     FinalIds, Rotated, Rotates, SpriteIndex, TilesheetCDDAId,
 };
 use crate::util::CardinalDirection::{East, North, South, West};
-use crate::util::{CardinalDirection, GetRandom, Rotation};
+use crate::util::{CardinalDirection, Rotation};
 use cdda_lib::types::{CDDAIdentifier, MeabyVec, Weighted};
+use cdda_lib::weighted::WeightedChoice;
 use data::MeabyAnimated;
+use glam::{IVec2, IVec3};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 pub(super) trait Tilesheet {
     fn get_fallback(
@@ -128,6 +134,7 @@ impl Sprite {
     }
 
     fn get_random_sprite(
+        picker: &VariantPicker,
         mapped_id: &MappedCDDAId,
         tilesheet_ids: &Vec<Weighted<Rotates>>,
         rotates: bool,
@@ -136,7 +143,7 @@ impl Sprite {
             return None;
         }
 
-        let random_id = tilesheet_ids.get_random().clone();
+        let random_id = picker.pick(tilesheet_ids).clone();
         let (random_index, rotation) = Self::get_sprite_index_from_rotates(
             mapped_id,
             random_id.clone(),
@@ -150,6 +157,7 @@ impl Sprite {
     }
 
     fn get_random_additional_tile_sprite(
+        picker: &VariantPicker,
         mapped_id: &MappedCDDAId,
         tilesheet_ids: &Vec<Weighted<Rotates>>,
         additional_ids: &Vec<Weighted<Rotates>>,
@@ -164,7 +172,7 @@ impl Sprite {
         let rotated = match additional_tile_type {
             Center | Unconnected => {
                 let random_id = MeabyAnimated::Single(
-                    additional_ids.get_random().get(&direction).clone(),
+                    picker.pick(additional_ids).get(&direction).clone(),
                 );
 
                 match does_rotate {
@@ -175,8 +183,8 @@ impl Sprite {
                     false => Rotated::none(random_id),
                 }
             },
-            Corner | TConnection | Edge | EndPiece => match additional_ids
-                .get_random()
+            Corner | TConnection | Edge | EndPiece => match picker
+                .pick(additional_ids)
             {
                 Rotates::Auto(a) => match does_rotate {
                     true => Rotated {
@@ -198,11 +206,13 @@ impl Sprite {
                         ),
                         // TODO: Don't know if this is correct
                         South => Self::get_random_sprite(
+                            picker,
                             mapped_id,
                             tilesheet_ids,
                             does_rotate,
                         )?,
                         West => Self::get_random_sprite(
+                            picker,
                             mapped_id,
                             tilesheet_ids,
                             does_rotate,
@@ -216,11 +226,13 @@ impl Sprite {
                             Rotated::none(MeabyAnimated::Single(p.1.clone()))
                         },
                         South => Self::get_random_sprite(
+                            picker,
                             mapped_id,
                             tilesheet_ids,
                             does_rotate,
                         )?,
                         West => Self::get_random_sprite(
+                            picker,
                             mapped_id,
                             tilesheet_ids,
                             does_rotate,
@@ -288,14 +300,15 @@ impl Sprite {
         }
     }
 
-    fn get_matching_list(
+    pub fn get_matching_list(
         this_id: &TilesheetCDDAId,
         layer: &TileLayer,
         json_data: &DeserializedCDDAJsonData,
+        custom_connect_groups: &CustomConnectGroups,
         adjacent_sprites: &AdjacentSprites,
     ) -> (bool, bool, bool, bool) {
         let mut this_connects_to = json_data
-            .get_connects_to(this_id.id.clone(), layer)
+            .get_connects_to(this_id.id.clone(), layer, custom_connect_groups)
             .unwrap_or_default();
 
         let this_flags = json_data
@@ -307,7 +320,11 @@ impl Sprite {
                 None => (HashSet::new(), Vec::new()),
                 Some(top) => (
                     json_data
-                        .get_connect_groups(top.clone(), layer)
+                        .get_connect_groups(
+                            top.clone(),
+                            layer,
+                            custom_connect_groups,
+                        )
                         .unwrap_or_default(),
                     json_data.get_flags(top, layer).unwrap_or_default(),
                 ),
@@ -318,7 +335,11 @@ impl Sprite {
                 None => (HashSet::new(), Vec::new()),
                 Some(right) => (
                     json_data
-                        .get_connect_groups(right.clone(), layer)
+                        .get_connect_groups(
+                            right.clone(),
+                            layer,
+                            custom_connect_groups,
+                        )
                         .unwrap_or_default(),
                     json_data.get_flags(right, layer).unwrap_or_default(),
                 ),
@@ -329,7 +350,11 @@ impl Sprite {
                 None => (HashSet::new(), Vec::new()),
                 Some(bottom) => (
                     json_data
-                        .get_connect_groups(bottom.clone(), layer)
+                        .get_connect_groups(
+                            bottom.clone(),
+                            layer,
+                            custom_connect_groups,
+                        )
                         .unwrap_or_default(),
                     json_data.get_flags(bottom, layer).unwrap_or_default(),
                 ),
@@ -340,7 +365,11 @@ impl Sprite {
                 None => (HashSet::new(), Vec::new()),
                 Some(left) => (
                     json_data
-                        .get_connect_groups(left.clone(), layer)
+                        .get_connect_groups(
+                            left.clone(),
+                            layer,
+                            custom_connect_groups,
+                        )
                         .unwrap_or_default(),
                     json_data.get_flags(left, layer).unwrap_or_default(),
                 ),
@@ -404,6 +433,7 @@ impl Sprite {
     }
 
     fn get_sprite_from_multitile_sprite(
+        picker: &VariantPicker,
         mapped_id: &MappedCDDAId,
         fallback_ids: &ForeBackIds<FinalIds, FinalIds>,
         direction: &CardinalDirection,
@@ -414,7 +444,12 @@ impl Sprite {
         match multitile_sprite {
             None => match &fallback_ids.fg {
                 None => None,
-                Some(fg) => Self::get_random_sprite(mapped_id, fg, does_rotate),
+                Some(fg) => Self::get_random_sprite(
+                    picker,
+                    mapped_id,
+                    fg,
+                    does_rotate,
+                ),
             },
             Some(sprite) => match &sprite.ids.fg {
                 None => None,
@@ -425,6 +460,7 @@ impl Sprite {
                     };
 
                     Self::get_random_additional_tile_sprite(
+                        picker,
                         mapped_id,
                         fg_ids,
                         fg,
@@ -437,12 +473,139 @@ impl Sprite {
         }
     }
 
+    /// The additional tile of a multitile sprite for the
+    /// `additional_tile_type`
+    fn get_additional_tile(
+        &self,
+        additional_tile_type: &AdditionalTileType,
+    ) -> Option<&SingleSprite> {
+        let Sprite::Multitile {
+            center,
+            corner,
+            edge,
+            t_connection,
+            end_piece,
+            unconnected,
+            broken,
+            open,
+            ..
+        } = self
+        else {
+            return None;
+        };
+
+        match additional_tile_type {
+            Center => center.as_ref(),
+            Corner => corner.as_ref(),
+            Edge => edge.as_ref(),
+            TConnection => t_connection.as_ref(),
+            EndPiece => end_piece.as_ref(),
+            Unconnected => unconnected.as_ref(),
+            AdditionalTileType::Broken => broken.as_ref(),
+            AdditionalTileType::Open => open.as_ref(),
+            AdditionalTileType::H => None,
+        }
+    }
+
+    /// The additional tile of a broken or open tile. Tiles without one are
+    /// drawn like tiles which are not broken or open.
+    fn get_state_tile(
+        &self,
+        mapped_id: &MappedCDDAId,
+    ) -> Option<&SingleSprite> {
+        if mapped_id.is_broken {
+            if let Some(broken) =
+                self.get_additional_tile(&AdditionalTileType::Broken)
+            {
+                return Some(broken);
+            }
+        }
+
+        match mapped_id.is_open {
+            true => self.get_additional_tile(&AdditionalTileType::Open),
+            false => None,
+        }
+    }
+
+    /// The additional tile which is used for the connections in the
+    /// `matching_list` and the direction it faces
+    fn get_additional_tile_type(
+        matching_list: (bool, bool, bool, bool),
+    ) -> (CardinalDirection, AdditionalTileType) {
+        match matching_list {
+            (true, true, true, true) => (North, Center),
+            (true, true, true, false) => (East, TConnection),
+            (true, true, false, true) => (North, TConnection),
+            (true, false, true, true) => (West, TConnection),
+            (false, true, true, true) => (South, TConnection),
+            (true, true, false, false) => (North, Corner),
+            (true, false, false, true) => (West, Corner),
+            (false, true, true, false) => (East, Corner),
+            (false, false, true, true) => (South, Corner),
+            (true, false, false, false) => (North, EndPiece),
+            (false, true, false, false) => (East, EndPiece),
+            (false, false, true, false) => (South, EndPiece),
+            (false, false, false, true) => (West, EndPiece),
+            (false, true, false, true) => (East, Edge),
+            (true, false, true, false) => (North, Edge),
+            (false, false, false, false) => (North, Unconnected),
+        }
+    }
+
+    /// A random variant of the `ids`, or every id if the ids are the frames
+    /// of an animation
+    fn get_sprite_from_ids(
+        picker: &VariantPicker,
+        mapped_id: &MappedCDDAId,
+        ids: &Vec<Weighted<Rotates>>,
+        animated: bool,
+        rotates: bool,
+    ) -> Option<Rotated<MeabyAnimated<SpriteIndex>>> {
+        match animated {
+            true => Self::get_random_animated_sprite(mapped_id, ids, rotates),
+            false => Self::get_random_sprite(picker, mapped_id, ids, rotates),
+        }
+    }
+
+    /// Every frame of an animated additional tile facing the `direction`
+    fn get_animated_additional_tile_sprite(
+        mapped_id: &MappedCDDAId,
+        additional_ids: &Vec<Weighted<Rotates>>,
+        direction: &CardinalDirection,
+        does_rotate: bool,
+    ) -> Option<Rotated<MeabyAnimated<SpriteIndex>>> {
+        let first = additional_ids.first()?;
+
+        let frames = additional_ids
+            .iter()
+            .map(|id| match (&id.data, direction) {
+                // Sprites with two rotations look the same when they are
+                // rotated by 180 degrees
+                (Rotates::Pre2(p), South) => p.0.clone(),
+                (Rotates::Pre2(p), West) => p.1.clone(),
+                (rotates, direction) => rotates.get(direction).clone(),
+            })
+            .collect();
+
+        let rotation = match (does_rotate, &first.data) {
+            (false, _) => Rotation::Deg0,
+            (true, Rotates::Auto(_)) => {
+                Rotation::from(direction.clone()) + mapped_id.rotation.clone()
+            },
+            (true, _) => mapped_id.rotation.clone(),
+        };
+
+        Some(Rotated::new(MeabyAnimated::Vec(frames), rotation))
+    }
+
     pub fn get_fg_id(
         &self,
+        picker: &VariantPicker,
         mapped_id: &MappedCDDAId,
         layer: &TileLayer,
         adjacent_sprites: &AdjacentSprites,
         json_data: &DeserializedCDDAJsonData,
+        custom_connect_groups: &CustomConnectGroups,
     ) -> Option<Rotated<MeabyVec<SpriteIndex>>> {
         match self {
             Sprite::Single(s) => match s.animated {
@@ -455,249 +618,77 @@ impl Sprite {
                 false => match &s.ids.fg {
                     None => None,
                     Some(fg) => {
-                        Self::get_random_sprite(mapped_id, fg, s.rotates)
+                        Self::get_random_sprite(
+                            picker, mapped_id, fg, s.rotates,
+                        )
                     },
                 },
             },
-            Sprite::Multitile {
-                fallback,
-                center,
-                corner,
-                t_connection,
-                edge,
-                unconnected,
-                end_piece,
-                broken,
-                open,
-            } => match fallback.animated {
-                true => todo!(),
-                false => {
-                    if mapped_id.is_broken {
-                        return match broken {
-                            None => {
-                                return None;
-                            },
-                            Some(broken) => match &broken.ids.fg {
-                                None => match &fallback.ids.fg {
-                                    None => None,
-                                    Some(fg) => Self::get_random_sprite(
-                                        mapped_id,
-                                        fg,
-                                        fallback.rotates,
-                                    ),
-                                },
-                                Some(fg) => Self::get_random_sprite(
-                                    mapped_id,
-                                    fg,
-                                    fallback.rotates,
-                                ),
-                            },
-                        };
-                    }
-
-                    if mapped_id.is_open {
-                        return match open {
-                            None => {
-                                return None;
-                            },
-                            Some(open) => match &open.ids.fg {
-                                None => match &fallback.ids.fg {
-                                    None => None,
-                                    Some(fg) => Self::get_random_sprite(
-                                        mapped_id,
-                                        fg,
-                                        fallback.rotates,
-                                    ),
-                                },
-                                Some(fg) => Self::get_random_sprite(
-                                    mapped_id,
-                                    fg,
-                                    fallback.rotates,
-                                ),
-                            },
-                        };
-                    }
-
-                    let matching_list = Self::get_matching_list(
-                        &mapped_id.tilesheet_id,
-                        layer,
-                        json_data,
-                        adjacent_sprites,
+            Sprite::Multitile { fallback, .. } => {
+                // Open and broken tiles do not connect to their neighbors
+                if let Some(state) = self.get_state_tile(mapped_id) {
+                    let fg =
+                        state.ids.fg.as_ref().or(fallback.ids.fg.as_ref())?;
+
+                    return Self::get_sprite_from_ids(
+                        picker,
+                        mapped_id,
+                        fg,
+                        fallback.animated,
+                        fallback.rotates,
                     );
+                }
 
-                    match matching_list {
-                        (true, true, true, true) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &North,
-                                &Center,
-                                center.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (true, true, true, false) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &East,
-                                &TConnection,
-                                t_connection.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (true, true, false, true) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &North,
-                                &TConnection,
-                                t_connection.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (true, false, true, true) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &West,
-                                &TConnection,
-                                t_connection.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (false, true, true, true) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &South,
-                                &TConnection,
-                                t_connection.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (true, true, false, false) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &North,
-                                &Corner,
-                                corner.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (true, false, false, true) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &West,
-                                &Corner,
-                                corner.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (false, true, true, false) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &East,
-                                &Corner,
-                                corner.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (false, false, true, true) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &South,
-                                &Corner,
-                                corner.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (true, false, false, false) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &North,
-                                &EndPiece,
-                                end_piece.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (false, true, false, false) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &East,
-                                &EndPiece,
-                                end_piece.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (false, false, true, false) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &South,
-                                &EndPiece,
-                                end_piece.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (false, false, false, true) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &West,
-                                &EndPiece,
-                                end_piece.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (false, true, false, true) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &East,
-                                &Edge,
-                                edge.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (true, false, true, false) => {
-                            Self::get_sprite_from_multitile_sprite(
-                                mapped_id,
-                                &fallback.ids,
-                                &North,
-                                &Edge,
-                                edge.as_ref(),
-                                fallback.rotates,
-                            )
-                        },
-                        (false, false, false, false) => {
-                            Self::get_sprite_from_multitile_sprite(
+                let matching_list = Self::get_matching_list(
+                    &mapped_id.tilesheet_id,
+                    layer,
+                    json_data,
+                    custom_connect_groups,
+                    adjacent_sprites,
+                );
+                let (direction, additional_tile_type) =
+                    Self::get_additional_tile_type(matching_list);
+                let additional_tile =
+                    self.get_additional_tile(&additional_tile_type);
+
+                match fallback.animated {
+                    true => match additional_tile {
+                        None => Self::get_random_animated_sprite(
+                            mapped_id,
+                            fallback.ids.fg.as_ref()?,
+                            fallback.rotates,
+                        ),
+                        Some(tile) => {
+                            Self::get_animated_additional_tile_sprite(
                                 mapped_id,
-                                &fallback.ids,
-                                &North,
-                                &Unconnected,
-                                unconnected.as_ref(),
-                                fallback.rotates,
+                                tile.ids.fg.as_ref()?,
+                                &direction,
+                                tile.rotates,
                             )
                         },
-                    }
-                },
+                    },
+                    false => Self::get_sprite_from_multitile_sprite(
+                        picker,
+                        mapped_id,
+                        &fallback.ids,
+                        &direction,
+                        &additional_tile_type,
+                        additional_tile,
+                        fallback.rotates,
+                    ),
+                }
             },
         }
     }
 
     pub fn get_bg_id(
         &self,
+        picker: &VariantPicker,
         mapped_id: &MappedCDDAId,
         layer: &TileLayer,
         adjacent_sprites: &AdjacentSprites,
         json_data: &DeserializedCDDAJsonData,
+        custom_connect_groups: &CustomConnectGroups,
     ) -> Option<Rotated<MeabyVec<SpriteIndex>>> {
         match self {
             Sprite::Single(single) => match single.animated {
@@ -712,151 +703,173 @@ impl Sprite {
                 false => match &single.ids.bg {
                     None => None,
                     Some(bg) => {
-                        Self::get_random_sprite(mapped_id, bg, single.rotates)
+                        Self::get_random_sprite(
+                            picker,
+                            mapped_id,
+                            bg,
+                            single.rotates,
+                        )
                     },
                 },
             },
-            Sprite::Multitile {
-                fallback,
-                center,
-                corner,
-                t_connection,
-                edge,
-                unconnected,
-                end_piece,
-                broken,
-                open,
-            } => match fallback.animated {
-                true => todo!(),
-                false => {
-                    let random_fallback_sprite = match &fallback.ids.bg {
-                        None => None,
-                        Some(bg) => Self::get_random_sprite(
-                            mapped_id,
-                            bg,
-                            fallback.rotates,
-                        ),
-                    };
+            Sprite::Multitile { fallback, .. } => {
+                if let Some(state) = self.get_state_tile(mapped_id) {
+                    let bg =
+                        state.ids.bg.as_ref().or(fallback.ids.bg.as_ref())?;
 
-                    if mapped_id.is_broken {
-                        return match broken {
-                            None => return None,
-                            Some(broken) => match &broken.ids.bg {
-                                None => random_fallback_sprite,
-                                Some(bg) => Self::get_random_sprite(
-                                    mapped_id,
-                                    bg,
-                                    fallback.rotates,
-                                ),
-                            },
-                        };
-                    }
-
-                    if mapped_id.is_open {
-                        return match open {
-                            None => return None,
-                            Some(open) => match &open.ids.bg {
-                                None => random_fallback_sprite,
-                                Some(bg) => Self::get_random_sprite(
-                                    mapped_id,
-                                    bg,
-                                    fallback.rotates,
-                                ),
-                            },
-                        };
-                    }
-
-                    let matching_list = Self::get_matching_list(
-                        &mapped_id.tilesheet_id,
-                        layer,
-                        json_data,
-                        adjacent_sprites,
+                    return Self::get_sprite_from_ids(
+                        picker,
+                        mapped_id,
+                        bg,
+                        fallback.animated,
+                        fallback.rotates,
                     );
+                }
 
-                    match matching_list {
-                        (true, true, true, true) => match center {
-                            None => random_fallback_sprite,
-                            Some(center) => match &center.ids.bg {
-                                None => None,
-                                Some(bg) => Self::get_random_sprite(
-                                    mapped_id,
-                                    bg,
-                                    fallback.rotates,
-                                ),
-                            },
-                        },
-                        (true, true, true, false)
-                        | (true, true, false, true)
-                        | (true, false, true, true)
-                        | (false, true, true, true) => match t_connection {
-                            None => random_fallback_sprite,
-                            Some(t_connection) => match &t_connection.ids.bg {
-                                None => None,
-                                Some(bg) => Self::get_random_sprite(
-                                    mapped_id,
-                                    bg,
-                                    fallback.rotates,
-                                ),
-                            },
-                        },
-                        (true, true, false, false)
-                        | (true, false, false, true)
-                        | (false, true, true, false)
-                        | (false, false, true, true) => match corner {
-                            None => random_fallback_sprite,
-                            Some(corner) => match &corner.ids.bg {
-                                None => None,
-                                Some(bg) => Self::get_random_sprite(
-                                    mapped_id,
-                                    bg,
-                                    fallback.rotates,
-                                ),
-                            },
-                        },
-                        (true, false, false, false)
-                        | (false, true, false, false)
-                        | (false, false, true, false)
-                        | (false, false, false, true) => match end_piece {
-                            None => random_fallback_sprite,
-                            Some(end_piece) => match &end_piece.ids.bg {
-                                None => None,
-                                Some(bg) => Self::get_random_sprite(
-                                    mapped_id,
-                                    bg,
-                                    fallback.rotates,
-                                ),
-                            },
-                        },
-                        (false, true, false, true)
-                        | (true, false, true, false) => match edge {
-                            None => random_fallback_sprite,
-                            Some(edge) => match &edge.ids.bg {
-                                None => None,
-                                Some(bg) => Self::get_random_sprite(
-                                    mapped_id,
-                                    bg,
-                                    fallback.rotates,
-                                ),
-                            },
-                        },
-                        (false, false, false, false) => match unconnected {
-                            None => random_fallback_sprite,
-                            Some(unconnected) => match &unconnected.ids.bg {
-                                None => None,
-                                Some(bg) => Self::get_random_sprite(
-                                    mapped_id,
-                                    bg,
-                                    fallback.rotates,
-                                ),
-                            },
-                        },
-                    }
-                },
+                let matching_list = Self::get_matching_list(
+                    &mapped_id.tilesheet_id,
+                    layer,
+                    json_data,
+                    custom_connect_groups,
+                    adjacent_sprites,
+                );
+                let (_, additional_tile_type) =
+                    Self::get_additional_tile_type(matching_list);
+
+                let bg = match self.get_additional_tile(&additional_tile_type) {
+                    None => fallback.ids.bg.as_ref()?,
+                    Some(tile) => tile.ids.bg.as_ref()?,
+                };
+
+                Self::get_sprite_from_ids(
+                    picker,
+                    mapped_id,
+                    bg,
+                    fallback.animated,
+                    fallback.rotates,
+                )
+            },
+        }
+    }
+}
+
+/// The season tiles are drawn in. Tilesets can define a variant of a tile for
+/// every season, which has the id of the tile with a `_season_<season>`
+/// suffix.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Season {
+    #[default]
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// The suffix of the ids of the tiles which are drawn in this season
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Season::Spring => "_season_spring",
+            Season::Summer => "_season_summer",
+            Season::Autumn => "_season_autumn",
+            Season::Winter => "_season_winter",
+        }
+    }
+}
+
+/// The amount of tiles in a row of the draw order. Maps have to be narrower
+/// than this for their tiles to be drawn in the right order.
+const DRAW_ROW_LENGTH: i32 = 1000;
+
+/// How the tiles of a tileset are placed on the screen
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct Projection {
+    /// Isometric tilesets draw tiles as diamonds which are half as tall as
+    /// they are wide
+    pub iso: bool,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub zlevel_height: u32,
+}
+
+impl Projection {
+    /// The amount of pixels the tiles of the z-level `z` are moved up
+    pub fn height_offset(&self, z: i32) -> i32 {
+        z * self.zlevel_height as i32
+    }
+
+    /// The position of the tile at `position` in the back to front order the
+    /// tiles of a z-level are drawn in
+    pub fn depth(&self, position: IVec2) -> i32 {
+        match self.iso {
+            false => (position.y + 1) * DRAW_ROW_LENGTH + position.x,
+            // The rows of an isometric projection run diagonally, starting
+            // with the row at the top right of the map
+            true => {
+                (position.y - position.x + DRAW_ROW_LENGTH / 2)
+                    * DRAW_ROW_LENGTH
+                    + position.x
             },
         }
     }
 }
 
+/// Picks one of the weighted sprite variants of a tile. The picked variant
+/// only depends on the seed of the picker, so the same cell keeps its variant
+/// between re-renders instead of flickering.
+#[derive(Debug, Clone)]
+pub struct VariantPicker {
+    seed: u64,
+    pinned: Option<usize>,
+}
+
+impl VariantPicker {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, pinned: None }
+    }
+
+    /// Always pick the variant at the index `pinned` if it exists
+    pub fn with_pinned(mut self, pinned: Option<usize>) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Create a picker for the tile with the id `id` at the `position` on the
+    /// `layer`. `variant_seed` is the seed of the project which can be changed
+    /// to reroll the variants of all tiles.
+    pub fn for_tile(
+        variant_seed: u64,
+        position: IVec3,
+        layer: &TileLayer,
+        id: &TilesheetCDDAId,
+    ) -> Self {
+        let mut hasher = DefaultHasher::new();
+        variant_seed.hash(&mut hasher);
+        position.hash(&mut hasher);
+        layer.hash(&mut hasher);
+        id.full().hash(&mut hasher);
+
+        Self::new(hasher.finish())
+    }
+
+    pub fn pick<'a, T>(&self, variants: &'a Vec<Weighted<T>>) -> &'a T {
+        match self.pinned.and_then(|i| variants.get(i)) {
+            None => {},
+            Some(pinned) => return &pinned.data,
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        // All weights are zero, so we just take the first variant
+        variants
+            .choose_weighted(&mut rng)
+            .unwrap_or_else(|| &variants.first().unwrap().data)
+    }
+}
+
 #[derive(Debug)]
 pub(super) struct ForeBackIds<FG, BG> {
     pub fg: FG,
@@ -873,4 +886,7 @@ impl<FG, BG> ForeBackIds<FG, BG> {
 pub(super) enum SpriteLayer {
     Bg = 0,
     Fg = 1,
+    /// The first overlay which is drawn on top of the foreground, the other
+    /// overlays of a tile follow on the sub layers after it
+    Overlay = 2,
 }
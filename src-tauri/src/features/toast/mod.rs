@@ -4,6 +4,7 @@ use serde::Serialize;
 #[serde(rename_all = "camelCase")]
 pub enum ToastType {
     Success,
+    Warning,
     Error,
 }
 
@@ -22,6 +23,13 @@ impl ToastMessage {
         }
     }
 
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            ty: ToastType::Warning,
+            message: message.into(),
+        }
+    }
+
     pub fn error(message: impl Into<String>) -> Self {
         Self {
             ty: ToastType::Error,
@@ -0,0 +1,45 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::program_data::EditorData;
+use crate::features::validation::{validate_mapgen_json, MapgenIssue};
+use crate::impl_serialize_for_error;
+use crate::util::{get_current_project, get_json_data, CDDADataError};
+use serde::{Serialize, Serializer};
+use std::fs;
+use std::path::PathBuf;
+use tauri::async_runtime::Mutex;
+use tauri::State;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ValidateMapgenError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error("Failed to read the mapgen file: {0}")]
+    ReadFailed(String),
+}
+
+impl_serialize_for_error!(ValidateMapgenError);
+
+/// Checks the mapgen entries of the json file at `path` against the
+/// constraints CDDA puts on mapgen and the loaded CDDA data. Regional ids are
+/// resolved with the region of the opened project.
+#[tauri::command]
+pub async fn validate_mapgen(
+    path: PathBuf,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<Vec<MapgenIssue>, ValidateMapgenError> {
+    let text = fs::read_to_string(&path)
+        .map_err(|e| ValidateMapgenError::ReadFailed(e.to_string()))?;
+
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let editor_data_lock = editor_data.lock().await;
+    let region = get_current_project(&editor_data_lock)
+        .ok()
+        .and_then(|project| project.region.as_ref());
+
+    Ok(validate_mapgen_json(&text, json_data, region))
+}
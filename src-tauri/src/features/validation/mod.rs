@@ -0,0 +1,621 @@
+pub(crate) mod handlers;
+pub mod positions;
+pub mod suggestions;
+
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::diagnostics::DiagnosticSeverity;
+use crate::features::palette::{
+    check_palette_references, PaletteReferenceProblem,
+};
+use crate::features::validation::positions::{
+    pointer_join, JsonPosition, JsonPositions,
+};
+use crate::features::validation::suggestions::closest_ids;
+use cdda_lib::types::{CDDAIdentifier, MapGenValue};
+use cdda_lib::{
+    DEFAULT_MAP_HEIGHT, DEFAULT_MAP_WIDTH, NULL_FURNITURE, NULL_TERRAIN,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// A problem with a mapgen entry which would make CDDA fail to load it or
+/// generate something else than intended
+#[derive(Debug, Clone, Serialize)]
+pub struct MapgenIssue {
+    pub severity: DiagnosticSeverity,
+    /// The om_terrain, nested_mapgen_id or update_mapgen_id of the mapgen
+    pub mapgen: Option<String>,
+    /// The json pointer of the value with the problem
+    pub pointer: String,
+    #[serde(flatten)]
+    pub position: JsonPosition,
+    pub message: String,
+    /// Known ids which are similar to the unknown id the issue is about
+    pub suggestions: Vec<CDDAIdentifier>,
+}
+
+/// An id which is referenced by a mapgen value
+struct IdReference {
+    pointer: String,
+    id: CDDAIdentifier,
+}
+
+/// The ids and weights which are referenced by mapgen values
+#[derive(Default)]
+struct References {
+    ids: Vec<IdReference>,
+    weights: Vec<(String, f64)>,
+}
+
+impl References {
+    /// Collects the ids of the mapgen `value` at `pointer`. Ids of parameters
+    /// are taken from the default of the parameter in the `parameters`, which
+    /// are located at `parameters_pointer`.
+    fn collect(
+        &mut self,
+        value: &Value,
+        pointer: &str,
+        parameters: Option<(&Value, &str)>,
+    ) {
+        match value {
+            Value::String(id) => self.ids.push(IdReference {
+                pointer: pointer.to_string(),
+                id: CDDAIdentifier(id.clone()),
+            }),
+            Value::Array(values) => match values.as_slice() {
+                // A weighted value like ["t_floor", 5]
+                [inner, Value::Number(weight)] if !inner.is_number() => {
+                    self.weights.push((
+                        pointer_join(pointer, 1),
+                        weight.as_f64().unwrap_or_default(),
+                    ));
+                    self.collect(inner, &pointer_join(pointer, 0), parameters);
+                },
+                values => {
+                    for (i, value) in values.iter().enumerate() {
+                        self.collect(
+                            value,
+                            &pointer_join(pointer, i),
+                            parameters,
+                        );
+                    }
+                },
+            },
+            Value::Object(object) => {
+                if let Some(param) =
+                    object.get("param").and_then(|p| p.as_str())
+                {
+                    if let Some((parameters, parameters_pointer)) = parameters {
+                        if let Some(default) =
+                            parameters.get(param).and_then(|p| p.get("default"))
+                        {
+                            let pointer = pointer_join(
+                                &pointer_join(parameters_pointer, param),
+                                "default",
+                            );
+                            self.collect(default, &pointer, None);
+                        }
+                    }
+
+                    if let Some(fallback) = object.get("fallback") {
+                        let pointer = pointer_join(pointer, "fallback");
+                        self.collect(fallback, &pointer, parameters);
+                    }
+                } else if let Some(cases) =
+                    object.get("cases").and_then(|c| c.as_object())
+                {
+                    let cases_pointer = pointer_join(pointer, "cases");
+
+                    for (case, value) in cases {
+                        let pointer = pointer_join(&cases_pointer, case);
+                        self.collect(value, &pointer, parameters);
+                    }
+                } else if let Some(distribution) = object.get("distribution") {
+                    let pointer = pointer_join(pointer, "distribution");
+                    self.collect(distribution, &pointer, parameters);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// The description of a mapgen which is shown with its issues
+fn mapgen_name(entry: &Value) -> Option<String> {
+    if let Some(om_terrain) = entry.get("om_terrain") {
+        let mut references = References::default();
+        references.collect(om_terrain, "", None);
+
+        let ids: Vec<String> =
+            references.ids.into_iter().map(|r| r.id.0).collect();
+
+        return Some(ids.join(", "));
+    }
+
+    entry
+        .get("nested_mapgen_id")
+        .or_else(|| entry.get("update_mapgen_id"))
+        .and_then(|id| id.as_str())
+        .map(|id| id.to_string())
+}
+
+struct MapgenValidator<'a> {
+    json_data: &'a DeserializedCDDAJsonData,
+    /// The region regional terrain and furniture are resolved with
+    region: Option<&'a CDDAIdentifier>,
+    positions: &'a JsonPositions,
+    mapgen: Option<String>,
+    issues: Vec<MapgenIssue>,
+}
+
+impl<'a> MapgenValidator<'a> {
+    fn report(
+        &mut self,
+        severity: DiagnosticSeverity,
+        pointer: &str,
+        position: JsonPosition,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(MapgenIssue {
+            severity,
+            mapgen: self.mapgen.clone(),
+            pointer: pointer.to_string(),
+            position,
+            message: message.into(),
+            suggestions: vec![],
+        })
+    }
+
+    fn error(&mut self, pointer: &str, message: impl Into<String>) {
+        let position = self.positions.get(pointer);
+        self.report(DiagnosticSeverity::Error, pointer, position, message);
+    }
+
+    fn warning(&mut self, pointer: &str, message: impl Into<String>) {
+        let position = self.positions.get(pointer);
+        self.report(DiagnosticSeverity::Warning, pointer, position, message);
+    }
+
+    /// Reports an id which does not exist together with the `known` ids which
+    /// are similar to it
+    fn unknown_id<'b>(
+        &mut self,
+        reference: &IdReference,
+        kind: &str,
+        known: impl IntoIterator<Item = &'b CDDAIdentifier>,
+    ) {
+        self.error(
+            &reference.pointer,
+            format!("The {} {} does not exist", kind, reference.id),
+        );
+
+        if let Some(issue) = self.issues.last_mut() {
+            issue.suggestions = closest_ids(&reference.id, known);
+        }
+    }
+
+    fn check_weights(&mut self, references: &References) {
+        for (pointer, weight) in references.weights.iter() {
+            if *weight <= 0. {
+                self.error(pointer, "The weight has to be greater than 0");
+            }
+        }
+    }
+
+    fn terrain_exists(&self, id: &CDDAIdentifier) -> bool {
+        let id = self.json_data.resolve_region_setting(id, self.region);
+        id.0 == NULL_TERRAIN || self.json_data.terrain.contains_key(&id)
+    }
+
+    fn furniture_exists(&self, id: &CDDAIdentifier) -> bool {
+        let id = self.json_data.resolve_region_setting(id, self.region);
+        id.0 == NULL_FURNITURE || self.json_data.furniture.contains_key(&id)
+    }
+
+    /// The symbols the palette `id` and the palettes it uses define
+    fn palette_symbols(
+        &self,
+        id: &CDDAIdentifier,
+        visited: &mut HashSet<CDDAIdentifier>,
+    ) -> HashSet<char> {
+        let mut symbols = HashSet::new();
+
+        if !visited.insert(id.clone()) {
+            return symbols;
+        }
+
+        let Some(palette) = self.json_data.palettes.get(id) else {
+            return symbols;
+        };
+
+        symbols.extend(palette.symbols.iter());
+
+        let palettes =
+            serde_json::to_value(&palette.palettes).unwrap_or_default();
+        let parameters =
+            serde_json::to_value(&palette.parameters).unwrap_or_default();
+
+        let mut references = References::default();
+        references.collect(&palettes, "", Some((&parameters, "")));
+
+        for reference in references.ids {
+            symbols.extend(self.palette_symbols(&reference.id, visited));
+        }
+
+        symbols
+    }
+
+    /// The size of the rows of a mapgen. Mapgens of multiple overmap terrains
+    /// contain the rows of every overmap terrain.
+    fn expected_size(entry: &Value, object: &Value) -> (usize, usize) {
+        let size = object
+            .get("mapgensize")
+            .and_then(|s| s.as_array())
+            .and_then(|s| Some((s.first()?.as_u64()?, s.get(1)?.as_u64()?)));
+
+        if let Some((width, height)) = size {
+            return (width as usize, height as usize);
+        }
+
+        match entry.get("om_terrain").and_then(|o| o.as_array()) {
+            Some(rows) if rows.iter().all(|r| r.is_array()) => {
+                let columns = rows
+                    .first()
+                    .and_then(|r| r.as_array())
+                    .map(|r| r.len())
+                    .unwrap_or(1);
+
+                (DEFAULT_MAP_WIDTH * columns, DEFAULT_MAP_HEIGHT * rows.len())
+            },
+            _ => (DEFAULT_MAP_WIDTH, DEFAULT_MAP_HEIGHT),
+        }
+    }
+
+    fn validate(&mut self, entry: &Value, pointer: &str) {
+        self.mapgen = mapgen_name(entry);
+
+        if let Some(weight) = entry.get("weight").and_then(|w| w.as_f64()) {
+            if weight <= 0. {
+                self.warning(
+                    &pointer_join(pointer, "weight"),
+                    "The mapgen is never chosen since its weight is not \
+                     greater than 0",
+                );
+            }
+        }
+
+        let object_pointer = pointer_join(pointer, "object");
+        let Some(object) = entry.get("object") else {
+            self.error(pointer, "The mapgen does not have an object");
+            return;
+        };
+
+        let parameters_pointer = pointer_join(&object_pointer, "parameters");
+        let parameters = object
+            .get("parameters")
+            .map(|p| (p, parameters_pointer.as_str()));
+
+        let fill_ter = object.get("fill_ter");
+        if let Some(fill_ter) = fill_ter {
+            let mut references = References::default();
+            references.collect(
+                fill_ter,
+                &pointer_join(&object_pointer, "fill_ter"),
+                parameters,
+            );
+            self.check_terrain(&references);
+        }
+
+        let mut symbols = HashSet::new();
+
+        if let Some(palettes) = object.get("palettes") {
+            let mut references = References::default();
+            references.collect(
+                palettes,
+                &pointer_join(&object_pointer, "palettes"),
+                parameters,
+            );
+            self.check_weights(&references);
+
+            let mut visited = HashSet::new();
+            for reference in references.ids {
+                match self.json_data.palettes.contains_key(&reference.id) {
+                    true => symbols.extend(
+                        self.palette_symbols(&reference.id, &mut visited),
+                    ),
+                    false => self.error(
+                        &reference.pointer,
+                        format!("The palette {} does not exist", reference.id),
+                    ),
+                }
+            }
+
+            self.check_nested_palettes(
+                palettes,
+                &pointer_join(&object_pointer, "palettes"),
+                object.get("parameters"),
+            );
+        }
+
+        if let Some(object) = object.as_object() {
+            for (key, mapping) in object {
+                if key == "parameters" {
+                    continue;
+                }
+
+                let Some(mapping) = mapping.as_object() else {
+                    continue;
+                };
+
+                for symbol in mapping.keys() {
+                    let mut chars = symbol.chars();
+                    if let (Some(c), None) = (chars.next(), chars.next()) {
+                        symbols.insert(c);
+                    }
+                }
+            }
+        }
+
+        for (field, is_terrain) in [("terrain", true), ("furniture", false)] {
+            let Some(mapping) = object.get(field).and_then(|m| m.as_object())
+            else {
+                continue;
+            };
+
+            let mapping_pointer = pointer_join(&object_pointer, field);
+            let mut references = References::default();
+
+            for (symbol, value) in mapping {
+                let pointer = pointer_join(&mapping_pointer, symbol);
+                references.collect(value, &pointer, parameters);
+            }
+
+            match is_terrain {
+                true => self.check_terrain(&references),
+                false => self.check_furniture(&references),
+            }
+        }
+
+        if let Some(rows) = object.get("rows").and_then(|r| r.as_array()) {
+            let rows_pointer = pointer_join(&object_pointer, "rows");
+            self.check_rows(
+                rows,
+                &rows_pointer,
+                Self::expected_size(entry, object),
+                &symbols,
+                fill_ter.is_some(),
+            );
+        }
+    }
+
+    /// Reports the palettes which are used through other palettes but do not
+    /// exist or include each other
+    fn check_nested_palettes(
+        &mut self,
+        palettes: &Value,
+        pointer: &str,
+        parameters: Option<&Value>,
+    ) {
+        let Ok(palettes) =
+            serde_json::from_value::<Vec<MapGenValue>>(palettes.clone())
+        else {
+            return;
+        };
+        let parameters = parameters
+            .and_then(|p| serde_json::from_value(p.clone()).ok())
+            .unwrap_or_default();
+
+        let problems = check_palette_references(
+            &palettes,
+            &parameters,
+            &self.json_data.palettes,
+        );
+
+        for problem in problems {
+            // Palettes which are used directly are reported with their own
+            // position
+            if let PaletteReferenceProblem::Missing { chain, .. } = &problem {
+                if chain.is_empty() {
+                    continue;
+                }
+            }
+
+            self.error(pointer, problem.to_string());
+        }
+    }
+
+    fn check_terrain(&mut self, references: &References) {
+        self.check_weights(references);
+
+        for reference in references.ids.iter() {
+            if !self.terrain_exists(&reference.id) {
+                let json_data = self.json_data;
+                let terrain = json_data.terrain.keys();
+                self.unknown_id(reference, "terrain", terrain);
+            }
+        }
+    }
+
+    fn check_furniture(&mut self, references: &References) {
+        self.check_weights(references);
+
+        for reference in references.ids.iter() {
+            if !self.furniture_exists(&reference.id) {
+                let json_data = self.json_data;
+                let furniture = json_data.furniture.keys();
+                self.unknown_id(reference, "furniture", furniture);
+            }
+        }
+    }
+
+    fn check_rows(
+        &mut self,
+        rows: &[Value],
+        rows_pointer: &str,
+        (width, height): (usize, usize),
+        symbols: &HashSet<char>,
+        has_fill_ter: bool,
+    ) {
+        if rows.len() != height {
+            self.error(
+                rows_pointer,
+                format!("Expected {} rows but found {}", height, rows.len()),
+            );
+        }
+
+        let mut reported = HashSet::new();
+
+        for (y, row) in rows.iter().enumerate() {
+            let pointer = pointer_join(rows_pointer, y);
+
+            let Some(row) = row.as_str() else {
+                self.error(&pointer, "The row has to be a string");
+                continue;
+            };
+
+            let row_width = row.chars().count();
+            if row_width != width {
+                self.error(
+                    &pointer,
+                    format!(
+                        "Expected the row to be {} characters wide but it is \
+                         {} characters wide",
+                        width, row_width
+                    ),
+                );
+            }
+
+            // The position of the first character of the row is one column
+            // after the opening quote
+            let row_position = self.positions.get(&pointer);
+
+            for (x, c) in row.chars().enumerate() {
+                if symbols.contains(&c) || !reported.insert(c) {
+                    continue;
+                }
+
+                let (severity, message) = match has_fill_ter {
+                    true => (
+                        DiagnosticSeverity::Warning,
+                        format!(
+                            "The symbol '{}' is not defined and is filled \
+                             with the fill_ter",
+                            c
+                        ),
+                    ),
+                    false => (
+                        DiagnosticSeverity::Error,
+                        format!("The symbol '{}' is not defined", c),
+                    ),
+                };
+
+                self.report(
+                    severity,
+                    &pointer,
+                    row_position.offset(x + 1),
+                    message,
+                );
+            }
+        }
+    }
+}
+
+/// Validates every mapgen in the json `text` against the constraints CDDA
+/// has for mapgens and the loaded `json_data`. Regional ids are resolved with
+/// the region settings of the `region`.
+pub fn validate_mapgen_json(
+    text: &str,
+    json_data: &DeserializedCDDAJsonData,
+    region: Option<&CDDAIdentifier>,
+) -> Vec<MapgenIssue> {
+    let document: Value = match serde_json::from_str(text) {
+        Ok(document) => document,
+        Err(e) => {
+            return vec![MapgenIssue {
+                severity: DiagnosticSeverity::Error,
+                mapgen: None,
+                pointer: String::new(),
+                position: JsonPosition {
+                    line: e.line(),
+                    column: e.column(),
+                },
+                message: e.to_string(),
+                suggestions: vec![],
+            }];
+        },
+    };
+
+    let positions = JsonPositions::index(text);
+    let mut validator = MapgenValidator {
+        json_data,
+        region,
+        positions: &positions,
+        mapgen: None,
+        issues: vec![],
+    };
+
+    let entries: Vec<(String, &Value)> = match &document {
+        Value::Array(entries) => entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (pointer_join("", i), entry))
+            .collect(),
+        entry => vec![(String::new(), entry)],
+    };
+
+    for (pointer, entry) in entries {
+        if entry.get("type").and_then(|t| t.as_str()) == Some("mapgen") {
+            validator.validate(entry, &pointer);
+        }
+    }
+
+    // Parameters which are used multiple times are checked multiple times
+    let mut seen = HashSet::new();
+    validator
+        .issues
+        .into_iter()
+        .filter(|i| seen.insert((i.pointer.clone(), i.message.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rows() {
+        let text = r##"[
+  {
+    "type": "mapgen",
+    "om_terrain": "test",
+    "weight": 0,
+    "object": {
+      "mapgensize": [3, 2],
+      "terrain": { ".": "t_null", "#": ["t_null", 0] },
+      "rows": [
+        "..#",
+        ".x"
+      ]
+    }
+  }
+]"##;
+
+        let json_data = DeserializedCDDAJsonData::default();
+        let issues = validate_mapgen_json(text, &json_data, None);
+        let messages: Vec<(&str, usize, usize)> = issues
+            .iter()
+            .map(|i| (i.pointer.as_str(), i.position.line, i.position.column))
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                ("/0/weight", 5, 15),
+                ("/0/object/terrain/#/1", 8, 51),
+                ("/0/object/rows/1", 11, 9),
+                ("/0/object/rows/1", 11, 11),
+            ]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A position in a json document. Lines and columns start at 1, columns are
+/// counted in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct JsonPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl JsonPosition {
+    /// The position `offset` characters after this position on the same line
+    pub fn offset(&self, offset: usize) -> Self {
+        Self {
+            line: self.line,
+            column: self.column + offset,
+        }
+    }
+}
+
+/// The positions every value of a json document starts at, keyed by the json
+/// pointer of the value. serde_json does not keep the positions of the values
+/// it parses, so the document is scanned a second time to find them.
+#[derive(Debug, Default)]
+pub struct JsonPositions {
+    positions: HashMap<String, JsonPosition>,
+}
+
+impl JsonPositions {
+    /// Scans the `text`, which has to be valid json
+    pub fn index(text: &str) -> Self {
+        let mut scanner = Scanner {
+            chars: text.chars().peekable(),
+            position: JsonPosition { line: 1, column: 1 },
+            positions: HashMap::new(),
+        };
+
+        scanner.value(String::new());
+
+        Self {
+            positions: scanner.positions,
+        }
+    }
+
+    /// The position of the value at the `pointer`. Falls back to the closest
+    /// parent if the value does not exist.
+    pub fn get(&self, pointer: &str) -> JsonPosition {
+        let mut pointer = pointer;
+
+        loop {
+            if let Some(position) = self.positions.get(pointer) {
+                return *position;
+            }
+
+            match pointer.rsplit_once('/') {
+                None => return JsonPosition { line: 1, column: 1 },
+                Some((parent, _)) => pointer = parent,
+            }
+        }
+    }
+}
+
+/// Appends the `key` to the json `pointer`
+pub fn pointer_join(pointer: &str, key: impl ToString) -> String {
+    let key = key.to_string().replace('~', "~0").replace('/', "~1");
+    format!("{}/{}", pointer, key)
+}
+
+struct Scanner<'a> {
+    chars: Peekable<Chars<'a>>,
+    position: JsonPosition,
+    positions: HashMap<String, JsonPosition>,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+
+        match c {
+            '\n' => {
+                self.position.line += 1;
+                self.position.column = 1;
+            },
+            _ => self.position.column += 1,
+        }
+
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn value(&mut self, pointer: String) {
+        self.skip_whitespace();
+        self.positions.insert(pointer.clone(), self.position);
+
+        match self.peek() {
+            Some('{') => self.object(&pointer),
+            Some('[') => self.array(&pointer),
+            Some('"') => {
+                self.string();
+            },
+            Some(_) => {
+                while self.peek().is_some_and(|c| {
+                    !matches!(c, ',' | ']' | '}') && !c.is_whitespace()
+                }) {
+                    self.bump();
+                }
+            },
+            None => {},
+        }
+    }
+
+    fn object(&mut self, pointer: &str) {
+        self.bump();
+
+        loop {
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some('"') => {},
+                Some('}') => {
+                    self.bump();
+                    return;
+                },
+                _ => return,
+            }
+
+            let key = self.string();
+
+            self.skip_whitespace();
+            if self.bump() != Some(':') {
+                return;
+            }
+
+            self.value(pointer_join(pointer, key));
+
+            self.skip_whitespace();
+            if self.peek() == Some(',') {
+                self.bump();
+            }
+        }
+    }
+
+    fn array(&mut self, pointer: &str) {
+        self.bump();
+
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+
+            match self.peek() {
+                None => return,
+                Some(']') => {
+                    self.bump();
+                    return;
+                },
+                Some(_) => {},
+            }
+
+            self.value(pointer_join(pointer, index));
+            index += 1;
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                },
+                Some(']') => {},
+                _ => return,
+            }
+        }
+    }
+
+    /// Reads a string and returns its unescaped content
+    fn string(&mut self) -> String {
+        let mut content = String::new();
+        self.bump();
+
+        while let Some(c) = self.bump() {
+            match c {
+                '"' => break,
+                '\\' => match self.bump() {
+                    Some('n') => content.push('\n'),
+                    Some('t') => content.push('\t'),
+                    Some('r') => content.push('\r'),
+                    Some('b') => content.push('\u{8}'),
+                    Some('f') => content.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String =
+                            (0..4).filter_map(|_| self.bump()).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32);
+                        content.extend(c);
+                    },
+                    Some(c) => content.push(c),
+                    None => break,
+                },
+                c => content.push(c),
+            }
+        }
+
+        content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_of_nested_values() {
+        let text = "[\n  {\n    \"rows\": [\n      \"..#\",\n      \"a/b\"\n    ],\n    \"a/b\": 1\n  }\n]";
+        let positions = JsonPositions::index(text);
+
+        assert_eq!(positions.get("/0"), JsonPosition { line: 2, column: 3 });
+        assert_eq!(
+            positions.get("/0/rows/1"),
+            JsonPosition { line: 5, column: 7 }
+        );
+        assert_eq!(
+            positions.get("/0/a~1b"),
+            JsonPosition {
+                line: 7,
+                column: 12
+            }
+        );
+        assert_eq!(
+            positions.get("/0/rows/5"),
+            JsonPosition {
+                line: 3,
+                column: 13
+            }
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use cdda_lib::types::CDDAIdentifier;
+
+/// Maximum amount of suggestions which are returned for an unknown id
+const MAX_SUGGESTIONS: usize = 3;
+
+/// The amount of single character insertions, deletions and substitutions
+/// which are needed to turn `a` into `b`
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] =
+                substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// The `candidates` which are the closest to the unknown `id`, sorted from
+/// the closest to the farthest. Candidates which differ in more than a third
+/// of the characters of the id are not considered similar.
+pub fn closest_ids<'a>(
+    id: &CDDAIdentifier,
+    candidates: impl IntoIterator<Item = &'a CDDAIdentifier>,
+) -> Vec<CDDAIdentifier> {
+    let max_distance = (id.0.chars().count() / 3).max(1);
+
+    let mut matches: Vec<(usize, &CDDAIdentifier)> = candidates
+        .into_iter()
+        .map(|candidate| (levenshtein(&id.0, &candidate.0), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+
+    matches.sort_by(|(a_distance, a), (b_distance, b)| {
+        a_distance.cmp(b_distance).then_with(|| a.0.cmp(&b.0))
+    });
+
+    matches
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_ids() {
+        let candidates = [
+            CDDAIdentifier("t_grass".into()),
+            CDDAIdentifier("t_grass_dead".into()),
+            CDDAIdentifier("t_grass_long".into()),
+            CDDAIdentifier("t_dirt".into()),
+        ];
+
+        assert_eq!(levenshtein("t_grass_dad", "t_grass_dead"), 1);
+        assert_eq!(
+            closest_ids(&CDDAIdentifier("t_grass_dad".into()), &candidates),
+            vec![CDDAIdentifier("t_grass_dead".into())]
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::program_data::EditorData;
+use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+use crate::features::vehicle::{
+    render_vehicle_preview, VehiclePreview, VehiclePrototypeSummary,
+};
+use crate::util::{get_json_data, CDDADataError};
+use cdda_lib::types::CDDAIdentifier;
+use serde::Serialize;
+use std::ops::Deref;
+use std::sync::Arc;
+use tauri::async_runtime::Mutex;
+use tauri::State;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+pub enum GetVehicleError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error("Vehicle {0} does not exist")]
+    UnknownVehicle(CDDAIdentifier),
+}
+
+/// Returns all vehicle prototypes sorted by their id
+#[tauri::command]
+pub async fn get_vehicle_prototypes(
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+) -> Result<Vec<VehiclePrototypeSummary>, GetVehicleError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let mut prototypes: Vec<VehiclePrototypeSummary> = json_data
+        .vehicles
+        .values()
+        .map(VehiclePrototypeSummary::new)
+        .collect();
+
+    prototypes.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+
+    Ok(prototypes)
+}
+
+/// Renders the part layout of the vehicle with the `id` using the currently
+/// selected tileset
+#[tauri::command]
+pub async fn get_vehicle_preview(
+    id: CDDAIdentifier,
+    rotation: Option<i32>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+    fallback_tilesheet: State<'_, Arc<LegacyTilesheet>>,
+) -> Result<VehiclePreview, GetVehicleError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let vehicle = match json_data.vehicles.get(&id) {
+        None => return Err(GetVehicleError::UnknownVehicle(id)),
+        Some(v) => v,
+    };
+
+    let editor_data_lock = editor_data.lock().await;
+    let tilesheet_lock = tilesheet.lock().await;
+    let tilesheet = match editor_data_lock
+        .config
+        .rendered_tilesheet(tilesheet_lock.as_ref())
+    {
+        None => fallback_tilesheet.deref().deref(),
+        Some(t) => t,
+    };
+
+    Ok(render_vehicle_preview(
+        vehicle,
+        rotation.unwrap_or(0),
+        tilesheet,
+        json_data,
+    ))
+}
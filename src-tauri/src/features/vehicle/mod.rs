@@ -0,0 +1,226 @@
+pub mod handlers;
+
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::vehicle_parts::Location;
+use crate::data::vehicles::{
+    get_part_sprite_rotation, CDDAVehicle, VisibleVehiclePart,
+};
+use crate::data::TileLayer;
+use crate::features::connect_groups::CustomConnectGroups;
+use crate::features::map::MappedCDDAId;
+use crate::features::program_data::AdjacentSprites;
+use crate::features::tileset::legacy_tileset::{
+    LegacyTilesheet, SpriteIndex, TilesheetCDDAId,
+};
+use crate::features::tileset::{Tilesheet, VariantPicker};
+use cdda_lib::types::{CDDAIdentifier, CDDAString};
+use glam::IVec2;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The area a vehicle occupies, all bounds are inclusive
+#[derive(Debug, Clone, Serialize)]
+pub struct VehicleBoundingBox {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl VehicleBoundingBox {
+    pub fn new(min: IVec2, max: IVec2) -> Self {
+        Self {
+            min_x: min.x,
+            min_y: min.y,
+            max_x: max.x,
+            max_y: max.y,
+            width: (max.x - min.x + 1) as u32,
+            height: (max.y - min.y + 1) as u32,
+        }
+    }
+
+    pub fn from_positions<'a>(
+        positions: impl IntoIterator<Item = &'a IVec2>,
+    ) -> Self {
+        let mut positions = positions.into_iter();
+
+        let first = match positions.next() {
+            None => return Self::new(IVec2::ZERO, IVec2::ZERO),
+            Some(p) => p.clone(),
+        };
+
+        let (min, max) = positions.fold((first, first), |(min, max), p| {
+            (min.min(*p), max.max(*p))
+        });
+
+        Self::new(min, max)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VehiclePrototypeSummary {
+    pub id: CDDAIdentifier,
+    pub name: Option<CDDAString>,
+    pub bounding_box: VehicleBoundingBox,
+    pub part_count: usize,
+}
+
+impl VehiclePrototypeSummary {
+    pub fn new(vehicle: &CDDAVehicle) -> Self {
+        let positions: Vec<IVec2> = vehicle
+            .parts
+            .iter()
+            .map(|p| IVec2::new(p.x, p.y))
+            .collect();
+
+        Self {
+            id: vehicle.id.clone(),
+            name: vehicle.name.clone(),
+            bounding_box: VehicleBoundingBox::from_positions(&positions),
+            part_count: vehicle.parts.iter().map(|p| p.parts.len()).sum(),
+        }
+    }
+}
+
+/// A single visible part of a vehicle preview
+#[derive(Debug, Clone, Serialize)]
+pub struct VehiclePreviewCell {
+    /// Position relative to the top left corner of the bounding box
+    pub x: i32,
+    pub y: i32,
+    pub tilesheet_id: TilesheetCDDAId,
+    pub location: Location,
+    /// Whether the current tileset contains a sprite for this part, either
+    /// directly or through a looks_like chain
+    pub has_sprite: bool,
+    pub fg: Option<Vec<SpriteIndex>>,
+    pub bg: Option<Vec<SpriteIndex>>,
+    pub rotate_deg: i32,
+    /// Only set if the tileset does not contain a sprite for this part
+    pub fallback: Option<SpriteIndex>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VehiclePreview {
+    pub id: CDDAIdentifier,
+    pub rotation: i32,
+    pub bounding_box: VehicleBoundingBox,
+    pub cells: Vec<VehiclePreviewCell>,
+}
+
+fn get_part_tilesheet_id(visible_part: &VisibleVehiclePart) -> TilesheetCDDAId {
+    TilesheetCDDAId {
+        id: visible_part.part.id.clone(),
+        prefix: Some("vp".to_string()),
+        postfix: visible_part.variant.clone(),
+    }
+}
+
+fn get_adjacent_parts(
+    position: IVec2,
+    visible_parts: &HashMap<IVec2, VisibleVehiclePart>,
+) -> AdjacentSprites {
+    let get_id = |offset: IVec2| {
+        visible_parts
+            .get(&(position + offset))
+            .map(|p| p.part.id.clone())
+    };
+
+    AdjacentSprites {
+        top: get_id(IVec2::new(0, 1)),
+        right: get_id(IVec2::new(1, 0)),
+        bottom: get_id(IVec2::new(0, -1)),
+        left: get_id(IVec2::new(-1, 0)),
+    }
+}
+
+/// Renders the visible parts of `vehicle` using `tilesheet` without placing
+/// the vehicle on a map
+pub fn render_vehicle_preview(
+    vehicle: &CDDAVehicle,
+    rotation: i32,
+    tilesheet: &LegacyTilesheet,
+    json_data: &DeserializedCDDAJsonData,
+) -> VehiclePreview {
+    let visible_parts =
+        vehicle.get_visible_parts(&json_data.vehicle_parts, rotation);
+    let bounding_box = VehicleBoundingBox::from_positions(visible_parts.keys());
+    let sprite_rotation = get_part_sprite_rotation(rotation);
+    let picker = VariantPicker::new(0);
+
+    let mut cells = Vec::new();
+
+    for (position, visible_part) in visible_parts.iter() {
+        let mapped_id = MappedCDDAId {
+            tilesheet_id: get_part_tilesheet_id(visible_part),
+            rotation: sprite_rotation.clone(),
+            is_broken: false,
+            is_open: false,
+        };
+
+        let adjacent_parts = get_adjacent_parts(*position, &visible_parts);
+
+        let (has_sprite, fg, bg, rotate_deg, fallback) =
+            match tilesheet.get_sprite(&mapped_id, json_data) {
+                None => (
+                    false,
+                    None,
+                    None,
+                    0,
+                    Some(tilesheet.get_fallback(&mapped_id, json_data)),
+                ),
+                Some(sprite) => {
+                    let fg = sprite.get_fg_id(
+                        &picker,
+                        &mapped_id,
+                        &TileLayer::Furniture,
+                        &adjacent_parts,
+                        json_data,
+                        &CustomConnectGroups::default(),
+                    );
+                    let bg = sprite.get_bg_id(
+                        &picker,
+                        &mapped_id,
+                        &TileLayer::Furniture,
+                        &adjacent_parts,
+                        json_data,
+                        &CustomConnectGroups::default(),
+                    );
+
+                    let rotate_deg = fg
+                        .as_ref()
+                        .map(|r| r.rotation.deg() + mapped_id.rotation.deg())
+                        .unwrap_or(0);
+
+                    (
+                        true,
+                        fg.map(|r| r.data.into_vec()),
+                        bg.map(|r| r.data.into_vec()),
+                        rotate_deg,
+                        None,
+                    )
+                },
+            };
+
+        cells.push(VehiclePreviewCell {
+            x: position.x - bounding_box.min_x,
+            y: position.y - bounding_box.min_y,
+            tilesheet_id: mapped_id.tilesheet_id,
+            location: visible_part.location.clone(),
+            has_sprite,
+            fg,
+            bg,
+            rotate_deg,
+            fallback,
+        });
+    }
+
+    VehiclePreview {
+        id: vehicle.id.clone(),
+        rotation,
+        bounding_box,
+        cells,
+    }
+}
@@ -0,0 +1,232 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::connect_groups::CustomConnectGroups;
+use crate::features::map::GetMappedCDDAIdsError;
+use crate::features::program_data::{
+    MappedCDDAIdContainer, Project, ProjectName, ZLevel,
+};
+use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+use crate::features::viewer::data::{
+    AnimatedSprite, FallbackSprite, StaticSprite,
+};
+use crate::features::viewer::render::render_project;
+use cdda_lib::types::{CDDAIdentifier, ParameterIdentifier};
+use glam::UVec2;
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Everything a render of a project depends on besides the map data itself.
+/// Changes to the map data have to invalidate the cached render explicitly.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct RenderCacheKey {
+    variant_seed: u64,
+    seed: u64,
+    data_revision: u64,
+    camp_upgrades: HashMap<ZLevel, Vec<CDDAIdentifier>>,
+    disabled_camp_upgrades: HashMap<ZLevel, HashSet<CDDAIdentifier>>,
+    custom_connect_groups: CustomConnectGroups,
+    region: Option<CDDAIdentifier>,
+    parameters:
+        HashMap<(ZLevel, UVec2), IndexMap<ParameterIdentifier, CDDAIdentifier>>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct CachedRender {
+    pub key: RenderCacheKey,
+    pub mapped_cdda_ids: HashMap<ZLevel, MappedCDDAIdContainer>,
+    pub static_sprites: HashSet<StaticSprite>,
+    pub animated_sprites: HashSet<AnimatedSprite>,
+    pub fallback_sprites: HashSet<FallbackSprite>,
+}
+
+/// The last full render of every project, so switching between tabs does not
+/// have to render the project again
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    /// Increased whenever the CDDA data or the tileset changes, which
+    /// invalidates the renders of all projects
+    data_revision: AtomicU64,
+    renders: Mutex<HashMap<ProjectName, CachedRender>>,
+}
+
+impl RenderCache {
+    pub(super) fn key_for(&self, project: &Project) -> RenderCacheKey {
+        let mut parameters = HashMap::new();
+
+        for (z, collection) in project.maps.iter() {
+            for (coordinates, map_data) in collection.maps.iter() {
+                parameters.insert(
+                    (*z, *coordinates),
+                    map_data.calculated_parameters.clone(),
+                );
+            }
+        }
+
+        RenderCacheKey {
+            variant_seed: project.render_state.variant_seed,
+            seed: project.seed(),
+            data_revision: self.data_revision.load(Ordering::Relaxed),
+            camp_upgrades: project.camp_upgrades.clone(),
+            disabled_camp_upgrades: project.disabled_camp_upgrades.clone(),
+            custom_connect_groups: project.custom_connect_groups.clone(),
+            region: project.region.clone(),
+            parameters,
+        }
+    }
+
+    /// Returns the cached render of the project if it was made with the `key`
+    pub(super) fn get(
+        &self,
+        name: &ProjectName,
+        key: &RenderCacheKey,
+    ) -> Option<CachedRender> {
+        self.renders
+            .lock()
+            .unwrap()
+            .get(name)
+            .filter(|render| &render.key == key)
+            .cloned()
+    }
+
+    pub(super) fn insert(&self, name: ProjectName, render: CachedRender) {
+        self.renders.lock().unwrap().insert(name, render);
+    }
+
+    /// Renders the `project` and caches the render, so the next request for
+    /// its sprites does not have to render it
+    pub fn render(
+        &self,
+        name: ProjectName,
+        project: &mut Project,
+        json_data: &mut DeserializedCDDAJsonData,
+        tilesheet: Option<&LegacyTilesheet>,
+        fallback_tilesheet: &LegacyTilesheet,
+    ) -> Result<(), GetMappedCDDAIdsError> {
+        let key = self.key_for(project);
+        let render =
+            render_project(project, json_data, tilesheet, fallback_tilesheet)?;
+
+        self.insert(
+            name,
+            CachedRender {
+                key,
+                mapped_cdda_ids: render.mapped_cdda_ids,
+                static_sprites: render.static_sprites,
+                animated_sprites: render.animated_sprites,
+                fallback_sprites: render.fallback_sprites,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Invalidates the render of a single project after its map data changed
+    pub fn invalidate_project(&self, name: &ProjectName) {
+        self.renders.lock().unwrap().remove(name);
+    }
+
+    /// Invalidates the renders of all projects after the CDDA data or the
+    /// tileset changed
+    pub fn invalidate_all(&self) {
+        self.data_revision.fetch_add(1, Ordering::Relaxed);
+        self.renders.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::map::Cell;
+
+    fn cached_render(key: RenderCacheKey) -> CachedRender {
+        CachedRender {
+            key,
+            mapped_cdda_ids: HashMap::new(),
+            static_sprites: HashSet::new(),
+            animated_sprites: HashSet::new(),
+            fallback_sprites: HashSet::new(),
+        }
+    }
+
+    fn cache_with(project: &Project) -> RenderCache {
+        let cache = RenderCache::default();
+        cache.insert(
+            project.name.clone(),
+            cached_render(cache.key_for(project)),
+        );
+
+        cache
+    }
+
+    #[test]
+    fn test_map_data_changes_need_invalidation() {
+        let mut project = Project::default();
+        let cache = cache_with(&project);
+
+        project
+            .maps
+            .get_mut(&0)
+            .unwrap()
+            .maps
+            .values_mut()
+            .for_each(|m| {
+                m.cells.insert(UVec2::ZERO, Cell { character: '#' });
+            });
+
+        // The key does not cover the map data, so the old render is still
+        // returned until the project is invalidated
+        let key = cache.key_for(&project);
+        assert!(cache.get(&project.name, &key).is_some());
+
+        cache.invalidate_project(&project.name);
+        assert!(cache.get(&project.name, &key).is_none());
+    }
+
+    #[test]
+    fn test_render_inputs_change_the_key() {
+        let mut project = Project::default();
+        let cache = cache_with(&project);
+
+        project.render_state.variant_seed += 1;
+        assert!(cache.get(&project.name, &cache.key_for(&project)).is_none());
+
+        let mut project = Project::default();
+        project.region = Some("test_region".into());
+        assert!(cache.get(&project.name, &cache.key_for(&project)).is_none());
+
+        let mut project = Project::default();
+        project.camp_upgrades.insert(0, vec!["upgrade".into()]);
+        assert!(cache.get(&project.name, &cache.key_for(&project)).is_none());
+
+        let project = Project::default();
+        assert!(cache.get(&project.name, &cache.key_for(&project)).is_some());
+    }
+
+    #[test]
+    fn test_invalidate_all() {
+        let project = Project::default();
+        let cache = cache_with(&project);
+        let old_key = cache.key_for(&project);
+
+        cache.invalidate_all();
+
+        assert_ne!(cache.key_for(&project), old_key);
+        assert!(cache.get(&project.name, &old_key).is_none());
+    }
+
+    #[test]
+    fn test_invalidate_project_keeps_other_projects() {
+        let project = Project::default();
+        let mut other = Project::default();
+        other.name = "other".into();
+
+        let cache = cache_with(&project);
+        cache.insert(other.name.clone(), cached_render(cache.key_for(&other)));
+
+        cache.invalidate_project(&project.name);
+
+        assert!(cache.get(&project.name, &cache.key_for(&project)).is_none());
+        assert!(cache.get(&other.name, &cache.key_for(&other)).is_some());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::features::map::importing::resolve_om_terrain_id;
+use crate::features::map::MapData;
+use crate::features::program_data::{MapDataCollection, ZLevel};
+use cdda_lib::types::CDDAIdentifier;
+use glam::UVec2;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// The vanilla overmap terrain which is used for [`Neighbor::House`]
+pub const DEFAULT_NEIGHBOR_HOUSE: &str = "house_01";
+
+/// The vanilla overmap terrain which is used for [`Neighbor::Road`]
+pub const DEFAULT_NEIGHBOR_ROAD: &str = "road";
+
+/// What is placed next to a side of the previewed maps
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Neighbor {
+    #[default]
+    Empty,
+    /// A straight road which runs along the side
+    Road,
+    /// A house which faces the previewed maps
+    House,
+    /// Any overmap terrain, including its rotation suffix
+    Terrain { om_id: CDDAIdentifier },
+}
+
+/// The typical neighbors the previewed maps are surrounded with, so the
+/// alignment of sidewalks, driveways and fences can be checked
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NeighborContext {
+    pub north: Neighbor,
+    pub east: Neighbor,
+    pub south: Neighbor,
+    pub west: Neighbor,
+}
+
+#[derive(Debug, Error)]
+pub enum NeighborContextError {
+    #[error("Could not find the map data of the neighbor {0}")]
+    MissingMapData(CDDAIdentifier),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Side {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Side {
+    /// The direction a house on this side has to face to face the previewed
+    /// maps
+    fn facing(&self) -> &'static str {
+        match self {
+            Side::North => "south",
+            Side::East => "west",
+            Side::South => "north",
+            Side::West => "east",
+        }
+    }
+
+    /// The linear suffix of a road which runs along this side
+    fn road_suffix(&self) -> &'static str {
+        match self {
+            Side::North | Side::South => "ew",
+            Side::East | Side::West => "ns",
+        }
+    }
+}
+
+fn find_neighbor_map_data(
+    om_id: CDDAIdentifier,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<MapData, NeighborContextError> {
+    if let Some(map_data) = json_data.map_data.get(&om_id) {
+        return Ok(map_data.clone());
+    }
+
+    let (base_id, rotation) = resolve_om_terrain_id(om_id.clone());
+
+    match json_data.map_data.get(&base_id) {
+        None => Err(NeighborContextError::MissingMapData(om_id)),
+        Some(map_data) => {
+            let mut map_data = map_data.clone();
+            map_data.rotation = rotation;
+            Ok(map_data)
+        },
+    }
+}
+
+fn neighbor_map_data(
+    neighbor: &Neighbor,
+    side: Side,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<Option<MapData>, NeighborContextError> {
+    let om_id = match neighbor {
+        Neighbor::Empty => return Ok(None),
+        Neighbor::Road => CDDAIdentifier(format!(
+            "{}_{}",
+            DEFAULT_NEIGHBOR_ROAD,
+            side.road_suffix()
+        )),
+        Neighbor::House => CDDAIdentifier(format!(
+            "{}_{}",
+            DEFAULT_NEIGHBOR_HOUSE,
+            side.facing()
+        )),
+        Neighbor::Terrain { om_id } => om_id.clone(),
+    };
+
+    find_neighbor_map_data(om_id, json_data).map(Some)
+}
+
+/// Moves the `maps` one overmap terrain to the south east and places the
+/// neighbors of the `context` along every side of the maps on the z-level 0.
+/// The parameters of the neighbors still have to be calculated afterward.
+pub fn surround_with_neighbors(
+    maps: &mut HashMap<ZLevel, MapDataCollection>,
+    context: &NeighborContext,
+    json_data: &DeserializedCDDAJsonData,
+) -> Result<(), NeighborContextError> {
+    let Some(ground) = maps.get(&0) else {
+        return Ok(());
+    };
+
+    let width = ground.maps.keys().map(|c| c.x + 1).max().unwrap_or(1);
+    let height = ground.maps.keys().map(|c| c.y + 1).max().unwrap_or(1);
+
+    let mut neighbors = vec![];
+
+    for (neighbor, side) in [
+        (&context.north, Side::North),
+        (&context.east, Side::East),
+        (&context.south, Side::South),
+        (&context.west, Side::West),
+    ] {
+        let Some(map_data) = neighbor_map_data(neighbor, side, json_data)?
+        else {
+            continue;
+        };
+
+        let positions: Vec<UVec2> = match side {
+            Side::North => (1..=width).map(|x| UVec2::new(x, 0)).collect(),
+            Side::South => {
+                (1..=width).map(|x| UVec2::new(x, height + 1)).collect()
+            },
+            Side::West => (1..=height).map(|y| UVec2::new(0, y)).collect(),
+            Side::East => {
+                (1..=height).map(|y| UVec2::new(width + 1, y)).collect()
+            },
+        };
+
+        for position in positions {
+            neighbors.push((position, map_data.clone()));
+        }
+    }
+
+    for collection in maps.values_mut() {
+        collection.maps = collection
+            .maps
+            .drain()
+            .map(|(coordinates, map_data)| (coordinates + UVec2::ONE, map_data))
+            .collect();
+    }
+
+    // Safe since the z-level 0 was checked above
+    let ground = maps.get_mut(&0).unwrap();
+
+    for (position, map_data) in neighbors {
+        ground.maps.insert(position, map_data);
+    }
+
+    Ok(())
+}
@@ -1,12 +1,26 @@
 use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::furniture::CDDAFurniture;
+use crate::data::map_object::{
+    CDDABash, CDDADeconstruct, CDDAHarvestBySeason, CDDAHarvestEntry,
+    CDDAPlantData, Season,
+};
+use crate::data::terrain::CDDATerrain;
 use crate::data::TileLayer;
-use crate::features::map::MappedCDDAId;
-use crate::features::program_data::{AdjacentSprites, ProjectType};
-use crate::features::tileset::{Sprite, SpriteLayer};
+use crate::events::{EditorEvent, EventPayload};
+use crate::features::connect_groups::CustomConnectGroups;
+use crate::features::map::{MappedCDDAId, MappedCDDAIdsForTile, TileAnnotation};
+use crate::features::program_data::{
+    AdjacentSprites, MappedCDDAIdContainer, ProjectType,
+};
+use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+use crate::features::tileset::{
+    Projection, Sprite, SpriteLayer, VariantPicker,
+};
 use crate::util::UVec2JsonKey;
+use cdda_lib::types::{CDDAIdentifier, CDDAString};
 use glam::{IVec3, UVec2};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -14,6 +28,354 @@ pub(super) struct PlaceSpritesEvent {
     pub static_sprites: HashSet<StaticSprite>,
     pub animated_sprites: HashSet<AnimatedSprite>,
     pub fallback_sprites: HashSet<FallbackSprite>,
+    pub ghost_sprites: Vec<GhostSprites>,
+    /// The spritesheets the `sheet` of the static and animated sprites
+    /// refers to
+    pub spritesheets: Vec<SpritesheetPlacement>,
+}
+
+impl EventPayload for PlaceSpritesEvent {
+    const EVENT: EditorEvent = EditorEvent::PlaceSprites;
+}
+
+/// Where the sprites of a spritesheet are drawn relative to their tile
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(super) struct SpritesheetPlacement {
+    pub file: String,
+    /// The sprite indices in this spritesheet, both ends are inclusive
+    pub range: (u32, u32),
+    pub sprite_width: u32,
+    pub sprite_height: u32,
+    /// The offset of the center of a sprite from the center of its tile in
+    /// pixels. The y axis points down.
+    pub center_offset_x: i32,
+    pub center_offset_y: i32,
+}
+
+impl SpritesheetPlacement {
+    /// The placements of the spritesheets of the `tilesheet`, in the same
+    /// order as [`LegacyTilesheet::spritesheets`]
+    pub fn for_tilesheet(tilesheet: Option<&LegacyTilesheet>) -> Vec<Self> {
+        let tilesheet = match tilesheet {
+            None => return vec![],
+            Some(t) => t,
+        };
+
+        let projection = tilesheet.projection();
+
+        tilesheet
+            .spritesheets()
+            .iter()
+            .map(|s| {
+                // Sprites which are larger than a tile, like trees, are
+                // aligned at the top left corner of the tile before the
+                // offset of the spritesheet is applied
+                let width_difference =
+                    s.sprite_width as i32 - projection.tile_width as i32;
+                let height_difference =
+                    s.sprite_height as i32 - projection.tile_height as i32;
+
+                SpritesheetPlacement {
+                    file: s.file.clone(),
+                    range: s.range.unwrap_or_default(),
+                    sprite_width: s.sprite_width,
+                    sprite_height: s.sprite_height,
+                    center_offset_x: s.sprite_offset_x + width_difference / 2,
+                    center_offset_y: s.sprite_offset_y + height_difference / 2,
+                }
+            })
+            .collect()
+    }
+}
+
+const DEFAULT_GHOST_OPACITY: f32 = 0.35;
+
+fn default_ghost_opacity() -> f32 {
+    DEFAULT_GHOST_OPACITY
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GhostLayerOptions {
+    /// Show the z-level below the current one
+    #[serde(default)]
+    pub below: bool,
+    /// Show the z-level above the current one
+    #[serde(default)]
+    pub above: bool,
+    #[serde(default = "default_ghost_opacity")]
+    pub opacity: f32,
+}
+
+/// Sprites of the z-level `source_z` which should be drawn on top of the
+/// z-level `target_z` with reduced opacity
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(super) struct GhostSprites {
+    pub source_z: i32,
+    pub target_z: i32,
+    pub opacity: f32,
+    pub static_sprites: Vec<StaticSprite>,
+    pub animated_sprites: Vec<AnimatedSprite>,
+    pub fallback_sprites: Vec<FallbackSprite>,
+}
+
+impl GhostSprites {
+    pub fn from_sprites(
+        options: &GhostLayerOptions,
+        z_levels: &[i32],
+        static_sprites: &HashSet<StaticSprite>,
+        animated_sprites: &HashSet<AnimatedSprite>,
+        fallback_sprites: &HashSet<FallbackSprite>,
+    ) -> Vec<GhostSprites> {
+        let mut ghost_sprites = Vec::new();
+
+        for target_z in z_levels {
+            let mut source_zs = Vec::new();
+
+            if options.below {
+                source_zs.push(target_z - 1);
+            }
+
+            if options.above {
+                source_zs.push(target_z + 1);
+            }
+
+            for source_z in source_zs {
+                if !z_levels.contains(&source_z) {
+                    continue;
+                }
+
+                ghost_sprites.push(GhostSprites {
+                    source_z,
+                    target_z: *target_z,
+                    opacity: options.opacity,
+                    static_sprites: static_sprites
+                        .iter()
+                        .filter(|s| s.z == source_z)
+                        .cloned()
+                        .collect(),
+                    animated_sprites: animated_sprites
+                        .iter()
+                        .filter(|s| s.z == source_z)
+                        .cloned()
+                        .collect(),
+                    fallback_sprites: fallback_sprites
+                        .iter()
+                        .filter(|s| s.z == source_z)
+                        .cloned()
+                        .collect(),
+                })
+            }
+        }
+
+        ghost_sprites
+    }
+}
+
+/// Detailed information about a terrain or furniture in a cell
+#[derive(Debug, Serialize, Clone)]
+pub(super) struct MapObjectInspection {
+    pub id: CDDAIdentifier,
+    pub name: Option<CDDAString>,
+    pub bash: Option<CDDABash>,
+    pub deconstruct: Option<CDDADeconstruct>,
+    pub harvest: Vec<SeasonalHarvest>,
+    pub plant_data: Option<CDDAPlantData>,
+}
+
+/// The products that can be harvested from a map object in the given seasons
+#[derive(Debug, Serialize, Clone)]
+pub(super) struct SeasonalHarvest {
+    pub seasons: Vec<Season>,
+    pub harvest_id: CDDAIdentifier,
+    /// Empty if the harvest definition could not be found
+    pub products: Vec<CDDAHarvestEntry>,
+}
+
+fn get_seasonal_harvests(
+    harvest_by_season: &Option<Vec<CDDAHarvestBySeason>>,
+    json_data: &DeserializedCDDAJsonData,
+) -> Vec<SeasonalHarvest> {
+    harvest_by_season
+        .iter()
+        .flatten()
+        .map(|hbs| SeasonalHarvest {
+            seasons: hbs.seasons.clone(),
+            harvest_id: hbs.id.clone(),
+            products: json_data
+                .harvests
+                .get(&hbs.id)
+                .map(|h| h.entries.clone())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+impl MapObjectInspection {
+    pub fn from_terrain(
+        terrain: &CDDATerrain,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Self {
+        Self {
+            id: terrain.id.clone(),
+            name: terrain.name.clone(),
+            bash: terrain.bash.clone(),
+            deconstruct: terrain.deconstruct.clone(),
+            harvest: get_seasonal_harvests(
+                &terrain.harvest_by_season,
+                json_data,
+            ),
+            plant_data: None,
+        }
+    }
+
+    pub fn from_furniture(
+        furniture: &CDDAFurniture,
+        json_data: &DeserializedCDDAJsonData,
+    ) -> Self {
+        Self {
+            id: furniture.id.clone(),
+            name: furniture.name.clone(),
+            bash: furniture.bash.clone(),
+            deconstruct: furniture.deconstruct.clone(),
+            harvest: get_seasonal_harvests(
+                &furniture.harvest_by_season,
+                json_data,
+            ),
+            plant_data: furniture.plant_data.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(super) struct CellInspection {
+    pub terrain: Option<MapObjectInspection>,
+    pub furniture: Option<MapObjectInspection>,
+    /// Graffiti, rubble, liquids, corpses and sealed items in the cell, which
+    /// are shown even if they do not have a sprite
+    pub annotations: Vec<TileAnnotation>,
+}
+
+impl CellInspection {
+    pub fn new(
+        ids: &MappedCDDAIdsForTile,
+        json_data: &DeserializedCDDAJsonData,
+        region: Option<&CDDAIdentifier>,
+    ) -> Self {
+        let terrain = ids.terrain.as_ref().and_then(|t| {
+            let id =
+                json_data.resolve_region_setting(&t.tilesheet_id.id, region);
+            json_data
+                .terrain
+                .get(&id)
+                .map(|t| MapObjectInspection::from_terrain(t, json_data))
+        });
+
+        let furniture = ids.furniture.as_ref().and_then(|f| {
+            let id =
+                json_data.resolve_region_setting(&f.tilesheet_id.id, region);
+            json_data
+                .furniture
+                .get(&id)
+                .map(|f| MapObjectInspection::from_furniture(f, json_data))
+        });
+
+        Self {
+            terrain,
+            furniture,
+            annotations: ids.annotations.clone(),
+        }
+    }
+}
+
+/// Whether a multitile sprite connects to the cell in each direction
+#[derive(Debug, Serialize, Clone, Default)]
+pub(super) struct MultitileConnections {
+    pub top: bool,
+    pub right: bool,
+    pub bottom: bool,
+    pub left: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(super) struct ProbeNeighbors {
+    pub top: MappedCDDAIdsForTile,
+    pub right: MappedCDDAIdsForTile,
+    pub bottom: MappedCDDAIdsForTile,
+    pub left: MappedCDDAIdsForTile,
+}
+
+/// Everything the hover display needs to know about a single cell
+#[derive(Debug, Serialize, Clone, Default)]
+pub(super) struct CellProbe {
+    pub ids: MappedCDDAIdsForTile,
+    pub neighbors: ProbeNeighbors,
+    /// The connections of the terrain and furniture of the cell to their
+    /// neighbors, as they are used to pick a multitile sprite
+    pub connections: HashMap<TileLayer, MultitileConnections>,
+}
+
+impl CellProbe {
+    pub fn new(
+        position: IVec3,
+        container: &MappedCDDAIdContainer,
+        json_data: &DeserializedCDDAJsonData,
+        custom_connect_groups: &CustomConnectGroups,
+    ) -> Self {
+        let ids_at = |offset: IVec3| {
+            container
+                .ids
+                .get(&(position + offset))
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        // Same directions as the adjacent sprites of the render
+        let neighbors = ProbeNeighbors {
+            top: ids_at(IVec3::new(0, 1, 0)),
+            right: ids_at(IVec3::new(1, 0, 0)),
+            bottom: ids_at(IVec3::new(0, -1, 0)),
+            left: ids_at(IVec3::new(-1, 0, 0)),
+        };
+
+        let ids = ids_at(IVec3::ZERO);
+        let mut connections = HashMap::new();
+
+        for (layer, mapped_id) in [
+            (TileLayer::Terrain, &ids.terrain),
+            (TileLayer::Furniture, &ids.furniture),
+        ] {
+            let Some(mapped_id) = mapped_id else {
+                continue;
+            };
+
+            let adjacent_sprites =
+                container.get_adjacent_identifiers(position, &layer);
+            let (top, right, bottom, left) = Sprite::get_matching_list(
+                &mapped_id.tilesheet_id,
+                &layer,
+                json_data,
+                custom_connect_groups,
+                &adjacent_sprites,
+            );
+
+            connections.insert(
+                layer,
+                MultitileConnections {
+                    top,
+                    right,
+                    bottom,
+                    left,
+                },
+            );
+        }
+
+        Self {
+            ids,
+            neighbors,
+            connections,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,6 +385,67 @@ pub(super) struct CreateMapData {
     ty: ProjectType,
 }
 
+/// Amount of overlay sprites which are drawn on top of a single tile
+pub(super) const MAX_OVERLAYS: u32 = 8;
+
+/// Amount of sub layers of every [`TileLayer`]
+const SUB_LAYER_COUNT: u32 = SpriteLayer::Overlay as u32 + MAX_OVERLAYS;
+
+/// Explicit ordering information so the frontend can sort all sprites into a
+/// single batch. Sprites are drawn by `z`, then `depth`, then `layer`, then
+/// `sub_layer`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct DrawOrder {
+    /// The [`TileLayer`] this sprite belongs to
+    pub layer: u32,
+    /// 0 for background and 1 for foreground sprites, the overlays on top of
+    /// the foreground start at 2
+    pub sub_layer: u32,
+    pub z: i32,
+    /// Vertical pixel offset of the sprite calculated from the `zlevel_height`
+    /// of the tileset
+    pub z_offset: i32,
+    /// The back to front position of the tile in the [`Projection`] of the
+    /// tileset
+    pub depth: i32,
+}
+
+impl DrawOrder {
+    pub fn new(
+        tile_layer: &TileLayer,
+        sprite_layer: SpriteLayer,
+        position: IVec3,
+        projection: &Projection,
+    ) -> Self {
+        Self {
+            layer: tile_layer.clone() as u32,
+            sub_layer: sprite_layer as u32,
+            z: position.z,
+            z_offset: projection.height_offset(position.z),
+            depth: projection.depth(position.truncate()),
+        }
+    }
+
+    /// The draw order of the overlay with the `index` on top of the tile
+    pub fn overlay(
+        tile_layer: &TileLayer,
+        index: u32,
+        position: IVec3,
+        projection: &Projection,
+    ) -> Self {
+        Self {
+            sub_layer: SpriteLayer::Overlay as u32 + index,
+            ..Self::new(tile_layer, SpriteLayer::Overlay, position, projection)
+        }
+    }
+
+    /// The combined layer that is used by the frontend before draw orders
+    /// were introduced
+    pub fn combined_layer(&self) -> u32 {
+        self.layer * SUB_LAYER_COUNT + self.sub_layer
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub(super) struct StaticSprite {
     pub position: UVec2JsonKey,
@@ -30,6 +453,10 @@ pub(super) struct StaticSprite {
     pub layer: u32,
     pub z: i32,
     pub rotate_deg: i32,
+    pub draw_order: DrawOrder,
+    /// The position of the spritesheet of the sprite in the
+    /// [`PlaceSpritesEvent::spritesheets`]
+    pub sheet: Option<usize>,
 }
 
 impl Hash for StaticSprite {
@@ -57,6 +484,10 @@ pub(super) struct AnimatedSprite {
     pub layer: u32,
     pub z: i32,
     pub rotate_deg: i32,
+    pub draw_order: DrawOrder,
+    /// The position of the spritesheet of the first frame in the
+    /// [`PlaceSpritesEvent::spritesheets`]
+    pub sheet: Option<usize>,
 }
 
 impl Hash for AnimatedSprite {
@@ -82,6 +513,7 @@ pub(super) struct FallbackSprite {
     pub position: UVec2JsonKey,
     pub index: u32,
     pub z: i32,
+    pub draw_order: DrawOrder,
 }
 
 impl Hash for FallbackSprite {
@@ -114,39 +546,67 @@ impl DisplaySprite {
         tile_layer: TileLayer,
         adjacent_sprites: &AdjacentSprites,
         json_data: &DeserializedCDDAJsonData,
+        custom_connect_groups: &CustomConnectGroups,
+        tilesheet: &LegacyTilesheet,
+        picker: &VariantPicker,
     ) -> (Option<DisplaySprite>, Option<DisplaySprite>) {
+        let projection = tilesheet.projection();
         let position_uvec2 =
             UVec2::new(tile_position.x as u32, tile_position.y as u32);
 
+        let fg_draw_order = DrawOrder::new(
+            &tile_layer,
+            SpriteLayer::Fg,
+            tile_position,
+            projection,
+        );
+        let bg_draw_order = DrawOrder::new(
+            &tile_layer,
+            SpriteLayer::Bg,
+            tile_position,
+            projection,
+        );
+
         let fg = match sprite.get_fg_id(
+            picker,
             &tile_id,
             &tile_layer,
             adjacent_sprites,
             json_data,
+            custom_connect_groups,
         ) {
             None => None,
             Some(sprite_id) => match sprite.is_animated() {
                 true => {
+                    let indices = sprite_id.data.into_vec();
+                    let sheet = indices
+                        .first()
+                        .and_then(|i| tilesheet.spritesheet_of(*i));
+
                     let display_sprite = AnimatedSprite {
                         position: UVec2JsonKey(position_uvec2),
-                        layer: (tile_layer.clone() as u32) * 2
-                            + SpriteLayer::Fg as u32,
-                        indices: sprite_id.data.into_vec(),
+                        layer: fg_draw_order.combined_layer(),
+                        indices,
                         rotate_deg: sprite_id.rotation.deg()
                             + tile_id.rotation.deg(),
                         z: tile_position.z,
+                        draw_order: fg_draw_order,
+                        sheet,
                     };
 
                     Some(DisplaySprite::Animated(display_sprite))
                 },
                 false => {
+                    let index = sprite_id.data.into_single().unwrap();
+
                     let display_sprite = StaticSprite {
                         position: UVec2JsonKey(position_uvec2),
-                        layer: (tile_layer.clone() as u32) * 2
-                            + SpriteLayer::Fg as u32,
-                        index: sprite_id.data.into_single().unwrap(),
+                        layer: fg_draw_order.combined_layer(),
+                        index,
                         rotate_deg: sprite_id.rotation.deg(),
                         z: tile_position.z,
+                        draw_order: fg_draw_order,
+                        sheet: tilesheet.spritesheet_of(index),
                     };
 
                     Some(DisplaySprite::Static(display_sprite))
@@ -155,31 +615,44 @@ impl DisplaySprite {
         };
 
         let bg = match sprite.get_bg_id(
+            picker,
             &tile_id,
             &tile_layer,
             adjacent_sprites,
             json_data,
+            custom_connect_groups,
         ) {
             None => None,
             Some(id) => match sprite.is_animated() {
                 true => {
+                    let indices = id.data.into_vec();
+                    let sheet = indices
+                        .first()
+                        .and_then(|i| tilesheet.spritesheet_of(*i));
+
                     let display_sprite = AnimatedSprite {
                         position: UVec2JsonKey(position_uvec2),
-                        layer: (tile_layer as u32) * 2 + SpriteLayer::Bg as u32,
-                        indices: id.data.into_vec(),
+                        layer: bg_draw_order.combined_layer(),
+                        indices,
                         rotate_deg: id.rotation.deg(),
                         z: tile_position.z,
+                        draw_order: bg_draw_order,
+                        sheet,
                     };
 
                     Some(DisplaySprite::Animated(display_sprite))
                 },
                 false => {
+                    let index = id.data.into_single().unwrap();
+
                     let display_sprite = StaticSprite {
                         position: UVec2JsonKey(position_uvec2),
-                        layer: (tile_layer as u32) * 2 + SpriteLayer::Bg as u32,
-                        index: id.data.into_single().unwrap(),
+                        layer: bg_draw_order.combined_layer(),
+                        index,
                         rotate_deg: id.rotation.deg(),
                         z: tile_position.z,
+                        draw_order: bg_draw_order,
+                        sheet: tilesheet.spritesheet_of(index),
                     };
 
                     Some(DisplaySprite::Static(display_sprite))
@@ -189,4 +662,77 @@ impl DisplaySprite {
 
         (fg, bg)
     }
+
+    /// The foreground of every overlay sprite in `overlays`, drawn on top of
+    /// the tile at the `tile_position` in the order of the overlays
+    pub fn get_overlay_display_sprites(
+        overlays: &[&Sprite],
+        tile_id: &MappedCDDAId,
+        tile_position: IVec3,
+        tile_layer: TileLayer,
+        adjacent_sprites: &AdjacentSprites,
+        json_data: &DeserializedCDDAJsonData,
+        tilesheet: &LegacyTilesheet,
+        picker: &VariantPicker,
+    ) -> Vec<DisplaySprite> {
+        let position_uvec2 =
+            UVec2::new(tile_position.x as u32, tile_position.y as u32);
+
+        overlays
+            .iter()
+            .take(MAX_OVERLAYS as usize)
+            .enumerate()
+            .filter_map(|(index, overlay)| {
+                let sprite_id = overlay.get_fg_id(
+                    picker,
+                    tile_id,
+                    &tile_layer,
+                    adjacent_sprites,
+                    json_data,
+                    &CustomConnectGroups::default(),
+                )?;
+
+                let draw_order = DrawOrder::overlay(
+                    &tile_layer,
+                    index as u32,
+                    tile_position,
+                    tilesheet.projection(),
+                );
+
+                let display_sprite = match overlay.is_animated() {
+                    true => {
+                        let indices = sprite_id.data.into_vec();
+                        let sheet = indices
+                            .first()
+                            .and_then(|i| tilesheet.spritesheet_of(*i));
+
+                        DisplaySprite::Animated(AnimatedSprite {
+                            position: UVec2JsonKey(position_uvec2),
+                            layer: draw_order.combined_layer(),
+                            indices,
+                            rotate_deg: sprite_id.rotation.deg(),
+                            z: tile_position.z,
+                            draw_order,
+                            sheet,
+                        })
+                    },
+                    false => {
+                        let index = sprite_id.data.into_single()?;
+
+                        DisplaySprite::Static(StaticSprite {
+                            position: UVec2JsonKey(position_uvec2),
+                            layer: draw_order.combined_layer(),
+                            index,
+                            rotate_deg: sprite_id.rotation.deg(),
+                            z: tile_position.z,
+                            draw_order,
+                            sheet: tilesheet.spritesheet_of(index),
+                        })
+                    },
+                };
+
+                Some(display_sprite)
+            })
+            .collect()
+    }
 }
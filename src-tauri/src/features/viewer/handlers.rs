@@ -1,30 +1,51 @@
 use super::data::PlaceSpritesEvent;
 use crate::data::io::DeserializedCDDAJsonData;
-use crate::data::replace_region_setting;
+use crate::data::map_data::NeighborDirection;
 use crate::data::TileLayer;
-use crate::events;
-use crate::events::UPDATE_LIVE_VIEWER;
+use crate::events::{EmitEvent, ProjectChangedEvent};
+use crate::features::demo::{lock_json_data, DemoJsonData};
 use crate::features::map::importing::{
-    OvermapSpecialImporter, SingleMapDataImporter,
+    OvermapSpecialImporter, OvermapSpecialImporterError,
+};
+use crate::features::map::probability::{
+    cell_breakdown, collect_parameters, probability_breakdown, IdProbability,
+};
+use crate::features::map::{
+    GetMappedCDDAIdsError, MappingKind, MappingSource, SimulatedNeighbors,
+    DEFAULT_MAP_DATA_SIZE,
 };
-use crate::features::map::MappedCDDAId;
 use crate::features::map::SPECIAL_EMPTY_CHAR;
-use crate::features::map::{CalculateParametersError, DEFAULT_MAP_DATA_SIZE};
+use crate::features::map::CalculateParametersError;
+use crate::features::program_data::changes::{
+    EditorDataChanges, EditorDataScope,
+};
+use crate::features::program_data::generations::ProjectGenerations;
 use crate::features::program_data::io::ProgramDataSaver;
-use crate::features::program_data::GetLiveViewerDataError;
+use crate::features::program_data::LoadProjectMapsError;
 use crate::features::program_data::LiveViewerData;
+use crate::features::program_data::{
+    get_map_data_collection_from_live_viewer_data, GetLiveViewerDataError,
+};
 use crate::features::program_data::MappedCDDAIdContainer;
 use crate::features::program_data::Project;
+use crate::features::program_data::ProjectName;
 use crate::features::program_data::ProjectType;
 use crate::features::program_data::ZLevel;
-use crate::features::program_data::{
-    get_map_data_collection_from_live_viewer_data, Tab, TabType,
-};
+use crate::features::program_data::{load_project_maps, Tab, TabType};
 use crate::features::program_data::{EditorData, RecentProject};
 use crate::features::tileset::legacy_tileset::LegacyTilesheet;
-use crate::features::tileset::legacy_tileset::TilesheetCDDAId;
-use crate::features::tileset::Tilesheet;
-use crate::features::viewer::data::{DisplaySprite, FallbackSprite};
+use crate::features::viewer::cache::{CachedRender, RenderCache};
+use crate::features::viewer::context::{
+    surround_with_neighbors, NeighborContext, NeighborContextError,
+};
+use crate::features::viewer::data::{
+    CellInspection, CellProbe, GhostLayerOptions, GhostSprites,
+    SpritesheetPlacement,
+};
+use crate::features::viewer::render::{render_project, ProjectRender};
+use crate::features::viewer::special::{
+    overmap_special_layout, SpecialOvermapTerrain,
+};
 use crate::impl_serialize_for_error;
 use crate::util;
 use crate::util::get_json_data;
@@ -32,9 +53,8 @@ use crate::util::CDDADataError;
 use crate::util::GetCurrentProjectError;
 use crate::util::IVec3JsonKey;
 use crate::util::Save;
-use crate::util::UVec2JsonKey;
 use crate::util::{get_current_project_mut, get_size, Load};
-use cdda_lib::types::{CDDAIdentifier, ParameterIdentifier};
+use cdda_lib::types::{CDDAIdentifier, MapGenValue, ParameterIdentifier};
 use cdda_lib::DEFAULT_EMPTY_CHAR_ROW;
 use cdda_lib::DEFAULT_MAP_HEIGHT;
 use cdda_lib::DEFAULT_MAP_ROWS;
@@ -47,14 +67,12 @@ use log::info;
 use log::warn;
 use notify::{recommended_watcher, Watcher};
 use notify_debouncer_full::new_debouncer;
-use rayon::iter::IntoParallelRefIterator;
-use rayon::iter::ParallelIterator;
 use serde::Deserialize;
 use serde::Serialize;
 use serde::Serializer;
 use serde_json::json;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::hash::Hasher;
 use std::ops::Deref;
 use std::ops::DerefMut;
@@ -64,7 +82,6 @@ use std::time::Duration;
 use strum::IntoEnumIterator;
 use tauri::async_runtime::Mutex;
 use tauri::AppHandle;
-use tauri::Emitter;
 use tauri::State;
 use thiserror::Error;
 use tokio::fs::File;
@@ -117,262 +134,887 @@ pub async fn get_calculated_parameters(
     Ok(calculated_parameters)
 }
 
+#[derive(Debug, Error)]
+pub enum GetSpritesError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error("Could not find project with name {0}")]
+    UnknownProject(ProjectName),
+
+    #[error(transparent)]
+    GetMappedCDDAIdsError(#[from] GetMappedCDDAIdsError),
+}
+
+impl_serialize_for_error!(GetSpritesError);
+
 #[tauri::command]
 pub async fn get_sprites(
     name: String,
+    ghost_layers: Option<GhostLayerOptions>,
     app: AppHandle,
     tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
     fallback_tilesheet: State<'_, Arc<LegacyTilesheet>>,
     editor_data: State<'_, Mutex<EditorData>>,
     json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
     mapped_cdda_ids: State<
         '_,
         Mutex<Option<HashMap<ZLevel, MappedCDDAIdContainer>>>,
     >,
-) -> Result<(), ()> {
-    let mut json_data_lock = json_data.lock().await;
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+) -> Result<(), GetSpritesError> {
+    let mut json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
 
-    let mut json_data = match json_data_lock.deref_mut() {
-        None => return Err(()),
+    let json_data = match json_data_lock.deref_mut() {
+        None => return Err(CDDADataError::NotLoaded.into()),
         Some(d) => d,
     };
 
-    let mut editor_data_lock = editor_data.lock().await;
+    let mut editor_data_lock = editor_data.lock().await;
+    let tilesheet_lock = tilesheet.lock().await;
+    let rendered_tilesheet = editor_data_lock
+        .config
+        .rendered_tilesheet(tilesheet_lock.as_ref());
+
+    let project = match editor_data_lock.loaded_projects.get_mut(&name) {
+        None => {
+            warn!("Could not find project with name {}", name);
+            return Err(GetSpritesError::UnknownProject(name));
+        },
+        Some(d) => d,
+    };
+
+    // Closing or reloading the project while it is rendered advances its
+    // generation, in which case the render is outdated
+    let generation = project_generations.current(&name);
+    let cache_key = render_cache.key_for(project);
+
+    // Serve the last render if nothing it depends on changed since then
+    if let Some(render) = render_cache.get(&name, &cache_key) {
+        info!("Using cached render of project {}", name);
+
+        let ghost_sprites = match ghost_layers {
+            None => Vec::new(),
+            Some(options) => {
+                let z_levels: Vec<ZLevel> =
+                    project.maps.keys().copied().collect();
+
+                GhostSprites::from_sprites(
+                    &options,
+                    &z_levels,
+                    &render.static_sprites,
+                    &render.animated_sprites,
+                    &render.fallback_sprites,
+                )
+            },
+        };
+
+        mapped_cdda_ids.lock().await.replace(render.mapped_cdda_ids);
+
+        let spritesheets =
+            SpritesheetPlacement::for_tilesheet(rendered_tilesheet);
+
+        app.emit_event(PlaceSpritesEvent {
+            static_sprites: render.static_sprites,
+            animated_sprites: render.animated_sprites,
+            fallback_sprites: render.fallback_sprites,
+            ghost_sprites,
+            spritesheets,
+        })
+        .unwrap();
+
+        return Ok(());
+    }
+
+    let ProjectRender {
+        mapped_cdda_ids: saved_cdda_ids,
+        static_sprites,
+        animated_sprites,
+        fallback_sprites,
+    } = render_project(
+        project,
+        json_data,
+        rendered_tilesheet,
+        &fallback_tilesheet,
+    )?;
+
+    let ghost_sprites = match ghost_layers {
+        None => Vec::new(),
+        Some(options) => {
+            let z_levels: Vec<ZLevel> = project.maps.keys().copied().collect();
+
+            GhostSprites::from_sprites(
+                &options,
+                &z_levels,
+                &static_sprites,
+                &animated_sprites,
+                &fallback_sprites,
+            )
+        },
+    };
+
+    if !project_generations.is_current(&name, generation) {
+        info!("Discarding the outdated render of project {}", name);
+        return Ok(());
+    }
+
+    render_cache.insert(
+        name,
+        CachedRender {
+            key: cache_key,
+            mapped_cdda_ids: saved_cdda_ids.clone(),
+            static_sprites: static_sprites.clone(),
+            animated_sprites: animated_sprites.clone(),
+            fallback_sprites: fallback_sprites.clone(),
+        },
+    );
+
+    let mut mapped_cdda_ids_lock = mapped_cdda_ids.lock().await;
+    mapped_cdda_ids_lock.replace(saved_cdda_ids);
+
+    app.emit_event(PlaceSpritesEvent {
+        static_sprites,
+        animated_sprites,
+        fallback_sprites,
+        ghost_sprites,
+        spritesheets: SpritesheetPlacement::for_tilesheet(rendered_tilesheet),
+    })
+    .unwrap();
+
+    Ok(())
+}
+
+/// Picks new weighted sprite variants for every tile of the current project
+/// the next time its sprites are requested
+#[tauri::command]
+pub async fn reroll_sprite_variants(
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<(), GetCurrentProjectError> {
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    project.render_state.variant_seed = rand::random();
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum SetSeedError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    CalculateParametersError(#[from] CalculateParametersError),
+
+    #[error("Failed to render project {0}, {1}")]
+    RenderError(String, String),
+}
+
+impl_serialize_for_error!(SetSeedError);
+
+/// Generates the maps of the current project with the `seed`. The same seed
+/// always results in the same parameters, places and sprites.
+#[tauri::command]
+pub async fn set_seed(
+    seed: u64,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+) -> Result<(), SetSeedError> {
+    apply_seed(
+        seed,
+        &json_data,
+        &demo_json_data,
+        &editor_data,
+        &render_cache,
+        &project_generations,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Generates the maps of the current project with a new random seed and
+/// returns it
+#[tauri::command]
+pub async fn reroll_seed(
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+) -> Result<u64, SetSeedError> {
+    let seed = rand::random();
+
+    apply_seed(
+        seed,
+        &json_data,
+        &demo_json_data,
+        &editor_data,
+        &render_cache,
+        &project_generations,
+    )
+    .await?;
+
+    Ok(seed)
+}
+
+/// Generates the current project with a new random seed and places its
+/// sprites right away. The maps are not imported again, so this is fast
+/// enough to cycle through the random variants of a mapgen. Returns the new
+/// seed.
+#[tauri::command]
+pub async fn reroll_preview(
+    ghost_layers: Option<GhostLayerOptions>,
+    app: AppHandle,
+    tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+    fallback_tilesheet: State<'_, Arc<LegacyTilesheet>>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+    mapped_cdda_ids: State<
+        '_,
+        Mutex<Option<HashMap<ZLevel, MappedCDDAIdContainer>>>,
+    >,
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+) -> Result<u64, SetSeedError> {
+    let seed = rand::random();
+
+    let name = apply_seed(
+        seed,
+        &json_data,
+        &demo_json_data,
+        &editor_data,
+        &render_cache,
+        &project_generations,
+    )
+    .await?;
+
+    // The render of the project was invalidated by the new seed, so this
+    // renders it again instead of serving the cached render
+    get_sprites(
+        name.clone(),
+        ghost_layers,
+        app,
+        tilesheet,
+        fallback_tilesheet,
+        editor_data,
+        json_data,
+        demo_json_data,
+        mapped_cdda_ids,
+        render_cache,
+        project_generations,
+    )
+    .await
+    .map_err(|e| SetSeedError::RenderError(name, e.to_string()))?;
+
+    Ok(seed)
+}
+
+/// Generates the maps of the current project with the `seed` and returns the
+/// name of the project
+async fn apply_seed(
+    seed: u64,
+    json_data: &Mutex<Option<DeserializedCDDAJsonData>>,
+    demo_json_data: &DemoJsonData,
+    editor_data: &Mutex<EditorData>,
+    render_cache: &RenderCache,
+    project_generations: &ProjectGenerations,
+) -> Result<ProjectName, SetSeedError> {
+    let json_data_lock = lock_json_data(json_data, demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    project.set_seed(seed, &json_data.palettes)?;
+
+    // Renders which were started with the old seed are outdated
+    project_generations.advance(&project.name);
+    render_cache.invalidate_project(&project.name);
+
+    Ok(project.name.clone())
+}
+
+/// Returns the ids of all loaded region settings, which can be chosen as the
+/// region of a project
+#[tauri::command]
+pub async fn get_region_settings_ids(
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+) -> Result<Vec<CDDAIdentifier>, CDDADataError> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let mut region_settings_ids: Vec<CDDAIdentifier> =
+        json_data.region_settings.keys().cloned().collect();
+    region_settings_ids.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(region_settings_ids)
+}
+
+#[derive(Debug, Error)]
+pub enum SetRegionError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error("The region settings {0} do not exist")]
+    UnknownRegion(CDDAIdentifier),
+}
+
+impl_serialize_for_error!(SetRegionError);
+
+/// Resolves the regional terrain and furniture of the current project with
+/// the region settings `region`, or with the `default` region settings if no
+/// region is passed
+#[tauri::command]
+pub async fn set_region(
+    region: Option<CDDAIdentifier>,
+    app: AppHandle,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+) -> Result<(), SetRegionError> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    if let Some(region) = &region {
+        if !json_data.region_settings.contains_key(region) {
+            return Err(SetRegionError::UnknownRegion(region.clone()));
+        }
+    }
+
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    project.region = region;
+
+    // The whole map is generated again with the new region, starting with the
+    // parameters
+    for (_, collection) in project.maps.iter_mut() {
+        if let Err(e) = collection.calculate_parameters(&json_data.palettes) {
+            warn!("Failed to calculate the parameters of the project, {}", e);
+        }
+    }
+
+    project_generations.advance(&project.name);
+    render_cache.invalidate_project(&project.name);
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    if let Err(e) = saver.save(editor_data_lock.deref()).await {
+        warn!("Failed to save the region of the project, {}", e);
+    }
+
+    editor_data_changes.notify(&app, &[EditorDataScope::OpenedProject]);
+
+    Ok(())
+}
+
+/// Pin the sprite variant at the index `variant` of the weighted sprite list
+/// for the tile at `position`. Passing [`None`] removes the pinned variant.
+#[tauri::command]
+pub async fn pin_sprite_variant(
+    position: IVec3JsonKey,
+    layer: TileLayer,
+    variant: Option<usize>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<(), GetCurrentProjectError> {
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+    render_cache.invalidate_project(&project.name);
+
+    let pinned_variants = project
+        .render_state
+        .pinned_variants
+        .entry(layer)
+        .or_default();
+
+    match variant {
+        None => {
+            pinned_variants.remove(&position);
+        },
+        Some(variant) => {
+            pinned_variants.insert(position, variant);
+        },
+    }
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    saver.save(editor_data_lock.deref()).await.unwrap();
+
+    Ok(())
+}
+
+/// Returns the ids of all update mapgens, these include the expansions and
+/// upgrades of faction camps
+#[tauri::command]
+pub async fn get_update_mapgen_ids(
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+) -> Result<Vec<CDDAIdentifier>, CDDADataError> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let mut update_mapgen_ids: Vec<CDDAIdentifier> =
+        json_data.update_mapgen_ids.iter().cloned().collect();
+    update_mapgen_ids.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(update_mapgen_ids)
+}
+
+#[derive(Debug, Error)]
+pub enum SetCampUpgradesError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error("{0} is not an update mapgen")]
+    NotAnUpdateMapgen(CDDAIdentifier),
+
+    #[error("{0} is not a camp upgrade of the z-level {1}")]
+    NotACampUpgrade(CDDAIdentifier, ZLevel),
+}
+
+impl_serialize_for_error!(SetCampUpgradesError);
+
+/// Set the update mapgens which are applied in order on top of the maps at
+/// the z-level `z` of the current project. This is used to preview each
+/// construction phase of a faction camp
+#[tauri::command]
+pub async fn set_camp_upgrades(
+    z: ZLevel,
+    upgrades: Vec<CDDAIdentifier>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+) -> Result<(), SetCampUpgradesError> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    if let Some(id) = upgrades
+        .iter()
+        .find(|id| !json_data.update_mapgen_ids.contains(id))
+    {
+        return Err(SetCampUpgradesError::NotAnUpdateMapgen(id.clone()));
+    }
+
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    match upgrades.is_empty() {
+        true => {
+            project.camp_upgrades.remove(&z);
+        },
+        false => {
+            project.camp_upgrades.insert(z, upgrades);
+        },
+    }
+
+    // Upgrades which were removed from the list can not stay disabled
+    if let Some(disabled) = project.disabled_camp_upgrades.get_mut(&z) {
+        let upgrades = project.camp_upgrades.get(&z);
+        disabled.retain(|id| upgrades.is_some_and(|u| u.contains(id)));
+    }
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    saver.save(editor_data_lock.deref()).await.unwrap();
+
+    Ok(())
+}
+
+/// Turns the camp upgrade `id` of the z-level `z` of the current project off
+/// or on again without removing it from the list, so the upgrades can be
+/// compared with and without a single update mapgen
+#[tauri::command]
+pub async fn toggle_camp_upgrade(
+    z: ZLevel,
+    id: CDDAIdentifier,
+    enabled: bool,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<(), SetCampUpgradesError> {
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    let is_upgrade = project
+        .camp_upgrades
+        .get(&z)
+        .is_some_and(|upgrades| upgrades.contains(&id));
+
+    if !is_upgrade {
+        return Err(SetCampUpgradesError::NotACampUpgrade(id, z));
+    }
+
+    let disabled = project.disabled_camp_upgrades.entry(z).or_default();
+
+    match enabled {
+        true => {
+            disabled.remove(&id);
+        },
+        false => {
+            disabled.insert(id);
+        },
+    }
+
+    if disabled.is_empty() {
+        project.disabled_camp_upgrades.remove(&z);
+    }
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    saver.save(editor_data_lock.deref()).await.unwrap();
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum ReloadProjectError {
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    LoadProjectMapsError(#[from] LoadProjectMapsError),
+
+    #[error(transparent)]
+    CalculateParametersError(#[from] CalculateParametersError),
+
+    #[error(transparent)]
+    NeighborContextError(#[from] NeighborContextError),
+
+    #[error("Only live viewers can be surrounded with neighbors")]
+    NotALiveViewer,
+}
+
+impl_serialize_for_error!(ReloadProjectError);
+
+/// Imports the maps of the `project` again and recalculates their parameters
+pub async fn reload_project_maps(
+    project: &mut Project,
+    json_data: &DeserializedCDDAJsonData,
+    render_cache: &RenderCache,
+    project_generations: &ProjectGenerations,
+) -> Result<(), ReloadProjectError> {
+    project_generations.advance(&project.name);
+    render_cache.invalidate_project(&project.name);
+
+    // Map editor projects are reloaded from their last save, which discards
+    // any unsaved changes
+    let mut map_data_collection = load_project_maps(&project.ty).await?;
+
+    if let Some(context) = &project.neighbor_context {
+        surround_with_neighbors(&mut map_data_collection, context, json_data)?;
+    }
+
+    // The reloaded maps keep the seed and the simulated neighbors so they
+    // look the same as before
+    let seed = project.seed();
+
+    let project_palettes = project.project_palettes();
+
+    for (_, map_data) in map_data_collection.iter_mut() {
+        map_data.set_seed(seed);
+        map_data.set_simulated_neighbors(&project.simulated_neighbors);
+        map_data.set_project_palettes(&project_palettes);
+        map_data.calculate_parameters(&json_data.palettes)?
+    }
+
+    project.maps = map_data_collection;
+    project.dirty = false;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn reload_project(
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+) -> Result<(), ReloadProjectError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    reload_project_maps(project, json_data, &render_cache, &project_generations)
+        .await
+}
+
+/// Surrounds the currently opened live viewer with the neighbors of the
+/// `context`, or removes the neighbors again if no `context` is given
+#[tauri::command]
+pub async fn set_neighbor_context(
+    context: Option<NeighborContext>,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+) -> Result<(), ReloadProjectError> {
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    if !matches!(project.ty, ProjectType::LiveViewer(_)) {
+        return Err(ReloadProjectError::NotALiveViewer);
+    }
+
+    project_generations.advance(&project.name);
+
+    let mut map_data_collection = load_project_maps(&project.ty).await?;
+
+    if let Some(context) = &context {
+        surround_with_neighbors(&mut map_data_collection, context, json_data)?;
+    }
+
+    let seed = project.seed();
+
+    let project_palettes = project.project_palettes();
+
+    for (_, map_data) in map_data_collection.iter_mut() {
+        map_data.set_seed(seed);
+        map_data.set_simulated_neighbors(&project.simulated_neighbors);
+        map_data.set_project_palettes(&project_palettes);
+        map_data.calculate_parameters(&json_data.palettes)?
+    }
+
+    project.size = get_size(&map_data_collection);
+    project.maps = map_data_collection;
+    project.neighbor_context = context;
+    render_cache.invalidate_project(&project.name);
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    if let Err(e) = saver.save(editor_data_lock.deref()).await {
+        warn!("Failed to save the neighbor context, {}", e);
+    }
+
+    editor_data_changes.notify(&app, &[EditorDataScope::OpenedProject]);
+
+    Ok(())
+}
+
+/// The overmap terrains and joins which are simulated next to the maps of the
+/// currently opened project
+#[tauri::command]
+pub async fn get_simulated_neighbors(
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<SimulatedNeighbors, GetCurrentProjectError> {
+    let editor_data_lock = editor_data.lock().await;
+    let project = util::get_current_project(&editor_data_lock)?;
+
+    Ok(project.simulated_neighbors.clone())
+}
+
+/// Simulates the `om_terrains` and `joins` next to the maps of the currently
+/// opened project in the `direction`, passing no ids for both removes the
+/// simulated neighbor. Nested chunks which depend on their neighbors are
+/// evaluated again the next time the project is rendered.
+#[tauri::command]
+pub async fn set_simulated_neighbor(
+    direction: NeighborDirection,
+    om_terrains: Vec<CDDAIdentifier>,
+    joins: Vec<CDDAIdentifier>,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
+    render_cache: State<'_, RenderCache>,
+    project_generations: State<'_, ProjectGenerations>,
+) -> Result<(), GetCurrentProjectError> {
+    let mut editor_data_lock = editor_data.lock().await;
+    let project = get_current_project_mut(&mut editor_data_lock)?;
+
+    let simulated = &mut project.simulated_neighbors;
+
+    match om_terrains.is_empty() {
+        true => {
+            simulated.neighbors.remove(&direction);
+        },
+        false => {
+            simulated.neighbors.insert(direction.clone(), om_terrains);
+        },
+    }
+
+    match joins.is_empty() {
+        true => {
+            simulated.joins.remove(&direction);
+        },
+        false => {
+            simulated.joins.insert(direction, joins);
+        },
+    }
+
+    let simulated = project.simulated_neighbors.clone();
+
+    for (_, map_collection) in project.maps.iter_mut() {
+        map_collection.set_simulated_neighbors(&simulated);
+    }
+
+    project_generations.advance(&project.name);
+    render_cache.invalidate_project(&project.name);
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    if let Err(e) = saver.save(editor_data_lock.deref()).await {
+        warn!("Failed to save the simulated neighbors, {}", e);
+    }
+
+    editor_data_changes.notify(&app, &[EditorDataScope::OpenedProject]);
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum GetOvermapSpecialLayoutError {
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    ImportError(#[from] OvermapSpecialImporterError),
+}
+
+impl_serialize_for_error!(GetOvermapSpecialLayoutError);
 
-    let project = match editor_data_lock.loaded_projects.get_mut(&name) {
-        None => {
-            warn!("Could not find project with name {}", name);
-            return Err(());
-        },
-        Some(d) => d,
-    };
+/// Every overmap terrain of the overmap special which is shown in the
+/// currently opened project, so each of them can be outlined and labeled.
+/// Projects which do not show an overmap special have no overmap terrains.
+#[tauri::command]
+pub async fn get_overmap_special_layout(
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<Vec<SpecialOvermapTerrain>, GetOvermapSpecialLayoutError> {
+    let (name, importer) = {
+        let editor_data_lock = editor_data.lock().await;
+        let project = util::get_current_project(&editor_data_lock)?;
 
-    let mut static_sprites = HashSet::new();
-    let mut animated_sprites = HashSet::new();
-    let mut fallback_sprites = HashSet::new();
-
-    macro_rules! insert_sprite_type {
-        ($val:expr) => {
-            match $val {
-                DisplaySprite::Static(s) => {
-                    static_sprites.insert(s);
-                },
-                DisplaySprite::Animated(a) => {
-                    animated_sprites.insert(a);
-                },
-                DisplaySprite::Fallback(f) => {
-                    fallback_sprites.insert(f);
-                },
-            }
+        let ProjectType::LiveViewer(LiveViewerData::Special {
+            om_id,
+            om_file_paths,
+            mapgen_file_paths,
+            ..
+        }) = &project.ty
+        else {
+            return Ok(vec![]);
         };
-    }
-
-    let tilesheet_lock = tilesheet.lock().await;
 
-    for (_, map_collection) in project.maps.iter_mut() {
-        // we need to calculate the parameters for the predecessor here because we
-        // cannot borrow json data as mutable inside the get_mapped_cdda_ids function
-        map_collection.calculate_predecessor_parameters(&mut json_data);
-    }
-
-    let region_settings = json_data
-        .region_settings
-        .get(&CDDAIdentifier("default".into()))
-        .expect("Region settings to exist");
-
-    let mut saved_cdda_ids = HashMap::new();
-
-    for (z, map_collection) in project.maps.iter() {
-        let local_mapped_cdda_ids =
-            map_collection.get_mapped_cdda_ids(json_data, *z).unwrap();
-
-        let tile_map: Vec<
-            HashMap<TileLayer, (Option<DisplaySprite>, Option<DisplaySprite>)>,
-        > = local_mapped_cdda_ids
-            .ids
-            .par_iter()
-            .map(|(p, identifier_group)| {
-                let tile_3d_coords = IVec3::new(p.x, p.y, *z);
-
-                if identifier_group.terrain.is_none()
-                    && identifier_group.furniture.is_none()
-                {
-                    warn!(
-                        "No sprites found for identifier_group {:?} at \
-                         coordinates {}",
-                        identifier_group, tile_3d_coords
-                    );
-
-                    return HashMap::new();
-                }
+        let importer = OvermapSpecialImporter {
+            om_special_id: om_id.clone(),
+            overmap_special_paths: om_file_paths.clone(),
+            mapgen_entry_paths: mapgen_file_paths.clone(),
+        };
 
-                let mut layer_map = HashMap::new();
-
-                // Layer is used here so furniture is
-                // above terrain
-                for (layer, o_id) in [
-                    (TileLayer::Terrain, &identifier_group.terrain),
-                    (TileLayer::Furniture, &identifier_group.furniture),
-                    (TileLayer::Monster, &identifier_group.monster),
-                    (TileLayer::Field, &identifier_group.field),
-                ] {
-                    let id = match o_id {
-                        None => continue,
-                        Some(mapped_id) => MappedCDDAId {
-                            tilesheet_id: TilesheetCDDAId {
-                                id: replace_region_setting(
-                                    &mapped_id.tilesheet_id.id,
-                                    region_settings,
-                                    &json_data.terrain,
-                                    &json_data.furniture,
-                                ),
-                                prefix: mapped_id.tilesheet_id.prefix.clone(),
-                                postfix: mapped_id.tilesheet_id.postfix.clone(),
-                            },
-                            rotation: mapped_id.rotation.clone(),
-                            is_broken: mapped_id.is_broken,
-                            is_open: mapped_id.is_open,
-                        },
-                    };
-
-                    match tilesheet_lock.deref() {
-                        None => {
-                            let sprite = fallback_tilesheet.get_fallback(&id, &json_data);
-
-                            let position_uvec2 = UVec2::new(
-                                tile_3d_coords.x as u32,
-                                tile_3d_coords.y as u32,
-                            );
-
-                            let fallback_sprite = DisplaySprite::Fallback(FallbackSprite {
-                                position: UVec2JsonKey(position_uvec2),
-                                index: sprite,
-                                z: tile_3d_coords.z,
-                            });
-
-                            layer_map.insert(layer.clone(), (Some(fallback_sprite), None));
-                        }
-                        Some(tilesheet) => {
-                            let sprite = tilesheet.get_sprite(&id, &json_data);
-
-                            let adjacent_idents = local_mapped_cdda_ids
-                                .get_adjacent_identifiers(tile_3d_coords, &layer);
-
-                            let (fg, bg) = match sprite {
-                                None => {
-                                    let fallback =
-                                        tilesheet.get_fallback(&id, &json_data);
-                                    let position_uvec2 = UVec2::new(
-                                        tile_3d_coords.x as u32,
-                                        tile_3d_coords.y as u32,
-                                    );
-
-                                    (
-                                        Some(DisplaySprite::Fallback(FallbackSprite {
-                                            position: UVec2JsonKey(position_uvec2),
-                                            index: fallback,
-                                            z: tile_3d_coords.z,
-                                        })),
-                                        None,
-                                    )
-                                }
-                                Some(sprite) => {
-                                    DisplaySprite::get_display_sprite_from_sprite(
-                                        &sprite,
-                                        &id,
-                                        tile_3d_coords.clone(),
-                                        layer.clone(),
-                                        &adjacent_idents,
-                                        json_data,
-                                    )
-                                }
-                            };
-
-                            layer_map.insert(layer.clone(), (fg, bg));
-                        }
-                    }
-                }
+        (project.name.clone(), importer)
+    };
 
-                layer_map
-            })
-            .collect();
-
-        tile_map.into_iter().for_each(|mut layer_map| {
-            for tile_layer in TileLayer::iter() {
-                match layer_map.remove(&tile_layer) {
-                    None => {},
-                    Some((fg, bg)) => {
-                        if let Some(fg) = fg {
-                            insert_sprite_type!(fg);
-                        }
-                        if let Some(bg) = bg {
-                            insert_sprite_type!(bg);
-                        }
-                    },
-                }
-            }
-        });
+    // The files are read without holding the lock, so other commands are not
+    // blocked by the file io
+    let overmaps = importer.load_overmaps().await?;
 
-        saved_cdda_ids.insert(*z, local_mapped_cdda_ids);
-    }
+    let editor_data_lock = editor_data.lock().await;
+    let project = editor_data_lock
+        .loaded_projects
+        .get(&name)
+        .ok_or(GetCurrentProjectError::InvalidProjectName(name.clone()))?;
 
-    let mut mapped_cdda_ids_lock = mapped_cdda_ids.lock().await;
-    mapped_cdda_ids_lock.replace(saved_cdda_ids);
+    Ok(overmap_special_layout(&overmaps, &project.maps))
+}
 
-    app.emit(
-        events::PLACE_SPRITES,
-        PlaceSpritesEvent {
-            static_sprites,
-            animated_sprites,
-            fallback_sprites,
-        },
-    )
-    .unwrap();
+/// The z-level of the currently opened project which is shown and edited
+#[tauri::command]
+pub async fn get_active_z_level(
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<ZLevel, GetCurrentProjectError> {
+    let editor_data_lock = editor_data.lock().await;
+    let project = util::get_current_project(&editor_data_lock)?;
 
-    Ok(())
+    Ok(project.active_z_level)
 }
 
 #[derive(Debug, Error)]
-pub enum ReloadProjectError {
-    #[error(transparent)]
-    CDDADataError(#[from] CDDADataError),
-
+pub enum SetActiveZLevelError {
     #[error(transparent)]
     ProjectError(#[from] GetCurrentProjectError),
 
-    #[error(transparent)]
-    GetLiveViewerError(#[from] GetLiveViewerDataError),
-
-    #[error(transparent)]
-    CalculateParametersError(#[from] CalculateParametersError),
+    #[error("The project has no maps on the z-level {0}")]
+    NoZLevel(ZLevel),
 }
 
-impl_serialize_for_error!(ReloadProjectError);
+impl_serialize_for_error!(SetActiveZLevelError);
 
+/// Switches the currently opened project to the z-level `z`. Map editor
+/// projects get a new empty z-level if it does not exist yet.
 #[tauri::command]
-pub async fn reload_project(
+pub async fn set_active_z_level(
+    z: ZLevel,
+    app: AppHandle,
     editor_data: State<'_, Mutex<EditorData>>,
-    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
-) -> Result<(), ReloadProjectError> {
-    let json_data_lock = json_data.lock().await;
-    let json_data = get_json_data(&json_data_lock)?;
+    editor_data_changes: State<'_, EditorDataChanges>,
+    render_cache: State<'_, RenderCache>,
+) -> Result<(), SetActiveZLevelError> {
     let mut editor_data_lock = editor_data.lock().await;
     let project = get_current_project_mut(&mut editor_data_lock)?;
 
-    match &project.ty {
-        ProjectType::MapEditor(_) => unimplemented!(),
-        ProjectType::LiveViewer(lvd) => {
-            let mut map_data_collection =
-                get_map_data_collection_from_live_viewer_data(lvd).await?;
+    if !project.maps.contains_key(&z) {
+        if !matches!(project.ty, ProjectType::MapEditor(_)) {
+            return Err(SetActiveZLevelError::NoZLevel(z));
+        }
+
+        project.add_z_level(z);
+        project.dirty = true;
+        render_cache.invalidate_project(&project.name);
 
-            for (_, map_data) in map_data_collection.iter_mut() {
-                map_data.calculate_parameters(&json_data.palettes)?
-            }
+        app.emit_event(ProjectChangedEvent {
+            name: project.name.clone(),
+            dirty: project.dirty,
+        })
+        .ok();
+    }
 
-            project.maps = map_data_collection;
-        },
+    project.active_z_level = z;
+
+    let saver = ProgramDataSaver {
+        path: editor_data_lock.config.config_path.clone(),
+    };
+
+    if let Err(e) = saver.save(editor_data_lock.deref()).await {
+        warn!("Failed to save the active z-level, {}", e);
     }
 
+    editor_data_changes.notify(&app, &[EditorDataScope::OpenedProject]);
+
     Ok(())
 }
 
@@ -404,6 +1046,263 @@ pub async fn get_project_cell_data(
     Ok(mapped_cdda_ids.clone())
 }
 
+/// Get detailed information about the terrain and furniture in the cell at
+/// `position`
+#[tauri::command]
+pub async fn get_cell_inspection(
+    position: IVec3JsonKey,
+    mapped_cdda_ids: State<
+        '_,
+        Mutex<Option<HashMap<ZLevel, MappedCDDAIdContainer>>>,
+    >,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<CellInspection, GetProjectCellDataError> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let editor_data_lock = editor_data.lock().await;
+    let project = util::get_current_project(&editor_data_lock)?;
+
+    let mapped_cdda_ids_lock = mapped_cdda_ids.lock().await;
+    let mapped_cdda_ids = match mapped_cdda_ids_lock.deref() {
+        None => return Err(GetProjectCellDataError::NoMapOpened),
+        Some(m) => m,
+    };
+
+    let inspection = mapped_cdda_ids
+        .get(&position.0.z)
+        .and_then(|container| container.ids.get(&position.0))
+        .map(|ids| CellInspection::new(ids, json_data, project.region.as_ref()))
+        .unwrap_or_default();
+
+    Ok(inspection)
+}
+
+/// Get the ids of the cell at `position` and its four neighbors together with
+/// the multitile connections of the cell in a single call
+#[tauri::command]
+pub async fn probe_cell(
+    position: IVec3JsonKey,
+    mapped_cdda_ids: State<
+        '_,
+        Mutex<Option<HashMap<ZLevel, MappedCDDAIdContainer>>>,
+    >,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+    editor_data: State<'_, Mutex<EditorData>>,
+) -> Result<CellProbe, GetProjectCellDataError> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let editor_data_lock = editor_data.lock().await;
+    let project = util::get_current_project(&editor_data_lock)?;
+
+    let mapped_cdda_ids_lock = mapped_cdda_ids.lock().await;
+    let mapped_cdda_ids = match mapped_cdda_ids_lock.deref() {
+        None => return Err(GetProjectCellDataError::NoMapOpened),
+        Some(m) => m,
+    };
+
+    let probe = mapped_cdda_ids
+        .get(&position.0.z)
+        .map(|container| {
+            CellProbe::new(
+                position.0,
+                container,
+                json_data,
+                &project.custom_connect_groups,
+            )
+        })
+        .unwrap_or_default();
+
+    Ok(probe)
+}
+
+#[derive(Debug, Error)]
+pub enum ResolveCharactersError {
+    #[error(transparent)]
+    ProjectError(#[from] GetCurrentProjectError),
+
+    #[error(transparent)]
+    CDDADataError(#[from] CDDADataError),
+
+    #[error("No map exists at {0}")]
+    UnknownMap(IVec3),
+
+    #[error("No map contains the cell at {0}")]
+    UnknownCell(IVec3),
+}
+
+impl_serialize_for_error!(ResolveCharactersError);
+
+/// Reports for every character where it is mapped per mapping kind in the map
+/// at `map` (map x, map y, z) of the current project. Characters which are not
+/// mapped for any kind are returned with an empty mapping.
+///
+/// Only the mappings are looked up and no tiles are generated, so this is fast
+/// enough to be called while the rows of a map are being edited.
+#[tauri::command]
+pub async fn resolve_characters(
+    map: IVec3JsonKey,
+    characters: Vec<char>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+) -> Result<
+    IndexMap<char, HashMap<MappingKind, MappingSource>>,
+    ResolveCharactersError,
+> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let editor_data_lock = editor_data.lock().await;
+    let project = util::get_current_project(&editor_data_lock)?;
+
+    let map_data = project
+        .maps
+        .get(&map.0.z)
+        .and_then(|collection| {
+            collection.maps.get(&UVec2::new(map.0.x as u32, map.0.y as u32))
+        })
+        .ok_or(ResolveCharactersError::UnknownMap(map.0))?;
+
+    let resolved = characters
+        .into_iter()
+        .map(|character| {
+            let mappings = MappingKind::iter()
+                .filter_map(|kind| {
+                    map_data
+                        .get_mapping_source(&kind, &character, json_data)
+                        .map(|source| (kind, source))
+                })
+                .collect();
+
+            (character, mappings)
+        })
+        .collect();
+
+    Ok(resolved)
+}
+
+/// Returns the normalized chance of every id the `characters` of the map at
+/// `map` (map x, map y, z) can turn into, so the weights of distributions,
+/// parameters and switches can be checked at a glance. Characters mapped to a
+/// single id are left out.
+#[tauri::command]
+pub async fn get_mapping_probabilities(
+    map: IVec3JsonKey,
+    characters: Vec<char>,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+) -> Result<
+    IndexMap<char, HashMap<MappingKind, Vec<IdProbability>>>,
+    ResolveCharactersError,
+> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let editor_data_lock = editor_data.lock().await;
+    let project = util::get_current_project(&editor_data_lock)?;
+
+    let map_data = project
+        .maps
+        .get(&map.0.z)
+        .and_then(|collection| {
+            collection.maps.get(&UVec2::new(map.0.x as u32, map.0.y as u32))
+        })
+        .ok_or(ResolveCharactersError::UnknownMap(map.0))?;
+
+    let parameters = collect_parameters(map_data, json_data);
+
+    let probabilities = characters
+        .into_iter()
+        .map(|character| {
+            let breakdowns = [MappingKind::Terrain, MappingKind::Furniture]
+                .into_iter()
+                .filter_map(|kind| {
+                    let value = map_data
+                        .get_mapgen_value(&kind, &character, json_data)?;
+
+                    if let MapGenValue::String(_) = value {
+                        return None;
+                    }
+
+                    Some((kind, probability_breakdown(&value, &parameters)))
+                })
+                .collect();
+
+            (character, breakdowns)
+        })
+        .collect();
+
+    Ok(probabilities)
+}
+
+/// Returns the normalized chance of every terrain and furniture the cell at
+/// the global `position` can turn into. Every palette, distribution, parameter
+/// and switch the mapping of the cell goes through is expanded, so all
+/// outcomes are listed instead of the one of the current seed.
+#[tauri::command]
+pub async fn get_cell_probabilities(
+    position: IVec3JsonKey,
+    editor_data: State<'_, Mutex<EditorData>>,
+    json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
+    demo_json_data: State<'_, DemoJsonData>,
+) -> Result<HashMap<MappingKind, Vec<IdProbability>>, ResolveCharactersError> {
+    let json_data_lock = lock_json_data(&json_data, &demo_json_data).await;
+    let json_data = get_json_data(&json_data_lock)?;
+
+    let editor_data_lock = editor_data.lock().await;
+    let project = util::get_current_project(&editor_data_lock)?;
+
+    if position.0.x < 0 || position.0.y < 0 {
+        return Err(ResolveCharactersError::UnknownCell(position.0));
+    }
+
+    let map_coordinates = UVec2::new(
+        position.0.x as u32 / DEFAULT_MAP_DATA_SIZE.x,
+        position.0.y as u32 / DEFAULT_MAP_DATA_SIZE.y,
+    );
+    let cell_coordinates = UVec2::new(
+        position.0.x as u32 % DEFAULT_MAP_DATA_SIZE.x,
+        position.0.y as u32 % DEFAULT_MAP_DATA_SIZE.y,
+    );
+
+    let map_data = project
+        .maps
+        .get(&position.0.z)
+        .and_then(|collection| collection.maps.get(&map_coordinates))
+        .ok_or(ResolveCharactersError::UnknownCell(position.0))?;
+
+    // Cells which are left out of sparse maps are not generated at all
+    let Some(cell) = map_data.cells.get(&cell_coordinates) else {
+        return Ok(HashMap::new());
+    };
+
+    let parameters = collect_parameters(map_data, json_data);
+
+    let probabilities = [MappingKind::Terrain, MappingKind::Furniture]
+        .into_iter()
+        .map(|kind| {
+            let breakdown = cell_breakdown(
+                map_data,
+                &kind,
+                &cell.character,
+                json_data,
+                &parameters,
+            );
+
+            (kind, breakdown)
+        })
+        .filter(|(_, breakdown)| !breakdown.is_empty())
+        .collect();
+
+    Ok(probabilities)
+}
+
 #[derive(Debug, Error)]
 pub enum NewMapgenViewerError {
     #[error(transparent)]
@@ -419,6 +1318,7 @@ pub async fn new_single_mapgen_viewer(
     project_name: String,
     app: AppHandle,
     editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
     json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
 ) -> Result<(), NewMapgenViewerError> {
     let data = serde_json::to_string_pretty(&json!(
@@ -446,8 +1346,11 @@ pub async fn new_single_mapgen_viewer(
             mapgen_file_paths: vec![path],
             project_name,
             om_id: CDDAIdentifier(om_terrain_name),
+            stacked_om_ids: BTreeMap::new(),
+            update_ids: vec![],
         },
         editor_data,
+        editor_data_changes,
         json_data,
     )
     .await?;
@@ -466,6 +1369,7 @@ pub async fn new_special_mapgen_viewer(
     special_z_to: i32,
     app: AppHandle,
     editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
     json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
 ) -> Result<(), NewMapgenViewerError> {
     let mut data = Vec::new();
@@ -539,6 +1443,7 @@ pub async fn new_special_mapgen_viewer(
             om_id: CDDAIdentifier(om_terrain_name),
         },
         editor_data,
+        editor_data_changes,
         json_data,
     )
     .await?;
@@ -555,6 +1460,7 @@ pub async fn new_nested_mapgen_viewer(
     nested_height: Bound_usize<1, 24>,
     app: AppHandle,
     editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
     json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
 ) -> Result<(), NewMapgenViewerError> {
     let mut rows = Vec::new();
@@ -588,8 +1494,11 @@ pub async fn new_nested_mapgen_viewer(
             mapgen_file_paths: vec![path.clone()],
             project_name,
             om_id: CDDAIdentifier(om_terrain_name),
+            stacked_om_ids: BTreeMap::new(),
+            update_ids: vec![],
         },
         editor_data,
+        editor_data_changes,
         json_data,
     )
     .await?;
@@ -608,6 +1517,13 @@ pub enum OpenViewerData {
         mapgen_file_paths: Vec<PathBuf>,
         project_name: String,
         om_id: CDDAIdentifier,
+        /// Overmap terrains which are shown on other z-levels than the `om_id`
+        #[serde(default)]
+        stacked_om_ids: BTreeMap<ZLevel, CDDAIdentifier>,
+        /// Update mapgens which are applied in order on top of the `om_id`,
+        /// like the upgrades of a faction camp
+        #[serde(default)]
+        update_ids: Vec<CDDAIdentifier>,
     },
     Special {
         mapgen_file_paths: Vec<PathBuf>,
@@ -615,6 +1531,21 @@ pub enum OpenViewerData {
         project_name: String,
         om_id: CDDAIdentifier,
     },
+    Linear {
+        mapgen_file_paths: Vec<PathBuf>,
+        project_name: String,
+        om_id: CDDAIdentifier,
+    },
+    Nested {
+        mapgen_file_paths: Vec<PathBuf>,
+        project_name: String,
+        nested_id: CDDAIdentifier,
+    },
+    Update {
+        mapgen_file_paths: Vec<PathBuf>,
+        project_name: String,
+        update_id: CDDAIdentifier,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -630,6 +1561,12 @@ pub enum OpenViewerError {
 
     #[error(transparent)]
     CalculateParametersError(#[from] CalculateParametersError),
+
+    #[error(transparent)]
+    LiveViewerError(#[from] GetLiveViewerDataError),
+
+    #[error("{0} is not an update mapgen")]
+    NotAnUpdateMapgen(CDDAIdentifier),
 }
 impl_serialize_for_error!(OpenViewerError);
 
@@ -638,19 +1575,22 @@ pub async fn create_viewer(
     app: AppHandle,
     data: OpenViewerData,
     editor_data: State<'_, Mutex<EditorData>>,
+    editor_data_changes: State<'_, EditorDataChanges>,
     json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
 ) -> Result<(), OpenViewerError> {
     info!("Creating Live viewer");
 
-    let mut editor_data_lock = editor_data.lock().await;
     let json_data_lock = json_data.lock().await;
     let json_data = get_json_data(&json_data_lock)?;
+    let mut editor_data_lock = editor_data.lock().await;
 
     match data {
         OpenViewerData::Terrain {
             project_name,
             mapgen_file_paths,
             om_id,
+            stacked_om_ids,
+            update_ids,
         } => {
             if editor_data_lock
                 .loaded_projects
@@ -660,25 +1600,39 @@ pub async fn create_viewer(
                 return Err(OpenViewerError::ProjectAlreadyExists);
             }
 
-            let mut overmap_terrain_importer = SingleMapDataImporter {
-                om_terrain: om_id.clone(),
-                paths: mapgen_file_paths.clone(),
+            if let Some(id) = update_ids
+                .iter()
+                .find(|id| !json_data.update_mapgen_ids.contains(id))
+            {
+                return Err(OpenViewerError::NotAnUpdateMapgen(id.clone()));
+            }
+
+            let live_viewer_data = LiveViewerData::Terrain {
+                mapgen_file_paths,
+                project_name: project_name.clone(),
+                om_id,
+                stacked_om_ids,
             };
 
-            let mut collection = overmap_terrain_importer.load().await.unwrap();
-            collection.calculate_parameters(&json_data.palettes)?;
+            let mut maps =
+                get_map_data_collection_from_live_viewer_data(&live_viewer_data)
+                    .await?;
+
+            for (_, m) in maps.iter_mut() {
+                m.calculate_parameters(&json_data.palettes)?
+            }
 
             let mut new_project = Project::new(
                 project_name.clone(),
-                DEFAULT_MAP_DATA_SIZE,
-                ProjectType::LiveViewer(LiveViewerData::Terrain {
-                    mapgen_file_paths,
-                    project_name: project_name.clone(),
-                    om_id,
-                }),
+                get_size(&maps),
+                ProjectType::LiveViewer(live_viewer_data),
             );
 
-            new_project.maps.insert(0, collection);
+            if !update_ids.is_empty() {
+                new_project.camp_upgrades.insert(0, update_ids);
+            }
+
+            new_project.maps = maps;
             editor_data_lock
                 .loaded_projects
                 .insert(project_name.clone(), new_project);
@@ -690,16 +1644,14 @@ pub async fn create_viewer(
             let recent_project = RecentProject {
                 path: editor_data_lock.config.config_path.clone(),
                 name: project_name.clone(),
+                thumbnail: None,
             };
             editor_data_lock.recent_projects.insert(recent_project);
 
-            app.emit(
-                events::TAB_CREATED,
-                Tab {
-                    name: project_name.clone(),
-                    tab_type: TabType::LiveViewer,
-                },
-            )?;
+            app.emit_event(Tab {
+                name: project_name.clone(),
+                tab_type: TabType::LiveViewer,
+            })?;
         },
         OpenViewerData::Special {
             project_name,
@@ -715,13 +1667,70 @@ pub async fn create_viewer(
                 return Err(OpenViewerError::ProjectAlreadyExists);
             }
 
-            let mut overmap_special_importer = OvermapSpecialImporter {
-                om_special_id: om_id.clone(),
-                overmap_special_paths: om_file_paths.clone(),
-                mapgen_entry_paths: mapgen_file_paths.clone(),
+            let live_viewer_data = LiveViewerData::Special {
+                mapgen_file_paths,
+                om_file_paths,
+                project_name: project_name.clone(),
+                om_id,
+            };
+
+            let mut maps =
+                get_map_data_collection_from_live_viewer_data(&live_viewer_data)
+                    .await?;
+
+            for (_, m) in maps.iter_mut() {
+                m.calculate_parameters(&json_data.palettes)?
+            }
+
+            let mut new_project = Project::new(
+                project_name.clone(),
+                get_size(&maps),
+                ProjectType::LiveViewer(live_viewer_data),
+            );
+
+            new_project.maps = maps;
+            editor_data_lock
+                .loaded_projects
+                .insert(project_name.clone(), new_project);
+            editor_data_lock
+                .openable_projects
+                .insert(project_name.clone());
+
+            let recent_project = RecentProject {
+                path: editor_data_lock.config.config_path.clone(),
+                name: project_name.clone(),
+                thumbnail: None,
+            };
+            editor_data_lock.recent_projects.insert(recent_project);
+
+            editor_data_lock.opened_project = Some(project_name.clone());
+            app.emit_event(Tab {
+                name: project_name.clone(),
+                tab_type: TabType::LiveViewer,
+            })?;
+        },
+        OpenViewerData::Linear {
+            project_name,
+            mapgen_file_paths,
+            om_id,
+        } => {
+            if editor_data_lock
+                .loaded_projects
+                .get(&project_name)
+                .is_some()
+            {
+                return Err(OpenViewerError::ProjectAlreadyExists);
+            }
+
+            let live_viewer_data = LiveViewerData::Linear {
+                mapgen_file_paths,
+                project_name: project_name.clone(),
+                om_id,
             };
 
-            let mut maps = overmap_special_importer.load().await.unwrap();
+            let mut maps =
+                get_map_data_collection_from_live_viewer_data(&live_viewer_data)
+                    .await?;
 
             for (_, m) in maps.iter_mut() {
                 m.calculate_parameters(&json_data.palettes)?
@@ -730,12 +1739,7 @@ pub async fn create_viewer(
             let mut new_project = Project::new(
                 project_name.clone(),
                 get_size(&maps),
-                ProjectType::LiveViewer(LiveViewerData::Special {
-                    mapgen_file_paths,
-                    om_file_paths,
-                    project_name: project_name.clone(),
-                    om_id,
-                }),
+                ProjectType::LiveViewer(live_viewer_data),
             );
 
             new_project.maps = maps;
@@ -749,17 +1753,59 @@ pub async fn create_viewer(
             let recent_project = RecentProject {
                 path: editor_data_lock.config.config_path.clone(),
                 name: project_name.clone(),
+                thumbnail: None,
             };
             editor_data_lock.recent_projects.insert(recent_project);
 
             editor_data_lock.opened_project = Some(project_name.clone());
-            app.emit(
-                events::TAB_CREATED,
-                Tab {
-                    name: project_name.clone(),
-                    tab_type: TabType::LiveViewer,
-                },
-            )?;
+            app.emit_event(Tab {
+                name: project_name.clone(),
+                tab_type: TabType::LiveViewer,
+            })?;
+        },
+        OpenViewerData::Nested {
+            project_name,
+            mapgen_file_paths,
+            nested_id,
+        } => {
+            let live_viewer_data = LiveViewerData::Nested {
+                mapgen_file_paths,
+                project_name: project_name.clone(),
+                nested_id,
+            };
+
+            open_mapgen_viewer(
+                &app,
+                &mut editor_data_lock,
+                json_data,
+                project_name,
+                live_viewer_data,
+            )
+            .await?;
+        },
+        OpenViewerData::Update {
+            project_name,
+            mapgen_file_paths,
+            update_id,
+        } => {
+            if !json_data.update_mapgen_ids.contains(&update_id) {
+                return Err(OpenViewerError::NotAnUpdateMapgen(update_id));
+            }
+
+            let live_viewer_data = LiveViewerData::Update {
+                mapgen_file_paths,
+                project_name: project_name.clone(),
+                update_id,
+            };
+
+            open_mapgen_viewer(
+                &app,
+                &mut editor_data_lock,
+                json_data,
+                project_name,
+                live_viewer_data,
+            )
+            .await?;
         },
     }
 
@@ -769,7 +1815,59 @@ pub async fn create_viewer(
 
     saver.save(editor_data_lock.deref()).await.unwrap();
 
-    app.emit(events::EDITOR_DATA_CHANGED, editor_data_lock.clone())?;
+    editor_data_changes.notify(
+        &app,
+        &[EditorDataScope::ProjectList, EditorDataScope::OpenedProject],
+    );
+
+    Ok(())
+}
+
+/// Opens a live viewer which shows a single nested or update mapgen on its
+/// own
+async fn open_mapgen_viewer(
+    app: &AppHandle,
+    editor_data: &mut EditorData,
+    json_data: &DeserializedCDDAJsonData,
+    project_name: String,
+    live_viewer_data: LiveViewerData,
+) -> Result<(), OpenViewerError> {
+    if editor_data.loaded_projects.get(&project_name).is_some() {
+        return Err(OpenViewerError::ProjectAlreadyExists);
+    }
+
+    let mut maps =
+        get_map_data_collection_from_live_viewer_data(&live_viewer_data)
+            .await?;
+
+    for (_, m) in maps.iter_mut() {
+        m.calculate_parameters(&json_data.palettes)?
+    }
+
+    let mut new_project = Project::new(
+        project_name.clone(),
+        get_size(&maps),
+        ProjectType::LiveViewer(live_viewer_data),
+    );
+
+    new_project.maps = maps;
+    editor_data
+        .loaded_projects
+        .insert(project_name.clone(), new_project);
+    editor_data.openable_projects.insert(project_name.clone());
+
+    let recent_project = RecentProject {
+        path: editor_data.config.config_path.clone(),
+        name: project_name.clone(),
+        thumbnail: None,
+    };
+    editor_data.recent_projects.insert(recent_project);
+
+    editor_data.opened_project = Some(project_name.clone());
+    app.emit_event(Tab {
+        name: project_name,
+        tab_type: TabType::LiveViewer,
+    })?;
 
     Ok(())
 }
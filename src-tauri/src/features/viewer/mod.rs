@@ -1,2 +1,6 @@
+pub mod cache;
+pub mod context;
 mod data;
 pub mod handlers;
+pub mod render;
+pub mod special;
@@ -0,0 +1,260 @@
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::data::TileLayer;
+use crate::features::map::GetMappedCDDAIdsError;
+use crate::features::program_data::{MappedCDDAIdContainer, Project, ZLevel};
+use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+use crate::features::tileset::{SpriteLayer, Tilesheet, VariantPicker};
+use crate::features::viewer::data::{
+    AnimatedSprite, DisplaySprite, DrawOrder, FallbackSprite, StaticSprite,
+};
+use crate::util::UVec2JsonKey;
+use glam::{IVec3, UVec2};
+use log::warn;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use std::collections::{HashMap, HashSet};
+use strum::IntoEnumIterator;
+
+/// The sprites of every tile of a project and the ids they were picked for
+#[derive(Debug, Clone)]
+pub struct ProjectRender {
+    pub mapped_cdda_ids: HashMap<ZLevel, MappedCDDAIdContainer>,
+    pub static_sprites: HashSet<StaticSprite>,
+    pub animated_sprites: HashSet<AnimatedSprite>,
+    pub fallback_sprites: HashSet<FallbackSprite>,
+}
+
+/// Picks the sprites of every tile of the `project`. Without a `tilesheet`,
+/// every tile is drawn with the `fallback_tilesheet`.
+pub fn render_project(
+    project: &mut Project,
+    json_data: &mut DeserializedCDDAJsonData,
+    tilesheet: Option<&LegacyTilesheet>,
+    fallback_tilesheet: &LegacyTilesheet,
+) -> Result<ProjectRender, GetMappedCDDAIdsError> {
+    let mut static_sprites = HashSet::new();
+    let mut animated_sprites = HashSet::new();
+    let mut fallback_sprites = HashSet::new();
+
+    macro_rules! insert_sprite_type {
+        ($val:expr) => {
+            match $val {
+                DisplaySprite::Static(s) => {
+                    static_sprites.insert(s);
+                },
+                DisplaySprite::Animated(a) => {
+                    animated_sprites.insert(a);
+                },
+                DisplaySprite::Fallback(f) => {
+                    fallback_sprites.insert(f);
+                },
+            }
+        };
+    }
+
+    for (_, map_collection) in project.maps.iter_mut() {
+        // we need to calculate the parameters for the predecessor here because we
+        // cannot borrow json data as mutable inside the get_mapped_cdda_ids function
+        map_collection.calculate_predecessor_parameters(json_data);
+    }
+
+    for (_, update_ids) in project.camp_upgrades.iter() {
+        if let Err(e) = json_data.calculate_update_mapgen_parameters(update_ids)
+        {
+            warn!("Could not calculate update mapgen parameters {}", e);
+        }
+    }
+
+    let region_settings = json_data
+        .get_region_settings(project.region.as_ref())
+        .ok_or(GetMappedCDDAIdsError::MissingRegionSettings)?;
+
+    let mut saved_cdda_ids = HashMap::new();
+    let render_state = project.render_state.clone();
+
+    let all_mapped_cdda_ids = project.get_mapped_cdda_ids(json_data)?;
+
+    for (z, local_mapped_cdda_ids) in all_mapped_cdda_ids {
+        // Regional ids are resolved once for the whole z-level, so every tile
+        // connects to the same ids its neighbors are drawn with, even across
+        // the borders of the overmap terrains
+        let resolved_cdda_ids = local_mapped_cdda_ids
+            .with_region_settings_replaced(region_settings);
+
+        let tile_map: Vec<(
+            HashMap<TileLayer, (Option<DisplaySprite>, Option<DisplaySprite>)>,
+            Vec<DisplaySprite>,
+        )> = resolved_cdda_ids
+            .ids
+            .par_iter()
+            .map(|(p, identifier_group)| {
+                let tile_3d_coords = IVec3::new(p.x, p.y, z);
+
+                if identifier_group.terrain.is_none()
+                    && identifier_group.furniture.is_none()
+                {
+                    warn!(
+                        "No sprites found for identifier_group {:?} at \
+                         coordinates {}",
+                        identifier_group, tile_3d_coords
+                    );
+
+                    return (HashMap::new(), vec![]);
+                }
+
+                let mut layer_map = HashMap::new();
+                let mut overlays = vec![];
+
+                // Layer is used here so furniture is
+                // above terrain
+                for (layer, o_id) in [
+                    (TileLayer::Terrain, &identifier_group.terrain),
+                    (TileLayer::Furniture, &identifier_group.furniture),
+                    (TileLayer::Trap, &identifier_group.trap),
+                    (TileLayer::Vehicle, &identifier_group.vehicle),
+                    (TileLayer::Monster, &identifier_group.monster),
+                    (TileLayer::Field, &identifier_group.field),
+                ] {
+                    let id = match o_id {
+                        None => continue,
+                        Some(mapped_id) => mapped_id.clone(),
+                    };
+
+                    match tilesheet {
+                        None => {
+                            let sprite = fallback_tilesheet.get_fallback(&id, &json_data);
+
+                            let position_uvec2 = UVec2::new(
+                                tile_3d_coords.x as u32,
+                                tile_3d_coords.y as u32,
+                            );
+
+                            let fallback_sprite = DisplaySprite::Fallback(FallbackSprite {
+                                position: UVec2JsonKey(position_uvec2),
+                                index: sprite,
+                                z: tile_3d_coords.z,
+                                draw_order: DrawOrder::new(
+                                    &layer,
+                                    SpriteLayer::Fg,
+                                    tile_3d_coords,
+                                    fallback_tilesheet.projection(),
+                                ),
+                            });
+
+                            layer_map.insert(layer.clone(), (Some(fallback_sprite), None));
+                        }
+                        Some(tilesheet) => {
+                            let sprite = tilesheet.get_sprite(&id, &json_data);
+
+                            let adjacent_idents = resolved_cdda_ids
+                                .get_adjacent_identifiers(tile_3d_coords, &layer);
+
+                            if layer == TileLayer::Monster && sprite.is_some() {
+                                let overlay_sprites = tilesheet
+                                    .get_overlay_sprites(&id.tilesheet_id.id, json_data);
+
+                                overlays.extend(DisplaySprite::get_overlay_display_sprites(
+                                    &overlay_sprites,
+                                    &id,
+                                    tile_3d_coords,
+                                    layer.clone(),
+                                    &adjacent_idents,
+                                    json_data,
+                                    tilesheet,
+                                    &VariantPicker::for_tile(
+                                        render_state.variant_seed,
+                                        tile_3d_coords,
+                                        &layer,
+                                        &id.tilesheet_id,
+                                    ),
+                                ));
+                            }
+
+                            let (fg, bg) = match sprite {
+                                None => {
+                                    let fallback =
+                                        tilesheet.get_fallback(&id, &json_data);
+                                    let position_uvec2 = UVec2::new(
+                                        tile_3d_coords.x as u32,
+                                        tile_3d_coords.y as u32,
+                                    );
+
+                                    (
+                                        Some(DisplaySprite::Fallback(FallbackSprite {
+                                            position: UVec2JsonKey(position_uvec2),
+                                            index: fallback,
+                                            z: tile_3d_coords.z,
+                                            draw_order: DrawOrder::new(
+                                                &layer,
+                                                SpriteLayer::Fg,
+                                                tile_3d_coords,
+                                                tilesheet.projection(),
+                                            ),
+                                        })),
+                                        None,
+                                    )
+                                }
+                                Some(sprite) => {
+                                    DisplaySprite::get_display_sprite_from_sprite(
+                                        &sprite,
+                                        &id,
+                                        tile_3d_coords.clone(),
+                                        layer.clone(),
+                                        &adjacent_idents,
+                                        json_data,
+                                        &project.custom_connect_groups,
+                                        tilesheet,
+                                        &VariantPicker::for_tile(
+                                            render_state.variant_seed,
+                                            tile_3d_coords,
+                                            &layer,
+                                            &id.tilesheet_id,
+                                        )
+                                        .with_pinned(
+                                            render_state.get_pinned_variant(
+                                                &layer,
+                                                tile_3d_coords,
+                                            ),
+                                        ),
+                                    )
+                                }
+                            };
+
+                            layer_map.insert(layer.clone(), (fg, bg));
+                        }
+                    }
+                }
+
+                (layer_map, overlays)
+            })
+            .collect();
+
+        tile_map.into_iter().for_each(|(mut layer_map, overlays)| {
+            for tile_layer in TileLayer::iter() {
+                match layer_map.remove(&tile_layer) {
+                    None => {},
+                    Some((fg, bg)) => {
+                        if let Some(fg) = fg {
+                            insert_sprite_type!(fg);
+                        }
+                        if let Some(bg) = bg {
+                            insert_sprite_type!(bg);
+                        }
+                    },
+                }
+            }
+
+            for overlay in overlays {
+                insert_sprite_type!(overlay);
+            }
+        });
+
+        saved_cdda_ids.insert(z, local_mapped_cdda_ids);
+    }
+
+    Ok(ProjectRender {
+        mapped_cdda_ids: saved_cdda_ids,
+        static_sprites,
+        animated_sprites,
+        fallback_sprites,
+    })
+}
@@ -0,0 +1,103 @@
+use crate::data::overmap::OvermapSpecialOvermap;
+use crate::features::map::importing::overmap_special_origin;
+use crate::features::program_data::{MapDataCollection, ZLevel};
+use cdda_lib::types::CDDAIdentifier;
+use glam::UVec2;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// An overmap terrain of an overmap special at the position it is drawn at
+/// in the viewer, used to draw the grid lines and labels of every overmap
+/// terrain
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecialOvermapTerrain {
+    /// Position of the map in its z-level, in overmap terrains
+    pub position: UVec2,
+    pub z: ZLevel,
+    /// The overmap terrain including its rotation suffix
+    pub om_id: CDDAIdentifier,
+    /// Whether a mapgen was found for the overmap terrain. Nothing is drawn
+    /// at the position of overmap terrains without one
+    pub has_mapgen: bool,
+}
+
+/// Lays out the `overmaps` of a special the same way the special importer
+/// places their maps, checking the imported `maps` for which of them could
+/// be resolved
+pub fn overmap_special_layout(
+    overmaps: &[OvermapSpecialOvermap],
+    maps: &HashMap<ZLevel, MapDataCollection>,
+) -> Vec<SpecialOvermapTerrain> {
+    let origin = overmap_special_origin(overmaps);
+
+    let mut layout: Vec<SpecialOvermapTerrain> = overmaps
+        .iter()
+        .map(|overmap| {
+            let position = (overmap.point.truncate() - origin).as_uvec2();
+            let z = overmap.point.z;
+
+            SpecialOvermapTerrain {
+                position,
+                z,
+                om_id: overmap.overmap.clone().unwrap_or("null".into()),
+                has_mapgen: maps
+                    .get(&z)
+                    .is_some_and(|c| c.maps.contains_key(&position)),
+            }
+        })
+        .collect();
+
+    layout.sort_by_key(|t| (t.z, t.position.y, t.position.x));
+    layout
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::map::MapData;
+    use glam::IVec3;
+
+    fn overmap(x: i32, y: i32, z: i32, id: &str) -> OvermapSpecialOvermap {
+        OvermapSpecialOvermap {
+            point: IVec3::new(x, y, z),
+            overmap: Some(id.into()),
+        }
+    }
+
+    #[test]
+    fn test_layout_moves_negative_points_to_origin() {
+        let overmaps = vec![
+            overmap(-1, 0, 0, "lab_north"),
+            overmap(0, -1, 1, "lab_roof_north"),
+        ];
+
+        let mut maps = HashMap::new();
+        maps.insert(
+            0,
+            MapDataCollection {
+                maps: HashMap::from([(UVec2::new(0, 1), MapData::default())]),
+            },
+        );
+
+        let layout = overmap_special_layout(&overmaps, &maps);
+
+        assert_eq!(
+            layout,
+            vec![
+                SpecialOvermapTerrain {
+                    position: UVec2::new(0, 1),
+                    z: 0,
+                    om_id: "lab_north".into(),
+                    has_mapgen: true,
+                },
+                SpecialOvermapTerrain {
+                    position: UVec2::new(1, 0),
+                    z: 1,
+                    om_id: "lab_roof_north".into(),
+                    has_mapgen: false,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use crate::data::io::{ChangedEntry, DeserializedCDDAJsonData};
+use crate::events::{EditorEvent, EmitEvent, EventPayload};
+use crate::features::program_data::generations::ProjectGenerations;
+use crate::features::program_data::EditorData;
+use crate::features::viewer::cache::RenderCache;
+use log::{info, warn};
+use notify::RecursiveMode;
+use notify_debouncer_full::{new_debouncer, DebounceEventResult};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, Manager};
+use tokio::task::JoinHandle;
+use tokio_test::block_on;
+
+/// Sent after json files in the CDDA data directory changed and their entries
+/// were reloaded. The parameters of all loaded projects are already
+/// recalculated when it is sent.
+#[derive(Debug, Clone, Serialize)]
+pub struct CDDADataChangedEvent {
+    pub entries: Vec<ChangedEntry>,
+}
+
+impl EventPayload for CDDADataChangedEvent {
+    const EVENT: EditorEvent = EditorEvent::CDDADataChanged;
+}
+
+/// The file watcher of the json directories of the CDDA installation and the
+/// active mods
+#[derive(Debug, Default)]
+pub struct CDDADataWatcher {
+    handle: std::sync::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl CDDADataWatcher {
+    /// Watches the `paths` instead of the previously watched directories
+    pub fn watch(&self, app: AppHandle, paths: Vec<PathBuf>) {
+        let handle = spawn_cdda_data_watcher(app, paths);
+
+        if let Some(old) = self.handle.lock().unwrap().replace(handle) {
+            old.abort();
+        }
+    }
+}
+
+/// Reloads the entries of the json files at `paths` and recalculates the
+/// parameters of every loaded project
+async fn reload_files(
+    app: &AppHandle,
+    paths: &BTreeSet<PathBuf>,
+) -> BTreeSet<ChangedEntry> {
+    let json_data = app.state::<Mutex<Option<DeserializedCDDAJsonData>>>();
+    let editor_data = app.state::<Mutex<EditorData>>();
+    let render_cache = app.state::<RenderCache>();
+    let project_generations = app.state::<ProjectGenerations>();
+
+    let mut json_data_lock = json_data.lock().await;
+    let Some(json_data) = json_data_lock.as_mut() else {
+        return BTreeSet::new();
+    };
+
+    let mut changed = BTreeSet::new();
+
+    for path in paths {
+        match json_data.reload_json_file(path) {
+            Ok(entries) => changed.extend(entries),
+            Err(e) => warn!("Failed to reload {}: {}", path.display(), e),
+        }
+    }
+
+    if changed.is_empty() {
+        return changed;
+    }
+
+    info!("Reloaded {} entries of the CDDA data", changed.len());
+    render_cache.invalidate_all();
+
+    let mut editor_data_lock = editor_data.lock().await;
+
+    for project in editor_data_lock.loaded_projects.values_mut() {
+        project_generations.advance(&project.name);
+
+        for (_, collection) in project.maps.iter_mut() {
+            if let Err(e) = collection.calculate_parameters(&json_data.palettes)
+            {
+                let name = &project.name;
+                warn!("Failed to recalculate parameters of {}: {}", name, e);
+            }
+        }
+    }
+
+    changed
+}
+
+/// Spawns a task which reloads the entries of every json file in the `paths`
+/// which changes, so changes to the CDDA data do not require a restart of the
+/// editor
+fn spawn_cdda_data_watcher(
+    app: AppHandle,
+    paths: Vec<PathBuf>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        info!("Spawning File Watcher for {:?}", paths);
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+
+        let mut debouncer = match new_debouncer(
+            Duration::from_millis(500),
+            None,
+            move |res: DebounceEventResult| {
+                block_on(async { tx.send(res).await.unwrap() });
+            },
+        ) {
+            Ok(d) => d,
+            Err(e) => {
+                warn!("Failed to create file watcher for CDDA data: {}", e);
+                return;
+            },
+        };
+
+        for path in paths.iter() {
+            if let Err(e) = debouncer.watch(path, RecursiveMode::Recursive) {
+                warn!("Failed to watch {}: {}", path.display(), e);
+            }
+        }
+
+        while let Some(res) = rx.recv().await {
+            let changed_paths: BTreeSet<PathBuf> = match res {
+                Ok(events) => events
+                    .iter()
+                    .flat_map(|e| e.paths.iter())
+                    .filter(|p| p.extension().is_some_and(|e| e == "json"))
+                    .cloned()
+                    .collect(),
+                Err(errors) => {
+                    errors
+                        .iter()
+                        .for_each(|e| warn!("File watcher error: {}", e));
+                    continue;
+                },
+            };
+
+            if changed_paths.is_empty() {
+                continue;
+            }
+
+            let changed = reload_files(&app, &changed_paths).await;
+
+            if !changed.is_empty() {
+                app.emit_event(CDDADataChangedEvent {
+                    entries: changed.into_iter().collect(),
+                })
+                .unwrap();
+            }
+        }
+    })
+}
@@ -0,0 +1,73 @@
+use crate::features::program_data::{EditorData, ProjectName, ProjectType};
+use crate::features::watcher::{
+    rescan_project, spawn_project_watcher, ProjectWatchers, WatchedFile,
+};
+use crate::impl_serialize_for_error;
+use serde::{Serialize, Serializer};
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, State};
+use thiserror::Error;
+use tokio::task::JoinHandle;
+
+#[tauri::command]
+pub async fn get_watcher_status(
+    name: ProjectName,
+    project_watchers: State<'_, ProjectWatchers>,
+) -> Result<Vec<WatchedFile>, ()> {
+    Ok(project_watchers.get(&name))
+}
+
+#[derive(Debug, Error)]
+pub enum ResyncProjectError {
+    #[error("No project with name `{0}` is loaded")]
+    ProjectNotFound(ProjectName),
+
+    #[error("Project `{0}` is not a live viewer")]
+    NotALiveViewer(ProjectName),
+}
+
+impl_serialize_for_error!(ResyncProjectError);
+
+/// Scans the watched files of the live viewer `name` again. If the project is
+/// opened, its file watcher is restarted, which reloads the viewer and picks
+/// up files that were deleted and created again while it was running.
+#[tauri::command]
+pub async fn resync_project(
+    name: ProjectName,
+    app: AppHandle,
+    editor_data: State<'_, Mutex<EditorData>>,
+    project_watchers: State<'_, ProjectWatchers>,
+    file_watcher: State<'_, Mutex<Option<JoinHandle<()>>>>,
+) -> Result<Vec<WatchedFile>, ResyncProjectError> {
+    let editor_data_lock = editor_data.lock().await;
+
+    let project = editor_data_lock
+        .loaded_projects
+        .get(&name)
+        .ok_or_else(|| ResyncProjectError::ProjectNotFound(name.clone()))?;
+
+    let paths = match &project.ty {
+        ProjectType::LiveViewer(lvd) => lvd.watched_paths(),
+        ProjectType::MapEditor(_) => {
+            return Err(ResyncProjectError::NotALiveViewer(name));
+        },
+    };
+
+    let files = rescan_project(&app, &project_watchers, &name, &paths);
+
+    if editor_data_lock.opened_project.as_ref() == Some(&name) {
+        let mut file_watcher_lock = file_watcher.lock().await;
+
+        if let Some(watcher) = file_watcher_lock.take() {
+            watcher.abort();
+        }
+
+        file_watcher_lock.replace(spawn_project_watcher(
+            app.clone(),
+            name.clone(),
+            paths,
+        ));
+    }
+
+    Ok(files)
+}
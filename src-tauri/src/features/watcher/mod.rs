@@ -0,0 +1,359 @@
+pub mod cdda_data;
+pub(crate) mod handlers;
+
+use crate::data::io::DeserializedCDDAJsonData;
+use crate::events::{
+    EditorEvent, EmitEvent, EventPayload, UpdateLiveViewerEvent,
+};
+use crate::features::palette::{
+    palette_source_paths, refresh_palette_users, reload_palettes_from_file,
+};
+use crate::features::program_data::generations::ProjectGenerations;
+use crate::features::program_data::{EditorData, ProjectName};
+use crate::features::toast::ToastMessage;
+use crate::features::viewer::cache::RenderCache;
+use crate::features::viewer::handlers::{
+    reload_project_maps, ReloadProjectError,
+};
+use crate::util::{get_json_data, GetCurrentProjectError};
+use cdda_lib::types::CDDAIdentifier;
+use log::{info, warn};
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{
+    new_debouncer, DebounceEventResult, Debouncer, RecommendedCache,
+};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, Manager};
+use tokio::task::JoinHandle;
+use tokio_test::block_on;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status")]
+pub enum WatchedFileStatus {
+    Active,
+    PathMissing,
+    PermissionError { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WatchedFile {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub status: WatchedFileStatus,
+}
+
+/// Sent when a watched file of a live viewer project disappeared, came back
+/// or can not be read anymore
+#[derive(Debug, Clone, Serialize)]
+pub struct WatcherStatusChangedEvent {
+    pub name: ProjectName,
+    pub files: Vec<WatchedFile>,
+}
+
+impl EventPayload for WatcherStatusChangedEvent {
+    const EVENT: EditorEvent = EditorEvent::WatcherStatusChanged;
+}
+
+fn file_status(path: &Path) -> WatchedFileStatus {
+    let error = match fs::File::open(path) {
+        Ok(_) => return WatchedFileStatus::Active,
+        Err(e) => e,
+    };
+
+    match error.kind() {
+        ErrorKind::NotFound => WatchedFileStatus::PathMissing,
+        _ => WatchedFileStatus::PermissionError {
+            message: error.to_string(),
+        },
+    }
+}
+
+/// The status of every watched file of the live viewer projects.
+///
+/// The lock is never held across an await point, so it can be used while any
+/// of the async state locks are held.
+#[derive(Debug, Default)]
+pub struct ProjectWatchers {
+    files: std::sync::Mutex<HashMap<ProjectName, Vec<WatchedFile>>>,
+}
+
+impl ProjectWatchers {
+    pub fn get(&self, name: &ProjectName) -> Vec<WatchedFile> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn remove(&self, name: &ProjectName) {
+        self.files.lock().unwrap().remove(name);
+    }
+
+    /// Checks all `paths` of the project `name` again and returns the new
+    /// status if it changed since the last scan
+    pub fn scan(
+        &self,
+        name: &ProjectName,
+        paths: &[PathBuf],
+    ) -> Option<Vec<WatchedFile>> {
+        let scanned: Vec<WatchedFile> = paths
+            .iter()
+            .map(|path| WatchedFile {
+                path: path.clone(),
+                status: file_status(path),
+            })
+            .collect();
+
+        let mut files = self.files.lock().unwrap();
+
+        if files.get(name) == Some(&scanned) {
+            return None;
+        }
+
+        files.insert(name.clone(), scanned.clone());
+        Some(scanned)
+    }
+}
+
+/// Scans the `paths` of the project `name` and tells the frontend if the
+/// status of any of them changed
+pub fn rescan_project(
+    app: &AppHandle,
+    watchers: &ProjectWatchers,
+    name: &ProjectName,
+    paths: &[PathBuf],
+) -> Vec<WatchedFile> {
+    match watchers.scan(name, paths) {
+        None => watchers.get(name),
+        Some(files) => {
+            app.emit_event(WatcherStatusChangedEvent {
+                name: name.clone(),
+                files: files.clone(),
+            })
+            .unwrap();
+
+            files
+        },
+    }
+}
+
+type ProjectDebouncer = Debouncer<RecommendedWatcher, RecommendedCache>;
+
+/// Watches the parent directories of the `paths` which are not watched yet
+fn watch_parents<'a>(
+    debouncer: &mut ProjectDebouncer,
+    directories: &mut BTreeSet<PathBuf>,
+    paths: impl Iterator<Item = &'a PathBuf>,
+) {
+    for directory in paths.filter_map(|p| p.parent()) {
+        if directories.contains(directory) {
+            continue;
+        }
+
+        match debouncer.watch(directory, RecursiveMode::NonRecursive) {
+            Ok(_) => {
+                directories.insert(directory.to_path_buf());
+            },
+            Err(e) => {
+                warn!("Failed to watch {}: {}", directory.display(), e)
+            },
+        }
+    }
+}
+
+/// The files of the palettes which are used by the mapgen at `paths`
+async fn palette_paths(app: &AppHandle, paths: &[PathBuf]) -> Vec<PathBuf> {
+    let json_data = app.state::<Mutex<Option<DeserializedCDDAJsonData>>>();
+    let json_data_lock = json_data.lock().await;
+
+    match json_data_lock.deref() {
+        None => vec![],
+        Some(json_data) => palette_source_paths(paths, json_data)
+            .into_iter()
+            .filter(|p| !paths.contains(p))
+            .collect(),
+    }
+}
+
+/// Deserializes the palettes in the file at `path` again and recalculates the
+/// parameters of all loaded projects which use them, so only the changed
+/// palettes have to be reloaded instead of all CDDA data
+async fn reload_palettes(app: &AppHandle, path: &Path) {
+    let json_data = app.state::<Mutex<Option<DeserializedCDDAJsonData>>>();
+    let editor_data = app.state::<Mutex<EditorData>>();
+    let render_cache = app.state::<RenderCache>();
+    let project_generations = app.state::<ProjectGenerations>();
+
+    let mut json_data_lock = json_data.lock().await;
+    let Some(json_data) = json_data_lock.as_mut() else {
+        return;
+    };
+
+    let reloaded: HashSet<CDDAIdentifier> =
+        match reload_palettes_from_file(path, json_data) {
+            Ok(ids) => ids.into_iter().collect(),
+            Err(e) => {
+                let path = path.display();
+                warn!("Failed to reload palettes from {}: {}", path, e);
+                return;
+            },
+        };
+
+    info!(
+        "Reloaded {} palettes from {}",
+        reloaded.len(),
+        path.display()
+    );
+
+    let mut editor_data_lock = editor_data.lock().await;
+
+    refresh_palette_users(
+        &mut editor_data_lock,
+        &reloaded,
+        json_data,
+        &render_cache,
+        &project_generations,
+    );
+}
+
+/// Imports the maps of the live viewer `name` again and recalculates their
+/// parameters, so the frontend only has to fetch the new sprites
+pub async fn reload_live_viewer(
+    app: &AppHandle,
+    name: &ProjectName,
+) -> Result<(), ReloadProjectError> {
+    let json_data = app.state::<Mutex<Option<DeserializedCDDAJsonData>>>();
+    let editor_data = app.state::<Mutex<EditorData>>();
+
+    let json_data_lock = json_data.lock().await;
+    let json_data = get_json_data(&json_data_lock)?;
+    let mut editor_data_lock = editor_data.lock().await;
+
+    let project = editor_data_lock
+        .loaded_projects
+        .get_mut(name)
+        .ok_or(GetCurrentProjectError::InvalidProjectName(name.clone()))?;
+
+    reload_project_maps(
+        project,
+        json_data,
+        &app.state::<RenderCache>(),
+        &app.state::<ProjectGenerations>(),
+    )
+    .await
+}
+
+/// Reloads the live viewer `name` and tells the frontend to draw it again
+async fn reload_and_update(app: &AppHandle, name: &ProjectName) {
+    info!("Reloading Project {}", name);
+
+    match reload_live_viewer(app, name).await {
+        Ok(_) => app.emit_event(UpdateLiveViewerEvent).unwrap(),
+        Err(e) => {
+            warn!("Failed to reload project {}: {}", name, e);
+            app.emit_event(ToastMessage::error(e.to_string())).unwrap();
+        },
+    }
+}
+
+/// Spawns a task which reloads the live viewer once and then whenever one of
+/// the mapgen files at `paths` or one of the palette files the mapgen uses
+/// changes.
+///
+/// Editors like vim or VSCode save by writing a temporary file and renaming
+/// it over the original. A watch on the file itself stays on the replaced
+/// file and never fires again, so the parent directories are watched instead
+/// and their events are filtered by the watched paths.
+pub fn spawn_project_watcher(
+    app: AppHandle,
+    name: ProjectName,
+    paths: Vec<PathBuf>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        info!("Spawning File Watcher for Live Viewer {}", name);
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+
+        // Thx -> https://github.com/notify-rs/notify/blob/d7e22791faffb7bd9bd10f031c260ae019d7f474/examples/async_monitor.rs
+        // And -> https://docs.rs/notify-debouncer-full/latest/notify_debouncer_full/
+        let mut debouncer = match new_debouncer(
+            Duration::from_millis(100),
+            None,
+            move |res: DebounceEventResult| {
+                block_on(async { tx.send(res).await.unwrap() });
+            },
+        ) {
+            Ok(d) => d,
+            Err(e) => {
+                warn!("Failed to create file watcher for {}: {}", name, e);
+                return;
+            },
+        };
+
+        let mut directories = BTreeSet::new();
+        let mut palettes = palette_paths(&app, &paths).await;
+        watch_parents(
+            &mut debouncer,
+            &mut directories,
+            paths.iter().chain(palettes.iter()),
+        );
+
+        let watchers = app.state::<ProjectWatchers>();
+        let watched = |palettes: &[PathBuf]| -> Vec<PathBuf> {
+            paths.iter().chain(palettes.iter()).cloned().collect()
+        };
+        rescan_project(&app, &watchers, &name, &watched(&palettes));
+
+        // The files might have changed while the project was not watched
+        reload_and_update(&app, &name).await;
+
+        while let Some(res) = rx.recv().await {
+            let changed: BTreeSet<PathBuf> = match res {
+                Ok(events) => events
+                    .iter()
+                    .flat_map(|e| e.paths.iter())
+                    .filter(|p| paths.contains(p) || palettes.contains(p))
+                    .cloned()
+                    .collect(),
+                Err(errors) => {
+                    errors
+                        .iter()
+                        .for_each(|e| warn!("File watcher error: {}", e));
+                    BTreeSet::new()
+                },
+            };
+
+            for path in changed.iter().filter(|p| palettes.contains(p)) {
+                reload_palettes(&app, path).await;
+            }
+
+            // The mapgen might use different palettes after it changed
+            if changed.iter().any(|p| paths.contains(p)) {
+                palettes = palette_paths(&app, &paths).await;
+                watch_parents(
+                    &mut debouncer,
+                    &mut directories,
+                    palettes.iter(),
+                );
+            }
+
+            rescan_project(&app, &watchers, &name, &watched(&palettes));
+
+            if changed.iter().any(|p| paths.contains(p)) {
+                reload_and_update(&app, &name).await;
+            } else if !changed.is_empty() {
+                // The changed palettes were already reloaded
+                app.emit_event(UpdateLiveViewerEvent).unwrap()
+            }
+        }
+    })
+}
@@ -1,30 +1,96 @@
+pub mod api;
+mod crash;
 mod data;
 mod events;
 mod features;
 mod util;
 
+pub use crate::events::typescript_definitions;
+
+use crate::data::cache::get_cdda_data_cache_path;
 use crate::data::io::{load_cdda_json_data, DeserializedCDDAJsonData};
+use crate::features::analysis::handlers::analyze_map;
+use crate::features::archive::handlers::{archive_project, restore_project};
+use crate::features::catalog::handlers::{
+    get_vanilla_map_catalog, open_vanilla_map, open_vanilla_special,
+};
+use crate::features::connect_groups::handlers::{
+    get_custom_connect_groups, set_custom_connect_groups,
+};
+use crate::features::demo::handlers::open_demo_project;
+use crate::features::demo::DemoJsonData;
+use crate::features::diagnostics::handlers::get_import_diagnostics;
+use crate::features::field::handlers::get_field_decay_preview;
+use crate::features::jobs::handlers::{cancel_job, list_jobs};
+use crate::features::jobs::Jobs;
+use crate::features::legend::handlers::export_map_legend;
+use crate::features::map::handlers::save_mapgen;
+use crate::features::map_editor::clipboard::MapClipboard;
+use crate::features::map_editor::handlers::{
+    copy_region, draw_line, draw_rect, flood_fill, new_map_editor_project,
+    new_place_entry, paint_cells, paste_region, run_edit_script,
+    save_map_editor_project, set_cells,
+};
+use crate::features::mods::handlers::{
+    get_mods, set_mod_active, set_user_mod_paths,
+};
+use crate::features::mods::{cdda_data_paths, opened_project_mods};
+use crate::features::overlay::handlers::{
+    get_item_overlay, get_monster_overlay, get_sightlines,
+    get_transparency_overlay,
+};
+use crate::features::palette::handlers::{
+    create_palette, import_palette_from_file, remove_palette_mapping,
+    save_palette, set_palette_mapping,
+};
 use crate::features::program_data::handlers::{
     cdda_installation_directory_picked, close_project, get_editor_data,
-    open_project, open_recent_project, save_editor_data, tileset_picked,
+    open_project, open_recent_project, reload_tileset, save_editor_data,
+    set_ascii_mode, set_export_target, set_place_defaults, set_season,
+    tileset_picked,
 };
 use crate::features::program_data::{
-    get_map_data_collection_from_live_viewer_data, EditorData, MappedCDDAIdContainer, ProjectType,
-    ZLevel,
+    load_project_maps, EditorData, MappedCDDAIdContainer, ZLevel,
 };
+use crate::features::raw_json::handlers::get_raw_json;
+use crate::features::refactor::handlers::rename_identifier;
+use crate::features::search::handlers::search_projects;
+use crate::features::third_party::handlers::import_third_party_project;
+use crate::features::thumbnail::handlers::get_project_thumbnail;
 use crate::features::tileset::handlers::{
-    download_spritesheet, get_info_of_current_tileset,
+    download_spritesheet, download_texture_atlas_page, get_ascii_colors,
+    get_info_of_current_tileset, get_texture_atlas,
+    get_tileset_validation_report,
 };
 use crate::features::tileset::legacy_tileset::fallback::get_fallback_tilesheet;
 use crate::features::tileset::legacy_tileset::LegacyTilesheet;
+use crate::features::validation::handlers::validate_mapgen;
+use crate::features::vehicle::handlers::{
+    get_vehicle_preview, get_vehicle_prototypes,
+};
+use crate::features::viewer::cache::RenderCache;
+use crate::features::viewer::context::surround_with_neighbors;
 use crate::features::viewer::handlers::{
-    create_viewer, get_calculated_parameters, get_current_project_data,
-    get_project_cell_data, get_sprites, new_nested_mapgen_viewer,
-    new_single_mapgen_viewer, new_special_mapgen_viewer, reload_project,
+    create_viewer, get_active_z_level, get_calculated_parameters,
+    get_cell_inspection, get_cell_probabilities, get_current_project_data,
+    get_mapping_probabilities, get_overmap_special_layout,
+    get_project_cell_data, get_region_settings_ids, get_simulated_neighbors,
+    get_sprites, get_update_mapgen_ids, new_nested_mapgen_viewer,
+    new_single_mapgen_viewer, new_special_mapgen_viewer, pin_sprite_variant,
+    probe_cell, reload_project, reroll_preview, reroll_seed,
+    reroll_sprite_variants, resolve_characters, set_active_z_level,
+    set_camp_upgrades, set_neighbor_context, set_region, set_seed,
+    set_simulated_neighbor, toggle_camp_upgrade,
 };
+use crate::features::watcher::cdda_data::CDDADataWatcher;
+use crate::features::watcher::handlers::{get_watcher_status, resync_project};
+use crate::features::watcher::ProjectWatchers;
 use async_once::AsyncOnce;
 use data::io;
-use features::program_data::{Tab, TabType};
+use events::EmitEvent;
+use features::program_data::changes::EditorDataChanges;
+use features::program_data::generations::ProjectGenerations;
+use features::program_data::Tab;
 use features::tileset::legacy_tileset;
 use features::toast::ToastMessage;
 use lazy_static::lazy_static;
@@ -34,7 +100,7 @@ use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::Arc;
 use tauri::async_runtime::Mutex;
-use tauri::{AppHandle, Emitter, Manager, State};
+use tauri::{AppHandle, Manager, State};
 use tauri_plugin_log::{Target, TargetKind};
 use tokio::task::JoinHandle;
 
@@ -51,9 +117,15 @@ lazy_static! {
             let cdda_json_path = std::env::var("CDDA_JSON_PATH")
                 .unwrap_or("data\\json\\".to_string());
 
-            let json_data = load_cdda_json_data(cdda_path, cdda_json_path)
-                .await
-                .unwrap();
+            let json_data = load_cdda_json_data(
+                cdda_path,
+                cdda_json_path,
+                vec![],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
 
             info!("Successfully Loaded CDDA data");
 
@@ -84,12 +156,14 @@ async fn about() -> AboutInfo {
 #[tauri::command]
 async fn frontend_ready(
     app: AppHandle,
+    jobs: State<'_, Jobs>,
     editor_data: State<'_, Mutex<EditorData>>,
     json_data: State<'_, Mutex<Option<DeserializedCDDAJsonData>>>,
     tilesheet: State<'_, Mutex<Option<LegacyTilesheet>>>,
+    cdda_data_watcher: State<'_, CDDADataWatcher>,
 ) -> Result<(), ()> {
-    let mut editor_data_lock = editor_data.lock().await;
     let mut json_data_lock = json_data.lock().await;
+    let mut editor_data_lock = editor_data.lock().await;
     let mut tilesheet_lock = tilesheet.lock().await;
 
     match json_data_lock.deref() {
@@ -98,15 +172,34 @@ async fn frontend_ready(
                 info!("No CDDA path set, skipping loading CDDA Json Data");
             },
             Some(cdda_path) => {
+                let mods = match opened_project_mods(&editor_data_lock) {
+                    Ok(mods) => mods,
+                    Err(e) => {
+                        warn!("Failed to load the mods of the project {}", e);
+                        vec![]
+                    },
+                };
+
                 info!("trying to load CDDA Json Data");
                 match load_cdda_json_data(
                     cdda_path,
                     &editor_data_lock.config.json_data_path,
+                    mods.clone(),
+                    Some(get_cdda_data_cache_path(&editor_data_lock.config)),
+                    Some(jobs.start(&app, "Loading CDDA data")),
                 )
                 .await
                 {
                     Ok(cdda_json_data) => {
                         json_data_lock.replace(cdda_json_data);
+                        cdda_data_watcher.watch(
+                            app.clone(),
+                            cdda_data_paths(
+                                cdda_path,
+                                &editor_data_lock.config.json_data_path,
+                                &mods,
+                            ),
+                        );
                     },
                     Err(e) => {
                         warn!("Failed to load editor data {}", e);
@@ -123,65 +216,58 @@ async fn frontend_ready(
             for (name, project) in editor_data_lock.loaded_projects.iter_mut() {
                 info!("Loading Project {}", name);
 
-                match &project.ty {
-                    ProjectType::MapEditor(me) => unimplemented!(),
-                    ProjectType::LiveViewer(lvd) => {
-                        info!("Opening Live viewer",);
-
-                        let mut map_data_collection =
-                            match get_map_data_collection_from_live_viewer_data(
-                                lvd,
-                            )
-                            .await
-                            {
-                                Ok(v) => v,
-                                Err(e) => {
-                                    app.emit(
-                                        events::TOAST_MESSAGE,
-                                        ToastMessage::error(e.to_string()),
-                                    )
-                                    .unwrap();
-                                    warn!(
-                                        "Failed to load map data for project {}: {}",
-                                        &project.name, e
-                                    );
-                                    continue;
-                                },
-                            };
-
-                        map_data_collection.iter_mut().for_each(
-                            |(_, m)| match m
-                                .calculate_parameters(&json_data.palettes)
-                            {
-                                Ok(_) => {},
-                                Err(e) => app
-                                    .emit(
-                                        events::TOAST_MESSAGE,
-                                        ToastMessage::error(e.to_string()),
-                                    )
-                                    .unwrap(),
-                            },
-                        );
-
-                        project.maps = map_data_collection;
+                let mut map_data_collection =
+                    match load_project_maps(&project.ty).await {
+                        Ok(v) => v,
+                        Err(e) => {
+                            app.emit_event(ToastMessage::error(e.to_string()))
+                                .unwrap();
+                            warn!(
+                                "Failed to load map data for project {}: {}",
+                                &project.name, e
+                            );
+                            continue;
+                        },
+                    };
 
-                        app.emit(
-                            events::TAB_CREATED,
-                            Tab {
-                                name: project.name.clone(),
-                                tab_type: TabType::LiveViewer,
-                            },
-                        )
-                        .unwrap()
-                    },
+                if let Some(context) = &project.neighbor_context {
+                    if let Err(e) = surround_with_neighbors(
+                        &mut map_data_collection,
+                        context,
+                        json_data,
+                    ) {
+                        app.emit_event(ToastMessage::error(e.to_string()))
+                            .unwrap();
+                    }
                 }
+
+                let project_palettes = project.project_palettes();
+
+                map_data_collection.iter_mut().for_each(|(_, m)| {
+                    m.set_simulated_neighbors(&project.simulated_neighbors);
+                    m.set_project_palettes(&project_palettes);
+
+                    match m.calculate_parameters(&json_data.palettes) {
+                        Ok(_) => {},
+                        Err(e) => app
+                            .emit_event(ToastMessage::error(e.to_string()))
+                            .unwrap(),
+                    }
+                });
+
+                project.maps = map_data_collection;
+
+                app.emit_event(Tab {
+                    name: project.name.clone(),
+                    tab_type: project.ty.tab_type(),
+                })
+                .unwrap()
             }
         },
     }
 
     info!("Sent initial editor data change");
-    app.emit(events::EDITOR_DATA_CHANGED, editor_data_lock.clone())
-        .unwrap();
+    app.emit_event(editor_data_lock.clone()).unwrap();
 
     info!("Loading tilesheet");
     let tilesheet = legacy_tileset::load_tilesheet(&editor_data_lock)
@@ -189,7 +275,17 @@ async fn frontend_ready(
         .map_err(|e| {})?;
     *tilesheet_lock = tilesheet;
 
-    app.emit(events::TILESET_CHANGED, ()).unwrap();
+    legacy_tileset::emit_embedded_fallback_warning(
+        &app,
+        tilesheet_lock.as_ref(),
+    );
+    legacy_tileset::emit_tileset_changed(
+        &app,
+        editor_data_lock.config.selected_tileset.clone(),
+        editor_data_lock
+            .config
+            .rendered_tilesheet(tilesheet_lock.as_ref()),
+    );
 
     Ok(())
 }
@@ -209,15 +305,31 @@ pub fn run() -> () {
             info!("Loading Editor data config");
             let editor_data = io::get_saved_editor_data()?;
 
+            crash::install_panic_hook(
+                app.handle().clone(),
+                editor_data.config.config_path.join("crashes"),
+            );
+
             info!("Getting fallback tilesheet");
             let fallback_tilesheet = get_fallback_tilesheet();
 
             app.manage(Arc::new(fallback_tilesheet));
+            // The state locks have to be acquired in the order the CDDA json
+            // data, the editor data and then everything else, since commands
+            // which lock them the other way around can deadlock each other
             app.manage(Mutex::new(editor_data));
+            app.manage(EditorDataChanges::default());
+            app.manage(Jobs::default());
+            app.manage(RenderCache::default());
+            app.manage(ProjectGenerations::default());
+            app.manage(ProjectWatchers::default());
+            app.manage(CDDADataWatcher::default());
+            app.manage(DemoJsonData::default());
             app.manage::<Mutex<Option<DeserializedCDDAJsonData>>>(Mutex::new(
                 None,
             ));
             app.manage::<Mutex<Option<LegacyTilesheet>>>(Mutex::new(None));
+            app.manage::<Mutex<Option<MapClipboard>>>(Mutex::new(None));
             app.manage::<Mutex<Option<JoinHandle<()>>>>(Mutex::new(None));
             app.manage::<Mutex<Option<HashMap<ZLevel, MappedCDDAIdContainer>>>>(Mutex::new(None));
 
@@ -238,11 +350,89 @@ pub fn run() -> () {
             create_viewer,
             get_sprites,
             reload_project,
+            set_neighbor_context,
+            get_simulated_neighbors,
+            set_simulated_neighbor,
+            save_mapgen,
+            get_vanilla_map_catalog,
+            open_vanilla_map,
+            open_vanilla_special,
+            rename_identifier,
+            paint_cells,
+            set_place_defaults,
+            set_export_target,
+            new_place_entry,
+            draw_line,
+            draw_rect,
+            flood_fill,
+            run_edit_script,
+            copy_region,
+            paste_region,
+            export_map_legend,
+            get_active_z_level,
+            set_active_z_level,
+            get_custom_connect_groups,
+            set_custom_connect_groups,
             new_single_mapgen_viewer,
             new_special_mapgen_viewer,
             new_nested_mapgen_viewer,
             get_calculated_parameters,
             open_recent_project,
+            get_tileset_validation_report,
+            reroll_sprite_variants,
+            pin_sprite_variant,
+            get_transparency_overlay,
+            get_sightlines,
+            get_item_overlay,
+            get_monster_overlay,
+            get_cell_inspection,
+            probe_cell,
+            analyze_map,
+            get_update_mapgen_ids,
+            set_camp_upgrades,
+            toggle_camp_upgrade,
+            get_overmap_special_layout,
+            get_vehicle_prototypes,
+            get_vehicle_preview,
+            get_field_decay_preview,
+            search_projects,
+            import_palette_from_file,
+            create_palette,
+            set_palette_mapping,
+            remove_palette_mapping,
+            save_palette,
+            resolve_characters,
+            get_mapping_probabilities,
+            get_cell_probabilities,
+            get_project_thumbnail,
+            archive_project,
+            restore_project,
+            import_third_party_project,
+            get_watcher_status,
+            resync_project,
+            list_jobs,
+            cancel_job,
+            get_raw_json,
+            new_map_editor_project,
+            open_demo_project,
+            get_mods,
+            set_mod_active,
+            set_user_mod_paths,
+            get_import_diagnostics,
+            validate_mapgen,
+            set_season,
+            get_texture_atlas,
+            download_texture_atlas_page,
+            reload_tileset,
+            set_ascii_mode,
+            get_ascii_colors,
+            set_seed,
+            reroll_seed,
+            reroll_preview,
+            get_region_settings_ids,
+            set_region,
+            set_cells,
+            save_map_editor_project,
             about
         ])
         .run(tauri::generate_context!())
@@ -4,12 +4,11 @@ use crate::features::program_data::{
     EditorData, MapDataCollection, Project, ZLevel,
 };
 use cdda_lib::types::Weighted;
+use cdda_lib::weighted::{choose_weighted, WeightedChoice};
 use derive_more::with_trait::Display;
 use glam::{IVec3, UVec2};
 use indexmap::IndexMap;
-use rand::distr::weighted::WeightedIndex;
-use rand::prelude::Distribution as RandDistribution;
-use rand::rng;
+use rand::{rng, Rng};
 use serde::de::Error as SerdeError;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
@@ -79,7 +78,7 @@ impl<'de> Deserialize<'de> for IVec3JsonKey {
         let s = String::deserialize(deserializer)?;
 
         let parts: Vec<&str> = s.split(',').collect();
-        if parts.len() != 2 {
+        if parts.len() != 3 {
             return Err(de::Error::invalid_value(
                 de::Unexpected::Str(&s),
                 &"a string in the format 'x,y,z'",
@@ -108,6 +107,15 @@ pub trait Load<T, E = anyhow::Error> {
     async fn load(&mut self) -> Result<T, E>;
 }
 
+/// Checks that `name` can be used as the name of a file without pointing
+/// outside of the directory it is joined to
+pub fn is_valid_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && !name.contains("..")
+        && !name.chars().any(|c| c == '/' || c == '\\')
+}
+
 pub fn bresenham_line(x0: i32, y0: i32, x1: i32, y1: i32) -> Vec<(i32, i32)> {
     let mut points = Vec::new();
 
@@ -285,42 +293,29 @@ pub fn get_json_data<'a>(
     }
 }
 
+/// Weighted random choice for collections that are known to contain at least
+/// one entry. If no entry has a positive weight, the first entry is chosen.
 pub trait GetRandom<T> {
-    fn get_random(&self) -> &T;
-}
+    fn get_random_with<R: Rng + ?Sized>(&self, rng: &mut R) -> &T;
 
-impl<T> GetRandom<T> for Vec<Weighted<T>> {
     fn get_random(&self) -> &T {
-        let mut weights = vec![];
-        self.iter().for_each(|v| weights.push(v.weight));
-
-        let weighted_index = WeightedIndex::new(weights).expect("No Error");
-
-        let mut rng = rng();
-        //let mut rng = RANDOM.write().unwrap();
-
-        let chosen_index = weighted_index.sample(&mut rng);
+        self.get_random_with(&mut rng())
+    }
+}
 
-        &self.get(chosen_index).unwrap().data
+impl<T> GetRandom<T> for Vec<Weighted<T>> {
+    fn get_random_with<R: Rng + ?Sized>(&self, rng: &mut R) -> &T {
+        self.choose_weighted(rng)
+            .or_else(|| self.first().map(|w| &w.data))
+            .expect("Weighted list to not be empty")
     }
 }
 
 impl<T> GetRandom<T> for IndexMap<T, i32> {
-    fn get_random(&self) -> &T {
-        let mut weights = vec![];
-
-        let mut vec = self.iter().collect::<Vec<(&T, &i32)>>();
-        vec.iter().for_each(|(_, w)| weights.push(**w));
-
-        let weighted_index = WeightedIndex::new(weights).expect("No Error");
-
-        let mut rng = rng();
-        //let mut rng = RANDOM.write().unwrap();
-
-        let chosen_index = weighted_index.sample(&mut rng);
-        let item = vec.remove(chosen_index);
-
-        &item.0
+    fn get_random_with<R: Rng + ?Sized>(&self, rng: &mut R) -> &T {
+        choose_weighted(self.iter().map(|(v, w)| (v, *w)), rng)
+            .or_else(|| self.keys().next())
+            .expect("Weighted map to not be empty")
     }
 }
 
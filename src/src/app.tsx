@@ -23,6 +23,7 @@ import {clsx} from "clsx";
 import {MultiMenu} from "./shared/components/imguilike/multimenu.js";
 import {Sidemenu} from "./shared/components/imguilike/sidemenu.js";
 import {show} from "@tauri-apps/api/app";
+import {CDDADataLoadProgress} from "./shared/components/cddaDataLoadProgress.js";
 
 export const ThemeContext = createContext<{ theme: Theme }>({
     theme: Theme.Dark,
@@ -63,6 +64,24 @@ function App() {
         })()
     }, []);
 
+    useTauriEvent(
+        TauriEvent.CDDA_DATA_LOAD_PROGRESS,
+        (d) => {
+            const toastId = `cdda-data-load-${d.job}`
+
+            if (d.phase === "Finished") {
+                toast.dismiss(toastId)
+                return
+            }
+
+            toast.loading(
+                <CDDADataLoadProgress progress={d}/>,
+                {id: toastId}
+            )
+        },
+        []
+    )
+
     useTauriEvent(
         TauriEvent.EMIT_TOAST_MESSAGE,
         (d) => {
@@ -73,6 +92,10 @@ function App() {
             if (d.type === ToastType.Success) {
                 toast.success(d.message)
             }
+
+            if (d.type === ToastType.Warning) {
+                toast(d.message, {icon: <Icon name={IconName.InfoMedium}/>})
+            }
         },
         []
     )
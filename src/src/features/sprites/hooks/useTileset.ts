@@ -1,6 +1,6 @@
 import {MutableRefObject, RefObject, useRef} from "react";
 import {Tilesheets} from "../tilesheets.js";
-import {SpritesheetConfig, TileInfo} from "../../../tauri/types/spritesheet.js";
+import {SpritesheetConfig, TextureAtlas, TileInfo} from "../../../tauri/types/spritesheet.js";
 import {useTauriEvent} from "../../../shared/hooks/useTauriEvent.js";
 import {tauriBridge} from "../../../tauri/events/tauriBridge.js";
 import {BackendResponse, BackendResponseType, TauriCommand, TauriEvent} from "../../../tauri/events/types.js";
@@ -19,9 +19,9 @@ export function useTileset(eventBus: RefObject<EventTarget>): UseTilesetRet {
 
     useTauriEvent(
         TauriEvent.TILESET_CHANGED,
-        () => {
+        (e) => {
             (async () => {
-                console.log("Loading Tileset")
+                console.log(`Loading Tileset ${e.tileset ?? "None"}`)
 
                 const infoResponse = await tauriBridge.invoke<
                     SpritesheetConfig,
@@ -37,63 +37,96 @@ export function useTileset(eventBus: RefObject<EventTarget>): UseTilesetRet {
                     return
                 }
 
-                spritesheetConfig.current = infoResponse.data;
+                // The projection of the event belongs to the tilesheet the backend renders the sprites with
+                spritesheetConfig.current = {...infoResponse.data, projection: e.projection};
 
                 const loadFromBackend = async (): Promise<{
                     atlases: { [key: string]: Tilesheet },
                     fallback: Tilesheet,
                     tileInfo: TileInfo
                 }> => {
-                    const downloadPromises: Promise<BackendResponse<ArrayBuffer, unknown>>[] = []
+                    const tileInfo = infoResponse.data.tile_info[0]
+
+                    // The normal spritesheets are stitched into the pages of a texture atlas by the backend, only the
+                    // fallback spritesheet is downloaded on its own
+                    const atlasResponse = await tauriBridge.invoke<
+                        TextureAtlas,
+                        unknown,
+                        TauriCommand.GET_TEXTURE_ATLAS
+                    >(
+                        TauriCommand.GET_TEXTURE_ATLAS,
+                        {}
+                    )
+
+                    if (atlasResponse.type === BackendResponseType.Error) {
+                        console.error(atlasResponse.error)
+                        return
+                    }
+
+                    const pages = atlasResponse.data.pages
+                    const fallbackInfo = infoResponse.data["tiles-new"].find(s => s.is_fallback)
+
+                    const downloadPromises: Promise<BackendResponse<ArrayBuffer, unknown>>[] = pages.map((_, page) => {
+                        console.log(`Loading texture atlas page ${page}`)
 
-                    for (let tileInfo of infoResponse.data["tiles-new"]) {
-                        console.log(`Loading ${tileInfo.file}`)
+                        return tauriBridge.invoke<
+                            ArrayBuffer,
+                            unknown,
+                            TauriCommand.DOWNLOAD_TEXTURE_ATLAS_PAGE
+                        >(
+                            TauriCommand.DOWNLOAD_TEXTURE_ATLAS_PAGE,
+                            {page},
+                        )
+                    })
 
-                        const promise = tauriBridge.invoke<
+                    downloadPromises.push(
+                        tauriBridge.invoke<
                             ArrayBuffer,
                             unknown,
                             TauriCommand.DOWNLOAD_SPRITESHEET
                         >(
                             TauriCommand.DOWNLOAD_SPRITESHEET,
-                            {name: tileInfo.file},
+                            {name: fallbackInfo.file},
                         )
-
-                        downloadPromises.push(promise)
-                    }
+                    )
 
                     const arrayBuffs = await Promise.all(downloadPromises)
                     const atlases = {}
-                    let fallback: Tilesheet;
 
                     storedObjectURLS.current.forEach(url => URL.revokeObjectURL(url))
 
-                    for (let i = 0; i < infoResponse.data["tiles-new"].length; i++) {
-                        const response = arrayBuffs[i]
-
+                    const urls: string[] = []
+                    for (const response of arrayBuffs) {
                         if (response.type === BackendResponseType.Error) {
                             console.log(`Failed to load Tileset ${response.error}`)
                             return
                         }
 
-                        const spritesheetInfo = infoResponse.data["tiles-new"][i]
-
                         const blob = new Blob([response.data], {type: "image/png"});
                         const url = URL.createObjectURL(blob)
                         storedObjectURLS.current.push(url)
+                        urls.push(url)
+                    }
 
-                        if (spritesheetInfo.file === "fallback.png") {
-                            fallback = await Tilesheet.fromURL(url, infoResponse.data.tile_info[0], spritesheetInfo)
-                            continue
-                        }
+                    for (let i = 0; i < pages.length; i++) {
+                        const page = pages[i]
 
-                        atlases[spritesheetInfo.file] = await Tilesheet.fromURL(
-                            url,
-                            infoResponse.data.tile_info[0],
-                            spritesheetInfo
+                        atlases[`atlas_${i}`] = await Tilesheet.fromURL(
+                            urls[i],
+                            page.sprite_width,
+                            page.sprite_height,
+                            page.ranges
                         )
                     }
 
-                    return {atlases, fallback, tileInfo: infoResponse.data.tile_info[0]}
+                    const fallback = await Tilesheet.fromURL(
+                        urls[pages.length],
+                        fallbackInfo.sprite_width || tileInfo.width,
+                        fallbackInfo.sprite_height || tileInfo.height,
+                        []
+                    )
+
+                    return {atlases, fallback, tileInfo}
                 }
 
                 console.log("Loading Tilesheet Sprites")
@@ -0,0 +1,38 @@
+import {Vector2} from "three";
+import {Projection} from "../../tauri/types/spritesheet.js";
+
+// Returns the pixel position of the tile at the cell `position` where the y axis points down
+export function projectTile(projection: Projection, position: Vector2): Vector2 {
+    if (!projection.iso) {
+        return new Vector2(position.x * projection.tile_width, position.y * projection.tile_height)
+    }
+
+    // Isometric tiles are diamonds which are half as tall as they are wide
+    return new Vector2(
+        (position.x + position.y) * projection.tile_width / 2,
+        (position.y - position.x) * projection.tile_width / 4
+    )
+}
+
+// Returns the cell whose tile contains the three.js world `position`
+export function unprojectTile(projection: Projection, position: Vector2): Vector2 {
+    const width = projection.tile_width
+    const height = projection.tile_height
+
+    if (!projection.iso) {
+        // The top left tile starts at -1 in three.js since its y axis points up
+        return new Vector2(
+            Math.floor(position.x / width + 0.5),
+            -Math.floor(position.y / height + 0.5) - 1
+        )
+    }
+
+    // The diamond of an isometric tile sits at the bottom of its sprite
+    const diagonal = position.x / (width / 2)
+    const antiDiagonal = -(position.y + height * 1.5 - width / 4) / (width / 4)
+
+    return new Vector2(
+        Math.round((diagonal - antiDiagonal) / 2),
+        Math.round((diagonal + antiDiagonal) / 2)
+    )
+}
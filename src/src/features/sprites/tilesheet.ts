@@ -10,7 +10,7 @@ import {
     Vector2,
     Vector3
 } from "three";
-import {TileInfo, TileNew} from "../../tauri/types/spritesheet.js";
+import {AtlasRange} from "../../tauri/types/spritesheet.js";
 
 export type InstanceNumber = number;
 
@@ -27,28 +27,23 @@ export type DrawLocalSprite = {
 }
 
 export class Tilesheet {
-    public readonly range: [number, number] | null
+    // The sprite indices in this tilesheet and the cells they are stored in. Tilesheets without ranges, like the
+    // fallback tilesheet, store every sprite in the cell of its index
+    public readonly ranges: AtlasRange[]
     public readonly material: AtlasMaterial
     public readonly yLayer: number
-    public readonly spritesheetInfo: TileNew
     public mappedTiles: Map<string, InstanceNumber>
     public mesh: InstancedMesh
     private atlasConfig: AtlasMaterialConfig
 
     constructor(
         texture: Texture,
-        tilesetInfo: TileInfo,
-        spritesheetInfo: TileNew
+        tileWidth: number,
+        tileHeight: number,
+        ranges: AtlasRange[]
     ) {
         const maxInstances = 200_000
 
-        const tileWidth = spritesheetInfo.sprite_width || tilesetInfo.width
-        const tileHeight = spritesheetInfo.sprite_height || tilesetInfo.height
-
-        let range: [number, number];
-        if (spritesheetInfo["//"]) range = spritesheetInfo["//"]
-        else range = null
-
         const atlasMaterialConfig = {
             tileWidth: tileWidth,
             tileHeight: tileHeight,
@@ -57,13 +52,12 @@ export class Tilesheet {
             maxInstances
         }
 
-        this.range = range
+        this.ranges = ranges
         this.yLayer = 0
         this.material = new AtlasMaterial(
             texture,
             atlasMaterialConfig
         )
-        this.spritesheetInfo = spritesheetInfo
         this.atlasConfig = atlasMaterialConfig
         this.mesh = new InstancedMesh(
             this.material.geometry,
@@ -85,8 +79,9 @@ export class Tilesheet {
 
     public static async fromURL(
         url: string,
-        tilesetInfo: TileInfo,
-        spritesheetInfo: TileNew
+        tileWidth: number,
+        tileHeight: number,
+        ranges: AtlasRange[]
     ): Promise<Tilesheet> {
         const texture = await new TextureLoader()
             .loadAsync(url, () => console.log(`Loading ${url}`))
@@ -98,14 +93,24 @@ export class Tilesheet {
 
         return new Tilesheet(
             texture,
-            tilesetInfo,
-            spritesheetInfo
+            tileWidth,
+            tileHeight,
+            ranges
         )
     }
 
     public isWithinRange(index: number): boolean {
-        if (!this.range) return false
-        return index >= this.range[0] && index <= this.range[1]
+        return this.ranges.some(r => index >= r.range[0] && index <= r.range[1])
+    }
+
+    // Returns the cell of the sprite with the `index` in the texture of this tilesheet
+    public localIndex(index: number): number {
+        if (this.ranges.length === 0) return index
+
+        const range = this.ranges.find(r => index >= r.range[0] && index <= r.range[1])
+        if (!range) return index
+
+        return range.first_cell + index - range.range[0]
     }
 
     public drawSpriteLocalIndexBatched(drawLocalSprites: DrawLocalSprite[]) {
@@ -132,7 +137,7 @@ export class Tilesheet {
 
             transform.position.set(
                 drawSprite.position.x,
-                drawSprite.position.y - (this.spritesheetInfo.sprite_offset_y || 0) / 2,
+                drawSprite.position.y,
                 drawSprite.position.z
             )
             transform.updateMatrix()
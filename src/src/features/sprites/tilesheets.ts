@@ -3,9 +3,9 @@ import {Vector2, Vector3} from "three";
 import {TileInfo} from "../../tauri/types/spritesheet.js";
 import {RefObject} from "react";
 import {ThreeConfig} from "../three/types/three.js";
+import {SpritesheetPlacement} from "../../tauri/types/map_data.js";
 
 export const MAX_DEPTH = 999997
-const MAX_ROW = 1000
 const ANIMATION_FRAME_DURATION = 50
 
 export type DrawStaticSprite = {
@@ -14,6 +14,8 @@ export type DrawStaticSprite = {
     layer: number
     rotate_deg: number
     z: number
+    depth: number
+    sheet: SpritesheetPlacement | null
 }
 
 export type DrawAnimatedSprite = {
@@ -22,6 +24,8 @@ export type DrawAnimatedSprite = {
     layer: number
     rotate_deg: number
     z: number
+    depth: number
+    sheet: SpritesheetPlacement | null
 }
 
 type SavedAnimatedSprite = DrawAnimatedSprite & {
@@ -69,16 +73,20 @@ export class Tilesheets {
             let nextFrame = animatedSprite.currentFrame + 1;
             if (nextFrame >= animatedSprite.indices.length) nextFrame = 0
 
-            for (let k of Object.keys(this.tilesheets)) {
-                const tilesheet = this.tilesheets[k]
-                if (!tilesheet.isWithinRange(animatedSprite.indices[nextFrame])) continue
+            const index = animatedSprite.indices[animatedSprite.currentFrame]
+            const found = this.findTilesheet(index, animatedSprite.sheet)
+
+            if (found) {
+                const [k, sheet] = found
 
                 const drawLocalSprite = this.getLocalDrawSprite(
-                    animatedSprite.indices[animatedSprite.currentFrame],
+                    index,
                     animatedSprite.position,
+                    animatedSprite.depth,
                     animatedSprite.layer,
-                    tilesheet,
-                    animatedSprite.rotate_deg
+                    this.tilesheets[k],
+                    animatedSprite.rotate_deg,
+                    sheet
                 )
 
                 if (!batches[animatedSprite.z]) batches[animatedSprite.z] = {}
@@ -87,8 +95,6 @@ export class Tilesheets {
                 }
 
                 batches[animatedSprite.z][k].draw.push(drawLocalSprite)
-
-                break
             }
 
             animatedSprite.framesSinceLastDraw = 0
@@ -148,25 +154,24 @@ export class Tilesheets {
 
         for (const staticSprite of staticSprites) {
             const index = staticSprite.index
+            const found = this.findTilesheet(index, staticSprite.sheet)
+            if (!found) continue
 
-            for (let k of Object.keys(this.tilesheets)) {
-                const tilesheet = this.tilesheets[k]
-                if (!tilesheet.isWithinRange(index)) continue
+            const [k, sheet] = found
 
-                const drawLocalSprite = this.getLocalDrawSprite(
-                    index,
-                    staticSprite.position,
-                    staticSprite.layer,
-                    tilesheet,
-                    staticSprite.rotate_deg
-                )
-
-                if (!batches[staticSprite.z]) batches[staticSprite.z] = {}
-                if (!batches[staticSprite.z][k]) batches[staticSprite.z][k] = []
-                batches[staticSprite.z][k].push(drawLocalSprite)
+            const drawLocalSprite = this.getLocalDrawSprite(
+                index,
+                staticSprite.position,
+                staticSprite.depth,
+                staticSprite.layer,
+                this.tilesheets[k],
+                staticSprite.rotate_deg,
+                sheet
+            )
 
-                break
-            }
+            if (!batches[staticSprite.z]) batches[staticSprite.z] = {}
+            if (!batches[staticSprite.z][k]) batches[staticSprite.z][k] = []
+            batches[staticSprite.z][k].push(drawLocalSprite)
         }
 
         this.cachedStaticBatches = batches
@@ -189,9 +194,11 @@ export class Tilesheets {
             const drawLocalSprite = this.getLocalDrawSprite(
                 index,
                 drawSprite.position,
+                drawSprite.depth,
                 drawSprite.layer,
                 this.fallback,
-                drawSprite.rotate_deg
+                drawSprite.rotate_deg,
+                null
             )
 
             if (!batches[drawSprite.z]) batches[drawSprite.z] = []
@@ -227,27 +234,45 @@ export class Tilesheets {
         this.animatedSprites = []
     }
 
+    // Returns the name of the tilesheet which contains the sprite `index` and the placement of its spritesheet.
+    // Sprites which are not in the range of their placement, like the later frames of an animation, are drawn
+    // without an offset
+    private findTilesheet(
+        index: number,
+        sheet: SpritesheetPlacement | null
+    ): [string, SpritesheetPlacement | null] | null {
+        const placement = sheet && index >= sheet.range[0] && index <= sheet.range[1] ? sheet : null
+
+        for (let k of Object.keys(this.tilesheets)) {
+            if (this.tilesheets[k].isWithinRange(index)) return [k, placement]
+        }
+
+        return null
+    }
+
     private getLocalDrawSprite(
         index: number,
         position: Vector2,
+        depth: number,
         layer: number,
         tilesheet: Tilesheet,
-        rotation: number
+        rotation: number,
+        sheet: SpritesheetPlacement | null
     ): DrawLocalSprite {
-        const worldY = position.y / this.tileInfo.width
-        const worldX = position.x / this.tileInfo.height
+        const offsetX = sheet ? sheet.center_offset_x : 0
+        const offsetY = sheet ? sheet.center_offset_y : 0
 
         // Since the three.js world goes from down to up and our cdda map goes from up to down, we need to invert the
-        // cell y position
+        // cell y position and the offset of the sprite
         const newPosition = new Vector3(
-            position.x,
-            -position.y - this.tileInfo.height,
-            // + 1 to always add an offset because if we didn't, a few sprites would not show up
-            (MAX_ROW * (worldY + 1)) + worldX + layer
+            position.x + offsetX,
+            -position.y - this.tileInfo.height - offsetY,
+            // The depth starts one row in because a few sprites would not show up otherwise
+            depth + layer
         )
 
         return {
-            index: index - (tilesheet.range ? tilesheet.range[0] : 0),
+            index: tilesheet.localIndex(index),
             layer: layer,
             position: newPosition,
             rotation
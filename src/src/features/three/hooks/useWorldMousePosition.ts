@@ -1,8 +1,9 @@
 import {MutableRefObject, RefObject, useEffect, useRef} from "react";
-import {Vector3} from "three";
+import {Vector2, Vector3} from "three";
 import {Canvas, ThreeConfig} from "../types/three.js";
 import {useMousePosition} from "../../../shared/hooks/useMousePosition.js";
 import {SpritesheetConfig} from "../../../tauri/types/spritesheet.js";
+import {unprojectTile} from "../../sprites/projection.js";
 
 export type UseWorldMousePositionProps = {
     spritesheetConfig: RefObject<SpritesheetConfig>
@@ -18,24 +19,19 @@ export function useWorldMousePosition(props: UseWorldMousePositionProps): RefObj
 
     useEffect(() => {
         function onMouseMove() {
-            const tileInfo = props.spritesheetConfig.current.tile_info[0]
-
             const rect = props.threeConfig.current.renderer.domElement.getBoundingClientRect();
             const mouseNormalized = new Vector3();
             mouseNormalized.x = ((mousePosition.current.x - rect.left) / (rect.right - rect.left)) * 2 - 1;
             mouseNormalized.y = -((mousePosition.current.y - rect.top) / (rect.bottom - rect.top)) * 2 + 1;
             mouseNormalized.z = 0
 
-            const offset = new Vector3(0.5, 0.5, 0)
-
-            const newWorldMousePosition = mouseNormalized.unproject(props.threeConfig.current.camera)
-                .divide(new Vector3(tileInfo.width, tileInfo.height, 1))
-                .add(offset)
-                .floor()
+            const worldPosition = mouseNormalized.unproject(props.threeConfig.current.camera)
+            const cell = unprojectTile(
+                props.spritesheetConfig.current.projection,
+                new Vector2(worldPosition.x, worldPosition.y)
+            )
 
-            // We need to invert the world mouse position since the cdda map goes from up to down
-            // Additionally, we need to remove 1 since the top left tile starts at +1
-            newWorldMousePosition.y = -newWorldMousePosition.y - 1
+            const newWorldMousePosition = new Vector3(cell.x, cell.y, 0)
 
             if (!newWorldMousePosition.equals(worldMousePosition.current)) {
                 if (props.onWorldMousePositionChange) props.onWorldMousePositionChange(newWorldMousePosition)
@@ -10,10 +10,11 @@ import {
     TilesetLoadedEvent
 } from "../../../shared/utils/localEvent.js";
 import {getColorFromTheme, Theme} from "../../../shared/hooks/useTheme.js";
-import {GridHelper, Vector3} from "three";
+import {GridHelper, Vector2, Vector3} from "three";
 import {degToRad} from "three/src/math/MathUtils.js";
 import {SpritesheetConfig, TileInfo} from "../../../tauri/types/spritesheet.js";
 import {DrawAnimatedSprite, DrawStaticSprite, MAX_DEPTH, Tilesheets} from "../../sprites/tilesheets.js";
+import {projectTile} from "../../sprites/projection.js";
 import {SidebarContent, TabContext, ThemeContext} from "../../../app.js";
 import {useTauriEvent} from "../../../shared/hooks/useTauriEvent.js";
 import {
@@ -32,6 +33,8 @@ import {clsx} from "clsx";
 import toast from "react-hot-toast";
 import {CellData} from "../../../tauri/types/map_data.js";
 import {Accordion} from "../../../shared/components/imguilike/accordion.js";
+import {SpecialOvermapTerrain} from "../../../tauri/types/viewer.js";
+import {useSpecialLayout} from "../hooks/useSpecialLayout.js";
 
 type CalculatedParametersTabProps = {
     calculatedParameters: RefObject<CalculatedParameters>
@@ -120,6 +123,7 @@ export function MapViewer(props: MapViewerProps) {
         selectedCellMeshRef,
         regenerate
     } = useMouseCells(props.threeConfig, props.spritesheetConfig)
+    const {setSpecialLayout, drawSpecialLayout} = useSpecialLayout(props.threeConfig, props.spritesheetConfig)
     const [selectedCellPosition, setSelectedCellPosition] = useState<Vector3 | null>(null)
     const [isLoading, setIsLoading] = useState<boolean>(false)
     const zLevel = useRef<number>(0)
@@ -139,12 +143,13 @@ export function MapViewer(props: MapViewerProps) {
             if (!hoveredCellMeshRef.current) return;
             if (!props.spritesheetConfig.current?.tile_info[0]) return;
 
-            const tileInfo = props.spritesheetConfig.current.tile_info[0]
+            const projection = props.spritesheetConfig.current.projection
+            const position = projectTile(projection, new Vector2(mousePosition.x, mousePosition.y))
 
             hoveredCellMeshRef.current.position.set(
-                mousePosition.x * tileInfo.width,
+                position.x,
                 // Remove one again for three.js since the top left tile is -1 in three.js
-                (-mousePosition.y - 1) * tileInfo.height,
+                -position.y - projection.tile_height,
                 MAX_DEPTH + 1
             )
         }
@@ -188,14 +193,7 @@ export function MapViewer(props: MapViewerProps) {
 
         props.tilesheets.current.clearAll()
 
-        const reloadResponse = await tauriBridge.invoke<unknown, string, TauriCommand.RELOAD_PROJECT>(TauriCommand.RELOAD_PROJECT, {})
-
-        if (reloadResponse.type === BackendResponseType.Error) {
-            toast.error(reloadResponse.error)
-            setIsLoading(false)
-            return
-        }
-
+        // The backend already imported the changed files again before it sent the update
         const getSpritesResponse = await tauriBridge.invoke<unknown, string, TauriCommand.GET_SPRITES>(TauriCommand.GET_SPRITES, {name: tabs.openedTab});
 
         if (getSpritesResponse.type === BackendResponseType.Error) {
@@ -224,6 +222,17 @@ export function MapViewer(props: MapViewerProps) {
 
         calculatedParameters.current = getCalculatedParametersResponse.data
 
+        const getSpecialLayoutResponse = await tauriBridge.invoke<SpecialOvermapTerrain[], string, TauriCommand.GET_OVERMAP_SPECIAL_LAYOUT>(TauriCommand.GET_OVERMAP_SPECIAL_LAYOUT, {})
+
+        if (getSpecialLayoutResponse.type === BackendResponseType.Error) {
+            toast.error(getSpecialLayoutResponse.error)
+            setIsLoading(false)
+            return
+        }
+
+        // Only the viewers of overmap specials have overmap terrains to outline
+        setSpecialLayout(getSpecialLayoutResponse.data, zLevel.current, theme.theme)
+
         props.setSidebarContent(
             (c) => {
                 return {
@@ -248,40 +257,34 @@ export function MapViewer(props: MapViewerProps) {
             console.log("Placing sprites")
             props.tilesheets.current.clearAll()
 
-            const tileInfo = props.spritesheetConfig.current.tile_info[0]
+            const projection = props.spritesheetConfig.current.projection
 
             const drawStaticSprites: DrawStaticSprite[] = d.static_sprites.map(ds => {
-                const vec2 = serializedVec2ToVector2(ds.position)
-                vec2.x *= tileInfo.width;
-                vec2.y *= tileInfo.height;
-
                 return {
                     ...ds,
-                    position: vec2
+                    position: projectTile(projection, serializedVec2ToVector2(ds.position)),
+                    depth: ds.draw_order.depth,
+                    sheet: ds.sheet === null ? null : d.spritesheets[ds.sheet]
                 }
             })
 
             const drawAnimatedSprites: DrawAnimatedSprite[] = d.animated_sprites.map(ds => {
-                const vec2 = serializedVec2ToVector2(ds.position)
-                vec2.x *= tileInfo.width;
-                vec2.y *= tileInfo.height;
-
                 return {
                     ...ds,
-                    position: vec2,
+                    position: projectTile(projection, serializedVec2ToVector2(ds.position)),
+                    depth: ds.draw_order.depth,
+                    sheet: ds.sheet === null ? null : d.spritesheets[ds.sheet]
                 }
             })
 
             const drawFallbackSprites: DrawStaticSprite[] = d.fallback_sprites.map(ds => {
-                const vec2 = serializedVec2ToVector2(ds.position)
-                vec2.x *= tileInfo.width;
-                vec2.y *= tileInfo.height;
-
                 return {
                     ...ds,
                     layer: 0,
-                    position: vec2,
-                    rotate_deg: 0
+                    position: projectTile(projection, serializedVec2ToVector2(ds.position)),
+                    depth: ds.draw_order.depth,
+                    rotate_deg: 0,
+                    sheet: null
                 }
             })
 
@@ -298,6 +301,18 @@ export function MapViewer(props: MapViewerProps) {
         [tabs.openedTab]
     )
 
+    useTauriEvent(
+        TauriEvent.CDDA_DATA_CHANGED,
+        updateLiveViewer,
+        [tabs.openedTab]
+    )
+
+    useTauriEvent(
+        TauriEvent.MODS_LOADED,
+        updateLiveViewer,
+        [tabs.openedTab]
+    )
+
     useEffect(() => {
         if (!props.showGrid) {
             if (!props.threeConfig.current.gridHelper) return
@@ -315,6 +330,7 @@ export function MapViewer(props: MapViewerProps) {
     useEffect(() => {
         const closeLocalTabHandler = async (t: CloseLocalTabEvent) => {
             props.tilesheets.current.clearAll()
+            setSpecialLayout([], zLevel.current, theme.theme)
         }
 
         const tilesetLoadedHandler = (e: TilesetLoadedEvent) => {
@@ -349,6 +365,7 @@ export function MapViewer(props: MapViewerProps) {
                     const tileInfo = props.spritesheetConfig.current.tile_info[0]
 
                     setupSceneData(tileInfo, theme.theme)
+                    drawSpecialLayout(zLevel.current, theme.theme)
                 })()
             }
         }
@@ -359,12 +376,14 @@ export function MapViewer(props: MapViewerProps) {
 
             console.log(`Changing Map viewer theme to ${e.detail.theme}`)
             setupSceneData(tileInfo, e.detail.theme)
+            drawSpecialLayout(zLevel.current, e.detail.theme)
         }
 
         const keydownHandler = (e: KeyboardEvent) => {
             if (e.key === "PageUp") {
                 zLevel.current += 1
                 props.tilesheets.current.switchZLevel(zLevel.current)
+                drawSpecialLayout(zLevel.current, theme.theme)
                 props.setSidebarContent(
                     (c) => {
                         return {
@@ -385,6 +404,7 @@ export function MapViewer(props: MapViewerProps) {
             } else if (e.key === "PageDown") {
                 zLevel.current -= 1
                 props.tilesheets.current.switchZLevel(zLevel.current)
+                drawSpecialLayout(zLevel.current, theme.theme)
                 props.setSidebarContent(
                     (c) => {
                         return {
@@ -506,7 +526,7 @@ export function MapViewer(props: MapViewerProps) {
 
     useEffect(() => {
         const onMouseDown = async (e: MouseEvent) => {
-            const tileInfo = props.spritesheetConfig.current.tile_info[0]
+            const projection = props.spritesheetConfig.current.projection
 
             if (e.button === 0) {
                 if (selectedCellPosition?.x === worldMousePosition.current.x && selectedCellPosition?.y === worldMousePosition.current.y) {
@@ -520,9 +540,14 @@ export function MapViewer(props: MapViewerProps) {
                         )
                     )
                 } else {
+                    const position = projectTile(
+                        projection,
+                        new Vector2(worldMousePosition.current.x, worldMousePosition.current.y)
+                    )
+
                     selectedCellMeshRef.current.position.set(
-                        worldMousePosition.current.x * tileInfo.width,
-                        (-worldMousePosition.current.y - 1) * tileInfo.height,
+                        position.x,
+                        -position.y - projection.tile_height,
                         MAX_DEPTH + 1
                     )
                     selectedCellMeshRef.current.visible = true
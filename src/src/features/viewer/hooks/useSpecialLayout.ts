@@ -0,0 +1,132 @@
+import {
+    BufferGeometry,
+    CanvasTexture,
+    Float32BufferAttribute,
+    Group,
+    LineBasicMaterial,
+    LineSegments,
+    Sprite,
+    SpriteMaterial,
+    Vector2
+} from "three";
+import {RefObject, useRef} from "react";
+import {ThreeConfig} from "../../three/types/three.js";
+import {SpritesheetConfig} from "../../../tauri/types/spritesheet.js";
+import {SpecialOvermapTerrain} from "../../../tauri/types/viewer.js";
+import {MAX_DEPTH} from "../../sprites/tilesheets.js";
+import {projectTile} from "../../sprites/projection.js";
+import {getColorFromTheme, Theme} from "../../../shared/hooks/useTheme.js";
+
+// The width and height of an overmap terrain in cells
+const OMT_SIZE = 24
+const LABEL_FONT_SIZE = 48
+const LABEL_PADDING = 8
+
+// Outlines and labels every overmap terrain of the overmap special which is shown in a special viewer
+export function useSpecialLayout(
+    threeConfig: RefObject<ThreeConfig>,
+    spritesheetConfig: RefObject<SpritesheetConfig>,
+) {
+    const layoutRef = useRef<SpecialOvermapTerrain[]>([])
+    const groupRef = useRef<Group>(null)
+
+    function clear() {
+        if (!groupRef.current) return
+
+        threeConfig.current.scene.remove(groupRef.current)
+
+        groupRef.current.traverse(object => {
+            if (object instanceof LineSegments) {
+                const material = object.material as LineBasicMaterial
+
+                object.geometry.dispose()
+                material.dispose()
+            } else if (object instanceof Sprite) {
+                object.material.map?.dispose()
+                object.material.dispose()
+            }
+        })
+
+        groupRef.current = null
+    }
+
+    // Returns the three.js position of the top left corner of the cell at `position`
+    function cellCorner(position: Vector2): Vector2 {
+        const projection = spritesheetConfig.current.projection
+        const corner = projectTile(projection, new Vector2(position.x - 0.5, position.y - 0.5))
+
+        return new Vector2(corner.x, -corner.y - projection.tile_height)
+    }
+
+    function createLabel(text: string, color: string): Sprite {
+        const canvas = document.createElement("canvas")
+        const context = canvas.getContext("2d")
+        const font = `${LABEL_FONT_SIZE}px sans-serif`
+
+        context.font = font
+        canvas.width = Math.ceil(context.measureText(text).width)
+        canvas.height = Math.ceil(LABEL_FONT_SIZE * 1.25)
+
+        // Resizing the canvas resets its context
+        context.font = font
+        context.fillStyle = color
+        context.textBaseline = "top"
+        context.fillText(text, 0, 0)
+
+        const material = new SpriteMaterial({map: new CanvasTexture(canvas), transparent: true})
+        const label = new Sprite(material)
+        label.scale.set(canvas.width, canvas.height, 1)
+        // Sprites are centered on their position by default, the label should start at its position instead
+        label.center.set(0, 1)
+
+        return label
+    }
+
+    function drawSpecialLayout(zLevel: number, theme: Theme) {
+        clear()
+
+        const terrains = layoutRef.current.filter(t => t.z === zLevel)
+        if (terrains.length === 0) return
+
+        const group = new Group()
+        const vertices: number[] = []
+        const depth = MAX_DEPTH + 1
+
+        for (const terrain of terrains) {
+            const [x, y] = terrain.position
+            const corners = [[0, 0], [1, 0], [1, 1], [0, 1]].map(([dx, dy]) => {
+                return cellCorner(new Vector2((x + dx) * OMT_SIZE, (y + dy) * OMT_SIZE))
+            })
+
+            for (let i = 0; i < corners.length; i++) {
+                const from = corners[i]
+                const to = corners[(i + 1) % corners.length]
+
+                vertices.push(from.x, from.y, depth, to.x, to.y, depth)
+            }
+
+            const label = createLabel(
+                terrain.hasMapgen ? terrain.omId : `${terrain.omId} (no mapgen)`,
+                getColorFromTheme(theme, terrain.hasMapgen ? "lightest" : "disabled")
+            )
+            label.position.set(corners[0].x + LABEL_PADDING, corners[0].y - LABEL_PADDING, depth)
+            group.add(label)
+        }
+
+        const geometry = new BufferGeometry()
+        geometry.setAttribute("position", new Float32BufferAttribute(vertices, 3))
+
+        const material = new LineBasicMaterial({color: getColorFromTheme(theme, "lightBlue")})
+        group.add(new LineSegments(geometry, material))
+
+        threeConfig.current.scene.add(group)
+        groupRef.current = group
+    }
+
+    function setSpecialLayout(layout: SpecialOvermapTerrain[], zLevel: number, theme: Theme) {
+        layoutRef.current = layout
+        drawSpecialLayout(zLevel, theme)
+    }
+
+    return {setSpecialLayout, drawSpecialLayout}
+}
@@ -0,0 +1,48 @@
+import React from "react";
+import "./cddaDataLoadProgress.scss"
+import {CDDADataLoadProgressEvent} from "../../tauri/types/editor.js";
+import {tauriBridge} from "../../tauri/events/tauriBridge.js";
+import {TauriCommand} from "../../tauri/events/types.js";
+
+export type CDDADataLoadProgressProps = {
+    progress: CDDADataLoadProgressEvent
+}
+
+function getFraction(progress: CDDADataLoadProgressEvent): number | null {
+    if (progress.phase === "Parsing")
+        return progress.files_parsed / Math.max(progress.files_discovered, 1)
+
+    if (progress.phase === "Flattening")
+        return progress.objects_flattened / Math.max(progress.objects_total, 1)
+
+    return null
+}
+
+function getDescription(progress: CDDADataLoadProgressEvent): string {
+    switch (progress.phase) {
+        case "Discovering":
+            return `Searching CDDA data, found ${progress.files_discovered} files`
+        case "Parsing":
+            return `Reading file ${progress.files_parsed} of ${progress.files_discovered}`
+        case "Flattening":
+            return `Resolving object ${progress.objects_flattened} of ${progress.objects_total}`
+        case "Finished":
+            return "Finished loading CDDA data"
+    }
+}
+
+export function CDDADataLoadProgress({progress}: CDDADataLoadProgressProps) {
+    const fraction = getFraction(progress)
+
+    async function cancel() {
+        await tauriBridge.invoke(TauriCommand.CANCEL_JOB, {id: progress.job})
+    }
+
+    return (
+        <div className={"cdda-data-load-progress"}>
+            <p>{getDescription(progress)}</p>
+            <progress value={fraction ?? undefined} max={1}/>
+            <button onClick={cancel}>Cancel</button>
+        </div>
+    )
+}
@@ -64,6 +64,10 @@ export function WelcomeScreen(props: WelcomeScreenProps) {
         setHasPickedCDDADirectory(true)
     }
 
+    async function onOpenDemoClick() {
+        await tauriBridge.invoke(TauriCommand.OPEN_DEMO_PROJECT, {})
+    }
+
     async function onSaveAndCloseClick() {
         if (!hasPickedCDDADirectory) {
             window.alert("You need to pick a CDDA install directory before proceeding")
@@ -113,6 +117,10 @@ export function WelcomeScreen(props: WelcomeScreenProps) {
                 <p>
                     To get started with creating maps, click on the <span><Icon name={IconName.AddSmall}/></span> Icon
                     next to the "Welcome to the CDDA Map Editor" Tab to create a new Map</p>
+                <p>
+                    If you just want to try the editor, you can also open a small demo map which does not need a
+                    CDDA installation</p>
+                <button onClick={onOpenDemoClick}>Open the demo map</button>
                 <p>
                     The previously selected settings can be changed anytime under the File {">"} Settings dropdown
                 </p>
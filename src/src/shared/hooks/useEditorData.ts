@@ -40,5 +40,29 @@ export function useEditorData(eventBus: RefObject<EventTarget>): EditorData {
         []
     )
 
+    useTauriEvent(
+        TauriEvent.CONFIG_CHANGED,
+        ({config, available_tilesets}) => {
+            setEditorData(prev => ({...prev, config, available_tilesets}))
+        },
+        []
+    )
+
+    useTauriEvent(
+        TauriEvent.PROJECT_LIST_CHANGED,
+        ({openable_projects, recent_projects}) => {
+            setEditorData(prev => ({...prev, openable_projects, recent_projects}))
+        },
+        []
+    )
+
+    useTauriEvent(
+        TauriEvent.OPENED_PROJECT_CHANGED,
+        ({opened_project}) => {
+            setEditorData(prev => ({...prev, opened_project}))
+        },
+        []
+    )
+
     return editorData
 }
\ No newline at end of file
@@ -0,0 +1,57 @@
+// This file is generated from src-tauri/src/events.rs, do not edit it manually.
+// Run `cargo run --bin event_catalog` to regenerate it.
+import type {EditorData} from "../types/editor.js";
+import type {ConfigChangedEvent} from "../types/editor.js";
+import type {ProjectListChangedEvent} from "../types/editor.js";
+import type {OpenedProjectChangedEvent} from "../types/editor.js";
+import type {TilesetChangedEvent} from "../types/spritesheet.js";
+import type {PlaceSpritesEvent} from "../types/map_data.js";
+import type {TabTypeKind} from "../../shared/hooks/useTabs.js";
+import type {ToastType} from "./types.js";
+import type {CrashReportedEvent} from "../types/editor.js";
+import type {JobInfo} from "../types/jobs.js";
+import type {ProjectChangedEvent} from "../types/editor.js";
+import type {WatcherStatusChangedEvent} from "../types/editor.js";
+import type {CDDADataChangedEvent} from "../types/editor.js";
+import type {ModsLoadedEvent} from "../types/editor.js";
+import type {CDDADataLoadProgressEvent} from "../types/editor.js";
+
+export enum TauriEvent {
+    EDITOR_DATA_CHANGED = "editor_data_changed",
+    CONFIG_CHANGED = "config_changed",
+    PROJECT_LIST_CHANGED = "project_list_changed",
+    OPENED_PROJECT_CHANGED = "opened_project_changed",
+    TILESET_CHANGED = "tileset_changed",
+    PLACE_SPRITES = "place_sprites",
+    TAB_CREATED = "tab_created",
+    TAB_REMOVED = "tab_removed",
+    UPDATE_LIVE_VIEWER = "update_live_viewer",
+    EMIT_TOAST_MESSAGE = "emit_toast_message",
+    CRASH_REPORTED = "crash_reported",
+    JOB_UPDATED = "job_updated",
+    PROJECT_CHANGED = "project_changed",
+    WATCHER_STATUS_CHANGED = "watcher_status_changed",
+    CDDA_DATA_CHANGED = "cdda_data_changed",
+    MODS_LOADED = "mods_loaded",
+    CDDA_DATA_LOAD_PROGRESS = "cdda_data_load_progress",
+}
+
+export interface TauriEventMap {
+    [TauriEvent.EDITOR_DATA_CHANGED]: EditorData;
+    [TauriEvent.CONFIG_CHANGED]: ConfigChangedEvent;
+    [TauriEvent.PROJECT_LIST_CHANGED]: ProjectListChangedEvent;
+    [TauriEvent.OPENED_PROJECT_CHANGED]: OpenedProjectChangedEvent;
+    [TauriEvent.TILESET_CHANGED]: TilesetChangedEvent;
+    [TauriEvent.PLACE_SPRITES]: PlaceSpritesEvent;
+    [TauriEvent.TAB_CREATED]: { name: string, tab_type: TabTypeKind };
+    [TauriEvent.TAB_REMOVED]: { name: string };
+    [TauriEvent.UPDATE_LIVE_VIEWER]: null;
+    [TauriEvent.EMIT_TOAST_MESSAGE]: { type: ToastType, message: string };
+    [TauriEvent.CRASH_REPORTED]: CrashReportedEvent;
+    [TauriEvent.JOB_UPDATED]: JobInfo;
+    [TauriEvent.PROJECT_CHANGED]: ProjectChangedEvent;
+    [TauriEvent.WATCHER_STATUS_CHANGED]: WatcherStatusChangedEvent;
+    [TauriEvent.CDDA_DATA_CHANGED]: CDDADataChangedEvent;
+    [TauriEvent.MODS_LOADED]: ModsLoadedEvent;
+    [TauriEvent.CDDA_DATA_LOAD_PROGRESS]: CDDADataLoadProgressEvent;
+}
@@ -1,8 +1,13 @@
-import {AnimatedSprite, FallbackSprite, StaticSprite} from "../types/map_data.js";
-import {EditorData} from "../types/editor.js";
-import {TabTypeKind} from "../../shared/hooks/useTabs.js";
+import {GhostLayerOptions, MappingKind, TileLayer} from "../types/map_data.js";
 import {Vector2, Vector3} from "three";
-import {OpenViewerData} from "../types/viewer.js";
+import {NeighborContext, NeighborDirection, OpenViewerData} from "../types/viewer.js";
+import {SearchTarget} from "../types/search.js";
+import {RawJsonTarget} from "../types/raw_json.js";
+import {CellChange, EditOperation} from "../types/map_data.js";
+import {ExportTarget, NumberOrRange, PlaceDefaults, Season} from "../types/editor.js";
+
+export {TauriEvent} from "./catalog.js";
+export type {TauriEventMap} from "./catalog.js";
 
 export function serializedVec2ToVector2(serializedVec2: string): Vector2 {
     const parts = serializedVec2.split(",")
@@ -56,6 +61,84 @@ export enum TauriCommand {
     NEW_NESTED_MAPGEN_VIEWER = "new_nested_mapgen_viewer",
     GET_CALCULATED_PARAMETERS = "get_calculated_parameters",
     OPEN_RECENT_PROJECT = "open_recent_project",
+    GET_TILESET_VALIDATION_REPORT = "get_tileset_validation_report",
+    REROLL_SPRITE_VARIANTS = "reroll_sprite_variants",
+    PIN_SPRITE_VARIANT = "pin_sprite_variant",
+    GET_TRANSPARENCY_OVERLAY = "get_transparency_overlay",
+    GET_SIGHTLINES = "get_sightlines",
+    GET_ITEM_OVERLAY = "get_item_overlay",
+    GET_MONSTER_OVERLAY = "get_monster_overlay",
+    GET_CELL_INSPECTION = "get_cell_inspection",
+    GET_UPDATE_MAPGEN_IDS = "get_update_mapgen_ids",
+    SET_CAMP_UPGRADES = "set_camp_upgrades",
+    TOGGLE_CAMP_UPGRADE = "toggle_camp_upgrade",
+    GET_OVERMAP_SPECIAL_LAYOUT = "get_overmap_special_layout",
+    GET_VEHICLE_PROTOTYPES = "get_vehicle_prototypes",
+    GET_VEHICLE_PREVIEW = "get_vehicle_preview",
+    GET_FIELD_DECAY_PREVIEW = "get_field_decay_preview",
+    SEARCH_PROJECTS = "search_projects",
+    IMPORT_PALETTE_FROM_FILE = "import_palette_from_file",
+    CREATE_PALETTE = "create_palette",
+    SET_PALETTE_MAPPING = "set_palette_mapping",
+    REMOVE_PALETTE_MAPPING = "remove_palette_mapping",
+    SAVE_PALETTE = "save_palette",
+    RESOLVE_CHARACTERS = "resolve_characters",
+    GET_PROJECT_THUMBNAIL = "get_project_thumbnail",
+    ARCHIVE_PROJECT = "archive_project",
+    RESTORE_PROJECT = "restore_project",
+    LIST_JOBS = "list_jobs",
+    CANCEL_JOB = "cancel_job",
+    GET_RAW_JSON = "get_raw_json",
+    NEW_MAP_EDITOR_PROJECT = "new_map_editor_project",
+    SET_CELLS = "set_cells",
+    SAVE_MAP_EDITOR_PROJECT = "save_map_editor_project",
+    SET_NEIGHBOR_CONTEXT = "set_neighbor_context",
+    GET_SIMULATED_NEIGHBORS = "get_simulated_neighbors",
+    SET_SIMULATED_NEIGHBOR = "set_simulated_neighbor",
+    SAVE_MAPGEN = "save_mapgen",
+    GET_VANILLA_MAP_CATALOG = "get_vanilla_map_catalog",
+    OPEN_VANILLA_MAP = "open_vanilla_map",
+    OPEN_VANILLA_SPECIAL = "open_vanilla_special",
+    RENAME_IDENTIFIER = "rename_identifier",
+    PAINT_CELLS = "paint_cells",
+    SET_PLACE_DEFAULTS = "set_place_defaults",
+    NEW_PLACE_ENTRY = "new_place_entry",
+    DRAW_LINE = "draw_line",
+    DRAW_RECT = "draw_rect",
+    FLOOD_FILL = "flood_fill",
+    RUN_EDIT_SCRIPT = "run_edit_script",
+    COPY_REGION = "copy_region",
+    PASTE_REGION = "paste_region",
+    EXPORT_MAP_LEGEND = "export_map_legend",
+    GET_ACTIVE_Z_LEVEL = "get_active_z_level",
+    SET_ACTIVE_Z_LEVEL = "set_active_z_level",
+    GET_CUSTOM_CONNECT_GROUPS = "get_custom_connect_groups",
+    SET_CUSTOM_CONNECT_GROUPS = "set_custom_connect_groups",
+    PROBE_CELL = "probe_cell",
+    ANALYZE_MAP = "analyze_map",
+    GET_MAPPING_PROBABILITIES = "get_mapping_probabilities",
+    GET_CELL_PROBABILITIES = "get_cell_probabilities",
+    IMPORT_THIRD_PARTY_PROJECT = "import_third_party_project",
+    SET_EXPORT_TARGET = "set_export_target",
+    GET_WATCHER_STATUS = "get_watcher_status",
+    RESYNC_PROJECT = "resync_project",
+    OPEN_DEMO_PROJECT = "open_demo_project",
+    GET_MODS = "get_mods",
+    SET_MOD_ACTIVE = "set_mod_active",
+    SET_USER_MOD_PATHS = "set_user_mod_paths",
+    GET_IMPORT_DIAGNOSTICS = "get_import_diagnostics",
+    VALIDATE_MAPGEN = "validate_mapgen",
+    SET_SEASON = "set_season",
+    GET_TEXTURE_ATLAS = "get_texture_atlas",
+    DOWNLOAD_TEXTURE_ATLAS_PAGE = "download_texture_atlas_page",
+    RELOAD_TILESET = "reload_tileset",
+    SET_ASCII_MODE = "set_ascii_mode",
+    GET_ASCII_COLORS = "get_ascii_colors",
+    SET_SEED = "set_seed",
+    REROLL_SEED = "reroll_seed",
+    REROLL_PREVIEW = "reroll_preview",
+    GET_REGION_SETTINGS_IDS = "get_region_settings_ids",
+    SET_REGION = "set_region",
     ABOUT = "about"
 }
 
@@ -76,7 +159,8 @@ export interface TauriCommandMap {
     [TauriCommand.SAVE_EDITOR_DATA]: {};
     [TauriCommand.GET_CURRENT_PROJECT_DATA]: {};
     [TauriCommand.GET_SPRITES]: {
-        name: string
+        name: string,
+        ghostLayers?: GhostLayerOptions
     };
     [TauriCommand.RELOAD_PROJECT]: {};
     [TauriCommand.OPEN_PROJECT]: {
@@ -119,44 +203,245 @@ export interface TauriCommandMap {
     [TauriCommand.OPEN_RECENT_PROJECT]: {
         name: string
     },
+    [TauriCommand.GET_TILESET_VALIDATION_REPORT]: {};
+    [TauriCommand.REROLL_SPRITE_VARIANTS]: {};
+    [TauriCommand.PIN_SPRITE_VARIANT]: {
+        position: string,
+        layer: TileLayer,
+        variant: number | null
+    };
+    [TauriCommand.GET_TRANSPARENCY_OVERLAY]: {};
+    [TauriCommand.GET_SIGHTLINES]: {
+        from: string,
+        range?: number
+    };
+    [TauriCommand.GET_ITEM_OVERLAY]: {};
+    [TauriCommand.GET_MONSTER_OVERLAY]: {};
+    [TauriCommand.GET_CELL_INSPECTION]: {
+        position: string
+    };
+    [TauriCommand.GET_UPDATE_MAPGEN_IDS]: {};
+    [TauriCommand.SET_CAMP_UPGRADES]: {
+        z: number,
+        upgrades: string[]
+    };
+    [TauriCommand.TOGGLE_CAMP_UPGRADE]: {
+        z: number,
+        id: string,
+        enabled: boolean
+    };
+    [TauriCommand.GET_OVERMAP_SPECIAL_LAYOUT]: {};
+    [TauriCommand.GET_VEHICLE_PROTOTYPES]: {};
+    [TauriCommand.GET_VEHICLE_PREVIEW]: {
+        id: string,
+        rotation?: number
+    };
+    [TauriCommand.GET_FIELD_DECAY_PREVIEW]: {
+        field: string,
+        intensity?: number,
+        age?: number
+    };
+    [TauriCommand.SEARCH_PROJECTS]: {
+        query: string,
+        targets?: SearchTarget[]
+    };
+    [TauriCommand.IMPORT_PALETTE_FROM_FILE]: {
+        path: string,
+        paletteId: string,
+        newId?: string
+    };
+    [TauriCommand.CREATE_PALETTE]: {
+        id: string
+    };
+    [TauriCommand.SET_PALETTE_MAPPING]: {
+        paletteId: string,
+        kind: MappingKind,
+        character: string,
+        value: unknown
+    };
+    [TauriCommand.REMOVE_PALETTE_MAPPING]: {
+        paletteId: string,
+        kind: MappingKind,
+        character: string
+    };
+    [TauriCommand.SAVE_PALETTE]: {
+        paletteId: string,
+        path: string
+    };
+    [TauriCommand.RESOLVE_CHARACTERS]: {
+        map: string,
+        characters: string[]
+    };
+    [TauriCommand.GET_CELL_PROBABILITIES]: {
+        position: string
+    };
+    [TauriCommand.GET_PROJECT_THUMBNAIL]: {
+        name: string
+    };
+    [TauriCommand.ARCHIVE_PROJECT]: {
+        name: string,
+        path: string,
+        unload: boolean
+    };
+    [TauriCommand.RESTORE_PROJECT]: {
+        path: string,
+        restoreDir: string
+    };
+    [TauriCommand.LIST_JOBS]: {};
+    [TauriCommand.CANCEL_JOB]: {
+        id: number
+    };
+    [TauriCommand.GET_RAW_JSON]: {
+        target: RawJsonTarget
+    };
+    [TauriCommand.NEW_MAP_EDITOR_PROJECT]: {
+        projectName: string
+    };
+    [TauriCommand.SET_CELLS]: {
+        changes: CellChange[]
+    };
+    [TauriCommand.SAVE_MAP_EDITOR_PROJECT]: {
+        path?: string
+    };
+    [TauriCommand.SET_NEIGHBOR_CONTEXT]: {
+        context: NeighborContext | null
+    };
+    [TauriCommand.GET_SIMULATED_NEIGHBORS]: {};
+    [TauriCommand.SET_SIMULATED_NEIGHBOR]: {
+        direction: NeighborDirection,
+        omTerrains: string[],
+        joins: string[]
+    };
+    [TauriCommand.SAVE_MAPGEN]: {
+        path: string,
+        z: number,
+        coordinates: string,
+        omTerrain: string
+    };
+    [TauriCommand.GET_VANILLA_MAP_CATALOG]: {};
+    [TauriCommand.OPEN_VANILLA_MAP]: {
+        omId: string
+    };
+    [TauriCommand.OPEN_VANILLA_SPECIAL]: {
+        specialId: string
+    };
+    [TauriCommand.RENAME_IDENTIFIER]: {
+        modPath: string,
+        oldId: string,
+        newId: string,
+        dryRun: boolean
+    };
+    [TauriCommand.PAINT_CELLS]: {
+        positions: string[],
+        mappingKind: Extract<MappingKind, "terrain" | "furniture">,
+        id: string
+    };
+    [TauriCommand.SET_PLACE_DEFAULTS]: {
+        defaults: PlaceDefaults
+    };
+    [TauriCommand.NEW_PLACE_ENTRY]: {
+        kind: "items" | "monsters",
+        z: number,
+        x: NumberOrRange,
+        y: NumberOrRange,
+        group?: string
+    };
+    [TauriCommand.DRAW_LINE]: {
+        from: string,
+        to: string,
+        mappingKind: Extract<MappingKind, "terrain" | "furniture">,
+        id: string
+    };
+    [TauriCommand.DRAW_RECT]: {
+        from: string,
+        to: string,
+        filled: boolean,
+        mappingKind: Extract<MappingKind, "terrain" | "furniture">,
+        id: string
+    };
+    [TauriCommand.FLOOD_FILL]: {
+        start: string,
+        mappingKind: Extract<MappingKind, "terrain" | "furniture">,
+        id: string
+    };
+    [TauriCommand.RUN_EDIT_SCRIPT]: {
+        operations: EditOperation[]
+    };
+    [TauriCommand.COPY_REGION]: {
+        from: string,
+        to: string
+    };
+    [TauriCommand.PASTE_REGION]: {
+        position: string
+    };
+    [TauriCommand.EXPORT_MAP_LEGEND]: {
+        z: number,
+        coordinates: string,
+        format: "json" | "markdown"
+    };
+    [TauriCommand.GET_ACTIVE_Z_LEVEL]: {};
+    [TauriCommand.SET_ACTIVE_Z_LEVEL]: {
+        z: number
+    };
+    [TauriCommand.GET_CUSTOM_CONNECT_GROUPS]: {};
+    [TauriCommand.SET_CUSTOM_CONNECT_GROUPS]: {
+        id: string,
+        connectGroups: string[],
+        connectsTo: string[]
+    };
+    [TauriCommand.PROBE_CELL]: {
+        position: string
+    };
+    [TauriCommand.ANALYZE_MAP]: {};
+    [TauriCommand.GET_MAPPING_PROBABILITIES]: {
+        map: string,
+        characters: string[]
+    };
+    [TauriCommand.IMPORT_THIRD_PARTY_PROJECT]: {
+        mapgenPath: string,
+        metadataPath?: string,
+        importDir: string
+    };
+    [TauriCommand.SET_EXPORT_TARGET]: {
+        target: ExportTarget
+    };
+    [TauriCommand.GET_WATCHER_STATUS]: {
+        name: string
+    };
+    [TauriCommand.RESYNC_PROJECT]: {
+        name: string
+    };
+    [TauriCommand.OPEN_DEMO_PROJECT]: {};
+    [TauriCommand.GET_MODS]: {};
+    [TauriCommand.SET_MOD_ACTIVE]: {
+        id: string
+        active: boolean
+    };
+    [TauriCommand.SET_USER_MOD_PATHS]: {
+        paths: string[]
+    };
+    [TauriCommand.GET_IMPORT_DIAGNOSTICS]: {};
+    [TauriCommand.VALIDATE_MAPGEN]: { path: string };
+    [TauriCommand.SET_SEASON]: { season: Season };
+    [TauriCommand.GET_TEXTURE_ATLAS]: {};
+    [TauriCommand.DOWNLOAD_TEXTURE_ATLAS_PAGE]: { page: number };
+    [TauriCommand.RELOAD_TILESET]: {};
+    [TauriCommand.SET_ASCII_MODE]: { enabled: boolean };
+    [TauriCommand.GET_ASCII_COLORS]: {};
+    [TauriCommand.SET_SEED]: { seed: number };
+    [TauriCommand.REROLL_SEED]: {};
+    [TauriCommand.REROLL_PREVIEW]: {
+        ghostLayers?: GhostLayerOptions
+    };
+    [TauriCommand.GET_REGION_SETTINGS_IDS]: {};
+    [TauriCommand.SET_REGION]: {
+        region: string | null
+    };
     [TauriCommand.ABOUT]: {};
 }
 
-export enum TauriEvent {
-    EDITOR_DATA_CHANGED = "editor_data_changed",
-    TILESET_CHANGED = "tileset_changed",
-    PLACE_SPRITES = "place_sprites",
-    TAB_CREATED = "tab_created",
-    TAB_REMOVED = "tab_removed",
-    UPDATE_LIVE_VIEWER = "update_live_viewer",
-    CHANGE_THEME = "change_theme",
-    EMIT_TOAST_MESSAGE = "emit_toast_message"
-}
-
 export enum ToastType {
     Success = "success",
+    Warning = "warning",
     Error = "error"
 }
-
-export interface TauriEventMap {
-    [TauriEvent.EDITOR_DATA_CHANGED]: EditorData;
-    [TauriEvent.PLACE_SPRITES]: {
-        static_sprites: StaticSprite[];
-        animated_sprites: AnimatedSprite[];
-        fallback_sprites: FallbackSprite[];
-    };
-    [TauriEvent.TAB_CREATED]: {
-        name: string,
-        tab_type: TabTypeKind,
-    };
-    [TauriEvent.TAB_REMOVED]: {
-        name: string
-    };
-    [TauriEvent.UPDATE_LIVE_VIEWER]: {};
-    [TauriEvent.CHANGE_THEME]: {},
-    [TauriEvent.EMIT_TOAST_MESSAGE]: {
-        type: ToastType,
-        message: string
-    }
-    [TauriEvent.TILESET_CHANGED]: {}
-}
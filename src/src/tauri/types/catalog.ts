@@ -0,0 +1,13 @@
+export enum VanillaMapKind {
+    Terrain = "terrain",
+    Update = "update",
+    Special = "special"
+}
+
+export type VanillaMapEntry = {
+    id: string
+    // Either a plain string or an object with the translated "str"
+    name: string | { str: string } | null
+    kind: VanillaMapKind
+    source: string | null
+}
@@ -20,11 +20,139 @@ export type EditorConfig = {
     cdda_path?: string
     selected_tileset?: string
     theme: string
+    place_defaults: PlaceDefaults
+    export_target: ExportTarget
+    user_mod_paths: string[]
+    season: Season
+    ascii_mode: boolean
+}
+
+export type ExportTarget = "stable" | "experimental"
+
+export type Season = "spring" | "summer" | "autumn" | "winter"
+
+export type NumberOrRange = number | [number, number]
+
+export type PlaceDefaults = {
+    chance: number
+    repeat: NumberOrRange
+    item_group: string | null
+    monster_group: string | null
 }
 export type EditorData = {
     config: EditorConfig
     openable_projects: string[],
     available_tilesets: string[] | null,
-    recent_projects: {name: string, path: string}[],
-    opened_project: number | null
-}
\ No newline at end of file
+    recent_projects: RecentProject[],
+    opened_project: string | null
+}
+
+export type RecentProject = {name: string, path: string, thumbnail: string | null}
+
+export type ConfigChangedEvent = {
+    config: EditorConfig
+    available_tilesets: string[] | null
+}
+
+export type ProjectListChangedEvent = {
+    openable_projects: string[]
+    recent_projects: RecentProject[]
+}
+
+export type OpenedProjectChangedEvent = {
+    opened_project: string | null
+}
+
+export type CrashReportedEvent = {
+    message: string
+    location: string
+    crash_file: string | null
+}
+
+export type ProjectChangedEvent = {
+    name: string
+    dirty: boolean
+}
+
+export type WatchedFileStatus =
+    { status: "Active" } |
+    { status: "PathMissing" } |
+    { status: "PermissionError", message: string }
+
+export type WatchedFile = { path: string } & WatchedFileStatus
+
+export type WatcherStatusChangedEvent = {
+    name: string
+    files: WatchedFile[]
+}
+
+export type ChangedEntry = {
+    json_type: string
+    id: string
+}
+
+export type CDDADataChangedEvent = {
+    entries: ChangedEntry[]
+}
+
+export type CDDAMod = {
+    id: string
+    name: string
+    authors: string[]
+    description?: string
+    category?: string
+    dependencies: string[]
+    path?: string
+    obsolete: boolean
+    json_path: string
+}
+
+export type ModListEntry = CDDAMod & {
+    active: boolean
+}
+
+export type ModsLoadedEvent = {
+    mods: string[]
+}
+
+export type CDDADataLoadPhase = "Discovering" | "Parsing" | "Flattening" | "Finished"
+
+export type CDDADataLoadProgressEvent = {
+    job: number
+    phase: CDDADataLoadPhase
+    files_discovered: number
+    files_parsed: number
+    objects_flattened: number
+    objects_total: number
+}
+
+export type DiagnosticSeverity = "Warning" | "Error"
+
+export type ImportDiagnostic = {
+    severity: DiagnosticSeverity
+    path: string
+    id: string | null
+    line: number | null
+    column: number | null
+    message: string
+}
+
+export type FileDiagnostics = {
+    path: string
+    diagnostics: ImportDiagnostic[]
+}
+
+export type MapgenIssue = {
+    severity: DiagnosticSeverity
+    mapgen?: string
+    pointer: string
+    line: number
+    column: number
+    message: string
+    suggestions: string[]
+}
+
+export type CustomConnectGroups = {
+    connect_groups: Record<string, string[]>
+    connects_to: Record<string, string[]>
+}
@@ -0,0 +1,13 @@
+export type JobState =
+    | { state: "Running" }
+    | { state: "Completed" }
+    | { state: "Failed", error: string }
+    | { state: "Canceled" }
+
+export type JobInfo = {
+    id: number
+    name: string
+    // Progress between 0 and 1 if the total amount of work is known
+    progress: number | null
+    message: string | null
+} & JobState
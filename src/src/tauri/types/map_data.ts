@@ -31,13 +31,21 @@ export type MappedCDDAId = {
     tilesheet_id: TilesheetCDDAId
 }
 
+export type NpcClassInfo = {
+    id: CDDAIdentifier
+    name?: CDDAString
+    job_description?: CDDAString
+}
+
 export type CellData = {
     [zLevel: number]: {
         [position: string]: {
             terrain?: MappedCDDAId,
             furniture?: MappedCDDAId,
+            vehicle?: MappedCDDAId,
             monster?: MappedCDDAId,
-            field?: MappedCDDAId
+            field?: MappedCDDAId,
+            npc_class?: NpcClassInfo
         }
     }
 }
@@ -55,12 +63,181 @@ export type CellData = {
 //     }
 // }
 
+export type CDDAString = string | { str: string }
+
+export type CDDADrops = CDDAIdentifier | unknown[]
+
+export type CDDABash = {
+    str_min?: number
+    str_max?: number
+    sound?: CDDAString
+    sound_fail?: CDDAString
+    ter_set?: CDDAIdentifier
+    furn_set?: CDDAIdentifier
+    items?: CDDADrops
+}
+
+export type CDDADeconstruct = {
+    ter_set?: CDDAIdentifier
+    furn_set?: CDDAIdentifier
+    items?: CDDADrops
+}
+
+export type Season = "spring" | "summer" | "autumn" | "winter"
+
+export type CDDAHarvestEntry = {
+    drop: CDDAIdentifier
+    type?: string
+    base_num?: number[]
+    scale_num?: number[]
+    max?: number
+}
+
+export type SeasonalHarvest = {
+    seasons: Season[]
+    harvest_id: CDDAIdentifier
+    products: CDDAHarvestEntry[]
+}
+
+export type CDDAPlantData = {
+    transform?: CDDAIdentifier
+    base?: CDDAIdentifier
+    growth_multiplier?: number
+    harvest_multiplier?: number
+}
+
+export type MapObjectInspection = {
+    id: CDDAIdentifier
+    name?: CDDAString
+    bash?: CDDABash
+    deconstruct?: CDDADeconstruct
+    harvest: SeasonalHarvest[]
+    plant_data?: CDDAPlantData
+}
+
+export type TileAnnotation = {
+    kind: "graffiti"
+    text?: string
+    snippet?: CDDAIdentifier
+} | {
+    kind: "rubble"
+    rubble_type: CDDAIdentifier
+    floor_type?: CDDAIdentifier
+    items: boolean
+} | {
+    kind: "liquid"
+    liquid: CDDAIdentifier
+    amount?: [number, number]
+} | {
+    kind: "corpse"
+    group: CDDAIdentifier
+    age?: number
+} | {
+    kind: "sealedItem"
+    furniture: CDDAIdentifier
+    item?: CDDAIdentifier
+    item_group?: CDDAIdentifier
+}
+
+export type CellInspection = {
+    terrain?: MapObjectInspection
+    furniture?: MapObjectInspection
+    annotations: TileAnnotation[]
+}
+
+export type ItemDensity = {
+    expected_spawns: number
+    groups: CDDAIdentifier[]
+    items: CDDAIdentifier[]
+    in_place_groups: number
+}
+
+export type MonsterDensity = {
+    expected_spawns: number
+    groups: CDDAIdentifier[]
+    monsters: CDDAIdentifier[]
+}
+
+export enum TileLayer {
+    Terrain = "Terrain",
+    Furniture = "Furniture",
+    Trap = "Trap",
+    Vehicle = "Vehicle",
+    Monster = "Monster",
+    Field = "Field",
+}
+
+export type MappedCDDAIdsForTile = {
+    terrain?: MappedCDDAId
+    furniture?: MappedCDDAId
+    trap?: MappedCDDAId
+    vehicle?: MappedCDDAId
+    monster?: MappedCDDAId
+    field?: MappedCDDAId
+    npc_class?: NpcClassInfo
+    annotations: TileAnnotation[]
+}
+
+export type MultitileConnections = {
+    top: boolean
+    right: boolean
+    bottom: boolean
+    left: boolean
+}
+
+export type CellProbe = {
+    ids: MappedCDDAIdsForTile
+    neighbors: {
+        top: MappedCDDAIdsForTile
+        right: MappedCDDAIdsForTile
+        bottom: MappedCDDAIdsForTile
+        left: MappedCDDAIdsForTile
+    }
+    connections: Partial<Record<TileLayer, MultitileConnections>>
+}
+
+export type FindingKind =
+    "unreachableArea"
+    | "blockedDoor"
+    | "rooflessIndoors"
+    | "missingOutdoorLighting"
+    | "emptyItemGroup"
+
+export type FindingSeverity = "info" | "warning" | "error"
+
+export type MapFinding = {
+    kind: FindingKind
+    severity: FindingSeverity
+    message: string
+    positions: string[]
+}
+
+export type MapAnalysis = {
+    score: number
+    findings: MapFinding[]
+}
+
+export type IdProbability = {
+    id: CDDAIdentifier
+    probability: number
+}
+
+export type DrawOrder = {
+    layer: number
+    sub_layer: number
+    z: number
+    z_offset: number
+    depth: number
+}
+
 export type StaticSprite = {
     position: string
     index: number
     layer: number
     rotate_deg: number
     z: number
+    draw_order: DrawOrder
+    sheet: number | null
 }
 
 export type AnimatedSprite = {
@@ -69,16 +246,125 @@ export type AnimatedSprite = {
     layer: number
     rotate_deg: number
     z: number,
+    draw_order: DrawOrder
+    sheet: number | null
 }
 
 export type FallbackSprite = {
     position: string,
     index: number
     z: number
+    draw_order: DrawOrder
+}
+
+export type GhostLayerOptions = {
+    below?: boolean
+    above?: boolean
+    opacity?: number
+}
+
+export type GhostSprites = {
+    source_z: number
+    target_z: number
+    opacity: number
+    static_sprites: StaticSprite[]
+    animated_sprites: AnimatedSprite[]
+    fallback_sprites: FallbackSprite[]
+}
+
+export type SpritesheetPlacement = {
+    file: string
+    range: [number, number]
+    sprite_width: number
+    sprite_height: number
+    center_offset_x: number
+    center_offset_y: number
+}
+
+export type PlaceSpritesEvent = {
+    static_sprites: StaticSprite[]
+    animated_sprites: AnimatedSprite[]
+    fallback_sprites: FallbackSprite[]
+    ghost_sprites: GhostSprites[]
+    spritesheets: SpritesheetPlacement[]
 }
 
 export enum DisplayItemGroupType {
     Single = "Single",
     Collection = "Collection",
     Distribution = "Distribution"
-}
\ No newline at end of file
+}
+export type FieldIntensityLevel = {
+    name?: CDDAString
+    sym?: string
+    color?: string
+}
+
+export type FieldDecayStage = {
+    intensity: number
+    starts_at: number
+    level?: FieldIntensityLevel
+    fg?: number[]
+    bg?: number[]
+    fallback?: number
+}
+
+export type FieldDecayPreview = {
+    field: CDDAIdentifier
+    half_life?: number
+    stages: FieldDecayStage[]
+    removed_at?: number
+}
+
+export type MappingKind =
+    | "terrain"
+    | "furniture"
+    | "trap"
+    | "item_groups"
+    | "computer"
+    | "sign"
+    | "toilet"
+    | "gaspump"
+    | "monsters"
+    | "monster"
+    | "field"
+    | "nested"
+    | "vehicle"
+    | "corpse"
+    | "appliance"
+    | "npc"
+    | "graffiti"
+    | "rubble"
+    | "liquid"
+    | "sealed_item"
+    | "loot"
+
+export type MappingSource =
+    | { type: "Map" }
+    | { type: "Palette", id: string }
+
+// Characters which are not mapped for any kind have an empty mapping
+export type CharacterResolution = Record<string, Partial<Record<MappingKind, MappingSource>>>
+
+export type CellChange = {
+    // Serialized global cell coordinates in the format "x,y,z"
+    coordinates: string
+    character: string
+}
+
+type PaintMappingKind = Extract<MappingKind, "terrain" | "furniture">
+
+// A single step of an edit script, coordinates use the format "x,y,z"
+export type EditOperation =
+    | { op: "replace_id", mapping_kind: PaintMappingKind, from: string, to: string, z?: number }
+    | { op: "scatter", mapping_kind: PaintMappingKind, id: string, from: string, to: string, chance: number, seed?: number }
+    | { op: "rect", mapping_kind: PaintMappingKind, id: string, from: string, to: string, filled?: boolean }
+    | { op: "line", mapping_kind: PaintMappingKind, id: string, from: string, to: string }
+    | { op: "flood_fill", mapping_kind: PaintMappingKind, id: string, start: string }
+
+export type MapClipboard = {
+    size: [number, number]
+    rows: string[]
+    terrain: Record<string, unknown>
+    furniture: Record<string, unknown>
+}
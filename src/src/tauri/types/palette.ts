@@ -0,0 +1,4 @@
+export type ImportedPalette = {
+    original_id: string
+    id: string
+}
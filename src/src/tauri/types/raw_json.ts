@@ -0,0 +1,11 @@
+export type RawJsonTarget =
+    | { type: "CurrentMap" }
+    | { type: "Palette", id: string }
+    // json_type is the "type" the object has in the CDDA json files
+    | { type: "Definition", json_type: string, id: string }
+
+export type RawJson = {
+    // Null for objects which only exist in the project, like imported palettes
+    source: string | null
+    json: string
+}
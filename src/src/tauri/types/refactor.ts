@@ -0,0 +1,4 @@
+export type RenamedFile = {
+    path: string
+    occurrences: number
+}
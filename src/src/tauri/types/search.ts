@@ -0,0 +1,19 @@
+import {TileLayer} from "./map_data.js";
+
+export enum SearchTarget {
+    Character = "Character",
+    Identifier = "Identifier",
+    Place = "Place"
+}
+
+export type SearchHitKind =
+    | { type: "Character", character: string }
+    | { type: "Identifier", layer: TileLayer, id: string }
+    | { type: "Place", mapping: string, layer: TileLayer, id: string }
+
+export type SearchHit = {
+    project: string
+    // Serialized global cell coordinates in the format "x,y,z"
+    coordinates: string
+    kind: SearchHitKind
+}
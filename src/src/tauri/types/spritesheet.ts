@@ -1,10 +1,11 @@
 export type TileNew = {
     file: string;
-    "//"?: [number, number];
-    sprite_width?: number;
-    sprite_height?: number;
-    sprite_offset_x?: number;
-    sprite_offset_y?: number;
+    "//": [number, number] | null;
+    sprite_width: number;
+    sprite_height: number;
+    sprite_offset_x: number;
+    sprite_offset_y: number;
+    is_fallback: boolean;
 };
 export type TileInfo = {
     pixelscale: number;
@@ -15,7 +16,56 @@ export type TileInfo = {
     retract_dist_min: number;
     retract_dist_max: number;
 };
+export type TilesetValidationIssue = {
+    type: "DuplicateId" | "AdditionalTilesOnNonMultitile";
+    id: string;
+    file: string;
+} | {
+    type: "EmptySpriteArray";
+    id: string;
+    file: string;
+    field: "fg" | "bg";
+} | {
+    type: "IndexOutOfRange";
+    id: string;
+    file: string;
+    index: number;
+    range: [number, number];
+};
+export type TilesetValidationReport = {
+    issues: TilesetValidationIssue[];
+};
+export type Projection = {
+    iso: boolean;
+    tile_width: number;
+    tile_height: number;
+    zlevel_height: number;
+};
+export type AtlasRange = {
+    range: [number, number];
+    first_cell: number;
+};
+export type AtlasPage = {
+    width: number;
+    height: number;
+    sprite_width: number;
+    sprite_height: number;
+    ranges: AtlasRange[];
+};
+export type TextureAtlas = {
+    pages: AtlasPage[];
+};
+export type TilesetChangedEvent = {
+    tileset: string | null;
+    projection: Projection;
+};
+export type AsciiColor = {
+    name: string;
+    offset: number | null;
+    rgb: [number, number, number];
+};
 export type SpritesheetConfig = {
     'tiles-new': TileNew[];
     tile_info: TileInfo[];
+    projection: Projection;
 };
\ No newline at end of file
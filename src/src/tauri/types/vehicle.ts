@@ -0,0 +1,36 @@
+import {CDDAIdentifier, CDDAString, TilesheetCDDAId} from "./map_data.js";
+
+export type VehicleBoundingBox = {
+    min_x: number
+    min_y: number
+    max_x: number
+    max_y: number
+    width: number
+    height: number
+}
+
+export type VehiclePrototypeSummary = {
+    id: CDDAIdentifier
+    name?: CDDAString
+    bounding_box: VehicleBoundingBox
+    part_count: number
+}
+
+export type VehiclePreviewCell = {
+    x: number
+    y: number
+    tilesheet_id: TilesheetCDDAId
+    location: string
+    has_sprite: boolean
+    fg?: number[]
+    bg?: number[]
+    rotate_deg: number
+    fallback?: number
+}
+
+export type VehiclePreview = {
+    id: CDDAIdentifier
+    rotation: number
+    bounding_box: VehicleBoundingBox
+    cells: VehiclePreviewCell[]
+}
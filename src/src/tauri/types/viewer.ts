@@ -1,6 +1,9 @@
 export enum OpenViewerDataType {
     Terrain = "terrain",
-    Special = "special"
+    Special = "special",
+    Linear = "linear",
+    Nested = "nested",
+    Update = "update"
 }
 
 export type OpenViewerData = {
@@ -14,4 +17,61 @@ export type OpenViewerData = {
     mapgenFilePaths: string[],
     projectName: string
     omId: string
-}
\ No newline at end of file
+    // Overmap terrains shown on other z-levels, like roofs and basements
+    stackedOmIds?: Record<number, string>
+    // Update mapgens applied in order on top of the map, like camp upgrades
+    updateIds?: string[]
+} | {
+    type: OpenViewerDataType.Linear,
+    mapgenFilePaths: string[],
+    projectName: string
+    omId: string
+} | {
+    type: OpenViewerDataType.Nested,
+    mapgenFilePaths: string[],
+    projectName: string
+    nestedId: string
+} | {
+    type: OpenViewerDataType.Update,
+    mapgenFilePaths: string[],
+    projectName: string
+    updateId: string
+}
+export enum NeighborType {
+    Empty = "Empty",
+    Road = "Road",
+    House = "House",
+    Terrain = "Terrain"
+}
+
+export type Neighbor =
+    { type: NeighborType.Empty } |
+    { type: NeighborType.Road } |
+    { type: NeighborType.House } |
+    { type: NeighborType.Terrain, om_id: string }
+
+export type NeighborContext = {
+    north: Neighbor
+    east: Neighbor
+    south: Neighbor
+    west: Neighbor
+}
+
+export type NeighborDirection =
+    "north" | "east" | "south" | "west" |
+    "north_east" | "north_west" | "south_east" | "south_west" |
+    "above" | "below"
+
+export type SimulatedNeighbors = {
+    neighbors: { [direction in NeighborDirection]?: string[] }
+    joins: { [direction in NeighborDirection]?: string[] }
+}
+
+// An overmap terrain of the overmap special shown in a special viewer
+export type SpecialOvermapTerrain = {
+    // Position of the map in its z-level, in overmap terrains
+    position: [number, number]
+    z: number
+    omId: string
+    hasMapgen: boolean
+}
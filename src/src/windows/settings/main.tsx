@@ -13,6 +13,7 @@ import { DEFAULT_TILESET } from "../../features/editor/index.ts";
 function Main() {
     const [selectedTilset, setSelectedTileset] = useState<string>("None")
     const [cddaDirectoryPath, setCDDADirectoryPath] = useState<string>(null)
+    const [asciiMode, setAsciiMode] = useState<boolean>(false)
     const [editorData, setEditorData] = useState<EditorData>(null)
     const selectRef = useRef<HTMLSelectElement>(null)
 
@@ -36,6 +37,7 @@ function Main() {
             setCDDADirectoryPath(response.data.config.cdda_path)
         }
 
+        setAsciiMode(response.data.config.ascii_mode)
         setEditorData(response.data)
     }
 
@@ -68,6 +70,23 @@ function Main() {
         setSelectedTileset(newTileset)
     }
 
+    async function onAsciiModeChange(enabled: boolean) {
+        const response = await tauriBridge.invoke(TauriCommand.SET_ASCII_MODE, {enabled})
+
+        if (response.type === BackendResponseType.Error) {
+            console.error(response.error)
+            return
+        }
+
+        setAsciiMode(enabled)
+    }
+
+    async function onTilesetReload() {
+        const response = await tauriBridge.invoke(TauriCommand.RELOAD_TILESET, {})
+
+        if (response.type === BackendResponseType.Error) console.error(response.error)
+    }
+
     async function onCDDAInputChange() {
         const path = await open({
             multiple: false,
@@ -133,6 +152,18 @@ function Main() {
                         </select>
                         <label>Select your tileset here</label>
                     </div>
+                    <div className={"form-element"}>
+                        <button onClick={onTilesetReload}>Reload Tileset</button>
+                        <label>Load the files of the selected tileset again</label>
+                    </div>
+                    <div className={"form-element"}>
+                        <input
+                            type={"checkbox"}
+                            checked={asciiMode}
+                            onChange={e => onAsciiModeChange(e.target.checked)}
+                        />
+                        <label>Draw the map with the symbols and colors of the tiles instead of the tileset</label>
+                    </div>
                 </Accordion>
             </div>
         </GenericWindow>